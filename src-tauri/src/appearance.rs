@@ -0,0 +1,183 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Per-project color and icon for the sidebar. Both are encrypted with the vault key like tag
+//! names, each bound to its project and field, so the database does not reveal how projects
+//! are grouped. They are under the vault key even for projects with a custom password, which
+//! keeps them listable while such a project is locked.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::crypto;
+use crate::models::ProjectAppearance;
+use crate::storage::StorageProvider;
+
+/// Icon names come from the frontend's icon set; an emoji fits too.
+const MAX_ICON_LEN: usize = 32;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Appearance {
+    /// `#rrggbb`.
+    pub color: Option<String>,
+    pub icon: Option<String>,
+}
+
+fn encrypt_field(
+    project_id: &str,
+    field: &str,
+    value: Option<&str>,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Option<Vec<u8>>, String> {
+    value
+        .map(|v| {
+            crypto::encrypt_with_key_aad(v.as_bytes(), key, &crypto::field_aad(project_id, field))
+                .map_err(|e| e.to_string())
+        })
+        .transpose()
+}
+
+fn decrypt_field(
+    project_id: &str,
+    field: &str,
+    value: Option<&[u8]>,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Option<String>, String> {
+    value
+        .map(|v| {
+            crypto::try_decrypt_with_key_aad(v, key, &crypto::field_aad(project_id, field))
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| format!("Failed to decrypt the {field} of project {project_id}"))
+        })
+        .transpose()
+}
+
+fn decrypt(record: &ProjectAppearance, key: &[u8; crypto::KEY_LEN]) -> Result<Appearance, String> {
+    let id = &record.project_id;
+    Ok(Appearance {
+        color: decrypt_field(id, "color", record.encrypted_color.as_deref(), key)?,
+        icon: decrypt_field(id, "icon", record.encrypted_icon.as_deref(), key)?,
+    })
+}
+
+fn encrypt(
+    project_id: &str,
+    appearance: &Appearance,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<ProjectAppearance, String> {
+    Ok(ProjectAppearance {
+        project_id: project_id.to_string(),
+        encrypted_color: encrypt_field(project_id, "color", appearance.color.as_deref(), key)?,
+        encrypted_icon: encrypt_field(project_id, "icon", appearance.icon.as_deref(), key)?,
+    })
+}
+
+/// Trimmed, with empty values meaning none and the color lowercased.
+fn normalize(color: Option<&str>, icon: Option<&str>) -> Result<Appearance, String> {
+    let color = color.map(str::trim).filter(|c| !c.is_empty()).map(str::to_lowercase);
+    if let Some(color) = &color {
+        let hex = color.strip_prefix('#').unwrap_or_default();
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Colors must be in #rrggbb form".to_string());
+        }
+    }
+    let icon = icon.map(str::trim).filter(|i| !i.is_empty()).map(str::to_string);
+    if icon.as_ref().is_some_and(|i| i.chars().count() > MAX_ICON_LEN) {
+        return Err(format!("Icons must be at most {MAX_ICON_LEN} characters"));
+    }
+    Ok(Appearance { color, icon })
+}
+
+/// Color and icon of every project that has either.
+pub fn by_project(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<HashMap<String, Appearance>, String> {
+    storage
+        .list_project_appearances()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|record| Ok((record.project_id.clone(), decrypt(record, key)?)))
+        .collect()
+}
+
+/// Set a project's color and icon; `None` or an empty string clears one.
+pub fn set_for_project(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    project_id: &str,
+    color: Option<&str>,
+    icon: Option<&str>,
+) -> Result<Appearance, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let appearance = normalize(color, icon)?;
+    storage
+        .set_project_appearance(&encrypt(project_id, &appearance, key)?)
+        .map_err(|e| e.to_string())?;
+    Ok(appearance)
+}
+
+/// Re-encrypt every project's color and icon under `new_key`, for a master password change.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for record in storage.list_project_appearances().map_err(|e| e.to_string())? {
+        let appearance = decrypt(&record, old_key)?;
+        storage
+            .set_project_appearance(&encrypt(&record.project_id, &appearance, new_key)?)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    fn project(storage: &LocalStorage, id: &str) {
+        storage
+            .create_project(&Project {
+                id: id.to_string(),
+                name: String::new(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn appearance_is_stored_encrypted_and_cleared() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        project(&storage, "a");
+
+        assert!(set_for_project(&storage, &key, "a", Some("red"), None).is_err());
+        set_for_project(&storage, &key, "a", Some(" #AA3300 "), Some("briefcase")).unwrap();
+        let stored = &storage.list_project_appearances().unwrap()[0];
+        let color = stored.encrypted_color.as_deref().unwrap();
+        assert!(!color.windows(6).any(|w| w == b"aa3300"));
+
+        let new_key = [6u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        let appearance = &by_project(&storage, &new_key).unwrap()["a"];
+        assert_eq!(appearance.color.as_deref(), Some("#aa3300"));
+        assert_eq!(appearance.icon.as_deref(), Some("briefcase"));
+
+        set_for_project(&storage, &new_key, "a", None, Some("")).unwrap();
+        assert!(by_project(&storage, &new_key).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! File attachments. Every attachment has a random key of its own: the content is sealed with
+//! it as a chunked stream (see `crypto::stream`) written straight into the attachment shard, so
+//! neither adding nor opening a file holds it in memory whole. The attachment key is wrapped
+//! with the project key, that is the vault key or the project's custom password; changing
+//! either only rewraps the key, never the content.
+
+use std::io::{self, Read, Write};
+
+use serde::Serialize;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::clock::Clock;
+use crate::crypto::{self, rng, stream};
+use crate::models::Attachment;
+use crate::storage::shard::{BlobReader, BlobWriter};
+use crate::storage::StorageProvider;
+
+pub const DEFAULT_MIME: &str = "application/octet-stream";
+const STREAM_VERSION: u8 = 1;
+
+/// The key a project's attachments are wrapped with.
+#[derive(Clone, Copy)]
+pub enum ProjectKey<'a> {
+    Vault(&'a [u8; crypto::KEY_LEN]),
+    Password(&'a str),
+}
+
+impl<'a> ProjectKey<'a> {
+    /// The project's own password when it has one, the vault key otherwise.
+    pub fn of(vault_key: &'a [u8; crypto::KEY_LEN], password: Option<&'a str>) -> Self {
+        match password {
+            Some(pw) => ProjectKey::Password(pw),
+            None => ProjectKey::Vault(vault_key),
+        }
+    }
+
+    pub(crate) fn same_as(&self, other: &ProjectKey<'_>) -> bool {
+        match (self, other) {
+            (ProjectKey::Vault(a), ProjectKey::Vault(b)) => a == b,
+            (ProjectKey::Password(a), ProjectKey::Password(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentInfo {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+fn wrap(
+    project_key: ProjectKey<'_>,
+    id: &str,
+    file_key: &[u8; crypto::KEY_LEN],
+) -> Result<Vec<u8>, String> {
+    match project_key {
+        ProjectKey::Vault(key) => {
+            crypto::encrypt_with_key_aad(file_key, key, &crypto::field_aad(id, "attachment-key"))
+        }
+        ProjectKey::Password(password) => crypto::encrypt(file_key, password),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn unwrap(
+    project_key: ProjectKey<'_>,
+    attachment: &Attachment,
+) -> Result<Zeroizing<[u8; crypto::KEY_LEN]>, String> {
+    let bytes = Zeroizing::new(match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &attachment.wrapped_key,
+            key,
+            &crypto::field_aad(&attachment.id, "attachment-key"),
+        )
+        .ok_or("Wrong key for this attachment")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&attachment.wrapped_key, password).map_err(|e| e.to_string())?
+        }
+    });
+    let key: [u8; crypto::KEY_LEN] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| "Invalid attachment key".to_string())?;
+    Ok(Zeroizing::new(key))
+}
+
+fn open_field(
+    attachment: &Attachment,
+    file_key: &[u8; crypto::KEY_LEN],
+    field: &str,
+    data: &[u8],
+) -> Result<String, String> {
+    let aad = crypto::field_aad(&attachment.id, field);
+    let plain = crypto::try_decrypt_with_key_aad(data, file_key, &aad)
+        .ok_or_else(|| format!("Attachment {} is corrupt", attachment.id))?;
+    String::from_utf8(plain).map_err(|e| e.to_string())
+}
+
+fn info(attachment: &Attachment, file_key: &[u8; crypto::KEY_LEN]) -> Result<AttachmentInfo, String> {
+    Ok(AttachmentInfo {
+        id: attachment.id.clone(),
+        project_id: attachment.project_id.clone(),
+        name: open_field(attachment, file_key, "name", &attachment.encrypted_name)?,
+        mime_type: open_field(attachment, file_key, "mime", &attachment.encrypted_mime)?,
+        size: attachment.size,
+        created_at: attachment.created_at.clone(),
+    })
+}
+
+fn seal_field(
+    id: &str,
+    file_key: &[u8; crypto::KEY_LEN],
+    field: &str,
+    value: &str,
+) -> Result<Vec<u8>, String> {
+    crypto::encrypt_with_key_aad(value.as_bytes(), file_key, &crypto::field_aad(id, field))
+        .map_err(|e| e.to_string())
+}
+
+fn store(
+    storage: &dyn StorageProvider,
+    attachment: &mut Attachment,
+    file_key: &[u8; crypto::KEY_LEN],
+    source: &mut dyn Read,
+) -> Result<(), String> {
+    let aad = crypto::field_aad(&attachment.id, "content");
+    let blob = BlobWriter::new(storage, &attachment.id);
+    let mut writer =
+        stream::StreamWriter::new(blob, file_key, STREAM_VERSION, &aad).map_err(|e| e.to_string())?;
+    attachment.size = io::copy(source, &mut writer).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    storage.create_attachment(attachment).map_err(|e| e.to_string())
+}
+
+/// Encrypt everything read from `source` and attach it to a project.
+pub fn add(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+    name: &str,
+    mime_type: Option<&str>,
+    source: &mut dyn Read,
+) -> Result<AttachmentInfo, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let id = Uuid::new_v4().to_string();
+    let mut file_key = Zeroizing::new([0u8; crypto::KEY_LEN]);
+    rng::fill(&mut *file_key);
+    let mime_type = mime_type.filter(|m| !m.is_empty()).unwrap_or(DEFAULT_MIME);
+
+    let mut attachment = Attachment {
+        wrapped_key: wrap(project_key, &id, &file_key)?,
+        encrypted_name: seal_field(&id, &file_key, "name", name)?,
+        encrypted_mime: seal_field(&id, &file_key, "mime", mime_type)?,
+        id,
+        project_id: project_id.to_string(),
+        size: 0,
+        created_at: clock.now().to_rfc3339(),
+    };
+    if let Err(e) = store(storage, &mut attachment, &file_key, source) {
+        let _ = storage.delete_blob(&attachment.id);
+        return Err(e);
+    }
+    info(&attachment, &file_key)
+}
+
+pub fn list(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<Vec<AttachmentInfo>, String> {
+    storage
+        .list_attachments(project_id)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|attachment| info(attachment, &*unwrap(project_key, attachment)?))
+        .collect()
+}
+
+/// Decrypt an attachment into `sink`. Each chunk is authenticated before it is written, but an
+/// error part way means what was written so far must be thrown away.
+pub fn read(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    id: &str,
+    sink: &mut dyn Write,
+) -> Result<AttachmentInfo, String> {
+    let attachment = storage.get_attachment(id).map_err(|e| e.to_string())?;
+    let file_key = unwrap(project_key, &attachment)?;
+    let (_, mut reader) = stream::StreamReader::new(
+        BlobReader::new(storage, id),
+        &file_key,
+        &crypto::field_aad(id, "content"),
+    )
+    .map_err(|e| e.to_string())?;
+    io::copy(&mut reader, sink).map_err(|e| e.to_string())?;
+    info(&attachment, &file_key)
+}
+
+pub fn delete(storage: &dyn StorageProvider, id: &str) -> Result<(), String> {
+    storage.delete_attachment(id).map_err(|e| e.to_string())
+}
+
+/// Wrap a project's attachment keys with its new key after the project password was set,
+/// changed or removed.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    project_id: &str,
+    old_key: ProjectKey<'_>,
+    new_key: ProjectKey<'_>,
+) -> Result<(), String> {
+    if old_key.same_as(&new_key) {
+        return Ok(());
+    }
+    for mut attachment in storage.list_attachments(project_id).map_err(|e| e.to_string())? {
+        let file_key = unwrap(old_key, &attachment)?;
+        attachment.wrapped_key = wrap(new_key, &attachment.id, &file_key)?;
+        storage.update_attachment(&attachment).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Rewrap every attachment key held under the vault key with `new_key`, for a master password
+/// change. Attachments of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        for mut attachment in storage.list_attachments(&project.id).map_err(|e| e.to_string())? {
+            let Ok(file_key) = unwrap(ProjectKey::Vault(old_key), &attachment) else {
+                continue;
+            };
+            attachment.wrapped_key = wrap(ProjectKey::Vault(new_key), &attachment.id, &file_key)?;
+            storage.update_attachment(&attachment).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::shard;
+
+    #[test]
+    fn attachments_stream_through_the_shard() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage
+            .create_project(&Project {
+                id: "p".to_string(),
+                name: "p".to_string(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        let content: Vec<u8> = (0..shard::CHUNK_LEN * 2 + 100).map(|i| i as u8).collect();
+
+        let added = add(
+            &storage,
+            &SystemClock,
+            ProjectKey::Vault(&key),
+            "p",
+            "recovery.pdf",
+            Some("application/pdf"),
+            &mut content.as_slice(),
+        )
+        .unwrap();
+        assert_eq!(added.size, content.len() as u64);
+        assert!(storage.list_blobs().unwrap()[0].chunks >= 3);
+
+        let mut out = Vec::new();
+        let opened = read(&storage, ProjectKey::Vault(&key), &added.id, &mut out).unwrap();
+        assert_eq!(out, content);
+        assert_eq!(opened.name, "recovery.pdf");
+        assert_eq!(opened.mime_type, "application/pdf");
+        let wrong = [6u8; crypto::KEY_LEN];
+        assert!(read(&storage, ProjectKey::Vault(&wrong), &added.id, &mut Vec::new()).is_err());
+
+        let new_key = [7u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        let listed = list(&storage, ProjectKey::Vault(&new_key), "p").unwrap();
+        assert_eq!(listed.len(), 1);
+
+        storage.delete_project("p").unwrap();
+        assert!(storage.list_blobs().unwrap().is_empty());
+        assert!(storage.get_attachment(&added.id).is_err());
+    }
+}
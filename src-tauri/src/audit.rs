@@ -0,0 +1,155 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Tamper-evident security audit log. Unlocks, failed attempts, exports, server sign-ins and
+//! master password changes are appended to the `audit_log` table, each entry carrying an
+//! HMAC-SHA256 over its own fields and the previous entry's MAC. The HMAC key lives in the OS
+//! keychain rather than the vault, so events are recorded while the vault is still locked, and
+//! editing, removing or reordering rows in the database file breaks the chain.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::crypto::rng;
+use crate::clock::Clock;
+use crate::keychain;
+use crate::models::AuditEvent;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+pub const KC_AUDIT_KEY: &str = "audit-log-key";
+const AUDIT_KEY_LEN: usize = 32;
+
+pub const EVENT_UNLOCK: &str = "unlock";
+pub const EVENT_UNLOCK_FAILED: &str = "unlock_failed";
+pub const EVENT_EXPORT: &str = "export";
+pub const EVENT_SERVER_SIGN_IN: &str = "server_sign_in";
+pub const EVENT_MASTER_PASSWORD_CHANGED: &str = "master_password_changed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub event_count: usize,
+    /// The first entry whose MAC or sequence number does not fit the chain.
+    pub first_invalid_seq: Option<i64>,
+}
+
+fn audit_key() -> Result<Vec<u8>, String> {
+    if let Some(encoded) = keychain::get(KC_AUDIT_KEY) {
+        return hex::decode(encoded).map_err(|e| format!("Invalid audit log key: {e}"));
+    }
+    let mut key = vec![0u8; AUDIT_KEY_LEN];
+    rng::fill(&mut key);
+    keychain::save(KC_AUDIT_KEY, &hex::encode(&key))?;
+    Ok(key)
+}
+
+fn compute_mac(
+    key: &[u8],
+    prev_mac: &str,
+    seq: i64,
+    event: &str,
+    detail: &str,
+    created_at: &str,
+) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    let seq = seq.to_be_bytes();
+    let fields: [&[u8]; 5] = [
+        prev_mac.as_bytes(),
+        &seq,
+        event.as_bytes(),
+        detail.as_bytes(),
+        created_at.as_bytes(),
+    ];
+    // Length-prefixed, so shifting bytes between fields changes the MAC.
+    for field in fields {
+        mac.update(&(field.len() as u64).to_be_bytes());
+        mac.update(field);
+    }
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn next_event(
+    key: &[u8],
+    last: Option<&AuditEvent>,
+    event: &str,
+    detail: &str,
+    created_at: String,
+) -> AuditEvent {
+    let seq = last.map_or(1, |e| e.seq + 1);
+    let prev_mac = last.map(|e| e.mac.as_str()).unwrap_or_default();
+    AuditEvent {
+        seq,
+        event: event.to_string(),
+        detail: detail.to_string(),
+        mac: compute_mac(key, prev_mac, seq, event, detail, &created_at),
+        created_at,
+    }
+}
+
+fn verify_events(key: &[u8], events: &[AuditEvent]) -> AuditVerification {
+    let mut prev: Option<&AuditEvent> = None;
+    let first_invalid_seq = events.iter().find_map(|e| {
+        let expected_seq = prev.map_or(1, |p| p.seq + 1);
+        let prev_mac = prev.map(|p| p.mac.as_str()).unwrap_or_default();
+        let expected = compute_mac(key, prev_mac, e.seq, &e.event, &e.detail, &e.created_at);
+        prev = Some(e);
+        (e.seq != expected_seq || e.mac != expected).then_some(e.seq)
+    });
+    AuditVerification {
+        valid: first_invalid_seq.is_none(),
+        event_count: events.len(),
+        first_invalid_seq,
+    }
+}
+
+/// Append an event. Auditing never blocks the action being audited, so failures are ignored.
+pub fn record(storage: &dyn StorageProvider, clock: &dyn Clock, event: &str, detail: &str) {
+    let Ok(key) = audit_key() else {
+        return;
+    };
+    if let Ok(last) = storage.last_audit_event() {
+        let next = next_event(&key, last.as_ref(), event, detail, clock.now().to_rfc3339());
+        let _ = storage.append_audit_event(&next);
+    }
+}
+
+/// Same as `record`, for callers that do not hold the storage lock.
+pub fn record_event(state: &AppState, event: &str, detail: &str) {
+    if let Ok(storage) = state.storage.lock() {
+        if let Some(storage) = storage.as_ref() {
+            record(&**storage, &*state.clock, event, detail);
+        }
+    }
+}
+
+pub fn verify(storage: &dyn StorageProvider) -> Result<AuditVerification, String> {
+    let events = storage.list_audit_events().map_err(|e| e.to_string())?;
+    Ok(verify_events(&audit_key()?, &events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edited_or_removed_entries_break_the_chain() {
+        let key = [9u8; AUDIT_KEY_LEN];
+        let mut events: Vec<AuditEvent> = Vec::new();
+        for name in [EVENT_UNLOCK, EVENT_UNLOCK_FAILED, EVENT_EXPORT] {
+            let created_at = "2026-01-01T00:00:00+00:00".to_string();
+            let next = next_event(&key, events.last(), name, "password", created_at);
+            events.push(next);
+        }
+        assert!(verify_events(&key, &events).valid);
+
+        let mut edited = events.clone();
+        edited[1].detail = "pin".to_string();
+        assert_eq!(verify_events(&key, &edited).first_invalid_seq, Some(2));
+
+        let mut removed = events.clone();
+        removed.remove(1);
+        assert_eq!(verify_events(&key, &removed).first_invalid_seq, Some(3));
+    }
+}
@@ -0,0 +1,1932 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Vault backup files. Projects and their backup history are encrypted with the vault key and
+//! the result is signed with the vault's Ed25519 backup key, so a restore can tell a backup this
+//! vault wrote from one that was edited, truncated or written by someone else. Attachment
+//! chunks from the vault's shard travel in the same payload with their attachment records, so a
+//! restore brings back both files. What is kept beside a project goes along too: whether it is
+//! pinned or archived, its color and icon, its custom fields, its TOTP secret, its checklist,
+//! its tags, its saved versions and the passwords it had before. So do the vault's folders and
+//! templates.
+//!
+//! A backup can also carry the vault key wrapped under the backup passphrase or the recovery
+//! code, copied from the vault's own slot when the backup is written. Such a backup still opens
+//! after the master password was changed or forgotten.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use chrono::DateTime;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+use crate::crypto::{self, signing, wrap};
+use crate::models::{
+    Attachment, ChecklistItem, Folder, PasswordHistoryEntry, Project, ProjectAppearance,
+    ProjectBackup, ProjectField, ProjectTotp, ProjectVersion, Tag, Template,
+};
+use crate::clock::Clock;
+use crate::folders;
+use crate::password_history;
+use crate::recovery;
+use crate::rehash;
+use crate::storage::StorageProvider;
+use crate::tags;
+use crate::trash;
+use crate::vault;
+use crate::versions;
+
+pub const BACKUP_FORMAT: &str = "vaultpad-backup";
+pub const BACKUP_VERSION: u32 = 1;
+
+pub const ERR_CORRUPT: &str = "backup_corrupt";
+pub const ERR_SIGNATURE: &str = "backup_signature_invalid";
+pub const ERR_OTHER_VAULT: &str = "backup_from_other_vault";
+pub const ERR_SAMPLE: &str = "backup_sample_unreadable";
+pub const ERR_SECRET: &str = "backup_secret_invalid";
+pub const ERR_TRUNCATED: &str = "backup_truncated";
+pub const ERR_WRITE_MISMATCH: &str = "backup_write_mismatch";
+
+/// How many projects a verification decrypts.
+const SAMPLE_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedProject {
+    id: String,
+    name: String,
+    encrypted_content: String,
+    key_check: String,
+    position: String,
+    created_at: String,
+    updated_at: String,
+    server_id: Option<String>,
+    sync_status: String,
+    last_synced_at: Option<String>,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    archived_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedBackup {
+    id: String,
+    project_id: String,
+    name: String,
+    encrypted_content: String,
+    key_check: String,
+    created_at: String,
+    trigger_type: String,
+    content_length: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedAttachment {
+    id: String,
+    project_id: String,
+    wrapped_key: String,
+    encrypted_name: String,
+    encrypted_mime: String,
+    size: u64,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedAppearance {
+    project_id: String,
+    color: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedField {
+    id: String,
+    project_id: String,
+    position: String,
+    encrypted_field: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedTotp {
+    project_id: String,
+    encrypted_secret: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedPastPassword {
+    id: String,
+    project_id: String,
+    encrypted_password: String,
+    replaced_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedChecklistItem {
+    id: String,
+    project_id: String,
+    position: String,
+    encrypted_item: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedFolder {
+    id: String,
+    parent_id: Option<String>,
+    encrypted_name: String,
+    position: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedTag {
+    id: String,
+    encrypted_name: String,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedProjectTag {
+    project_id: String,
+    tag_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedVersion {
+    id: String,
+    project_id: String,
+    name: String,
+    encrypted_content: String,
+    key_check: String,
+    updated_at: String,
+    saved_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedTemplate {
+    id: String,
+    encrypted_template: String,
+    created_at: String,
+    updated_at: String,
+}
+
+/// An attachment blob from the shard: its chunks in order, still encrypted, base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedBlob {
+    id: String,
+    chunks: Vec<String>,
+}
+
+/// The encrypted payload. Everything after `backups` is absent from backups written before it
+/// was archived, and reads as empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Contents {
+    projects: Vec<ArchivedProject>,
+    backups: Vec<ArchivedBackup>,
+    #[serde(default)]
+    blobs: Vec<ArchivedBlob>,
+    #[serde(default)]
+    attachments: Vec<ArchivedAttachment>,
+    #[serde(default)]
+    appearances: Vec<ArchivedAppearance>,
+    #[serde(default)]
+    fields: Vec<ArchivedField>,
+    #[serde(default)]
+    totps: Vec<ArchivedTotp>,
+    #[serde(default)]
+    password_history: Vec<ArchivedPastPassword>,
+    #[serde(default)]
+    templates: Vec<ArchivedTemplate>,
+    #[serde(default)]
+    checklist_items: Vec<ArchivedChecklistItem>,
+    #[serde(default)]
+    folders: Vec<ArchivedFolder>,
+    #[serde(default)]
+    tags: Vec<ArchivedTag>,
+    #[serde(default)]
+    project_tags: Vec<ArchivedProjectTag>,
+    #[serde(default)]
+    versions: Vec<ArchivedVersion>,
+}
+
+/// What a backup can be opened with besides the unlocked vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupEncryption {
+    #[default]
+    VaultKey,
+    Passphrase,
+    RecoveryCode,
+}
+
+/// The vault key wrapped under the backup passphrase or the recovery code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupKeySlot {
+    pub encryption: BackupEncryption,
+    pub slot: wrap::KeySlot,
+}
+
+/// The part of the file covered by the signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupBody {
+    pub format: String,
+    pub version: u32,
+    pub vault_id: String,
+    pub created_at: String,
+    pub project_count: usize,
+    /// `Contents` as JSON, encrypted with the vault key, base64.
+    pub payload: String,
+    /// Left out when absent, so backups written before it still verify.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_slot: Option<BackupKeySlot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedBackup {
+    pub body: BackupBody,
+    pub public_key: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupVerification {
+    pub created_at: String,
+    pub project_count: usize,
+    pub history_count: usize,
+    /// Projects picked for a trial decryption.
+    pub sampled: usize,
+    pub decrypted: usize,
+    /// Sampled projects with their own password, which the vault key cannot open.
+    pub skipped: usize,
+}
+
+/// How a restore treats the projects the vault already has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestoreMode {
+    /// The vault ends up as the backup: projects in both take the backed-up version, projects
+    /// the backup does not have go to the trash.
+    #[default]
+    Replace,
+    /// Projects in both keep whichever version was changed last.
+    MergeNewerWins,
+    /// Every backed-up project comes in as a new local copy; nothing in the vault changes.
+    ImportAsCopies,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewContents {
+    pub history_count: usize,
+    pub attachment_count: usize,
+    /// Range of the backed-up projects' last changes.
+    pub oldest_update: Option<String>,
+    pub newest_update: Option<String>,
+    /// Projects the vault does not have.
+    pub new_projects: usize,
+    /// In both, changed in the vault since the backup; merge-newer-wins keeps these.
+    pub newer_in_vault: usize,
+    pub newer_in_backup: usize,
+    /// Projects only in the vault; replace moves them to the trash.
+    pub only_in_vault: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupPreview {
+    pub vault_id: String,
+    pub same_vault: bool,
+    pub created_at: String,
+    pub project_count: usize,
+    pub encryption: BackupEncryption,
+    /// `None` for a backup of another vault, whose payload this vault cannot open.
+    pub contents: Option<PreviewContents>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreSummary {
+    pub projects_restored: usize,
+    pub projects_added: usize,
+    /// Left alone by merge-newer-wins because the vault's version is newer.
+    pub projects_skipped: usize,
+    /// Moved to the trash by replace.
+    pub projects_trashed: usize,
+    pub backups_added: usize,
+    pub blobs_added: usize,
+    pub attachments_added: usize,
+}
+
+fn archived_project(p: Project, archived_at: Option<String>) -> ArchivedProject {
+    ArchivedProject {
+        id: p.id,
+        name: p.name,
+        encrypted_content: B64.encode(&p.encrypted_content),
+        key_check: B64.encode(&p.key_check),
+        position: p.position,
+        created_at: p.created_at,
+        updated_at: p.updated_at,
+        server_id: p.server_id,
+        sync_status: p.sync_status,
+        last_synced_at: p.last_synced_at,
+        folder_id: p.folder_id,
+        pinned: p.pinned,
+        archived_at,
+    }
+}
+
+fn archived_backup(b: ProjectBackup) -> ArchivedBackup {
+    ArchivedBackup {
+        id: b.id,
+        project_id: b.project_id,
+        name: b.name,
+        encrypted_content: B64.encode(&b.encrypted_content),
+        key_check: B64.encode(&b.key_check),
+        created_at: b.created_at,
+        trigger_type: b.trigger_type,
+        content_length: b.content_length,
+    }
+}
+
+fn archived_attachment(a: Attachment) -> ArchivedAttachment {
+    ArchivedAttachment {
+        id: a.id,
+        project_id: a.project_id,
+        wrapped_key: B64.encode(&a.wrapped_key),
+        encrypted_name: B64.encode(&a.encrypted_name),
+        encrypted_mime: B64.encode(&a.encrypted_mime),
+        size: a.size,
+        created_at: a.created_at,
+    }
+}
+
+fn archived_appearance(a: ProjectAppearance) -> ArchivedAppearance {
+    ArchivedAppearance {
+        project_id: a.project_id,
+        color: a.encrypted_color.map(|c| B64.encode(c)),
+        icon: a.encrypted_icon.map(|i| B64.encode(i)),
+    }
+}
+
+fn archived_field(f: ProjectField) -> ArchivedField {
+    ArchivedField {
+        id: f.id,
+        project_id: f.project_id,
+        position: f.position,
+        encrypted_field: B64.encode(&f.encrypted_field),
+        created_at: f.created_at,
+        updated_at: f.updated_at,
+    }
+}
+
+fn archived_totp(t: ProjectTotp) -> ArchivedTotp {
+    ArchivedTotp {
+        project_id: t.project_id,
+        encrypted_secret: B64.encode(&t.encrypted_secret),
+        created_at: t.created_at,
+        updated_at: t.updated_at,
+    }
+}
+
+fn archived_past_password(e: PasswordHistoryEntry) -> ArchivedPastPassword {
+    ArchivedPastPassword {
+        id: e.id,
+        project_id: e.project_id,
+        encrypted_password: B64.encode(&e.encrypted_password),
+        replaced_at: e.replaced_at,
+    }
+}
+
+fn archived_checklist_item(i: ChecklistItem) -> ArchivedChecklistItem {
+    ArchivedChecklistItem {
+        id: i.id,
+        project_id: i.project_id,
+        position: i.position,
+        encrypted_item: B64.encode(&i.encrypted_item),
+        created_at: i.created_at,
+        updated_at: i.updated_at,
+    }
+}
+
+fn archived_folder(f: Folder) -> ArchivedFolder {
+    ArchivedFolder {
+        id: f.id,
+        parent_id: f.parent_id,
+        encrypted_name: B64.encode(&f.encrypted_name),
+        position: f.position,
+        created_at: f.created_at,
+        updated_at: f.updated_at,
+    }
+}
+
+fn archived_tag(t: Tag) -> ArchivedTag {
+    ArchivedTag {
+        id: t.id,
+        encrypted_name: B64.encode(&t.encrypted_name),
+        created_at: t.created_at,
+    }
+}
+
+fn archived_version(v: ProjectVersion) -> ArchivedVersion {
+    ArchivedVersion {
+        id: v.id,
+        project_id: v.project_id,
+        name: v.name,
+        encrypted_content: B64.encode(&v.encrypted_content),
+        key_check: B64.encode(&v.key_check),
+        updated_at: v.updated_at,
+        saved_at: v.saved_at,
+    }
+}
+
+fn archived_template(t: Template) -> ArchivedTemplate {
+    ArchivedTemplate {
+        id: t.id,
+        encrypted_template: B64.encode(&t.encrypted_template),
+        created_at: t.created_at,
+        updated_at: t.updated_at,
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    B64.decode(value).map_err(|_| ERR_CORRUPT.to_string())
+}
+
+fn signed_message(body: &BackupBody) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(body).map_err(|e| e.to_string())
+}
+
+fn payload_aad(vault_id: &str) -> Vec<u8> {
+    crypto::field_aad(vault_id, "backup")
+}
+
+/// The vault's slot for `encryption`, to be copied into a backup.
+fn key_slot(
+    storage: &dyn StorageProvider,
+    encryption: BackupEncryption,
+) -> Result<Option<BackupKeySlot>, String> {
+    let slot = match encryption {
+        BackupEncryption::VaultKey => return Ok(None),
+        BackupEncryption::Passphrase => {
+            vault::backup_passphrase_slot(storage)?.ok_or("No backup passphrase set up")?
+        }
+        BackupEncryption::RecoveryCode => {
+            vault::recovery_slot(storage)?.ok_or("No recovery code set up")?
+        }
+    };
+    Ok(Some(BackupKeySlot { encryption, slot: slot.slot }))
+}
+
+/// Write the vault into a signed backup. Unless `encryption` is the vault key, the vault key
+/// goes along wrapped under the chosen secret.
+pub fn seal(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    encryption: BackupEncryption,
+) -> Result<SignedBackup, String> {
+    let key_slot = key_slot(storage, encryption)?;
+    let vault_id = vault::vault_id(storage)?.ok_or("This vault has no identity to sign for")?;
+    let mut archived: HashMap<String, String> =
+        storage.list_archived_projects().map_err(|e| e.to_string())?.into_iter().collect();
+    let projects: Vec<ArchivedProject> = storage
+        .list_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| p.sync_status != "deleted")
+        .map(|p| {
+            let archived_at = archived.remove(&p.id);
+            archived_project(p, archived_at)
+        })
+        .collect();
+    let mut backups = Vec::new();
+    let mut attachments = Vec::new();
+    let mut fields = Vec::new();
+    let mut password_history = Vec::new();
+    let mut checklist_items = Vec::new();
+    let mut versions = Vec::new();
+    for project in &projects {
+        let history = storage.list_backups(&project.id).map_err(|e| e.to_string())?;
+        backups.extend(history.into_iter().map(archived_backup));
+        let files = storage.list_attachments(&project.id).map_err(|e| e.to_string())?;
+        attachments.extend(files.into_iter().map(archived_attachment));
+        let custom = storage.list_project_fields(&project.id).map_err(|e| e.to_string())?;
+        fields.extend(custom.into_iter().map(archived_field));
+        let past = storage.list_password_history(&project.id).map_err(|e| e.to_string())?;
+        password_history.extend(past.into_iter().map(archived_past_password));
+        let items = storage.list_checklist_items(&project.id).map_err(|e| e.to_string())?;
+        checklist_items.extend(
+            items
+                .into_iter()
+                .filter(|i| i.sync_status != "deleted")
+                .map(archived_checklist_item),
+        );
+        let saved = storage.list_versions(&project.id).map_err(|e| e.to_string())?;
+        versions.extend(saved.into_iter().map(archived_version));
+    }
+    let mut blobs = Vec::new();
+    for info in storage.list_blobs().map_err(|e| e.to_string())? {
+        let mut chunks = Vec::with_capacity(info.chunks as usize);
+        for index in 0..info.chunks {
+            let chunk = storage
+                .get_blob_chunk(&info.id, index)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Attachment {} is missing chunk {index}", info.id))?;
+            chunks.push(B64.encode(chunk));
+        }
+        blobs.push(ArchivedBlob { id: info.id, chunks });
+    }
+    let ids: HashSet<&str> = projects.iter().map(|p| p.id.as_str()).collect();
+    let appearances = storage
+        .list_project_appearances()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|a| ids.contains(a.project_id.as_str()))
+        .map(archived_appearance)
+        .collect();
+    let totps = storage
+        .list_project_totps()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|t| ids.contains(t.project_id.as_str()))
+        .map(archived_totp)
+        .collect();
+    let templates = storage
+        .list_templates()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|t| t.sync_status != "deleted")
+        .map(archived_template)
+        .collect();
+    let folders = storage
+        .list_folders()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|f| f.sync_status != "deleted")
+        .map(archived_folder)
+        .collect();
+    let project_tags: Vec<ArchivedProjectTag> = storage
+        .list_project_tags()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(project_id, _)| ids.contains(project_id.as_str()))
+        .map(|(project_id, tag_id)| ArchivedProjectTag { project_id, tag_id })
+        .collect();
+    let tags = storage
+        .list_tags()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|t| project_tags.iter().any(|pt| pt.tag_id == t.id))
+        .map(archived_tag)
+        .collect();
+    let project_count = projects.len();
+
+    let contents = Contents {
+        projects,
+        backups,
+        blobs,
+        attachments,
+        appearances,
+        fields,
+        totps,
+        password_history,
+        templates,
+        checklist_items,
+        folders,
+        tags,
+        project_tags,
+        versions,
+    };
+    let json = serde_json::to_vec(&contents).map_err(|e| e.to_string())?;
+    let payload =
+        crypto::encrypt_with_key_aad(&json, key, &payload_aad(&vault_id)).map_err(|e| e.to_string())?;
+    let body = BackupBody {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        vault_id,
+        created_at: clock.now().to_rfc3339(),
+        project_count,
+        payload: B64.encode(payload),
+        key_slot,
+    };
+
+    let signing_key = vault::backup_signing_key(storage, key)?;
+    let signature = signing::sign(&signing_key, &signed_message(&body)?);
+    Ok(SignedBackup {
+        body,
+        public_key: B64.encode(signing::public_key(&signing_key)),
+        signature: B64.encode(signature),
+    })
+}
+
+/// Parse a backup file. A truncated or otherwise unreadable file is `backup_corrupt`.
+pub fn parse(data: &[u8]) -> Result<SignedBackup, String> {
+    serde_json::from_slice(data).map_err(|_| ERR_CORRUPT.to_string())
+}
+
+/// Check that the backup was signed by this vault's key and has not changed since.
+fn verify(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+) -> Result<(), String> {
+    if backup.body.format != BACKUP_FORMAT || backup.body.version > BACKUP_VERSION {
+        return Err("Unsupported backup format".to_string());
+    }
+    if vault::vault_id(storage)?.as_deref() != Some(backup.body.vault_id.as_str()) {
+        return Err(ERR_OTHER_VAULT.to_string());
+    }
+    // Verify against the key kept in the vault, never against the one the file brings along.
+    let public = signing::public_key(&vault::backup_signing_key(storage, key)?);
+    if decode(&backup.public_key)? != public {
+        return Err(ERR_SIGNATURE.to_string());
+    }
+    signing::verify(
+        &public,
+        &signed_message(&backup.body)?,
+        &decode(&backup.signature)?,
+    )
+    .map_err(|_| ERR_SIGNATURE.to_string())
+}
+
+/// Write `backup` to `path` for a medium that may fail quietly, like a USB stick: under a
+/// temporary name, flushed to the device, then renamed. The file is read back and must match
+/// byte for byte (`backup_truncated:<read>:<written>` or `backup_write_mismatch`) and pass the
+/// signature and manifest checks. Returns the file size.
+pub fn write_verified(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+    path: &Path,
+) -> Result<u64, String> {
+    let data = serde_json::to_vec_pretty(backup).map_err(|e| e.to_string())?;
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    let written = File::create(&partial).and_then(|mut file| {
+        file.write_all(&data)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| std::fs::rename(&partial, path)) {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("Failed to write backup: {e}"));
+    }
+    // The rename is only durable once the directory entry is on the device too.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| format!("Failed to write backup: {e}"))?;
+    }
+
+    let read = std::fs::read(path).map_err(|e| format!("Failed to read backup back: {e}"))?;
+    if read.len() < data.len() {
+        return Err(format!("{ERR_TRUNCATED}:{}:{}", read.len(), data.len()));
+    }
+    if read != data {
+        return Err(ERR_WRITE_MISMATCH.to_string());
+    }
+    open_contents(storage, key, &parse(&read)?)?;
+    Ok(data.len() as u64)
+}
+
+/// The vault key from the backup's own slot, for when the vault cannot be unlocked. The key
+/// must open this vault's backup signing key and the signature must hold, so a slot from some
+/// other vault or an edited file never yields a key. A recovery code is normalized first.
+pub fn open_key(
+    storage: &dyn StorageProvider,
+    backup: &SignedBackup,
+    secret: &str,
+) -> Result<[u8; crypto::KEY_LEN], String> {
+    let slot = backup
+        .body
+        .key_slot
+        .as_ref()
+        .ok_or("This backup can only be opened with the vault key")?;
+    let secret = match slot.encryption {
+        BackupEncryption::RecoveryCode => {
+            recovery::normalize(secret).map_err(|_| ERR_SECRET.to_string())?
+        }
+        _ => secret.to_string(),
+    };
+    let mut key =
+        wrap::unwrap_with_secret(&slot.slot, &secret).map_err(|_| ERR_SECRET.to_string())?;
+    // Without a stored signing key, verifying would create one under whatever key it is given.
+    let has_signing_key = storage
+        .get_meta(vault::META_BACKUP_SIGNING_KEY)
+        .map_err(|e| e.to_string())?
+        .is_some_and(|v| !v.is_empty());
+    let verified = if has_signing_key {
+        verify(storage, &key, backup)
+    } else {
+        Err(ERR_SIGNATURE.to_string())
+    };
+    if let Err(e) = verified {
+        key.zeroize();
+        return Err(e);
+    }
+    Ok(key)
+}
+
+fn open_contents(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+) -> Result<Contents, String> {
+    verify(storage, key, backup)?;
+    let payload = decode(&backup.body.payload)?;
+    let json = crypto::try_decrypt_with_key_aad(&payload, key, &payload_aad(&backup.body.vault_id))
+        .ok_or(ERR_CORRUPT)?;
+    let contents: Contents = serde_json::from_slice(&json).map_err(|_| ERR_CORRUPT.to_string())?;
+    if contents.projects.len() != backup.body.project_count {
+        return Err(ERR_CORRUPT.to_string());
+    }
+    Ok(contents)
+}
+
+fn project_from(archived: ArchivedProject) -> Result<Project, String> {
+    Ok(Project {
+        id: archived.id,
+        name: archived.name,
+        encrypted_content: decode(&archived.encrypted_content)?,
+        key_check: decode(&archived.key_check)?,
+        position: archived.position,
+        created_at: archived.created_at,
+        updated_at: archived.updated_at,
+        server_id: archived.server_id,
+        sync_status: archived.sync_status,
+        last_synced_at: archived.last_synced_at,
+        folder_id: archived.folder_id,
+        revision: 0,
+        pinned: archived.pinned,
+    })
+}
+
+/// Restore drill: check the signature and manifest, then decrypt a random sample of projects.
+/// Nothing is written to the vault. A sample that fails to open is `backup_sample_unreadable:<id>`.
+pub fn verify_contents(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+) -> Result<BackupVerification, String> {
+    let contents = open_contents(storage, key, backup)?;
+    let sample: Vec<&ArchivedProject> = contents
+        .projects
+        .choose_multiple(&mut crypto::rng::SecureRng, SAMPLE_SIZE)
+        .collect();
+
+    let mut report = BackupVerification {
+        created_at: backup.body.created_at.clone(),
+        project_count: contents.projects.len(),
+        history_count: contents.backups.len(),
+        sampled: sample.len(),
+        decrypted: 0,
+        skipped: 0,
+    };
+    for archived in sample {
+        let project = project_from(archived.clone())?;
+        let uses_vault_key = project.key_check.is_empty()
+            || crypto::try_decrypt_with_key(&project.key_check, key).is_some();
+        if !uses_vault_key {
+            report.skipped += 1;
+            continue;
+        }
+        // Archived content is checked as it will be restored, before `rehash::bind_content` binds
+        // what is not bound yet.
+        let (id, name) = (&project.id, &project.name);
+        match crypto::content::decrypt(&project.encrypted_content, key, id, name, true) {
+            Ok(mut content) => {
+                content.zeroize();
+                report.decrypted += 1;
+            }
+            Err(_) => return Err(format!("{ERR_SAMPLE}:{}", project.id)),
+        }
+    }
+    Ok(report)
+}
+
+/// Whether `a` is a later RFC 3339 time than `b`.
+fn is_newer(a: &str, b: &str) -> bool {
+    match (DateTime::parse_from_rfc3339(a), DateTime::parse_from_rfc3339(b)) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => a > b,
+    }
+}
+
+/// Projects the vault shows, by id: neither deleted nor in the trash.
+fn live_projects(storage: &dyn StorageProvider) -> Result<HashMap<String, Project>, String> {
+    let trashed = trash::ids(storage)?;
+    Ok(storage
+        .list_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| p.sync_status != "deleted" && !trashed.contains(&p.id))
+        .map(|p| (p.id.clone(), p))
+        .collect())
+}
+
+/// What a restore would do, without writing anything. The counts need the payload and are left
+/// out for a backup of another vault.
+pub fn preview(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+) -> Result<BackupPreview, String> {
+    let same_vault = vault::vault_id(storage)?.as_deref() == Some(backup.body.vault_id.as_str());
+    let mut preview = BackupPreview {
+        vault_id: backup.body.vault_id.clone(),
+        same_vault,
+        created_at: backup.body.created_at.clone(),
+        project_count: backup.body.project_count,
+        encryption: backup.body.key_slot.as_ref().map(|s| s.encryption).unwrap_or_default(),
+        contents: None,
+    };
+    if !same_vault {
+        return Ok(preview);
+    }
+    let contents = open_contents(storage, key, backup)?;
+    let live = live_projects(storage)?;
+    let mut summary = PreviewContents {
+        history_count: contents.backups.len(),
+        attachment_count: contents.attachments.len(),
+        oldest_update: None,
+        newest_update: None,
+        new_projects: 0,
+        newer_in_vault: 0,
+        newer_in_backup: 0,
+        only_in_vault: 0,
+    };
+    for project in &contents.projects {
+        let at = &project.updated_at;
+        if summary.oldest_update.as_ref().is_none_or(|oldest| is_newer(oldest, at)) {
+            summary.oldest_update = Some(at.clone());
+        }
+        if summary.newest_update.as_ref().is_none_or(|newest| is_newer(at, newest)) {
+            summary.newest_update = Some(at.clone());
+        }
+        match live.get(&project.id) {
+            None => summary.new_projects += 1,
+            Some(existing) if is_newer(at, &existing.updated_at) => summary.newer_in_backup += 1,
+            Some(_) => summary.newer_in_vault += 1,
+        }
+    }
+    let in_backup: HashSet<&str> = contents.projects.iter().map(|p| p.id.as_str()).collect();
+    summary.only_in_vault = live.keys().filter(|id| !in_backup.contains(id.as_str())).count();
+    preview.contents = Some(summary);
+    Ok(preview)
+}
+
+/// Master-key content bound to project `project_id` named `name`, re-encrypted unbound so it
+/// can move to another record. Anything else is not bound to its record and is returned as it
+/// is.
+fn unbind(
+    data: Vec<u8>,
+    key: &[u8; crypto::KEY_LEN],
+    project_id: &str,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    if !crypto::is_aad_bound(&data) {
+        return Ok(data);
+    }
+    let mut plain = crypto::content::decrypt(&data, key, project_id, name, true)
+        .map_err(|_| ERR_CORRUPT.to_string())?;
+    let result = crypto::encrypt_with_key(&plain, key).map_err(|e| e.to_string());
+    plain.zeroize();
+    result
+}
+
+/// Replace the color and icon of each project in `taken` with the backed-up ones.
+fn restore_appearances(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedAppearance>,
+) -> Result<(), String> {
+    let mut archived: HashMap<String, ArchivedAppearance> =
+        archived.into_iter().map(|a| (a.project_id.clone(), a)).collect();
+    let current: HashSet<String> = storage
+        .list_project_appearances()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|a| a.project_id)
+        .collect();
+    for id in taken {
+        let appearance = match archived.remove(id) {
+            Some(a) => ProjectAppearance {
+                project_id: a.project_id,
+                encrypted_color: a.color.as_deref().map(decode).transpose()?,
+                encrypted_icon: a.icon.as_deref().map(decode).transpose()?,
+            },
+            None if current.contains(id) => ProjectAppearance {
+                project_id: id.clone(),
+                encrypted_color: None,
+                encrypted_icon: None,
+            },
+            None => continue,
+        };
+        storage.set_project_appearance(&appearance).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Replace the custom fields of each project in `taken` with the backed-up ones.
+fn restore_fields(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedField>,
+) -> Result<(), String> {
+    let archived: Vec<ArchivedField> =
+        archived.into_iter().filter(|f| taken.contains(&f.project_id)).collect();
+    for id in taken {
+        for field in storage.list_project_fields(id).map_err(|e| e.to_string())? {
+            if !archived.iter().any(|a| a.id == field.id) {
+                storage.delete_project_field(&field.id).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    for field in archived {
+        storage
+            .save_project_field(&ProjectField {
+                encrypted_field: decode(&field.encrypted_field)?,
+                id: field.id,
+                project_id: field.project_id,
+                position: field.position,
+                created_at: field.created_at,
+                updated_at: field.updated_at,
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Replace the TOTP secret of each project in `taken` with the backed-up one.
+fn restore_totps(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedTotp>,
+) -> Result<(), String> {
+    let mut archived: HashMap<String, ArchivedTotp> =
+        archived.into_iter().map(|t| (t.project_id.clone(), t)).collect();
+    for id in taken {
+        let restored = match archived.remove(id) {
+            Some(totp) => storage.save_project_totp(&ProjectTotp {
+                encrypted_secret: decode(&totp.encrypted_secret)?,
+                project_id: totp.project_id,
+                created_at: totp.created_at,
+                updated_at: totp.updated_at,
+            }),
+            None if storage.get_project_totp(id).map_err(|e| e.to_string())?.is_some() => {
+                storage.delete_project_totp(id)
+            }
+            None => continue,
+        };
+        restored.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Pin and archive each project in `taken` as it was in the backup.
+fn restore_flags(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    flags: &HashMap<String, (bool, Option<String>)>,
+) -> Result<(), String> {
+    let archived: HashSet<String> = storage
+        .list_archived_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    for id in taken {
+        let Some((pinned, archived_at)) = flags.get(id) else {
+            continue;
+        };
+        storage.set_pinned(id, *pinned).map_err(|e| e.to_string())?;
+        if archived.contains(id) {
+            storage.unarchive_project(id).map_err(|e| e.to_string())?;
+        }
+        if let Some(archived_at) = archived_at {
+            storage.archive_project(id, archived_at).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Add the backed-up past passwords of each project in `taken` that the vault no longer has.
+/// Like version history, past passwords are only ever added.
+fn restore_password_history(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedPastPassword>,
+) -> Result<(), String> {
+    let mut touched = HashSet::new();
+    for entry in archived.into_iter().filter(|e| taken.contains(&e.project_id)) {
+        if storage.get_password_history_entry(&entry.id).is_ok() {
+            continue;
+        }
+        storage
+            .add_password_history(&PasswordHistoryEntry {
+                encrypted_password: decode(&entry.encrypted_password)?,
+                id: entry.id,
+                project_id: entry.project_id.clone(),
+                replaced_at: entry.replaced_at,
+            })
+            .map_err(|e| e.to_string())?;
+        touched.insert(entry.project_id);
+    }
+    for project_id in touched {
+        storage
+            .prune_password_history(&project_id, password_history::MAX_ENTRIES_PER_PROJECT)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Replace the checklist of each project in `taken` with the backed-up one. Items the vault
+/// has beyond the backup's are removed as `checklist::remove` does, through a tombstone once
+/// they were synced.
+fn restore_checklists(
+    storage: &dyn StorageProvider,
+    taken: &HashSet<String>,
+    now: &str,
+    archived: Vec<ArchivedChecklistItem>,
+) -> Result<(), String> {
+    let mut current: HashMap<String, ChecklistItem> = HashMap::new();
+    for id in taken {
+        let items = storage.list_checklist_items(id).map_err(|e| e.to_string())?;
+        current.extend(items.into_iter().map(|i| (i.id.clone(), i)));
+    }
+    for item in archived.into_iter().filter(|i| taken.contains(&i.project_id)) {
+        let existing = current.remove(&item.id);
+        let sync_status = match &existing {
+            None => "local",
+            Some(i) if i.sync_status == "local" => "local",
+            Some(_) => "modified",
+        };
+        storage
+            .save_checklist_item(&ChecklistItem {
+                encrypted_item: decode(&item.encrypted_item)?,
+                id: item.id,
+                project_id: item.project_id,
+                position: item.position,
+                created_at: item.created_at,
+                updated_at: if existing.is_some() { now.to_string() } else { item.updated_at },
+                sync_status: sync_status.to_string(),
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    for mut item in current.into_values().filter(|i| i.sync_status != "deleted") {
+        if item.sync_status == "local" {
+            storage.delete_checklist_item(&item.id).map_err(|e| e.to_string())?;
+            continue;
+        }
+        item.updated_at = now.to_string();
+        item.sync_status = "deleted".to_string();
+        storage.save_checklist_item(&item).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Give each project in `taken` the tags it had in the backup. A tag the vault lost since comes
+/// back, unless the vault has a tag of the same name by now, which is used instead.
+fn restore_tags(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedTag>,
+    links: Vec<ArchivedProjectTag>,
+) -> Result<(), String> {
+    let archived: HashMap<String, ArchivedTag> =
+        archived.into_iter().map(|t| (t.id.clone(), t)).collect();
+    for project_id in taken {
+        let mut tag_ids: Vec<String> = Vec::new();
+        for link in links.iter().filter(|l| &l.project_id == project_id) {
+            let Some(tag) = archived.get(&link.tag_id) else {
+                continue;
+            };
+            // Adopted one project at a time: setting a project's tags drops those left unused.
+            let tag = Tag {
+                id: tag.id.clone(),
+                encrypted_name: decode(&tag.encrypted_name)?,
+                created_at: tag.created_at.clone(),
+            };
+            let id = tags::adopt(storage, key, &tag)?;
+            if !tag_ids.contains(&id) {
+                tag_ids.push(id);
+            }
+        }
+        storage.set_project_tags(project_id, &tag_ids).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Add the backed-up versions of each project in `taken` that the vault no longer has, then
+/// apply the retention policy to them.
+fn restore_versions(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    taken: &HashSet<String>,
+    archived: Vec<ArchivedVersion>,
+) -> Result<(), String> {
+    let mut touched = HashSet::new();
+    for version in archived.into_iter().filter(|v| taken.contains(&v.project_id)) {
+        if storage.get_version(&version.id).is_ok() {
+            continue;
+        }
+        storage
+            .create_version(&ProjectVersion {
+                encrypted_content: decode(&version.encrypted_content)?,
+                key_check: decode(&version.key_check)?,
+                id: version.id,
+                project_id: version.project_id.clone(),
+                name: version.name,
+                updated_at: version.updated_at,
+                saved_at: version.saved_at,
+            })
+            .map_err(|e| e.to_string())?;
+        touched.insert(version.project_id);
+    }
+    for project_id in touched {
+        versions::apply_retention(storage, clock, Some(&project_id))?;
+    }
+    Ok(())
+}
+
+/// Write the backed-up folders into the vault, under the same rules as templates.
+fn restore_folders(
+    storage: &dyn StorageProvider,
+    mode: RestoreMode,
+    now: &str,
+    archived: Vec<ArchivedFolder>,
+) -> Result<(), String> {
+    let mut current: HashMap<String, Folder> = storage
+        .list_folders()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|f| (f.id.clone(), f))
+        .collect();
+    for folder in archived {
+        let existing = current.remove(&folder.id);
+        let sync_status = match &existing {
+            None => "local",
+            Some(_) if mode == RestoreMode::ImportAsCopies => continue,
+            Some(f)
+                if mode == RestoreMode::MergeNewerWins
+                    && f.sync_status != "deleted"
+                    && !is_newer(&folder.updated_at, &f.updated_at) =>
+            {
+                continue
+            }
+            Some(f) if f.sync_status == "local" => "local",
+            Some(_) => "modified",
+        };
+        storage
+            .save_folder(&Folder {
+                encrypted_name: decode(&folder.encrypted_name)?,
+                id: folder.id,
+                parent_id: folder.parent_id,
+                position: folder.position,
+                created_at: folder.created_at,
+                updated_at: if existing.is_some() { now.to_string() } else { folder.updated_at },
+                sync_status: sync_status.to_string(),
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    if mode != RestoreMode::Replace {
+        return Ok(());
+    }
+    for mut folder in current.into_values().filter(|f| f.sync_status != "deleted") {
+        if folder.sync_status == "local" {
+            storage.delete_folder(&folder.id).map_err(|e| e.to_string())?;
+            continue;
+        }
+        folder.updated_at = now.to_string();
+        folder.sync_status = "deleted".to_string();
+        storage.save_folder(&folder).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Move out of folders the vault no longer has the projects the restore wrote, and those left
+/// in a folder it removed. A folder id that was unknown before the restore is left as it is, for
+/// sync to fill in.
+fn drop_missing_folders(
+    storage: &dyn StorageProvider,
+    known_before: &HashSet<String>,
+    written: &HashSet<String>,
+) -> Result<(), String> {
+    let known = folders::known_ids(storage)?;
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        let Some(folder_id) = project.folder_id.as_deref() else {
+            continue;
+        };
+        if !known.contains(folder_id)
+            && (known_before.contains(folder_id) || written.contains(&project.id))
+        {
+            storage.set_project_folder(&project.id, None).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the backed-up templates into the vault. Like projects, replace leaves the vault with
+/// the backup's templates, merge-newer-wins keeps whichever side changed last and
+/// import-as-copies only adds the ones the vault lacks. A template the replace drops is deleted
+/// as `templates::delete` does, through a tombstone once it was synced.
+fn restore_templates(
+    storage: &dyn StorageProvider,
+    mode: RestoreMode,
+    now: &str,
+    archived: Vec<ArchivedTemplate>,
+) -> Result<(), String> {
+    let mut current: HashMap<String, Template> = storage
+        .list_templates()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+    for template in archived {
+        let existing = current.remove(&template.id);
+        let sync_status = match &existing {
+            None => "local",
+            Some(_) if mode == RestoreMode::ImportAsCopies => continue,
+            Some(t)
+                if mode == RestoreMode::MergeNewerWins
+                    && t.sync_status != "deleted"
+                    && !is_newer(&template.updated_at, &t.updated_at) =>
+            {
+                continue
+            }
+            Some(t) if t.sync_status == "local" => "local",
+            Some(_) => "modified",
+        };
+        storage
+            .save_template(&Template {
+                encrypted_template: decode(&template.encrypted_template)?,
+                id: template.id,
+                created_at: template.created_at,
+                updated_at: if existing.is_some() { now.to_string() } else { template.updated_at },
+                sync_status: sync_status.to_string(),
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    if mode != RestoreMode::Replace {
+        return Ok(());
+    }
+    for mut template in current.into_values().filter(|t| t.sync_status != "deleted") {
+        if template.sync_status == "local" {
+            storage.delete_template(&template.id).map_err(|e| e.to_string())?;
+            continue;
+        }
+        template.updated_at = now.to_string();
+        template.sync_status = "deleted".to_string();
+        storage.save_template(&template).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Verify `backup` and write it into the vault as `mode` says. History entries, saved versions,
+/// attachments and blobs are only ever added. What is kept beside a project comes back with it
+/// wherever the project is taken from the backup, out of the trash if it was there; a copy
+/// leaves it behind, as `duplicate_project` does. Projects the restore writes never end up in a
+/// folder the vault does not have.
+///
+/// Everything is written in one transaction, so a restore that fails leaves the vault as it
+/// was. Blobs live in the shard, outside it, and the ones the restore added are removed again.
+pub fn restore(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    backup: &SignedBackup,
+    mode: RestoreMode,
+) -> Result<RestoreSummary, String> {
+    let contents = open_contents(storage, key, backup)?;
+    let existing: HashSet<String> = storage
+        .list_blobs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
+    let new_blobs: Vec<String> = contents
+        .blobs
+        .iter()
+        .map(|b| b.id.clone())
+        .filter(|id| !existing.contains(id))
+        .collect();
+
+    storage.begin_transaction().map_err(|e| e.to_string())?;
+    let written = write_restore(storage, clock, key, contents, mode).and_then(|summary| {
+        storage.commit_transaction().map_err(|e| e.to_string())?;
+        Ok(summary)
+    });
+    if written.is_err() {
+        let _ = storage.rollback_transaction();
+        for id in &new_blobs {
+            let _ = storage.delete_blob(id);
+        }
+    }
+    written
+}
+
+fn write_restore(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    contents: Contents,
+    mode: RestoreMode,
+) -> Result<RestoreSummary, String> {
+    let now = clock.now().to_rfc3339();
+    let live = live_projects(storage)?;
+    let trashed = trash::ids(storage)?;
+    let known_folders = folders::known_ids(storage)?;
+
+    let mut summary = RestoreSummary {
+        projects_restored: 0,
+        projects_added: 0,
+        projects_skipped: 0,
+        projects_trashed: 0,
+        backups_added: 0,
+        blobs_added: 0,
+        attachments_added: 0,
+    };
+    let in_backup: HashSet<String> = contents.projects.iter().map(|p| p.id.clone()).collect();
+    let flags: HashMap<String, (bool, Option<String>)> = contents
+        .projects
+        .iter()
+        .map(|p| (p.id.clone(), (p.pinned, p.archived_at.clone())))
+        .collect();
+    // Ids of the copies made in import-as-copies mode, by original id.
+    let mut copies: HashMap<String, String> = HashMap::new();
+    // Projects now as they are in the backup.
+    let mut taken: HashSet<String> = HashSet::new();
+    for archived in contents.projects {
+        let mut project = project_from(archived)?;
+        if mode == RestoreMode::ImportAsCopies {
+            let id = Uuid::new_v4().to_string();
+            project.encrypted_content =
+                unbind(project.encrypted_content, key, &project.id, &project.name)?;
+            copies.insert(std::mem::replace(&mut project.id, id.clone()), id);
+            project.name = format!("{} (restored)", project.name);
+            project.position = String::new();
+            project.updated_at = now.clone();
+            project.server_id = None;
+            project.sync_status = "local".to_string();
+            project.last_synced_at = None;
+            storage.create_project(&project).map_err(|e| e.to_string())?;
+            summary.projects_added += 1;
+            continue;
+        }
+        match storage.get_project(&project.id) {
+            // A trashed project is not one the vault shows, so merging takes the backup's.
+            Ok(existing)
+                if mode == RestoreMode::MergeNewerWins
+                    && !trashed.contains(&project.id)
+                    && !is_newer(&project.updated_at, &existing.updated_at) =>
+            {
+                summary.projects_skipped += 1;
+            }
+            Ok(existing) => {
+                taken.insert(project.id.clone());
+                project.updated_at = now.clone();
+                project.server_id = existing.server_id;
+                project.sync_status = match existing.sync_status.as_str() {
+                    "synced" => "modified".to_string(),
+                    status => status.to_string(),
+                };
+                storage.update_project(&project).map_err(|e| e.to_string())?;
+                storage
+                    .set_project_folder(&project.id, project.folder_id.as_deref())
+                    .map_err(|e| e.to_string())?;
+                if trashed.contains(&project.id) {
+                    trash::restore(storage, &project.id)?;
+                }
+                summary.projects_restored += 1;
+            }
+            Err(_) => {
+                // Recreated locally; the server copy, if any, is gone or will sync anew.
+                project.server_id = None;
+                project.sync_status = "local".to_string();
+                project.last_synced_at = None;
+                taken.insert(project.id.clone());
+                storage.create_project(&project).map_err(|e| e.to_string())?;
+                summary.projects_added += 1;
+            }
+        }
+    }
+    if mode == RestoreMode::Replace {
+        // To the trash rather than gone, so a replace can still be undone project by project.
+        for id in live.keys().filter(|id| !in_backup.contains(*id)) {
+            storage.trash_project(id, &now).map_err(|e| e.to_string())?;
+            summary.projects_trashed += 1;
+        }
+    }
+    for mut archived in contents.backups {
+        let mut encrypted_content = decode(&archived.encrypted_content)?;
+        if mode == RestoreMode::ImportAsCopies {
+            let Some(copy_id) = copies.get(&archived.project_id) else {
+                continue;
+            };
+            encrypted_content =
+                unbind(encrypted_content, key, &archived.project_id, &archived.name)?;
+            archived.id = Uuid::new_v4().to_string();
+            archived.project_id = copy_id.clone();
+        } else if storage.get_backup(&archived.id).is_ok() {
+            continue;
+        }
+        storage
+            .create_backup(&ProjectBackup {
+                id: archived.id,
+                project_id: archived.project_id,
+                name: archived.name,
+                encrypted_content,
+                key_check: decode(&archived.key_check)?,
+                created_at: archived.created_at,
+                trigger_type: archived.trigger_type,
+                content_length: archived.content_length,
+            })
+            .map_err(|e| e.to_string())?;
+        summary.backups_added += 1;
+    }
+    // Blobs are immutable once written, so one already in the shard is the same blob.
+    let existing: Vec<String> = storage
+        .list_blobs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
+    for blob in contents.blobs {
+        if existing.contains(&blob.id) {
+            continue;
+        }
+        for (index, chunk) in blob.chunks.iter().enumerate() {
+            storage
+                .put_blob_chunk(&blob.id, index as u32, &decode(chunk)?)
+                .map_err(|e| e.to_string())?;
+        }
+        summary.blobs_added += 1;
+    }
+    // Attachment keys are bound to the attachment, not the project, so a copy can take over
+    // an attachment the vault no longer has. One it still has stays with the original.
+    for archived in contents.attachments {
+        if storage.get_attachment(&archived.id).is_ok() {
+            continue;
+        }
+        let project_id = match mode {
+            RestoreMode::ImportAsCopies => match copies.get(&archived.project_id) {
+                Some(copy_id) => copy_id.clone(),
+                None => continue,
+            },
+            _ => archived.project_id,
+        };
+        storage
+            .create_attachment(&Attachment {
+                id: archived.id,
+                project_id,
+                wrapped_key: decode(&archived.wrapped_key)?,
+                encrypted_name: decode(&archived.encrypted_name)?,
+                encrypted_mime: decode(&archived.encrypted_mime)?,
+                size: archived.size,
+                created_at: archived.created_at,
+            })
+            .map_err(|e| e.to_string())?;
+        summary.attachments_added += 1;
+    }
+    restore_flags(storage, &taken, &flags)?;
+    restore_appearances(storage, &taken, contents.appearances)?;
+    restore_fields(storage, &taken, contents.fields)?;
+    restore_totps(storage, &taken, contents.totps)?;
+    restore_password_history(storage, &taken, contents.password_history)?;
+    restore_checklists(storage, &taken, &now, contents.checklist_items)?;
+    restore_tags(storage, key, &taken, contents.tags, contents.project_tags)?;
+    restore_versions(storage, clock, &taken, contents.versions)?;
+    restore_templates(storage, mode, &now, contents.templates)?;
+    restore_folders(storage, mode, &now, contents.folders)?;
+    let written: HashSet<String> = taken.into_iter().chain(copies.into_values()).collect();
+    drop_missing_folders(storage, &known_folders, &written)?;
+    // Copies, and content from archives made before the vault was bound, are bound like the rest.
+    if rehash::is_bound(storage) {
+        storage.set_meta(rehash::META_CONTENT_BOUND, "").map_err(|e| e.to_string())?;
+        rehash::bind_content(storage, key);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::appearance;
+    use crate::attachments::ProjectKey;
+    use crate::checklist;
+    use crate::clock::{ManualClock, SystemClock};
+    use crate::custom_fields;
+    use crate::storage::local::LocalStorage;
+    use crate::templates;
+    use crate::totp;
+
+    #[test]
+    fn tampered_or_truncated_backups_are_rejected() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+        assert!(verify_contents(&storage, &key, &backup).is_ok());
+        assert!(restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).is_ok());
+
+        let mut tampered = backup.clone();
+        tampered.body.project_count += 1;
+        assert_eq!(restore(&storage, &SystemClock, &key, &tampered, RestoreMode::Replace).unwrap_err(), ERR_SIGNATURE);
+
+        let data = serde_json::to_vec(&backup).unwrap();
+        assert_eq!(parse(&data[..data.len() / 2]).unwrap_err(), ERR_CORRUPT);
+    }
+
+    #[test]
+    fn backups_and_restores_take_their_time_from_the_clock() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let start = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z").unwrap();
+        let clock = ManualClock::new(start.with_timezone(&chrono::Utc));
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+
+        let backup = seal(&storage, &clock, &key, BackupEncryption::VaultKey).unwrap();
+        assert_eq!(backup.body.created_at, clock.now().to_rfc3339());
+        clock.advance(std::time::Duration::from_secs(60));
+        restore(&storage, &clock, &key, &backup, RestoreMode::Replace).unwrap();
+        assert_eq!(storage.get_project("a").unwrap().updated_at, clock.now().to_rfc3339());
+    }
+
+    #[test]
+    fn restore_brings_back_shard_blobs() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.put_blob_chunk("blob", 0, b"one").unwrap();
+        storage.put_blob_chunk("blob", 1, b"two").unwrap();
+
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+        storage.delete_blob("blob").unwrap();
+        assert_eq!(restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap().blobs_added, 1);
+        assert_eq!(storage.get_blob_chunk("blob", 1).unwrap().as_deref(), Some(&b"two"[..]));
+        assert_eq!(restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap().blobs_added, 0);
+    }
+
+    #[test]
+    fn passphrase_backups_open_without_the_vault() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        assert!(seal(&storage, &SystemClock, &key, BackupEncryption::Passphrase).is_err());
+
+        let slot = wrap::wrap_with_secret(&key, "long backup phrase", &crypto::KdfParams::default())
+            .unwrap();
+        let created_at = "2026-01-01T00:00:00Z".to_string();
+        vault::set_backup_passphrase_slot(&storage, Some(&vault::RecoverySlot { slot, created_at }))
+            .unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::Passphrase).unwrap();
+        assert_eq!(open_key(&storage, &backup, "long backup phrase").unwrap(), key);
+        assert_eq!(open_key(&storage, &backup, "wrong phrase").unwrap_err(), ERR_SECRET);
+        let plain = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+        assert!(open_key(&storage, &plain, "long backup phrase").is_err());
+
+        let other = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&other, "2026-01-01T00:00:00Z").unwrap();
+        vault::backup_signing_key(&other, &[9u8; crypto::KEY_LEN]).unwrap();
+        assert_eq!(open_key(&other, &backup, "long backup phrase").unwrap_err(), ERR_OTHER_VAULT);
+    }
+
+    fn project(id: &str, content: &[u8], key: &[u8; crypto::KEY_LEN], updated_at: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: id.to_string(),
+            encrypted_content: crypto::content::encrypt(content, key, id, id, true).unwrap(),
+            key_check: crypto::encrypt_with_key(b"mk", key).unwrap(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: updated_at.to_string(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn restore_modes_treat_existing_projects_differently() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.create_project(&project("a", b"old a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"old b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        storage.update_project(&project("a", b"new a", &key, "2026-01-03T00:00:00Z")).unwrap();
+        storage.create_project(&project("c", b"c", &key, "2026-01-03T00:00:00Z")).unwrap();
+        let contents = preview(&storage, &key, &backup).unwrap().contents.unwrap();
+        assert_eq!((contents.newer_in_vault, contents.only_in_vault), (2, 1));
+
+        let merged = restore(&storage, &SystemClock, &key, &backup, RestoreMode::MergeNewerWins).unwrap();
+        assert_eq!((merged.projects_skipped, merged.projects_trashed), (2, 0));
+
+        let copied = restore(&storage, &SystemClock, &key, &backup, RestoreMode::ImportAsCopies).unwrap();
+        assert_eq!(copied.projects_added, 2);
+        let projects = storage.list_projects().unwrap();
+        let copy = projects.iter().find(|p| p.name == "a (restored)").unwrap();
+        assert_eq!(rehash::open_project_content(copy, &key).unwrap(), b"old a");
+
+        let replaced = restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        assert_eq!(replaced.projects_restored, 2);
+        assert_eq!(replaced.projects_trashed, 3);
+        let a = storage.get_project("a").unwrap();
+        assert_eq!(rehash::open_project_content(&a, &key).unwrap(), b"old a");
+        assert!(trash::ids(&storage).unwrap().contains("c"));
+    }
+
+    /// `backup` with its payload changed by `edit`, signed again with the vault's key.
+    fn edited(
+        storage: &dyn StorageProvider,
+        key: &[u8; crypto::KEY_LEN],
+        backup: &SignedBackup,
+        edit: impl FnOnce(&mut Contents),
+    ) -> SignedBackup {
+        let mut contents = open_contents(storage, key, backup).unwrap();
+        edit(&mut contents);
+        let json = serde_json::to_vec(&contents).unwrap();
+        let aad = payload_aad(&backup.body.vault_id);
+        let mut edited = backup.clone();
+        edited.body.payload = B64.encode(crypto::encrypt_with_key_aad(&json, key, &aad).unwrap());
+        let signing_key = vault::backup_signing_key(storage, key).unwrap();
+        let signature = signing::sign(&signing_key, &signed_message(&edited.body).unwrap());
+        edited.signature = B64.encode(signature);
+        edited
+    }
+
+    #[test]
+    fn failed_restores_leave_the_vault_unchanged() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.create_project(&project("a", b"old a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        storage.update_project(&project("a", b"new a", &key, "2026-01-03T00:00:00Z")).unwrap();
+        trash::trash(&storage, &SystemClock, "b").unwrap();
+        storage.create_project(&project("c", b"c", &key, "2026-01-03T00:00:00Z")).unwrap();
+        // The attachment cannot be read, which only shows once the projects and the blob are in.
+        let broken = edited(&storage, &key, &backup, |contents| {
+            contents.blobs.push(ArchivedBlob {
+                id: "blob".to_string(),
+                chunks: vec![B64.encode(b"one")],
+            });
+            contents.attachments.push(ArchivedAttachment {
+                id: "file".to_string(),
+                project_id: "a".to_string(),
+                wrapped_key: "not base64!".to_string(),
+                encrypted_name: String::new(),
+                encrypted_mime: String::new(),
+                size: 3,
+                created_at: "2026-01-02T00:00:00Z".to_string(),
+            });
+        });
+        assert_eq!(restore(&storage, &SystemClock, &key, &broken, RestoreMode::Replace).unwrap_err(), ERR_CORRUPT);
+
+        let a = storage.get_project("a").unwrap();
+        assert_eq!(rehash::open_project_content(&a, &key).unwrap(), b"new a");
+        assert_eq!(trash::ids(&storage).unwrap(), HashSet::from(["b".to_string()]));
+        assert!(storage.list_blobs().unwrap().is_empty());
+
+        // Merging takes a trashed project from the backup and brings it out of the trash.
+        let merged = restore(&storage, &SystemClock, &key, &backup, RestoreMode::MergeNewerWins).unwrap();
+        assert_eq!((merged.projects_restored, merged.projects_skipped), (1, 1));
+        assert!(trash::ids(&storage).unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_brings_back_pins_and_archiving() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.set_pinned("a", true).unwrap();
+        storage.archive_project("a", "2026-01-03T00:00:00Z").unwrap();
+        storage.set_pinned("b", true).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        storage.set_pinned("a", false).unwrap();
+        storage.unarchive_project("a").unwrap();
+        storage.delete_project("b").unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+
+        assert!(storage.get_project("a").unwrap().pinned);
+        assert!(storage.get_project("b").unwrap().pinned);
+        let archived = storage.list_archived_projects().unwrap();
+        assert_eq!(archived, [("a".to_string(), "2026-01-03T00:00:00Z".to_string())]);
+    }
+
+    #[test]
+    fn restore_brings_back_project_appearance() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        appearance::set_for_project(&storage, &key, "a", Some("#ff0000"), Some("🔑")).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        appearance::set_for_project(&storage, &key, "a", Some("#00ff00"), None).unwrap();
+        appearance::set_for_project(&storage, &key, "b", Some("#0000ff"), None).unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        let restored = appearance::by_project(&storage, &key).unwrap();
+        assert_eq!(restored["a"].color.as_deref(), Some("#ff0000"));
+        assert_eq!(restored["a"].icon.as_deref(), Some("🔑"));
+        assert!(!restored.contains_key("b"));
+    }
+
+    #[test]
+    fn restore_brings_back_custom_fields() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let vault_key = ProjectKey::Vault(&key);
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let pin = custom_fields::add(&storage, &SystemClock, vault_key, "a", "PIN", "1234", true)
+            .unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        custom_fields::remove(&storage, &pin.id).unwrap();
+        custom_fields::add(&storage, &SystemClock, vault_key, "a", "Door", "42", false).unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        let fields = custom_fields::list(&storage, vault_key, "a").unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!((fields[0].label.as_str(), fields[0].value.as_str()), ("PIN", "1234"));
+    }
+
+    #[test]
+    fn restore_brings_back_totp_secrets() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let vault_key = ProjectKey::Vault(&key);
+        let options = totp::TotpOptions::default();
+        for id in ["a", "b"] {
+            storage.create_project(&project(id, b"x", &key, "2026-01-02T00:00:00Z")).unwrap();
+        }
+        totp::set(&storage, &SystemClock, vault_key, "a", "JBSWY3DPEHPK3PXP", options).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        totp::remove(&storage, "a").unwrap();
+        totp::set(&storage, &SystemClock, vault_key, "b", "JBSWY3DPEHPK3PXP", options).unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        assert_eq!(totp::info(&storage, vault_key, "a").unwrap().unwrap().digits, 6);
+        assert!(totp::info(&storage, vault_key, "b").unwrap().is_none());
+    }
+
+    #[test]
+    fn restore_brings_back_password_history() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let vault_key = ProjectKey::Vault(&key);
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let login = |password: &str| {
+            let mut credential = crate::models::Credential::default();
+            credential.password = password.to_string();
+            crate::credential::encode(&credential).unwrap().to_string()
+        };
+        let (one, two) = (login("one"), login("two"));
+        password_history::record_change(&storage, &SystemClock, vault_key, "a", &one, &two).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        let entry = storage.list_password_history("a").unwrap().remove(0);
+        storage.delete_project("a").unwrap();
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        assert!(storage.get_password_history_entry(&entry.id).is_err());
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        let past = password_history::list(&storage, vault_key, "a").unwrap();
+        assert_eq!(past.iter().map(|p| p.password.as_str()).collect::<Vec<_>>(), ["one"]);
+    }
+
+    #[test]
+    fn restore_brings_back_checklists() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let vault_key = ProjectKey::Vault(&key);
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let milk = checklist::add(&storage, &SystemClock, vault_key, "a", "Milk").unwrap();
+        checklist::toggle(&storage, &SystemClock, vault_key, &milk.id).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        checklist::remove(&storage, &SystemClock, &milk.id).unwrap();
+        let bread = checklist::add(&storage, &SystemClock, vault_key, "a", "Bread").unwrap();
+        let mut bread = storage.get_checklist_item(&bread.id).unwrap().unwrap();
+        bread.sync_status = "synced".to_string();
+        storage.save_checklist_item(&bread).unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+
+        let items = checklist::list(&storage, vault_key, "a").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].text.as_str(), items[0].checked), ("Milk", true));
+        let tombstone = storage.get_checklist_item(&bread.id).unwrap().unwrap();
+        assert_eq!(tombstone.sync_status, "deleted");
+    }
+
+    #[test]
+    fn restore_brings_back_folders_and_drops_missing_ones() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        let work = folders::create(&storage, &SystemClock, &key, "Work", None).unwrap();
+        folders::move_project(&storage, "a", Some(&work.id)).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        folders::delete(&storage, &SystemClock, &work.id).unwrap();
+        let home = folders::create(&storage, &SystemClock, &key, "Home", None).unwrap();
+        folders::move_project(&storage, "b", Some(&home.id)).unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+
+        let names: Vec<String> =
+            folders::list(&storage, &key).unwrap().into_iter().map(|f| f.name).collect();
+        assert_eq!(names, ["Work"]);
+        assert_eq!(storage.get_project("a").unwrap().folder_id, Some(work.id));
+        assert_eq!(storage.get_project("b").unwrap().folder_id, None);
+    }
+
+    #[test]
+    fn restore_brings_back_tags_and_versions() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        tags::set_for_project(&storage, &SystemClock, &key, "a", &names(&["Bank", "Tax"])).unwrap();
+        versions::record(&storage, &SystemClock, &storage.get_project("a").unwrap()).unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        storage.delete_project("a").unwrap();
+        storage.create_project(&project("a", b"a", &key, "2026-01-02T00:00:00Z")).unwrap();
+        storage.create_project(&project("b", b"b", &key, "2026-01-02T00:00:00Z")).unwrap();
+        tags::set_for_project(&storage, &SystemClock, &key, "b", &names(&["tax"])).unwrap();
+        assert!(versions::list(&storage, "a").unwrap().is_empty());
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::MergeNewerWins).unwrap();
+        assert!(versions::list(&storage, "a").unwrap().is_empty());
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+
+        assert_eq!(versions::list(&storage, "a").unwrap().len(), 1);
+        let tags = tags::list(&storage, &key).unwrap();
+        let counts: Vec<(&str, usize)> =
+            tags.iter().map(|t| (t.name.as_str(), t.project_count)).collect();
+        assert_eq!(counts, [("Bank", 1), ("tax", 2)]);
+    }
+
+    #[test]
+    fn restore_brings_back_templates() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let wifi = templates::create(&storage, &SystemClock, &key, "Wi-Fi", "SSID:").unwrap();
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+
+        templates::delete(&storage, &SystemClock, &wifi.id).unwrap();
+        templates::create(&storage, &SystemClock, &key, "Bank", "IBAN:").unwrap();
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::ImportAsCopies).unwrap();
+        assert_eq!(templates::list(&storage, &key).unwrap().len(), 2);
+
+        restore(&storage, &SystemClock, &key, &backup, RestoreMode::Replace).unwrap();
+        let restored = templates::list(&storage, &key).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!((restored[0].name.as_str(), restored[0].content.as_str()), ("Wi-Fi", "SSID:"));
+    }
+
+    #[test]
+    fn verified_writes_leave_only_the_finished_file() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let dir = std::env::temp_dir().join(format!("vaultpad-removable-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.vpbackup");
+
+        let backup = seal(&storage, &SystemClock, &key, BackupEncryption::VaultKey).unwrap();
+        let size = write_verified(&storage, &key, &backup, &path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), size);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
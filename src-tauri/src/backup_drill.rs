@@ -0,0 +1,148 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Restore drills. When enabled, the most recent vault backup file is verified once a week
+//! while the vault is unlocked, and `backup-verification-failed` is emitted if it no longer
+//! checks out, so a broken backup is noticed before it is needed.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup_archive::{self, BackupVerification};
+use crate::crypto;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+pub const SETTING_BACKUP_DRILL: &str = "backup_drill";
+const DRILL_INTERVAL_DAYS: i64 = 7;
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DrillSettings {
+    pub enabled: bool,
+    /// The file written by the latest `export_vault_backup`.
+    pub latest_backup: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DrillFailure {
+    pub path: String,
+    pub error: String,
+}
+
+pub fn settings(storage: &dyn StorageProvider) -> Result<DrillSettings, String> {
+    match storage
+        .get_setting(SETTING_BACKUP_DRILL)
+        .map_err(|e| e.to_string())?
+    {
+        Some(json) if !json.is_empty() => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid backup drill settings: {e}"))
+        }
+        _ => Ok(DrillSettings::default()),
+    }
+}
+
+fn save(storage: &dyn StorageProvider, settings: &DrillSettings) -> Result<(), String> {
+    let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+    storage
+        .set_setting(SETTING_BACKUP_DRILL, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn set_enabled(storage: &dyn StorageProvider, enabled: bool) -> Result<DrillSettings, String> {
+    let mut current = settings(storage)?;
+    current.enabled = enabled;
+    save(storage, &current)?;
+    Ok(current)
+}
+
+pub fn record_export(storage: &dyn StorageProvider, path: &str) -> Result<(), String> {
+    let mut current = settings(storage)?;
+    current.latest_backup = Some(path.to_string());
+    save(storage, &current)
+}
+
+fn is_due(settings: &DrillSettings, now: DateTime<Utc>) -> bool {
+    if !settings.enabled || settings.latest_backup.is_none() {
+        return false;
+    }
+    settings
+        .last_run_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_none_or(|at| now.signed_duration_since(at).num_days() >= DRILL_INTERVAL_DAYS)
+}
+
+/// Read and verify the backup at `path` against the open vault, leaving the vault unchanged.
+pub fn verify_file(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    path: &str,
+) -> Result<BackupVerification, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read backup: {e}"))?;
+    let backup = backup_archive::parse(&data)?;
+    backup_archive::verify_contents(storage, key, &backup)
+}
+
+/// Run the drill if one is due and the vault is unlocked. Only a failed check is returned.
+fn run_if_due(state: &AppState) -> Result<Option<DrillFailure>, String> {
+    let cached = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(crypto::locked::copy);
+    let Some(key) = cached else {
+        return Ok(None);
+    };
+    let guard = state.storage.lock().map_err(|e| e.to_string());
+    let outcome = guard.and_then(|guard| {
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+        let mut current = settings(&**storage)?;
+        let path = match current.latest_backup.clone() {
+            Some(path) if is_due(&current, state.clock.now()) => path,
+            _ => return Ok(None),
+        };
+        let result = verify_file(&**storage, &key, &path);
+        current.last_run_at = Some(state.clock.now().to_rfc3339());
+        current.last_error = result.as_ref().err().cloned();
+        save(&**storage, &current)?;
+        Ok(result.err().map(|error| DrillFailure { path, error }))
+    });
+    outcome
+}
+
+/// Check once an hour whether a drill is due for the open vault.
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let state = app.state::<AppState>();
+        if let Ok(Some(failure)) = run_if_due(&state) {
+            let _ = app.emit("backup-verification-failed", failure);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drill_is_due_weekly_once_enabled() {
+        let now = Utc::now();
+        let mut settings = DrillSettings {
+            enabled: true,
+            latest_backup: Some("/backups/vault.vpbackup".to_string()),
+            ..DrillSettings::default()
+        };
+        assert!(is_due(&settings, now));
+
+        settings.last_run_at = Some((now - chrono::Duration::days(2)).to_rfc3339());
+        assert!(!is_due(&settings, now));
+        settings.last_run_at = Some((now - chrono::Duration::days(8)).to_rfc3339());
+        assert!(is_due(&settings, now));
+
+        settings.enabled = false;
+        assert!(!is_due(&settings, now));
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Scheduled vault backups. Once a schedule is set, a signed, encrypted backup file (see
+//! `backup_archive`) is written to the backup directory every day or week while the vault is
+//! unlocked, and older files of the same vault are pruned by count and age. Each new file also
+//! becomes the one the restore drill verifies. A failed run emits `scheduled-backup-failed`.
+//! The files can also be made to open with the backup passphrase or the recovery code.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup_archive::{self, BackupEncryption};
+use crate::backup_drill;
+use crate::clock::Clock;
+use crate::crypto;
+use crate::storage::StorageProvider;
+use crate::vault;
+use crate::AppState;
+
+pub const SETTING_BACKUP_SCHEDULE: &str = "backup_schedule";
+pub const BACKUP_EXTENSION: &str = "vpbackup";
+const FILE_PREFIX: &str = "vaultpad-";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+impl Frequency {
+    fn interval(self) -> chrono::Duration {
+        match self {
+            Frequency::Daily => chrono::Duration::days(1),
+            Frequency::Weekly => chrono::Duration::days(7),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleSettings {
+    /// `None` while scheduled backups are off.
+    pub frequency: Option<Frequency>,
+    /// Where backup files go; `~/.vaultpad/backups` when not set.
+    pub directory: Option<String>,
+    /// Keep at most this many files.
+    pub keep_count: Option<usize>,
+    /// Remove files older than this many days.
+    pub keep_days: Option<u32>,
+    /// What else besides the vault key opens the files.
+    #[serde(default)]
+    pub encryption: BackupEncryption,
+    pub last_run_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupFile {
+    pub path: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledBackup {
+    pub path: String,
+    pub created_at: String,
+    pub project_count: usize,
+    /// Files removed by the retention rules.
+    pub pruned: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledBackupFailure {
+    pub directory: String,
+    pub error: String,
+}
+
+pub fn settings(storage: &dyn StorageProvider) -> Result<ScheduleSettings, String> {
+    match storage
+        .get_setting(SETTING_BACKUP_SCHEDULE)
+        .map_err(|e| e.to_string())?
+    {
+        Some(json) if !json.is_empty() => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid backup schedule: {e}"))
+        }
+        _ => Ok(ScheduleSettings::default()),
+    }
+}
+
+pub fn save(storage: &dyn StorageProvider, settings: &ScheduleSettings) -> Result<(), String> {
+    let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+    storage
+        .set_setting(SETTING_BACKUP_SCHEDULE, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn directory(settings: &ScheduleSettings) -> Result<PathBuf, String> {
+    match settings.directory.as_deref().filter(|d| !d.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::home_dir()
+            .map(|home| home.join(".vaultpad").join("backups"))
+            .ok_or_else(|| "Cannot determine home directory".to_string()),
+    }
+}
+
+fn is_due(settings: &ScheduleSettings, now: DateTime<Utc>) -> bool {
+    let Some(frequency) = settings.frequency else {
+        return false;
+    };
+    settings
+        .last_run_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_none_or(|at| now.signed_duration_since(at) >= frequency.interval())
+}
+
+fn file_name(vault_id: &str, at: DateTime<Utc>) -> String {
+    format!("{FILE_PREFIX}{vault_id}-{}.{BACKUP_EXTENSION}", at.format(TIMESTAMP_FORMAT))
+}
+
+/// When a file named by `file_name` for `vault_id` was written.
+fn written_at(name: &str, vault_id: &str) -> Option<DateTime<Utc>> {
+    let stamp = name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_prefix(vault_id)?
+        .strip_prefix('-')?
+        .strip_suffix(BACKUP_EXTENSION)?
+        .strip_suffix('.')?;
+    chrono::NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+/// The scheduled backups of the vault `vault_id` in `dir`, newest first.
+pub fn list(dir: &Path, vault_id: &str) -> Result<Vec<BackupFile>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Failed to read backup directory: {e}")),
+    };
+    let mut files: Vec<(DateTime<Utc>, BackupFile)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let at = written_at(&entry.file_name().to_string_lossy(), vault_id)?;
+            Some((
+                at,
+                BackupFile {
+                    path: entry.path().to_string_lossy().to_string(),
+                    created_at: at.to_rfc3339(),
+                    size: entry.metadata().ok()?.len(),
+                },
+            ))
+        })
+        .collect();
+    files.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+    Ok(files.into_iter().map(|(_, file)| file).collect())
+}
+
+/// Files to remove so at most `keep_count` remain and none is older than `keep_days`. The
+/// newest file is always kept.
+fn to_prune(
+    files: &[BackupFile],
+    keep_count: Option<usize>,
+    keep_days: Option<u32>,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    files
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(index, file)| {
+            let too_many = keep_count.is_some_and(|keep| *index >= keep);
+            let too_old = keep_days.is_some_and(|days| {
+                DateTime::parse_from_rfc3339(&file.created_at)
+                    .is_ok_and(|at| now.signed_duration_since(at).num_days() >= i64::from(days))
+            });
+            too_many || too_old
+        })
+        .map(|(_, file)| file.path.clone())
+        .collect()
+}
+
+/// Write a backup of the open vault into the backup directory now and apply the retention
+/// rules. The file is written and checked as `backup_archive::write_verified` does.
+pub fn run(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<ScheduledBackup, String> {
+    let now = clock.now();
+    let mut current = settings(storage)?;
+    let result = write_and_prune(storage, clock, key, &current);
+    current.last_run_at = Some(now.to_rfc3339());
+    current.last_error = result.as_ref().err().cloned();
+    save(storage, &current)?;
+    let backup = result?;
+    let _ = backup_drill::record_export(storage, &backup.path);
+    Ok(backup)
+}
+
+fn write_and_prune(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    settings: &ScheduleSettings,
+) -> Result<ScheduledBackup, String> {
+    let now = clock.now();
+    let vault_id = vault::vault_id(storage)?.ok_or("This vault has no identity to sign for")?;
+    let dir = directory(settings)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {e}"))?;
+
+    let backup = backup_archive::seal(storage, clock, key, settings.encryption)?;
+    let path = dir.join(file_name(&vault_id, now));
+    backup_archive::write_verified(storage, key, &backup, &path)?;
+
+    let pruned = to_prune(&list(&dir, &vault_id)?, settings.keep_count, settings.keep_days, now);
+    for old in &pruned {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(ScheduledBackup {
+        path: path.to_string_lossy().to_string(),
+        created_at: backup.body.created_at,
+        project_count: backup.body.project_count,
+        pruned,
+    })
+}
+
+/// Run a backup if one is due and the vault is unlocked. Only a failed run is returned.
+fn run_if_due(state: &AppState) -> Result<Option<ScheduledBackupFailure>, String> {
+    let cached = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(crypto::locked::copy);
+    let Some(key) = cached else {
+        return Ok(None);
+    };
+    let guard = state.storage.lock().map_err(|e| e.to_string());
+    let outcome = guard.and_then(|guard| {
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+        let current = settings(&**storage)?;
+        if storage.is_read_only() || !is_due(&current, state.clock.now()) {
+            return Ok(None);
+        }
+        let directory = directory(&current)?.to_string_lossy().to_string();
+        Ok(run(&**storage, &*state.clock, &key)
+            .err()
+            .map(|error| ScheduledBackupFailure { directory, error }))
+    });
+    outcome
+}
+
+/// Check every quarter of an hour whether a backup of the open vault is due.
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let state = app.state::<AppState>();
+        if let Ok(Some(failure)) = run_if_due(&state) {
+            let _ = app.emit("scheduled-backup-failed", failure);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_keeps_the_newest_files() {
+        let now = Utc::now();
+        assert!(!is_due(&ScheduleSettings::default(), now));
+        let mut schedule = ScheduleSettings { frequency: Some(Frequency::Daily), ..Default::default() };
+        assert!(is_due(&schedule, now));
+        schedule.last_run_at = Some((now - chrono::Duration::hours(20)).to_rfc3339());
+        assert!(!is_due(&schedule, now));
+
+        let name = file_name("v1", now);
+        assert_eq!(written_at(&name, "v1").map(|at| at.timestamp()), Some(now.timestamp()));
+        assert!(written_at(&name, "v2").is_none());
+
+        let files: Vec<BackupFile> = (0..5)
+            .map(|age| BackupFile {
+                path: format!("b{age}"),
+                created_at: (now - chrono::Duration::days(age * 10)).to_rfc3339(),
+                size: 1,
+            })
+            .collect();
+        assert_eq!(to_prune(&files, Some(3), None, now), ["b3", "b4"]);
+        assert_eq!(to_prune(&files, None, Some(15), now), ["b2", "b3", "b4"]);
+        assert_eq!(to_prune(&files[4..], Some(0), Some(1), now), Vec::<String>::new());
+    }
+}
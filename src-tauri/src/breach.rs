@@ -0,0 +1,76 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Opt-in check of passwords against the Have I Been Pwned corpus. Only the first five hex
+//! characters of the password's SHA-1 leave the device (k-anonymity): the service answers with
+//! every suffix in that range, padded with decoys, and the match is looked up locally.
+
+use sha1::{Digest, Sha1};
+
+use crate::storage::remote::http_client;
+use crate::storage::StorageProvider;
+
+pub const SETTING_BREACH_CHECK_ENABLED: &str = "breach_check_enabled";
+const RANGE_API_URL: &str = "https://api.pwnedpasswords.com/range/";
+const PREFIX_LEN: usize = 5;
+
+pub fn is_enabled(storage: &dyn StorageProvider) -> bool {
+    matches!(
+        storage
+            .get_setting(SETTING_BREACH_CHECK_ENABLED)
+            .ok()
+            .flatten()
+            .as_deref(),
+        Some("true")
+    )
+}
+
+/// Upper-case hex SHA-1, split into the prefix that is sent and the suffix that is not.
+fn hash_parts(password: &str) -> (String, String) {
+    let digest = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = digest.split_at(PREFIX_LEN);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Find `suffix` in a range response (`SUFFIX:COUNT` per line). Padding entries have a count
+/// of zero and so never report a breach.
+fn count_in_range(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// How often the password appears in known breaches; zero if it does not.
+pub fn breach_count(password: &str) -> Result<u64, String> {
+    let (prefix, suffix) = hash_parts(password);
+    let resp = http_client()
+        .get(format!("{RANGE_API_URL}{prefix}"))
+        .header("Add-Padding", "true")
+        .send()
+        .map_err(|e| format!("Breach check failed: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("Breach check failed: HTTP {}", resp.status()));
+    }
+    let body = resp.text().map_err(|e| format!("Breach check failed: {e}"))?;
+    Ok(count_in_range(&body, &suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_prefix_is_sent_and_suffix_matched_locally() {
+        let (prefix, suffix) = hash_parts("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+
+        let body = "003D68EB55068C33ACE09247EE4C639306B:3\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n\
+                    FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF:0\r\n";
+        assert_eq!(count_in_range(body, &suffix), 9659365);
+        assert_eq!(count_in_range(body, "0000000000000000000000000000000000A"), 0);
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Change notifications for the UI. The database records every write in its change log (see
+//! `LocalStorage::init`); this watcher emits `data-changed {seq}` whenever the log moves, and
+//! the frontend calls `get_changes_since` with the last sequence it saw to refresh only what
+//! changed. Watching the log rather than hooking each command also catches writes made by sync
+//! or by another process on the same file.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+pub const MAX_CHANGES_PER_READ: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DataChanged {
+    pub seq: i64,
+}
+
+/// Latest change sequence of the open vault. `None` when no vault is open, or when the storage
+/// is busy and this poll should be skipped rather than wait.
+fn current_seq(state: &AppState) -> Option<i64> {
+    let guard = state.storage.try_lock().ok()?;
+    guard.as_ref()?.change_seq().ok()
+}
+
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let state = app.state::<AppState>();
+            let Some(seq) = current_seq(&state) else {
+                continue;
+            };
+            if last.is_some_and(|last| last != seq) {
+                let _ = app.emit("data-changed", DataChanged { seq });
+            }
+            last = Some(seq);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::StorageProvider;
+
+    fn project(id: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: String::new(),
+            encrypted_content: vec![1],
+            key_check: Vec::new(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn writes_appear_in_the_feed_in_order() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let start = storage.change_seq().unwrap();
+
+        storage.create_project(&project("a")).unwrap();
+        storage.update_project(&project("a")).unwrap();
+        storage.set_setting("theme", "dark").unwrap();
+        storage.delete_project("a").unwrap();
+
+        let feed = storage.get_changes_since(start, 2).unwrap();
+        assert!(feed.has_more && !feed.reset);
+        let feed_rest = storage.get_changes_since(feed.seq, 10).unwrap();
+        let ops: Vec<String> = feed
+            .changes
+            .iter()
+            .chain(&feed_rest.changes)
+            .map(|c| format!("{} {}", c.entity, c.op))
+            .collect();
+        assert_eq!(ops, ["project insert", "project update", "setting insert", "project delete"]);
+        assert_eq!(feed_rest.seq, storage.change_seq().unwrap());
+        assert!(!feed_rest.has_more);
+
+        assert!(storage.get_changes_since(feed_rest.seq + 5, 10).unwrap().reset);
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! A checklist on a project, kept apart from its content. Each item is one record, its text and
+//! checked state encrypted together with the project key, so ticking a box rewrites that item
+//! and nothing else. Order is a fractional position, as for projects: moving an item gives it
+//! a new key between its neighbours' and leaves the rest alone.
+//!
+//! Items sync one by one, like folders: the newer side of each item wins, and a synced item
+//! that was removed is kept as a `deleted` tombstone until the removal reaches the server. Two
+//! devices ticking different items of the same list therefore both keep their change. On the
+//! server an item refers to its project by the project's server id; items of a project that
+//! was never uploaded wait until it is.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::ChecklistItem;
+use crate::ordering;
+use crate::server_features;
+use crate::storage::remote::RemoteStorage;
+use crate::storage::{StorageError, StorageProvider};
+
+const MAX_TEXT_LEN: usize = 4096;
+const MAX_ITEMS_PER_PROJECT: usize = 500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecklistEntry {
+    pub id: String,
+    pub text: String,
+    pub checked: bool,
+    pub position: String,
+}
+
+/// What is encrypted for an item.
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    text: String,
+    checked: bool,
+}
+
+fn seal(project_key: ProjectKey<'_>, id: &str, sealed: &Sealed) -> Result<Vec<u8>, String> {
+    let plain = Zeroizing::new(serde_json::to_vec(sealed).map_err(|e| e.to_string())?);
+    match project_key {
+        ProjectKey::Vault(key) => {
+            crypto::encrypt_with_key_aad(&plain, key, &crypto::field_aad(id, "checklist-item"))
+        }
+        ProjectKey::Password(password) => crypto::encrypt(&plain, password),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn open(project_key: ProjectKey<'_>, item: &ChecklistItem) -> Result<Sealed, String> {
+    let plain = Zeroizing::new(match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &item.encrypted_item,
+            key,
+            &crypto::field_aad(&item.id, "checklist-item"),
+        )
+        .ok_or("Wrong key for this checklist")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&item.encrypted_item, password).map_err(|e| e.to_string())?
+        }
+    });
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+fn entry(item: &ChecklistItem, sealed: Sealed) -> ChecklistEntry {
+    ChecklistEntry {
+        id: item.id.clone(),
+        text: sealed.text,
+        checked: sealed.checked,
+        position: item.position.clone(),
+    }
+}
+
+/// Record a local change: a synced item needs uploading again.
+fn touch(item: &mut ChecklistItem, clock: &dyn Clock) {
+    item.updated_at = clock.now().to_rfc3339();
+    if item.sync_status == "synced" {
+        item.sync_status = "modified".to_string();
+    }
+}
+
+/// A project's items that have not been removed, by position.
+fn live(storage: &dyn StorageProvider, project_id: &str) -> Result<Vec<ChecklistItem>, String> {
+    Ok(storage
+        .list_checklist_items(project_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|i| i.sync_status != "deleted")
+        .collect())
+}
+
+fn live_item(storage: &dyn StorageProvider, id: &str) -> Result<ChecklistItem, String> {
+    storage
+        .get_checklist_item(id)
+        .map_err(|e| e.to_string())?
+        .filter(|i| i.sync_status != "deleted")
+        .ok_or_else(|| format!("Checklist item not found: {id}"))
+}
+
+fn validate(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("A checklist item needs some text".to_string());
+    }
+    if text.chars().count() > MAX_TEXT_LEN {
+        return Err(format!("Checklist items must be at most {MAX_TEXT_LEN} characters"));
+    }
+    Ok(text.to_string())
+}
+
+/// A project's checklist, in order.
+pub fn list(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<Vec<ChecklistEntry>, String> {
+    live(storage, project_id)?
+        .iter()
+        .map(|item| Ok(entry(item, open(project_key, item)?)))
+        .collect()
+}
+
+/// Add an unchecked item at the end of the checklist.
+pub fn add(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+    text: &str,
+) -> Result<ChecklistEntry, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let text = validate(text)?;
+    let existing = live(storage, project_id)?;
+    if existing.len() >= MAX_ITEMS_PER_PROJECT {
+        return Err(format!("A checklist can have at most {MAX_ITEMS_PER_PROJECT} items"));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = clock.now().to_rfc3339();
+    let sealed = Sealed { text, checked: false };
+    let item = ChecklistItem {
+        encrypted_item: seal(project_key, &id, &sealed)?,
+        id,
+        project_id: project_id.to_string(),
+        position: ordering::between(existing.last().map(|i| i.position.as_str()), None),
+        created_at: now.clone(),
+        updated_at: now,
+        sync_status: "local".to_string(),
+    };
+    storage.save_checklist_item(&item).map_err(|e| e.to_string())?;
+    Ok(entry(&item, sealed))
+}
+
+/// Flip an item's checked state.
+pub fn toggle(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    id: &str,
+) -> Result<ChecklistEntry, String> {
+    let mut item = live_item(storage, id)?;
+    let mut sealed = open(project_key, &item)?;
+    sealed.checked = !sealed.checked;
+    item.encrypted_item = seal(project_key, id, &sealed)?;
+    touch(&mut item, clock);
+    storage.save_checklist_item(&item).map_err(|e| e.to_string())?;
+    Ok(entry(&item, sealed))
+}
+
+pub fn remove(storage: &dyn StorageProvider, clock: &dyn Clock, id: &str) -> Result<(), String> {
+    let mut item = live_item(storage, id)?;
+    if item.sync_status == "local" {
+        return storage.delete_checklist_item(id).map_err(|e| e.to_string());
+    }
+    touch(&mut item, clock);
+    item.sync_status = "deleted".to_string();
+    storage.save_checklist_item(&item).map_err(|e| e.to_string())
+}
+
+/// Put a project's items in the order of `ids`. Only items that moved get a new position;
+/// items missing from `ids` keep theirs.
+pub fn reorder(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: &str,
+    ids: &[String],
+) -> Result<(), String> {
+    let mut items: HashMap<String, ChecklistItem> = live(storage, project_id)?
+        .into_iter()
+        .map(|i| (i.id.clone(), i))
+        .collect();
+    let order: Vec<(String, String)> = ids
+        .iter()
+        .filter_map(|id| items.get(id).map(|i| (id.clone(), i.position.clone())))
+        .collect();
+
+    let mut changes = ordering::plan_reorder(&order);
+    if ordering::needs_rebalance(&changes) {
+        let mut all: Vec<(String, String)> =
+            items.values().map(|i| (i.id.clone(), i.position.clone())).collect();
+        let moved: HashMap<&str, &str> =
+            changes.iter().map(|(id, key)| (id.as_str(), key.as_str())).collect();
+        for (id, position) in &mut all {
+            if let Some(key) = moved.get(id.as_str()) {
+                *position = key.to_string();
+            }
+        }
+        all.sort_by(|a, b| (&a.1, &a.0).cmp(&(&b.1, &b.0)));
+        let ids: Vec<String> = all.into_iter().map(|(id, _)| id).collect();
+        changes = ordering::rebalance(&ids);
+    }
+    for (id, position) in changes {
+        let Some(item) = items.get_mut(&id) else {
+            continue;
+        };
+        item.position = position;
+        touch(item, clock);
+        storage.save_checklist_item(item).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Re-encrypt a project's items with its new key after the project password was set, changed
+/// or removed.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: &str,
+    old_key: ProjectKey<'_>,
+    new_key: ProjectKey<'_>,
+) -> Result<(), String> {
+    if old_key.same_as(&new_key) {
+        return Ok(());
+    }
+    for mut item in live(storage, project_id)? {
+        let sealed = open(old_key, &item)?;
+        item.encrypted_item = seal(new_key, &item.id, &sealed)?;
+        touch(&mut item, clock);
+        storage.save_checklist_item(&item).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Re-encrypt every item held under the vault key with `new_key`, for a master password change.
+/// Items of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        for mut item in live(storage, &project.id)? {
+            let Ok(sealed) = open(ProjectKey::Vault(old_key), &item) else {
+                continue;
+            };
+            item.encrypted_item = seal(ProjectKey::Vault(new_key), &item.id, &sealed)?;
+            touch(&mut item, clock);
+            storage.save_checklist_item(&item).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Exchange checklist items with the server, after projects were synced so that new ones have
+/// their server ids. Does nothing on a server without checklists.
+pub fn sync(local: &dyn StorageProvider, remote: &RemoteStorage) -> Result<(), String> {
+    if !server_features::supports(remote, server_features::FEATURE_CHECKLISTS)? {
+        return Ok(());
+    }
+    let remote_items = match remote.list_checklist_items() {
+        Ok(items) => items,
+        Err(StorageError::NotFound(_)) => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let projects = local.list_projects().map_err(|e| e.to_string())?;
+    let by_server_id: HashMap<&str, &str> = projects
+        .iter()
+        .filter_map(|p| p.server_id.as_deref().map(|sid| (sid, p.id.as_str())))
+        .collect();
+    let server_ids: HashMap<&str, &str> = by_server_id.iter().map(|(s, l)| (*l, *s)).collect();
+
+    let mut local_items: HashMap<String, ChecklistItem> = HashMap::new();
+    for project in &projects {
+        for item in local.list_checklist_items(&project.id).map_err(|e| e.to_string())? {
+            local_items.insert(item.id.clone(), item);
+        }
+    }
+
+    for mut theirs in remote_items {
+        let Some(project_id) = by_server_id.get(theirs.project_id.as_str()) else {
+            continue;
+        };
+        let remote_wins = local_items
+            .get(&theirs.id)
+            .is_none_or(|ours| ours.sync_status == "synced" || theirs.updated_at > ours.updated_at);
+        if !remote_wins {
+            continue;
+        }
+        local_items.remove(&theirs.id);
+        if theirs.sync_status == "deleted" {
+            local.delete_checklist_item(&theirs.id).map_err(|e| e.to_string())?;
+        } else {
+            theirs.project_id = project_id.to_string();
+            local.save_checklist_item(&theirs).map_err(|e| e.to_string())?;
+        }
+    }
+
+    for mut ours in local_items.into_values().filter(|i| i.sync_status != "synced") {
+        let Some(server_id) = server_ids.get(ours.project_id.as_str()) else {
+            continue;
+        };
+        let mut upload = ours.clone();
+        upload.project_id = server_id.to_string();
+        remote.put_checklist_item(&upload).map_err(|e| e.to_string())?;
+        if ours.sync_status == "deleted" {
+            local.delete_checklist_item(&ours.id).map_err(|e| e.to_string())?;
+        } else {
+            ours.sync_status = "synced".to_string();
+            local.save_checklist_item(&ours).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn items_change_one_at_a_time() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage
+            .create_project(&Project {
+                id: "p".to_string(),
+                name: "p".to_string(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        let vault = ProjectKey::Vault(&key);
+        let clock = SystemClock;
+
+        let milk = add(&storage, &clock, vault, "p", "Milk").unwrap();
+        let eggs = add(&storage, &clock, vault, "p", "Eggs").unwrap();
+        let bread = add(&storage, &clock, vault, "p", " Bread ").unwrap();
+        assert!(add(&storage, &clock, vault, "p", "  ").is_err());
+
+        let mut synced = storage.get_checklist_item(&eggs.id).unwrap().unwrap();
+        synced.sync_status = "synced".to_string();
+        storage.save_checklist_item(&synced).unwrap();
+        assert!(toggle(&storage, &clock, vault, &eggs.id).unwrap().checked);
+        let toggled = storage.get_checklist_item(&eggs.id).unwrap().unwrap();
+        assert_eq!(toggled.sync_status, "modified");
+        assert_eq!(toggled.position, eggs.position);
+
+        let before = storage.get_checklist_item(&milk.id).unwrap().unwrap();
+        let order = [bread.id.clone(), milk.id.clone(), eggs.id.clone()];
+        reorder(&storage, &clock, "p", &order).unwrap();
+        let texts: Vec<String> =
+            list(&storage, vault, "p").unwrap().into_iter().map(|i| i.text).collect();
+        assert_eq!(texts, ["Bread", "Milk", "Eggs"]);
+        let after = storage.get_checklist_item(&milk.id).unwrap().unwrap();
+        assert_eq!(after.updated_at, before.updated_at);
+
+        remove(&storage, &clock, &eggs.id).unwrap();
+        remove(&storage, &clock, &milk.id).unwrap();
+        assert_eq!(list(&storage, vault, "p").unwrap().len(), 1);
+        let tombstone = storage.get_checklist_item(&eggs.id).unwrap().unwrap();
+        assert_eq!(tombstone.sync_status, "deleted");
+        assert!(storage.get_checklist_item(&milk.id).unwrap().is_none());
+    }
+}
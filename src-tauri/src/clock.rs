@@ -0,0 +1,84 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Source of the current time. The app runs on `SystemClock`, held in `AppState`; anything
+//! whose behaviour depends on time (expiry, lockouts, cooling-off delays, scheduled drills)
+//! takes a `&dyn Clock` so it can be driven by a `ManualClock` instead.
+
+#[cfg(test)]
+use std::sync::Mutex;
+#[cfg(test)]
+use std::time::Duration;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+pub trait Clock: Send + Sync {
+    /// Wall-clock time, for anything stored or compared across runs.
+    fn now(&self) -> DateTime<Utc>;
+    /// Monotonic time, for in-memory deadlines.
+    fn instant(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Both readings advance together.
+#[cfg(test)]
+pub struct ManualClock {
+    start: Instant,
+    state: Mutex<(DateTime<Utc>, Duration)>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            start: Instant::now(),
+            state: Mutex::new((now, Duration::ZERO)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.0 += chrono::Duration::from_std(by).unwrap_or(chrono::Duration::MAX);
+        state.1 += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).0
+    }
+
+    fn instant(&self) -> Instant {
+        self.start + self.state.lock().unwrap_or_else(|e| e.into_inner()).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_moves_only_when_advanced() {
+        let start = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = ManualClock::new(start);
+        let instant = clock.instant();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+        assert_eq!(clock.instant() - instant, Duration::from_secs(90));
+    }
+}
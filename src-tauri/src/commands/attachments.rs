@@ -0,0 +1,150 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::attachments::{self, AttachmentInfo, ProjectKey};
+use crate::commands::get_cached_key;
+use crate::cooling_off;
+use crate::crypto;
+use crate::keychain;
+use crate::models::Project;
+use crate::password_registry;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+/// The password of a project that has its own, checked against the project; `None` for a
+/// project under the vault key. An empty `password` falls back to the saved one.
+pub(crate) fn project_password(
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+    password: String,
+) -> Result<Option<Zeroizing<String>>, String> {
+    if project.key_check.is_empty()
+        || crypto::try_decrypt_with_key(&project.key_check, key).is_some()
+    {
+        return Ok(None);
+    }
+    let pw = Zeroizing::new(if password.is_empty() {
+        keychain::get(&password_registry::kc_key(&project.id))
+            .ok_or("No saved password for this project")?
+    } else {
+        password
+    });
+    crypto::decrypt(&project.key_check, &pw)
+        .map_err(|_| "Invalid project password".to_string())?;
+    Ok(Some(pw))
+}
+
+pub(crate) fn unlocked_project(
+    state: &AppState,
+    storage: &dyn StorageProvider,
+    project_id: &str,
+) -> Result<Project, String> {
+    cooling_off::check(storage, &*state.clock, project_id)?;
+    storage.get_project(project_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_attachments(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Vec<AttachmentInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    attachments::list(&**storage, project_key, &project_id)
+}
+
+/// Encrypt the file at `path` into the vault. The attachment takes the file's name; without a
+/// `mime_type` it is stored as `application/octet-stream`.
+#[tauri::command]
+pub fn add_attachment(
+    state: State<AppState>,
+    project_id: String,
+    path: String,
+    mime_type: Option<String>,
+    password: String,
+) -> Result<AttachmentInfo, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    let name = Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Not a file")?;
+    let mut file = File::open(&path).map_err(|e| format!("Failed to read file: {e}"))?;
+    attachments::add(
+        &**storage,
+        &*state.clock,
+        project_key,
+        &project_id,
+        &name,
+        mime_type.as_deref(),
+        &mut file,
+    )
+}
+
+/// Decrypt an attachment to `path`. The file is only put in place once all of it has been
+/// authenticated.
+#[tauri::command]
+pub fn get_attachment(
+    state: State<AppState>,
+    id: String,
+    path: String,
+    password: String,
+) -> Result<AttachmentInfo, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let attachment = storage.get_attachment(&id).map_err(|e| e.to_string())?;
+    let project = unlocked_project(&state, &**storage, &attachment.project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+
+    let partial = format!("{path}.part");
+    let written = File::create(&partial)
+        .map_err(|e| format!("Failed to write file: {e}"))
+        .and_then(|file| {
+            let mut sink = BufWriter::new(file);
+            let info = attachments::read(&**storage, project_key, &id, &mut sink)?;
+            sink.into_inner()
+                .map_err(|e| e.to_string())?
+                .sync_all()
+                .map_err(|e| e.to_string())?;
+            Ok(info)
+        });
+    match written {
+        Ok(info) => {
+            std::fs::rename(&partial, &path).map_err(|e| format!("Failed to write file: {e}"))?;
+            Ok(info)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn delete_attachment(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    attachments::delete(&**storage, &id)
+}
@@ -3,9 +3,11 @@
 
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::audit;
 use crate::AppState;
+use crate::commands::blocking;
 
 #[derive(Serialize)]
 struct AuthPayload {
@@ -50,28 +52,32 @@ fn send_auth_request(url: &str, email: String, password: String) -> Result<AuthR
 }
 
 #[tauri::command]
-pub fn server_login(
-    state: State<AppState>,
+pub async fn server_login(
+    app: AppHandle,
     server_url: String,
     email: String,
     password: String,
 ) -> Result<LoginResult, String> {
-    let url = format!("{}/api/auth/login", server_url.trim_end_matches('/'));
-    let body = send_auth_request(&url, email, password)?;
-
-    let result = LoginResult {
-        token: body.token.clone(),
-        user_id: body.user.id,
-        email: body.user.email,
-    };
-
-    let mut token_guard = state.server_token.lock().map_err(|e| e.to_string())?;
-    *token_guard = Some(body.token);
-
-    let mut url_guard = state.server_url.lock().map_err(|e| e.to_string())?;
-    *url_guard = Some(server_url);
-
-    Ok(result)
+    blocking(app, move |state| {
+        let url = format!("{}/api/auth/login", server_url.trim_end_matches('/'));
+        let body = send_auth_request(&url, email, password)?;
+
+        let result = LoginResult {
+            token: body.token.clone(),
+            user_id: body.user.id,
+            email: body.user.email,
+        };
+
+        let mut token_guard = state.server_token.lock().map_err(|e| e.to_string())?;
+        *token_guard = Some(body.token);
+
+        audit::record_event(&state, audit::EVENT_SERVER_SIGN_IN, &server_url);
+        let mut url_guard = state.server_url.lock().map_err(|e| e.to_string())?;
+        *url_guard = Some(server_url);
+
+        Ok(result)
+    })
+    .await
 }
 
 #[tauri::command]
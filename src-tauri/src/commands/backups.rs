@@ -1,11 +1,24 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
+use zeroize::{Zeroize, Zeroizing};
 
-use crate::crypto;
+use crate::audit;
+use crate::backup_archive::{
+    self, BackupEncryption, BackupPreview, BackupVerification, RestoreMode, RestoreSummary,
+};
+use crate::backup_drill::{self, DrillSettings};
+use crate::backup_schedule::{self, BackupFile, Frequency, ScheduleSettings, ScheduledBackup};
+use crate::commands::{blocking, get_cached_key};
+use crate::crypto::{self, wrap};
+use crate::insights;
 use crate::keychain;
+use crate::rehash;
+use crate::vault;
 use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,17 +36,7 @@ pub struct BackupContent {
     pub content: String,
 }
 
-fn get_cached_key(state: &AppState) -> Result<[u8; crypto::KEY_LEN], String> {
-    state
-        .cached_key
-        .lock()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "No cached key. Please unlock first.".to_string())
-}
-
-fn get_master_password(state: &AppState) -> Option<String> {
-    state.master_password.lock().ok()?.clone()
-}
+const MIN_BACKUP_PASSPHRASE_CHARS: usize = 12;
 
 fn kc_key(project_id: &str) -> String {
     format!("project-password-{}", project_id)
@@ -62,71 +65,79 @@ pub fn list_project_backups(
 }
 
 #[tauri::command]
-pub fn get_backup_content(
-    state: State<AppState>,
+pub async fn get_backup_content(
+    app: AppHandle,
     backup_id: String,
     password: String,
 ) -> Result<BackupContent, String> {
-    let key = get_cached_key(&state)?;
-    let mp = get_master_password(&state);
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let backup = storage.get_backup(&backup_id).map_err(|e| e.to_string())?;
+        let backup = storage.get_backup(&backup_id).map_err(|e| e.to_string())?;
 
-    let has_custom = if !backup.key_check.is_empty() {
-        crypto::try_decrypt_with_key(&backup.key_check, &key).is_none()
-    } else {
-        false
-    };
+        let has_custom = if !backup.key_check.is_empty() {
+            crypto::try_decrypt_with_key(&backup.key_check, &key).is_none()
+        } else {
+            false
+        };
 
-    let content_bytes = if !has_custom {
-        crypto::decrypt_auto(&backup.encrypted_content, Some(&key), mp.as_deref())
-            .map_err(|e| e.to_string())?
-    } else {
-        let pw = if password.is_empty() {
-            keychain::get(&kc_key(&backup.project_id))
-                .ok_or("No password available for decryption")?
+        let content_bytes = if !has_custom {
+            let portable = rehash::is_portable(&**storage, &backup.project_id);
+            rehash::open_backup_content(&backup, &key, portable)?
         } else {
-            password
+            let pw = if password.is_empty() {
+                keychain::get(&kc_key(&backup.project_id))
+                    .ok_or("No password available for decryption")?
+            } else {
+                password
+            };
+            crypto::decrypt_auto(&backup.encrypted_content, None, Some(&pw))
+                .map_err(|e| e.to_string())?
         };
-        crypto::decrypt_auto(&backup.encrypted_content, None, Some(&pw))
-            .map_err(|e| e.to_string())?
-    };
 
-    Ok(BackupContent {
-        name: backup.name,
-        content: String::from_utf8(content_bytes).map_err(|e| e.to_string())?,
+        Ok(BackupContent {
+            name: backup.name,
+            content: String::from_utf8(content_bytes).map_err(|e| e.to_string())?,
+        })
     })
+    .await
 }
 
 #[tauri::command]
-pub fn restore_backup(
-    state: State<AppState>,
+pub async fn restore_backup(
+    app: AppHandle,
     backup_id: String,
     password: String,
 ) -> Result<(), String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    blocking(app, move |state| {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let backup = storage.get_backup(&backup_id).map_err(|e| e.to_string())?;
-    let mut project = storage.get_project(&backup.project_id).map_err(|e| e.to_string())?;
+        let backup = storage.get_backup(&backup_id).map_err(|e| e.to_string())?;
+        let mut project = storage.get_project(&backup.project_id).map_err(|e| e.to_string())?;
 
-    let _ = password;
+        let _ = password;
 
-    project.name = backup.name;
-    project.encrypted_content = backup.encrypted_content;
-    project.key_check = backup.key_check;
-    project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.name = backup.name;
+        project.encrypted_content = backup.encrypted_content;
+        project.key_check = backup.key_check;
+        project.updated_at = state.clock.now().to_rfc3339();
 
-    if project.sync_status == "synced" {
-        project.sync_status = "modified".to_string();
-    }
+        if project.sync_status == "synced" {
+            project.sync_status = "modified".to_string();
+        }
 
-    storage
-        .update_project(&project)
-        .map_err(|e| e.to_string())?;
-    Ok(())
+        storage
+            .update_project(&project)
+            .map_err(|e| e.to_string())?;
+        if let Ok(key) = get_cached_key(&state) {
+            insights::record(&**storage, &*state.clock, &key, insights::EVENT_BACKUP_RESTORED);
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -140,3 +151,272 @@ pub fn delete_backup_cmd(
     storage.delete_backup(&backup_id).map_err(|e| e.to_string())?;
     Ok(())
 }
+
+#[derive(Debug, Serialize)]
+pub struct VaultBackupResult {
+    pub path: String,
+    pub created_at: String,
+    pub project_count: usize,
+}
+
+/// Write all projects and their history to a signed, encrypted backup file. With an
+/// `encryption` other than the vault key, the file also opens with the backup passphrase or
+/// the recovery code.
+#[tauri::command]
+pub async fn export_vault_backup(
+    app: AppHandle,
+    path: String,
+    encryption: Option<BackupEncryption>,
+) -> Result<VaultBackupResult, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let backup = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            backup_archive::seal(&**storage, &*state.clock, &key, encryption.unwrap_or_default())?
+        };
+        let json = serde_json::to_vec_pretty(&backup).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write backup: {e}"))?;
+        audit::record_event(&state, audit::EVENT_EXPORT, "vault_backup");
+        if let Some(storage) = state.storage.lock().map_err(|e| e.to_string())?.as_ref() {
+            let _ = backup_drill::record_export(&**storage, &path);
+        }
+
+        Ok(VaultBackupResult {
+            path,
+            created_at: backup.body.created_at,
+            project_count: backup.body.project_count,
+        })
+    })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemovableExport {
+    pub path: String,
+    pub created_at: String,
+    pub project_count: usize,
+    pub size: u64,
+}
+
+/// Like `export_vault_backup`, for a removable drive: the file is flushed to the device and read
+/// back before this returns, so the drive can be ejected once it succeeds. A drive that cut the
+/// file short or returned other bytes fails with `backup_truncated` or `backup_write_mismatch`.
+#[tauri::command]
+pub async fn export_to_removable(
+    app: AppHandle,
+    path: String,
+    encryption: Option<BackupEncryption>,
+) -> Result<RemovableExport, String> {
+    blocking(app, move |state| {
+        let mut key = get_cached_key(&state)?;
+        let result = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            let sealed =
+                backup_archive::seal(&**storage, &*state.clock, &key, encryption.unwrap_or_default());
+            sealed.and_then(|backup| {
+                let size = backup_archive::write_verified(&**storage, &key, &backup, Path::new(&path))?;
+                let _ = backup_drill::record_export(&**storage, &path);
+                Ok((backup, size))
+            })
+        };
+        key.zeroize();
+        let (backup, size) = result?;
+        let export = RemovableExport {
+            path,
+            created_at: backup.body.created_at,
+            project_count: backup.body.project_count,
+            size,
+        };
+        audit::record_event(&state, audit::EVENT_EXPORT, "vault_backup");
+        Ok(export)
+    })
+    .await
+}
+
+/// What restoring a file written by `export_vault_backup` would do, without changing anything:
+/// where and when it was made, how many projects it holds and how they compare to the vault's.
+#[tauri::command]
+pub async fn preview_backup(app: AppHandle, path: String) -> Result<BackupPreview, String> {
+    blocking(app, move |state| {
+        let mut key = get_cached_key(&state)?;
+        let data = std::fs::read(&path).map_err(|e| format!("Failed to read backup: {e}"));
+        let result = data.and_then(|data| {
+            let backup = backup_archive::parse(&data)?;
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            backup_archive::preview(&**storage, &key, &backup)
+        });
+        key.zeroize();
+        result
+    })
+    .await
+}
+
+/// Restore a file written by `export_vault_backup` in the given `mode`. Fails with
+/// `backup_corrupt`, `backup_signature_invalid` or `backup_from_other_vault` before anything is
+/// changed.
+#[tauri::command]
+pub async fn restore_vault_backup(
+    app: AppHandle,
+    path: String,
+    mode: RestoreMode,
+) -> Result<RestoreSummary, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let data = std::fs::read(&path).map_err(|e| format!("Failed to read backup: {e}"))?;
+        let backup = backup_archive::parse(&data)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let summary = backup_archive::restore(&**storage, &*state.clock, &key, &backup, mode)?;
+        insights::record(&**storage, &*state.clock, &key, insights::EVENT_BACKUP_RESTORED);
+        Ok(summary)
+    })
+    .await
+}
+
+/// Check a backup file without restoring it: signature, manifest and a trial decryption of a
+/// sample of projects. With an empty `passphrase` the unlocked vault's key is used; otherwise
+/// the master password given, which also works while the vault is locked.
+#[tauri::command]
+pub async fn verify_backup(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<BackupVerification, String> {
+    blocking(app, move |state| {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let key = if passphrase.is_empty() {
+            get_cached_key(&state)?
+        } else {
+            let secret = vault::unlock_secret(&**storage, &passphrase)?;
+            Zeroizing::new(vault::peek_data_key(&**storage, &secret)?.ok_or("Invalid master password")?)
+        };
+        backup_drill::verify_file(&**storage, &key, &path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn get_backup_drill(state: State<AppState>) -> Result<DrillSettings, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    backup_drill::settings(&**storage)
+}
+
+/// Turn the weekly verification of the latest backup on or off.
+#[tauri::command]
+pub fn set_backup_drill(state: State<AppState>, enabled: bool) -> Result<DrillSettings, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    backup_drill::set_enabled(&**storage, enabled)
+}
+
+/// Set up scheduled backups; a `frequency` of `None` turns them off. Retention limits apply
+/// from the next run.
+#[tauri::command]
+pub fn configure_backups(
+    state: State<AppState>,
+    frequency: Option<Frequency>,
+    directory: Option<String>,
+    keep_count: Option<usize>,
+    keep_days: Option<u32>,
+    encryption: Option<BackupEncryption>,
+) -> Result<ScheduleSettings, String> {
+    if keep_count == Some(0) || keep_days == Some(0) {
+        return Err("Retention limits must keep at least one backup".to_string());
+    }
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let mut current = backup_schedule::settings(&**storage)?;
+    current.frequency = frequency;
+    current.directory = directory.filter(|d| !d.trim().is_empty());
+    current.keep_count = keep_count;
+    current.keep_days = keep_days;
+    current.encryption = encryption.unwrap_or_default();
+    backup_schedule::save(&**storage, &current)?;
+    Ok(current)
+}
+
+/// Set the backup passphrase: the vault key is wrapped under it, and backups written with
+/// passphrase encryption carry a copy. Backups written before keep the passphrase they had.
+#[tauri::command]
+pub async fn set_backup_passphrase(app: AppHandle, passphrase: String) -> Result<String, String> {
+    blocking(app, move |state| {
+        if passphrase.chars().count() < MIN_BACKUP_PASSPHRASE_CHARS {
+            return Err(format!(
+                "The backup passphrase must have at least {MIN_BACKUP_PASSPHRASE_CHARS} characters"
+            ));
+        }
+        let mut key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let slot = vault::kdf_params(&**storage).and_then(|params| {
+            wrap::wrap_with_secret(&key, &passphrase, &params).map_err(|e| e.to_string())
+        });
+        key.zeroize();
+        let created_at = state.clock.now().to_rfc3339();
+        vault::set_backup_passphrase_slot(
+            &**storage,
+            Some(&vault::RecoverySlot { slot: slot?, created_at: created_at.clone() }),
+        )?;
+        Ok(created_at)
+    })
+    .await
+}
+
+/// Forget the backup passphrase. Scheduled backups that use it fail until a new one is set.
+#[tauri::command]
+pub fn remove_backup_passphrase(state: State<AppState>) -> Result<(), String> {
+    get_cached_key(&state)?.zeroize();
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    vault::set_backup_passphrase_slot(&**storage, None)
+}
+
+/// When the current backup passphrase was set, if there is one.
+#[tauri::command]
+pub fn get_backup_passphrase_created_at(state: State<AppState>) -> Result<Option<String>, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(vault::backup_passphrase_slot(&**storage)?.map(|slot| slot.created_at))
+}
+
+#[tauri::command]
+pub fn get_backup_schedule(state: State<AppState>) -> Result<ScheduleSettings, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    backup_schedule::settings(&**storage)
+}
+
+/// Write a scheduled-style backup right away, whether or not one is due.
+#[tauri::command]
+pub async fn run_backup_now(app: AppHandle) -> Result<ScheduledBackup, String> {
+    blocking(app, move |state| {
+        let mut key = get_cached_key(&state)?;
+        let result = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            backup_schedule::run(&**storage, &*state.clock, &key)
+        };
+        key.zeroize();
+        let backup = result?;
+        audit::record_event(&state, audit::EVENT_EXPORT, "scheduled_backup");
+        Ok(backup)
+    })
+    .await
+}
+
+/// The open vault's files in the backup directory, newest first.
+#[tauri::command]
+pub fn list_backups(state: State<AppState>) -> Result<Vec<BackupFile>, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let Some(vault_id) = vault::vault_id(&**storage)? else {
+        return Ok(vec![]);
+    };
+    let dir = backup_schedule::directory(&backup_schedule::settings(&**storage)?)?;
+    backup_schedule::list(&dir, &vault_id)
+}
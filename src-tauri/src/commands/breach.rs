@@ -0,0 +1,49 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::{AppHandle, State};
+use zeroize::Zeroize;
+
+use crate::breach;
+use crate::AppState;
+use crate::commands::blocking;
+
+#[tauri::command]
+pub fn get_breach_check_enabled(state: State<AppState>) -> Result<bool, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(breach::is_enabled(&**storage))
+}
+
+#[tauri::command]
+pub fn set_breach_check_enabled(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    storage
+        .set_setting(
+            breach::SETTING_BREACH_CHECK_ENABLED,
+            if enabled { "true" } else { "false" },
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Number of times `password` appears in known breaches. Fails with `breach_check_disabled`
+/// unless the user turned the check on.
+#[tauri::command]
+pub async fn check_password_breach(app: AppHandle, mut password: String) -> Result<u64, String> {
+    blocking(app, move |state| {
+        let enabled = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            breach::is_enabled(&**storage)
+        };
+        let result = if enabled {
+            breach::breach_count(&password)
+        } else {
+            Err("breach_check_disabled".to_string())
+        };
+        password.zeroize();
+        result
+    })
+    .await
+}
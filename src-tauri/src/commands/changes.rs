@@ -0,0 +1,19 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+
+use crate::change_feed::MAX_CHANGES_PER_READ;
+use crate::models::ChangeFeed;
+use crate::AppState;
+
+/// What changed in the vault after `seq`, as announced by `data-changed`. Call again from the
+/// returned `seq` while `has_more` is set; on `reset`, reload everything.
+#[tauri::command]
+pub fn get_changes_since(state: State<AppState>, seq: i64) -> Result<ChangeFeed, String> {
+    let guard = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = guard.as_ref().ok_or("Database not initialized")?;
+    storage
+        .get_changes_since(seq, MAX_CHANGES_PER_READ)
+        .map_err(|e| e.to_string())
+}
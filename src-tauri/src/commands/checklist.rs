@@ -0,0 +1,82 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+
+use crate::attachments::ProjectKey;
+use crate::checklist::{self, ChecklistEntry};
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::get_cached_key;
+use crate::AppState;
+
+#[tauri::command]
+pub fn list_checklist(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Vec<ChecklistEntry>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    checklist::list(&**storage, project_key, &project_id)
+}
+
+/// Add an unchecked item at the end of the project's checklist.
+#[tauri::command]
+pub fn add_checklist_item(
+    state: State<AppState>,
+    project_id: String,
+    text: String,
+    password: String,
+) -> Result<ChecklistEntry, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    checklist::add(&**storage, &*state.clock, project_key, &project_id, &text)
+}
+
+#[tauri::command]
+pub fn toggle_checklist_item(
+    state: State<AppState>,
+    project_id: String,
+    id: String,
+    password: String,
+) -> Result<ChecklistEntry, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    checklist::toggle(&**storage, &*state.clock, project_key, &id)
+}
+
+#[tauri::command]
+pub fn remove_checklist_item(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    checklist::remove(&**storage, &*state.clock, &id)
+}
+
+/// `ids` is the new order of the project's checklist.
+#[tauri::command]
+pub fn reorder_checklist(
+    state: State<AppState>,
+    project_id: String,
+    ids: Vec<String>,
+) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    checklist::reorder(&**storage, &*state.clock, &project_id, &ids)
+}
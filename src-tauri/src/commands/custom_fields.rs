@@ -0,0 +1,85 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::get_cached_key;
+use crate::custom_fields::{self, CustomField};
+use crate::AppState;
+
+#[tauri::command]
+pub fn list_custom_fields(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Vec<CustomField>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    custom_fields::list(&**storage, project_key, &project_id)
+}
+
+#[tauri::command]
+pub fn add_custom_field(
+    state: State<AppState>,
+    project_id: String,
+    label: String,
+    value: String,
+    is_secret: bool,
+    password: String,
+) -> Result<CustomField, String> {
+    let key = get_cached_key(&state)?;
+    let value = Zeroizing::new(value);
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    custom_fields::add(
+        &**storage,
+        &*state.clock,
+        project_key,
+        &project_id,
+        &label,
+        &value,
+        is_secret,
+    )
+}
+
+/// Replace one field's label, value and secret flag; the project content is not touched.
+#[tauri::command]
+pub fn update_custom_field(
+    state: State<AppState>,
+    id: String,
+    label: String,
+    value: String,
+    is_secret: bool,
+    password: String,
+) -> Result<CustomField, String> {
+    let key = get_cached_key(&state)?;
+    let value = Zeroizing::new(value);
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let field = storage.get_project_field(&id).map_err(|e| e.to_string())?;
+    let project = unlocked_project(&state, &**storage, &field.project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    custom_fields::update(&**storage, &*state.clock, project_key, &id, &label, &value, is_secret)
+}
+
+#[tauri::command]
+pub fn remove_custom_field(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    custom_fields::remove(&**storage, &id)
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::AppHandle;
+
+use crate::commands::blocking;
+use crate::commands::sync::build_remote;
+use crate::device;
+use crate::server_features::{self, FEATURE_DEVICES};
+use crate::storage::remote::{RemoteDevice, RemoteStorage};
+use crate::wipe;
+use crate::AppState;
+
+pub const DEVICE_WIPED: &str = "device_wiped";
+
+/// Check in with the server before syncing. If another session flagged this device for
+/// wiping, erase all local data, report completion and fail with `DEVICE_WIPED`.
+///
+/// Must be called without holding the storage lock. Servers without device management are
+/// skipped.
+pub(crate) fn enforce_remote_wipe(state: &AppState, remote: &RemoteStorage) -> Result<(), String> {
+    if !server_features::supports(remote, FEATURE_DEVICES)? {
+        return Ok(());
+    }
+    let device_id = device::device_id()?;
+    let checkin = remote
+        .device_checkin(&device_id, &device::device_name())
+        .map_err(|e| e.to_string())?;
+    if !checkin.wipe_requested {
+        return Ok(());
+    }
+
+    wipe::wipe_local_data(state)?;
+    let _ = remote.confirm_device_wiped(&device_id);
+    Err(DEVICE_WIPED.to_string())
+}
+
+#[tauri::command]
+pub fn get_device_id() -> Result<String, String> {
+    device::device_id()
+}
+
+#[tauri::command]
+pub async fn list_devices(app: AppHandle) -> Result<Vec<RemoteDevice>, String> {
+    blocking(app, move |state| {
+        let remote = build_remote(&state)?;
+        server_features::require(&remote, FEATURE_DEVICES)?;
+        remote
+            .list_devices()
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn request_device_wipe(app: AppHandle, device_id: String) -> Result<(), String> {
+    blocking(app, move |state| {
+        if device_id == device::device_id()? {
+            return Err("Cannot wipe the current device remotely".to_string());
+        }
+        let remote = build_remote(&state)?;
+        server_features::require(&remote, FEATURE_DEVICES)?;
+        remote
+            .request_device_wipe(&device_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
@@ -0,0 +1,56 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+
+use crate::attachments::ProjectKey;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::get_cached_key;
+use crate::drafts::{self, RecoveredDraft};
+use crate::AppState;
+
+/// Keep the edit in progress, called by the editor every few seconds. `base_revision` is the
+/// revision the editor loaded. Saving the project drops the draft.
+#[tauri::command]
+pub fn save_draft(
+    state: State<AppState>,
+    project_id: String,
+    base_revision: i64,
+    name: String,
+    content: String,
+    password: String,
+) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    drafts::save(&**storage, &*state.clock, project_key, &project, base_revision, &name, &content)
+}
+
+/// The draft left by an edit that was never saved, to offer when the project is opened.
+#[tauri::command]
+pub fn get_draft(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Option<RecoveredDraft>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    drafts::get(&**storage, project_key, &project)
+}
+
+#[tauri::command]
+pub fn discard_draft(state: State<AppState>, project_id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    drafts::discard(&**storage, &project_id)
+}
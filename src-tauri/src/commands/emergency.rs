@@ -0,0 +1,179 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::Serialize;
+use tauri::AppHandle;
+use zeroize::Zeroize;
+
+use crate::commands::{blocking, get_cached_key};
+use crate::commands::sync::build_remote;
+use crate::crypto::sealed;
+use crate::device;
+use crate::emergency::{self, EmergencyContact};
+use crate::server_features::{self, FEATURE_EMERGENCY_ACCESS};
+use crate::storage::remote::{RemoteEmergencyGrant, RemoteStorage};
+use crate::AppState;
+
+fn emergency_remote(state: &AppState) -> Result<RemoteStorage, String> {
+    let remote = build_remote(state)?;
+    server_features::require(&remote, FEATURE_EMERGENCY_ACCESS)?;
+    Ok(remote)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyContactStatus {
+    pub contact: EmergencyContact,
+    /// The server's view of the grant; `None` if it no longer exists there.
+    pub grant: Option<RemoteEmergencyGrant>,
+}
+
+/// This device's public key, to be given to someone who wants to name us as their contact.
+#[tauri::command]
+pub fn get_emergency_public_key() -> Result<String, String> {
+    Ok(B64.encode(device::exchange_public_key()?))
+}
+
+#[tauri::command]
+pub async fn add_emergency_contact(
+    app: AppHandle,
+    grantee_email: String,
+    public_key: String,
+    wait_secs: u64,
+) -> Result<EmergencyContact, String> {
+    blocking(app, move |state| {
+        emergency::validate_wait(wait_secs)?;
+        let grantee_key = emergency::parse_public_key(&public_key)?;
+        let key = get_cached_key(&state)?;
+        let wrapped = sealed::seal(&key[..], &grantee_key).map_err(|e| e.to_string())?;
+
+        let grant = emergency_remote(&state)?
+            .create_emergency_grant(grantee_email.trim(), &grantee_key, &wrapped, wait_secs)
+            .map_err(|e| e.to_string())?;
+        let contact = EmergencyContact {
+            grant_id: grant.id,
+            grantee_email: grant.grantee_email,
+            public_key: B64.encode(grantee_key),
+            wait_secs: grant.wait_secs,
+            created_at: grant.created_at,
+        };
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        emergency::add_contact(&**storage, &key, contact.clone())?;
+        Ok(contact)
+    })
+    .await
+}
+
+/// The configured contacts together with any pending access request.
+#[tauri::command]
+pub async fn list_emergency_contacts(
+    app: AppHandle,
+) -> Result<Vec<EmergencyContactStatus>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let contacts = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            emergency::contacts(&**storage, &key)?
+        };
+        let grants = emergency_remote(&state)?
+            .list_emergency_grants()
+            .map_err(|e| e.to_string())?;
+        Ok(contacts
+            .into_iter()
+            .map(|contact| EmergencyContactStatus {
+                grant: grants.iter().find(|g| g.id == contact.grant_id).cloned(),
+                contact,
+            })
+            .collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn deny_emergency_request(app: AppHandle, grant_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        emergency_remote(&state)?
+            .deny_emergency_request(grant_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_emergency_contact(app: AppHandle, grant_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        emergency_remote(&state)?
+            .delete_emergency_grant(grant_id)
+            .map_err(|e| e.to_string())?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        emergency::remove_contact(&**storage, &key, grant_id)
+    })
+    .await
+}
+
+/// Grants that name the signed-in user as the trusted contact.
+#[tauri::command]
+pub async fn list_incoming_emergency_access(
+    app: AppHandle,
+) -> Result<Vec<RemoteEmergencyGrant>, String> {
+    blocking(app, move |state| {
+        emergency_remote(&state)?
+            .list_incoming_emergency_grants()
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Start the owner's waiting period.
+#[tauri::command]
+pub async fn request_emergency_access(
+    app: AppHandle,
+    grant_id: i64,
+) -> Result<RemoteEmergencyGrant, String> {
+    blocking(app, move |state| {
+        emergency_remote(&state)?
+            .request_emergency_access(grant_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Fetch a released key and keep it, so the owner's projects open in this vault.
+#[tauri::command]
+pub async fn claim_emergency_access(app: AppHandle, grant_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let remote = emergency_remote(&state)?;
+        let grant = remote
+            .list_incoming_emergency_grants()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|g| g.id == grant_id)
+            .ok_or("Emergency access grant not found")?;
+        if grant.released_at.is_none() {
+            return Err("emergency_access_not_released".to_string());
+        }
+        let sealed_key = remote
+            .fetch_emergency_key(grant_id)
+            .map_err(|e| e.to_string())?;
+        let mut exchange_key = device::exchange_key()?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let result = emergency::store_released_key(
+            &**storage,
+            &key,
+            &exchange_key,
+            grant_id,
+            &grant.owner_email,
+            &sealed_key,
+        );
+        exchange_key.zeroize();
+        result
+    })
+    .await
+}
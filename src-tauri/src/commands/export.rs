@@ -0,0 +1,176 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use zeroize::Zeroizing;
+
+use crate::audit;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::{blocking, get_cached_key};
+use crate::compliance::{self, ComplianceArchive};
+use crate::crypto;
+use crate::device;
+use crate::insights;
+use crate::models::Project;
+use crate::plain_export::{self, ExportFormat};
+use crate::rehash;
+use crate::trash;
+
+const SETTING_TSA_URL: &str = "compliance_tsa_url";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComplianceExportResult {
+    pub path: String,
+    pub created_at: String,
+    pub project_count: usize,
+    pub state_digest: String,
+    pub public_key: String,
+    pub timestamped: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComplianceVerifyResult {
+    pub created_at: String,
+    pub project_count: usize,
+    pub state_digest: String,
+    pub signed_by_this_device: bool,
+    pub timestamp_authority: Option<String>,
+    pub payload_verified: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectExportResult {
+    pub path: String,
+    pub bytes: usize,
+}
+
+#[tauri::command]
+pub async fn export_compliance_archive(
+    app: AppHandle,
+    path: String,
+) -> Result<ComplianceExportResult, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+
+        let (projects, tsa_url): (Vec<Project>, Option<String>) = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            let trashed = trash::ids(&**storage)?;
+            let projects = storage
+                .list_projects()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|p| p.sync_status != "deleted" && !trashed.contains(&p.id))
+                .collect();
+            let tsa_url = storage
+                .get_setting(SETTING_TSA_URL)
+                .map_err(|e| e.to_string())?
+                .filter(|u| !u.trim().is_empty());
+            (projects, tsa_url)
+        };
+
+        let signing_key = device::signing_key()?;
+        let archive = compliance::seal(&*state.clock, &projects, &key, tsa_url.as_deref(), &signing_key)?;
+
+        let json = serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write archive: {e}"))?;
+
+        insights::record_event(&state, insights::EVENT_EXPORT);
+        audit::record_event(&state, audit::EVENT_EXPORT, "compliance_archive");
+
+        Ok(ComplianceExportResult {
+            path,
+            created_at: archive.body.created_at,
+            project_count: archive.body.project_count,
+            state_digest: archive.body.state_digest,
+            public_key: archive.public_key,
+            timestamped: archive.body.timestamp.token.is_some(),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn verify_compliance_archive(
+    app: AppHandle,
+    path: String,
+) -> Result<ComplianceVerifyResult, String> {
+    blocking(app, move |state| {
+        let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read archive: {e}"))?;
+        let archive: ComplianceArchive =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid archive: {e}"))?;
+
+        compliance::verify(&archive)?;
+
+        let signed_by_this_device = device::public_key()
+            .map(|pk| B64.encode(pk) == archive.public_key)
+            .unwrap_or(false);
+
+        let payload_verified = match get_cached_key(&state) {
+            Ok(key) => compliance::open(&archive, &key).is_ok(),
+            Err(_) => false,
+        };
+
+        Ok(ComplianceVerifyResult {
+            created_at: archive.body.created_at,
+            project_count: archive.body.project_count,
+            state_digest: archive.body.state_digest,
+            signed_by_this_device,
+            timestamp_authority: archive.body.timestamp.authority,
+            payload_verified,
+        })
+    })
+    .await
+}
+
+/// Write one project to `path` as an unencrypted Markdown or text file (see `plain_export`).
+/// `confirm_unencrypted` must be set, acknowledging that anyone who can read the file can read
+/// the note. A path without an extension gets the format's.
+#[tauri::command]
+pub async fn export_project(
+    app: AppHandle,
+    id: String,
+    path: String,
+    format: ExportFormat,
+    password: String,
+    confirm_unencrypted: bool,
+) -> Result<ProjectExportResult, String> {
+    blocking(app, move |state| {
+        if !confirm_unencrypted {
+            return Err("The exported file is not encrypted; confirm to export it".to_string());
+        }
+        let key = get_cached_key(&state)?;
+        let rendered = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+            let project = unlocked_project(&state, &**storage, &id)?;
+            let pw = project_password(&project, &key, password)?;
+            let content = Zeroizing::new(match &pw {
+                Some(pw) => crypto::decrypt_auto(&project.encrypted_content, None, Some(pw))
+                    .map_err(|e| e.to_string())?,
+                None => rehash::open_project_content(&project, &key)?,
+            });
+            let content = std::str::from_utf8(&content).map_err(|e| e.to_string())?;
+            plain_export::render(&project.name, content, format)
+        };
+
+        let mut path = std::path::PathBuf::from(path);
+        if path.extension().is_none() {
+            path.set_extension(format.extension());
+        }
+        std::fs::write(&path, rendered.as_bytes())
+            .map_err(|e| format!("Failed to write file: {e}"))?;
+
+        insights::record_event(&state, insights::EVENT_EXPORT);
+        audit::record_event(&state, audit::EVENT_EXPORT, "project");
+
+        Ok(ProjectExportResult {
+            path: path.to_string_lossy().into_owned(),
+            bytes: rendered.len(),
+        })
+    })
+    .await
+}
@@ -0,0 +1,58 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+
+use crate::commands::get_cached_key;
+use crate::folders::{self, FolderInfo};
+use crate::AppState;
+
+#[tauri::command]
+pub fn list_folders(state: State<AppState>) -> Result<Vec<FolderInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    folders::list(&**storage, &key)
+}
+
+/// A new folder under `parent_id`, or at the top level.
+#[tauri::command]
+pub fn create_folder(
+    state: State<AppState>,
+    name: String,
+    parent_id: Option<String>,
+) -> Result<FolderInfo, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    folders::create(&**storage, &*state.clock, &key, &name, parent_id.as_deref())
+}
+
+#[tauri::command]
+pub fn rename_folder(state: State<AppState>, id: String, name: String) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    folders::rename(&**storage, &*state.clock, &key, &id, &name)
+}
+
+#[tauri::command]
+pub fn move_folder(
+    state: State<AppState>,
+    id: String,
+    parent_id: Option<String>,
+) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    folders::move_to(&**storage, &*state.clock, &id, parent_id.as_deref())
+}
+
+/// Delete a folder; its projects and subfolders move up to its parent.
+#[tauri::command]
+pub fn delete_folder(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    folders::delete(&**storage, &*state.clock, &id)
+}
@@ -0,0 +1,328 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::clock::Clock;
+use crate::commands::{blocking, get_cached_key};
+use crate::commands::projects;
+use crate::crypto;
+use crate::importers::{self, browser, directory, ImportedItem, ItemError};
+use crate::insights;
+use crate::pipeline::{self, PipelineOptions, Throughput};
+use crate::rehash;
+use crate::storage::StorageProvider;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImporterInfo {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportPreviewItem {
+    pub index: usize,
+    pub title: String,
+    pub folder: Option<String>,
+    pub field_count: usize,
+    pub attachment_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub importer: String,
+    pub dry_run: bool,
+    pub total: usize,
+    pub imported: usize,
+    pub items: Vec<ImportPreviewItem>,
+    pub errors: Vec<ItemError>,
+    /// Absent for a dry run.
+    pub throughput: Option<Throughput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowserImportReport {
+    pub imported: usize,
+    /// Entries skipped because a login with the same URL and username already exists.
+    pub duplicates: usize,
+    /// Tag added to every imported entry so it can be found and reviewed.
+    pub review_tag: String,
+    pub errors: Vec<ItemError>,
+    pub throughput: Throughput,
+}
+
+/// What became of one file of an `import_directory`.
+#[derive(Debug, Serialize)]
+pub struct ImportedFile {
+    /// Relative to the imported folder.
+    pub path: String,
+    pub name: String,
+    /// The new project, when the file was imported.
+    pub project_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryImportReport {
+    pub imported: usize,
+    pub files: Vec<ImportedFile>,
+}
+
+/// Create a project for each `(index, item)` through the import pipeline: entries are
+/// encrypted on a worker thread and stored here in batches, each batch in one transaction.
+/// Failures are added to `errors`; when a batch cannot be stored, every entry in it fails.
+fn insert_items(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    bind: bool,
+    items: impl Iterator<Item = (usize, ImportedItem)> + Send,
+    options: PipelineOptions,
+    errors: &mut Vec<ItemError>,
+) -> (usize, Throughput) {
+    let mut imported = 0;
+    let encrypt = |(index, item): (usize, ImportedItem)| {
+        let project = projects::master_key_project(clock, key, bind, &item.title, &item.to_content());
+        (index, item.title, project)
+    };
+    let Ok(throughput) = pipeline::run(items, options, encrypt, |batch| {
+        let mut encrypted = Vec::new();
+        let mut projects = Vec::new();
+        for (index, title, project) in batch {
+            match project {
+                Ok(project) => {
+                    encrypted.push((index, title));
+                    projects.push(project);
+                }
+                Err(message) => errors.push(ItemError { index, title: Some(title), message }),
+            }
+        }
+        match storage.create_projects_batch(&projects) {
+            Ok(_) => imported += projects.len(),
+            Err(e) => errors.extend(encrypted.into_iter().map(|(index, title)| ItemError {
+                index,
+                title: Some(title),
+                message: e.to_string(),
+            })),
+        }
+        Ok::<_, std::convert::Infallible>(())
+    });
+    (imported, throughput)
+}
+
+/// Logins already in the vault, as `browser::login_key`s. Custom-password projects cannot be
+/// read here and are not considered.
+fn existing_logins(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<HashSet<(String, String)>, String> {
+    Ok(storage
+        .list_projects()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|p| p.sync_status != "deleted")
+        .filter_map(|p| rehash::open_project_content(p, key).ok())
+        .filter_map(|content| browser::content_login_key(&String::from_utf8_lossy(&content)))
+        .collect())
+}
+
+#[tauri::command]
+pub fn list_importers() -> Vec<ImporterInfo> {
+    importers::registry()
+        .iter()
+        .map(|c| ImporterInfo {
+            id: c.id().to_string(),
+            name: c.name().to_string(),
+            extensions: c.extensions().iter().map(|e| e.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Convert an export file with the given importer. With `dry_run` nothing is written and the
+/// report shows what would be imported; otherwise every converted entry becomes a project,
+/// `batch_size` entries at a time.
+#[tauri::command]
+pub async fn import_file(
+    app: AppHandle,
+    importer: String,
+    path: String,
+    dry_run: bool,
+    batch_size: Option<usize>,
+) -> Result<ImportReport, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let options = PipelineOptions::with_batch_size(batch_size)?;
+        let converter =
+            importers::find(&importer).ok_or_else(|| format!("Unknown importer: {importer}"))?;
+        let data = std::fs::read(&path).map_err(|e| format!("Failed to read file: {e}"))?;
+        let parsed = converter.parse(&data).map_err(|e| e.to_string())?;
+
+        let total = parsed.items.len() + parsed.errors.len();
+        let items: Vec<ImportPreviewItem> = parsed
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| ImportPreviewItem {
+                index,
+                title: item.title.clone(),
+                folder: item.folder.clone(),
+                field_count: item.fields.len(),
+                attachment_count: item.attachments.len(),
+            })
+            .collect();
+        let mut errors = parsed.errors;
+
+        if dry_run {
+            return Ok(ImportReport {
+                importer,
+                dry_run,
+                total,
+                imported: 0,
+                items,
+                errors,
+                throughput: None,
+            });
+        }
+
+        let bind = projects::binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let (imported, throughput) = insert_items(
+            &**storage,
+            &*state.clock,
+            &key,
+            bind,
+            parsed.items.into_iter().enumerate(),
+            options,
+            &mut errors,
+        );
+        if imported > 0 {
+            insights::record(&**storage, &*state.clock, &key, insights::EVENT_IMPORT);
+        }
+
+        Ok(ImportReport {
+            importer,
+            dry_run,
+            total,
+            imported,
+            items,
+            errors,
+            throughput: Some(throughput),
+        })
+    })
+    .await
+}
+
+/// Import a Chrome, Edge or Firefox saved-passwords CSV. Entries whose URL and username match
+/// an existing login (or an earlier row of the same file) are skipped; the rest are tagged
+/// with `browser::REVIEW_TAG`.
+#[tauri::command]
+pub async fn import_browser_passwords(
+    app: AppHandle,
+    browser: String,
+    csv_path: String,
+    batch_size: Option<usize>,
+) -> Result<BrowserImportReport, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let options = PipelineOptions::with_batch_size(batch_size)?;
+        let converter = match browser.as_str() {
+            "chrome" | "edge" | "firefox" => importers::find(&browser),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Unsupported browser: {browser}"))?;
+        let data = std::fs::read(&csv_path).map_err(|e| format!("Failed to read file: {e}"))?;
+        let parsed = converter.parse(&data).map_err(|e| e.to_string())?;
+        let mut errors = parsed.errors;
+
+        let bind = projects::binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let mut seen = existing_logins(&**storage, &key)?;
+        let mut duplicates = 0;
+        let fresh: Vec<(usize, ImportedItem)> = parsed
+            .items
+            .into_iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                let is_new = browser::item_login_key(item).is_none_or(|login| seen.insert(login));
+                if !is_new {
+                    duplicates += 1;
+                }
+                is_new
+            })
+            .collect();
+
+        let (imported, throughput) = insert_items(
+            &**storage,
+            &*state.clock,
+            &key,
+            bind,
+            fresh.into_iter(),
+            options,
+            &mut errors,
+        );
+        if imported > 0 {
+            insights::record(&**storage, &*state.clock, &key, insights::EVENT_IMPORT);
+        }
+
+        Ok(BrowserImportReport {
+            imported,
+            duplicates,
+            review_tag: browser::REVIEW_TAG.to_string(),
+            errors,
+            throughput,
+        })
+    })
+    .await
+}
+
+/// Import every Markdown and text file under the folder at `path` as a note named after the
+/// file (see `importers::directory`). A file that cannot be read or stored is reported and the
+/// rest are still imported.
+#[tauri::command]
+pub async fn import_directory(
+    app: AppHandle,
+    path: String,
+) -> Result<DirectoryImportReport, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let notes = directory::collect(std::path::Path::new(&path)).map_err(|e| e.to_string())?;
+
+        let bind = projects::binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let mut files = Vec::with_capacity(notes.len());
+        for note in notes {
+            let created = note.text.and_then(|text| {
+                let text = zeroize::Zeroizing::new(text);
+                let project = projects::master_key_project(&*state.clock, &key, bind, &note.name, &text)?;
+                storage.create_project(&project).map_err(|e| e.to_string())?;
+                Ok(project.id)
+            });
+            let (project_id, error) = match created {
+                Ok(id) => (Some(id), None),
+                Err(message) => (None, Some(message)),
+            };
+            files.push(ImportedFile {
+                path: note.path.to_string_lossy().into_owned(),
+                name: note.name,
+                project_id,
+                error,
+            });
+        }
+        let imported = files.iter().filter(|f| f.project_id.is_some()).count();
+        if imported > 0 {
+            insights::record(&**storage, &*state.clock, &key, insights::EVENT_IMPORT);
+        }
+        Ok(DirectoryImportReport { imported, files })
+    })
+    .await
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::get_cached_key;
+use crate::insights::{self, UsageInsights};
+use crate::storage::metrics::StorageMetrics;
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct UsageInsightsReport {
+    pub enabled: bool,
+    pub insights: UsageInsights,
+}
+
+#[tauri::command]
+pub fn get_usage_insights(state: State<AppState>) -> Result<UsageInsightsReport, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    Ok(UsageInsightsReport {
+        enabled: insights::is_enabled(&**storage),
+        insights: insights::load(&**storage, &key)?,
+    })
+}
+
+/// Turning insights off also forgets everything recorded so far.
+#[tauri::command]
+pub fn set_usage_insights_enabled(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    storage
+        .set_setting(
+            insights::SETTING_INSIGHTS_ENABLED,
+            if enabled { "true" } else { "false" },
+        )
+        .map_err(|e| e.to_string())?;
+    if !enabled {
+        insights::clear(&**storage, &key)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_usage_insights(state: State<AppState>) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    insights::clear(&**storage, &key)
+}
+
+/// Count a feature used in the frontend (searches, editor actions, ...).
+#[tauri::command]
+pub fn record_usage_event(state: State<AppState>, event: String) -> Result<(), String> {
+    if !insights::is_valid_event(&event) {
+        return Err("Invalid event name".to_string());
+    }
+    insights::record_event(&state, &event);
+    Ok(())
+}
+
+/// Read/write statistics of the open vault, for diagnosing a sluggish UI. `None` when the
+/// storage does not collect them.
+#[tauri::command]
+pub fn get_storage_metrics(state: State<AppState>) -> Result<Option<StorageMetrics>, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(storage.metrics())
+}
+
+/// Turn the slow-operation log on for operations taking at least `threshold_ms`, or off with
+/// `None`. The log is shown by `get_storage_metrics` and lasts until the vault is closed.
+#[tauri::command]
+pub fn set_slow_log_threshold(
+    state: State<AppState>,
+    threshold_ms: Option<u64>,
+) -> Result<(), String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    storage.set_slow_threshold(threshold_ms.map(Duration::from_millis));
+    Ok(())
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use zeroize::Zeroize;
+
+use crate::commands::{blocking, get_cached_key, run_blocking};
+use crate::commands::settings::{quick_unlock_kind, KC_LEGACY_MASTER_PASSWORD, KC_SESSION_KEY};
+use crate::crypto::{self, KdfParams};
+use crate::keychain;
+use crate::rehash;
+use crate::security_report::{self, KdfTiming, SecurityReport};
+use crate::storage::local::LocalStorage;
+use crate::system_idle;
+use crate::vault;
+use crate::AppState;
+
+const BENCHMARK_CANDIDATES: [(u32, u32, u32); 5] = [
+    (16384, 1, 1),
+    (32768, 2, 1),
+    (65536, 3, 4),
+    (131072, 3, 4),
+    (262144, 4, 4),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KdfBenchmark {
+    pub params: KdfParams,
+    pub millis: u64,
+    pub is_current: bool,
+}
+
+fn time_derivation(params: &KdfParams) -> Result<u64, String> {
+    let started = Instant::now();
+    crypto::derive_master_key_with_params("vaultpad-kdf-benchmark", params)
+        .map_err(|e| e.to_string())?;
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+/// Strongest candidate that still derives within `target_ms` on this machine.
+/// Falls back to the defaults when even the cheapest candidate is too slow.
+pub(crate) fn calibrate(target_ms: u64) -> Result<KdfParams, String> {
+    let mut chosen = KdfParams::default();
+    for (memory_kb, iterations, parallelism) in BENCHMARK_CANDIDATES {
+        let params = KdfParams {
+            memory_kb,
+            iterations,
+            parallelism,
+        };
+        if time_derivation(&params)? > target_ms {
+            break;
+        }
+        chosen = params;
+    }
+    Ok(chosen)
+}
+
+#[tauri::command]
+pub fn get_kdf_params(state: State<AppState>) -> Result<KdfParams, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    vault::kdf_params(&**storage)
+}
+
+/// Time one master-key derivation for each candidate parameter set on this machine.
+#[tauri::command]
+pub async fn benchmark_kdf(app: AppHandle) -> Result<Vec<KdfBenchmark>, String> {
+    blocking(app, move |state| {
+        let current = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            match storage.as_ref() {
+                Some(storage) => vault::kdf_params(&**storage)?,
+                None => KdfParams::default(),
+            }
+        };
+
+        let mut results = Vec::with_capacity(BENCHMARK_CANDIDATES.len());
+        for (memory_kb, iterations, parallelism) in BENCHMARK_CANDIDATES {
+            let params = KdfParams {
+                memory_kb,
+                iterations,
+                parallelism,
+            };
+            results.push(KdfBenchmark {
+                params,
+                millis: time_derivation(&params)?,
+                is_current: params == current,
+            });
+        }
+        Ok(results)
+    })
+    .await
+}
+
+/// Re-wrap the vault key under a KEK derived with new Argon2 parameters. The content keeps
+/// its key, so nothing is re-encrypted. Only the local vault is supported: server contexts must
+/// derive the same key on every device.
+#[tauri::command]
+pub async fn set_kdf_params(
+    app: AppHandle,
+    password: String,
+    params: KdfParams,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        params.validate().map_err(|e| e.to_string())?;
+
+        {
+            let active = state.active_context.lock().map_err(|e| e.to_string())?;
+            if *active != "local" {
+                return Err("KDF parameters can only be changed for the local vault".to_string());
+            }
+        }
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        if vault::kdf_params(&**storage)? == params {
+            return Ok(());
+        }
+
+        let secret = vault::unlock_secret(&**storage, &password)?;
+        let mut key = vault::unlock(&**storage, &secret)?.ok_or("wrong_password")?;
+        let rewrapped = vault::rewrap(&**storage, &key, &secret, &params);
+        key.zeroize();
+        rewrapped
+    })
+    .await
+}
+
+/// How well the open vault is protected on this machine, with recommendations. Auto-lock is a
+/// frontend setting, so the frontend passes its value in.
+#[tauri::command]
+pub async fn security_report(
+    app: AppHandle,
+    auto_lock_minutes: Option<u32>,
+) -> Result<SecurityReport, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
+        let (params, content, lock_on_screen_lock, database_encrypted) = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            (
+                vault::kdf_params(&**storage)?,
+                rehash::census(&**storage, &key)?,
+                system_idle::lock_on_screen_lock(&**storage),
+                storage.is_encrypted(),
+            )
+        };
+        let format = db_path
+            .as_deref()
+            .and_then(|path| LocalStorage::inspect(path).ok());
+
+        let mut report = SecurityReport {
+            kdf: KdfTiming {
+                millis: time_derivation(&params)?,
+                params,
+            },
+            format_version: format.as_ref().and_then(|f| f.format_version),
+            schema_version: format.as_ref().map(|f| f.schema_version).unwrap_or_default(),
+            database_encrypted,
+            content,
+            keychain_backend: keychain::backend().as_str().to_string(),
+            master_password_persisted: keychain::get(KC_LEGACY_MASTER_PASSWORD).is_some(),
+            session_saved: keychain::get(KC_SESSION_KEY).is_some(),
+            quick_unlock: quick_unlock_kind(),
+            auto_lock_minutes,
+            lock_on_screen_lock,
+            recommendations: Vec::new(),
+        };
+        report.recommendations = security_report::recommendations(&report);
+        Ok(report)
+    })
+    .await
+}
+
+/// Known-answer tests of the crypto stack; needs no open or unlocked vault.
+#[tauri::command]
+pub async fn crypto_self_test() -> Result<crypto::self_test::SelfTestReport, String> {
+    run_blocking(|| Ok(crypto::self_test::run())).await
+}
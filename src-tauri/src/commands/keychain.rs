@@ -0,0 +1,154 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! An audit of what VaultPad keeps in the OS keychain. Only the names of the entries are ever
+//! listed, never their values, and each one the user can safely drop has a remove action.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::audit;
+use crate::commands::settings::{
+    self, KC_DB_FOLDER, KC_DB_PATH, KC_LEGACY_MASTER_PASSWORD, KC_LEGACY_PIN_HASH, KC_PIN_SLOT,
+    KC_QUICK_UNLOCK_KIND, KC_SESSION_KEY,
+};
+use crate::device;
+use crate::keychain;
+use crate::password_registry;
+use crate::server_config::{self, KC_SERVERS};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeychainItemKind {
+    /// The saved vault key, or a legacy saved master password.
+    SavedSession,
+    /// The PIN slot and what kind of quick unlock is set up.
+    QuickUnlock,
+    VaultLocation,
+    ProjectPassword,
+    /// The list of configured servers.
+    ServerProfiles,
+    /// A server's token, saved master password, PIN hash or admin flag.
+    ServerCredential,
+    Device,
+    AuditKey,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeychainItem {
+    pub key: String,
+    pub kind: KeychainItemKind,
+    /// The project name or server name the entry belongs to, when it is known.
+    pub label: Option<String>,
+    pub project_id: Option<String>,
+    pub server_id: Option<String>,
+    /// Stored encrypted with DPAPI on top of the keychain.
+    pub sealed: bool,
+    pub removable: bool,
+}
+
+fn classify(key: &str) -> (KeychainItemKind, Option<&str>, Option<&str>) {
+    let kind = match key {
+        KC_SESSION_KEY | KC_LEGACY_MASTER_PASSWORD => KeychainItemKind::SavedSession,
+        KC_PIN_SLOT | KC_QUICK_UNLOCK_KIND | KC_LEGACY_PIN_HASH => KeychainItemKind::QuickUnlock,
+        KC_DB_PATH | KC_DB_FOLDER => KeychainItemKind::VaultLocation,
+        KC_SERVERS => KeychainItemKind::ServerProfiles,
+        audit::KC_AUDIT_KEY => KeychainItemKind::AuditKey,
+        _ if device::KEYCHAIN_KEYS.contains(&key) => KeychainItemKind::Device,
+        _ => {
+            if let Some(project_id) = password_registry::project_of(key) {
+                return (KeychainItemKind::ProjectPassword, Some(project_id), None);
+            }
+            if let Some(server_id) = server_config::server_of(key) {
+                return (KeychainItemKind::ServerCredential, None, Some(server_id));
+            }
+            KeychainItemKind::Other
+        }
+    };
+    (kind, None, None)
+}
+
+/// Entries whose removal would break something rather than just forget it: the server list
+/// (use `remove_server`), the device identity the servers know this machine by, and the key
+/// the audit log is chained with.
+fn is_removable(kind: KeychainItemKind) -> bool {
+    !matches!(
+        kind,
+        KeychainItemKind::ServerProfiles | KeychainItemKind::Device | KeychainItemKind::AuditKey
+    )
+}
+
+fn project_name(state: &AppState, project_id: &str) -> Option<String> {
+    let guard = state.storage.lock().ok()?;
+    guard.as_ref()?.get_project(project_id).ok().map(|p| p.name)
+}
+
+#[tauri::command]
+pub fn list_keychain_items(state: State<AppState>) -> Vec<KeychainItem> {
+    keychain::keys()
+        .into_iter()
+        .map(|key| {
+            let (kind, project_id, server_id) = classify(&key);
+            let label = match (project_id, server_id) {
+                (Some(id), _) => project_name(&state, id),
+                (_, Some(id)) => server_config::find_server(id).map(|s| s.name),
+                _ => None,
+            };
+            KeychainItem {
+                kind,
+                label,
+                project_id: project_id.map(str::to_string),
+                server_id: server_id.map(str::to_string),
+                sealed: keychain::is_sealed(&key),
+                removable: is_removable(kind),
+                key,
+            }
+        })
+        .collect()
+}
+
+/// Remove one keychain entry. Quick-unlock entries go as a set, the same as `remove_pin`.
+#[tauri::command]
+pub fn remove_keychain_item(key: String) -> Result<(), String> {
+    if keychain::get(&key).is_none() {
+        return Err("No such keychain item".to_string());
+    }
+    match classify(&key).0 {
+        kind if !is_removable(kind) => Err("This keychain item cannot be removed".to_string()),
+        KeychainItemKind::QuickUnlock => {
+            settings::remove_pin();
+            Ok(())
+        }
+        _ => {
+            keychain::remove(&key);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_classified_by_name() {
+        assert_eq!(classify(KC_SESSION_KEY).0, KeychainItemKind::SavedSession);
+        assert_eq!(
+            classify("project-password-abc"),
+            (KeychainItemKind::ProjectPassword, Some("abc"), None)
+        );
+        assert_eq!(
+            classify("srv-my-server-master-password"),
+            (KeychainItemKind::ServerCredential, None, Some("my-server"))
+        );
+        assert_eq!(
+            classify("srv-my-server-master-password-set"),
+            (KeychainItemKind::ServerCredential, None, Some("my-server"))
+        );
+        assert_eq!(classify("srv-x-unknown").0, KeychainItemKind::Other);
+        assert!(!is_removable(classify(audit::KC_AUDIT_KEY).0));
+        assert!(!is_removable(classify("device-id").0));
+    }
+}
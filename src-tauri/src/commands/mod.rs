@@ -1,9 +1,76 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+//! Tauri command handlers. Storage calls and the sync client (`reqwest::blocking`) are
+//! synchronous. Commands that wait on the network (the sync server, breach checks) or on a
+//! hardware key, derive a key from a password (Argon2), or read every project of the vault are
+//! `async` and hand their body to `blocking`, which runs it on the blocking thread pool: such
+//! calls must not run on an async runtime worker, where reqwest panics in debug builds and
+//! anything slow holds up every other async command. Plain synchronous commands are kept to
+//! quick lookups and single-row writes.
+
+pub mod attachments;
 pub mod auth;
 pub mod backups;
+pub mod breach;
+pub mod changes;
+pub mod checklist;
+pub mod custom_fields;
+pub mod devices;
+pub mod drafts;
+pub mod emergency;
+pub mod export;
+pub mod folders;
+pub mod import;
+pub mod insights;
+pub mod kdf;
+pub mod keychain;
+pub mod password_history;
+pub mod passwords;
 pub mod projects;
 pub mod servers;
+pub mod session;
 pub mod settings;
 pub mod sync;
+pub mod templates;
+pub mod totp;
+pub mod vaults;
+pub mod versions;
+
+use tauri::{AppHandle, Manager, State};
+use zeroize::Zeroizing;
+
+use crate::crypto;
+use crate::AppState;
+
+/// A copy of the unlocked vault's key, wiped when dropped; fails while the vault is locked.
+pub(crate) fn get_cached_key(
+    state: &AppState,
+) -> Result<Zeroizing<[u8; crypto::KEY_LEN]>, String> {
+    state
+        .cached_key
+        .lock()
+        .map_err(|e| e.to_string())?
+        .with_key(crypto::locked::copy)
+        .ok_or_else(|| "No cached key. Please unlock first.".to_string())
+}
+
+/// Run `f` on the blocking thread pool and wait for it without holding up an async worker.
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Background task failed: {e}"))?
+}
+
+/// `run_blocking` for a command body that needs the app state.
+pub(crate) async fn blocking<T, F>(app: AppHandle, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(State<'_, AppState>) -> Result<T, String> + Send + 'static,
+{
+    run_blocking(move || f(app.state::<AppState>())).await
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::get_cached_key;
+use crate::commands::projects::update_project;
+use crate::credential;
+use crate::crypto;
+use crate::password_history::{self, PastPassword};
+use crate::rehash;
+use crate::AppState;
+
+/// Passwords the credential had before, most recently replaced first.
+#[tauri::command]
+pub fn list_password_history(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Vec<PastPassword>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    password_history::list(&**storage, project_key, &project_id)
+}
+
+/// Make a previous password the credential's password again. This is a save like any other:
+/// the password it replaces goes into the history and `base_revision` works as in
+/// `update_project`.
+#[tauri::command]
+pub fn restore_password_from_history(
+    state: State<AppState>,
+    id: String,
+    password: String,
+    base_revision: Option<i64>,
+) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let (project, pw, content) = {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let entry = storage.get_password_history_entry(&id).map_err(|e| e.to_string())?;
+        let project = unlocked_project(&state, &**storage, &entry.project_id)?;
+        let pw = project_password(&project, &key, password)?;
+        let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+        let restored = password_history::open(project_key, &entry)?;
+
+        let content = Zeroizing::new(match &pw {
+            Some(pw) => crypto::decrypt_auto(&project.encrypted_content, None, Some(pw))
+                .map_err(|e| e.to_string())?,
+            None => rehash::open_project_content(&project, &key)?,
+        });
+        let content = std::str::from_utf8(&content).map_err(|e| e.to_string())?;
+        let mut current = credential::decode(content).ok_or("This project is not a credential")?;
+        current.password = restored.to_string();
+        (project, pw, credential::encode(&current)?)
+    };
+
+    update_project(
+        state,
+        project.id,
+        project.name,
+        content.to_string(),
+        pw.as_ref().map(|pw| pw.to_string()).unwrap_or_default(),
+        pw.is_some(),
+        base_revision,
+    )
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use chrono::Datelike;
+use tauri::State;
+use zeroize::Zeroize;
+
+use crate::passphrase::{self, Capitalization, GeneratedPassphrase};
+use crate::strength::{self, PasswordStrength};
+use crate::AppState;
+
+/// Strength of a master or project password, for the meter in the password dialogs.
+/// `user_inputs` are strings an attacker would try first, such as the e-mail address or the
+/// project name.
+#[tauri::command]
+pub fn estimate_password_strength(
+    state: State<AppState>,
+    mut password: String,
+    user_inputs: Option<Vec<String>>,
+) -> PasswordStrength {
+    let estimate = strength::estimate(
+        &password,
+        &user_inputs.unwrap_or_default(),
+        state.clock.now().year(),
+    );
+    password.zeroize();
+    estimate
+}
+
+/// A diceware-style passphrase of `word_count` words (six by default) joined by `separator`
+/// (`-` by default).
+#[tauri::command]
+pub fn generate_passphrase(
+    word_count: Option<usize>,
+    separator: Option<String>,
+    capitalization: Option<Capitalization>,
+) -> Result<GeneratedPassphrase, String> {
+    passphrase::generate(
+        word_count.unwrap_or(passphrase::DEFAULT_WORD_COUNT),
+        separator.as_deref().unwrap_or("-"),
+        capitalization.unwrap_or_default(),
+    )
+}
@@ -1,382 +1,1117 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
 
+use crate::appearance::{self, Appearance};
+use crate::attachments::{self, ProjectKey};
 use crate::backup;
+use crate::checklist;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::clock::Clock;
+use crate::commands::{blocking, get_cached_key};
+use crate::cooling_off;
+use crate::credential;
 use crate::crypto;
+use crate::custom_fields;
+use crate::drafts;
+use crate::emergency;
+use crate::folders;
+use crate::insights;
 use crate::keychain;
-use crate::models::{DecryptedProject, Project, ProjectBackup};
+use crate::links::{self, Backlink};
+use crate::models::{Credential, DecryptedProject, Project, ProjectBackup, ProjectFilter};
+use crate::ordering;
+use crate::password_history;
 use crate::password_registry;
+use crate::rehash;
+use crate::search::{self, SearchHit};
+use crate::storage::{in_transaction, StorageProvider};
+use crate::tags::{self, TagInfo};
+use crate::totp;
+use crate::trash::{self, TrashedProject};
+use crate::versions;
 use crate::AppState;
 
 const BACKUP_KEEP_COUNT: usize = 15;
+/// Items `stream_projects` returns directly; the rest arrive in batches.
+const STREAM_FIRST_PAGE: usize = 100;
+const STREAM_BATCH_SIZE: usize = 500;
+const RECENT_DEFAULT: usize = 10;
+const RECENT_MAX: usize = 100;
+/// `update_project` was given a stale base revision; followed by `:` and the current one.
+pub const ERR_CONFLICT: &str = "project_conflict";
+
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectListItem {
     pub id: String,
     pub name: String,
     pub has_custom_password: bool,
     pub password_saved: bool,
-    pub sort_order: i32,
+    /// `false` for items from `stream_projects`, whose `password_saved` was not looked up.
+    pub probed: bool,
+    pub position: String,
     pub created_at: String,
     pub updated_at: String,
     pub server_id: Option<String>,
     pub is_password_registry: bool,
+    /// Tag ids; names come from `list_tags`.
+    pub tags: Vec<String>,
+    /// `None` at the top level.
+    pub folder_id: Option<String>,
+    pub archived: bool,
+    pub pinned: bool,
+    /// `#rrggbb`.
+    pub color: Option<String>,
+    pub icon: Option<String>,
 }
 
-fn get_cached_key(state: &AppState) -> Result<[u8; crypto::KEY_LEN], String> {
-    state
-        .cached_key
-        .lock()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "No cached key. Please unlock first.".to_string())
+#[derive(Debug, Serialize)]
+pub struct ProjectPage {
+    pub stream_id: u64,
+    pub items: Vec<ProjectListItem>,
+    /// Items in the whole list, this page included.
+    pub total: usize,
+    /// No `projects-batch` events follow.
+    pub done: bool,
 }
 
-fn get_master_password(state: &AppState) -> Option<String> {
-    state.master_password.lock().ok()?.clone()
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectBatch {
+    pub stream_id: u64,
+    pub items: Vec<ProjectListItem>,
+    pub done: bool,
 }
 
 fn kc_key(project_id: &str) -> String {
     format!("project-password-{}", project_id)
 }
 
-#[tauri::command]
-pub fn list_projects(state: State<AppState>) -> Result<Vec<ProjectListItem>, String> {
-    let key = get_cached_key(&state)?;
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
+/// Only local projects that were never uploaded get their content bound to the project id and
+/// name: ids are reassigned by the server, so synced content has to stay portable.
+pub(crate) fn binds_content(state: &AppState, server_id: &Option<String>) -> bool {
+    server_id.is_none()
+        && state
+            .active_context
+            .lock()
+            .map(|ctx| ctx.as_str() == "local")
+            .unwrap_or(false)
+}
 
-    let projects = storage.list_projects().map_err(|e| e.to_string())?;
-    let mut items = Vec::new();
+/// A new master-key project holding `content`, not yet stored. An empty position places it
+/// after all others.
+pub(crate) fn master_key_project(
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    bind: bool,
+    name: &str,
+    content: &str,
+) -> Result<Project, String> {
+    let id = Uuid::new_v4().to_string();
+    let now = clock.now().to_rfc3339();
+    let encrypted_content = crypto::content::encrypt(content.as_bytes(), key, &id, name, bind)
+        .map_err(|e| e.to_string())?;
 
+    Ok(Project {
+        id,
+        name: name.to_string(),
+        encrypted_content,
+        key_check: crypto::encrypt_with_key(b"mk", key).map_err(|e| e.to_string())?,
+        position: String::new(),
+        created_at: now.clone(),
+        updated_at: now,
+        server_id: None,
+        sync_status: "local".to_string(),
+        last_synced_at: None,
+        folder_id: None,
+        revision: 0,
+        pinned: false,
+    })
+}
+
+/// Projects the list shows, each with its tag ids: neither trashed nor deleted, archived only
+/// with `include_archived`, and carrying every tag in `filter`. Pinned ones come first, each
+/// group in list order.
+fn listed_projects(
+    storage: &dyn StorageProvider,
+    filter: &[String],
+    include_archived: bool,
+) -> Result<Vec<(Project, Vec<String>)>, String> {
+    let projects = storage.list_projects().map_err(|e| e.to_string())?;
+    let mut project_tags = tags::by_project(storage)?;
+    let trashed = trash::ids(storage)?;
+    let archived = archived_ids(storage)?;
+    let mut listed = Vec::new();
     for p in projects {
-        if p.sync_status == "deleted" {
+        if p.sync_status == "deleted" || trashed.contains(&p.id) {
             continue;
         }
+        if !include_archived && archived.contains(&p.id) {
+            continue;
+        }
+        let tag_ids = project_tags.remove(&p.id).unwrap_or_default();
+        if !filter.iter().all(|t| tag_ids.contains(t)) {
+            continue;
+        }
+        listed.push((p, tag_ids));
+    }
+    listed.sort_by_key(|(p, _)| !p.pinned);
+    Ok(listed)
+}
 
-        let srv_id = p.server_id.clone();
-        let is_registry_by_id = password_registry::is_registry(&p.id);
-        let is_registry = is_registry_by_id || p.name == password_registry::PASSWORD_REGISTRY_NAME;
+/// What list items show about projects besides the projects themselves.
+struct ListLookups {
+    known_folders: HashSet<String>,
+    archived: HashSet<String>,
+    appearance: HashMap<String, Appearance>,
+}
 
-        let has_custom = if !p.key_check.is_empty() {
-            crypto::try_decrypt_with_key(&p.key_check, &key).is_none()
-        } else {
-            false
-        };
+impl ListLookups {
+    fn load(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> Result<Self, String> {
+        Ok(Self {
+            known_folders: folders::known_ids(storage)?,
+            archived: archived_ids(storage)?,
+            appearance: appearance::by_project(storage, key)?,
+        })
+    }
+}
 
-        let password_saved = if has_custom {
-            keychain::get(&kc_key(&p.id)).is_some()
-        } else {
-            false
-        };
+/// Without `probe` the keychain is not asked for a saved custom password, which is what makes
+/// large lists slow; `password_saved` is then `false`.
+fn list_item(
+    p: Project,
+    tag_ids: Vec<String>,
+    key: &[u8; crypto::KEY_LEN],
+    lookups: &ListLookups,
+    probe: bool,
+) -> ProjectListItem {
+    let srv_id = p.server_id.clone();
+    let is_archived = lookups.archived.contains(&p.id);
+    let look = lookups.appearance.get(&p.id).cloned().unwrap_or_default();
+    let is_registry_by_id = password_registry::is_registry(&p.id);
+    let is_registry = is_registry_by_id || p.name == password_registry::PASSWORD_REGISTRY_NAME;
 
-        let display_name = if p.name.is_empty() {
-            if has_custom && !password_saved {
-                "locked_custom_password".to_string()
-            } else {
-                p.id.clone()
-            }
+    let has_custom = if !p.key_check.is_empty() {
+        crypto::try_decrypt_with_key(&p.key_check, key).is_none()
+    } else {
+        false
+    };
+
+    let password_saved = if has_custom && probe {
+        keychain::get(&kc_key(&p.id)).is_some()
+    } else {
+        false
+    };
+
+    let display_name = if p.name.is_empty() {
+        if has_custom && !password_saved {
+            "locked_custom_password".to_string()
         } else {
-            p.name
-        };
+            p.id.clone()
+        }
+    } else {
+        p.name
+    };
 
-        items.push(ProjectListItem {
-            id: p.id,
-            name: display_name,
-            has_custom_password: has_custom,
-            password_saved,
-            sort_order: p.sort_order,
-            created_at: p.created_at,
-            updated_at: p.updated_at,
-            server_id: srv_id,
-            is_password_registry: is_registry,
-        });
+    ProjectListItem {
+        id: p.id,
+        name: display_name,
+        has_custom_password: has_custom,
+        password_saved,
+        probed: probe,
+        position: p.position.clone(),
+        created_at: p.created_at,
+        updated_at: p.updated_at,
+        server_id: srv_id,
+        is_password_registry: is_registry,
+        tags: tag_ids,
+        folder_id: p.folder_id.filter(|f| lookups.known_folders.contains(f)),
+        archived: is_archived,
+        pinned: p.pinned,
+        color: look.color,
+        icon: look.icon,
     }
+}
+
+fn archived_ids(storage: &dyn StorageProvider) -> Result<HashSet<String>, String> {
+    Ok(storage
+        .list_archived_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect())
+}
+
+/// With `tags`, only projects carrying every one of those tag ids are listed. Archived projects
+/// are left out unless `include_archived` is set.
+#[tauri::command]
+pub async fn list_projects(
+    app: AppHandle,
+    tags: Option<Vec<String>>,
+    include_archived: Option<bool>,
+) -> Result<Vec<ProjectListItem>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    Ok(items)
+        let lookups = ListLookups::load(&**storage, &key)?;
+        let listed = listed_projects(
+            &**storage,
+            &tags.unwrap_or_default(),
+            include_archived.unwrap_or(false),
+        )?;
+        Ok(listed
+            .into_iter()
+            .map(|(p, tag_ids)| list_item(p, tag_ids, &key, &lookups, true))
+            .collect())
+    })
+    .await
 }
 
+fn emit_remaining(
+    app: AppHandle,
+    stream_id: u64,
+    key: Zeroizing<[u8; crypto::KEY_LEN]>,
+    lookups: ListLookups,
+    rest: Vec<(Project, Vec<String>)>,
+) {
+    let mut rest = rest.into_iter().peekable();
+    while rest.peek().is_some() {
+        // Stop as soon as the vault locks; the list is gone from the screen anyway.
+        let unlocked = app.state::<AppState>().cached_key.lock().is_ok_and(|k| k.is_some());
+        if !unlocked {
+            break;
+        }
+        let items = rest
+            .by_ref()
+            .take(STREAM_BATCH_SIZE)
+            .map(|(p, tag_ids)| list_item(p, tag_ids, &key, &lookups, false))
+            .collect();
+        let done = rest.peek().is_none();
+        let _ = app.emit("projects-batch", ProjectBatch { stream_id, items, done });
+    }
+}
+
+/// `list_projects` for very large vaults. The first `STREAM_FIRST_PAGE` items come back right
+/// away, the rest follow in `projects-batch` events carrying the same `stream_id`. Items are
+/// not probed for a saved custom password; `probe_project` does that for one item when needed.
 #[tauri::command]
-pub fn get_project(
+pub fn stream_projects(
+    app: AppHandle,
     state: State<AppState>,
-    id: String,
-    password: String,
-) -> Result<DecryptedProject, String> {
-    let cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    let mp = get_master_password(&state);
+    tags: Option<Vec<String>>,
+    include_archived: Option<bool>,
+) -> Result<ProjectPage, String> {
+    let key = get_cached_key(&state)?;
+    let (mut rest, lookups) = {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        (
+            listed_projects(
+                &**storage,
+                &tags.unwrap_or_default(),
+                include_archived.unwrap_or(false),
+            )?,
+            ListLookups::load(&**storage, &key)?,
+        )
+    };
+    let total = rest.len();
+    let items = rest
+        .drain(..total.min(STREAM_FIRST_PAGE))
+        .map(|(p, tag_ids)| list_item(p, tag_ids, &key, &lookups, false))
+        .collect();
+    let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+    let done = rest.is_empty();
+    if !done {
+        std::thread::spawn(move || emit_remaining(app, stream_id, key, lookups, rest));
+    }
+    Ok(ProjectPage {
+        stream_id,
+        items,
+        total,
+        done,
+    })
+}
+
+/// The full list item for one project, including whether its custom password is saved.
+#[tauri::command]
+pub fn probe_project(state: State<AppState>, id: String) -> Result<ProjectListItem, String> {
+    let key = get_cached_key(&state)?;
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
 
     let project = storage.get_project(&id).map_err(|e| e.to_string())?;
+    let tag_ids = tags::by_project(&**storage)?.remove(&id).unwrap_or_default();
+    let lookups = ListLookups::load(&**storage, &key)?;
+    Ok(list_item(project, tag_ids, &key, &lookups, true))
+}
 
-    let has_custom = if !project.key_check.is_empty() {
-        cached.as_ref().map_or(true, |key| {
-            crypto::try_decrypt_with_key(&project.key_check, key).is_none()
-        })
-    } else {
-        false
-    };
+#[tauri::command]
+pub fn list_tags(state: State<AppState>) -> Result<Vec<TagInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    tags::list(&**storage, &key)
+}
 
-    if !has_custom {
-        if let Some(key) = cached.as_ref() {
-            let content_bytes =
-                crypto::decrypt_auto(&project.encrypted_content, Some(key), mp.as_deref())
-                    .map_err(|e| e.to_string())?;
-            return Ok(DecryptedProject {
-                id: project.id,
-                name: project.name,
-                content: String::from_utf8(content_bytes).map_err(|e| e.to_string())?,
-                has_custom_password: false,
-                sort_order: project.sort_order,
-                created_at: project.created_at,
-                updated_at: project.updated_at,
-            });
+/// Replace a project's tags by name; unknown names become new tags.
+#[tauri::command]
+pub fn set_project_tags(
+    state: State<AppState>,
+    id: String,
+    tags: Vec<String>,
+) -> Result<Vec<TagInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    tags::set_for_project(&**storage, &*state.clock, &key, &id, &tags)
+}
+
+/// Set a project's sidebar color (`#rrggbb`) and icon; `None` or an empty string clears one.
+#[tauri::command]
+pub fn set_project_appearance(
+    state: State<AppState>,
+    id: String,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<Appearance, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    appearance::set_for_project(&**storage, &key, &id, color.as_deref(), icon.as_deref())
+}
+
+#[tauri::command]
+pub async fn get_project(
+    app: AppHandle,
+    id: String,
+    password: String,
+) -> Result<DecryptedProject, String> {
+    blocking(app, move |state| {
+        let cached = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(crypto::locked::copy);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        cooling_off::check(&**storage, &*state.clock, &id)?;
+        let project = storage.get_project_meta(&id).map_err(|e| e.to_string())?;
+        let _ = storage.record_access(&id, &state.clock.now().to_rfc3339());
+
+        let has_custom = if !project.key_check.is_empty() {
+            cached.as_ref().map_or(true, |key| {
+                crypto::try_decrypt_with_key(&project.key_check, key).is_none()
+            })
+        } else {
+            false
+        };
+
+        if !has_custom {
+            if let Some(key) = cached.as_ref() {
+                let content_bytes = rehash::read_project_content(&**storage, &project, key)?;
+                return decrypted(project, content_bytes, false);
+            }
         }
-    }
 
-    let explicitly_provided = !password.is_empty();
-    let pw = if password.is_empty() {
-        keychain::get(&kc_key(&id)).ok_or("No saved password for this project")?
-    } else {
-        password
-    };
+        // The other paths open the content whole.
+        let project = storage.get_project(&id).map_err(|e| e.to_string())?;
+        let released = if has_custom && password.is_empty() {
+            cached
+                .as_ref()
+                .and_then(|key| emergency::key_for(&**storage, key, &project.key_check))
+        } else {
+            None
+        };
+        if let Some(mut owner_key) = released {
+            let content_bytes = rehash::open_project_content(&project, &owner_key);
+            owner_key.zeroize();
+            return decrypted(project, content_bytes?, false);
+        }
 
-    let content_bytes = crypto::decrypt_auto(&project.encrypted_content, None, Some(&pw))
-        .map_err(|e| e.to_string())?;
+        let explicitly_provided = !password.is_empty();
+        let pw = if password.is_empty() {
+            keychain::get(&kc_key(&id)).ok_or("No saved password for this project")?
+        } else {
+            password
+        };
 
-    if explicitly_provided {
-        let _ = keychain::save(&kc_key(&id), &pw);
-    }
+        let content_bytes = crypto::decrypt_auto(&project.encrypted_content, None, Some(&pw))
+            .map_err(|e| e.to_string())?;
+
+        if explicitly_provided {
+            let _ = keychain::save(&kc_key(&id), &pw);
+        }
+
+        decrypted(project, content_bytes, true)
+    })
+    .await
+}
 
+fn decrypted(
+    project: Project,
+    content_bytes: Vec<u8>,
+    has_custom_password: bool,
+) -> Result<DecryptedProject, String> {
+    let content = String::from_utf8(content_bytes).map_err(|e| e.to_string())?;
+    let (kind, credential) = credential::parse(&content);
     Ok(DecryptedProject {
         id: project.id,
         name: project.name,
-        content: String::from_utf8(content_bytes).map_err(|e| e.to_string())?,
-        has_custom_password: true,
-        sort_order: project.sort_order,
+        content,
+        has_custom_password,
+        position: project.position,
         created_at: project.created_at,
         updated_at: project.updated_at,
+        revision: project.revision,
+        kind,
+        credential,
     })
 }
 
+#[derive(Debug, Deserialize)]
+pub struct NewProject {
+    pub name: String,
+    pub content: String,
+}
+
+/// Create master-key projects for all of `projects` at once, in their order after all others.
+/// Either every one is created or, on an error, none is. Returns the new ids.
 #[tauri::command]
-pub fn create_project(
-    state: State<AppState>,
+pub async fn bulk_create_projects(
+    app: AppHandle,
+    projects: Vec<NewProject>,
+) -> Result<Vec<String>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let bind = binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let projects = projects
+            .iter()
+            .map(|new| master_key_project(&*state.clock, &key, bind, &new.name, &new.content))
+            .collect::<Result<Vec<Project>, String>>()?;
+        storage.create_projects_batch(&projects).map_err(|e| e.to_string())?;
+        for _ in &projects {
+            insights::record(&**storage, &*state.clock, &key, insights::EVENT_PROJECT_CREATED);
+        }
+        Ok(projects.into_iter().map(|p| p.id).collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_project(
+    app: AppHandle,
     name: String,
     content: String,
     password: String,
     has_custom_password: bool,
 ) -> Result<String, String> {
-    let key = get_cached_key(&state)?;
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
-
-    let id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().to_rfc3339();
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let bind = binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let (encrypted_content, key_check) = if has_custom_password {
-        let _ = keychain::save(&kc_key(&id), &password);
-        (
-            crypto::encrypt(content.as_bytes(), &password).map_err(|e| e.to_string())?,
-            crypto::encrypt(b"cp", &password).map_err(|e| e.to_string())?,
-        )
-    } else {
-        keychain::remove(&kc_key(&id));
-        (
-            crypto::encrypt_with_key(content.as_bytes(), &key).map_err(|e| e.to_string())?,
-            crypto::encrypt_with_key(b"mk", &key).map_err(|e| e.to_string())?,
-        )
-    };
+        let id = Uuid::new_v4().to_string();
+        let now = state.clock.now().to_rfc3339();
+        let mut streamed = None;
 
-    let max_order: i32 = storage
-        .list_projects()
-        .map_err(|e| e.to_string())?
-        .iter()
-        .map(|p| p.sort_order)
-        .max()
-        .unwrap_or(-1);
+        let (encrypted_content, key_check) = if has_custom_password {
+            let _ = keychain::save(&kc_key(&id), &password);
+            (
+                crypto::encrypt(content.as_bytes(), &password).map_err(|e| e.to_string())?,
+                crypto::encrypt(b"cp", &password).map_err(|e| e.to_string())?,
+            )
+        } else {
+            keychain::remove(&kc_key(&id));
+            streamed = streamed_content(&content, &key, &id, &name, bind)?;
+            let encrypted_content = match &streamed {
+                Some(_) => Vec::new(),
+                None => crypto::content::encrypt(content.as_bytes(), &key, &id, &name, bind)
+                    .map_err(|e| e.to_string())?,
+            };
+            (
+                encrypted_content,
+                crypto::encrypt_with_key(b"mk", &key).map_err(|e| e.to_string())?,
+            )
+        };
 
-    let project = Project {
-        id: id.clone(),
-        name,
-        encrypted_content,
-        key_check,
-        sort_order: max_order + 1,
-        created_at: now.clone(),
-        updated_at: now,
-        server_id: None,
-        sync_status: "local".to_string(),
-        last_synced_at: None,
-    };
+        let project = Project {
+            id: id.clone(),
+            name,
+            encrypted_content,
+            key_check,
+            position: String::new(),
+            created_at: now.clone(),
+            updated_at: now,
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        };
 
-    storage
-        .create_project(&project)
+        match &streamed {
+            Some(seal) => storage.create_project_streamed(&project, seal),
+            None => storage.create_project(&project),
+        }
         .map_err(|e| e.to_string())?;
+        let _ = search::index_project(&**storage, &key, &project, &content);
 
-    if has_custom_password {
-        let _ = password_registry::rebuild_registry(&**storage, &key);
-    }
+        if has_custom_password {
+            let _ = password_registry::rebuild_registry(&**storage, &key);
+        }
+        insights::record(&**storage, &*state.clock, &key, insights::EVENT_PROJECT_CREATED);
+
+        Ok(id)
+    })
+    .await
+}
 
-    Ok(id)
+/// Bound content from `STREAM_THRESHOLD` up, to be sealed as the storage writes it rather than
+/// in memory.
+fn streamed_content(
+    content: &str,
+    key: &[u8; crypto::KEY_LEN],
+    id: &str,
+    name: &str,
+    bind: bool,
+) -> Result<Option<crypto::StreamedSeal>, String> {
+    if !bind || content.len() < crypto::STREAM_THRESHOLD {
+        return Ok(None);
+    }
+    crypto::content::stream(content.as_bytes(), key, id, name)
+        .map(Some)
+        .map_err(|e| e.to_string())
 }
 
+/// Save a project. With `base_revision`, the revision the edit started from, the save is
+/// refused with `project_conflict:<current revision>` when the project was saved since, say by
+/// another window or a sync, instead of overwriting that.
 #[tauri::command]
-pub fn update_project(
-    state: State<AppState>,
+pub async fn update_project(
+    app: AppHandle,
     id: String,
     name: String,
     content: String,
     password: String,
     has_custom_password: bool,
+    base_revision: Option<i64>,
 ) -> Result<(), String> {
-    let key = get_cached_key(&state)?;
-    let mp = get_master_password(&state);
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let existing = storage.get_project(&id).map_err(|e| e.to_string())?;
-    let now = chrono::Utc::now().to_rfc3339();
-    let had_custom_password = keychain::get(&kc_key(&id)).is_some();
+        let existing = storage.get_project(&id).map_err(|e| e.to_string())?;
+        if base_revision.is_some_and(|base| base != existing.revision) {
+            return Err(format!("{ERR_CONFLICT}:{}", existing.revision));
+        }
+        let now = state.clock.now().to_rfc3339();
+        let had_custom_password = keychain::get(&kc_key(&id)).is_some();
+        let bind = binds_content(&state, &existing.server_id);
 
-    let old_content = crypto::decrypt_auto(
-        &existing.encrypted_content,
-        Some(&key),
-        mp.as_deref(),
-    )
-    .ok()
-    .and_then(|bytes| String::from_utf8(bytes).ok());
-
-    if let Some(ref old_text) = old_content {
-        if backup::is_significant_change(old_text, &content) {
-            let backup_entry = ProjectBackup {
-                id: Uuid::new_v4().to_string(),
-                project_id: id.clone(),
-                name: existing.name.clone(),
-                encrypted_content: existing.encrypted_content.clone(),
-                key_check: existing.key_check.clone(),
-                created_at: now.clone(),
-                trigger_type: "auto".to_string(),
-                content_length: old_text.len() as i64,
-            };
-            let _ = storage.create_backup(&backup_entry);
-            let _ = storage.cleanup_backups(&id, BACKUP_KEEP_COUNT);
+        let old_content = rehash::open_project_content(&existing, &key)
+            .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+
+        if let Some(ref old_text) = old_content {
+            if backup::is_significant_change(old_text, &content) {
+                let backup_entry = ProjectBackup {
+                    id: Uuid::new_v4().to_string(),
+                    project_id: id.clone(),
+                    name: existing.name.clone(),
+                    encrypted_content: existing.encrypted_content.clone(),
+                    key_check: existing.key_check.clone(),
+                    created_at: now.clone(),
+                    trigger_type: "auto".to_string(),
+                    content_length: old_text.len() as i64,
+                };
+                let _ = storage.create_backup(&backup_entry);
+                let _ = storage.cleanup_backups(&id, BACKUP_KEEP_COUNT);
+            }
         }
-    }
 
-    let (encrypted_content, key_check) = if has_custom_password {
-        let pw = if password.is_empty() {
-            keychain::get(&kc_key(&id)).ok_or("No password available for this project")?
+        // Attachment keys are wrapped with the project key, so they follow a password change.
+        let old_password = (!existing.key_check.is_empty()
+            && crypto::try_decrypt_with_key(&existing.key_check, &key).is_none())
+        .then(|| keychain::get(&kc_key(&id)).unwrap_or_else(|| password.clone()));
+        let mut new_password = None;
+        let mut streamed = None;
+
+        let (encrypted_content, key_check) = if has_custom_password {
+            let pw = if password.is_empty() {
+                keychain::get(&kc_key(&id)).ok_or("No password available for this project")?
+            } else {
+                password
+            };
+            let sealed = (
+                crypto::encrypt(content.as_bytes(), &pw).map_err(|e| e.to_string())?,
+                crypto::encrypt(b"cp", &pw).map_err(|e| e.to_string())?,
+            );
+            new_password = Some(pw);
+            sealed
         } else {
-            let _ = keychain::save(&kc_key(&id), &password);
-            password
+            streamed = streamed_content(&content, &key, &id, &name, bind)?;
+            let encrypted_content = match &streamed {
+                Some(_) => Vec::new(),
+                None => crypto::content::encrypt(content.as_bytes(), &key, &id, &name, bind)
+                    .map_err(|e| e.to_string())?,
+            };
+            (
+                encrypted_content,
+                crypto::encrypt_with_key(b"mk", &key).map_err(|e| e.to_string())?,
+            )
         };
-        (
-            crypto::encrypt(content.as_bytes(), &pw).map_err(|e| e.to_string())?,
-            crypto::encrypt(b"cp", &pw).map_err(|e| e.to_string())?,
-        )
-    } else {
-        keychain::remove(&kc_key(&id));
-        (
-            crypto::encrypt_with_key(content.as_bytes(), &key).map_err(|e| e.to_string())?,
-            crypto::encrypt_with_key(b"mk", &key).map_err(|e| e.to_string())?,
-        )
-    };
 
-    let sync_status = if existing.sync_status == "synced" {
-        "modified".to_string()
-    } else {
-        existing.sync_status
-    };
+        let sync_status = if existing.sync_status == "synced" {
+            "modified".to_string()
+        } else {
+            existing.sync_status.clone()
+        };
+
+        let project = Project {
+            id: id.clone(),
+            name,
+            encrypted_content,
+            key_check,
+            position: existing.position.clone(),
+            created_at: existing.created_at.clone(),
+            updated_at: now,
+            server_id: existing.server_id.clone(),
+            sync_status,
+            last_synced_at: existing.last_synced_at.clone(),
+            folder_id: existing.folder_id.clone(),
+            revision: existing.revision,
+            pinned: existing.pinned,
+        };
+
+        // Side tables are rewrapped under the new password in the same transaction as the project,
+        // so a failure part way leaves every piece under the key it had.
+        in_transaction(&**storage, || {
+            versions::record(&**storage, &*state.clock, &existing)?;
+            attachments::rewrap(
+                &**storage,
+                &id,
+                ProjectKey::of(&key, old_password.as_deref()),
+                ProjectKey::of(&key, new_password.as_deref()),
+            )?;
+            custom_fields::rewrap(
+                &**storage,
+                &id,
+                ProjectKey::of(&key, old_password.as_deref()),
+                ProjectKey::of(&key, new_password.as_deref()),
+            )?;
+            totp::rewrap(
+                &**storage,
+                &id,
+                ProjectKey::of(&key, old_password.as_deref()),
+                ProjectKey::of(&key, new_password.as_deref()),
+            )?;
+            checklist::rewrap(
+                &**storage,
+                &*state.clock,
+                &id,
+                ProjectKey::of(&key, old_password.as_deref()),
+                ProjectKey::of(&key, new_password.as_deref()),
+            )?;
+            password_history::rewrap(
+                &**storage,
+                &id,
+                ProjectKey::of(&key, old_password.as_deref()),
+                ProjectKey::of(&key, new_password.as_deref()),
+            )?;
+            let previous = old_content.or_else(|| {
+                let pw = old_password.as_deref()?;
+                let bytes = crypto::decrypt_auto(&existing.encrypted_content, None, Some(pw)).ok()?;
+                String::from_utf8(bytes).ok()
+            });
+            if let Some(previous) = previous.map(Zeroizing::new) {
+                password_history::record_change(
+                    &**storage,
+                    &*state.clock,
+                    ProjectKey::of(&key, new_password.as_deref()),
+                    &id,
+                    &previous,
+                    &content,
+                )?;
+            }
 
-    let project = Project {
+            match &streamed {
+                Some(seal) => storage.update_project_streamed(&project, seal),
+                None => storage.update_project(&project),
+            }
+            .map_err(|e| e.to_string())
+        })?;
+        match &new_password {
+            Some(pw) => {
+                let _ = keychain::save(&kc_key(&id), pw);
+            }
+            None => keychain::remove(&kc_key(&id)),
+        }
+        let _ = search::index_project(&**storage, &key, &project, &content);
+        let _ = drafts::discard(&**storage, &project.id);
+
+        if has_custom_password || had_custom_password {
+            let _ = password_registry::rebuild_registry(&**storage, &key);
+        }
+
+        Ok(())
+    })
+    .await
+}
+
+/// `create_project` for a credential; its fields are stored as the project content.
+#[tauri::command]
+pub async fn create_credential(
+    app: AppHandle,
+    name: String,
+    credential: Credential,
+    password: String,
+    has_custom_password: bool,
+) -> Result<String, String> {
+    let content = credential::encode(&credential)?;
+    create_project(app, name, content.to_string(), password, has_custom_password).await
+}
+
+/// `update_project` for a credential. A note saved this way becomes a credential.
+#[tauri::command]
+pub async fn update_credential(
+    app: AppHandle,
+    id: String,
+    name: String,
+    credential: Credential,
+    password: String,
+    has_custom_password: bool,
+    base_revision: Option<i64>,
+) -> Result<(), String> {
+    let content = credential::encode(&credential)?;
+    update_project(
+        app,
         id,
         name,
-        encrypted_content,
-        key_check,
-        sort_order: existing.sort_order,
-        created_at: existing.created_at,
-        updated_at: now,
-        server_id: existing.server_id,
-        sync_status,
-        last_synced_at: existing.last_synced_at,
-    };
+        content.to_string(),
+        password,
+        has_custom_password,
+        base_revision,
+    )
+    .await
+}
 
-    storage
-        .update_project(&project)
-        .map_err(|e| e.to_string())?;
+/// Copy a project's name and content into a new project named "<name> (copy)", placed right
+/// after it in its folder. A project with its own password is copied under the same password.
+/// Attachments, fields, tags and history stay with the original.
+#[tauri::command]
+pub async fn duplicate_project(
+    app: AppHandle,
+    id: String,
+    password: String,
+) -> Result<String, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let bind = binds_content(&state, &None);
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    if has_custom_password || had_custom_password {
-        let _ = password_registry::rebuild_registry(&**storage, &key);
-    }
+        let original = unlocked_project(&state, &**storage, &id)?;
+        let pw = project_password(&original, &key, password)?;
+        let content = Zeroizing::new(match &pw {
+            Some(pw) => crypto::decrypt_auto(&original.encrypted_content, None, Some(pw))
+                .map_err(|e| e.to_string())?,
+            None => rehash::open_project_content(&original, &key)?,
+        });
+        let content = std::str::from_utf8(&content).map_err(|e| e.to_string())?;
 
-    Ok(())
+        let name = format!("{} (copy)", original.name);
+        let mut copy = master_key_project(&*state.clock, &key, bind, &name, content)?;
+        if let Some(pw) = &pw {
+            copy.encrypted_content =
+                crypto::encrypt(content.as_bytes(), pw).map_err(|e| e.to_string())?;
+            copy.key_check = crypto::encrypt(b"cp", pw).map_err(|e| e.to_string())?;
+            let _ = keychain::save(&kc_key(&copy.id), pw);
+        }
+        if !original.position.is_empty() {
+            let next = storage
+                .list_projects()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|p| p.position)
+                .filter(|position| *position > original.position)
+                .min();
+            copy.position = ordering::between(Some(&original.position), next.as_deref());
+        }
+        copy.folder_id = original.folder_id;
+
+        storage.create_project(&copy).map_err(|e| e.to_string())?;
+        let _ = search::index_project(&**storage, &key, &copy, content);
+        if pw.is_some() {
+            let _ = password_registry::rebuild_registry(&**storage, &key);
+        }
+        insights::record(&**storage, &*state.clock, &key, insights::EVENT_PROJECT_CREATED);
+
+        Ok(copy.id)
+    })
+    .await
+}
+
+/// Projects whose content links to project `id` (see `links`).
+#[tauri::command]
+pub async fn get_backlinks(app: AppHandle, id: String) -> Result<Vec<Backlink>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        links::backlinks(&**storage, &*state.clock, &key, &id)
+    })
+    .await
+}
+
+/// Projects whose name or content matches `query` and that pass `filters`, best first (see
+/// `search`).
+#[tauri::command]
+pub async fn search_projects(
+    app: AppHandle,
+    query: String,
+    filters: Option<ProjectFilter>,
+) -> Result<Vec<SearchHit>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        search::search(&**storage, &*state.clock, &key, &query, &filters.unwrap_or_default())
+    })
+    .await
 }
 
+/// Move a project to the trash; `purge_project` deletes it for good.
 #[tauri::command]
 pub fn delete_project(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    trash::trash(&**storage, &*state.clock, &id)
+}
+
+/// Keep a project but leave it out of the project list; see `list_projects`.
+#[tauri::command]
+pub fn archive_project(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    storage
+        .archive_project(&id, &state.clock.now().to_rfc3339())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unarchive_project(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    storage.unarchive_project(&id).map_err(|e| e.to_string())
+}
+
+/// Pin or unpin a project; returns whether it is now pinned.
+#[tauri::command]
+pub fn toggle_pin(state: State<AppState>, id: String) -> Result<bool, String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let pinned = !storage.get_project(&id).map_err(|e| e.to_string())?.pinned;
+    storage.set_pinned(&id, pinned).map_err(|e| e.to_string())?;
+    Ok(pinned)
+}
+
+#[tauri::command]
+pub async fn list_trashed_projects(app: AppHandle) -> Result<Vec<TrashedProject>, String> {
+    blocking(app, move |state| {
+        get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        trash::list(&**storage)
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn restore_project(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    trash::restore(&**storage, &id)
+}
+
+#[tauri::command]
+pub fn purge_project(state: State<AppState>, id: String) -> Result<(), String> {
     let key = get_cached_key(&state)?;
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
-    let had_custom_password = keychain::get(&kc_key(&id)).is_some();
-    keychain::remove(&kc_key(&id));
-
-    let existing = storage.get_project(&id).map_err(|e| e.to_string())?;
-    if existing.server_id.is_some() {
-        let mut tombstone = existing;
-        tombstone.sync_status = "deleted".to_string();
-        storage.update_project(&tombstone).map_err(|e| e.to_string())?;
-    } else {
-        storage.delete_project(&id).map_err(|e| e.to_string())?;
-    }
+    trash::purge(&**storage, &key, &id)
+}
 
-    if had_custom_password {
-        let _ = password_registry::rebuild_registry(&**storage, &key);
-    }
+#[tauri::command]
+pub fn get_trash_retention_days(state: State<AppState>) -> Result<u32, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    trash::retention_days(&**storage)
+}
 
-    Ok(())
+/// Days before trashed projects are purged automatically; 0 keeps them until purged by hand.
+#[tauri::command]
+pub fn set_trash_retention_days(state: State<AppState>, days: u32) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    trash::set_retention_days(&**storage, days)
 }
 
 #[tauri::command]
-pub fn reorder_projects(state: State<AppState>, ids: Vec<String>) -> Result<(), String> {
+/// `ids` is the new order of the projects in `folder_id` (the top level when `None`). Listed
+/// projects that were in another folder are moved into it.
+pub fn reorder_projects(
+    state: State<AppState>,
+    ids: Vec<String>,
+    folder_id: Option<String>,
+) -> Result<(), String> {
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let pairs: Vec<(String, i32)> = ids
+    for id in &ids {
+        folders::move_project(&**storage, id, folder_id.as_deref())?;
+    }
+
+    let mut positions: std::collections::HashMap<String, String> = storage
+        .list_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|p| (p.id, p.position))
+        .collect();
+    let order: Vec<(String, String)> = ids
         .into_iter()
-        .enumerate()
-        .map(|(i, id)| (id, i as i32))
+        .filter_map(|id| positions.get(&id).cloned().map(|position| (id, position)))
         .collect();
 
-    storage
-        .reorder_projects(&pairs)
-        .map_err(|e| e.to_string())?;
+    let mut changes = ordering::plan_reorder(&order);
+    if ordering::needs_rebalance(&changes) {
+        positions.extend(changes);
+        let mut all: Vec<(String, String)> = positions.into_iter().collect();
+        all.sort_by(|a, b| (&a.1, &a.0).cmp(&(&b.1, &b.0)));
+        let ids: Vec<String> = all.into_iter().map(|(id, _)| id).collect();
+        changes = ordering::rebalance(&ids);
+    }
+    storage.reorder_projects(&changes).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_project_password(id: String) -> Result<Option<String>, String> {
-    Ok(keychain::get(&kc_key(&id)))
+pub fn set_cooling_off(
+    state: State<AppState>,
+    id: String,
+    delay_secs: Option<u64>,
+) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    storage.get_project(&id).map_err(|e| e.to_string())?;
+    cooling_off::configure(&**storage, &*state.clock, &id, delay_secs)
 }
 
 #[tauri::command]
-pub fn import_password_registry(state: State<AppState>) -> Result<u32, String> {
-    let key = get_cached_key(&state)?;
+pub fn get_cooling_off_status(
+    state: State<AppState>,
+    id: String,
+) -> Result<Option<cooling_off::AccessStatus>, String> {
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
-    password_registry::import_registry(&**storage, &key)
+    cooling_off::status(&**storage, &*state.clock, &id)
 }
 
+/// Start the delay for a flagged project. `cooling-off-ready` is emitted with the project id
+/// once it can be opened.
 #[tauri::command]
-pub fn get_password_registry(state: State<AppState>) -> Result<Vec<password_registry::RegistryEntry>, String> {
-    let key = get_cached_key(&state)?;
+pub fn request_access(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> Result<cooling_off::AccessStatus, String> {
+    get_cached_key(&state)?;
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
-    let reg_project = storage
-        .get_project(password_registry::PASSWORD_REGISTRY_UUID)
-        .map_err(|e| e.to_string())?;
-    let registry = password_registry::parse_registry(&reg_project, &key)?;
-    Ok(registry.entries)
+    let status = cooling_off::request_access(&**storage, &*state.clock, &id)?;
+    if status.phase == cooling_off::Phase::Waiting {
+        let wait = std::time::Duration::from_secs(status.remaining_secs as u64);
+        std::thread::spawn(move || {
+            std::thread::sleep(wait);
+            let _ = app.emit("cooling-off-ready", id);
+        });
+    }
+    Ok(status)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentProject {
+    pub id: String,
+    pub name: String,
+    pub last_accessed_at: String,
+}
+
+/// The `limit` (10 unless given, at most 100) most recently opened projects, most recent first.
+#[tauri::command]
+pub fn list_recent_projects(
+    state: State<AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentProject>, String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let limit = limit.unwrap_or(RECENT_DEFAULT).min(RECENT_MAX);
+    let recent = storage.recently_accessed(limit).map_err(|e| e.to_string())?;
+    Ok(recent
+        .into_iter()
+        .filter_map(|(id, last_accessed_at)| {
+            let project = storage.get_project(&id).ok()?;
+            Some(RecentProject { id, name: project.name, last_accessed_at })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_project_password(id: String) -> Result<Option<String>, String> {
+    Ok(keychain::get(&kc_key(&id)))
+}
+
+#[tauri::command]
+pub async fn import_password_registry(app: AppHandle) -> Result<u32, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        password_registry::import_registry(&**storage, &key)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_password_registry(app: AppHandle) -> Result<Vec<password_registry::RegistryEntry>, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let reg_project = storage
+            .get_project(password_registry::PASSWORD_REGISTRY_UUID)
+            .map_err(|e| e.to_string())?;
+        let registry = password_registry::parse_registry(&reg_project, &key)?;
+        Ok(registry.entries)
+    })
+    .await
 }
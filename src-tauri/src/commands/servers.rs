@@ -3,15 +3,23 @@
 
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 use zeroize::Zeroize;
 
-use crate::crypto;
+use crate::audit;
+use crate::commands::{blocking, run_blocking};
+use crate::commands::vaults;
+use crate::crypto::{self, locked::LockedKey};
 use crate::keychain;
+use crate::rehash;
 use crate::server_config::{self, ServerConfig};
+use crate::server_features;
+use crate::session_bundle;
 use crate::storage::local::LocalStorage;
+use crate::storage::remote::RemoteStorage;
 use crate::storage::StorageProvider;
+use crate::vaults::ParkedVault;
 use crate::AppState;
 
 fn transliterate_to_filename(name: &str) -> String {
@@ -58,6 +66,8 @@ pub struct ServerInfo {
     pub db_path: String,
     pub is_authenticated: bool,
     pub has_master_password: bool,
+    /// Whether the vault's key is held this session, so switching to it needs no password.
+    pub is_unlocked: bool,
     pub is_admin: bool,
 }
 
@@ -89,14 +99,26 @@ pub struct ServerLoginResult {
 }
 
 #[tauri::command]
-pub fn list_servers() -> Vec<ServerInfo> {
-    server_config::list_servers()
+pub fn list_servers(state: State<AppState>) -> Result<Vec<ServerInfo>, String> {
+    let mut unlocked: Vec<String> = state
+        .vaults
+        .lock()
+        .map_err(|e| e.to_string())?
+        .list()
+        .into_iter()
+        .filter_map(|(path, unlocked)| unlocked.then_some(path))
+        .collect();
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_some() {
+        unlocked.extend(state.db_path.lock().map_err(|e| e.to_string())?.clone());
+    }
+    Ok(server_config::list_servers()
         .into_iter()
         .map(|cfg| {
             let is_authenticated = server_config::get_server_token(&cfg.id).is_some();
-            let has_master_password = server_config::get_server_master_password(&cfg.id).is_some();
+            let has_master_password = server_config::has_server_master_password(&cfg.id);
             let is_admin = server_config::get_server_is_admin(&cfg.id);
             ServerInfo {
+                is_unlocked: unlocked.contains(&cfg.db_path),
                 id: cfg.id,
                 name: cfg.name,
                 url: cfg.url,
@@ -106,7 +128,7 @@ pub fn list_servers() -> Vec<ServerInfo> {
                 is_admin,
             }
         })
-        .collect()
+        .collect())
 }
 
 #[tauri::command]
@@ -142,10 +164,48 @@ pub fn add_server(name: String, url: String, db_folder: String) -> Result<Server
         db_path,
         is_authenticated: false,
         has_master_password: false,
+        is_unlocked: false,
         is_admin: false,
     })
 }
 
+/// Write server profiles, their sign-in tokens and the device identity to `path`, encrypted
+/// under `passphrase`, for setting up another machine. Requires an unlocked vault.
+#[tauri::command]
+pub async fn export_session_bundle(
+    app: AppHandle,
+    passphrase: String,
+    path: String,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+            return Err("No cached key. Please unlock first.".to_string());
+        }
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+        let bundle = session_bundle::collect(&*state.clock)?;
+        let data = session_bundle::seal(&bundle, &passphrase)?;
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write bundle: {e}"))?;
+        audit::record_event(&state, audit::EVENT_EXPORT, "session_bundle");
+        Ok(())
+    })
+    .await
+}
+
+/// Restore a bundle written by `export_session_bundle` on another machine.
+#[tauri::command]
+pub async fn import_session_bundle(
+    path: String,
+    passphrase: String,
+) -> Result<session_bundle::ImportSummary, String> {
+    run_blocking(move || {
+        let data = std::fs::read(&path).map_err(|e| format!("Failed to read bundle: {e}"))?;
+        session_bundle::apply(session_bundle::open(&data, &passphrase)?)
+    })
+    .await
+}
+
 #[tauri::command]
 pub fn remove_server(state: State<AppState>, server_id: String) -> Result<(), String> {
     let cfg = server_config::find_server(&server_id)
@@ -165,91 +225,36 @@ pub fn remove_server(state: State<AppState>, server_id: String) -> Result<(), St
     Ok(())
 }
 
+/// Make `context_id` ("local" or a server id) the active context. The vault active until now
+/// stays open in the background with its key, so switching back does not ask for its password
+/// again until the app is locked.
 #[tauri::command]
 pub fn switch_context(state: State<AppState>, context_id: String) -> Result<(), String> {
-    {
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        if let Some(ref mut k) = *cached {
-            k.zeroize();
-        }
-        *cached = None;
-    }
-    {
-        let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-        *mp = None;
-    }
-    {
-        let mut storage = state.storage.lock().map_err(|e| e.to_string())?;
-        *storage = None;
-    }
-    {
-        let mut db_path = state.db_path.lock().map_err(|e| e.to_string())?;
-        *db_path = None;
-    }
-    {
-        let mut token = state.server_token.lock().map_err(|e| e.to_string())?;
-        *token = None;
-    }
-    {
-        let mut url = state.server_url.lock().map_err(|e| e.to_string())?;
-        *url = None;
-    }
-
-    if context_id == "local" {
-        let mut active = state.active_context.lock().map_err(|e| e.to_string())?;
-        *active = "local".to_string();
-        drop(active);
+    *state.server_token.lock().map_err(|e| e.to_string())? = None;
+    *state.server_url.lock().map_err(|e| e.to_string())? = None;
 
-        if let Some(db_path) = keychain::get("db-path") {
-            if let Ok(storage) = LocalStorage::new(&db_path) {
-                let mut s = state.storage.lock().map_err(|e| e.to_string())?;
-                *s = Some(Box::new(storage));
-                drop(s);
-                let mut p = state.db_path.lock().map_err(|e| e.to_string())?;
-                *p = Some(db_path);
-            }
+    let server = match context_id.as_str() {
+        "local" => None,
+        id => Some(server_config::find_server(id).ok_or("Server not found")?),
+    };
+    let db_path = match &server {
+        Some(cfg) => Some(cfg.db_path.clone()),
+        None => keychain::get("db-path"),
+    };
+    match db_path {
+        Some(db_path) => {
+            let target = vaults::take_or_open(&state, &db_path)?;
+            vaults::activate(&state, db_path, target)?;
         }
-        return Ok(());
-    }
-
-    let cfg = server_config::find_server(&context_id)
-        .ok_or("Server not found")?;
-
-    let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
-
-    {
-        let mut s = state.storage.lock().map_err(|e| e.to_string())?;
-        *s = Some(Box::new(storage));
-    }
-    {
-        let mut p = state.db_path.lock().map_err(|e| e.to_string())?;
-        *p = Some(cfg.db_path);
+        None => vaults::park_active(&state)?,
     }
 
-    if let Some(token) = server_config::get_server_token(&context_id) {
-        let mut t = state.server_token.lock().map_err(|e| e.to_string())?;
-        *t = Some(token);
-    }
-    {
-        let mut u = state.server_url.lock().map_err(|e| e.to_string())?;
-        *u = Some(cfg.url);
+    if let Some(cfg) = server {
+        *state.server_token.lock().map_err(|e| e.to_string())? =
+            server_config::get_server_token(&cfg.id);
+        *state.server_url.lock().map_err(|e| e.to_string())? = Some(cfg.url);
     }
-
-    if let Some(mp) = server_config::get_server_master_password(&context_id) {
-        let mut key = crypto::derive_master_key(&mp).map_err(|e| e.to_string())?;
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        *cached = Some(key);
-        key.zeroize();
-
-        let mut master = state.master_password.lock().map_err(|e| e.to_string())?;
-        *master = Some(mp);
-    }
-
-    {
-        let mut active = state.active_context.lock().map_err(|e| e.to_string())?;
-        *active = context_id;
-    }
-
+    *state.active_context.lock().map_err(|e| e.to_string())? = context_id;
     Ok(())
 }
 
@@ -259,49 +264,85 @@ pub fn get_active_context(state: State<AppState>) -> Result<String, String> {
     Ok(active.clone())
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    pub url: String,
+    pub client_version: String,
+    pub server_version: Option<String>,
+    pub min_client_version: Option<String>,
+    pub features: Option<Vec<String>>,
+}
+
+/// What the connected server reports about itself. Fails with
+/// `server_requires_newer_client:<version>` if it no longer accepts this client.
+#[tauri::command]
+pub async fn server_status(app: AppHandle) -> Result<ServerStatus, String> {
+    blocking(app, move |state| {
+        let url = state
+            .server_url
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("Not connected to server")?;
+        let health = server_features::check(&RemoteStorage::new(&url, ""))?;
+        Ok(ServerStatus {
+            url,
+            client_version: server_features::CLIENT_VERSION.to_string(),
+            server_version: health.version,
+            min_client_version: health.min_client_version,
+            features: health.features,
+        })
+    })
+    .await
+}
+
 #[tauri::command]
-pub fn srv_auth(
-    state: State<AppState>,
+pub async fn srv_auth(
+    app: AppHandle,
     server_id: String,
     email: String,
     password: String,
 ) -> Result<ServerLoginResult, String> {
-    let cfg = server_config::find_server(&server_id)
-        .ok_or("Server not found")?;
-
-    let url = format!("{}/api/auth/login", cfg.url.trim_end_matches('/'));
-    let client = Client::new();
-    let resp = client
-        .post(&url)
-        .json(&AuthPayload { email, password })
-        .send()
-        .map_err(|e| format!("Connection failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Auth failed: {}", text));
-    }
+    blocking(app, move |state| {
+        let cfg = server_config::find_server(&server_id)
+            .ok_or("Server not found")?;
+
+        let url = format!("{}/api/auth/login", cfg.url.trim_end_matches('/'));
+        let client = Client::new();
+        let resp = client
+            .post(&url)
+            .json(&AuthPayload { email, password })
+            .send()
+            .map_err(|e| format!("Connection failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Auth failed: {}", text));
+        }
 
-    let body: AuthResponseBody = resp
-        .json()
-        .map_err(|e| format!("Parse error: {}", e))?;
+        let body: AuthResponseBody = resp
+            .json()
+            .map_err(|e| format!("Parse error: {}", e))?;
 
-    server_config::save_server_token(&server_id, &body.token)?;
-    server_config::save_server_is_admin(&server_id, body.user.is_admin)?;
+        server_config::save_server_token(&server_id, &body.token)?;
+        server_config::save_server_is_admin(&server_id, body.user.is_admin)?;
+        audit::record_event(&state, audit::EVENT_SERVER_SIGN_IN, &cfg.url);
 
-    let active = state.active_context.lock().map_err(|e| e.to_string())?;
-    if *active == server_id {
-        drop(active);
-        let mut t = state.server_token.lock().map_err(|e| e.to_string())?;
-        *t = Some(body.token.clone());
-    }
+        let active = state.active_context.lock().map_err(|e| e.to_string())?;
+        if *active == server_id {
+            drop(active);
+            let mut t = state.server_token.lock().map_err(|e| e.to_string())?;
+            *t = Some(body.token.clone());
+        }
 
-    Ok(ServerLoginResult {
-        token: body.token,
-        user_id: body.user.id,
-        email: body.user.email,
-        is_admin: body.user.is_admin,
+        Ok(ServerLoginResult {
+            token: body.token,
+            user_id: body.user.id,
+            email: body.user.email,
+            is_admin: body.user.is_admin,
+        })
     })
+    .await
 }
 
 #[derive(Deserialize)]
@@ -313,34 +354,37 @@ struct MeResponse {
 }
 
 #[tauri::command]
-pub fn refresh_server_user(server_id: String) -> Result<bool, String> {
-    let cfg = server_config::find_server(&server_id).ok_or("Server not found")?;
-    let token = match server_config::get_server_token(&server_id) {
-        Some(t) => t,
-        None => return Ok(false),
-    };
+pub async fn refresh_server_user(server_id: String) -> Result<bool, String> {
+    run_blocking(move || {
+        let cfg = server_config::find_server(&server_id).ok_or("Server not found")?;
+        let token = match server_config::get_server_token(&server_id) {
+            Some(t) => t,
+            None => return Ok(false),
+        };
 
-    let client = Client::new();
-    let resp = client
-        .get(format!("{}/api/auth/me", cfg.url.trim_end_matches('/')))
-        .header("Authorization", format!("Bearer {}", token))
-        .send();
-
-    match resp {
-        Ok(r) if r.status().is_success() => {
-            if let Ok(me) = r.json::<MeResponse>() {
-                let _ = server_config::save_server_is_admin(&server_id, me.is_admin);
-                return Ok(me.is_admin);
+        let client = Client::new();
+        let resp = client
+            .get(format!("{}/api/auth/me", cfg.url.trim_end_matches('/')))
+            .header("Authorization", format!("Bearer {}", token))
+            .send();
+
+        match resp {
+            Ok(r) if r.status().is_success() => {
+                if let Ok(me) = r.json::<MeResponse>() {
+                    let _ = server_config::save_server_is_admin(&server_id, me.is_admin);
+                    return Ok(me.is_admin);
+                }
             }
+            Ok(r) if r.status().as_u16() == 401 => {
+                server_config::remove_server_token(&server_id);
+                server_config::remove_server_is_admin(&server_id);
+            }
+            _ => {}
         }
-        Ok(r) if r.status().as_u16() == 401 => {
-            server_config::remove_server_token(&server_id);
-            server_config::remove_server_is_admin(&server_id);
-        }
-        _ => {}
-    }
 
-    Ok(server_config::get_server_is_admin(&server_id))
+        Ok(server_config::get_server_is_admin(&server_id))
+    })
+    .await
 }
 
 #[tauri::command]
@@ -348,73 +392,86 @@ pub fn is_server_authenticated(server_id: String) -> bool {
     server_config::get_server_token(&server_id).is_some()
 }
 
+/// Hold `key` for the server vault at `cfg.db_path` until the app is locked: as the cached key
+/// when the server is the active context, otherwise with the vault parked in the background.
+fn hold_server_key(
+    state: &AppState,
+    cfg: &ServerConfig,
+    storage: LocalStorage,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    if *state.active_context.lock().map_err(|e| e.to_string())? == cfg.id {
+        state.cached_key.lock().map_err(|e| e.to_string())?.set(*key);
+        super::settings::install_nonce_counter(state, key);
+        super::settings::require_bound_content(state, key);
+        return Ok(());
+    }
+    let mut locked = LockedKey::new();
+    locked.set(*key);
+    state
+        .vaults
+        .lock()
+        .map_err(|e| e.to_string())?
+        .park(cfg.db_path.clone(), ParkedVault { storage: Box::new(storage), key: locked });
+    Ok(())
+}
+
 #[tauri::command]
-pub fn set_server_master_password(
-    state: State<AppState>,
+pub async fn set_server_master_password(
+    app: AppHandle,
     server_id: String,
     password: String,
 ) -> Result<(), String> {
-    let cfg = server_config::find_server(&server_id)
-        .ok_or("Server not found")?;
-
-    let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
+    blocking(app, move |state| {
+        let cfg = server_config::find_server(&server_id)
+            .ok_or("Server not found")?;
 
-    let token = crypto::create_verification_token(&password).map_err(|e| e.to_string())?;
-    storage.set_verification_token(&token).map_err(|e| e.to_string())?;
+        let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
 
-    server_config::save_server_master_password(&server_id, &password)?;
+        let token = crypto::create_verification_token(&password).map_err(|e| e.to_string())?;
+        storage.set_verification_token(&token).map_err(|e| e.to_string())?;
 
-    let active = state.active_context.lock().map_err(|e| e.to_string())?;
-    if *active == server_id {
-        drop(active);
+        server_config::mark_server_master_password(&server_id)?;
 
         let mut key = crypto::derive_master_key(&password).map_err(|e| e.to_string())?;
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        *cached = Some(key);
+        let held = hold_server_key(&state, &cfg, storage, &key);
         key.zeroize();
-
-        let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-        *mp = Some(password);
-    }
-
-    Ok(())
+        held
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn verify_server_master_password(
-    state: State<AppState>,
+pub async fn verify_server_master_password(
+    app: AppHandle,
     server_id: String,
     password: String,
 ) -> Result<bool, String> {
-    let cfg = server_config::find_server(&server_id)
-        .ok_or("Server not found")?;
-
-    let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
-    let token = storage
-        .get_verification_token()
-        .map_err(|e| e.to_string())?
-        .ok_or("No master password set for this server")?;
-
-    if !crypto::verify_password(&token, &password) {
-        return Ok(false);
-    }
-
-    server_config::save_server_master_password(&server_id, &password)?;
+    blocking(app, move |state| {
+        let cfg = server_config::find_server(&server_id)
+            .ok_or("Server not found")?;
+
+        let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
+        let token = storage
+            .get_verification_token()
+            .map_err(|e| e.to_string())?
+            .ok_or("No master password set for this server")?;
+
+        if !crypto::verify_password(&token, &password) {
+            return Ok(false);
+        }
 
-    let active = state.active_context.lock().map_err(|e| e.to_string())?;
-    if *active == server_id {
-        drop(active);
+        server_config::mark_server_master_password(&server_id)?;
 
         let mut key = crypto::derive_master_key(&password).map_err(|e| e.to_string())?;
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        *cached = Some(key);
+        rehash::upgrade_all(&storage, &key, &password, false);
+        let held = hold_server_key(&state, &cfg, storage, &key);
         key.zeroize();
+        held?;
 
-        let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-        *mp = Some(password);
-    }
-
-    Ok(true)
+        Ok(true)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -487,209 +544,224 @@ fn admin_request(state: &AppState) -> Result<(String, String), String> {
 }
 
 #[tauri::command]
-pub fn admin_list_users(state: State<AppState>) -> Result<Vec<AdminUser>, String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .get(format!("{}/api/admin/users", url.trim_end_matches('/')))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
+pub async fn admin_list_users(app: AppHandle) -> Result<Vec<AdminUser>, String> {
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .get(format!("{}/api/admin/users", url.trim_end_matches('/')))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
 
-    resp.json::<Vec<AdminUser>>()
-        .map_err(|e| format!("Parse error: {}", e))
+        resp.json::<Vec<AdminUser>>()
+            .map_err(|e| format!("Parse error: {}", e))
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn admin_create_user(
-    state: State<AppState>,
+pub async fn admin_create_user(
+    app: AppHandle,
     email: String,
     password: String,
     is_admin: bool,
 ) -> Result<AdminUser, String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .post(format!("{}/api/admin/users", url.trim_end_matches('/')))
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&AdminCreatePayload { email, password, is_admin })
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .post(format!("{}/api/admin/users", url.trim_end_matches('/')))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&AdminCreatePayload { email, password, is_admin })
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
 
-    resp.json::<AdminUser>()
-        .map_err(|e| format!("Parse error: {}", e))
+        resp.json::<AdminUser>()
+            .map_err(|e| format!("Parse error: {}", e))
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn admin_update_user(
-    state: State<AppState>,
+pub async fn admin_update_user(
+    app: AppHandle,
     user_id: i64,
     email: String,
     password: String,
     is_admin: bool,
 ) -> Result<(), String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .put(format!("{}/api/admin/users/{}", url.trim_end_matches('/'), user_id))
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&AdminUpdatePayload { email, password, is_admin })
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
-    Ok(())
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .put(format!("{}/api/admin/users/{}", url.trim_end_matches('/'), user_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&AdminUpdatePayload { email, password, is_admin })
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn admin_list_user_shares(state: State<AppState>, user_id: i64) -> Result<Vec<UserShare>, String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .get(format!("{}/api/admin/users/{}/shares", url.trim_end_matches('/'), user_id))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
+pub async fn admin_list_user_shares(
+    app: AppHandle,
+    user_id: i64,
+) -> Result<Vec<UserShare>, String> {
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .get(format!("{}/api/admin/users/{}/shares", url.trim_end_matches('/'), user_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
 
-    resp.json::<Vec<UserShare>>()
-        .map_err(|e| format!("Parse error: {}", e))
+        resp.json::<Vec<UserShare>>()
+            .map_err(|e| format!("Parse error: {}", e))
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn admin_delete_user(state: State<AppState>, user_id: i64) -> Result<(), String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .delete(format!("{}/api/admin/users/{}", url.trim_end_matches('/'), user_id))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
-    Ok(())
+pub async fn admin_delete_user(app: AppHandle, user_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .delete(format!("{}/api/admin/users/{}", url.trim_end_matches('/'), user_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn share_project(
-    state: State<AppState>,
-    project_id: i64,
-    user_id: i64,
-) -> Result<(), String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .post(format!(
-            "{}/api/projects/{}/share",
-            url.trim_end_matches('/'),
-            project_id
-        ))
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&SharePayload { user_id })
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
-    Ok(())
+pub async fn share_project(app: AppHandle, project_id: i64, user_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .post(format!(
+                "{}/api/projects/{}/share",
+                url.trim_end_matches('/'),
+                project_id
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&SharePayload { user_id })
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn unshare_project(
-    state: State<AppState>,
-    project_id: i64,
-    user_id: i64,
-) -> Result<(), String> {
-    let (url, token) = admin_request(&state)?;
-    let client = Client::new();
-    let resp = client
-        .delete(format!(
-            "{}/api/projects/{}/share/{}",
-            url.trim_end_matches('/'),
-            project_id,
-            user_id
-        ))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
-    Ok(())
+pub async fn unshare_project(app: AppHandle, project_id: i64, user_id: i64) -> Result<(), String> {
+    blocking(app, move |state| {
+        let (url, token) = admin_request(&state)?;
+        let client = Client::new();
+        let resp = client
+            .delete(format!(
+                "{}/api/projects/{}/share/{}",
+                url.trim_end_matches('/'),
+                project_id,
+                user_id
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn change_server_master_password(
-    state: State<AppState>,
+pub async fn change_server_master_password(
+    app: AppHandle,
     server_id: String,
     current_password: String,
     new_password: String,
 ) -> Result<u32, String> {
-    let cfg = server_config::find_server(&server_id).ok_or("Server not found")?;
-    let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
-
-    let token = storage
-        .get_verification_token()
-        .map_err(|e| e.to_string())?
-        .ok_or("No master password set for this server")?;
-
-    if !crypto::verify_password(&token, &current_password) {
-        return Err("wrong_password".to_string());
-    }
-
-    if current_password == new_password {
-        return Err("same_password".to_string());
-    }
-
-    let old_key = crypto::derive_master_key(&current_password).map_err(|e| e.to_string())?;
-    let new_key = crypto::derive_master_key(&new_password).map_err(|e| e.to_string())?;
-
-    let count = super::settings::reencrypt_storage(&storage, &old_key, &new_key)?;
+    blocking(app, move |state| {
+        let cfg = server_config::find_server(&server_id).ok_or("Server not found")?;
+        let storage = LocalStorage::new(&cfg.db_path).map_err(|e| e.to_string())?;
+
+        let token = storage
+            .get_verification_token()
+            .map_err(|e| e.to_string())?
+            .ok_or("No master password set for this server")?;
+
+        if !crypto::verify_password(&token, &current_password) {
+            audit::record(&storage, &*state.clock, audit::EVENT_UNLOCK_FAILED, "change_master_password");
+            return Err("wrong_password".to_string());
+        }
 
-    let new_token =
-        crypto::create_verification_token(&new_password).map_err(|e| e.to_string())?;
-    storage
-        .set_verification_token(&new_token)
-        .map_err(|e| e.to_string())?;
+        if current_password == new_password {
+            return Err("same_password".to_string());
+        }
 
-    server_config::save_server_master_password(&server_id, &new_password)?;
+        let old_key = crypto::derive_master_key(&current_password).map_err(|e| e.to_string())?;
+        let new_key = crypto::derive_master_key(&new_password).map_err(|e| e.to_string())?;
+        storage.unseal(&old_key).map_err(|e| e.to_string())?;
 
-    let active = state.active_context.lock().map_err(|e| e.to_string())?;
-    if *active == server_id {
-        drop(active);
+        let count = super::settings::reencrypt_storage(&storage, &*state.clock, &old_key, &new_key)?;
+        storage.rekey(&new_key).map_err(|e| e.to_string())?;
 
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        *cached = Some(new_key);
+        let new_token =
+            crypto::create_verification_token(&new_password).map_err(|e| e.to_string())?;
+        storage
+            .set_verification_token(&new_token)
+            .map_err(|e| e.to_string())?;
 
-        let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-        *mp = Some(new_password);
-    }
+        server_config::mark_server_master_password(&server_id)?;
+        audit::record(&storage, &*state.clock, audit::EVENT_MASTER_PASSWORD_CHANGED, &server_id);
 
-    Ok(count)
+        // A parked copy of the vault still holds the old key.
+        state.vaults.lock().map_err(|e| e.to_string())?.close(&cfg.db_path);
+        hold_server_key(&state, &cfg, storage, &new_key)?;
+        Ok(count)
+    })
+    .await
 }
 
 #[derive(Serialize)]
@@ -700,33 +772,36 @@ struct UpdateProfilePayload {
 }
 
 #[tauri::command]
-pub fn srv_update_profile(
-    _state: State<AppState>,
+pub async fn srv_update_profile(
+    app: AppHandle,
     server_id: String,
     current_password: String,
     new_email: String,
     new_password: String,
 ) -> Result<(), String> {
-    let cfg = server_config::find_server(&server_id)
-        .ok_or("Server not found")?;
-    let token = server_config::get_server_token(&server_id)
-        .ok_or("Not authenticated")?;
-
-    let client = Client::new();
-    let resp = client
-        .put(format!("{}/api/users/me", cfg.url.trim_end_matches('/')))
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&UpdateProfilePayload {
-            current_password,
-            email: new_email,
-            new_password,
-        })
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let text = resp.text().unwrap_or_default();
-        return Err(format!("Failed: {}", text));
-    }
-    Ok(())
+    blocking(app, move |_state| {
+        let cfg = server_config::find_server(&server_id)
+            .ok_or("Server not found")?;
+        let token = server_config::get_server_token(&server_id)
+            .ok_or("Not authenticated")?;
+
+        let client = Client::new();
+        let resp = client
+            .put(format!("{}/api/users/me", cfg.url.trim_end_matches('/')))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&UpdateProfilePayload {
+                current_password,
+                email: new_email,
+                new_password,
+            })
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Failed: {}", text));
+        }
+        Ok(())
+    })
+    .await
 }
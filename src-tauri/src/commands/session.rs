@@ -0,0 +1,147 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::get_cached_key;
+use crate::crypto;
+use crate::ephemeral;
+use crate::system_idle::{self, IdleSupport};
+use crate::AppState;
+
+const META_SESSION_SNAPSHOT: &str = "session_snapshot";
+
+/// UI state saved while the vault is unlocked, so that the next unlock after a crash returns
+/// the user to where they left off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub open_project_id: Option<String>,
+    pub scroll_top: Option<f64>,
+    pub cursor_line: Option<u32>,
+    pub search_query: Option<String>,
+    #[serde(default)]
+    pub saved_at: String,
+}
+
+fn snapshot_aad() -> Vec<u8> {
+    crypto::field_aad("session", META_SESSION_SNAPSHOT)
+}
+
+/// Encrypt the snapshot with the vault key and keep it in the vault metadata.
+#[tauri::command]
+pub fn save_session_snapshot(
+    state: State<AppState>,
+    snapshot: SessionSnapshot,
+) -> Result<(), String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let snapshot = SessionSnapshot {
+        saved_at: state.clock.now().to_rfc3339(),
+        ..snapshot
+    };
+    let json = serde_json::to_vec(&snapshot).map_err(|e| e.to_string())?;
+    let encrypted =
+        crypto::encrypt_with_key_aad(&json, &key, &snapshot_aad()).map_err(|e| e.to_string())?;
+
+    storage
+        .set_meta(META_SESSION_SNAPSHOT, &B64.encode(encrypted))
+        .map_err(|e| e.to_string())
+}
+
+/// The last saved snapshot, or `None` when there is none or it no longer decrypts. A project
+/// that was deleted since is dropped from the snapshot.
+#[tauri::command]
+pub fn get_session_snapshot(state: State<AppState>) -> Result<Option<SessionSnapshot>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let encoded = match storage
+        .get_meta(META_SESSION_SNAPSHOT)
+        .map_err(|e| e.to_string())?
+    {
+        Some(encoded) => encoded,
+        None => return Ok(None),
+    };
+
+    let snapshot = B64
+        .decode(encoded)
+        .ok()
+        .and_then(|data| crypto::try_decrypt_with_key_aad(&data, &key, &snapshot_aad()))
+        .and_then(|json| serde_json::from_slice::<SessionSnapshot>(&json).ok());
+
+    Ok(snapshot.map(|mut snapshot| {
+        let project_gone = snapshot.open_project_id.as_ref().is_some_and(|id| {
+            storage
+                .get_project(id)
+                .map(|p| p.sync_status == "deleted")
+                .unwrap_or(true)
+        });
+        if project_gone {
+            snapshot.open_project_id = None;
+            snapshot.scroll_top = None;
+            snapshot.cursor_line = None;
+        }
+        snapshot
+    }))
+}
+
+fn require_unlocked(state: &AppState) -> Result<(), String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
+    }
+    Ok(())
+}
+
+/// Keep a decrypted value in backend memory for `ttl_secs`, instead of web storage.
+#[tauri::command]
+pub fn put_ephemeral(
+    state: State<AppState>,
+    name: String,
+    value: String,
+    ttl_secs: u64,
+) -> Result<(), String> {
+    require_unlocked(&state)?;
+    ephemeral::put(&*state.clock, &name, &value, ttl_secs)
+}
+
+#[tauri::command]
+pub fn get_ephemeral(state: State<AppState>, name: String) -> Result<Option<String>, String> {
+    require_unlocked(&state)?;
+    ephemeral::get(&*state.clock, &name)
+}
+
+#[tauri::command]
+pub fn remove_ephemeral(name: String) {
+    ephemeral::remove(&name);
+}
+
+#[tauri::command]
+pub fn clear_ephemeral() {
+    ephemeral::clear();
+}
+
+/// Whether session idle time and screen locks can be observed on this desktop.
+#[tauri::command]
+pub fn get_system_idle_support() -> IdleSupport {
+    system_idle::support()
+}
+
+#[tauri::command]
+pub fn get_lock_on_screen_lock(state: State<AppState>) -> Result<bool, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(system_idle::lock_on_screen_lock(&**storage))
+}
+
+#[tauri::command]
+pub fn set_lock_on_screen_lock(state: State<AppState>, enabled: bool) -> Result<(), String> {
+    require_unlocked(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    system_idle::set_lock_on_screen_lock(&**storage, enabled)
+}
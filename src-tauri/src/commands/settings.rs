@@ -2,21 +2,246 @@
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
 use std::path::Path;
+use std::sync::Mutex;
 
 use base64::Engine;
-use tauri::State;
-use zeroize::Zeroize;
-
-use crate::crypto;
+use tauri::{AppHandle, Emitter, State};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::appearance;
+use crate::attachments;
+use crate::audit;
+use crate::backup_archive;
+use crate::checklist;
+use crate::clock::Clock;
+use crate::commands::blocking;
+use crate::crypto::{self, challenge_response, wrap};
+use crate::custom_fields;
+use crate::demo;
+use crate::drafts;
+use crate::display_info::{self, VaultDisplayInfo};
+use crate::ephemeral;
+use crate::folders;
+use crate::insights;
 use crate::keychain;
-use crate::storage::local::LocalStorage;
-use crate::storage::StorageProvider;
+use crate::models::{AuditEvent, Project};
+use crate::password_history;
+use crate::password_registry;
+use crate::pin_attempts;
+use crate::pipeline::{self, PipelineOptions};
+use crate::quick_unlock::QuickUnlockKind;
+use crate::recovery;
+use crate::rehash;
+use crate::relocate;
+use crate::search;
+use crate::secure_prompt;
+use crate::startup::{self, VaultCandidate};
+use crate::storage::local::{self, LocalStorage};
+use crate::storage::{cipher, compact};
+use crate::storage::{StorageError, StorageProvider};
+use crate::tags;
+use crate::templates;
+use crate::totp;
+use crate::vault;
+use crate::versions;
+use crate::wipe;
 use crate::AppState;
 
-const KC_DB_PATH: &str = "db-path";
-const KC_DB_FOLDER: &str = "db-folder";
-const KC_MASTER_PASSWORD: &str = "master-password";
-const KC_PIN_HASH: &str = "pin-hash";
+pub(crate) const KC_DB_PATH: &str = "db-path";
+pub(crate) const KC_DB_FOLDER: &str = "db-folder";
+/// Written by versions that kept the plaintext master password; only read to migrate it away.
+pub(crate) const KC_LEGACY_MASTER_PASSWORD: &str = "master-password";
+/// Written by versions that kept the vault key unwrapped when no quick-unlock factor was set;
+/// only read to restore that session once and then removed.
+pub(crate) const KC_SESSION_KEY: &str = "session-key";
+/// The vault key wrapped under the quick-unlock factor: a PIN, a pattern or a word phrase.
+pub(crate) const KC_PIN_SLOT: &str = "pin-slot";
+pub(crate) const KC_QUICK_UNLOCK_KIND: &str = "quick-unlock-kind";
+/// Where an earlier build kept the Touch ID key among the other entries, unprotected. Only
+/// removed now: the key lives in an item of its own that Touch ID guards (`keychain::biometric`).
+pub(crate) const KC_TOUCH_ID_KEY: &str = "touch-id-key";
+/// Written by versions that checked the PIN against a hash and kept the key unwrapped beside it.
+pub(crate) const KC_LEGACY_PIN_HASH: &str = "pin-hash";
+
+/// `(real, decoy)` database paths after an unlock with the duress password; until restart,
+/// `init_database` on the real path opens the decoy instead.
+static DURESS_REDIRECT: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+pub(crate) fn load_session_key() -> Option<[u8; crypto::KEY_LEN]> {
+    let encoded = keychain::get_secret(KC_SESSION_KEY)?;
+    let mut bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+    let key = bytes.as_slice().try_into().ok();
+    bytes.zeroize();
+    key
+}
+
+/// Make `key` the unlocked vault's key. This takes the storage lock more than once, so callers
+/// must release theirs first.
+fn set_cached_key(state: &AppState, mut key: [u8; crypto::KEY_LEN]) -> Result<(), String> {
+    // An encrypted database can only be read once the key is known.
+    let unsealed = {
+        let guard = state.storage.lock().map_err(|e| e.to_string())?;
+        guard.as_ref().map_or(Ok(()), |storage| storage.unseal(&key))
+    };
+    if let Err(e) = unsealed {
+        key.zeroize();
+        return Err(e.to_string());
+    }
+    state
+        .cached_key
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set(key);
+    install_nonce_counter(state, &key);
+    require_bound_content(state, &key);
+    // A PIN-protected keychain file also opens with the vault key.
+    let _ = keychain::unlock_with_vault_key(&key);
+    key.zeroize();
+    let _ = with_storage(state, pin_attempts::reset);
+    Ok(())
+}
+
+/// Switch encryption under `key` to counter nonces persisted in the open vault. A vault that
+/// cannot be written to keeps random nonces.
+pub(crate) fn install_nonce_counter(state: &AppState, key: &[u8; crypto::KEY_LEN]) {
+    crypto::nonce::uninstall();
+    let reserve = state
+        .storage
+        .lock()
+        .ok()
+        .and_then(|s| s.as_ref().and_then(|s| s.nonce_reserve()));
+    if let Some(reserve) = reserve {
+        let _ = crypto::nonce::install(key, reserve);
+    }
+}
+
+/// Bind the open vault's content to its projects if that is still to be done, and refuse
+/// unbound local content under `key` once it is. Only local vaults bind their content.
+pub(crate) fn require_bound_content(state: &AppState, key: &[u8; crypto::KEY_LEN]) {
+    let local = state.active_context.lock().is_ok_and(|ctx| ctx.as_str() == "local");
+    let bound = local
+        && with_storage(state, |storage| {
+            if !rehash::is_bound(storage) && !storage.is_read_only() {
+                rehash::bind_content(storage, key);
+            }
+            Ok(rehash::is_bound(storage))
+        })
+        .unwrap_or(false);
+    crypto::content::require_binding(bound.then_some(key));
+}
+
+fn with_storage<T>(
+    state: &AppState,
+    f: impl FnOnce(&dyn StorageProvider) -> Result<T, String>,
+) -> Result<T, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    f(&**storage)
+}
+
+/// Count a failed quick-unlock attempt and return the error to report. At the wipe threshold
+/// the saved session and the quick-unlock factor are removed, so only the master password
+/// opens the vault again.
+fn pin_failure(state: &AppState) -> String {
+    audit::record_event(state, audit::EVENT_UNLOCK_FAILED, "pin");
+    match with_storage(state, |s| pin_attempts::record_failure(s, &*state.clock)) {
+        Ok(pin_attempts::Outcome::Retry) => "invalid_pin".to_string(),
+        Ok(pin_attempts::Outcome::LockedOut) => "pin_locked_out".to_string(),
+        Ok(pin_attempts::Outcome::Wipe) => {
+            forget_keychain_session();
+            "pin_session_wiped".to_string()
+        }
+        Err(e) => e,
+    }
+}
+
+/// Remove the saved session and the quick-unlock factor from the keychain.
+fn forget_keychain_session() {
+    if keychain::backend() == keychain::Backend::FilePin {
+        let _ = keychain::migrate(keychain::Backend::FileMachine, None, None);
+    }
+    keychain::remove(KC_SESSION_KEY);
+    keychain::remove(KC_LEGACY_MASTER_PASSWORD);
+    keychain::remove(KC_PIN_SLOT);
+    keychain::remove(KC_QUICK_UNLOCK_KIND);
+    keychain::remove(KC_LEGACY_PIN_HASH);
+    keychain::remove(KC_TOUCH_ID_KEY);
+    keychain::biometric::remove();
+    let _ = display_info::set_quick_unlock(None);
+}
+
+/// Remember the unlocked vault's path. Its key is only kept wrapped in the quick-unlock slot:
+/// without a PIN, pattern or phrase the next start asks for the master password.
+fn save_session(state: &AppState) -> Result<(), String> {
+    let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
+    if let Some(ref path) = db_path {
+        keychain::save(KC_DB_PATH, path)?;
+        save_db_folder_if_empty(&derive_folder(path))?;
+        let opened_at = state.clock.now().to_rfc3339();
+        let _ = display_info::record_opened(path, &opened_at, quick_unlock_kind());
+    }
+    keychain::remove(KC_SESSION_KEY);
+    keychain::remove(KC_LEGACY_MASTER_PASSWORD);
+    Ok(())
+}
+
+fn pin_slot() -> Result<Option<wrap::KeySlot>, String> {
+    match keychain::get_secret(KC_PIN_SLOT) {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid PIN slot: {e}")),
+        None => Ok(None),
+    }
+}
+
+/// The configured quick-unlock factor. Slots written before other factors existed are PINs.
+pub(crate) fn quick_unlock_kind() -> Option<QuickUnlockKind> {
+    if keychain::get(KC_PIN_SLOT).is_none() {
+        return keychain::get(KC_LEGACY_PIN_HASH).map(|_| QuickUnlockKind::Pin);
+    }
+    Some(
+        keychain::get(KC_QUICK_UNLOCK_KIND)
+            .and_then(|kind| QuickUnlockKind::parse(&kind))
+            .unwrap_or(QuickUnlockKind::Pin),
+    )
+}
+
+/// Wrap the vault key under a normalized quick-unlock secret. The slot replaces every other
+/// copy of the key (or the password) in the keychain.
+fn save_pin_slot(
+    state: &AppState,
+    key: &[u8; crypto::KEY_LEN],
+    kind: QuickUnlockKind,
+    secret: &str,
+) -> Result<(), String> {
+    let params = {
+        let guard = state.storage.lock().map_err(|e| e.to_string())?;
+        match guard.as_ref() {
+            Some(storage) => vault::kdf_params(&**storage)?,
+            None => crypto::KdfParams::default(),
+        }
+    };
+    let slot = wrap::wrap_with_secret(key, secret, &params).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&slot).map_err(|e| e.to_string())?;
+    keychain::save_secret(KC_PIN_SLOT, &json)?;
+    keychain::save(KC_QUICK_UNLOCK_KIND, kind.as_str())?;
+    keychain::set_pin(secret)?;
+    let _ = display_info::set_quick_unlock(Some(kind));
+
+    keychain::remove(KC_SESSION_KEY);
+    keychain::remove(KC_LEGACY_MASTER_PASSWORD);
+    keychain::remove(KC_LEGACY_PIN_HASH);
+    Ok(())
+}
+
+/// Check a PIN against the hash written by older versions.
+fn verify_legacy_pin(pin: &str) -> Result<bool, String> {
+    let pin_hash_b64 = keychain::get(KC_LEGACY_PIN_HASH).ok_or("No PIN configured")?;
+    let pin_hash = base64::engine::general_purpose::STANDARD
+        .decode(&pin_hash_b64)
+        .map_err(|e| format!("Invalid PIN hash: {e}"))?;
+    Ok(crypto::verify_pin(&pin_hash, pin) || crypto::verify_password(&pin_hash, pin))
+}
 
 fn derive_folder(db_path: &str) -> String {
     Path::new(db_path)
@@ -32,9 +257,35 @@ fn save_db_folder_if_empty(folder: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// The path to open for `db_path`: the decoy instead of the real vault after a duress unlock.
+pub(crate) fn duress_redirect(db_path: String) -> Result<String, String> {
+    Ok(match DURESS_REDIRECT.lock().map_err(|e| e.to_string())?.as_ref() {
+        Some((real, decoy)) if *real == db_path => decoy.clone(),
+        _ => db_path,
+    })
+}
+
+/// Unseal an encrypted `storage` with the cached key, if the vault is unlocked.
+pub(crate) fn unseal_cached(state: &AppState, storage: &dyn StorageProvider) -> Result<(), String> {
+    let unsealed =
+        state.cached_key.lock().map_err(|e| e.to_string())?.with_key(|key| storage.unseal(key));
+    unsealed.unwrap_or(Ok(())).map_err(|e| e.to_string())
+}
+
+/// Open the existing vault at `db_path` as `init_database` does, unsealed with the cached key
+/// while the vault is unlocked.
+pub(crate) fn open_unlocked(state: &AppState, db_path: &str) -> Result<LocalStorage, String> {
+    let storage = startup::open(db_path)?;
+    unseal_cached(state, &storage)?;
+    Ok(storage)
+}
+
+/// Open the existing vault at `db_path`. Fails with one of the `startup::ERR_*` codes, which
+/// the frontend turns into the recovery wizard.
 #[tauri::command]
 pub fn init_database(state: State<AppState>, db_path: String) -> Result<(), String> {
-    let storage = LocalStorage::new(&db_path).map_err(|e| e.to_string())?;
+    let db_path = duress_redirect(db_path)?;
+    let storage = open_unlocked(&state, &db_path)?;
     let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
     *guard = Some(Box::new(storage));
 
@@ -47,6 +298,142 @@ pub fn init_database(state: State<AppState>, db_path: String) -> Result<(), Stri
     Ok(())
 }
 
+/// Vault files and copies found where vaults were kept before, for a vault that will not open.
+#[tauri::command]
+pub fn locate_vault_candidates() -> Vec<VaultCandidate> {
+    let default_folder = get_default_db_folder().ok().map(Into::into);
+    startup::locate_candidates(keychain::get(KC_DB_FOLDER).as_deref(), default_folder)
+}
+
+/// Open a copy of the vault file or `.bak` copy at `path` instead of a vault that will not
+/// open. The copy is made next to it so `path` itself is never migrated or changed. Returns
+/// the path of the vault now open.
+#[tauri::command]
+pub fn open_backup_instead(state: State<AppState>, path: String) -> Result<String, String> {
+    let db_path = startup::copy_as_vault(&path)?;
+    let storage = startup::open(&db_path)?;
+    *state.storage.lock().map_err(|e| e.to_string())? = Some(Box::new(storage));
+    *state.db_path.lock().map_err(|e| e.to_string())? = Some(db_path.clone());
+    save_db_folder_if_empty(&derive_folder(&db_path))?;
+    Ok(db_path)
+}
+
+/// Format version of the database at `db_path`, so the frontend can explain a vault written by
+/// a newer version and offer read-only mode when `read_only_possible`.
+#[tauri::command]
+pub fn inspect_database(db_path: String) -> Result<local::FormatInfo, String> {
+    LocalStorage::inspect(&db_path).map_err(|e| e.to_string())
+}
+
+/// Encrypt the open vault's database file with SQLCipher, or turn that back into a plain
+/// SQLite file. Field encryption is unaffected either way; this only hides the metadata the
+/// file otherwise shows. An encrypted vault is unlocked from its `.unlock.json` sidecar, which
+/// has to stay beside the database file: without it the vault cannot be opened.
+#[tauri::command]
+pub async fn set_database_encryption(app: AppHandle, enabled: bool) -> Result<(), String> {
+    blocking(app, move |state| convert_database(&state, enabled, LocalStorage::new)).await
+}
+
+/// `set_database_encryption`, reopening the converted file with `reopen`. A vault that cannot
+/// be reopened is left closed and locked, to be unlocked again.
+fn convert_database(
+    state: &AppState,
+    enabled: bool,
+    reopen: impl FnOnce(&str) -> Result<LocalStorage, StorageError>,
+) -> Result<(), String> {
+    let key = state
+        .cached_key
+        .lock()
+        .map_err(|e| e.to_string())?
+        .with_key(crypto::locked::copy)
+        .ok_or("No cached key. Please unlock first.")?;
+    let db_path = state
+        .db_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("No database path set")?;
+
+    let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = guard.as_ref().ok_or("Database not initialized")?;
+    if storage.is_read_only() {
+        return Err("The vault is open read-only".to_string());
+    }
+    let encrypted = storage.is_encrypted();
+    if encrypted == enabled {
+        return Ok(());
+    }
+
+    // The file is replaced, so no connection to it may stay open.
+    crypto::nonce::uninstall();
+    *guard = None;
+    let db_key = cipher::database_key(&key);
+    let converted = cipher::convert(
+        &db_path,
+        encrypted.then_some(&*db_key),
+        enabled.then_some(&*db_key),
+    )
+    .map_err(|e| e.to_string());
+    let reopened = reopen(&db_path).and_then(|storage| {
+        storage.unseal(&key)?;
+        Ok(storage)
+    });
+    match reopened {
+        Ok(storage) => *guard = Some(Box::new(storage)),
+        Err(e) => {
+            drop(guard);
+            state.cached_key.lock().map_err(|e| e.to_string())?.clear();
+            crypto::nonce::uninstall();
+            ephemeral::clear();
+            let reason = match converted {
+                Ok(()) => format!("the database could not be reopened: {e}"),
+                Err(failed) => {
+                    format!("conversion failed ({failed}) and the database could not be reopened: {e}")
+                }
+            };
+            return Err(format!("The vault was closed because {reason}. Unlock it again."));
+        }
+    }
+    drop(guard);
+    converted?;
+    install_nonce_counter(state, &key);
+    require_bound_content(state, &key);
+    Ok(())
+}
+
+/// Rebuild the open vault and its attachment shard without free pages, reporting each file in
+/// a `compact-progress` event. This can take a while on a large vault, so it runs off the main
+/// thread; the vault stays locked against other commands meanwhile.
+#[tauri::command]
+pub async fn compact_database(app: AppHandle) -> Result<compact::CompactStats, String> {
+    let handle = app.clone();
+    blocking(app, move |state| {
+        let guard = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+        storage
+            .compact(&mut |progress| {
+                let _ = handle.emit("compact-progress", progress);
+            })
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Open a vault from a newer version without migrating it. It can be unlocked and read, but
+/// every change is refused.
+#[tauri::command]
+pub fn open_database_read_only(state: State<AppState>, db_path: String) -> Result<(), String> {
+    let storage = LocalStorage::open_read_only(&db_path).map_err(|e| e.to_string())?;
+    *state.storage.lock().map_err(|e| e.to_string())? = Some(Box::new(storage));
+    *state.db_path.lock().map_err(|e| e.to_string())? = Some(db_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_database_read_only(state: State<AppState>) -> Result<bool, String> {
+    with_storage(&state, |storage| Ok(storage.is_read_only()))
+}
+
 #[tauri::command]
 pub fn init_new_database(state: State<AppState>, db_path: String) -> Result<(), String> {
     if Path::new(&db_path).exists() {
@@ -75,94 +462,832 @@ pub fn has_master_password(state: State<AppState>) -> Result<bool, String> {
 }
 
 #[tauri::command]
-pub fn set_master_password(state: State<AppState>, password: String) -> Result<(), String> {
+pub async fn set_master_password(app: AppHandle, password: String) -> Result<(), String> {
+    blocking(app, move |state| create_master_password(&state, &password)).await
+}
+
+fn create_master_password(state: &AppState, password: &str) -> Result<(), String> {
+    let mut key = {
+        let guard = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+
+        let existing = storage.get_verification_token().map_err(|e| e.to_string())?;
+        if existing.is_some() {
+            return Err("Master password already set".to_string());
+        }
+
+        vault::initialize(&**storage, password)?
+    };
+    let saved = save_session(state);
+    set_cached_key(state, key)?;
+    key.zeroize();
+    saved
+}
+
+#[tauri::command]
+pub async fn verify_master_password(app: AppHandle, password: String) -> Result<bool, String> {
+    blocking(app, move |state| unlock_with_password(&state, &password)).await
+}
+
+fn unlock_with_password(state: &AppState, password: &str) -> Result<bool, String> {
+    let (unlocked, decoy_path) = {
+        let guard = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+        let secret = vault::unlock_secret(&**storage, password)?;
+        let unlocked = vault::unlock(&**storage, &secret)?;
+        // Checked on every attempt, so the time taken does not tell the two passwords apart.
+        let decoy_path = vault::duress_slot(&**storage)?
+            .and_then(|slot| vault::open_duress(&slot, password));
+        (unlocked, decoy_path)
+    };
+
+    match (unlocked, decoy_path) {
+        (Some(key), _) => finish_unlock(state, key, insights::EVENT_UNLOCK).map(|_| true),
+        (None, Some(decoy_path)) => open_decoy(state, &decoy_path, password),
+        (None, None) => {
+            audit::record_event(state, audit::EVENT_UNLOCK_FAILED, "password");
+            Ok(false)
+        }
+    }
+}
+
+/// Switch to the decoy database and unlock it with the duress password. The real vault's
+/// keychain session is removed first, so nothing on this machine points back to it.
+fn open_decoy(state: &AppState, decoy_path: &str, password: &str) -> Result<bool, String> {
+    let decoy = LocalStorage::new(decoy_path).map_err(|e| e.to_string())?;
+    let key = match vault::unlock(&decoy, password)? {
+        Some(key) => key,
+        None => return Ok(false),
+    };
+
+    forget_keychain_session();
+    let real_path = state
+        .db_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .replace(decoy_path.to_string());
+    *state.storage.lock().map_err(|e| e.to_string())? = Some(Box::new(decoy));
+    if let Some(real_path) = real_path {
+        *DURESS_REDIRECT.lock().map_err(|e| e.to_string())? =
+            Some((real_path, decoy_path.to_string()));
+    }
+    finish_unlock(state, key, insights::EVENT_UNLOCK).map(|_| true)
+}
+
+fn finish_unlock(
+    state: &AppState,
+    mut key: [u8; crypto::KEY_LEN],
+    event: &str,
+) -> Result<(), String> {
+    let saved = save_session(state);
+    set_cached_key(state, key)?;
+    key.zeroize();
+    saved?;
+    insights::record_event(state, event);
+    audit::record_event(state, audit::EVENT_UNLOCK, event);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_secure_prompt_available() -> bool {
+    secure_prompt::is_available()
+}
+
+/// What the Touch ID item holds. The vault id keeps the key from being tried on another vault.
+#[derive(serde::Serialize, serde::Deserialize, zeroize::Zeroize, zeroize::ZeroizeOnDrop)]
+struct TouchIdKey {
+    vault_id: String,
+    key: String,
+}
+
+/// The saved Touch ID key, if it is for the active vault. Reading it is what asks for Touch ID.
+fn touch_id_key(state: &AppState) -> Result<Option<Zeroizing<[u8; crypto::KEY_LEN]>>, String> {
+    let Some(saved) = keychain::biometric::load()? else {
+        return Ok(None);
+    };
+    let saved: TouchIdKey = serde_json::from_slice(&saved).map_err(|e| e.to_string())?;
+    let vault_id = with_storage(state, |storage| vault::vault_id(storage))?;
+    if vault_id.as_deref() != Some(saved.vault_id.as_str()) {
+        return Ok(None);
+    }
+    let bytes = Zeroizing::new(
+        base64::engine::general_purpose::STANDARD
+            .decode(&saved.key)
+            .map_err(|e| e.to_string())?,
+    );
+    let key: [u8; crypto::KEY_LEN] =
+        bytes.as_slice().try_into().map_err(|_| "Invalid Touch ID key".to_string())?;
+    Ok(Some(Zeroizing::new(key)))
+}
+
+/// Unlock with a password typed into the OS's native dialog, or on macOS with Touch ID once
+/// `set_touch_id_unlock` is on. The password stays in the backend; the frontend only learns
+/// whether it was correct. The dialog waits for the user, so it runs off the main thread.
+#[tauri::command]
+pub async fn unlock_with_secure_prompt(app: AppHandle) -> Result<bool, String> {
+    blocking(app, |state| {
+        if secure_prompt::touch_id_available() {
+            if let Some(key) = touch_id_key(&state)? {
+                finish_unlock(&state, *key, insights::EVENT_QUICK_UNLOCK)?;
+                return Ok(true);
+            }
+        }
+        let password =
+            secure_prompt::prompt_password("Enter your master password to unlock the vault")?
+                .ok_or("prompt_cancelled")?;
+        unlock_with_password(&state, &password)
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn is_touch_id_available() -> bool {
+    secure_prompt::touch_id_available()
+}
+
+/// Let `unlock_with_secure_prompt` unlock the active vault with Touch ID, or stop that. The key
+/// is saved in a keychain item only Touch ID opens, then read back once, so turning it on asks
+/// for a finger and fails if Touch ID does not work.
+#[tauri::command]
+pub async fn set_touch_id_unlock(app: AppHandle, enabled: bool) -> Result<(), String> {
+    blocking(app, move |state| {
+        keychain::remove(KC_TOUCH_ID_KEY);
+        if !enabled {
+            keychain::biometric::remove();
+            return Ok(());
+        }
+        if !secure_prompt::touch_id_available() {
+            return Err("Touch ID is not available".to_string());
+        }
+        let vault_id = with_storage(&state, |storage| vault::vault_id(storage))?
+            .ok_or("The vault has no id")?;
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(|key| base64::engine::general_purpose::STANDARD.encode(key))
+            .ok_or("No cached key. Please unlock first.")?;
+        let saved = TouchIdKey { vault_id, key };
+        let json = Zeroizing::new(serde_json::to_vec(&saved).map_err(|e| e.to_string())?);
+        keychain::biometric::save(&json)?;
+        match touch_id_key(&state) {
+            Ok(Some(_)) => Ok(()),
+            failed => {
+                keychain::biometric::remove();
+                Err(failed.err().unwrap_or_else(|| "Touch ID did not confirm".to_string()))
+            }
+        }
+    })
+    .await
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FidoCredential {
+    pub credential_id: String,
+    pub label: String,
+    /// Salt to evaluate the `hmac-secret` / PRF extension with when unlocking.
+    pub salt: String,
+    pub created_at: String,
+}
+
+/// Security keys registered for the open vault. Available before unlock, so the frontend can
+/// build the WebAuthn assertion request.
+#[tauri::command]
+pub fn list_fido_keys(state: State<AppState>) -> Result<Vec<FidoCredential>, String> {
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(vault::fido_keys(&**storage)?
+        .into_iter()
+        .map(|slot| FidoCredential {
+            credential_id: slot.credential_id,
+            label: slot.label,
+            salt: slot.salt,
+            created_at: slot.created_at,
+        })
+        .collect())
+}
+
+/// Register a FIDO2 security key. The frontend runs the WebAuthn ceremony with the PRF
+/// (`hmac-secret`) extension over `salt` and passes the authenticator's output; the vault key
+/// is stored in the vault wrapped under a key derived from that output.
+#[tauri::command]
+pub async fn register_fido_key(
+    app: AppHandle,
+    credential_id: String,
+    label: String,
+    salt: String,
+    hmac_output: String,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Security keys can only be registered for the local vault".to_string());
+        }
+        if credential_id.is_empty() {
+            return Err("Missing credential id".to_string());
+        }
+        let salt_bytes = base64::engine::general_purpose::STANDARD
+            .decode(&salt)
+            .map_err(|e| format!("Invalid salt: {e}"))?;
+        if salt_bytes.len() != 32 {
+            return Err("The hmac-secret salt must be 32 bytes".to_string());
+        }
+        let mut output = base64::engine::general_purpose::STANDARD
+            .decode(&hmac_output)
+            .map_err(|e| format!("Invalid hmac-secret output: {e}"))?;
+
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key. Please unlock first.")?;
+        let kek = wrap::kek_from_hmac_secret(&output);
+        output.zeroize();
+        let mut kek = kek.map_err(|e| e.to_string())?;
+        let wrapped = wrap::wrap_key(&key, &kek);
+        kek.zeroize();
+        let wrapped = wrapped.map_err(|e| e.to_string())?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let mut slots = vault::fido_keys(&**storage)?;
+        slots.retain(|slot| slot.credential_id != credential_id);
+        slots.push(vault::FidoKeySlot {
+            credential_id,
+            label,
+            salt,
+            wrapped_key: base64::engine::general_purpose::STANDARD.encode(wrapped),
+            created_at: state.clock.now().to_rfc3339(),
+        });
+        vault::set_fido_keys(&**storage, &slots)
+    })
+    .await
+}
+
+/// Unlock with the `hmac-secret` output a registered security key produced for its salt.
+/// Returns `false` when the output does not unwrap the vault key.
+#[tauri::command]
+pub async fn unlock_with_fido_key(
+    app: AppHandle,
+    credential_id: String,
+    hmac_output: String,
+) -> Result<bool, String> {
+    blocking(app, move |state| {
+        let slot = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            vault::fido_keys(&**storage)?
+                .into_iter()
+                .find(|slot| slot.credential_id == credential_id)
+                .ok_or("Unknown security key")?
+        };
+        let wrapped = base64::engine::general_purpose::STANDARD
+            .decode(&slot.wrapped_key)
+            .map_err(|e| format!("Invalid FIDO key slot: {e}"))?;
+        let mut output = base64::engine::general_purpose::STANDARD
+            .decode(&hmac_output)
+            .map_err(|e| format!("Invalid hmac-secret output: {e}"))?;
+
+        let kek = wrap::kek_from_hmac_secret(&output);
+        output.zeroize();
+        let mut kek = kek.map_err(|e| e.to_string())?;
+        let unwrapped = wrap::unwrap_key(&wrapped, &kek);
+        kek.zeroize();
+
+        match unwrapped {
+            Ok(key) => finish_unlock(&state, key, insights::EVENT_HARDWARE_KEY_UNLOCK).map(|_| true),
+            Err(_) => Ok(false),
+        }
+    })
+    .await
+}
 
-    let existing = storage.get_verification_token().map_err(|e| e.to_string())?;
-    if existing.is_some() {
-        return Err("Master password already set".to_string());
+#[tauri::command]
+pub fn remove_fido_key(state: State<AppState>, credential_id: String) -> Result<(), String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
     }
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let mut slots = vault::fido_keys(&**storage)?;
+    slots.retain(|slot| slot.credential_id != credential_id);
+    vault::set_fido_keys(&**storage, &slots)
+}
 
-    let token = crypto::create_verification_token(&password).map_err(|e| e.to_string())?;
-    storage.set_verification_token(&token).map_err(|e| e.to_string())?;
+#[derive(Debug, serde::Serialize)]
+pub struct ChallengeResponseStatus {
+    pub enabled: bool,
+    pub slot: Option<u8>,
+    pub enabled_at: Option<String>,
+    /// Whether `ykman` or `ykchalresp` is installed to talk to the YubiKey.
+    pub tool_available: bool,
+}
 
-    let mut key = crypto::derive_master_key(&password).map_err(|e| e.to_string())?;
-    let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    *cached = Some(key);
-    key.zeroize();
+/// Available before unlock, so the unlock screen can tell the user to insert their YubiKey.
+#[tauri::command]
+pub fn get_challenge_response_status(
+    state: State<AppState>,
+) -> Result<ChallengeResponseStatus, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    let config = vault::challenge_response_config(&**storage)?;
+    Ok(ChallengeResponseStatus {
+        enabled: config.is_some(),
+        slot: config.as_ref().map(|c| c.slot),
+        enabled_at: config.map(|c| c.enabled_at),
+        tool_available: challenge_response::is_available(),
+    })
+}
+
+/// Require a YubiKey HMAC-SHA1 slot (1 or 2) in addition to the master password. A random
+/// challenge is stored in the vault and the password slot is re-wrapped under the password
+/// mixed with the key's response.
+#[tauri::command]
+pub async fn enable_challenge_response(
+    app: AppHandle,
+    password: String,
+    slot: u8,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Challenge-response can only be enabled for the local vault".to_string());
+        }
+        if with_storage(&state, vault::challenge_response_config)?.is_some() {
+            return Err("Challenge-response is already enabled".to_string());
+        }
+        // Waits for a touch, so the storage lock is taken only once the key has answered.
+        let challenge = challenge_response::random_challenge();
+        let response = challenge_response::respond(slot, &challenge).map_err(|e| e.to_string())?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        if vault::challenge_response_config(&**storage)?.is_some() {
+            return Err("Challenge-response is already enabled".to_string());
+        }
+        let mut key = vault::unlock(&**storage, &password)?.ok_or("wrong_password")?;
+        let result = vault::kdf_params(&**storage).and_then(|params| {
+            let secret = challenge_response::mix(&password, &response[..]);
+            vault::rewrap(&**storage, &key, &secret, &params)?;
+            let config = vault::ChallengeResponseConfig {
+                slot,
+                challenge: base64::engine::general_purpose::STANDARD.encode(challenge),
+                enabled_at: state.clock.now().to_rfc3339(),
+            };
+            vault::set_challenge_response_config(&**storage, Some(&config)).inspect_err(|_| {
+                // Keep the vault openable with the password alone.
+                let _ = vault::rewrap(&**storage, &key, &password, &params);
+            })
+        });
+        key.zeroize();
+        result
+    })
+    .await
+}
+
+/// Go back to the master password alone. The YubiKey is still needed this one time.
+#[tauri::command]
+pub async fn disable_challenge_response(app: AppHandle, password: String) -> Result<(), String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Challenge-response can only be disabled for the local vault".to_string());
+        }
+        let config = with_storage(&state, vault::challenge_response_config)?
+            .ok_or("Challenge-response is not enabled")?;
+        let response = vault::respond(&config)?;
+        let secret = challenge_response::mix(&password, &response[..]);
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let mut key = vault::unlock(&**storage, &secret)?.ok_or("wrong_password")?;
+        let params = vault::kdf_params(&**storage)?;
+        let result = vault::rewrap(&**storage, &key, &password, &params).and_then(|_| {
+            vault::set_challenge_response_config(&**storage, None).inspect_err(|_| {
+                let _ = vault::rewrap(&**storage, &key, &secret, &params);
+            })
+        });
+        key.zeroize();
+        result
+    })
+    .await
+}
+
+/// Create a recovery code for the unlocked vault, replacing any earlier one, and return the
+/// emergency kit to show or print. The code is not kept anywhere.
+#[tauri::command]
+pub async fn generate_recovery_code(app: AppHandle) -> Result<recovery::EmergencyKit, String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Recovery codes can only be created for the local vault".to_string());
+        }
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key. Please unlock first.")?;
+        let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let code = recovery::generate_code();
+        let canonical = recovery::normalize(&code)?;
+        let created_at = state.clock.now().to_rfc3339();
+        let slot = wrap::wrap_with_secret(&key, &canonical, &vault::kdf_params(&**storage)?);
+        vault::set_recovery_slot(
+            &**storage,
+            Some(&vault::RecoverySlot {
+                slot: slot.map_err(|e| e.to_string())?,
+                created_at: created_at.clone(),
+            }),
+        )?;
+
+        let vault_id = storage
+            .get_meta(vault::META_VAULT_ID)
+            .map_err(|e| e.to_string())?;
+        Ok(recovery::emergency_kit(code, vault_id, db_path, created_at))
+    })
+    .await
+}
+
+/// When the current recovery code was created, if there is one.
+#[tauri::command]
+pub fn get_recovery_code_created_at(state: State<AppState>) -> Result<Option<String>, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(vault::recovery_slot(&**storage)?.map(|slot| slot.created_at))
+}
+
+/// Unlock with a recovery code after the master password was forgotten, and set
+/// `new_password` as the master password. Returns `false` for a wrong code.
+#[tauri::command]
+pub async fn unlock_with_recovery_code(
+    app: AppHandle,
+    code: String,
+    new_password: String,
+) -> Result<bool, String> {
+    blocking(app, move |state| {
+        if new_password.is_empty() {
+            return Err("Master password must not be empty".to_string());
+        }
+        let canonical = match recovery::normalize(&code) {
+            Ok(canonical) => canonical,
+            Err(_) => return Ok(false),
+        };
+        let key = {
+            let guard = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = guard.as_ref().ok_or("Database not initialized")?;
+            let slot = vault::recovery_slot(&**storage)?.ok_or("No recovery code set up")?;
+            let mut key = match wrap::unwrap_with_secret(&slot.slot, &canonical) {
+                Ok(key) => key,
+                Err(_) => return Ok(false),
+            };
+
+            let response = vault::challenge_response(&**storage)?;
+            let secret = vault::password_secret(&new_password, response.as_ref().map(|r| &r[..]));
+            let rewrapped = vault::kdf_params(&**storage)
+                .and_then(|params| vault::rewrap(&**storage, &key, &secret, &params));
+            if let Err(e) = rewrapped {
+                key.zeroize();
+                return Err(e);
+            }
+            key
+        };
+        finish_unlock(&state, key, insights::EVENT_RECOVERY_UNLOCK).map(|_| true)
+    })
+    .await
+}
+
+/// Unlock with a backup file written under the backup passphrase or the recovery code, after
+/// the master password was forgotten, and set `new_password` as the master password. The
+/// backup must come from this vault. Returns `false` for a wrong passphrase or code.
+#[tauri::command]
+pub async fn unlock_with_backup(
+    app: AppHandle,
+    path: String,
+    secret: String,
+    new_password: String,
+) -> Result<bool, String> {
+    blocking(app, move |state| {
+        if new_password.is_empty() {
+            return Err("Master password must not be empty".to_string());
+        }
+        let data = std::fs::read(&path).map_err(|e| format!("Failed to read backup: {e}"))?;
+        let backup = backup_archive::parse(&data)?;
+        let key = {
+            let guard = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = guard.as_ref().ok_or("Database not initialized")?;
+            let mut key = match backup_archive::open_key(&**storage, &backup, &secret) {
+                Ok(key) => key,
+                Err(e) if e == backup_archive::ERR_SECRET => return Ok(false),
+                Err(e) => return Err(e),
+            };
+
+            let response = vault::challenge_response(&**storage)?;
+            let secret = vault::password_secret(&new_password, response.as_ref().map(|r| &r[..]));
+            let rewrapped = vault::kdf_params(&**storage)
+                .and_then(|params| vault::rewrap(&**storage, &key, &secret, &params));
+            if let Err(e) = rewrapped {
+                key.zeroize();
+                return Err(e);
+            }
+            key
+        };
+        finish_unlock(&state, key, insights::EVENT_RECOVERY_UNLOCK).map(|_| true)
+    })
+    .await
+}
+
+/// Set up a duress password for the unlocked vault. Entered at the unlock screen, it opens the
+/// database at `decoy_path` instead, which is created with the duress password as its master
+/// password if it does not exist yet.
+#[tauri::command]
+pub async fn set_duress_password(
+    app: AppHandle,
+    duress_password: String,
+    decoy_path: String,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("A duress password can only be set for the local vault".to_string());
+        }
+        if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+            return Err("No cached key. Please unlock first.".to_string());
+        }
+        if duress_password.is_empty() {
+            return Err("Duress password must not be empty".to_string());
+        }
+        let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
+        if db_path.as_deref() == Some(decoy_path.as_str()) {
+            return Err("The decoy must be a different database file".to_string());
+        }
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        let secret = vault::unlock_secret(&**storage, &duress_password)?;
+        if let Some(mut key) = vault::unlock(&**storage, &secret)? {
+            key.zeroize();
+            return Err("The duress password must differ from the master password".to_string());
+        }
+
+        let decoy = LocalStorage::new(&decoy_path).map_err(|e| e.to_string())?;
+        let initialized = decoy
+            .get_verification_token()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        let mut decoy_key = if initialized {
+            vault::unlock(&decoy, &duress_password)?
+                .ok_or("The duress password does not open the decoy database")?
+        } else {
+            vault::initialize(&decoy, &duress_password)?
+        };
+        decoy_key.zeroize();
+
+        let params = vault::kdf_params(&**storage)?;
+        let slot = vault::seal_duress(&decoy_path, &duress_password, &params)?;
+        vault::set_duress_slot(&**storage, Some(&slot))
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn remove_duress_password(state: State<AppState>) -> Result<(), String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
+    }
+    with_storage(&state, |storage| vault::set_duress_slot(storage, None))
+}
+
+/// Only answered while unlocked, so the locked app does not reveal whether a duress password
+/// is configured.
+#[tauri::command]
+pub fn has_duress_password(state: State<AppState>) -> Result<bool, String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
+    }
+    with_storage(&state, |storage| Ok(vault::duress_slot(storage)?.is_some()))
+}
 
-    let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-    *mp = Some(password.clone());
+/// The security audit log, oldest entry first.
+#[tauri::command]
+pub fn list_audit_events(state: State<AppState>) -> Result<Vec<AuditEvent>, String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
+    }
+    with_storage(&state, |storage| {
+        storage.list_audit_events().map_err(|e| e.to_string())
+    })
+}
+
+/// Check every audit log entry against the HMAC chain.
+#[tauri::command]
+pub async fn verify_audit_chain(app: AppHandle) -> Result<audit::AuditVerification, String> {
+    blocking(app, move |state| {
+        if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+            return Err("No cached key. Please unlock first.".to_string());
+        }
+        with_storage(&state, audit::verify)
+    })
+    .await
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RecoverySharesStatus {
+    pub threshold: u8,
+    pub shares: u8,
+    pub created_at: String,
+}
+
+/// Split a new recovery key into `shares` text shares, any `threshold` of which unlock the
+/// vault, replacing any earlier split. The shares are returned once and not kept anywhere.
+#[tauri::command]
+pub async fn generate_recovery_shares(
+    app: AppHandle,
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<String>, String> {
+    blocking(app, move |state| {
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Recovery shares can only be created for the local vault".to_string());
+        }
+        if threshold < 2 || shares < threshold {
+            return Err("Need at least two shares and no more required than handed out".to_string());
+        }
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key. Please unlock first.")?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let mut recovery_key = wrap::generate_key();
+        let wrapped = wrap::wrap_key(&key, &recovery_key);
+        let split = crypto::sss::split(&recovery_key, threshold, shares);
+        recovery_key.zeroize();
+        let split = split.map_err(|e| e.to_string())?;
+
+        let set_id = hex::encode(&wrap::generate_key()[..4]);
+        vault::set_shamir_slot(
+            &**storage,
+            Some(&vault::ShamirSlot {
+                set_id: set_id.clone(),
+                threshold,
+                shares,
+                wrapped_key: base64::engine::general_purpose::STANDARD
+                    .encode(wrapped.map_err(|e| e.to_string())?),
+                created_at: state.clock.now().to_rfc3339(),
+            }),
+        )?;
+
+        Ok(split
+            .iter()
+            .map(|share| recovery::encode_share(&set_id, threshold, share))
+            .collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn get_recovery_shares_status(
+    state: State<AppState>,
+) -> Result<Option<RecoverySharesStatus>, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(vault::shamir_slot(&**storage)?.map(|slot| RecoverySharesStatus {
+        threshold: slot.threshold,
+        shares: slot.shares,
+        created_at: slot.created_at,
+    }))
+}
+
+/// Unlock with recovery shares from the current split and set `new_password` as the master
+/// password. Returns `false` when the shares do not rebuild the recovery key.
+#[tauri::command]
+pub async fn unlock_with_recovery_shares(
+    app: AppHandle,
+    shares: Vec<String>,
+    new_password: String,
+) -> Result<bool, String> {
+    blocking(app, move |state| {
+        if new_password.is_empty() {
+            return Err("Master password must not be empty".to_string());
+        }
+        let key = {
+            let guard = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = guard.as_ref().ok_or("Database not initialized")?;
+            let slot = vault::shamir_slot(&**storage)?.ok_or("No recovery shares set up")?;
+
+            let mut parsed = Vec::with_capacity(shares.len());
+            for text in &shares {
+                let share = recovery::parse_share(text)?;
+                if share.set_id != slot.set_id || share.threshold != slot.threshold {
+                    return Err("A share belongs to a different or older set".to_string());
+                }
+                if !parsed
+                    .iter()
+                    .any(|s: &crypto::sss::Share| s.index == share.share.index)
+                {
+                    parsed.push(share.share);
+                }
+            }
+            if parsed.len() < slot.threshold as usize {
+                return Err(format!("{} different shares are needed", slot.threshold));
+            }
+
+            let wrapped = base64::engine::general_purpose::STANDARD
+                .decode(&slot.wrapped_key)
+                .map_err(|e| e.to_string())?;
+            let mut recovery_key: [u8; crypto::KEY_LEN] = match crypto::sss::combine(&parsed)
+                .ok()
+                .and_then(|secret| secret.as_slice().try_into().ok())
+            {
+                Some(recovery_key) => recovery_key,
+                None => return Ok(false),
+            };
+            let unwrapped = wrap::unwrap_key(&wrapped, &recovery_key);
+            recovery_key.zeroize();
+            let mut key = match unwrapped {
+                Ok(key) => key,
+                Err(_) => return Ok(false),
+            };
 
-    let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
-    if let Some(ref path) = db_path {
-        keychain::save(KC_DB_PATH, path)?;
-        save_db_folder_if_empty(&derive_folder(path))?;
-    }
-    keychain::save(KC_MASTER_PASSWORD, &password)?;
+            let response = vault::challenge_response(&**storage)?;
+            let secret = vault::password_secret(&new_password, response.as_ref().map(|r| &r[..]));
+            let rewrapped = vault::kdf_params(&**storage)
+                .and_then(|params| vault::rewrap(&**storage, &key, &secret, &params));
+            if let Err(e) = rewrapped {
+                key.zeroize();
+                return Err(e);
+            }
+            key
+        };
+        finish_unlock(&state, key, insights::EVENT_RECOVERY_UNLOCK).map(|_| true)
+    })
+    .await
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn cache_master_key(app: AppHandle, password: String) -> Result<(), String> {
+    blocking(app, move |state| {
+        let unlocked = {
+            let guard = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = guard.as_ref().ok_or("Database not initialized")?;
+            let secret = vault::unlock_secret(&**storage, &password)?;
+            vault::unlock(&**storage, &secret)?
+        };
+        set_cached_key(&state, unlocked.ok_or("wrong_password")?)
+    })
+    .await
 }
 
+/// Unlock with a session saved by an older version, which kept the data key or the master
+/// password in the keychain. Either is used once and then removed.
 #[tauri::command]
-pub fn verify_master_password(state: State<AppState>, password: String) -> Result<bool, String> {
-    let token = {
-        let guard = state.storage.lock().map_err(|e| e.to_string())?;
-        let storage = guard.as_ref().ok_or("Database not initialized")?;
-        storage
-            .get_verification_token()
-            .map_err(|e| e.to_string())?
-            .ok_or("No master password set")?
-    };
+pub async fn restore_saved_session(app: AppHandle) -> Result<bool, String> {
+    blocking(app, move |state| restore_session(&state)).await
+}
 
-    if !crypto::verify_password(&token, &password) {
-        return Ok(false);
+/// Whether the saved session and the quick-unlock slot are for the active vault. They belong to
+/// the vault unlocked last, which with several vaults open need not be the active one.
+fn session_is_for_active_vault(state: &AppState) -> bool {
+    let active = state.db_path.lock().ok().and_then(|p| p.clone());
+    match (keychain::get(KC_DB_PATH), active) {
+        (Some(saved), Some(active)) => saved == active,
+        _ => true,
     }
+}
 
-    let mut key = crypto::derive_master_key(&password).map_err(|e| e.to_string())?;
-    let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    *cached = Some(key);
-    key.zeroize();
-    drop(cached);
-
-    let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-    *mp = Some(password.clone());
-    drop(mp);
-
-    let db_path = state.db_path.lock().map_err(|e| e.to_string())?.clone();
-    if let Some(ref path) = db_path {
-        keychain::save(KC_DB_PATH, path)?;
-        save_db_folder_if_empty(&derive_folder(path))?;
+fn restore_session(state: &AppState) -> Result<bool, String> {
+    if !session_is_for_active_vault(state) {
+        return Ok(false);
+    }
+    if let Some(key) = load_session_key() {
+        keychain::remove(KC_SESSION_KEY);
+        set_cached_key(state, key)?;
+        return Ok(true);
     }
-    keychain::save(KC_MASTER_PASSWORD, &password)?;
 
-    Ok(true)
+    match keychain::get(KC_LEGACY_MASTER_PASSWORD) {
+        Some(password) => unlock_with_password(state, &password),
+        None => Ok(false),
+    }
 }
 
-#[tauri::command]
-pub fn cache_master_key(state: State<AppState>, password: String) -> Result<(), String> {
-    let mut key = crypto::derive_master_key(&password).map_err(|e| e.to_string())?;
+/// Forget the vault key and everything decrypted with it, and the keys of the vaults open in
+/// the background too.
+pub(crate) fn lock_session(state: &AppState) -> Result<(), String> {
+    state.vaults.lock().map_err(|e| e.to_string())?.lock_all();
     let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    *cached = Some(key);
-    key.zeroize();
-
-    let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-    *mp = Some(password);
+    cached.clear();
+    crypto::nonce::uninstall();
+    ephemeral::clear();
     Ok(())
 }
 
 #[tauri::command]
 pub fn clear_cached_key(state: State<AppState>) -> Result<(), String> {
-    let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    if let Some(ref mut k) = *cached {
-        k.zeroize();
-    }
-    *cached = None;
-
-    let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-    *mp = None;
-    Ok(())
+    lock_session(&state)
 }
 
 #[tauri::command]
@@ -178,8 +1303,15 @@ pub fn get_setting(state: State<AppState>, key: String) -> Result<Option<String>
     storage.get_setting(&key).map_err(|e| e.to_string())
 }
 
+/// Write a setting. Those with a command of their own, which checks the value, are refused.
 #[tauri::command]
 pub fn set_setting(state: State<AppState>, key: String, value: String) -> Result<(), String> {
+    if key == vault::META_KDF_PARAMS {
+        return Err("KDF parameters can only be changed with set_kdf_params".to_string());
+    }
+    if pin_attempts::SETTINGS.contains(&key.as_str()) {
+        return Err("PIN attempts can only be changed with set_pin_attempt_policy".to_string());
+    }
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let storage = storage.as_ref().ok_or("Database not initialized")?;
     storage.set_setting(&key, &value).map_err(|e| e.to_string())
@@ -191,167 +1323,444 @@ pub fn is_database_initialized(state: State<AppState>) -> bool {
 }
 
 #[tauri::command]
-pub fn setup_pin(
-    state: State<AppState>,
-    pin: String,
-    master_password: String,
-) -> Result<(), String> {
-    let pin_token = crypto::create_pin_verification_token(&pin).map_err(|e| e.to_string())?;
-    let pin_hash_b64 = base64::engine::general_purpose::STANDARD.encode(&pin_token);
+pub async fn setup_pin(app: AppHandle, pin: String) -> Result<(), String> {
+    setup_quick_unlock(app, QuickUnlockKind::Pin, pin).await
+}
 
-    let db_path = state
-        .db_path
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("No database path")?;
+/// Set up (or replace) the quick-unlock factor for the unlocked vault.
+#[tauri::command]
+pub async fn setup_quick_unlock(
+    app: AppHandle,
+    kind: QuickUnlockKind,
+    secret: String,
+) -> Result<(), String> {
+    blocking(app, move |state| {
+        let secret = kind.normalize(&secret)?;
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key. Please unlock first.")?;
 
-    keychain::save(KC_DB_PATH, &db_path)?;
-    keychain::save(KC_MASTER_PASSWORD, &master_password)?;
-    keychain::save(KC_PIN_HASH, &pin_hash_b64)?;
+        let db_path = state
+            .db_path
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("No database path")?;
 
-    Ok(())
+        keychain::save(KC_DB_PATH, &db_path)?;
+        save_pin_slot(&state, &key, kind, &secret)
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn verify_pin(state: State<AppState>, pin: String) -> Result<String, String> {
-    let pin_hash_b64 = keychain::get(KC_PIN_HASH).ok_or("No PIN configured")?;
-    let pin_hash = base64::engine::general_purpose::STANDARD
-        .decode(&pin_hash_b64)
-        .map_err(|e| format!("Invalid PIN hash: {e}"))?;
-
-    if !crypto::verify_pin(&pin_hash, &pin) && !crypto::verify_password(&pin_hash, &pin) {
-        return Err("invalid_pin".to_string());
-    }
+pub fn get_quick_unlock_kind() -> Option<QuickUnlockKind> {
+    quick_unlock_kind()
+}
 
-    let master_password = keychain::get(KC_MASTER_PASSWORD)
-        .ok_or("Master password not found in keychain")?;
+/// Unwrap the vault key with the quick-unlock factor straight into `cached_key`, without the
+/// password ever leaving the backend.
+#[tauri::command]
+pub async fn verify_quick_unlock(app: AppHandle, secret: String) -> Result<(), String> {
+    verify_pin(app, secret).await
+}
 
-    let mut key = crypto::derive_master_key(&master_password).map_err(|e| e.to_string())?;
-    let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-    *cached = Some(key);
-    key.zeroize();
-    drop(cached);
+/// Unwrap the vault key with the PIN straight into `cached_key`. A PIN set up by an older
+/// version is checked against its hash once and then moved to a PIN slot.
+#[tauri::command]
+pub async fn verify_pin(app: AppHandle, pin: String) -> Result<(), String> {
+    blocking(app, move |state| {
+        if !session_is_for_active_vault(&state) {
+            return Err("Quick unlock is set up for another vault".to_string());
+        }
+        with_storage(&state, |s| pin_attempts::check(s, &*state.clock))?;
+        if let Some(slot) = pin_slot()? {
+            let kind = quick_unlock_kind().unwrap_or(QuickUnlockKind::Pin);
+            let secret = kind.normalize(&pin).map_err(|_| "invalid_pin".to_string())?;
+            let key = wrap::unwrap_with_secret(&slot, &secret).map_err(|_| pin_failure(&state))?;
+            let _ = keychain::unlock_with_pin(&secret);
+            set_cached_key(&state, key)?;
+            insights::record_event(&state, insights::EVENT_QUICK_UNLOCK);
+            audit::record_event(&state, audit::EVENT_UNLOCK, insights::EVENT_QUICK_UNLOCK);
+            return Ok(());
+        }
 
-    let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-    *mp = Some(master_password.clone());
-    drop(mp);
+        if !verify_legacy_pin(&pin)? {
+            return Err(pin_failure(&state));
+        }
+        if !restore_session(&state)? {
+            return Err("Vault key not found in keychain".to_string());
+        }
 
-    Ok(master_password)
+        let key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("Vault key not found in keychain")?;
+        save_pin_slot(&state, &key, QuickUnlockKind::Pin, &pin)
+    })
+    .await
 }
 
 #[tauri::command]
 pub fn has_saved_session() -> bool {
     keychain::get(KC_DB_PATH).is_some()
-        && keychain::get(KC_MASTER_PASSWORD).is_some()
+        && (keychain::get(KC_SESSION_KEY).is_some()
+            || keychain::get(KC_PIN_SLOT).is_some()
+            || keychain::get(KC_LEGACY_MASTER_PASSWORD).is_some())
 }
 
 #[tauri::command]
 pub fn has_pin() -> bool {
-    keychain::get(KC_PIN_HASH).is_some()
+    quick_unlock_kind() == Some(QuickUnlockKind::Pin)
 }
 
+/// Drop the quick-unlock factor and the vault key wrapped under it. The next start asks for the
+/// master password.
 #[tauri::command]
-pub fn get_saved_master_password() -> Option<String> {
-    keychain::get(KC_MASTER_PASSWORD)
+pub fn remove_pin() {
+    // Without a PIN, a PIN-protected keychain file falls back to the machine-bound key.
+    if keychain::backend() == keychain::Backend::FilePin {
+        let _ = keychain::migrate(keychain::Backend::FileMachine, None, None);
+    }
+    keychain::remove(KC_PIN_SLOT);
+    keychain::remove(KC_QUICK_UNLOCK_KIND);
+    keychain::remove(KC_LEGACY_PIN_HASH);
+    let _ = display_info::set_quick_unlock(None);
 }
 
 #[tauri::command]
-pub fn remove_pin() {
-    keychain::remove(KC_PIN_HASH);
+pub fn get_pin_attempt_status(
+    state: State<AppState>,
+) -> Result<pin_attempts::AttemptStatus, String> {
+    with_storage(&state, |s| pin_attempts::status(s, &*state.clock))
 }
 
+/// Change after how many failed quick-unlock attempts the PIN is locked out and, optionally,
+/// the keychain session wiped. Requires an unlocked vault.
 #[tauri::command]
-pub fn get_saved_db_path() -> Option<String> {
-    keychain::get(KC_DB_PATH)
+pub fn set_pin_attempt_policy(
+    state: State<AppState>,
+    lockout_after: u32,
+    wipe_after: Option<u32>,
+) -> Result<(), String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
+    }
+    let policy = pin_attempts::AttemptPolicy {
+        lockout_after,
+        wipe_after,
+    };
+    with_storage(&state, |storage| pin_attempts::set_policy(storage, &policy))
 }
 
-#[tauri::command]
-pub fn clear_saved_session() {
-    keychain::remove(KC_DB_PATH);
-    keychain::remove(KC_DB_FOLDER);
-    keychain::remove(KC_MASTER_PASSWORD);
-    keychain::remove(KC_PIN_HASH);
+#[derive(Debug, serde::Serialize)]
+pub struct KeychainBackendStatus {
+    /// `os`, `file-machine` or `file-pin`.
+    pub backend: String,
+    pub locked: bool,
+    pub file_path: Option<String>,
 }
 
 #[tauri::command]
-pub fn change_pin(old_pin: String, new_pin: String) -> Result<(), String> {
-    let pin_hash_b64 = keychain::get(KC_PIN_HASH).ok_or("No PIN configured")?;
-    let pin_hash = base64::engine::general_purpose::STANDARD
-        .decode(&pin_hash_b64)
-        .map_err(|e| format!("Invalid PIN hash: {e}"))?;
-
-    if !crypto::verify_pin(&pin_hash, &old_pin) && !crypto::verify_password(&pin_hash, &old_pin) {
-        return Err("invalid_pin".to_string());
+pub fn get_keychain_backend() -> KeychainBackendStatus {
+    let backend = keychain::backend();
+    KeychainBackendStatus {
+        backend: backend.as_str().to_string(),
+        locked: keychain::is_locked(),
+        file_path: match backend {
+            keychain::Backend::Os => None,
+            _ => keychain::file::path().map(|p| p.to_string_lossy().to_string()),
+        },
     }
+}
 
-    let new_token = crypto::create_pin_verification_token(&new_pin).map_err(|e| e.to_string())?;
-    let new_hash_b64 = base64::engine::general_purpose::STANDARD.encode(&new_token);
-    keychain::save(KC_PIN_HASH, &new_hash_b64)?;
+#[derive(Debug, serde::Serialize)]
+pub struct PlatformProtection {
+    pub platform: String,
+    /// Same values as `KeychainBackendStatus::backend`.
+    pub keychain_backend: String,
+    /// Whether new unlock secrets are sealed with DPAPI-NG before they are stored.
+    pub dpapi_ng: bool,
+    /// The DPAPI-NG protection rule in use, e.g. `LOCAL=user AND LOCAL=machine`.
+    pub protection_descriptor: Option<String>,
+    /// Sealing is not tied to Windows Hello yet.
+    pub hello_bound: bool,
+    pub session_key_sealed: bool,
+    pub pin_slot_sealed: bool,
+}
 
-    Ok(())
+/// What protects the saved unlock secrets on this machine beyond the keychain itself.
+#[tauri::command]
+pub fn get_platform_protection_info() -> PlatformProtection {
+    let descriptor = keychain::dpapi::active_descriptor();
+    PlatformProtection {
+        platform: std::env::consts::OS.to_string(),
+        keychain_backend: keychain::backend().as_str().to_string(),
+        dpapi_ng: descriptor.is_some(),
+        protection_descriptor: descriptor.map(str::to_string),
+        hello_bound: false,
+        session_key_sealed: keychain::is_sealed(KC_SESSION_KEY),
+        pin_slot_sealed: keychain::is_sealed(KC_PIN_SLOT),
+    }
 }
 
 #[tauri::command]
-pub fn get_db_folder() -> Option<String> {
-    keychain::get(KC_DB_FOLDER)
+pub fn get_keyring_status() -> keychain::secret_service::KeyringStatus {
+    keychain::secret_service::status()
 }
 
+/// Ask the Secret Service to unlock the keyring collection in use.
 #[tauri::command]
-pub fn change_db_folder(state: State<AppState>, new_folder: String) -> Result<Option<String>, String> {
-    let current_db_path = state.db_path.lock().map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("No database path set")?;
+pub fn unlock_keyring() -> Result<keychain::secret_service::KeyringStatus, String> {
+    keychain::secret_service::unlock()?;
+    Ok(keychain::secret_service::status())
+}
 
-    let old_folder = keychain::get(KC_DB_FOLDER);
-    let current_parent = derive_folder(&current_db_path);
-    let needs_move = old_folder.as_deref() == Some(&current_parent);
+/// Keep saved sessions in another Secret Service collection, or in the default one for `None`.
+#[tauri::command]
+pub fn set_keyring_collection(
+    collection: Option<String>,
+) -> Result<keychain::secret_service::KeyringStatus, String> {
+    keychain::set_collection(collection.as_deref())?;
+    Ok(keychain::secret_service::status())
+}
 
-    keychain::save(KC_DB_FOLDER, &new_folder)?;
+#[derive(Debug, serde::Serialize)]
+pub struct HealthReport {
+    pub database_open: bool,
+    pub unlocked: bool,
+    pub keychain: KeychainBackendStatus,
+    pub keyring: keychain::secret_service::KeyringStatus,
+}
 
-    if !needs_move {
-        return Ok(None);
+/// Local state of the app: the open database, the vault lock and where saved sessions go.
+#[tauri::command]
+pub fn health_check(state: State<AppState>) -> Result<HealthReport, String> {
+    Ok(HealthReport {
+        database_open: state.storage.lock().map_err(|e| e.to_string())?.is_some(),
+        unlocked: state.cached_key.lock().map_err(|e| e.to_string())?.is_some(),
+        keychain: get_keychain_backend(),
+        keyring: keychain::secret_service::status(),
+    })
+}
+
+/// Move saved sessions and passwords to another keychain backend. Protecting the keychain
+/// file with the PIN requires the vault to be unlocked and the current PIN.
+#[tauri::command]
+pub fn migrate_keychain_backend(
+    state: State<AppState>,
+    target: String,
+    pin: Option<String>,
+) -> Result<(), String> {
+    let target = keychain::Backend::parse(&target)
+        .ok_or_else(|| format!("Unknown keychain backend: {target}"))?;
+    if target != keychain::Backend::FilePin {
+        return keychain::migrate(target, None, None);
     }
 
-    let filename = Path::new(&current_db_path)
-        .file_name()
-        .ok_or("Invalid database path")?
-        .to_string_lossy()
-        .to_string();
+    let slot = pin_slot()?.ok_or("Set up a PIN first")?;
+    let kind = quick_unlock_kind().unwrap_or(QuickUnlockKind::Pin);
+    let secret = kind
+        .normalize(pin.as_deref().unwrap_or(""))
+        .map_err(|_| "invalid_pin".to_string())?;
+    let mut key = wrap::unwrap_with_secret(&slot, &secret).map_err(|_| "invalid_pin".to_string())?;
+    let cached = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(|c| *c == key);
+    let migrated = if cached == Some(true) {
+        keychain::migrate(target, Some(&secret), Some(&key))
+    } else {
+        Err("No cached key. Please unlock first.".to_string())
+    };
+    key.zeroize();
+    migrated
+}
+
+#[tauri::command]
+pub fn get_saved_db_path() -> Option<String> {
+    keychain::get(KC_DB_PATH)
+}
 
-    let new_path = format!(
-        "{}/{}",
-        new_folder.trim_end_matches('/'),
-        filename
-    );
+#[tauri::command]
+pub fn clear_saved_session() {
+    keychain::remove(KC_DB_PATH);
+    keychain::remove(KC_DB_FOLDER);
+    keychain::remove(KC_SESSION_KEY);
+    keychain::remove(KC_LEGACY_MASTER_PASSWORD);
+    keychain::remove(KC_PIN_SLOT);
+    keychain::remove(KC_QUICK_UNLOCK_KIND);
+    keychain::remove(KC_LEGACY_PIN_HASH);
+    keychain::remove(KC_TOUCH_ID_KEY);
+    keychain::biometric::remove();
+    let _ = display_info::forget_session();
+}
+
+/// What the lock screen may show before unlock, for the vault at `db_path` or the one opened
+/// last. Safe to call before `init_database`: it reads neither the database, the keychain nor
+/// any key material.
+#[tauri::command]
+pub fn get_vault_display_info(db_path: Option<String>) -> Option<VaultDisplayInfo> {
+    display_info::get(db_path.as_deref())
+}
 
-    if Path::new(&new_path).exists() {
-        return Err("Database file already exists at target path".to_string());
+/// Name and color the open vault on the lock screen. Empty values reset them.
+#[tauri::command]
+pub fn set_vault_display_info(
+    state: State<AppState>,
+    name: Option<String>,
+    color: Option<String>,
+) -> Result<Option<VaultDisplayInfo>, String> {
+    if state.cached_key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("No cached key. Please unlock first.".to_string());
     }
+    let db_path = state
+        .db_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Database not initialized")?;
+    display_info::set_appearance(&db_path, name.as_deref(), color.as_deref())?;
+    Ok(display_info::get(Some(&db_path)))
+}
 
-    std::fs::copy(&current_db_path, &new_path)
-        .map_err(|e| format!("Failed to copy database: {e}"))?;
+/// Replace the quick-unlock secret. The kind stays as it is: a pattern or word phrase is
+/// replaced by another of the same kind.
+#[tauri::command]
+pub async fn change_pin(app: AppHandle, old_pin: String, new_pin: String) -> Result<(), String> {
+    blocking(app, move |state| {
+        // Legacy PINs from before quick-unlock kinds were always PINs.
+        let kind = quick_unlock_kind().unwrap_or(QuickUnlockKind::Pin);
+        let mut key = match pin_slot()? {
+            Some(slot) => {
+                let old_secret = kind.normalize(&old_pin).map_err(|_| "invalid_pin".to_string())?;
+                wrap::unwrap_with_secret(&slot, &old_secret).map_err(|_| "invalid_pin".to_string())?
+            }
+            None => {
+                if !verify_legacy_pin(&old_pin)? {
+                    return Err("invalid_pin".to_string());
+                }
+                load_session_key().ok_or("Vault key not found in keychain")?
+            }
+        };
 
-    let storage = LocalStorage::new(&new_path).map_err(|e| e.to_string())?;
+        let saved = kind
+            .normalize(&new_pin)
+            .and_then(|secret| save_pin_slot(&state, &key, kind, &secret));
+        key.zeroize();
+        saved
+    })
+    .await
+}
 
-    {
-        let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
-        *guard = Some(Box::new(storage));
-    }
-    {
-        let mut path_guard = state.db_path.lock().map_err(|e| e.to_string())?;
-        *path_guard = Some(new_path.clone());
+/// Move the open vault's files to `new_path` and reopen it there, keeping its saved session.
+/// On any failure the vault stays open where it was. Returns the new path.
+#[tauri::command]
+pub async fn move_database(app: AppHandle, new_path: String) -> Result<String, String> {
+    blocking(app, move |state| {
+        let current_db_path = state
+            .db_path
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("No database path set")?;
+        if !Path::new(&new_path).parent().is_some_and(Path::is_dir) {
+            return Err("The target folder does not exist".to_string());
+        }
+        move_open_vault(&state, &current_db_path, &new_path)?;
+        Ok(new_path)
+    })
+    .await
+}
+
+/// Copy the open vault from `current_db_path` to `new_path` while holding its storage, open
+/// the copy, then switch over and remove the originals. Until the switch, a failure only
+/// removes the copy.
+fn move_open_vault(state: &AppState, current_db_path: &str, new_path: &str) -> Result<(), String> {
+    let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = guard.as_ref().ok_or("Database not initialized")?;
+    if storage.is_read_only() {
+        return Err("The vault is open read-only".to_string());
     }
+    storage.checkpoint().map_err(|e| e.to_string())?;
+    relocate::copy_vault(current_db_path, new_path)?;
+
+    let opened = LocalStorage::new(new_path).map_err(|e| e.to_string()).and_then(|storage| {
+        if let Some(unsealed) = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(|key| storage.unseal(key))
+        {
+            unsealed.map_err(|e| e.to_string())?;
+        }
+        if keychain::get(KC_DB_PATH).as_deref() == Some(current_db_path) {
+            keychain::save(KC_DB_PATH, new_path)?;
+        }
+        Ok(storage)
+    });
+    let storage = match opened {
+        Ok(storage) => storage,
+        Err(e) => {
+            relocate::remove_vault(new_path);
+            return Err(e);
+        }
+    };
+
+    // Closes the old connection, so its files can go.
+    *guard = Some(Box::new(storage));
+    *state.db_path.lock().map_err(|e| e.to_string())? = Some(new_path.to_string());
+    drop(guard);
+    let _ = display_info::moved(current_db_path, new_path);
+    relocate::remove_vault(current_db_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_db_folder() -> Option<String> {
+    keychain::get(KC_DB_FOLDER)
+}
+
+#[tauri::command]
+pub async fn change_db_folder(app: AppHandle, new_folder: String) -> Result<Option<String>, String> {
+    blocking(app, move |state| {
+        let current_db_path = state.db_path.lock().map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("No database path set")?;
+
+        let old_folder = keychain::get(KC_DB_FOLDER);
+        let current_parent = derive_folder(&current_db_path);
+        let needs_move = old_folder.as_deref() == Some(&current_parent);
 
-    keychain::save(KC_DB_PATH, &new_path)?;
+        keychain::save(KC_DB_FOLDER, &new_folder)?;
 
-    let _ = std::fs::remove_file(&current_db_path);
+        if !needs_move {
+            return Ok(None);
+        }
 
-    Ok(Some(new_path))
+        let filename = Path::new(&current_db_path)
+            .file_name()
+            .ok_or("Invalid database path")?
+            .to_string_lossy()
+            .to_string();
+
+        let new_path = format!(
+            "{}/{}",
+            new_folder.trim_end_matches('/'),
+            filename
+        );
+
+        move_open_vault(&state, &current_db_path, &new_path)?;
+        Ok(Some(new_path))
+    })
+    .await
 }
 
 pub(crate) fn reencrypt_storage(
     storage: &dyn StorageProvider,
+    clock: &dyn Clock,
     old_key: &[u8; crypto::KEY_LEN],
     new_key: &[u8; crypto::KEY_LEN],
 ) -> Result<u32, String> {
@@ -360,33 +1769,46 @@ pub(crate) fn reencrypt_storage(
 
     let mut v2_project_ids = Vec::new();
 
-    for p in &projects {
+    // Decrypt and re-encrypt on the pipeline's worker thread; `None` for projects that are not
+    // under the master key.
+    let reencrypt = |p: Project| -> Result<Option<Project>, String> {
         let is_master = if !p.key_check.is_empty() {
             crypto::try_decrypt_with_key(&p.key_check, old_key).is_some()
         } else {
             false
         };
+        if !is_master {
+            return Ok(None);
+        }
 
-        if is_master {
-            let content_bytes = crypto::try_decrypt_with_key(&p.encrypted_content, old_key)
-                .ok_or_else(|| format!("Failed to decrypt content for project {}", p.id))?;
-
-            let new_enc_content =
-                crypto::encrypt_with_key(&content_bytes, new_key).map_err(|e| e.to_string())?;
-            let new_key_check =
-                crypto::encrypt_with_key(b"mk", new_key).map_err(|e| e.to_string())?;
-
-            let mut updated = p.clone();
-            updated.encrypted_content = new_enc_content;
-            updated.key_check = new_key_check;
-            storage.update_project(&updated).map_err(|e| e.to_string())?;
-
-            v2_project_ids.push(p.id.clone());
-            count += 1;
+        let (id, name, portable) = (&p.id, &p.name, p.server_id.is_some());
+        let content_bytes =
+            crypto::content::decrypt(&p.encrypted_content, old_key, id, name, portable)
+                .map_err(|_| format!("Failed to decrypt content for project {}", p.id))?;
+
+        let bind = crypto::is_aad_bound(&p.encrypted_content);
+        let new_enc_content = crypto::content::encrypt(&content_bytes, new_key, id, name, bind)
+            .map_err(|e| e.to_string())?;
+        let new_key_check =
+            crypto::encrypt_with_key(b"mk", new_key).map_err(|e| e.to_string())?;
+
+        let mut updated = p;
+        updated.encrypted_content = new_enc_content;
+        updated.key_check = new_key_check;
+        Ok(Some(updated))
+    };
+    pipeline::run(projects.into_iter(), PipelineOptions::default(), reencrypt, |batch| {
+        for updated in batch {
+            if let Some(updated) = updated? {
+                storage.update_project(&updated).map_err(|e| e.to_string())?;
+                v2_project_ids.push((updated.id, updated.server_id.is_some()));
+                count += 1;
+            }
         }
-    }
+        Ok::<_, String>(())
+    })?;
 
-    for pid in &v2_project_ids {
+    for (pid, portable) in &v2_project_ids {
         let backups = storage.list_backups(pid).map_err(|e| e.to_string())?;
         for b in &backups {
             let is_master_backup = if !b.key_check.is_empty() {
@@ -396,11 +1818,15 @@ pub(crate) fn reencrypt_storage(
             };
 
             if is_master_backup {
-                let content_bytes = crypto::try_decrypt_with_key(&b.encrypted_content, old_key)
-                    .unwrap_or_default();
+                let (id, name) = (&b.project_id, &b.name);
+                let content_bytes =
+                    crypto::content::decrypt(&b.encrypted_content, old_key, id, name, *portable)
+                        .unwrap_or_default();
 
+                let bind = crypto::is_aad_bound(&b.encrypted_content);
                 let new_enc_content =
-                    crypto::encrypt_with_key(&content_bytes, new_key).map_err(|e| e.to_string())?;
+                    crypto::content::encrypt(&content_bytes, new_key, id, name, bind)
+                        .map_err(|e| e.to_string())?;
                 let new_key_check =
                     crypto::encrypt_with_key(b"mk", new_key).map_err(|e| e.to_string())?;
 
@@ -411,55 +1837,78 @@ pub(crate) fn reencrypt_storage(
             }
         }
     }
+    versions::reencrypt(storage, old_key, new_key)?;
+    attachments::reencrypt(storage, old_key, new_key)?;
+    custom_fields::reencrypt(storage, old_key, new_key)?;
+    totp::reencrypt(storage, old_key, new_key)?;
+    password_history::reencrypt(storage, old_key, new_key)?;
+    drafts::reencrypt(storage, old_key, new_key)?;
+    checklist::reencrypt(storage, clock, old_key, new_key)?;
+    tags::reencrypt(storage, old_key, new_key)?;
+    appearance::reencrypt(storage, old_key, new_key)?;
+    folders::reencrypt(storage, clock, old_key, new_key)?;
+    templates::reencrypt(storage, clock, old_key, new_key)?;
+    search::rebuild_index(storage, new_key)?;
 
     Ok(count)
 }
 
 #[tauri::command]
-pub fn change_master_password(
-    state: State<AppState>,
+pub async fn change_master_password(
+    app: AppHandle,
     current_password: String,
     new_password: String,
 ) -> Result<u32, String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    blocking(app, move |state| {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
 
-    let token = storage
-        .get_verification_token()
-        .map_err(|e| e.to_string())?
-        .ok_or("No master password set")?;
+        let token = storage
+            .get_verification_token()
+            .map_err(|e| e.to_string())?
+            .ok_or("No master password set")?;
 
-    if !crypto::verify_password(&token, &current_password) {
-        return Err("wrong_password".to_string());
-    }
+        // With challenge-response the YubiKey is asked once; both secrets use the same response.
+        let response = vault::challenge_response(&**storage)?;
+        let response = response.as_ref().map(|r| &r[..]);
+        let current_secret = vault::password_secret(&current_password, response);
 
-    if current_password == new_password {
-        return Err("same_password".to_string());
-    }
+        if !crypto::verify_password(&token, &current_secret) {
+            audit::record(&**storage, &*state.clock, audit::EVENT_UNLOCK_FAILED, "change_master_password");
+            return Err("wrong_password".to_string());
+        }
 
-    let old_key = crypto::derive_master_key(&current_password).map_err(|e| e.to_string())?;
-    let new_key = crypto::derive_master_key(&new_password).map_err(|e| e.to_string())?;
+        if current_password == new_password {
+            return Err("same_password".to_string());
+        }
 
-    let count = reencrypt_storage(&**storage, &old_key, &new_key)?;
+        let mut key = vault::unlock(&**storage, &current_secret)?.ok_or("wrong_password")?;
+        let params = vault::kdf_params(&**storage)?;
+        let new_secret = vault::password_secret(&new_password, response);
+        let rewrapped = vault::rewrap(&**storage, &key, &new_secret, &params);
+        let count = master_key_project_count(&**storage, &key);
+        key.zeroize();
+        rewrapped?;
+        audit::record(&**storage, &*state.clock, audit::EVENT_MASTER_PASSWORD_CHANGED, "");
+
+        Ok(count)
+    })
+    .await
+}
 
-    let new_token =
-        crypto::create_verification_token(&new_password).map_err(|e| e.to_string())?;
+/// Projects protected by the vault key rather than a custom password.
+fn master_key_project_count(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> u32 {
     storage
-        .set_verification_token(&new_token)
-        .map_err(|e| e.to_string())?;
-
-    {
-        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
-        *cached = Some(new_key);
-    }
-    {
-        let mut mp = state.master_password.lock().map_err(|e| e.to_string())?;
-        *mp = Some(new_password.clone());
-    }
-
-    keychain::save(KC_MASTER_PASSWORD, &new_password)?;
-
-    Ok(count)
+        .list_projects()
+        .map(|projects| {
+            projects
+                .iter()
+                .filter(|p| {
+                    !p.key_check.is_empty() && crypto::try_decrypt_with_key(&p.key_check, key).is_some()
+                })
+                .count() as u32
+        })
+        .unwrap_or(0)
 }
 
 #[tauri::command]
@@ -506,8 +1955,310 @@ pub fn open_local_database(state: State<AppState>, db_path: String) -> Result<()
     keychain::save(KC_DB_PATH, &db_path)?;
     keychain::save(KC_DB_FOLDER, &folder)?;
 
-    keychain::remove(KC_MASTER_PASSWORD);
-    keychain::remove(KC_PIN_HASH);
+    keychain::remove(KC_SESSION_KEY);
+    keychain::remove(KC_LEGACY_MASTER_PASSWORD);
+    keychain::remove(KC_PIN_SLOT);
+    keychain::remove(KC_QUICK_UNLOCK_KIND);
+    keychain::remove(KC_LEGACY_PIN_HASH);
+    let _ = display_info::set_quick_unlock(None);
 
     Ok(())
 }
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct CreateVaultOptions {
+    /// Explicit Argon2 parameters; take precedence over calibration.
+    pub kdf_params: Option<crypto::KdfParams>,
+    /// Pick the strongest benchmark candidate that unlocks within this many milliseconds.
+    pub calibrate_ms: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct CreateVaultResult {
+    pub db_path: String,
+    pub vault_id: String,
+    pub format_version: u32,
+    pub kdf_params: crypto::KdfParams,
+    pub kdf_calibrated: bool,
+    pub created_at: String,
+}
+
+/// Create a new local vault in one step: database, vault metadata, KDF parameters and
+/// verification token. If any step fails the half-created database is removed again.
+#[tauri::command]
+pub async fn create_vault(
+    app: AppHandle,
+    path: String,
+    master_password: String,
+    options: Option<CreateVaultOptions>,
+) -> Result<CreateVaultResult, String> {
+    blocking(app, move |state| {
+        let options = options.unwrap_or_default();
+        if master_password.is_empty() {
+            return Err("Master password must not be empty".to_string());
+        }
+        if Path::new(&path).exists() {
+            return Err("Database file already exists at this path".to_string());
+        }
+
+        let (kdf_params, kdf_calibrated) = match (options.kdf_params, options.calibrate_ms) {
+            (Some(params), _) => (params, false),
+            (None, Some(target_ms)) => (crate::commands::kdf::calibrate(target_ms)?, true),
+            (None, None) => (crypto::KdfParams::default(), false),
+        };
+        kdf_params.validate().map_err(|e| e.to_string())?;
+
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Cannot create db folder: {e}"))?;
+        }
+
+        let created_at = state.clock.now().to_rfc3339();
+        let bootstrap = || -> Result<(LocalStorage, String, [u8; crypto::KEY_LEN]), String> {
+            let storage = LocalStorage::new(&path).map_err(|e| e.to_string())?;
+            let vault_id = vault::write_identity(&storage, &created_at)?;
+            vault::set_kdf_params(&storage, &kdf_params)?;
+            let key = vault::initialize(&storage, &master_password)?;
+            Ok((storage, vault_id, key))
+        };
+
+        let (storage, vault_id, mut key) = match bootstrap() {
+            Ok(created) => created,
+            Err(e) => {
+                for file in wipe::database_files(&path) {
+                    let _ = std::fs::remove_file(file);
+                }
+                return Err(e);
+            }
+        };
+
+        {
+            let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
+            *guard = Some(Box::new(storage));
+        }
+        {
+            let mut path_guard = state.db_path.lock().map_err(|e| e.to_string())?;
+            *path_guard = Some(path.clone());
+        }
+        let saved = save_session(&state);
+        set_cached_key(&state, key)?;
+        key.zeroize();
+        saved?;
+
+        Ok(CreateVaultResult {
+            db_path: path,
+            vault_id,
+            format_version: vault::VAULT_FORMAT_VERSION,
+            kdf_params,
+            kdf_calibrated,
+            created_at,
+        })
+    })
+    .await
+}
+
+/// Open a throwaway vault in memory, filled with sample projects (see `demo`), for trying the
+/// app out. Its key is random and kept only in memory; nothing goes to disk or the keychain, so
+/// the vault is gone once another one is opened or the app quits. Whatever vault was open
+/// before is closed.
+#[tauri::command]
+pub async fn init_demo_vault(app: AppHandle) -> Result<(), String> {
+    blocking(app, move |state| {
+        let storage = LocalStorage::new(":memory:").map_err(|e| e.to_string())?;
+        let mut key = wrap::generate_key();
+        let seeded = vault::write_identity(&storage, &state.clock.now().to_rfc3339())
+            .and_then(|_| demo::seed(&storage, &*state.clock, &key));
+        if let Err(e) = seeded {
+            key.zeroize();
+            return Err(e);
+        }
+
+        *state.storage.lock().map_err(|e| e.to_string())? = Some(Box::new(storage));
+        *state.db_path.lock().map_err(|e| e.to_string())? = None;
+        *state.active_context.lock().map_err(|e| e.to_string())? = "local".to_string();
+        ephemeral::clear();
+        state.cached_key.lock().map_err(|e| e.to_string())?.set(key);
+        install_nonce_counter(&state, &key);
+        require_bound_content(&state, &key);
+        key.zeroize();
+        Ok(())
+    })
+    .await
+}
+
+/// Token to pass to `panic_wipe` to also erase the databases. Valid once, for one minute.
+#[tauri::command]
+pub fn request_panic_wipe_token(state: State<AppState>) -> String {
+    wipe::issue_panic_token(&*state.clock)
+}
+
+#[tauri::command]
+pub fn panic_wipe(
+    state: State<AppState>,
+    confirmation_token: Option<String>,
+) -> Result<wipe::PanicWipeReport, String> {
+    wipe::panic_wipe(&state, confirmation_token.as_deref())
+}
+
+const DESTROY_CONFIRMATION_PHRASE: &str = "DESTROY MY VAULT";
+
+#[derive(Debug, serde::Serialize)]
+pub struct DestroyedFile {
+    pub path: String,
+    pub erasure: wipe::Erasure,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DestroyVaultReport {
+    pub db_path: String,
+    pub files: Vec<DestroyedFile>,
+    pub keychain_entries_removed: u32,
+    pub destroyed_at: String,
+}
+
+/// Permanently erase the local vault: overwrite and delete its database files (and, if asked,
+/// its `.bak` copies), drop its keychain entries and reset the in-memory state.
+#[tauri::command]
+pub async fn destroy_vault(
+    app: AppHandle,
+    confirmation_phrase: String,
+    include_backups: bool,
+) -> Result<DestroyVaultReport, String> {
+    blocking(app, move |state| {
+        if confirmation_phrase.trim() != DESTROY_CONFIRMATION_PHRASE {
+            return Err("confirmation_mismatch".to_string());
+        }
+        if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+            return Err("Switch to the local vault before destroying it".to_string());
+        }
+
+        let project_ids: Vec<String> = {
+            let storage = state.storage.lock().map_err(|e| e.to_string())?;
+            let storage = storage.as_ref().ok_or("Database not initialized")?;
+            storage
+                .list_projects()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|p| p.id)
+                .collect()
+        };
+
+        let db_path = wipe::reset_state(&state)?.ok_or("No database path set")?;
+
+        let mut paths = wipe::database_files(&db_path);
+        if include_backups {
+            paths.extend(wipe::backup_files(&db_path));
+        }
+
+        let mut files = Vec::new();
+        for path in paths {
+            let erasure = wipe::erase_file(&path)
+                .map_err(|e| format!("Failed to erase {}: {e}", path.display()))?;
+            if erasure != wipe::Erasure::Missing {
+                files.push(DestroyedFile {
+                    path: path.to_string_lossy().to_string(),
+                    erasure,
+                });
+            }
+        }
+
+        let mut keychain_keys: Vec<String> = project_ids
+            .iter()
+            .map(|id| password_registry::kc_key(id))
+            .collect();
+        keychain_keys.extend(
+            [
+                KC_SESSION_KEY,
+                KC_LEGACY_MASTER_PASSWORD,
+                KC_PIN_SLOT,
+                KC_QUICK_UNLOCK_KIND,
+                KC_LEGACY_PIN_HASH,
+                KC_TOUCH_ID_KEY,
+            ]
+            .map(String::from),
+        );
+        if keychain::get(KC_DB_PATH).as_deref() == Some(db_path.as_str()) {
+            keychain_keys.push(KC_DB_PATH.to_string());
+        }
+
+        let mut keychain_entries_removed = 0u32;
+        for key in &keychain_keys {
+            if keychain::get(key).is_some() {
+                keychain::remove(key);
+                keychain_entries_removed += 1;
+            }
+        }
+        keychain::biometric::remove();
+
+        Ok(DestroyVaultReport {
+            db_path,
+            files,
+            keychain_entries_removed,
+            destroyed_at: state.clock.now().to_rfc3339(),
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::clock::SystemClock;
+    use crate::crypto::locked::LockedKey;
+
+    #[test]
+    fn setting_the_first_master_password_unlocks_the_vault() {
+        let state = AppState {
+            storage: Mutex::new(Some(Box::new(LocalStorage::new(":memory:").unwrap()))),
+            db_path: Mutex::new(None),
+            server_token: Mutex::new(None),
+            server_url: Mutex::new(None),
+            cached_key: Mutex::new(LockedKey::new()),
+            active_context: Mutex::new("local".to_string()),
+            clock: Arc::new(SystemClock),
+            vaults: Mutex::new(Default::default()),
+        };
+
+        create_master_password(&state, "correct horse").unwrap();
+
+        let cached = state.cached_key.lock().unwrap().with_key(|key| *key);
+        let unlocked = with_storage(&state, |storage| vault::unlock(storage, "correct horse"));
+        assert_eq!(cached, unlocked.unwrap());
+        assert!(cached.is_some());
+        assert_eq!(
+            create_master_password(&state, "another").unwrap_err(),
+            "Master password already set"
+        );
+    }
+
+    #[test]
+    fn a_vault_that_cannot_be_reopened_after_conversion_is_left_locked() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-convert-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+        let mut key = LockedKey::new();
+        key.set([6u8; crypto::KEY_LEN]);
+        let state = AppState {
+            storage: Mutex::new(Some(Box::new(LocalStorage::new(&db_path).unwrap()))),
+            db_path: Mutex::new(Some(db_path.clone())),
+            server_token: Mutex::new(None),
+            server_url: Mutex::new(None),
+            cached_key: Mutex::new(key),
+            active_context: Mutex::new("local".to_string()),
+            clock: Arc::new(SystemClock),
+            vaults: Mutex::new(Default::default()),
+        };
+
+        let err = convert_database(&state, true, |_| {
+            Err(StorageError::Database("disk unplugged".to_string()))
+        })
+        .unwrap_err();
+        assert!(err.contains("disk unplugged"), "{err}");
+        assert!(err.contains("Unlock it again"), "{err}");
+        assert!(state.storage.lock().unwrap().is_none());
+        assert!(state.cached_key.lock().unwrap().is_none());
+        drop(state);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,15 +2,26 @@
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 
+use crate::checklist;
+use crate::commands::blocking;
+use crate::commands::devices::enforce_remote_wipe;
+use crate::cooling_off;
 use crate::crypto;
+use crate::folders;
+use crate::insights;
 use crate::keychain;
-use crate::models::{DecryptedProjectData, Project, ProjectBackup};
+use crate::merge::{self, MergePreview};
+use crate::models::{DecryptedProjectData, Project, ProjectBackup, ProjectVersion};
+use crate::ordering;
 use crate::password_registry::{self, RegistryEntry};
+use crate::server_features;
+use crate::settings_sync;
 use crate::storage::remote::RemoteStorage;
 use crate::storage::StorageProvider;
+use crate::templates;
 use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +35,8 @@ pub struct SyncPushResult {
     pub uploaded: u32,
     pub deleted: u32,
     pub conflicts: Vec<ConflictInfo>,
+    /// Synced preferences from another device were applied.
+    pub settings_updated: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +64,8 @@ pub struct SyncResult {
     pub downloaded: u32,
     pub deleted: u32,
     pub conflicts: Vec<ConflictInfo>,
+    /// Synced preferences from another device were applied.
+    pub settings_updated: bool,
 }
 
 fn decrypt_project_data(
@@ -79,364 +94,502 @@ fn decrypt_project_data(
     })
 }
 
-#[tauri::command]
-pub fn sync_projects(state: State<AppState>) -> Result<SyncResult, String> {
-    let server_url = state
-        .server_url
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("Not connected to server")?;
-
-    let token = state
-        .server_token
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("Not authenticated")?;
-
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let local = storage.as_ref().ok_or("Database not initialized")?;
-
-    let cached_key = state
-        .cached_key
-        .lock()
-        .map_err(|e| e.to_string())?
-        .ok_or("No cached key")?;
-
-    let master_password = state
-        .master_password
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone();
-
-    let remote = RemoteStorage::new(&server_url, &token);
-    remote.health_check().map_err(|e| e.to_string())?;
-
-    let password_pool = password_registry::collect_password_pool(&**local, &cached_key, None);
-    let mut all_passwords = Vec::new();
-    if let Some(ref mp) = master_password {
-        all_passwords.push(mp.clone());
+/// Content of never-synced projects is bound to the local id; the server assigns its own id,
+/// so strip the binding before the first upload.
+fn portable_for_upload(
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Project, String> {
+    let mut portable = project.clone();
+    if crypto::is_aad_bound(&project.encrypted_content) {
+        let (id, name) = (&project.id, &project.name);
+        let content = crypto::content::decrypt(&project.encrypted_content, key, id, name, true)
+            .map_err(|_| format!("Cannot decrypt project {}", project.id))?;
+        portable.encrypted_content =
+            crypto::encrypt_with_key(&content, key).map_err(|e| e.to_string())?;
     }
-    all_passwords.extend(password_pool);
-
-    let local_projects = local.list_projects().map_err(|e| e.to_string())?;
-    let remote_metas = remote.list_projects_meta().map_err(|e| e.to_string())?;
-
-    let mut uploaded = 0u32;
-    let mut downloaded = 0u32;
-    let mut deleted = 0u32;
-    let mut conflicts = Vec::new();
-
-    let now = chrono::Utc::now().to_rfc3339();
-
-    for lp in &local_projects {
-        if password_registry::is_registry(&lp.id) {
-            continue;
-        }
-        if matches!(lp.sync_status.as_str(), "modified" | "synced") {
-            let content_len = crypto::decrypt_auto(
-                &lp.encrypted_content,
-                Some(&cached_key),
-                master_password.as_deref(),
-            )
-            .ok()
-            .and_then(|bytes| String::from_utf8(bytes).ok())
-            .map(|s| s.len() as i64)
-            .unwrap_or(0);
+    Ok(portable)
+}
 
-            let backup_entry = ProjectBackup {
-                id: Uuid::new_v4().to_string(),
-                project_id: lp.id.clone(),
-                name: lp.name.clone(),
-                encrypted_content: lp.encrypted_content.clone(),
-                key_check: lp.key_check.clone(),
-                created_at: now.clone(),
-                trigger_type: "pre_sync".to_string(),
-                content_length: content_len,
-            };
-            let _ = local.create_backup(&backup_entry);
-            let _ = local.cleanup_backups(&lp.id, BACKUP_KEEP_COUNT);
-        }
+/// Both sides changed a project but ended up with the same content, so only its position or
+/// folder can differ. `ordering::resolve` picks the same winner on every device; the server is
+/// updated when the local position wins or the local folder differs, since only a local change
+/// marks the project modified.
+fn settle_position(
+    remote: &RemoteStorage,
+    local_project: &Project,
+    remote_project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<String, String> {
+    let position = ordering::resolve(&local_project.position, &remote_project.position);
+    if position != remote_project.position || local_project.folder_id != remote_project.folder_id {
+        let mut upload = portable_for_upload(local_project, key)?;
+        upload.position = position.to_string();
+        remote.update_project(&upload).map_err(|e| e.to_string())?;
     }
+    Ok(position.to_string())
+}
 
-    for lp in &local_projects {
-        if password_registry::is_registry(&lp.id) {
-            continue;
-        }
-        match lp.sync_status.as_str() {
-            "local" => {
-                let server_id = remote
-                    .create_project(lp)
-                    .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn sync_projects(app: AppHandle) -> Result<SyncResult, String> {
+    blocking(app, move |state| {
+        let server_url = state
+            .server_url
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("Not connected to server")?;
+
+        let token = state
+            .server_token
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("Not authenticated")?;
+
+        let remote = RemoteStorage::new(&server_url, &token);
+        server_features::check(&remote)?;
+        enforce_remote_wipe(&state, &remote)?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let local = storage.as_ref().ok_or("Database not initialized")?;
+
+        let cached_key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key")?;
+
+        let all_passwords = password_registry::collect_password_pool(&**local, &cached_key, None);
+        folders::sync(&**local, &remote)?;
+        templates::sync(&**local, &remote)?;
+
+        let local_projects = local.list_projects().map_err(|e| e.to_string())?;
+        let remote_metas = remote.list_projects_meta().map_err(|e| e.to_string())?;
+
+        let mut uploaded = 0u32;
+        let mut downloaded = 0u32;
+        let mut deleted = 0u32;
+        let mut conflicts = Vec::new();
+
+        let now = state.clock.now().to_rfc3339();
+        let settings_updated = settings_sync::sync(&**local, &remote, &cached_key, &now)?;
 
-                let mut updated_project = lp.clone();
-                updated_project.sync_status = "synced".to_string();
-                updated_project.last_synced_at = Some(now.clone());
-                if let Some(sid) = server_id {
-                    updated_project.server_id = Some(sid);
-                }
-                local
-                    .update_project(&updated_project)
-                    .map_err(|e| e.to_string())?;
-                uploaded += 1;
+        for lp in &local_projects {
+            if password_registry::is_registry(&lp.id) {
+                continue;
             }
-            "modified" | "conflict" => {
-                if let Some(ref local_server_id) = lp.server_id {
-                    let remote_meta = remote_metas
-                        .iter()
-                        .find(|rm| rm.id.to_string() == *local_server_id);
+            if matches!(lp.sync_status.as_str(), "modified" | "synced") {
+                let content_len = crypto::content::decrypt(
+                    &lp.encrypted_content,
+                    &cached_key,
+                    &lp.id,
+                    &lp.name,
+                    lp.server_id.is_some(),
+                )
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .map(|s| s.len() as i64)
+                .unwrap_or(0);
+
+                let backup_entry = ProjectBackup {
+                    id: Uuid::new_v4().to_string(),
+                    project_id: lp.id.clone(),
+                    name: lp.name.clone(),
+                    encrypted_content: lp.encrypted_content.clone(),
+                    key_check: lp.key_check.clone(),
+                    created_at: now.clone(),
+                    trigger_type: "pre_sync".to_string(),
+                    content_length: content_len,
+                };
+                let _ = local.create_backup(&backup_entry);
+                let _ = local.cleanup_backups(&lp.id, BACKUP_KEEP_COUNT);
+            }
+        }
 
-                    let remote_changed = remote_meta.map_or(false, |rm| {
-                        lp.last_synced_at
-                            .as_ref()
-                            .map(|lst| rm.updated_at > *lst)
-                            .unwrap_or(true)
-                    });
+        for lp in &local_projects {
+            if password_registry::is_registry(&lp.id) {
+                continue;
+            }
+            match lp.sync_status.as_str() {
+                "local" => {
+                    let upload = portable_for_upload(lp, &cached_key)?;
+                    let server_id = remote
+                        .create_project(&upload)
+                        .map_err(|e| e.to_string())?;
 
-                    if remote_changed {
-                        let rv = remote.get_project(local_server_id).map_err(|e| e.to_string())?;
-                        match (
-                            decrypt_project_data(lp, &cached_key, &all_passwords),
-                            decrypt_project_data(&rv, &cached_key, &all_passwords),
-                        ) {
-                            (Ok(local_data), Ok(remote_data)) => {
-                                if local_data.name == remote_data.name
-                                    && local_data.content == remote_data.content
-                                {
-                                    let mut updated_project = lp.clone();
-                                    updated_project.sync_status = "synced".to_string();
-                                    updated_project.last_synced_at = Some(now.clone());
-                                    local
-                                        .update_project(&updated_project)
-                                        .map_err(|e| e.to_string())?;
-                                } else {
+                    let mut updated_project = upload;
+                    updated_project.sync_status = "synced".to_string();
+                    updated_project.last_synced_at = Some(now.clone());
+                    if let Some(sid) = server_id {
+                        updated_project.server_id = Some(sid);
+                    }
+                    local
+                        .update_project(&updated_project)
+                        .map_err(|e| e.to_string())?;
+                    uploaded += 1;
+                }
+                "modified" | "conflict" => {
+                    if let Some(ref local_server_id) = lp.server_id {
+                        let remote_meta = remote_metas
+                            .iter()
+                            .find(|rm| rm.id.to_string() == *local_server_id);
+
+                        let remote_changed = remote_meta.map_or(false, |rm| {
+                            lp.last_synced_at
+                                .as_ref()
+                                .map(|lst| rm.updated_at > *lst)
+                                .unwrap_or(true)
+                        });
+
+                        if remote_changed {
+                            let rv = remote.get_project(local_server_id).map_err(|e| e.to_string())?;
+                            match (
+                                decrypt_project_data(lp, &cached_key, &all_passwords),
+                                decrypt_project_data(&rv, &cached_key, &all_passwords),
+                            ) {
+                                (Ok(local_data), Ok(remote_data)) => {
+                                    if local_data.name == remote_data.name
+                                        && local_data.content == remote_data.content
+                                    {
+                                        let mut updated_project = lp.clone();
+                                        updated_project.position =
+                                            settle_position(&remote, lp, &rv, &cached_key)?;
+                                        updated_project.sync_status = "synced".to_string();
+                                        updated_project.last_synced_at = Some(now.clone());
+                                        local
+                                            .update_project(&updated_project)
+                                            .map_err(|e| e.to_string())?;
+                                    } else {
+                                        conflicts.push(ConflictInfo {
+                                            project_id: lp.id.clone(),
+                                            local_name: local_data.name,
+                                            local_content: local_data.content,
+                                            remote_name: remote_data.name,
+                                            remote_content: remote_data.content,
+                                            local_updated_at: lp.updated_at.clone(),
+                                            remote_updated_at: rv.updated_at.clone(),
+                                        });
+
+                                        let mut conflict_project = lp.clone();
+                                        conflict_project.sync_status = "conflict".to_string();
+                                        local
+                                            .update_project(&conflict_project)
+                                            .map_err(|e| e.to_string())?;
+                                    }
+                                }
+                                _ => {
                                     conflicts.push(ConflictInfo {
                                         project_id: lp.id.clone(),
-                                        local_name: local_data.name,
-                                        local_content: local_data.content,
-                                        remote_name: remote_data.name,
-                                        remote_content: remote_data.content,
+                                        local_name: "[encrypted]".to_string(),
+                                        local_content: "[encrypted]".to_string(),
+                                        remote_name: "[encrypted]".to_string(),
+                                        remote_content: "[encrypted]".to_string(),
                                         local_updated_at: lp.updated_at.clone(),
                                         remote_updated_at: rv.updated_at.clone(),
                                     });
-
-                                    let mut conflict_project = lp.clone();
-                                    conflict_project.sync_status = "conflict".to_string();
-                                    local
-                                        .update_project(&conflict_project)
-                                        .map_err(|e| e.to_string())?;
                                 }
                             }
-                            _ => {
-                                conflicts.push(ConflictInfo {
-                                    project_id: lp.id.clone(),
-                                    local_name: "[encrypted]".to_string(),
-                                    local_content: "[encrypted]".to_string(),
-                                    remote_name: "[encrypted]".to_string(),
-                                    remote_content: "[encrypted]".to_string(),
-                                    local_updated_at: lp.updated_at.clone(),
-                                    remote_updated_at: rv.updated_at.clone(),
-                                });
-                            }
+                        } else {
+                            remote
+                                .update_project(lp)
+                                .map_err(|e| e.to_string())?;
+
+                            let mut updated_project = lp.clone();
+                            updated_project.sync_status = "synced".to_string();
+                            updated_project.last_synced_at = Some(now.clone());
+                            local
+                                .update_project(&updated_project)
+                                .map_err(|e| e.to_string())?;
+                            uploaded += 1;
                         }
                     } else {
-                        remote
-                            .update_project(lp)
+                        let upload = portable_for_upload(lp, &cached_key)?;
+                        let server_id = remote
+                            .create_project(&upload)
                             .map_err(|e| e.to_string())?;
 
-                        let mut updated_project = lp.clone();
+                        let mut updated_project = upload;
                         updated_project.sync_status = "synced".to_string();
                         updated_project.last_synced_at = Some(now.clone());
+                        if let Some(sid) = server_id {
+                            updated_project.server_id = Some(sid);
+                        }
                         local
                             .update_project(&updated_project)
                             .map_err(|e| e.to_string())?;
                         uploaded += 1;
                     }
-                } else {
-                    let server_id = remote
-                        .create_project(lp)
-                        .map_err(|e| e.to_string())?;
-
-                    let mut updated_project = lp.clone();
-                    updated_project.sync_status = "synced".to_string();
-                    updated_project.last_synced_at = Some(now.clone());
-                    if let Some(sid) = server_id {
-                        updated_project.server_id = Some(sid);
-                    }
-                    local
-                        .update_project(&updated_project)
-                        .map_err(|e| e.to_string())?;
-                    uploaded += 1;
                 }
-            }
-            "deleted" => {
-                if let Some(ref sid) = lp.server_id {
-                    let _ = remote.delete_project(sid);
+                "deleted" => {
+                    if let Some(ref sid) = lp.server_id {
+                        let _ = remote.delete_project(sid);
+                    }
+                    local.delete_project(&lp.id).map_err(|e| e.to_string())?;
+                    deleted += 1;
                 }
-                local.delete_project(&lp.id).map_err(|e| e.to_string())?;
-                deleted += 1;
+                _ => {}
             }
-            _ => {}
         }
-    }
-
-    // Handle registry push separately (auto-merge, never conflict)
-    sync_registry_push(&**local, &remote, &cached_key, &now)?;
 
-    let local_all = local.list_projects().map_err(|e| e.to_string())?;
-    let local_server_ids: Vec<String> = local_all
-        .iter()
-        .filter_map(|p| p.server_id.clone())
-        .collect();
-
-    let remote_server_ids: std::collections::HashSet<String> = remote_metas
-        .iter()
-        .map(|rm| rm.id.to_string())
-        .collect();
-
-    for rm in &remote_metas {
-        let sid = rm.id.to_string();
-        if !local_server_ids.contains(&sid) {
-            let rp = remote.get_project(&sid).map_err(|e| e.to_string())?;
-            if password_registry::is_registry_by_name(&rp, &cached_key) {
-                handle_pulled_registry(&**local, &rp, &cached_key, &now)?;
-            } else {
-                let mut new_project = rp;
-                new_project.sync_status = "synced".to_string();
-                new_project.last_synced_at = Some(now.clone());
-                local
-                    .create_project(&new_project)
-                    .map_err(|e| e.to_string())?;
+        // Handle registry push separately (auto-merge, never conflict)
+        sync_registry_push(&**local, &remote, &cached_key, &now)?;
+
+        let local_all = local.list_projects().map_err(|e| e.to_string())?;
+        let local_server_ids: Vec<String> = local_all
+            .iter()
+            .filter_map(|p| p.server_id.clone())
+            .collect();
+
+        let remote_server_ids: std::collections::HashSet<String> = remote_metas
+            .iter()
+            .map(|rm| rm.id.to_string())
+            .collect();
+
+        for rm in &remote_metas {
+            let sid = rm.id.to_string();
+            if !local_server_ids.contains(&sid) {
+                let rp = remote.get_project(&sid).map_err(|e| e.to_string())?;
+                if password_registry::is_registry_by_name(&rp, &cached_key) {
+                    handle_pulled_registry(&**local, &rp, &cached_key, &now)?;
+                } else {
+                    let mut new_project = rp;
+                    new_project.sync_status = "synced".to_string();
+                    new_project.last_synced_at = Some(now.clone());
+                    local
+                        .create_project(&new_project)
+                        .map_err(|e| e.to_string())?;
+                }
+                downloaded += 1;
             }
-            downloaded += 1;
         }
-    }
 
-    for lp in &local_all {
-        if password_registry::is_registry(&lp.id) || lp.sync_status == "deleted" {
-            continue;
-        }
-        if let Some(ref sid) = lp.server_id {
-            if !remote_server_ids.contains(sid) && lp.sync_status == "synced" {
-                local.delete_project(&lp.id).map_err(|e| e.to_string())?;
-                deleted += 1;
+        for lp in &local_all {
+            if password_registry::is_registry(&lp.id) || lp.sync_status == "deleted" {
+                continue;
+            }
+            if let Some(ref sid) = lp.server_id {
+                if !remote_server_ids.contains(sid) && lp.sync_status == "synced" {
+                    local.delete_project(&lp.id).map_err(|e| e.to_string())?;
+                    deleted += 1;
+                }
             }
         }
-    }
 
-    let _ = password_registry::import_registry(&**local, &cached_key);
+        checklist::sync(&**local, &remote)?;
+        let _ = password_registry::import_registry(&**local, &cached_key);
+        insights::record(&**local, &*state.clock, &cached_key, insights::EVENT_SYNC);
 
-    Ok(SyncResult {
-        uploaded,
-        downloaded,
-        deleted,
-        conflicts,
+        Ok(SyncResult {
+            uploaded,
+            downloaded,
+            deleted,
+            conflicts,
+            settings_updated,
+        })
     })
+    .await
 }
 
 #[tauri::command]
-pub fn resolve_conflict(
-    state: State<AppState>,
+pub async fn resolve_conflict(
+    app: AppHandle,
     project_id: String,
     resolution: String,
     merged_name: Option<String>,
     merged_content: Option<String>,
     password: String,
 ) -> Result<(), String> {
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let local = storage.as_ref().ok_or("Database not initialized")?;
-
-    let server_url = state
-        .server_url
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("Not connected to server")?;
-
-    let token = state
-        .server_token
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone()
-        .ok_or("Not authenticated")?;
-
-    let cached_key = state
-        .cached_key
-        .lock()
-        .map_err(|e| e.to_string())?
-        .ok_or("No cached key")?;
-
-    let existing = local.get_project(&project_id).map_err(|e| e.to_string())?;
-    let remote = RemoteStorage::new(&server_url, &token);
-    let now = chrono::Utc::now().to_rfc3339();
-
-    match resolution.as_str() {
-        "local" => {
-            remote
-                .update_project(&existing)
-                .map_err(|e| e.to_string())?;
-
-            let mut resolved = existing;
-            resolved.sync_status = "synced".to_string();
-            resolved.last_synced_at = Some(now);
-            local
-                .update_project(&resolved)
-                .map_err(|e| e.to_string())?;
-        }
-        "remote" => {
-            if let Some(ref sid) = existing.server_id {
-                let remote_project = remote.get_project(sid).map_err(|e| e.to_string())?;
+    blocking(app, move |state| {
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let local = storage.as_ref().ok_or("Database not initialized")?;
+
+        let server_url = state
+            .server_url
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("Not connected to server")?;
+
+        let token = state
+            .server_token
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("Not authenticated")?;
+
+        let cached_key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key")?;
+
+        let existing = local.get_project(&project_id).map_err(|e| e.to_string())?;
+        let remote = RemoteStorage::new(&server_url, &token);
+        let now = state.clock.now().to_rfc3339();
+
+        match resolution.as_str() {
+            "local" => {
+                remote
+                    .update_project(&existing)
+                    .map_err(|e| e.to_string())?;
 
-                let mut resolved = remote_project;
-                resolved.id = existing.id;
+                let mut resolved = existing;
                 resolved.sync_status = "synced".to_string();
                 resolved.last_synced_at = Some(now);
                 local
                     .update_project(&resolved)
                     .map_err(|e| e.to_string())?;
-            } else {
-                return Err("No server_id for remote resolution".to_string());
             }
-        }
-        "merged" => {
-            let name = merged_name.ok_or("Merged name required")?;
-            let content = merged_content.ok_or("Merged content required")?;
-
-            let use_custom = !password.is_empty();
-            let (encrypted_content, key_check) = if use_custom {
-                (
-                    crypto::encrypt(content.as_bytes(), &password).map_err(|e| e.to_string())?,
-                    crypto::encrypt(b"cp", &password).map_err(|e| e.to_string())?,
-                )
-            } else {
-                (
-                    crypto::encrypt_with_key(content.as_bytes(), &cached_key)
-                        .map_err(|e| e.to_string())?,
-                    crypto::encrypt_with_key(b"mk", &cached_key)
-                        .map_err(|e| e.to_string())?,
-                )
-            };
+            "remote" => {
+                if let Some(ref sid) = existing.server_id {
+                    let remote_project = remote.get_project(sid).map_err(|e| e.to_string())?;
+
+                    let mut resolved = remote_project;
+                    resolved.id = existing.id;
+                    resolved.sync_status = "synced".to_string();
+                    resolved.last_synced_at = Some(now);
+                    local
+                        .update_project(&resolved)
+                        .map_err(|e| e.to_string())?;
+                } else {
+                    return Err("No server_id for remote resolution".to_string());
+                }
+            }
+            "merged" => {
+                let name = merged_name.ok_or("Merged name required")?;
+                let content = merged_content.ok_or("Merged content required")?;
+
+                let use_custom = !password.is_empty();
+                let (encrypted_content, key_check) = if use_custom {
+                    (
+                        crypto::encrypt(content.as_bytes(), &password).map_err(|e| e.to_string())?,
+                        crypto::encrypt(b"cp", &password).map_err(|e| e.to_string())?,
+                    )
+                } else {
+                    (
+                        crypto::encrypt_with_key(content.as_bytes(), &cached_key)
+                            .map_err(|e| e.to_string())?,
+                        crypto::encrypt_with_key(b"mk", &cached_key)
+                            .map_err(|e| e.to_string())?,
+                    )
+                };
+
+                let mut resolved = existing;
+                resolved.name = name;
+                resolved.encrypted_content = encrypted_content;
+                resolved.key_check = key_check;
+                resolved.updated_at = now.clone();
+                resolved.sync_status = "synced".to_string();
+                resolved.last_synced_at = Some(now);
 
-            let mut resolved = existing;
-            resolved.name = name;
-            resolved.encrypted_content = encrypted_content;
-            resolved.key_check = key_check;
-            resolved.updated_at = now.clone();
-            resolved.sync_status = "synced".to_string();
-            resolved.last_synced_at = Some(now);
-
-            remote
-                .update_project(&resolved)
-                .map_err(|e| e.to_string())?;
-            local
-                .update_project(&resolved)
-                .map_err(|e| e.to_string())?;
+                remote
+                    .update_project(&resolved)
+                    .map_err(|e| e.to_string())?;
+                local
+                    .update_project(&resolved)
+                    .map_err(|e| e.to_string())?;
+            }
+            _ => return Err(format!("Unknown resolution: {}", resolution)),
         }
-        _ => return Err(format!("Unknown resolution: {}", resolution)),
+
+        Ok(())
+    })
+    .await
+}
+
+/// The remote side of a conflict as `sync_push` reported it.
+#[derive(Debug, Deserialize)]
+pub struct RemoteSnapshot {
+    pub name: String,
+    pub content: String,
+}
+
+/// Decrypt a revision of `project` named `name`, under the vault key or the project's password.
+fn open_revision(
+    project: &Project,
+    name: &str,
+    encrypted_content: &[u8],
+    key_check: &[u8],
+    key: &[u8; crypto::KEY_LEN],
+    password: &str,
+) -> Result<String, String> {
+    let bytes = if key_check.is_empty() || crypto::try_decrypt_with_key(key_check, key).is_some() {
+        let portable = project.server_id.is_some();
+        crypto::content::decrypt(encrypted_content, key, &project.id, name, portable)
+    } else {
+        crypto::decrypt_auto(encrypted_content, None, Some(password))
     }
+    .map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// The revision local and remote last agreed on: the newest kept version written no later
+/// than the last sync.
+fn merge_base(local: &dyn StorageProvider, project: &Project) -> Result<Option<ProjectVersion>, String> {
+    let Some(synced_at) = project.last_synced_at.as_deref() else {
+        return Ok(None);
+    };
+    Ok(local
+        .list_versions(&project.id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|v| v.updated_at.as_str() <= synced_at)
+        .max_by(|a, b| a.updated_at.cmp(&b.updated_at)))
+}
+
+/// Three-way diff of a conflicting project against the remote revision, for the merge editor.
+/// The base comes from the version history; when it has been pruned, or was written under a
+/// password no longer known, the preview is a two-way diff with every difference a conflict.
+#[tauri::command]
+pub async fn get_merge_preview(
+    app: AppHandle,
+    local_id: String,
+    remote_snapshot: RemoteSnapshot,
+    password: String,
+) -> Result<MergePreview, String> {
+    blocking(app, move |state| {
+        let cached_key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key")?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let local = storage.as_ref().ok_or("Database not initialized")?;
+
+        cooling_off::check(&**local, &*state.clock, &local_id)?;
+        let project = local.get_project(&local_id).map_err(|e| e.to_string())?;
+        let password = if password.is_empty() {
+            keychain::get(&password_registry::kc_key(&local_id)).unwrap_or_default()
+        } else {
+            password
+        };
+
+        let local_content = open_revision(
+            &project,
+            &project.name,
+            &project.encrypted_content,
+            &project.key_check,
+            &cached_key,
+            &password,
+        )?;
+        let base = merge_base(&**local, &project)?.and_then(|v| {
+            let (content, key_check) = (&v.encrypted_content, &v.key_check);
+            open_revision(&project, &v.name, content, key_check, &cached_key, &password)
+                .ok()
+                .map(|content| (v.name, content))
+        });
+
+        Ok(merge::preview(
+            base.as_ref().map(|(name, content)| (name.as_str(), content.as_str())),
+            (&project.name, &local_content),
+            (&remote_snapshot.name, &remote_snapshot.content),
+        ))
+    })
+    .await
 }
 
 /// Push the password registry to the server with auto-merge (never creates conflict dialog).
@@ -633,7 +786,7 @@ fn fetch_remote_registry_entries(
         .map(|r| r.entries)
 }
 
-fn build_remote(state: &AppState) -> Result<RemoteStorage, String> {
+pub(crate) fn build_remote(state: &AppState) -> Result<RemoteStorage, String> {
     let server_url = state
         .server_url
         .lock()
@@ -650,9 +803,17 @@ fn build_remote(state: &AppState) -> Result<RemoteStorage, String> {
 }
 
 #[tauri::command]
-pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
-    let remote = build_remote(&state)?;
-    remote.health_check().map_err(|e| e.to_string())?;
+pub async fn sync_push(app: AppHandle) -> Result<SyncPushResult, String> {
+    blocking(app, move |state| {
+        push(&state)
+    })
+    .await
+}
+
+fn push(state: &AppState) -> Result<SyncPushResult, String> {
+    let remote = build_remote(state)?;
+    server_features::check(&remote)?;
+    enforce_remote_wipe(state, &remote)?;
 
     let storage = state.storage.lock().map_err(|e| e.to_string())?;
     let local = storage.as_ref().ok_or("Database not initialized")?;
@@ -661,25 +822,15 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
         .cached_key
         .lock()
         .map_err(|e| e.to_string())?
+        .with_key(crypto::locked::copy)
         .ok_or("No cached key")?;
 
-    let master_password = state
-        .master_password
-        .lock()
-        .map_err(|e| e.to_string())?
-        .clone();
-
     // Step 0: fetch remote registry entries (without importing)
     let remote_reg_entries = fetch_remote_registry_entries(&remote, &**local, &cached_key);
 
-    // Build password pool: master_password + keychain + remote registry
-    let keychain_pool =
+    // Build password pool: keychain + remote registry
+    let all_passwords =
         password_registry::collect_password_pool(&**local, &cached_key, remote_reg_entries.as_deref());
-    let mut all_passwords = Vec::new();
-    if let Some(ref mp) = master_password {
-        all_passwords.push(mp.clone());
-    }
-    all_passwords.extend(keychain_pool);
 
     // Step 1: pre-encrypt local projects where password changed
     if let Some(ref reg_entries) = remote_reg_entries {
@@ -708,10 +859,13 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
         }
     }
 
+    folders::sync(&**local, &remote)?;
+    templates::sync(&**local, &remote)?;
     let local_projects = local.list_projects().map_err(|e| e.to_string())?;
     let remote_metas = remote.list_projects_meta().map_err(|e| e.to_string())?;
 
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = state.clock.now().to_rfc3339();
+    let settings_updated = settings_sync::sync(&**local, &remote, &cached_key, &now)?;
     let mut uploaded = 0u32;
     let mut deleted = 0u32;
     let mut conflicts = Vec::new();
@@ -721,10 +875,12 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
             continue;
         }
         if lp.sync_status == "modified" || lp.sync_status == "synced" {
-            let content_len = crypto::decrypt_auto(
+            let content_len = crypto::content::decrypt(
                 &lp.encrypted_content,
-                Some(&cached_key),
-                master_password.as_deref(),
+                &cached_key,
+                &lp.id,
+                &lp.name,
+                lp.server_id.is_some(),
             )
             .ok()
             .and_then(|bytes| String::from_utf8(bytes).ok())
@@ -752,10 +908,11 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
         }
         match lp.sync_status.as_str() {
             "local" => {
+                let upload = portable_for_upload(lp, &cached_key)?;
                 let server_id = remote
-                    .create_project(lp)
+                    .create_project(&upload)
                     .map_err(|e| e.to_string())?;
-                let mut updated_project = lp.clone();
+                let mut updated_project = upload;
                 updated_project.sync_status = "synced".to_string();
                 updated_project.last_synced_at = Some(now.clone());
                 if let Some(sid) = server_id {
@@ -788,6 +945,8 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
                                     && local_data.content == remote_data.content
                                 {
                                     let mut updated_project = lp.clone();
+                                    updated_project.position =
+                                        settle_position(&remote, lp, &rv, &cached_key)?;
                                     updated_project.sync_status = "synced".to_string();
                                     updated_project.last_synced_at = Some(now.clone());
                                     local.update_project(&updated_project).map_err(|e| e.to_string())?;
@@ -827,8 +986,9 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
                         uploaded += 1;
                     }
                 } else {
-                    let server_id = remote.create_project(lp).map_err(|e| e.to_string())?;
-                    let mut updated_project = lp.clone();
+                    let upload = portable_for_upload(lp, &cached_key)?;
+                    let server_id = remote.create_project(&upload).map_err(|e| e.to_string())?;
+                    let mut updated_project = upload;
                     updated_project.sync_status = "synced".to_string();
                     updated_project.last_synced_at = Some(now.clone());
                     if let Some(sid) = server_id {
@@ -853,146 +1013,300 @@ pub fn sync_push(state: State<AppState>) -> Result<SyncPushResult, String> {
     sync_registry_push(&**local, &remote, &cached_key, &now)?;
 
     // Step 4: finalize — import registry passwords into keychain
+    checklist::sync(&**local, &remote)?;
     let _ = password_registry::import_registry(&**local, &cached_key);
+    insights::record(&**local, &*state.clock, &cached_key, insights::EVENT_SYNC);
 
     Ok(SyncPushResult {
         uploaded,
         deleted,
         conflicts,
+        settings_updated,
     })
 }
 
 #[tauri::command]
-pub fn check_remote_changes(state: State<AppState>) -> Result<Vec<RemoteChangedInfo>, String> {
-    let remote = build_remote(&state)?;
-    remote.health_check().map_err(|e| e.to_string())?;
-
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let local = storage.as_ref().ok_or("Database not initialized")?;
-
-    let remote_metas = remote.list_projects_meta().map_err(|e| e.to_string())?;
-    let local_projects = local.list_projects().map_err(|e| e.to_string())?;
-
-    let local_server_ids: std::collections::HashMap<String, Option<String>> = local_projects
-        .iter()
-        .filter_map(|p| {
-            p.server_id
-                .as_ref()
-                .map(|sid| (sid.clone(), p.last_synced_at.clone()))
-        })
-        .collect();
-
-    let mut changed = Vec::new();
-
-    for rm in &remote_metas {
-        let sid = rm.id.to_string();
-        match local_server_ids.get(&sid) {
-            Some(last_synced) => {
-                let is_newer = last_synced
+pub async fn check_remote_changes(app: AppHandle) -> Result<Vec<RemoteChangedInfo>, String> {
+    blocking(app, move |state| {
+        let remote = build_remote(&state)?;
+        server_features::check(&remote)?;
+        enforce_remote_wipe(&state, &remote)?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let local = storage.as_ref().ok_or("Database not initialized")?;
+
+        let remote_metas = remote.list_projects_meta().map_err(|e| e.to_string())?;
+        let local_projects = local.list_projects().map_err(|e| e.to_string())?;
+
+        let local_server_ids: std::collections::HashMap<String, Option<String>> = local_projects
+            .iter()
+            .filter_map(|p| {
+                p.server_id
                     .as_ref()
-                    .map(|lst| rm.updated_at > *lst)
-                    .unwrap_or(true);
-                if is_newer {
+                    .map(|sid| (sid.clone(), p.last_synced_at.clone()))
+            })
+            .collect();
+
+        let mut changed = Vec::new();
+
+        for rm in &remote_metas {
+            let sid = rm.id.to_string();
+            match local_server_ids.get(&sid) {
+                Some(last_synced) => {
+                    let is_newer = last_synced
+                        .as_ref()
+                        .map(|lst| rm.updated_at > *lst)
+                        .unwrap_or(true);
+                    if is_newer {
+                        changed.push(RemoteChangedInfo {
+                            server_id: sid,
+                            remote_updated_at: rm.updated_at.clone(),
+                        });
+                    }
+                }
+                None => {
                     changed.push(RemoteChangedInfo {
                         server_id: sid,
                         remote_updated_at: rm.updated_at.clone(),
                     });
                 }
             }
-            None => {
-                changed.push(RemoteChangedInfo {
-                    server_id: sid,
-                    remote_updated_at: rm.updated_at.clone(),
-                });
-            }
         }
-    }
 
-    Ok(changed)
+        Ok(changed)
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn sync_pull_changed(
-    state: State<AppState>,
+pub async fn sync_pull_changed(
+    app: AppHandle,
     changed_ids: Vec<String>,
 ) -> Result<SyncPullResult, String> {
-    if changed_ids.is_empty() {
-        return Ok(SyncPullResult {
-            downloaded: 0,
-            updated: 0,
-        });
-    }
+    blocking(app, move |state| {
+        if changed_ids.is_empty() {
+            return Ok(SyncPullResult {
+                downloaded: 0,
+                updated: 0,
+            });
+        }
 
-    let remote = build_remote(&state)?;
+        let remote = build_remote(&state)?;
+        enforce_remote_wipe(&state, &remote)?;
+
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let local = storage.as_ref().ok_or("Database not initialized")?;
+
+        let cached_key = state
+            .cached_key
+            .lock()
+            .map_err(|e| e.to_string())?
+            .with_key(crypto::locked::copy)
+            .ok_or("No cached key")?;
+
+        let folders_synced = folders::sync(&**local, &remote)?;
+        templates::sync(&**local, &remote)?;
+        let local_projects = local.list_projects().map_err(|e| e.to_string())?;
+        let local_by_server_id: std::collections::HashMap<String, &Project> = local_projects
+            .iter()
+            .filter_map(|p| p.server_id.as_ref().map(|sid| (sid.clone(), p)))
+            .collect();
+
+        let now = state.clock.now().to_rfc3339();
+        let mut downloaded = 0u32;
+        let mut updated = 0u32;
+
+        for sid in &changed_ids {
+            let rp = match remote.get_project(sid) {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
 
-    let storage = state.storage.lock().map_err(|e| e.to_string())?;
-    let local = storage.as_ref().ok_or("Database not initialized")?;
+            if password_registry::is_registry_by_name(&rp, &cached_key) {
+                handle_pulled_registry(&**local, &rp, &cached_key, &now)?;
+                downloaded += 1;
+                continue;
+            }
 
-    let cached_key = state
-        .cached_key
-        .lock()
-        .map_err(|e| e.to_string())?
-        .ok_or("No cached key")?;
+            if let Some(existing) = local_by_server_id.get(sid) {
+                if existing.sync_status == "modified" || existing.sync_status == "conflict" {
+                    continue;
+                }
+                let mut update = rp.clone();
+                update.id = existing.id.clone();
+                update.sync_status = "synced".to_string();
+                update.last_synced_at = Some(now.clone());
+                local.update_project(&update).map_err(|e| e.to_string())?;
+                if folders_synced && update.folder_id != existing.folder_id {
+                    local
+                        .set_project_folder(&update.id, update.folder_id.as_deref())
+                        .map_err(|e| e.to_string())?;
+                }
+                updated += 1;
+            } else {
+                let mut new_project = rp;
+                new_project.sync_status = "synced".to_string();
+                new_project.last_synced_at = Some(now.clone());
+                local.create_project(&new_project).map_err(|e| e.to_string())?;
+                downloaded += 1;
+            }
+        }
 
-    let local_projects = local.list_projects().map_err(|e| e.to_string())?;
-    let local_by_server_id: std::collections::HashMap<String, &Project> = local_projects
-        .iter()
-        .filter_map(|p| p.server_id.as_ref().map(|sid| (sid.clone(), p)))
-        .collect();
-
-    let now = chrono::Utc::now().to_rfc3339();
-    let mut downloaded = 0u32;
-    let mut updated = 0u32;
-
-    for sid in &changed_ids {
-        let rp = match remote.get_project(sid) {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
+        if let Ok(remote_metas) = remote.list_projects_meta() {
+            let remote_ids: std::collections::HashSet<String> =
+                remote_metas.iter().map(|rm| rm.id.to_string()).collect();
 
-        if password_registry::is_registry_by_name(&rp, &cached_key) {
-            handle_pulled_registry(&**local, &rp, &cached_key, &now)?;
-            downloaded += 1;
-            continue;
+            for lp in &local_projects {
+                if password_registry::is_registry(&lp.id) || lp.sync_status == "deleted" {
+                    continue;
+                }
+                if let Some(ref sid) = lp.server_id {
+                    if !remote_ids.contains(sid) && lp.sync_status == "synced" {
+                        let _ = local.delete_project(&lp.id);
+                    }
+                }
+            }
         }
 
-        if let Some(existing) = local_by_server_id.get(sid) {
-            if existing.sync_status == "modified" || existing.sync_status == "conflict" {
-                continue;
-            }
-            let mut update = rp.clone();
-            update.id = existing.id.clone();
-            update.sync_status = "synced".to_string();
-            update.last_synced_at = Some(now.clone());
-            local.update_project(&update).map_err(|e| e.to_string())?;
-            updated += 1;
-        } else {
-            let mut new_project = rp;
-            new_project.sync_status = "synced".to_string();
-            new_project.last_synced_at = Some(now.clone());
-            local.create_project(&new_project).map_err(|e| e.to_string())?;
-            downloaded += 1;
+        checklist::sync(&**local, &remote)?;
+
+        Ok(SyncPullResult {
+            downloaded,
+            updated,
+        })
+    })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingsSyncPreferences {
+    pub enabled: bool,
+    /// Settings this device keeps to itself besides `device_specific`.
+    pub excluded: Vec<String>,
+    /// Settings that are never synced.
+    pub device_specific: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_settings_sync(state: State<AppState>) -> Result<SettingsSyncPreferences, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    Ok(SettingsSyncPreferences {
+        enabled: settings_sync::is_enabled(&**storage),
+        excluded: settings_sync::excluded(&**storage),
+        device_specific: settings_sync::DEVICE_SPECIFIC.iter().map(|k| k.to_string()).collect(),
+    })
+}
+
+/// Turn settings sync on or off for this device and choose which settings it keeps to itself.
+#[tauri::command]
+pub fn set_settings_sync(
+    state: State<AppState>,
+    enabled: bool,
+    excluded: Vec<String>,
+) -> Result<(), String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    settings_sync::set_preferences(&**storage, enabled, &excluded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::commands::projects::master_key_project;
+    use crate::crypto::locked::LockedKey;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::mock_server::MockServer;
+
+    const KEY: [u8; crypto::KEY_LEN] = [5u8; crypto::KEY_LEN];
+
+    fn signed_in_state(server: &MockServer) -> AppState {
+        let user = server.add_user("sync@example.com", "secret");
+        let mut cached_key = LockedKey::new();
+        cached_key.set(KEY);
+        AppState {
+            storage: Mutex::new(Some(Box::new(LocalStorage::new(":memory:").unwrap()))),
+            db_path: Mutex::new(None),
+            server_token: Mutex::new(Some(server.issue_token(user))),
+            server_url: Mutex::new(Some(server.url.clone())),
+            cached_key: Mutex::new(cached_key),
+            active_context: Mutex::new("mock".to_string()),
+            clock: Arc::new(SystemClock),
+            vaults: Mutex::new(Default::default()),
         }
     }
 
-    if let Ok(remote_metas) = remote.list_projects_meta() {
-        let remote_ids: std::collections::HashSet<String> =
-            remote_metas.iter().map(|rm| rm.id.to_string()).collect();
+    fn with_local<T>(state: &AppState, f: impl FnOnce(&dyn StorageProvider) -> T) -> T {
+        let storage = state.storage.lock().unwrap();
+        f(&**storage.as_ref().unwrap())
+    }
 
-        for lp in &local_projects {
-            if password_registry::is_registry(&lp.id) || lp.sync_status == "deleted" {
-                continue;
-            }
-            if let Some(ref sid) = lp.server_id {
-                if !remote_ids.contains(sid) && lp.sync_status == "synced" {
-                    let _ = local.delete_project(&lp.id);
-                }
-            }
-        }
+    #[test]
+    fn push_uploads_then_reports_conflicting_remote_edits() {
+        let server = MockServer::start();
+        let state = signed_in_state(&server);
+        let id = with_local(&state, |local| {
+            let project = master_key_project(&SystemClock, &KEY, false, "Notes", "first").unwrap();
+            local.create_project(&project).unwrap();
+            project.id
+        });
+
+        let result = push(&state).unwrap();
+        assert_eq!(result.uploaded, 1);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(server.project_names(), vec!["Notes".to_string()]);
+
+        // Another device edits the project on the server, and this one edits it locally.
+        let mut local_copy = with_local(&state, |local| local.get_project(&id).unwrap());
+        let other_device = build_remote(&state).unwrap();
+        let mut remote_copy = local_copy.clone();
+        remote_copy.encrypted_content = crypto::encrypt_with_key(b"theirs", &KEY).unwrap();
+        other_device.update_project(&remote_copy).unwrap();
+        local_copy.encrypted_content = crypto::encrypt_with_key(b"ours", &KEY).unwrap();
+        local_copy.sync_status = "modified".to_string();
+        with_local(&state, |local| local.update_project(&local_copy).unwrap());
+
+        let result = push(&state).unwrap();
+        assert_eq!(result.uploaded, 0);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].local_content, "ours");
+        assert_eq!(result.conflicts[0].remote_content, "theirs");
+        let status = with_local(&state, |local| local.get_project(&id).unwrap().sync_status);
+        assert_eq!(status, "conflict");
     }
 
-    Ok(SyncPullResult {
-        downloaded,
-        updated,
-    })
+    #[test]
+    fn push_runs_on_the_blocking_pool_from_inside_the_runtime() {
+        let server = MockServer::start();
+        let state = signed_in_state(&server);
+        with_local(&state, |local| {
+            let project = master_key_project(&SystemClock, &KEY, false, "Notes", "first").unwrap();
+            local.create_project(&project).unwrap();
+        });
+
+        // As `sync_push` does: the blocking client must not run on an async worker.
+        let result = tauri::async_runtime::block_on(async move {
+            crate::commands::run_blocking(move || push(&state)).await
+        });
+        assert_eq!(result.unwrap().uploaded, 1);
+        assert_eq!(server.project_names(), vec!["Notes".to_string()]);
+    }
+
+    #[test]
+    fn push_surfaces_server_refusals() {
+        let server = MockServer::start();
+        let state = signed_in_state(&server);
+
+        server.set_health(serde_json::json!({ "min_client_version": "999.0" }));
+        assert_eq!(push(&state).unwrap_err(), "server_requires_newer_client:999.0");
+
+        server.set_health(serde_json::json!({ "features": [] }));
+        server.revoke_tokens();
+        let err = push(&state).unwrap_err();
+        assert!(err.contains("Server error: invalid token"), "{err}");
+    }
 }
+
@@ -0,0 +1,68 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::{AppHandle, State};
+
+use crate::commands::{blocking, get_cached_key};
+use crate::commands::projects::create_project;
+use crate::credential;
+use crate::models::Credential;
+use crate::templates::{self, TemplateInfo};
+use crate::AppState;
+
+#[tauri::command]
+pub fn list_templates(state: State<AppState>) -> Result<Vec<TemplateInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    templates::list(&**storage, &key)
+}
+
+/// A new template with `content`, or with the fields of `credential` for a credential template.
+#[tauri::command]
+pub fn create_template(
+    state: State<AppState>,
+    name: String,
+    content: String,
+    credential: Option<Credential>,
+) -> Result<TemplateInfo, String> {
+    let key = get_cached_key(&state)?;
+    let content = match credential {
+        Some(credential) => credential::encode(&credential)?,
+        None => content.into(),
+    };
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    templates::create(&**storage, &*state.clock, &key, &name, &content)
+}
+
+#[tauri::command]
+pub fn delete_template(state: State<AppState>, id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    templates::delete(&**storage, &*state.clock, &id)
+}
+
+/// `create_project` with the template's content; `name` defaults to the template's.
+#[tauri::command]
+pub async fn create_project_from_template(
+    app: AppHandle,
+    template_id: String,
+    name: Option<String>,
+    password: String,
+    has_custom_password: bool,
+) -> Result<String, String> {
+    let template = blocking(app.clone(), move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+        templates::get(&**storage, &key, &template_id)
+    })
+    .await?;
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or(template.name);
+    create_project(app, name, template.content, password, has_custom_password).await
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use tauri::State;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::commands::attachments::{project_password, unlocked_project};
+use crate::commands::get_cached_key;
+use crate::totp::{self, TotpCode, TotpInfo, TotpOptions};
+use crate::AppState;
+
+/// Store a project's TOTP secret, given as base32 or as an `otpauth://totp/...` URI. Settings
+/// in `options` win over the URI's.
+#[tauri::command]
+pub fn set_totp_secret(
+    state: State<AppState>,
+    project_id: String,
+    secret: String,
+    options: Option<TotpOptions>,
+    password: String,
+) -> Result<TotpInfo, String> {
+    let key = get_cached_key(&state)?;
+    let secret = Zeroizing::new(secret);
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    totp::set(
+        &**storage,
+        &*state.clock,
+        project_key,
+        &project_id,
+        &secret,
+        options.unwrap_or_default(),
+    )
+}
+
+/// The project's TOTP settings, or `None` when it has no secret.
+#[tauri::command]
+pub fn get_totp_info(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<Option<TotpInfo>, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    totp::info(&**storage, project_key, &project_id)
+}
+
+/// The current code for the project's TOTP secret.
+#[tauri::command]
+pub fn get_totp_code(
+    state: State<AppState>,
+    project_id: String,
+    password: String,
+) -> Result<TotpCode, String> {
+    let key = get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+    let project = unlocked_project(&state, &**storage, &project_id)?;
+    let pw = project_password(&project, &key, password)?;
+    let project_key = ProjectKey::of(&key, pw.as_ref().map(|pw| pw.as_str()));
+    totp::code(&**storage, &*state.clock, project_key, &project_id)
+}
+
+#[tauri::command]
+pub fn remove_totp_secret(state: State<AppState>, project_id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    totp::remove(&**storage, &project_id)
+}
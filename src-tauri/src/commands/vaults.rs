@@ -0,0 +1,217 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Several local vaults open side by side, say a work and a personal one. See `crate::vaults`.
+
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::settings::{self, install_nonce_counter, require_bound_content};
+use crate::crypto::{self, locked::LockedKey};
+use crate::display_info;
+use crate::ephemeral;
+use crate::server_config;
+use crate::storage::local::LocalStorage;
+use crate::vaults::ParkedVault;
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnownVault {
+    pub db_path: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub last_opened: Option<String>,
+    pub open: bool,
+    pub unlocked: bool,
+    pub active: bool,
+}
+
+/// Vaults this machine knows, most recently opened first, with which are open and unlocked.
+#[tauri::command]
+pub fn list_known_vaults(state: State<AppState>) -> Result<Vec<KnownVault>, String> {
+    let mut open = state.vaults.lock().map_err(|e| e.to_string())?.list();
+    // Server vaults are parked here too while another context is active.
+    let server_paths: Vec<String> =
+        server_config::list_servers().into_iter().map(|cfg| cfg.db_path).collect();
+    open.retain(|(path, _)| !server_paths.contains(path));
+    let active = state.db_path.lock().map_err(|e| e.to_string())?.clone();
+    if let Some(ref path) = active {
+        let unlocked = state.cached_key.lock().map_err(|e| e.to_string())?.is_some();
+        open.push((path.clone(), unlocked));
+    }
+
+    let mut known = display_info::known();
+    for (path, _) in &open {
+        if !known.iter().any(|info| info.db_path == *path) {
+            known.extend(display_info::get(Some(path)));
+        }
+    }
+    Ok(known
+        .into_iter()
+        .map(|info| {
+            let unlocked = open.iter().find(|(path, _)| *path == info.db_path).map(|(_, u)| *u);
+            KnownVault {
+                active: active.as_deref() == Some(info.db_path.as_str()),
+                open: unlocked.is_some(),
+                unlocked: unlocked.unwrap_or(false),
+                db_path: info.db_path,
+                name: info.name,
+                color: info.color,
+                last_opened: info.last_opened,
+            }
+        })
+        .collect())
+}
+
+/// Make the vault at `db_path` the active one. The vault active until now stays open in the
+/// background with its key; a vault not open yet is opened locked. Returns whether the new
+/// active vault is unlocked.
+#[tauri::command]
+pub fn switch_vault(state: State<AppState>, db_path: String) -> Result<bool, String> {
+    if *state.active_context.lock().map_err(|e| e.to_string())? != "local" {
+        return Err("Vaults can only be switched in the local context".to_string());
+    }
+    let db_path = settings::duress_redirect(db_path)?;
+    if state.db_path.lock().map_err(|e| e.to_string())?.as_deref() == Some(db_path.as_str()) {
+        return Ok(state.cached_key.lock().map_err(|e| e.to_string())?.is_some());
+    }
+
+    let is_open = state
+        .vaults
+        .lock()
+        .map_err(|e| e.to_string())?
+        .list()
+        .iter()
+        .any(|(path, _)| *path == db_path);
+    if !is_open && !Path::new(&db_path).exists() {
+        return Err("No vault at this path".to_string());
+    }
+    let target = take_or_open(&state, &db_path)?;
+    activate(&state, db_path, target)
+}
+
+/// Make `target`, opened from `db_path`, the active vault. The vault active until now stays
+/// open in the background with its key. Returns whether the new active vault is unlocked.
+pub(crate) fn activate(state: &AppState, db_path: String, target: ParkedVault) -> Result<bool, String> {
+    let unlocked = target.key.is_some();
+    {
+        let mut storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let mut path = state.db_path.lock().map_err(|e| e.to_string())?;
+        let mut key = state.cached_key.lock().map_err(|e| e.to_string())?;
+        let previous_storage = storage.replace(target.storage);
+        let previous_key = std::mem::replace(&mut *key, target.key);
+        if let (Some(storage), Some(path)) = (previous_storage, path.replace(db_path)) {
+            state
+                .vaults
+                .lock()
+                .map_err(|e| e.to_string())?
+                .park(path, ParkedVault { storage, key: previous_key });
+        }
+    }
+
+    // Nonces and cached plaintext belong to the vault that was active.
+    crypto::nonce::uninstall();
+    ephemeral::clear();
+    let key = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(crypto::locked::copy);
+    if let Some(key) = key {
+        install_nonce_counter(state, &key);
+        require_bound_content(state, &key);
+    }
+    Ok(unlocked)
+}
+
+/// Park the active vault with its key, leaving none active.
+pub(crate) fn park_active(state: &AppState) -> Result<(), String> {
+    {
+        let mut storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let mut path = state.db_path.lock().map_err(|e| e.to_string())?;
+        let mut key = state.cached_key.lock().map_err(|e| e.to_string())?;
+        let previous_key = std::mem::take(&mut *key);
+        if let (Some(storage), Some(path)) = (storage.take(), path.take()) {
+            state
+                .vaults
+                .lock()
+                .map_err(|e| e.to_string())?
+                .park(path, ParkedVault { storage, key: previous_key });
+        }
+    }
+    crypto::nonce::uninstall();
+    ephemeral::clear();
+    Ok(())
+}
+
+/// The vault at `db_path` as it was parked, or freshly opened and locked.
+pub(crate) fn take_or_open(state: &AppState, db_path: &str) -> Result<ParkedVault, String> {
+    if let Some(vault) = state.vaults.lock().map_err(|e| e.to_string())?.take(db_path) {
+        return Ok(vault);
+    }
+    Ok(ParkedVault {
+        storage: Box::new(LocalStorage::new(db_path).map_err(|e| e.to_string())?),
+        key: LockedKey::new(),
+    })
+}
+
+/// Close the vault at `db_path`, forgetting its key. Closing the active vault leaves none active.
+#[tauri::command]
+pub fn close_vault(state: State<AppState>, db_path: String) -> Result<(), String> {
+    let is_active =
+        state.db_path.lock().map_err(|e| e.to_string())?.as_deref() == Some(db_path.as_str());
+    if !is_active {
+        let closed = state.vaults.lock().map_err(|e| e.to_string())?.close(&db_path);
+        return if closed { Ok(()) } else { Err("This vault is not open".to_string()) };
+    }
+    let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
+    cached.clear();
+    drop(cached);
+    crypto::nonce::uninstall();
+    ephemeral::clear();
+    *state.storage.lock().map_err(|e| e.to_string())? = None;
+    *state.db_path.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::clock::SystemClock;
+
+    fn vault(key: Option<[u8; crypto::KEY_LEN]>) -> ParkedVault {
+        let mut locked = LockedKey::new();
+        if let Some(key) = key {
+            locked.set(key);
+        }
+        ParkedVault { storage: Box::new(LocalStorage::new(":memory:").unwrap()), key: locked }
+    }
+
+    #[test]
+    fn switching_back_finds_the_parked_vault_still_unlocked() {
+        let state = AppState {
+            storage: Mutex::new(None),
+            db_path: Mutex::new(None),
+            server_token: Mutex::new(None),
+            server_url: Mutex::new(None),
+            cached_key: Mutex::new(LockedKey::new()),
+            active_context: Mutex::new("local".to_string()),
+            clock: Arc::new(SystemClock),
+            vaults: Mutex::new(Default::default()),
+        };
+        assert!(activate(&state, "work.db".to_string(), vault(Some([3u8; crypto::KEY_LEN]))).unwrap());
+        assert!(!activate(&state, "server.db".to_string(), vault(None)).unwrap());
+
+        let target = take_or_open(&state, "work.db").unwrap();
+        assert!(activate(&state, "work.db".to_string(), target).unwrap());
+        let key = state.cached_key.lock().unwrap().with_key(|key| *key);
+        assert_eq!(key, Some([3u8; crypto::KEY_LEN]));
+
+        park_active(&state).unwrap();
+        assert!(state.storage.lock().unwrap().is_none());
+        let mut open = state.vaults.lock().unwrap().list();
+        open.sort();
+        assert_eq!(open, [("server.db".to_string(), false), ("work.db".to_string(), true)]);
+    }
+}
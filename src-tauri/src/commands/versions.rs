@@ -0,0 +1,163 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use zeroize::Zeroizing;
+
+use crate::commands::{blocking, get_cached_key};
+use crate::cooling_off;
+use crate::crypto;
+use crate::keychain;
+use crate::merge::{self, DiffHunk};
+use crate::models::ProjectVersion;
+use crate::password_registry;
+use crate::rehash;
+use crate::storage::StorageProvider;
+use crate::versions::{self, VersionInfo, VersionRetention};
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct VersionContent {
+    pub name: String,
+    pub content: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionDiff {
+    pub from_name: String,
+    pub to_name: String,
+    pub from_updated_at: String,
+    pub to_updated_at: String,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Previous revisions of a project, newest first.
+#[tauri::command]
+pub fn list_versions(state: State<AppState>, project_id: String) -> Result<Vec<VersionInfo>, String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    versions::list(&**storage, &project_id)
+}
+
+/// Decrypt a version's content. Revisions written under a custom password need it, or a saved
+/// one.
+fn open_version(
+    storage: &dyn StorageProvider,
+    version: &ProjectVersion,
+    key: &[u8; crypto::KEY_LEN],
+    password: &str,
+) -> Result<Zeroizing<String>, String> {
+    let has_custom = !version.key_check.is_empty()
+        && crypto::try_decrypt_with_key(&version.key_check, key).is_none();
+
+    let content_bytes = if !has_custom {
+        let portable = rehash::is_portable(storage, &version.project_id);
+        versions::open_content(version, key, portable)?
+    } else {
+        let pw = Zeroizing::new(if password.is_empty() {
+            keychain::get(&password_registry::kc_key(&version.project_id))
+                .ok_or("No password available for decryption")?
+        } else {
+            password.to_string()
+        });
+        crypto::decrypt_auto(&version.encrypted_content, None, Some(&pw))
+            .map_err(|e| e.to_string())?
+    };
+    String::from_utf8(content_bytes).map(Zeroizing::new).map_err(|e| e.to_string())
+}
+
+/// Decrypt a version. Revisions written under a custom password need it, or a saved one. A
+/// project in its cooling-off delay keeps its old revisions closed as well.
+#[tauri::command]
+pub async fn get_version(
+    app: AppHandle,
+    version_id: String,
+    password: String,
+) -> Result<VersionContent, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let version = storage.get_version(&version_id).map_err(|e| e.to_string())?;
+        cooling_off::check(&**storage, &*state.clock, &version.project_id)?;
+        let content = open_version(&**storage, &version, &key, &password)?;
+        Ok(VersionContent {
+            name: version.name,
+            content: content.to_string(),
+            updated_at: version.updated_at,
+        })
+    })
+    .await
+}
+
+/// Line diff between two versions of a project, `from` the older one `to` the newer, so the
+/// plaintexts never leave the backend. Takes the password as `get_version` does.
+#[tauri::command]
+pub async fn diff_versions(
+    app: AppHandle,
+    project_id: String,
+    from: String,
+    to: String,
+    password: String,
+) -> Result<VersionDiff, String> {
+    blocking(app, move |state| {
+        let key = get_cached_key(&state)?;
+        let storage = state.storage.lock().map_err(|e| e.to_string())?;
+        let storage = storage.as_ref().ok_or("Database not initialized")?;
+
+        let from = storage.get_version(&from).map_err(|e| e.to_string())?;
+        let to = storage.get_version(&to).map_err(|e| e.to_string())?;
+        if from.project_id != project_id || to.project_id != project_id {
+            return Err("Both versions must belong to the project".to_string());
+        }
+        cooling_off::check(&**storage, &*state.clock, &project_id)?;
+        let old = open_version(&**storage, &from, &key, &password)?;
+        let new = open_version(&**storage, &to, &key, &password)?;
+        Ok(VersionDiff {
+            from_name: from.name,
+            to_name: to.name,
+            from_updated_at: from.updated_at,
+            to_updated_at: to.updated_at,
+            hunks: merge::diff(&old, &new),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn restore_version(state: State<AppState>, version_id: String) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    versions::restore(&**storage, &*state.clock, &version_id)
+}
+
+#[tauri::command]
+pub fn get_version_retention(state: State<AppState>) -> Result<VersionRetention, String> {
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    versions::retention(&**storage)
+}
+
+#[tauri::command]
+pub fn set_version_retention(
+    state: State<AppState>,
+    max_versions: u32,
+    max_age_days: u32,
+) -> Result<(), String> {
+    get_cached_key(&state)?;
+    let storage = state.storage.lock().map_err(|e| e.to_string())?;
+    let storage = storage.as_ref().ok_or("Database not initialized")?;
+    versions::set_retention(
+        &**storage,
+        &*state.clock,
+        VersionRetention {
+            max_versions,
+            max_age_days,
+        },
+    )
+}
@@ -0,0 +1,371 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::clock::Clock;
+use crate::crypto::{self, signing};
+use crate::models::Project;
+
+pub const ARCHIVE_FORMAT: &str = "vaultpad-compliance-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedProject {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub server_id: Option<String>,
+    pub sync_status: String,
+    pub encrypted_content: String,
+    pub key_check: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub created_at: String,
+    pub projects: Vec<ArchivedProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveTimestamp {
+    pub local_time: String,
+    pub authority: Option<String>,
+    /// DER-encoded RFC 3161 TimeStampResp over `state_digest`, base64.
+    pub token: Option<String>,
+}
+
+/// The part of the archive covered by the device signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveBody {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    pub project_count: usize,
+    pub state_digest: String,
+    pub timestamp: ArchiveTimestamp,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceArchive {
+    pub body: ArchiveBody,
+    pub public_key: String,
+    pub signature: String,
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+fn archived_project(p: &Project) -> ArchivedProject {
+    ArchivedProject {
+        id: p.id.clone(),
+        name: p.name.clone(),
+        created_at: p.created_at.clone(),
+        updated_at: p.updated_at.clone(),
+        server_id: p.server_id.clone(),
+        sync_status: p.sync_status.clone(),
+        encrypted_content: B64.encode(&p.encrypted_content),
+        key_check: B64.encode(&p.key_check),
+    }
+}
+
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const OID: u8 = 0x06;
+const GENERALIZED_TIME: u8 = 0x18;
+const CONTEXT_0: u8 = 0xa0;
+
+/// 2.16.840.1.101.3.4.2.1
+const OID_SHA256: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+/// 1.2.840.113549.1.7.2
+const OID_SIGNED_DATA: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+/// 1.2.840.113549.1.9.16.1.4
+const OID_TST_INFO: [u8; 11] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04];
+
+const ERR_TSA_MALFORMED: &str = "Malformed timestamp response";
+
+/// RFC 3161 TimeStampReq for a SHA-256 imprint, with a nonce and certReq = TRUE.
+fn timestamp_request_der(digest: &[u8; 32], nonce: &[u8; 8]) -> Vec<u8> {
+    let mut algorithm = vec![SEQUENCE, (2 + OID_SHA256.len() + 2) as u8, OID, OID_SHA256.len() as u8];
+    algorithm.extend_from_slice(&OID_SHA256);
+    algorithm.extend_from_slice(&[0x05, 0x00]);
+    let mut imprint = vec![SEQUENCE, (algorithm.len() + 2 + 32) as u8];
+    imprint.extend_from_slice(&algorithm);
+    imprint.extend_from_slice(&[OCTET_STRING, 0x20]);
+    imprint.extend_from_slice(digest);
+
+    let mut body = vec![INTEGER, 0x01, 0x01];
+    body.extend_from_slice(&imprint);
+    body.extend_from_slice(&[INTEGER, 0x08]);
+    body.extend_from_slice(nonce);
+    body.extend_from_slice(&[0x01, 0x01, 0xff]);
+
+    let mut req = vec![SEQUENCE, body.len() as u8];
+    req.extend_from_slice(&body);
+    req
+}
+
+/// The first DER element of `data`: its tag, its contents and what follows it.
+fn der_element(data: &[u8]) -> Result<(u8, &[u8], &[u8]), String> {
+    let malformed = || ERR_TSA_MALFORMED.to_string();
+    let (&tag, rest) = data.split_first().ok_or_else(malformed)?;
+    let (&first, rest) = rest.split_first().ok_or_else(malformed)?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return Err(malformed());
+        }
+        let len = rest[..count].iter().fold(0usize, |len, b| (len << 8) | *b as usize);
+        (len, &rest[count..])
+    };
+    if rest.len() < len {
+        return Err(malformed());
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
+
+/// `der_element` that must have tag `tag`; returns its contents and what follows it.
+fn der_expect(data: &[u8], tag: u8) -> Result<(&[u8], &[u8]), String> {
+    match der_element(data)? {
+        (found, content, rest) if found == tag => Ok((content, rest)),
+        _ => Err(ERR_TSA_MALFORMED.to_string()),
+    }
+}
+
+/// Check that a TimeStampResp grants a timestamp for `digest` and answers the request sent with
+/// `nonce`. The TSA's signature over the token is left to whoever verifies the archive.
+fn check_timestamp_response(resp: &[u8], digest: &[u8; 32], nonce: &[u8; 8]) -> Result<(), String> {
+    let (resp, _) = der_expect(resp, SEQUENCE)?;
+    let (status_info, rest) = der_expect(resp, SEQUENCE)?;
+    let (status, _) = der_expect(status_info, INTEGER)?;
+    // granted (0) or grantedWithMods (1); anything else carries no token.
+    if status != [0] && status != [1] {
+        let code = status.iter().fold(0i64, |code, b| (code << 8) | i64::from(*b));
+        return Err(format!("Timestamp authority refused the request (status {code})"));
+    }
+
+    let (content_info, _) = der_expect(rest, SEQUENCE)?;
+    let (content_type, rest) = der_expect(content_info, OID)?;
+    if content_type != OID_SIGNED_DATA {
+        return Err(ERR_TSA_MALFORMED.to_string());
+    }
+    let (signed_data, _) = der_expect(rest, CONTEXT_0)?;
+    let (signed_data, _) = der_expect(signed_data, SEQUENCE)?;
+    let (_, rest) = der_expect(signed_data, INTEGER)?;
+    let (_, rest) = der_expect(rest, SET)?;
+    let (encap_content, _) = der_expect(rest, SEQUENCE)?;
+    let (content_type, rest) = der_expect(encap_content, OID)?;
+    if content_type != OID_TST_INFO {
+        return Err(ERR_TSA_MALFORMED.to_string());
+    }
+    let (econtent, _) = der_expect(rest, CONTEXT_0)?;
+    let (tst_info, _) = der_expect(econtent, OCTET_STRING)?;
+
+    let (tst_info, _) = der_expect(tst_info, SEQUENCE)?;
+    let (_, rest) = der_expect(tst_info, INTEGER)?;
+    let (_, rest) = der_expect(rest, OID)?;
+    let (imprint, rest) = der_expect(rest, SEQUENCE)?;
+    let (algorithm, hashed) = der_expect(imprint, SEQUENCE)?;
+    let (algorithm, _) = der_expect(algorithm, OID)?;
+    let (hashed, _) = der_expect(hashed, OCTET_STRING)?;
+    if algorithm != OID_SHA256 || hashed != digest {
+        return Err("Timestamp is for a different digest".to_string());
+    }
+    let (_, rest) = der_expect(rest, INTEGER)?;
+    let (_, mut rest) = der_expect(rest, GENERALIZED_TIME)?;
+    // The nonce is the next INTEGER, after the optional accuracy and ordering.
+    let mut echoed = None;
+    while !rest.is_empty() {
+        let (tag, content, next) = der_element(rest)?;
+        if tag == INTEGER {
+            echoed = Some(content);
+            break;
+        }
+        rest = next;
+    }
+    if echoed != Some(&nonce[..]) {
+        return Err("Timestamp response does not answer this request".to_string());
+    }
+    Ok(())
+}
+
+/// Ask a timestamp authority to countersign `digest`. Returns the raw TimeStampResp once it is
+/// checked to grant a timestamp for `digest` in answer to this request.
+pub fn request_timestamp(authority_url: &str, digest: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; 8];
+    crypto::rng::fill(&mut nonce);
+    // Positive and without a leading zero byte, so its DER INTEGER is exactly these eight bytes.
+    nonce[0] = (nonce[0] & 0x7f) | 0x40;
+
+    let resp = Client::new()
+        .post(authority_url)
+        .header("Content-Type", "application/timestamp-query")
+        .body(timestamp_request_der(digest, &nonce))
+        .send()
+        .map_err(|e| format!("Timestamp authority unreachable: {e}"))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Timestamp authority error: {}", resp.status()));
+    }
+
+    let resp = resp
+        .bytes()
+        .map(|b| b.to_vec())
+        .map_err(|e| format!("Timestamp authority error: {e}"))?;
+    check_timestamp_response(&resp, digest, &nonce)?;
+    Ok(resp)
+}
+
+/// Encrypt the current vault state with `key`, timestamp its digest and sign it with the device key.
+pub fn seal(
+    clock: &dyn Clock,
+    projects: &[Project],
+    key: &[u8; crypto::KEY_LEN],
+    timestamp_authority: Option<&str>,
+    signing_key: &[u8; signing::SECRET_KEY_LEN],
+) -> Result<ComplianceArchive, String> {
+    let created_at = clock.now().to_rfc3339();
+    let manifest = ArchiveManifest {
+        created_at: created_at.clone(),
+        projects: projects.iter().map(archived_project).collect(),
+    };
+    let manifest_json = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+    let digest = sha256(&manifest_json);
+
+    let token = match timestamp_authority {
+        Some(url) => Some(B64.encode(request_timestamp(url, &digest)?)),
+        None => None,
+    };
+
+    let payload = crypto::encrypt_with_key(&manifest_json, key).map_err(|e| e.to_string())?;
+
+    let body = ArchiveBody {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        created_at: created_at.clone(),
+        project_count: manifest.projects.len(),
+        state_digest: hex::encode(digest),
+        timestamp: ArchiveTimestamp {
+            local_time: created_at,
+            authority: timestamp_authority.map(str::to_string),
+            token,
+        },
+        payload: B64.encode(payload),
+    };
+
+    let signed_bytes = serde_json::to_vec(&body).map_err(|e| e.to_string())?;
+    let signature = signing::sign(signing_key, &signed_bytes);
+
+    Ok(ComplianceArchive {
+        body,
+        public_key: B64.encode(signing::public_key(signing_key)),
+        signature: B64.encode(signature),
+    })
+}
+
+/// Check the archive format and the signature over its body.
+pub fn verify(archive: &ComplianceArchive) -> Result<(), String> {
+    if archive.body.format != ARCHIVE_FORMAT {
+        return Err("Not a compliance archive".to_string());
+    }
+    if archive.body.version > ARCHIVE_VERSION {
+        return Err(format!("Unsupported archive version {}", archive.body.version));
+    }
+
+    let public_key = B64.decode(&archive.public_key).map_err(|e| e.to_string())?;
+    let signature = B64.decode(&archive.signature).map_err(|e| e.to_string())?;
+    let signed_bytes = serde_json::to_vec(&archive.body).map_err(|e| e.to_string())?;
+
+    signing::verify(&public_key, &signed_bytes, &signature).map_err(|e| e.to_string())
+}
+
+/// Decrypt the archive payload and check it against the signed state digest.
+pub fn open(
+    archive: &ComplianceArchive,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<ArchiveManifest, String> {
+    let payload = B64.decode(&archive.body.payload).map_err(|e| e.to_string())?;
+    let manifest_json = crypto::try_decrypt_with_key(&payload, key)
+        .ok_or("Cannot decrypt archive with the current vault key")?;
+
+    if hex::encode(sha256(&manifest_json)) != archive.body.state_digest {
+        return Err("Archive payload does not match its state digest".to_string());
+    }
+
+    serde_json::from_slice(&manifest_json).map_err(|e| format!("Invalid archive payload: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if content.len() < 0x80 {
+            out.push(content.len() as u8);
+        } else {
+            out.extend_from_slice(&[0x82, (content.len() >> 8) as u8, content.len() as u8]);
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn response(status: u8, digest: &[u8; 32], nonce: &[u8]) -> Vec<u8> {
+        let algorithm = der(SEQUENCE, &[der(OID, &OID_SHA256), vec![0x05, 0x00]].concat());
+        let imprint = der(SEQUENCE, &[algorithm.clone(), der(OCTET_STRING, digest)].concat());
+        let tst_info = der(
+            SEQUENCE,
+            &[
+                der(INTEGER, &[1]),
+                der(OID, &[0x2a, 0x03, 0x04]),
+                imprint,
+                der(INTEGER, &[0x11, 0x22]),
+                der(GENERALIZED_TIME, b"20261016120000Z"),
+                der(SEQUENCE, &der(INTEGER, &[1])),
+                der(INTEGER, nonce),
+            ]
+            .concat(),
+        );
+        let encap = der(
+            SEQUENCE,
+            &[der(OID, &OID_TST_INFO), der(CONTEXT_0, &der(OCTET_STRING, &tst_info))].concat(),
+        );
+        let signed_data = der(
+            SEQUENCE,
+            &[der(INTEGER, &[3]), der(SET, &algorithm), encap, der(SET, &[])].concat(),
+        );
+        let token = der(SEQUENCE, &[der(OID, &OID_SIGNED_DATA), der(CONTEXT_0, &signed_data)].concat());
+        let status_info = der(SEQUENCE, &der(INTEGER, &[status]));
+        der(SEQUENCE, &[status_info, token].concat())
+    }
+
+    #[test]
+    fn granted_timestamps_for_the_digest_and_nonce_pass() {
+        let digest = [7u8; 32];
+        let nonce = [0x41, 2, 3, 4, 5, 6, 7, 8];
+        check_timestamp_response(&response(0, &digest, &nonce), &digest, &nonce).unwrap();
+        check_timestamp_response(&response(1, &digest, &nonce), &digest, &nonce).unwrap();
+    }
+
+    #[test]
+    fn refused_or_mismatched_timestamps_are_rejected() {
+        let digest = [7u8; 32];
+        let nonce = [0x41, 2, 3, 4, 5, 6, 7, 8];
+        let refused = der(SEQUENCE, &der(SEQUENCE, &der(INTEGER, &[2])));
+        assert!(check_timestamp_response(&refused, &digest, &nonce).unwrap_err().contains("status 2"));
+        assert!(check_timestamp_response(&response(3, &digest, &nonce), &digest, &nonce).is_err());
+        assert!(check_timestamp_response(&response(0, &[8u8; 32], &nonce), &digest, &nonce).is_err());
+        assert!(check_timestamp_response(&response(0, &digest, &[0x42; 8]), &digest, &nonce).is_err());
+        assert!(check_timestamp_response(&[0x30, 0x05, 0x30], &digest, &nonce).is_err());
+    }
+}
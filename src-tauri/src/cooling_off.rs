@@ -0,0 +1,200 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Time-delayed access ("cooling off") for designated projects. Opening a flagged project
+//! first needs an access request; the content is released only once the project's delay has
+//! passed, and then for a limited window. The request time lives in the vault metadata, so
+//! restarting the app neither resets nor skips the wait.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::storage::StorageProvider;
+
+const META_COOLING_OFF: &str = "cooling_off";
+/// How long a project stays readable after its delay has passed.
+pub const ACCESS_WINDOW_SECS: i64 = 15 * 60;
+pub const MAX_DELAY_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    delay_secs: u64,
+    requested_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// No access request is pending.
+    Locked,
+    Waiting,
+    Open,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessStatus {
+    pub project_id: String,
+    pub delay_secs: u64,
+    pub phase: Phase,
+    /// Until the content is released (`waiting`) or locked again (`open`).
+    pub remaining_secs: i64,
+}
+
+fn load(storage: &dyn StorageProvider) -> Result<HashMap<String, Entry>, String> {
+    match storage.get_meta(META_COOLING_OFF).map_err(|e| e.to_string())? {
+        Some(json) if !json.is_empty() => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid cooling-off data: {e}"))
+        }
+        _ => Ok(HashMap::new()),
+    }
+}
+
+fn save(storage: &dyn StorageProvider, entries: &HashMap<String, Entry>) -> Result<(), String> {
+    let json = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_COOLING_OFF, &json)
+        .map_err(|e| e.to_string())
+}
+
+fn evaluate(project_id: &str, entry: &Entry, now: DateTime<Utc>) -> AccessStatus {
+    let requested = entry
+        .requested_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Utc));
+    let (phase, remaining_secs) = match requested {
+        None => (Phase::Locked, 0),
+        Some(at) => {
+            let ready = at + Duration::seconds(entry.delay_secs as i64);
+            let closes = ready + Duration::seconds(ACCESS_WINDOW_SECS);
+            if now < ready {
+                (Phase::Waiting, (ready - now).num_seconds().max(1))
+            } else if now < closes {
+                (Phase::Open, (closes - now).num_seconds())
+            } else {
+                (Phase::Locked, 0)
+            }
+        }
+    };
+    AccessStatus {
+        project_id: project_id.to_string(),
+        delay_secs: entry.delay_secs,
+        phase,
+        remaining_secs,
+    }
+}
+
+pub fn status(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: &str,
+) -> Result<Option<AccessStatus>, String> {
+    Ok(load(storage)?
+        .get(project_id)
+        .map(|entry| evaluate(project_id, entry, clock.now())))
+}
+
+/// `Ok` when the project is not flagged or its access window is open; otherwise
+/// `cooling_off_locked` or `cooling_off_waiting:<seconds>`.
+pub fn check(storage: &dyn StorageProvider, clock: &dyn Clock, project_id: &str) -> Result<(), String> {
+    match status(storage, clock, project_id)? {
+        None => Ok(()),
+        Some(s) => match s.phase {
+            Phase::Open => Ok(()),
+            Phase::Waiting => Err(format!("cooling_off_waiting:{}", s.remaining_secs)),
+            Phase::Locked => Err("cooling_off_locked".to_string()),
+        },
+    }
+}
+
+/// Start the timer. A request that is already waiting or open is left as it is.
+pub fn request_access(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: &str,
+) -> Result<AccessStatus, String> {
+    let mut entries = load(storage)?;
+    let now = clock.now();
+    let entry = entries
+        .get_mut(project_id)
+        .ok_or("Project has no access delay")?;
+    if evaluate(project_id, entry, now).phase == Phase::Locked {
+        entry.requested_at = Some(now.to_rfc3339());
+    }
+    let status = evaluate(project_id, entry, now);
+    save(storage, &entries)?;
+    Ok(status)
+}
+
+/// Flag a project with `delay_secs`, or clear the flag with `None`. Lowering or removing an
+/// existing delay is only allowed while the project's access window is open, so the delay
+/// cannot be switched off on impulse either.
+pub fn configure(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: &str,
+    delay_secs: Option<u64>,
+) -> Result<(), String> {
+    if delay_secs.is_some_and(|d| d == 0 || d > MAX_DELAY_SECS) {
+        return Err(format!("Delay must be between 1 and {MAX_DELAY_SECS} seconds"));
+    }
+    let mut entries = load(storage)?;
+    if let Some(current) = entries.get(project_id) {
+        let weakens = delay_secs.is_none_or(|d| d < current.delay_secs);
+        if weakens && evaluate(project_id, current, clock.now()).phase != Phase::Open {
+            return Err("cooling_off_locked".to_string());
+        }
+    }
+    match delay_secs {
+        Some(delay_secs) => {
+            let requested_at = entries.get(project_id).and_then(|e| e.requested_at.clone());
+            entries.insert(
+                project_id.to_string(),
+                Entry {
+                    delay_secs,
+                    requested_at,
+                },
+            );
+        }
+        None => {
+            entries.remove(project_id);
+        }
+    }
+    save(storage, &entries)
+}
+
+/// Drop the entry of a deleted project.
+pub fn forget(storage: &dyn StorageProvider, project_id: &str) -> Result<(), String> {
+    let mut entries = load(storage)?;
+    if entries.remove(project_id).is_some() {
+        save(storage, &entries)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_opens_after_the_delay_for_a_window() {
+        let start = Utc::now();
+        let entry = Entry {
+            delay_secs: 3600,
+            requested_at: Some(start.to_rfc3339()),
+        };
+        let at = |secs: i64| evaluate("p", &entry, start + Duration::seconds(secs)).phase;
+        assert_eq!(at(10), Phase::Waiting);
+        assert_eq!(at(3600), Phase::Open);
+        assert_eq!(at(3600 + ACCESS_WINDOW_SECS), Phase::Locked);
+
+        let idle = Entry {
+            delay_secs: 3600,
+            requested_at: None,
+        };
+        assert_eq!(evaluate("p", &idle, start).phase, Phase::Locked);
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Credential projects: a username, password, URL and notes instead of free text. The fields
+//! are kept as the project content, a small JSON document encrypted exactly like a note's
+//! text, so sync, version history, custom passwords and re-encryption treat both kinds alike.
+//! Which kind a project is shows only once its content is decrypted, by the document's `type`.
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::models::{Credential, ProjectKind};
+
+const DOCUMENT_TYPE: &str = "vaultpad/credential";
+const DOCUMENT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    #[serde(rename = "type")]
+    document_type: String,
+    version: u32,
+    credential: Credential,
+}
+
+/// The content to store for `credential`.
+pub fn encode(credential: &Credential) -> Result<Zeroizing<String>, String> {
+    let document = Document {
+        document_type: DOCUMENT_TYPE.to_string(),
+        version: DOCUMENT_VERSION,
+        credential: credential.clone(),
+    };
+    serde_json::to_string(&document).map(Zeroizing::new).map_err(|e| e.to_string())
+}
+
+/// The credential stored as `content`; `None` when the project is a note. Documents from a
+/// newer version are read for the fields this one knows.
+pub fn decode(content: &str) -> Option<Credential> {
+    if !content.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str::<Document>(content)
+        .ok()
+        .filter(|d| d.document_type == DOCUMENT_TYPE)
+        .map(|d| d.credential)
+}
+
+/// The kind of a project with `content`, along with its credential when it is one.
+pub fn parse(content: &str) -> (ProjectKind, Option<Credential>) {
+    match decode(content) {
+        Some(credential) => (ProjectKind::Credential, Some(credential)),
+        None => (ProjectKind::Note, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_round_trip_and_notes_stay_notes() {
+        let credential = Credential {
+            username: "admin".to_string(),
+            password: "s3cret".to_string(),
+            url: "https://router.local".to_string(),
+            notes: "Reset by holding the button".to_string(),
+        };
+        let content = encode(&credential).unwrap();
+        let (kind, decoded) = parse(&content);
+        assert_eq!(kind, ProjectKind::Credential);
+        assert_eq!(decoded.unwrap().password, "s3cret");
+
+        assert_eq!(parse("just some text").0, ProjectKind::Note);
+        assert_eq!(parse(r#"{"type": "something else", "version": 1}"#).0, ProjectKind::Note);
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! YubiKey HMAC-SHA1 challenge-response, as used by KeePassXC. The vault stores a random
+//! challenge; the key's response is mixed with the master password before Argon2, so the
+//! password alone no longer unwraps the data key. The YubiKey is driven through `ykman` or,
+//! failing that, `ykchalresp` from ykpers.
+
+use std::process::{Command, Stdio};
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::rng;
+
+pub const CHALLENGE_LEN: usize = 32;
+pub const RESPONSE_LEN: usize = 20;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChallengeResponseError {
+    #[error("No YubiKey tool found; install ykman or ykpers")]
+    ToolMissing,
+    #[error("YubiKey did not respond: {0}")]
+    Device(String),
+    #[error("Invalid YubiKey response")]
+    InvalidResponse,
+    #[error("Invalid YubiKey slot {0}; expected 1 or 2")]
+    InvalidSlot(u8),
+}
+
+pub fn random_challenge() -> [u8; CHALLENGE_LEN] {
+    let mut challenge = [0u8; CHALLENGE_LEN];
+    rng::fill(&mut challenge);
+    challenge
+}
+
+fn commands(slot: u8, challenge_hex: &str) -> [Command; 2] {
+    let mut ykman = Command::new("ykman");
+    ykman.args(["otp", "calculate", &slot.to_string(), challenge_hex]);
+    let mut ykchalresp = Command::new("ykchalresp");
+    ykchalresp.args([&format!("-{slot}"), "-x", challenge_hex]);
+    [ykman, ykchalresp]
+}
+
+/// Whether one of the supported command-line tools is installed.
+pub fn is_available() -> bool {
+    ["ykman", "ykchalresp"].iter().any(|tool| {
+        Command::new(tool)
+            .arg("--help")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    })
+}
+
+/// Send `challenge` to the HMAC-SHA1 slot (1 or 2) of the connected YubiKey. Blocks until the
+/// key answers, which includes waiting for a touch if the slot requires one.
+pub fn respond(
+    slot: u8,
+    challenge: &[u8],
+) -> Result<Zeroizing<[u8; RESPONSE_LEN]>, ChallengeResponseError> {
+    if slot != 1 && slot != 2 {
+        return Err(ChallengeResponseError::InvalidSlot(slot));
+    }
+
+    let challenge_hex = hex::encode(challenge);
+    for mut cmd in commands(slot, &challenge_hex) {
+        let output = match cmd.stdin(Stdio::null()).output() {
+            Ok(output) => output,
+            // Tool not installed; try the next one.
+            Err(_) => continue,
+        };
+        let stdout = Zeroizing::new(output.stdout);
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(ChallengeResponseError::Device(message));
+        }
+
+        let text = std::str::from_utf8(&stdout).map_err(|_| ChallengeResponseError::InvalidResponse)?;
+        let mut response = Zeroizing::new([0u8; RESPONSE_LEN]);
+        hex::decode_to_slice(text.trim(), &mut response[..])
+            .map_err(|_| ChallengeResponseError::InvalidResponse)?;
+        return Ok(response);
+    }
+    Err(ChallengeResponseError::ToolMissing)
+}
+
+/// Argon2 input for a password protected by challenge-response:
+/// `hex(SHA-256(SHA-256(password) || SHA-256(response)))`, the composite-key construction
+/// KeePassXC uses.
+pub fn mix(password: &str, response: &[u8]) -> Zeroizing<String> {
+    let mut composite = Sha256::new();
+    composite.update(Sha256::digest(password.as_bytes()));
+    composite.update(Sha256::digest(response));
+    Zeroizing::new(hex::encode(composite.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_depends_on_password_and_response() {
+        let response = [7u8; RESPONSE_LEN];
+        let mixed = mix("hunter2", &response);
+        assert_eq!(*mixed, *mix("hunter2", &response));
+        assert_ne!(*mixed, *mix("hunter3", &response));
+        assert_ne!(*mixed, *mix("hunter2", &[8u8; RESPONSE_LEN]));
+        assert!(matches!(
+            respond(3, &random_challenge()),
+            Err(ChallengeResponseError::InvalidSlot(3))
+        ));
+    }
+}
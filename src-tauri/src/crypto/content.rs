@@ -0,0 +1,201 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Project content under the vault key. Content of local projects is bound to the project id
+//! and to the project's name, which is stored in the clear beside it, so neither can be swapped
+//! for another record's without the decryption failing. Backups and versions keep the blob of
+//! the revision they were taken from, so they open with the name recorded alongside them.
+//!
+//! Older vaults hold content bound to the id alone or not bound at all. Such content opens
+//! until `rehash::bind_content` has bound everything in the vault and `require_binding` has
+//! been called for its key; from then on only content that is portable by design, that of
+//! synced projects, may still be unbound.
+
+use std::io::Read;
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use super::{
+    base_format, decrypt_auto, encrypt_with_key, encrypt_with_key_aad, field_aad, is_aad_bound,
+    is_streamed, open_stream, open_with_key, stream_with_key_aad, CryptoError, StreamedSeal,
+    FORMAT_V2, FORMAT_V3, KEY_LEN,
+};
+
+static BOUND_KEY: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+/// Identifies a key without keeping a copy of it.
+fn key_id(key: &[u8; KEY_LEN]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"vaultpad/bound-content");
+    hasher.update(key);
+    hasher.finalize().into()
+}
+
+/// Refuse unbound content of local projects under `key` from now on, or stop refusing it with
+/// `None`. Only the open vault's key is remembered.
+pub fn require_binding(key: Option<&[u8; KEY_LEN]>) {
+    if let Ok(mut bound) = BOUND_KEY.lock() {
+        *bound = key.map(key_id);
+    }
+}
+
+fn binding_required(key: &[u8; KEY_LEN]) -> bool {
+    BOUND_KEY
+        .lock()
+        .map(|bound| *bound == Some(key_id(key)))
+        .unwrap_or(true)
+}
+
+/// Associated data for the content of project `project_id` named `name`.
+pub fn content_aad(project_id: &str, name: &str) -> Vec<u8> {
+    let mut aad = field_aad(project_id, "content");
+    aad.push(0);
+    aad.extend_from_slice(name.as_bytes());
+    aad
+}
+
+/// Encrypt content for project `project_id` named `name`; unbound unless `bind` is set.
+pub fn encrypt(
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    project_id: &str,
+    name: &str,
+    bind: bool,
+) -> Result<Vec<u8>, CryptoError> {
+    if bind {
+        encrypt_with_key_aad(plaintext, key, &content_aad(project_id, name))
+    } else {
+        encrypt_with_key(plaintext, key)
+    }
+}
+
+/// `encrypt` with binding, for content from `STREAM_THRESHOLD` up that is sealed only as it is
+/// written out.
+pub fn stream(
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    project_id: &str,
+    name: &str,
+) -> Result<StreamedSeal, CryptoError> {
+    stream_with_key_aad(plaintext, key, &content_aad(project_id, name))
+}
+
+/// Whether `data` is content bound to project `project_id` named `name`.
+pub fn is_bound(data: &[u8], key: &[u8; KEY_LEN], project_id: &str, name: &str) -> bool {
+    is_aad_bound(data) && open_with_key(data, key, &content_aad(project_id, name)).is_some()
+}
+
+/// Decrypt content of project `project_id` named `name`. `portable` content, that of synced
+/// projects, is accepted in any format the vault key opens; other content only once bound,
+/// if its key requires binding.
+pub fn decrypt(
+    data: &[u8],
+    key: &[u8; KEY_LEN],
+    project_id: &str,
+    name: &str,
+    portable: bool,
+) -> Result<Vec<u8>, CryptoError> {
+    if is_aad_bound(data) {
+        if let Some(plaintext) = open_with_key(data, key, &content_aad(project_id, name)) {
+            return Ok(plaintext);
+        }
+    }
+    if !portable && binding_required(key) {
+        return Err(CryptoError::DecryptionFailed(
+            "Content is not bound to its project".to_string(),
+        ));
+    }
+    if is_aad_bound(data) {
+        if let Some(plaintext) = open_with_key(data, key, &field_aad(project_id, "content")) {
+            return Ok(plaintext);
+        }
+        // A V1 salt may start with the V3 marker by chance; fall through to the V1 paths.
+    }
+    decrypt_auto(data, Some(key), None)
+}
+
+/// `decrypt` reading the content from `reader`, so chunked content is opened as it is read.
+/// Chunked content bound to the id alone, or a V1 blob whose salt happens to look chunked, fails
+/// here even where `decrypt` would open it.
+pub fn decrypt_from(
+    reader: &mut dyn Read,
+    key: &[u8; KEY_LEN],
+    project_id: &str,
+    name: &str,
+    portable: bool,
+) -> Result<Vec<u8>, CryptoError> {
+    let failed = |e: std::io::Error| CryptoError::DecryptionFailed(e.to_string());
+    let mut version = [0u8; 1];
+    reader.read_exact(&mut version).map_err(failed)?;
+    let version = version[0];
+    let aad = match base_format(version) {
+        _ if !is_streamed(version) => None,
+        FORMAT_V3 => Some(content_aad(project_id, name)),
+        FORMAT_V2 if portable || !binding_required(key) => Some(Vec::new()),
+        FORMAT_V2 => {
+            return Err(CryptoError::DecryptionFailed(
+                "Content is not bound to its project".to_string(),
+            ))
+        }
+        _ => None,
+    };
+    let Some(aad) = aad else {
+        let mut data = vec![version];
+        reader.read_to_end(&mut data).map_err(failed)?;
+        return decrypt(&data, key, project_id, name, portable);
+    };
+    open_stream(version, reader, key, &aad)
+        .ok_or_else(|| CryptoError::DecryptionFailed("Content does not open".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_is_bound_to_id_and_name() {
+        let key = [0x4b; KEY_LEN];
+        let sealed = encrypt(b"secret", &key, "project-a", "Bank", true).unwrap();
+        assert!(is_bound(&sealed, &key, "project-a", "Bank"));
+        assert_eq!(decrypt(&sealed, &key, "project-a", "Bank", false).unwrap(), b"secret");
+        assert!(decrypt(&sealed, &key, "project-b", "Bank", false).is_err());
+        assert!(decrypt(&sealed, &key, "project-a", "Mail", false).is_err());
+    }
+
+    #[test]
+    fn unbound_content_is_refused_once_binding_is_required() {
+        let key = [0x5b; KEY_LEN];
+        let unbound = encrypt(b"old", &key, "project-a", "Bank", false).unwrap();
+        let id_only =
+            encrypt_with_key_aad(b"older", &key, &field_aad("project-a", "content")).unwrap();
+        assert_eq!(decrypt(&unbound, &key, "project-a", "Bank", false).unwrap(), b"old");
+        assert_eq!(decrypt(&id_only, &key, "project-a", "Bank", false).unwrap(), b"older");
+
+        require_binding(Some(&key));
+        assert!(decrypt(&unbound, &key, "project-a", "Bank", false).is_err());
+        assert!(decrypt(&id_only, &key, "project-a", "Bank", false).is_err());
+        assert_eq!(decrypt(&unbound, &key, "project-a", "Bank", true).unwrap(), b"old");
+        require_binding(None);
+        assert_eq!(decrypt(&unbound, &key, "project-a", "Bank", false).unwrap(), b"old");
+    }
+
+    #[test]
+    fn streamed_content_opens_as_it_is_read() {
+        let key = [0x6b; KEY_LEN];
+        let large = "a long note\n".repeat(super::super::STREAM_THRESHOLD / 10);
+        let seal = stream(large.as_bytes(), &key, "project-a", "Bank").unwrap();
+        let mut sealed = Vec::new();
+        seal.write_to(&mut sealed).unwrap();
+        assert_eq!(sealed.len() as u64, seal.sealed_len());
+        assert!(is_bound(&sealed, &key, "project-a", "Bank"));
+
+        let opened = decrypt_from(&mut sealed.as_slice(), &key, "project-a", "Bank", false);
+        assert_eq!(opened.unwrap(), large.as_bytes());
+        assert!(decrypt_from(&mut sealed.as_slice(), &key, "project-a", "Mail", false).is_err());
+
+        let small = encrypt(b"small", &key, "project-a", "Bank", true).unwrap();
+        let opened = decrypt_from(&mut small.as_slice(), &key, "project-a", "Bank", false);
+        assert_eq!(opened.unwrap(), b"small");
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Page-locked storage for the vault key. The key lives on its own page, which is locked into
+//! RAM (`mlock` / `VirtualLock`) so it is never written to swap, excluded from core dumps where
+//! the platform allows, and zeroed whenever the key is replaced, cleared or dropped.
+
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use super::KEY_LEN;
+
+pub struct LockedKey {
+    ptr: NonNull<[u8; KEY_LEN]>,
+    layout: Layout,
+    locked: bool,
+    present: bool,
+}
+
+// The key is only reachable through `&self` for reads and `&mut self` for writes.
+unsafe impl Send for LockedKey {}
+unsafe impl Sync for LockedKey {}
+
+impl LockedKey {
+    /// An empty slot on a freshly allocated, locked page. Locking is best effort: without the
+    /// privilege or under a low `RLIMIT_MEMLOCK` the key is still kept and zeroed, only not pinned.
+    pub fn new() -> Self {
+        let page = page_size();
+        let layout = Layout::from_size_align(page, page).expect("page size is a power of two");
+        let raw = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(raw as *mut [u8; KEY_LEN])
+            .unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        let locked = unsafe { lock_page(raw, page) };
+        Self {
+            ptr,
+            layout,
+            locked,
+            present: false,
+        }
+    }
+
+    /// Run `f` on the key in place, if one is set. Callers that have to keep the key past the
+    /// call copy it into a `Zeroizing`.
+    pub fn with_key<R>(&self, f: impl FnOnce(&[u8; KEY_LEN]) -> R) -> Option<R> {
+        self.present.then(|| f(unsafe { self.ptr.as_ref() }))
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.present
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.present
+    }
+
+    /// Whether the page is actually pinned in RAM.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Store `key`, overwriting the previous one in place.
+    pub fn set(&mut self, key: [u8; KEY_LEN]) {
+        unsafe { *self.ptr.as_mut() = key };
+        self.present = true;
+    }
+
+    /// Zero the key bytes and leave the slot empty.
+    pub fn clear(&mut self) {
+        unsafe { self.ptr.as_mut() }.zeroize();
+        self.present = false;
+    }
+}
+
+/// For `with_key`: a copy of the key that is zeroed when dropped.
+pub fn copy(key: &[u8; KEY_LEN]) -> Zeroizing<[u8; KEY_LEN]> {
+    Zeroizing::new(*key)
+}
+
+impl Default for LockedKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LockedKey {
+    fn drop(&mut self) {
+        self.clear();
+        let raw = self.ptr.as_ptr() as *mut u8;
+        unsafe {
+            if self.locked {
+                unlock_page(raw, self.layout.size());
+            }
+            alloc::dealloc(raw, self.layout);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(unix)]
+unsafe fn lock_page(ptr: *mut u8, len: usize) -> bool {
+    #[cfg(target_os = "linux")]
+    libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_DONTDUMP);
+    libc::mlock(ptr as *const libc::c_void, len) == 0
+}
+
+#[cfg(unix)]
+unsafe fn unlock_page(ptr: *mut u8, len: usize) {
+    libc::munlock(ptr as *const libc::c_void, len);
+}
+
+#[cfg(windows)]
+unsafe fn lock_page(ptr: *mut u8, len: usize) -> bool {
+    windows_sys::Win32::System::Memory::VirtualLock(ptr as *const core::ffi::c_void, len) != 0
+}
+
+#[cfg(windows)]
+unsafe fn unlock_page(ptr: *mut u8, len: usize) {
+    windows_sys::Win32::System::Memory::VirtualUnlock(ptr as *const core::ffi::c_void, len);
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn lock_page(_ptr: *mut u8, _len: usize) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn unlock_page(_ptr: *mut u8, _len: usize) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_zeroes_the_key() {
+        let mut slot = LockedKey::new();
+        assert!(slot.is_none());
+        slot.set([7u8; KEY_LEN]);
+        assert_eq!(slot.with_key(|key| key[0]), Some(7));
+        slot.clear();
+        assert!(slot.is_none() && slot.with_key(|_| ()).is_none());
+        assert_eq!(unsafe { *slot.ptr.as_ref() }, [0u8; KEY_LEN]);
+    }
+}
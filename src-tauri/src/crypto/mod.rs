@@ -1,13 +1,27 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+use std::io::{Read, Write};
+
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     Aes256Gcm, Nonce,
 };
 use argon2::{Argon2, Params, Version};
-use rand::RngCore;
-use zeroize::Zeroize;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+pub mod challenge_response;
+pub mod content;
+pub mod locked;
+pub mod nonce;
+pub mod rng;
+pub mod sealed;
+pub mod self_test;
+pub mod signing;
+pub mod sss;
+pub mod stream;
+pub mod wrap;
 
 const SALT_LEN: usize = 16;
 const NONCE_LEN: usize = 12;
@@ -17,6 +31,11 @@ const ARGON2_MEMORY_KB: u32 = 16384; // 16 MB -- balanced for local desktop use
 const ARGON2_ITERATIONS: u32 = 1;
 const ARGON2_PARALLELISM: u32 = 1;
 
+const KDF_MIN_MEMORY_KB: u32 = 8192;
+const KDF_MAX_MEMORY_KB: u32 = 1024 * 1024; // 1 GB
+const KDF_MAX_ITERATIONS: u32 = 10;
+const KDF_MAX_PARALLELISM: u32 = 16;
+
 const LEGACY_ARGON2_MEMORY_KB: u32 = 65536; // old: 64 MB
 const LEGACY_ARGON2_ITERATIONS: u32 = 3;
 const LEGACY_ARGON2_PARALLELISM: u32 = 4;
@@ -28,6 +47,19 @@ const PIN_ARGON2_PARALLELISM: u32 = 1;
 const VERIFICATION_PLAINTEXT: &[u8] = b"ACCESS_STORAGE_OK";
 
 const FORMAT_V2: u8 = 0x02;
+const FORMAT_V3: u8 = 0x03;
+/// Set on V2/V3 blobs whose plaintext was zstd-compressed before encryption.
+const FORMAT_COMPRESSED: u8 = 0x10;
+/// Set on blobs sealed in chunks by `stream`; always combined with `FORMAT_COMPRESSED`.
+const FORMAT_STREAMED: u8 = 0x20;
+
+/// Plaintexts at least this long are compressed before encryption, if that makes them smaller.
+pub const COMPRESSION_THRESHOLD: usize = 4096;
+const COMPRESSION_LEVEL: i32 = 3;
+/// Plaintexts at least this long are compressed and sealed chunk by chunk.
+pub const STREAM_THRESHOLD: usize = 1024 * 1024;
+/// Refuse to inflate a blob beyond this, whatever its compressed size.
+const MAX_DECOMPRESSED_LEN: usize = 256 * 1024 * 1024;
 
 #[derive(Debug, thiserror::Error)]
 pub enum CryptoError {
@@ -39,6 +71,8 @@ pub enum CryptoError {
     InvalidFormat,
     #[error("Key derivation failed: {0}")]
     KeyDerivationFailed(String),
+    #[error("Signature verification failed: {0}")]
+    SignatureInvalid(String),
 }
 
 fn derive_key_with_params(
@@ -62,39 +96,291 @@ fn derive_key(password: &[u8], salt: &[u8]) -> Result<[u8; KEY_LEN], CryptoError
     derive_key_with_params(password, salt, ARGON2_MEMORY_KB, ARGON2_ITERATIONS, ARGON2_PARALLELISM)
 }
 
+/// Argon2id cost parameters for the master key, stored per vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kb: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kb: ARGON2_MEMORY_KB,
+            iterations: ARGON2_ITERATIONS,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl KdfParams {
+    pub fn validate(&self) -> Result<(), CryptoError> {
+        if !(KDF_MIN_MEMORY_KB..=KDF_MAX_MEMORY_KB).contains(&self.memory_kb) {
+            return Err(CryptoError::KeyDerivationFailed(format!(
+                "memory must be between {} and {} KB",
+                KDF_MIN_MEMORY_KB, KDF_MAX_MEMORY_KB
+            )));
+        }
+        if !(1..=KDF_MAX_ITERATIONS).contains(&self.iterations) {
+            return Err(CryptoError::KeyDerivationFailed(format!(
+                "iterations must be between 1 and {}",
+                KDF_MAX_ITERATIONS
+            )));
+        }
+        if !(1..=KDF_MAX_PARALLELISM).contains(&self.parallelism) {
+            return Err(CryptoError::KeyDerivationFailed(format!(
+                "parallelism must be between 1 and {}",
+                KDF_MAX_PARALLELISM
+            )));
+        }
+        Ok(())
+    }
+
+    /// These parameters pulled into the range `validate` accepts, so parameters read back from
+    /// a vault file cannot make a derivation take unbounded memory or time.
+    pub fn clamped(self) -> Self {
+        Self {
+            memory_kb: self.memory_kb.clamp(KDF_MIN_MEMORY_KB, KDF_MAX_MEMORY_KB),
+            iterations: self.iterations.clamp(1, KDF_MAX_ITERATIONS),
+            parallelism: self.parallelism.clamp(1, KDF_MAX_PARALLELISM),
+        }
+    }
+}
+
 pub fn derive_master_key(password: &str) -> Result<[u8; KEY_LEN], CryptoError> {
+    derive_master_key_with_params(password, &KdfParams::default())
+}
+
+pub fn derive_master_key_with_params(
+    password: &str,
+    params: &KdfParams,
+) -> Result<[u8; KEY_LEN], CryptoError> {
     let salt = b"access-storage-session-key-salt!";
-    derive_key(password.as_bytes(), salt)
+    derive_key_with_params(
+        password.as_bytes(),
+        salt,
+        params.memory_kb,
+        params.iterations,
+        params.parallelism,
+    )
 }
 
-/// V2 encrypt: version(1) || nonce(12) || ciphertext. Uses pre-derived key, no Argon2id.
-pub fn encrypt_with_key(plaintext: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<u8>, CryptoError> {
-    let mut nonce_bytes = [0u8; NONCE_LEN];
-    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+/// Associated data binding a ciphertext to one field of one record.
+pub fn field_aad(record_id: &str, field: &str) -> Vec<u8> {
+    format!("vaultpad/{}/{}", record_id, field).into_bytes()
+}
+
+/// The format byte is authenticated along with `aad` for compressed blobs, so the flag cannot be
+/// added or stripped without the decryption failing.
+fn compressed_aad(version: u8, aad: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(1 + aad.len());
+    bound.push(version);
+    bound.extend_from_slice(aad);
+    bound
+}
+
+/// The V2/V3 format of a version byte, without the compression and streaming flags.
+fn base_format(version: u8) -> u8 {
+    version & !(FORMAT_COMPRESSED | FORMAT_STREAMED | stream::SALTED)
+}
+
+/// Whether `data` is sealed in chunks, and so can be opened by `open_stream` as it is read.
+fn is_streamed(version: u8) -> bool {
+    version & FORMAT_STREAMED != 0
+}
+
+fn seal_with_key(
+    version: u8,
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    if plaintext.len() >= STREAM_THRESHOLD {
+        return seal_streamed(version, plaintext, key, aad);
+    }
+    if plaintext.len() >= COMPRESSION_THRESHOLD {
+        if let Ok(mut compressed) = zstd::bulk::compress(plaintext, COMPRESSION_LEVEL) {
+            let sealed = (compressed.len() < plaintext.len()).then(|| {
+                let version = version | FORMAT_COMPRESSED;
+                seal_raw(version, &compressed, key, &compressed_aad(version, aad))
+            });
+            compressed.zeroize();
+            if let Some(sealed) = sealed {
+                return sealed;
+            }
+        }
+    }
+    seal_raw(version, plaintext, key, aad)
+}
+
+fn seal_raw(
+    version: u8,
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let nonce_bytes = nonce::next(key);
 
     let cipher = Aes256Gcm::new_from_slice(key)
         .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
     let nonce = Nonce::from_slice(&nonce_bytes);
     let ciphertext = cipher
-        .encrypt(nonce, plaintext)
+        .encrypt(nonce, Payload { msg: plaintext, aad })
         .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
 
     let mut result = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
-    result.push(FORMAT_V2);
+    result.push(version);
     result.extend_from_slice(&nonce_bytes);
     result.extend_from_slice(&ciphertext);
     Ok(result)
 }
 
-/// Attempts V2 decryption only. Returns Some(plaintext) if data is V2-format and the key matches.
-pub fn try_decrypt_with_key(data: &[u8], key: &[u8; KEY_LEN]) -> Option<Vec<u8>> {
-    if data.is_empty() || data[0] != FORMAT_V2 || data.len() < 1 + NONCE_LEN + 1 {
+/// A payload compressed and ready to be sealed in chunks as it is written out, so its whole
+/// ciphertext never has to be held in memory (see `StorageProvider::create_project_streamed`).
+pub struct StreamedSeal {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    version: u8,
+    aad: Vec<u8>,
+    compressed: Zeroizing<Vec<u8>>,
+}
+
+impl StreamedSeal {
+    fn new(
+        version: u8,
+        plaintext: &[u8],
+        key: &[u8; KEY_LEN],
+        aad: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let version = version | FORMAT_STREAMED | FORMAT_COMPRESSED | stream::SALTED;
+        let compressed = zstd::bulk::compress(plaintext, COMPRESSION_LEVEL)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+        Ok(Self {
+            key: Zeroizing::new(*key),
+            version,
+            aad: compressed_aad(version, aad),
+            compressed: Zeroizing::new(compressed),
+        })
+    }
+
+    /// Length of the sealed payload, as `write_to` writes it.
+    pub fn sealed_len(&self) -> u64 {
+        stream::sealed_len(self.compressed.len() as u64)
+    }
+
+    /// Seal the payload into `sink`, one chunk at a time.
+    pub fn write_to(&self, sink: &mut dyn Write) -> std::io::Result<()> {
+        let mut writer = stream::StreamWriter::new(sink, &self.key, self.version, &self.aad)?;
+        writer.write_all(&self.compressed)?;
+        writer.finish().map(|_| ())
+    }
+}
+
+fn seal_streamed(
+    version: u8,
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let seal = StreamedSeal::new(version, plaintext, key, aad)?;
+    let mut sealed = Vec::with_capacity(seal.sealed_len() as usize);
+    seal.write_to(&mut sealed)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+    Ok(sealed)
+}
+
+/// Open a chunked stream whose version byte, already read, is `version`, reading the rest of it
+/// from `rest`.
+fn open_stream(version: u8, rest: impl Read, key: &[u8; KEY_LEN], aad: &[u8]) -> Option<Vec<u8>> {
+    if version & FORMAT_COMPRESSED == 0 {
+        return None;
+    }
+    let header = [version];
+    let (_, reader) =
+        stream::StreamReader::new(header.as_slice().chain(rest), key, &compressed_aad(version, aad))
+            .ok()?;
+    let decoder = zstd::stream::Decoder::new(reader).ok()?;
+    let mut plaintext = Vec::new();
+    let read = decoder
+        .take(MAX_DECOMPRESSED_LEN as u64 + 1)
+        .read_to_end(&mut plaintext);
+    if read.is_err() || plaintext.len() > MAX_DECOMPRESSED_LEN {
+        plaintext.zeroize();
+        return None;
+    }
+    Some(plaintext)
+}
+
+fn open_with_key(data: &[u8], key: &[u8; KEY_LEN], aad: &[u8]) -> Option<Vec<u8>> {
+    let (&version, rest) = data.split_first()?;
+    if is_streamed(version) {
+        return open_stream(version, rest, key, aad);
+    }
+    if version & FORMAT_COMPRESSED == 0 {
+        return open_raw(data, key, aad);
+    }
+    let mut compressed = open_raw(data, key, &compressed_aad(version, aad))?;
+    let plaintext = zstd::bulk::decompress(&compressed, MAX_DECOMPRESSED_LEN).ok();
+    compressed.zeroize();
+    plaintext
+}
+
+fn open_raw(data: &[u8], key: &[u8; KEY_LEN], aad: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 1 + NONCE_LEN + 1 {
         return None;
     }
     let nonce = Nonce::from_slice(&data[1..1 + NONCE_LEN]);
     let ciphertext = &data[1 + NONCE_LEN..];
     let cipher = Aes256Gcm::new_from_slice(key).ok()?;
-    cipher.decrypt(nonce, ciphertext).ok()
+    cipher.decrypt(nonce, Payload { msg: ciphertext, aad }).ok()
+}
+
+/// V2 encrypt: version(1) || nonce(12) || ciphertext. Uses pre-derived key, no Argon2id.
+/// Plaintexts from `COMPRESSION_THRESHOLD` up are zstd-compressed first and flagged as such;
+/// from `STREAM_THRESHOLD` up they are also sealed in chunks (see `stream`).
+pub fn encrypt_with_key(plaintext: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<u8>, CryptoError> {
+    seal_with_key(FORMAT_V2, plaintext, key, &[])
+}
+
+/// V3 encrypt: same layout as V2, but `aad` (see `field_aad`) is authenticated with the
+/// ciphertext, so the blob cannot be moved to another record or field undetected.
+pub fn encrypt_with_key_aad(
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    seal_with_key(FORMAT_V3, plaintext, key, aad)
+}
+
+/// `encrypt_with_key_aad` for a payload from `STREAM_THRESHOLD` up, sealed only as it is
+/// written out.
+pub fn stream_with_key_aad(
+    plaintext: &[u8],
+    key: &[u8; KEY_LEN],
+    aad: &[u8],
+) -> Result<StreamedSeal, CryptoError> {
+    StreamedSeal::new(FORMAT_V3, plaintext, key, aad)
+}
+
+/// Attempts V2 decryption only. Returns Some(plaintext) if data is V2-format and the key matches.
+pub fn try_decrypt_with_key(data: &[u8], key: &[u8; KEY_LEN]) -> Option<Vec<u8>> {
+    match data.first().map(|&v| base_format(v)) {
+        Some(FORMAT_V2) => open_with_key(data, key, &[]),
+        _ => None,
+    }
+}
+
+/// Decrypts V3 data bound to `aad`. Unbound data is refused: only project content was ever
+/// written unbound, and it is opened through `content`.
+pub fn try_decrypt_with_key_aad(data: &[u8], key: &[u8; KEY_LEN], aad: &[u8]) -> Option<Vec<u8>> {
+    match data.first().map(|&v| base_format(v)) {
+        Some(FORMAT_V3) => open_with_key(data, key, aad),
+        _ => None,
+    }
+}
+
+pub fn is_aad_bound(data: &[u8]) -> bool {
+    data.first().is_some_and(|&v| base_format(v) == FORMAT_V3)
 }
 
 /// Decrypts both V1 (salt+nonce+ct, Argon2id) and V2 (version+nonce+ct, pre-derived key) formats.
@@ -108,6 +394,13 @@ pub fn decrypt_auto(
         return Err(CryptoError::InvalidFormat);
     }
 
+    if data[0] != FORMAT_V2 && base_format(data[0]) == FORMAT_V2 {
+        if let Some(plaintext) = cached_key.and_then(|key| open_with_key(data, key, &[])) {
+            return Ok(plaintext);
+        }
+        // A V1 salt may start with a flagged V2 marker by chance; fall through to the V1 paths.
+    }
+
     if data[0] == FORMAT_V2 {
         let key = cached_key.ok_or(CryptoError::DecryptionFailed(
             "V2 format requires cached key".to_string(),
@@ -149,8 +442,8 @@ pub fn decrypt_auto(
 pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>, CryptoError> {
     let mut salt = [0u8; SALT_LEN];
     let mut nonce_bytes = [0u8; NONCE_LEN];
-    rand::thread_rng().fill_bytes(&mut salt);
-    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    rng::fill(&mut salt);
+    rng::fill(&mut nonce_bytes);
 
     let mut key = derive_key(password.as_bytes(), &salt)?;
     let cipher = Aes256Gcm::new_from_slice(&key)
@@ -217,11 +510,14 @@ pub fn verify_password(token: &[u8], password: &str) -> bool {
     }
 }
 
+/// PINs now wrap the vault key instead (see `wrap`); new PIN hashes are only built in tests,
+/// to exercise `verify_pin` on hashes written by older versions.
+#[cfg(test)]
 pub fn create_pin_verification_token(pin: &str) -> Result<Vec<u8>, CryptoError> {
     let mut salt = [0u8; SALT_LEN];
     let mut nonce_bytes = [0u8; NONCE_LEN];
-    rand::thread_rng().fill_bytes(&mut salt);
-    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    rng::fill(&mut salt);
+    rng::fill(&mut nonce_bytes);
 
     let mut key = derive_key_with_params(
         pin.as_bytes(), &salt,
@@ -324,6 +620,49 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_v3_binds_record_and_field() {
+        let key = derive_master_key("test_password").unwrap();
+        let aad = field_aad("project-a", "content");
+        let encrypted = encrypt_with_key_aad(b"bound", &key, &aad).unwrap();
+        assert!(is_aad_bound(&encrypted));
+        assert_eq!(try_decrypt_with_key_aad(&encrypted, &key, &aad).unwrap(), b"bound");
+        assert!(try_decrypt_with_key_aad(&encrypted, &key, &field_aad("project-b", "content")).is_none());
+        assert!(try_decrypt_with_key_aad(&encrypted, &key, &field_aad("project-a", "name")).is_none());
+        assert!(try_decrypt_with_key(&encrypted, &key).is_none());
+    }
+
+    #[test]
+    fn test_v3_decrypt_refuses_unbound_v2() {
+        let key = derive_master_key("test_password").unwrap();
+        let aad = field_aad("project-a", "content");
+        let unbound = encrypt_with_key(b"unbound", &key).unwrap();
+        assert!(try_decrypt_with_key_aad(&unbound, &key, &aad).is_none());
+        assert_eq!(decrypt_auto(&unbound, Some(&key), None).unwrap(), b"unbound");
+    }
+
+    #[test]
+    fn test_kdf_params_change_master_key() {
+        let default_key = derive_master_key("test_password").unwrap();
+        assert_eq!(
+            derive_master_key_with_params("test_password", &KdfParams::default()).unwrap(),
+            default_key
+        );
+        let stronger = KdfParams { memory_kb: 32768, iterations: 2, parallelism: 2 };
+        assert!(stronger.validate().is_ok());
+        assert_ne!(derive_master_key_with_params("test_password", &stronger).unwrap(), default_key);
+        assert!(KdfParams { memory_kb: 1024, iterations: 1, parallelism: 1 }.validate().is_err());
+    }
+
+    #[test]
+    fn stored_kdf_params_are_clamped() {
+        let huge = KdfParams { memory_kb: u32::MAX, iterations: u32::MAX, parallelism: 0 };
+        let clamped = huge.clamped();
+        assert!(clamped.validate().is_ok());
+        assert_eq!(clamped.memory_kb, KDF_MAX_MEMORY_KB);
+        assert_eq!(KdfParams::default().clamped(), KdfParams::default());
+    }
+
     #[test]
     fn test_pin_verification() {
         let pin = "1234";
@@ -331,4 +670,38 @@ mod tests {
         assert!(verify_pin(&token, pin));
         assert!(!verify_pin(&token, "5678"));
     }
+
+    #[test]
+    fn test_large_payload_is_compressed() {
+        let key = derive_master_key("test_password").unwrap();
+        let plaintext = "line of a long note\n".repeat(1000);
+        let aad = field_aad("project-1", "content");
+
+        let encrypted = encrypt_with_key_aad(plaintext.as_bytes(), &key, &aad).unwrap();
+        assert_eq!(encrypted[0], FORMAT_V3 | FORMAT_COMPRESSED);
+        assert!(encrypted.len() < plaintext.len() / 4);
+        assert!(is_aad_bound(&encrypted));
+        let decrypted = try_decrypt_with_key_aad(&encrypted, &key, &aad).unwrap();
+        assert_eq!(decrypted, plaintext.as_bytes());
+
+        let mut stripped = encrypted.clone();
+        stripped[0] = FORMAT_V3;
+        assert!(try_decrypt_with_key_aad(&stripped, &key, &aad).is_none());
+
+        let legacy = encrypt_with_key(plaintext.as_bytes(), &key).unwrap();
+        assert_eq!(legacy[0], FORMAT_V2 | FORMAT_COMPRESSED);
+        assert_eq!(try_decrypt_with_key(&legacy, &key).unwrap(), plaintext.as_bytes());
+        assert_eq!(encrypt_with_key(b"short", &key).unwrap()[0], FORMAT_V2);
+
+        let large = "a much longer note\n".repeat(STREAM_THRESHOLD / 10);
+        let streamed = encrypt_with_key_aad(large.as_bytes(), &key, &aad).unwrap();
+        assert_eq!(
+            streamed[0],
+            FORMAT_V3 | FORMAT_COMPRESSED | FORMAT_STREAMED | stream::SALTED
+        );
+        assert!(is_aad_bound(&streamed));
+        let decrypted = try_decrypt_with_key_aad(&streamed, &key, &aad).unwrap();
+        assert_eq!(decrypted, large.as_bytes());
+        assert!(try_decrypt_with_key_aad(&streamed, &key, b"other").is_none());
+    }
 }
@@ -0,0 +1,123 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Counter-based nonces for the vault key. Random 96-bit nonces are safe for a few billion
+//! messages under one key; a vault that lives for years and rewrites projects on every keystroke
+//! gets closer to that bound than we like. While a vault is unlocked, nonces for its key are a
+//! 4-byte random per-vault prefix followed by a 64-bit counter persisted in `vault_meta`.
+//!
+//! The counter is reserved in blocks, each in a transaction on the vault's own connection, so
+//! the database is written once per `BLOCK_SIZE` encryptions; a crash only skips the unused rest
+//! of a block. A reservation never hands out values below the end of the block in use, so an
+//! enclosing transaction that rolls the counter back cannot make it repeat itself. Any other
+//! key, or a vault whose counter cannot be reserved (e.g. opened read-only), keeps using random
+//! nonces; so does a single encryption that finds the connection busy when its block runs out.
+
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use super::{rng, KEY_LEN, NONCE_LEN};
+
+pub const PREFIX_LEN: usize = 4;
+pub const BLOCK_SIZE: u64 = 4096;
+
+/// Reserves `count` counter values, none below `after`, and returns the vault's prefix and the
+/// first value.
+pub type Reserve = Box<dyn Fn(u64, u64) -> Result<([u8; PREFIX_LEN], u64), String> + Send>;
+
+struct CounterState {
+    key_id: [u8; 32],
+    prefix: [u8; PREFIX_LEN],
+    next: u64,
+    end: u64,
+    reserve: Reserve,
+}
+
+static COUNTER: Mutex<Option<CounterState>> = Mutex::new(None);
+
+/// Identifies a key without keeping a copy of it.
+fn key_id(key: &[u8; KEY_LEN]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"vaultpad/nonce-counter");
+    hasher.update(key);
+    hasher.finalize().into()
+}
+
+/// Use counter nonces for `key` from now on, replacing any previous vault's counter.
+pub fn install(key: &[u8; KEY_LEN], reserve: Reserve) -> Result<(), String> {
+    let (prefix, start) = reserve(0, BLOCK_SIZE)?;
+    let state = CounterState {
+        key_id: key_id(key),
+        prefix,
+        next: start,
+        end: start.checked_add(BLOCK_SIZE).ok_or("Nonce counter exhausted")?,
+        reserve,
+    };
+    *COUNTER.lock().map_err(|e| e.to_string())? = Some(state);
+    Ok(())
+}
+
+pub fn uninstall() {
+    if let Ok(mut counter) = COUNTER.lock() {
+        *counter = None;
+    }
+}
+
+fn counter_nonce(state: &mut CounterState) -> Option<[u8; NONCE_LEN]> {
+    if state.next >= state.end {
+        let (prefix, start) = (state.reserve)(state.end, BLOCK_SIZE).ok()?;
+        state.prefix = prefix;
+        state.next = start;
+        state.end = start.checked_add(BLOCK_SIZE)?;
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..PREFIX_LEN].copy_from_slice(&state.prefix);
+    nonce[PREFIX_LEN..].copy_from_slice(&state.next.to_be_bytes());
+    state.next += 1;
+    Some(nonce)
+}
+
+/// A fresh nonce for encrypting under `key`.
+pub(super) fn next(key: &[u8; KEY_LEN]) -> [u8; NONCE_LEN] {
+    if let Ok(mut counter) = COUNTER.lock() {
+        if let Some(state) = counter.as_mut().filter(|s| s.key_id == key_id(key)) {
+            if let Some(nonce) = counter_nonce(state) {
+                return nonce;
+            }
+        }
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    rng::fill(&mut nonce);
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn counter_nonces_continue_across_blocks() {
+        let reserved = Arc::new(AtomicU64::new(BLOCK_SIZE));
+        let tracker = reserved.clone();
+        let mut state = CounterState {
+            key_id: key_id(&[1u8; KEY_LEN]),
+            prefix: [0xAB; PREFIX_LEN],
+            next: BLOCK_SIZE - 1,
+            end: BLOCK_SIZE,
+            reserve: Box::new(move |after, count| {
+                let start = tracker.load(Ordering::SeqCst).max(after);
+                tracker.store(start + count, Ordering::SeqCst);
+                Ok(([0xAB; PREFIX_LEN], start))
+            }),
+        };
+        let first = counter_nonce(&mut state).unwrap();
+        let second = counter_nonce(&mut state).unwrap();
+        assert_eq!(&first[..PREFIX_LEN], &[0xAB; PREFIX_LEN]);
+        assert_eq!(u64::from_be_bytes(first[PREFIX_LEN..].try_into().unwrap()), BLOCK_SIZE - 1);
+        assert_eq!(u64::from_be_bytes(second[PREFIX_LEN..].try_into().unwrap()), BLOCK_SIZE);
+        assert_eq!(reserved.load(Ordering::SeqCst), 2 * BLOCK_SIZE);
+    }
+}
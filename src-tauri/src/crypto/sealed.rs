@@ -0,0 +1,86 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Public-key encryption to another person's X25519 key: an ephemeral key pair is generated per
+//! message, the shared secret is run through HKDF into an AES key, and the result is
+//! `ephemeral_public(32) || V3 ciphertext`. Only the holder of the recipient's secret can open it.
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroize;
+
+use super::rng::SecureRng;
+use super::{encrypt_with_key_aad, try_decrypt_with_key_aad, CryptoError, KEY_LEN};
+
+pub const SECRET_KEY_LEN: usize = 32;
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+const SEALED_INFO: &[u8] = b"vaultpad/sealed-box";
+
+pub fn generate_secret_key() -> [u8; SECRET_KEY_LEN] {
+    StaticSecret::random_from_rng(SecureRng).to_bytes()
+}
+
+pub fn public_key(secret: &[u8; SECRET_KEY_LEN]) -> [u8; PUBLIC_KEY_LEN] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+fn derive_key(shared: &[u8; 32], ephemeral: &[u8], recipient: &[u8]) -> [u8; KEY_LEN] {
+    let mut salt = Vec::with_capacity(ephemeral.len() + recipient.len());
+    salt.extend_from_slice(ephemeral);
+    salt.extend_from_slice(recipient);
+    let mut key = [0u8; KEY_LEN];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(SEALED_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF output length");
+    key
+}
+
+pub fn seal(plaintext: &[u8], recipient: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let recipient: [u8; PUBLIC_KEY_LEN] =
+        recipient.try_into().map_err(|_| CryptoError::InvalidFormat)?;
+    let ephemeral = StaticSecret::random_from_rng(SecureRng);
+    let ephemeral_public = PublicKey::from(&ephemeral).to_bytes();
+    let shared = ephemeral.diffie_hellman(&PublicKey::from(recipient));
+    if !shared.was_contributory() {
+        return Err(CryptoError::InvalidFormat);
+    }
+
+    let mut key = derive_key(shared.as_bytes(), &ephemeral_public, &recipient);
+    let sealed = encrypt_with_key_aad(plaintext, &key, &ephemeral_public);
+    key.zeroize();
+
+    let mut result = ephemeral_public.to_vec();
+    result.extend_from_slice(&sealed?);
+    Ok(result)
+}
+
+pub fn open(data: &[u8], secret: &[u8; SECRET_KEY_LEN]) -> Result<Vec<u8>, CryptoError> {
+    if data.len() <= PUBLIC_KEY_LEN {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let (ephemeral, sealed) = data.split_at(PUBLIC_KEY_LEN);
+    let ephemeral: [u8; PUBLIC_KEY_LEN] = ephemeral.try_into().expect("split at key length");
+    let secret = StaticSecret::from(*secret);
+    let recipient = PublicKey::from(&secret).to_bytes();
+    let shared = secret.diffie_hellman(&PublicKey::from(ephemeral));
+
+    let mut key = derive_key(shared.as_bytes(), &ephemeral, &recipient);
+    let plain = try_decrypt_with_key_aad(sealed, &key, &ephemeral);
+    key.zeroize();
+    plain.ok_or_else(|| CryptoError::DecryptionFailed("Sealed message cannot be opened".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_recipient_can_open() {
+        let secret = generate_secret_key();
+        let sealed = seal(b"vault key", &public_key(&secret)).unwrap();
+        assert_eq!(open(&sealed, &secret).unwrap(), b"vault key");
+        assert!(open(&sealed, &generate_secret_key()).is_err());
+    }
+}
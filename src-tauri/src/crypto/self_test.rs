@@ -0,0 +1,183 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Known-answer tests for the primitives the vault depends on, runnable before unlock. A build
+//! whose AES-GCM, Argon2id or SHA-256 does not reproduce the published vectors, or whose RNG
+//! returns obviously non-random output, must not be trusted with a vault.
+
+use std::time::Instant;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use argon2::{Algorithm, Argon2, AssociatedData, ParamsBuilder, Version};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::rng::SecureRng;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+    pub duration_ms: u64,
+}
+
+fn expect_hex(actual: &[u8], expected: &str) -> Result<(), String> {
+    let actual = hex::encode(actual);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("expected {expected}, got {actual}"))
+    }
+}
+
+/// GCM specification test cases 13 and 14 (256-bit zero key, zero IV).
+fn aes_gcm_known_answer() -> Result<(), String> {
+    let cipher = Aes256Gcm::new_from_slice(&[0u8; 32]).map_err(|e| e.to_string())?;
+    let nonce = Nonce::from_slice(&[0u8; 12]);
+
+    let empty = cipher
+        .encrypt(nonce, &[][..])
+        .map_err(|e| e.to_string())?;
+    expect_hex(&empty, "530f8afbc74536b9a963b4f1c4cb738b")?;
+
+    let block = cipher
+        .encrypt(nonce, &[0u8; 16][..])
+        .map_err(|e| e.to_string())?;
+    expect_hex(
+        &block,
+        "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919",
+    )?;
+
+    let mut forged = block.clone();
+    forged[0] ^= 1;
+    if cipher.decrypt(nonce, &forged[..]).is_ok() {
+        return Err("tampered ciphertext was accepted".to_string());
+    }
+    Ok(())
+}
+
+/// The application's own envelope: encrypt, decrypt, and reject the wrong associated data.
+fn aes_gcm_round_trip() -> Result<(), String> {
+    let key = [0x5Au8; 32];
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+    let nonce = Nonce::from_slice(&[7u8; 12]);
+    let sealed = cipher
+        .encrypt(nonce, Payload { msg: b"self-test", aad: b"field" })
+        .map_err(|e| e.to_string())?;
+    let opened = cipher
+        .decrypt(nonce, Payload { msg: &sealed, aad: b"field" })
+        .map_err(|e| e.to_string())?;
+    if opened != b"self-test" {
+        return Err("round trip changed the plaintext".to_string());
+    }
+    if cipher
+        .decrypt(nonce, Payload { msg: &sealed, aad: b"other" })
+        .is_ok()
+    {
+        return Err("wrong associated data was accepted".to_string());
+    }
+    Ok(())
+}
+
+/// RFC 9106, section 5.3 (Argon2id).
+fn argon2id_known_answer() -> Result<(), String> {
+    let params = ParamsBuilder::new()
+        .m_cost(32)
+        .t_cost(3)
+        .p_cost(4)
+        .output_len(32)
+        .data(AssociatedData::new(&[0x04; 12]).map_err(|e| e.to_string())?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let argon2 = Argon2::new_with_secret(&[0x03; 8], Algorithm::Argon2id, Version::V0x13, params)
+        .map_err(|e| e.to_string())?;
+    let mut tag = [0u8; 32];
+    argon2
+        .hash_password_into(&[0x01; 32], &[0x02; 16], &mut tag)
+        .map_err(|e| e.to_string())?;
+    expect_hex(
+        &tag,
+        "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+    )
+}
+
+/// FIPS 180-2 example "abc".
+fn sha256_known_answer() -> Result<(), String> {
+    expect_hex(
+        &Sha256::digest(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    )
+}
+
+/// Catches a stuck or constant generator, not subtle bias: successive outputs must differ and
+/// the share of one bits must be close to half.
+fn rng_sanity() -> Result<(), String> {
+    let mut rng = SecureRng;
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    rng.fill_bytes(&mut first);
+    rng.fill_bytes(&mut second);
+    if first == second || first == [0u8; 32] {
+        return Err("generator repeated its output".to_string());
+    }
+
+    let mut sample = [0u8; 1024];
+    rng.fill_bytes(&mut sample);
+    let ones: u32 = sample.iter().map(|b| b.count_ones()).sum();
+    // 8192 bits: mean 4096, standard deviation about 45; this allows roughly 6.5 sigma.
+    if !(3800..=4392).contains(&ones) {
+        return Err(format!("{ones} of 8192 bits set"));
+    }
+    Ok(())
+}
+
+type Check = fn() -> Result<(), String>;
+
+pub fn run() -> SelfTestReport {
+    let started = Instant::now();
+    let tests: [(&str, Check); 5] = [
+        ("aes_gcm_known_answer", aes_gcm_known_answer),
+        ("aes_gcm_round_trip", aes_gcm_round_trip),
+        ("argon2id_known_answer", argon2id_known_answer),
+        ("sha256_known_answer", sha256_known_answer),
+        ("rng_sanity", rng_sanity),
+    ];
+    let checks: Vec<SelfTestCheck> = tests
+        .iter()
+        .map(|(name, test)| {
+            let result = test();
+            SelfTestCheck {
+                name: name.to_string(),
+                passed: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect();
+    SelfTestReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_checks_pass() {
+        let report = run();
+        assert!(report.passed, "{:?}", report.checks);
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use super::{rng, CryptoError};
+
+pub const SECRET_KEY_LEN: usize = ed25519_dalek::SECRET_KEY_LENGTH;
+pub const PUBLIC_KEY_LEN: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
+pub const SIGNATURE_LEN: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+pub fn generate_secret_key() -> [u8; SECRET_KEY_LEN] {
+    let mut secret = [0u8; SECRET_KEY_LEN];
+    rng::fill(&mut secret);
+    secret
+}
+
+pub fn public_key(secret: &[u8; SECRET_KEY_LEN]) -> [u8; PUBLIC_KEY_LEN] {
+    SigningKey::from_bytes(secret).verifying_key().to_bytes()
+}
+
+pub fn sign(secret: &[u8; SECRET_KEY_LEN], message: &[u8]) -> [u8; SIGNATURE_LEN] {
+    SigningKey::from_bytes(secret).sign(message).to_bytes()
+}
+
+pub fn verify(public: &[u8], message: &[u8], signature: &[u8]) -> Result<(), CryptoError> {
+    let public: &[u8; PUBLIC_KEY_LEN] = public.try_into().map_err(|_| CryptoError::InvalidFormat)?;
+    let signature: &[u8; SIGNATURE_LEN] =
+        signature.try_into().map_err(|_| CryptoError::InvalidFormat)?;
+    let key = VerifyingKey::from_bytes(public)
+        .map_err(|e| CryptoError::SignatureInvalid(e.to_string()))?;
+    key.verify(message, &Signature::from_bytes(signature))
+        .map_err(|e| CryptoError::SignatureInvalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_and_verify_roundtrip() {
+        let secret = generate_secret_key();
+        let public = public_key(&secret);
+        let sig = sign(&secret, b"vault state");
+        assert!(verify(&public, b"vault state", &sig).is_ok());
+        assert!(verify(&public, b"tampered state", &sig).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Shamir secret sharing over GF(2^8). A secret is split into `n` shares so that any `k` of
+//! them rebuild it and fewer reveal nothing about it. Each byte of the secret is the constant
+//! term of its own random polynomial of degree `k - 1`; share `x` holds the polynomials'
+//! values at `x`.
+
+use zeroize::Zeroize;
+
+use super::{rng, CryptoError};
+
+#[derive(Debug, Clone)]
+pub struct Share {
+    /// Evaluation point, 1..=255.
+    pub index: u8,
+    pub data: Vec<u8>,
+}
+
+/// Multiplication in GF(2^8) with the AES polynomial, without secret-dependent branches.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse as `a^254`; zero maps to zero.
+fn inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of which rebuild it.
+pub fn split(secret: &[u8], threshold: u8, shares: u8) -> Result<Vec<Share>, CryptoError> {
+    if threshold < 2 || shares < threshold || secret.is_empty() {
+        return Err(CryptoError::InvalidFormat);
+    }
+
+    let mut out: Vec<Share> = (1..=shares)
+        .map(|index| Share {
+            index,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng::fill(&mut coefficients[1..]);
+        for share in &mut out {
+            // Horner's rule, highest coefficient first.
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(0u8, |acc, &c| mul(acc, share.index) ^ c);
+            share.data.push(value);
+        }
+    }
+    coefficients.zeroize();
+    Ok(out)
+}
+
+/// Rebuild the secret from at least `threshold` distinct shares by Lagrange interpolation at
+/// zero. With too few shares the result is garbage rather than an error; callers detect that
+/// when the rebuilt secret fails to unwrap anything.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, CryptoError> {
+    let len = shares.first().map(|s| s.data.len()).ok_or(CryptoError::InvalidFormat)?;
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0
+            || share.data.len() != len
+            || shares[..i].iter().any(|other| other.index == share.index)
+        {
+            return Err(CryptoError::InvalidFormat);
+        }
+    }
+
+    let weights: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1u8, |acc, other| {
+                    // x_j / (x_j - x_i); subtraction is XOR in GF(2^8).
+                    mul(acc, mul(other.index, inv(other.index ^ share.index)))
+                })
+        })
+        .collect();
+
+    Ok((0..len)
+        .map(|byte| {
+            shares
+                .iter()
+                .zip(&weights)
+                .fold(0u8, |acc, (share, &w)| acc ^ mul(share.data[byte], w))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_subset_rebuilds_the_secret() {
+        assert_eq!(mul(0x57, 0x83), 0xc1);
+        assert_eq!(mul(0x53, inv(0x53)), 1);
+
+        let secret = b"correct horse battery staple 123";
+        let shares = split(secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        for picked in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<Share> = picked.iter().map(|&i| shares[i].clone()).collect();
+            assert_eq!(combine(&subset).unwrap(), secret);
+        }
+        assert_ne!(combine(&shares[..2]).unwrap(), secret);
+        assert!(combine(&[shares[0].clone(), shares[0].clone()]).is_err());
+        assert!(split(secret, 1, 3).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Chunked AES-GCM for content too large to seal in one piece, following the STREAM
+//! construction (Hoang, Reyhanitabar, Rogaway, Vizár 2015). The plaintext is cut into
+//! `CHUNK_LEN` pieces, each sealed on its own with the nonce `prefix || index || last`, so
+//! chunks cannot be reordered, dropped or cut off at a chunk boundary without the decryption
+//! failing, and only one chunk needs to be in memory at a time.
+//!
+//! Layout: version(1) || salt(12) || chunk_0 || ... || chunk_n, where every chunk but the last
+//! holds exactly `CHUNK_LEN` bytes of plaintext plus the 16-byte tag. The chunks are sealed
+//! under a key derived from the given one and the salt, with an all-zero prefix. The salt is a
+//! nonce taken like any other under the given key, so with the vault's nonce counter (see
+//! `nonce`) no two streams share a chunk key, and no stream needs nonces of its own.
+//!
+//! Streams written before the salt was introduced, whose version byte lacks `SALTED`, hold a
+//! random 7-byte prefix instead and are sealed under the given key directly; they are still
+//! read.
+
+use std::io::{self, Read, Write};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{nonce, KEY_LEN, NONCE_LEN};
+
+pub const CHUNK_LEN: usize = 64 * 1024;
+pub const PREFIX_LEN: usize = 7;
+/// Set on the version byte of streams with a salt, which all streams written now have.
+pub const SALTED: u8 = 0x40;
+const HEADER_LEN: usize = 1 + NONCE_LEN;
+const TAG_LEN: usize = 16;
+const SEALED_CHUNK_LEN: usize = CHUNK_LEN + TAG_LEN;
+const KEY_INFO: &[u8] = b"vaultpad/stream-chunks";
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Length of the stream `StreamWriter` makes of `plaintext_len` bytes.
+pub fn sealed_len(plaintext_len: u64) -> u64 {
+    let chunks = plaintext_len.div_ceil(CHUNK_LEN as u64).max(1);
+    HEADER_LEN as u64 + plaintext_len + chunks * TAG_LEN as u64
+}
+
+/// The cipher sealing the chunks of a stream with `salt` under `key`.
+fn chunk_cipher(key: &[u8; KEY_LEN], salt: &[u8; NONCE_LEN]) -> io::Result<Aes256Gcm> {
+    let mut chunk_key = Zeroizing::new([0u8; KEY_LEN]);
+    Hkdf::<Sha256>::new(Some(salt), key)
+        .expand(KEY_INFO, &mut *chunk_key)
+        .map_err(|_| invalid("invalid key"))?;
+    Aes256Gcm::new_from_slice(&*chunk_key).map_err(|_| invalid("invalid key"))
+}
+
+fn chunk_nonce(prefix: &[u8; PREFIX_LEN], index: u32, last: bool) -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..PREFIX_LEN].copy_from_slice(prefix);
+    nonce[PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&index.to_be_bytes());
+    nonce[NONCE_LEN - 1] = last as u8;
+    nonce
+}
+
+/// Encrypts everything written to it into `inner`. `finish` must be called to seal the last
+/// chunk; a stream that is dropped unfinished cannot be decrypted.
+pub struct StreamWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    aad: Vec<u8>,
+    prefix: [u8; PREFIX_LEN],
+    index: u32,
+    buffer: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Writes the header, with `SALTED` added to `version`; `aad` is authenticated with every
+    /// chunk.
+    pub fn new(mut inner: W, key: &[u8; KEY_LEN], version: u8, aad: &[u8]) -> io::Result<Self> {
+        let salt = nonce::next(key);
+        let cipher = chunk_cipher(key, &salt)?;
+        inner.write_all(&[version | SALTED])?;
+        inner.write_all(&salt)?;
+        Ok(Self {
+            inner,
+            cipher,
+            aad: aad.to_vec(),
+            prefix: [0u8; PREFIX_LEN],
+            index: 0,
+            buffer: Zeroizing::new(Vec::with_capacity(CHUNK_LEN)),
+        })
+    }
+
+    fn seal_buffer(&mut self, last: bool) -> io::Result<()> {
+        let nonce = chunk_nonce(&self.prefix, self.index, last);
+        let sealed = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.buffer,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| invalid("chunk encryption failed"))?;
+        self.buffer.zeroize();
+        self.buffer.clear();
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| invalid("stream too long"))?;
+        self.inner.write_all(&sealed)
+    }
+
+    /// Seal the last chunk and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.seal_buffer(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for StreamWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // A full chunk is only sealed once more data arrives: until then it may be the last one.
+        if self.buffer.len() == CHUNK_LEN {
+            self.seal_buffer(false)?;
+        }
+        let take = data.len().min(CHUNK_LEN - self.buffer.len());
+        self.buffer.extend_from_slice(&data[..take]);
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts a stream written by `StreamWriter`, one chunk at a time. Plaintext is only handed
+/// out after its chunk has been authenticated; an error means the rest must be discarded.
+pub struct StreamReader<R: Read> {
+    inner: R,
+    cipher: Aes256Gcm,
+    aad: Vec<u8>,
+    prefix: [u8; PREFIX_LEN],
+    index: u32,
+    sealed: Vec<u8>,
+    plain: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> StreamReader<R> {
+    /// Reads the header and returns its version byte along with the reader.
+    pub fn new(mut inner: R, key: &[u8; KEY_LEN], aad: &[u8]) -> io::Result<(u8, Self)> {
+        let mut version = [0u8; 1];
+        inner.read_exact(&mut version)?;
+        let mut prefix = [0u8; PREFIX_LEN];
+        let cipher = if version[0] & SALTED != 0 {
+            let mut salt = [0u8; NONCE_LEN];
+            inner.read_exact(&mut salt)?;
+            chunk_cipher(key, &salt)?
+        } else {
+            inner.read_exact(&mut prefix)?;
+            Aes256Gcm::new_from_slice(key).map_err(|_| invalid("invalid key"))?
+        };
+        let reader = Self {
+            inner,
+            cipher,
+            aad: aad.to_vec(),
+            prefix,
+            index: 0,
+            sealed: Vec::with_capacity(SEALED_CHUNK_LEN + 1),
+            plain: Vec::new(),
+            pos: 0,
+            done: false,
+        };
+        Ok((version[0], reader))
+    }
+
+    /// Read one byte past a full chunk: only a chunk followed by end of input is the last one.
+    fn fill_sealed(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 8192];
+        while self.sealed.len() <= SEALED_CHUNK_LEN {
+            let want = (SEALED_CHUNK_LEN + 1 - self.sealed.len()).min(buf.len());
+            match self.inner.read(&mut buf[..want]) {
+                Ok(0) => break,
+                Ok(n) => self.sealed.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn open_next(&mut self) -> io::Result<()> {
+        self.fill_sealed()?;
+        let last = self.sealed.len() <= SEALED_CHUNK_LEN;
+        let len = self.sealed.len().min(SEALED_CHUNK_LEN);
+        if len < TAG_LEN {
+            return Err(invalid("stream truncated"));
+        }
+        let nonce = chunk_nonce(&self.prefix, self.index, last);
+        let plain = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.sealed[..len],
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| invalid("chunk authentication failed"))?;
+        self.sealed.drain(..len);
+        self.plain.zeroize();
+        self.plain = plain;
+        self.pos = 0;
+        self.done = last;
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| invalid("stream too long"))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for StreamReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.open_next()?;
+        }
+        let n = out.len().min(self.plain.len() - self.pos);
+        out[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<R: Read> Drop for StreamReader<R> {
+    fn drop(&mut self) {
+        self.plain.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seal(plaintext: &[u8], key: &[u8; KEY_LEN], aad: &[u8]) -> Vec<u8> {
+        let mut writer = StreamWriter::new(Vec::new(), key, 0x02, aad).unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn open(data: &[u8], key: &[u8; KEY_LEN], aad: &[u8]) -> io::Result<Vec<u8>> {
+        let (version, mut reader) = StreamReader::new(data, key, aad)?;
+        assert_eq!(version, 0x02 | SALTED);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn chunks_cannot_be_dropped_or_reordered() {
+        let key = [9u8; KEY_LEN];
+        let plaintext: Vec<u8> = (0..3 * CHUNK_LEN).map(|i| (i % 251) as u8).collect();
+        let sealed = seal(&plaintext, &key, b"aad");
+        assert_eq!(sealed.len() as u64, sealed_len(plaintext.len() as u64));
+        assert_eq!(sealed.len(), HEADER_LEN + 3 * SEALED_CHUNK_LEN);
+        assert_eq!(open(&sealed, &key, b"aad").unwrap(), plaintext);
+        assert!(open(&sealed, &key, b"other").is_err());
+
+        // Cut off after the second chunk: that chunk was not sealed as the last one.
+        let truncated = &sealed[..HEADER_LEN + 2 * SEALED_CHUNK_LEN];
+        assert!(open(truncated, &key, b"aad").is_err());
+
+        let mut swapped = sealed.clone();
+        let (first, second) = (HEADER_LEN, HEADER_LEN + SEALED_CHUNK_LEN);
+        let chunk: Vec<u8> = swapped[first..second].to_vec();
+        swapped.copy_within(second..second + SEALED_CHUNK_LEN, first);
+        swapped[second..second + SEALED_CHUNK_LEN].copy_from_slice(&chunk);
+        assert!(open(&swapped, &key, b"aad").is_err());
+
+        let empty = seal(b"", &key, b"aad");
+        assert_eq!(empty.len() as u64, sealed_len(0));
+        assert_eq!(open(&empty, &key, b"aad").unwrap(), b"");
+    }
+
+    #[test]
+    fn streams_under_one_key_get_their_own_chunk_keys() {
+        let key = [7u8; KEY_LEN];
+        let a = seal(b"same", &key, b"aad");
+        let b = seal(b"same", &key, b"aad");
+        assert_ne!(a[1..HEADER_LEN], b[1..HEADER_LEN]);
+        assert_ne!(a[HEADER_LEN..], b[HEADER_LEN..]);
+    }
+
+    #[test]
+    fn unsalted_streams_are_still_read() {
+        let key = [8u8; KEY_LEN];
+        let cipher = Aes256Gcm::new_from_slice(&key).unwrap();
+        let prefix = [3u8; PREFIX_LEN];
+        let nonce = chunk_nonce(&prefix, 0, true);
+        let chunk = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: b"legacy", aad: b"aad" })
+            .unwrap();
+        let mut legacy = vec![0x02];
+        legacy.extend_from_slice(&prefix);
+        legacy.extend_from_slice(&chunk);
+        let (version, mut reader) = StreamReader::new(&legacy[..], &key, b"aad").unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!((version, out.as_slice()), (0x02, &b"legacy"[..]));
+    }
+}
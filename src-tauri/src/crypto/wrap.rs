@@ -0,0 +1,115 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Key wrapping. Vault content is encrypted with a data-encryption key (DEK); the DEK is only
+//! ever stored encrypted ("wrapped") under a key-encryption key (KEK) derived from a secret.
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use super::{
+    derive_key_with_params, encrypt_with_key, rng, try_decrypt_with_key, CryptoError, KdfParams,
+    KEY_LEN, SALT_LEN,
+};
+
+const HMAC_SECRET_INFO: &[u8] = b"vaultpad/hmac-secret-kek";
+
+/// A DEK wrapped under a KEK derived from a secret with Argon2id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySlot {
+    pub kdf: KdfParams,
+    pub salt: String,
+    pub wrapped_key: String,
+}
+
+pub fn generate_key() -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    rng::fill(&mut key);
+    key
+}
+
+pub fn wrap_key(dek: &[u8; KEY_LEN], kek: &[u8; KEY_LEN]) -> Result<Vec<u8>, CryptoError> {
+    encrypt_with_key(dek, kek)
+}
+
+pub fn unwrap_key(wrapped: &[u8], kek: &[u8; KEY_LEN]) -> Result<[u8; KEY_LEN], CryptoError> {
+    let mut plain = try_decrypt_with_key(wrapped, kek)
+        .ok_or_else(|| CryptoError::DecryptionFailed("Key unwrap failed".to_string()))?;
+    let key: Result<[u8; KEY_LEN], _> = plain.as_slice().try_into();
+    plain.zeroize();
+    key.map_err(|_| CryptoError::InvalidFormat)
+}
+
+/// Wrap `dek` under a KEK derived from `secret` with a fresh random salt.
+pub fn wrap_with_secret(
+    dek: &[u8; KEY_LEN],
+    secret: &str,
+    kdf: &KdfParams,
+) -> Result<KeySlot, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    rng::fill(&mut salt);
+
+    let mut kek = derive_key_with_params(
+        secret.as_bytes(),
+        &salt,
+        kdf.memory_kb,
+        kdf.iterations,
+        kdf.parallelism,
+    )?;
+    let wrapped = wrap_key(dek, &kek);
+    kek.zeroize();
+
+    Ok(KeySlot {
+        kdf: *kdf,
+        salt: B64.encode(salt),
+        wrapped_key: B64.encode(wrapped?),
+    })
+}
+
+pub fn unwrap_with_secret(slot: &KeySlot, secret: &str) -> Result<[u8; KEY_LEN], CryptoError> {
+    let salt = B64.decode(&slot.salt).map_err(|_| CryptoError::InvalidFormat)?;
+    let wrapped = B64
+        .decode(&slot.wrapped_key)
+        .map_err(|_| CryptoError::InvalidFormat)?;
+
+    let kdf = slot.kdf.clamped();
+    let mut kek = derive_key_with_params(
+        secret.as_bytes(),
+        &salt,
+        kdf.memory_kb,
+        kdf.iterations,
+        kdf.parallelism,
+    )?;
+    let dek = unwrap_key(&wrapped, &kek);
+    kek.zeroize();
+    dek
+}
+
+/// KEK from the output of a FIDO2 authenticator's `hmac-secret` extension (WebAuthn PRF).
+/// The output is already uniformly random, so HKDF only separates it from other uses.
+pub fn kek_from_hmac_secret(output: &[u8]) -> Result<[u8; KEY_LEN], CryptoError> {
+    if output.len() < KEY_LEN {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let mut kek = [0u8; KEY_LEN];
+    Hkdf::<Sha256>::new(None, output)
+        .expand(HMAC_SECRET_INFO, &mut kek)
+        .map_err(|_| CryptoError::InvalidFormat)?;
+    Ok(kek)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_and_unwrap_with_secret() {
+        let dek = generate_key();
+        let slot = wrap_with_secret(&dek, "correct horse", &KdfParams::default()).unwrap();
+        assert_eq!(unwrap_with_secret(&slot, "correct horse").unwrap(), dek);
+        assert!(unwrap_with_secret(&slot, "wrong horse").is_err());
+    }
+}
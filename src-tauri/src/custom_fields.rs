@@ -0,0 +1,253 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Labelled key/value fields on a project, such as a URL, a username or a license key, kept
+//! next to its free-form content. Each field is stored as one record, its label, value and
+//! secret flag encrypted together with the project key, so one can be changed without
+//! re-sending the content and the database does not tell which fields are secret.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::ProjectField;
+use crate::ordering;
+use crate::storage::StorageProvider;
+
+const MAX_LABEL_LEN: usize = 128;
+const MAX_VALUE_LEN: usize = 64 * 1024;
+const MAX_FIELDS_PER_PROJECT: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomField {
+    pub id: String,
+    pub label: String,
+    pub value: String,
+    /// Shown masked until revealed, like a password.
+    pub is_secret: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What is encrypted for a field.
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    label: String,
+    value: String,
+    is_secret: bool,
+}
+
+fn seal(project_key: ProjectKey<'_>, id: &str, sealed: &Sealed) -> Result<Vec<u8>, String> {
+    let plain = Zeroizing::new(serde_json::to_vec(sealed).map_err(|e| e.to_string())?);
+    match project_key {
+        ProjectKey::Vault(key) => {
+            crypto::encrypt_with_key_aad(&plain, key, &crypto::field_aad(id, "custom-field"))
+        }
+        ProjectKey::Password(password) => crypto::encrypt(&plain, password),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn open(project_key: ProjectKey<'_>, field: &ProjectField) -> Result<Sealed, String> {
+    let plain = Zeroizing::new(match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &field.encrypted_field,
+            key,
+            &crypto::field_aad(&field.id, "custom-field"),
+        )
+        .ok_or("Wrong key for this field")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&field.encrypted_field, password).map_err(|e| e.to_string())?
+        }
+    });
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+fn info(field: &ProjectField, sealed: Sealed) -> CustomField {
+    CustomField {
+        id: field.id.clone(),
+        label: sealed.label,
+        value: sealed.value,
+        is_secret: sealed.is_secret,
+        created_at: field.created_at.clone(),
+        updated_at: field.updated_at.clone(),
+    }
+}
+
+fn validate(label: &str, value: &str) -> Result<String, String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("A field needs a label".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(format!("Field labels must be at most {MAX_LABEL_LEN} characters"));
+    }
+    if value.len() > MAX_VALUE_LEN {
+        return Err(format!("Field values must be at most {} KiB", MAX_VALUE_LEN / 1024));
+    }
+    Ok(label.to_string())
+}
+
+/// A project's fields, in the order they were added.
+pub fn list(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<Vec<CustomField>, String> {
+    storage
+        .list_project_fields(project_id)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|field| Ok(info(field, open(project_key, field)?)))
+        .collect()
+}
+
+/// Add a field after the project's other fields.
+pub fn add(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+    label: &str,
+    value: &str,
+    is_secret: bool,
+) -> Result<CustomField, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let label = validate(label, value)?;
+    let existing = storage.list_project_fields(project_id).map_err(|e| e.to_string())?;
+    if existing.len() >= MAX_FIELDS_PER_PROJECT {
+        return Err(format!("A project can have at most {MAX_FIELDS_PER_PROJECT} fields"));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = clock.now().to_rfc3339();
+    let sealed = Sealed { label, value: value.to_string(), is_secret };
+    let field = ProjectField {
+        encrypted_field: seal(project_key, &id, &sealed)?,
+        id,
+        project_id: project_id.to_string(),
+        position: ordering::between(existing.last().map(|f| f.position.as_str()), None),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    storage.save_project_field(&field).map_err(|e| e.to_string())?;
+    Ok(info(&field, sealed))
+}
+
+/// Replace a field's label, value and secret flag. The field must open with `project_key`.
+pub fn update(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    id: &str,
+    label: &str,
+    value: &str,
+    is_secret: bool,
+) -> Result<CustomField, String> {
+    let mut field = storage.get_project_field(id).map_err(|e| e.to_string())?;
+    open(project_key, &field)?;
+    let sealed = Sealed { label: validate(label, value)?, value: value.to_string(), is_secret };
+    field.encrypted_field = seal(project_key, id, &sealed)?;
+    field.updated_at = clock.now().to_rfc3339();
+    storage.save_project_field(&field).map_err(|e| e.to_string())?;
+    Ok(info(&field, sealed))
+}
+
+pub fn remove(storage: &dyn StorageProvider, id: &str) -> Result<(), String> {
+    storage.delete_project_field(id).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt a project's fields with its new key after the project password was set, changed
+/// or removed.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    project_id: &str,
+    old_key: ProjectKey<'_>,
+    new_key: ProjectKey<'_>,
+) -> Result<(), String> {
+    if old_key.same_as(&new_key) {
+        return Ok(());
+    }
+    for mut field in storage.list_project_fields(project_id).map_err(|e| e.to_string())? {
+        let sealed = open(old_key, &field)?;
+        field.encrypted_field = seal(new_key, &field.id, &sealed)?;
+        storage.save_project_field(&field).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Re-encrypt every field held under the vault key with `new_key`, for a master password
+/// change. Fields of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        for mut field in storage.list_project_fields(&project.id).map_err(|e| e.to_string())? {
+            let Ok(sealed) = open(ProjectKey::Vault(old_key), &field) else {
+                continue;
+            };
+            field.encrypted_field = seal(ProjectKey::Vault(new_key), &field.id, &sealed)?;
+            storage.save_project_field(&field).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn fields_follow_the_project_key() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage
+            .create_project(&Project {
+                id: "p".to_string(),
+                name: "p".to_string(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        let vault = ProjectKey::Vault(&key);
+
+        let url = add(&storage, &SystemClock, vault, "p", " URL ", "https://example.com", false)
+            .unwrap();
+        assert_eq!(url.label, "URL");
+        let licence = add(&storage, &SystemClock, vault, "p", "License", "ABCD-1234", true).unwrap();
+        assert!(add(&storage, &SystemClock, vault, "p", " ", "x", false).is_err());
+        let stored = storage.get_project_field(&licence.id).unwrap();
+        assert!(!stored.encrypted_field.windows(9).any(|w| w == b"ABCD-1234"));
+
+        update(&storage, &SystemClock, vault, &url.id, "Login URL", "https://example.org", false)
+            .unwrap();
+        let password = ProjectKey::Password("hunter2");
+        rewrap(&storage, "p", vault, password).unwrap();
+        assert!(list(&storage, vault, "p").is_err());
+        let listed = list(&storage, password, "p").unwrap();
+        let labels: Vec<&str> = listed.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["Login URL", "License"]);
+        assert!(listed[1].is_secret);
+
+        remove(&storage, &url.id).unwrap();
+        storage.delete_project("p").unwrap();
+        assert!(storage.get_project_field(&licence.id).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Sample content for the demo vault (see `init_demo_vault`): a few notes and credentials in
+//! folders, with tags, a checklist and a TOTP secret, so every part of the app has something to
+//! show. Everything is made up; the passwords are not real ones.
+
+use crate::attachments::ProjectKey;
+use crate::checklist;
+use crate::clock::Clock;
+use crate::commands::projects::master_key_project;
+use crate::credential;
+use crate::crypto;
+use crate::folders;
+use crate::models::Credential;
+use crate::storage::StorageProvider;
+use crate::tags;
+use crate::totp::{self, TotpOptions};
+
+/// The RFC 6238 test secret, so the codes shown are obviously not for a real account.
+const DEMO_TOTP_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+fn credential(username: &str, password: &str, url: &str, notes: &str) -> Credential {
+    let mut credential = Credential::default();
+    credential.username = username.to_string();
+    credential.password = password.to_string();
+    credential.url = url.to_string();
+    credential.notes = notes.to_string();
+    credential
+}
+
+/// Store a master-key project in `folder_id` with `tag_names`; returns its id.
+fn add_project(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    name: &str,
+    content: &str,
+    folder_id: Option<&str>,
+    tag_names: &[&str],
+) -> Result<String, String> {
+    let mut project = master_key_project(clock, key, true, name, content)?;
+    project.folder_id = folder_id.map(str::to_string);
+    storage.create_project(&project).map_err(|e| e.to_string())?;
+    let names: Vec<String> = tag_names.iter().map(|t| t.to_string()).collect();
+    tags::set_for_project(storage, clock, key, &project.id, &names)?;
+    Ok(project.id)
+}
+
+/// Fill an empty vault with the sample projects.
+pub fn seed(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    let personal = folders::create(storage, clock, key, "Personal", None)?;
+    let work = folders::create(storage, clock, key, "Work", None)?;
+
+    let wifi = credential(
+        "Home-Network-5G",
+        "correct-horse-battery-staple",
+        "",
+        "Router admin page is at 192.168.1.1.",
+    );
+    let wifi = add_project(
+        storage,
+        clock,
+        key,
+        "Home Wi-Fi",
+        &credential::encode(&wifi)?,
+        Some(&personal.id),
+        &["home"],
+    )?;
+
+    let bank = credential("jane.doe", "Demo-Only-Pa55!", "https://bank.example.com", "");
+    add_project(
+        storage,
+        clock,
+        key,
+        "Example Bank",
+        &credential::encode(&bank)?,
+        Some(&personal.id),
+        &["finance"],
+    )?;
+
+    let server = credential(
+        "deploy",
+        "s3rver-demo-password",
+        "ssh://build.example.com",
+        "Staging build server. Keys rotate every quarter.",
+    );
+    let server = add_project(
+        storage,
+        clock,
+        key,
+        "Build server",
+        &credential::encode(&server)?,
+        Some(&work.id),
+        &["servers"],
+    )?;
+    totp::set(
+        storage,
+        clock,
+        ProjectKey::Vault(key),
+        &server,
+        DEMO_TOTP_SECRET,
+        TotpOptions::default(),
+    )?;
+
+    let moving = add_project(
+        storage,
+        clock,
+        key,
+        "Moving day",
+        "Things to sort out before the move.",
+        Some(&personal.id),
+        &["home"],
+    )?;
+    for item in ["Book the van", "Forward the mail", "Cancel the old internet plan"] {
+        checklist::add(storage, clock, ProjectKey::Vault(key), &moving, item)?;
+    }
+
+    let welcome = format!(
+        "# Welcome to VaultPad\n\n\
+         This is a demo vault. It lives only in memory: nothing here is saved, and it is gone \
+         when the app closes.\n\n\
+         - Notes and credentials are encrypted one by one.\n\
+         - Projects link to each other, like the [Wi-Fi](vaultpad://project/{wifi}) or the \
+         [build server](vaultpad://project/{server}), which also has a TOTP code.\n\
+         - [Moving day](vaultpad://project/{moving}) has a checklist.\n\n\
+         Create a real vault when you are ready."
+    );
+    add_project(storage, clock, key, "Welcome", &welcome, None, &[])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::links;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn seeded_projects_open_with_the_key() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [9u8; crypto::KEY_LEN];
+        seed(&storage, &SystemClock, &key).unwrap();
+
+        let projects = storage.list_projects().unwrap();
+        assert_eq!(projects.len(), 5);
+        let wifi = projects.iter().find(|p| p.name == "Home Wi-Fi").unwrap();
+        let backlinks = links::backlinks(&storage, &SystemClock, &key, &wifi.id).unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].name, "Welcome");
+        assert_eq!(folders::list(&storage, &key).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+
+use crate::crypto::{sealed, signing};
+use crate::keychain;
+
+const KC_DEVICE_SIGNING_KEY: &str = "device-signing-key";
+const KC_DEVICE_ID: &str = "device-id";
+const KC_DEVICE_EXCHANGE_KEY: &str = "device-exchange-key";
+pub const KEYCHAIN_KEYS: [&str; 3] = [KC_DEVICE_ID, KC_DEVICE_SIGNING_KEY, KC_DEVICE_EXCHANGE_KEY];
+
+/// Stable identifier of this installation, generated on first use.
+pub fn device_id() -> Result<String, String> {
+    if let Some(id) = keychain::get(KC_DEVICE_ID) {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    keychain::save(KC_DEVICE_ID, &id)?;
+    Ok(id)
+}
+
+/// Human-readable name shown in the device list on other sessions.
+pub fn device_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("VaultPad ({})", std::env::consts::OS))
+}
+
+/// Load this installation's Ed25519 signing key from the keychain, generating it on first use.
+pub fn signing_key() -> Result<[u8; signing::SECRET_KEY_LEN], String> {
+    if let Some(b64) = keychain::get_secret(KC_DEVICE_SIGNING_KEY) {
+        let bytes = B64
+            .decode(&b64)
+            .map_err(|e| format!("Invalid device key: {e}"))?;
+        return bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Invalid device key length".to_string());
+    }
+
+    let secret = signing::generate_secret_key();
+    keychain::save_secret(KC_DEVICE_SIGNING_KEY, &B64.encode(secret))?;
+    Ok(secret)
+}
+
+/// The signing key as stored in the keychain, for moving it to another machine.
+pub fn encoded_signing_key() -> Result<String, String> {
+    Ok(B64.encode(signing_key()?))
+}
+
+/// Take over the identity of another installation, e.g. when replacing a laptop.
+pub fn restore_identity(id: &str, encoded_signing_key: &str) -> Result<(), String> {
+    let valid = B64
+        .decode(encoded_signing_key)
+        .is_ok_and(|bytes| bytes.len() == signing::SECRET_KEY_LEN);
+    if id.trim().is_empty() || !valid {
+        return Err("Invalid device identity".to_string());
+    }
+    keychain::save_secret(KC_DEVICE_SIGNING_KEY, encoded_signing_key)?;
+    keychain::save(KC_DEVICE_ID, id)
+}
+
+pub fn public_key() -> Result<[u8; signing::PUBLIC_KEY_LEN], String> {
+    Ok(signing::public_key(&signing_key()?))
+}
+
+/// Load this installation's X25519 key, used to receive keys sealed to this device (emergency
+/// access), generating it on first use.
+pub fn exchange_key() -> Result<[u8; sealed::SECRET_KEY_LEN], String> {
+    if let Some(b64) = keychain::get_secret(KC_DEVICE_EXCHANGE_KEY) {
+        let bytes = B64
+            .decode(&b64)
+            .map_err(|e| format!("Invalid device key: {e}"))?;
+        return bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Invalid device key length".to_string());
+    }
+
+    let secret = sealed::generate_secret_key();
+    keychain::save_secret(KC_DEVICE_EXCHANGE_KEY, &B64.encode(secret))?;
+    Ok(secret)
+}
+
+pub fn exchange_public_key() -> Result<[u8; sealed::PUBLIC_KEY_LEN], String> {
+    Ok(sealed::public_key(&exchange_key()?))
+}
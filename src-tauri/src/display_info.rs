@@ -0,0 +1,214 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! What the lock screen may show before anything is unlocked: the vault's name and color, when
+//! it was last opened and which quick-unlock factor is set up. It lives in a plain file,
+//! `~/.vaultpad/display-info.json`, so reading it never opens the database, the keychain
+//! (which may prompt on Linux) or any key material. Nothing secret may ever be added here.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::quick_unlock::QuickUnlockKind;
+
+const FILE_NAME: &str = "display-info.json";
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct VaultEntry {
+    name: Option<String>,
+    color: Option<String>,
+    last_opened: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DisplayFile {
+    last_vault: Option<String>,
+    quick_unlock: Option<QuickUnlockKind>,
+    #[serde(default)]
+    vaults: BTreeMap<String, VaultEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultDisplayInfo {
+    pub db_path: String,
+    /// The name given to the vault, or its file name.
+    pub name: String,
+    /// `#rrggbb`, when one was chosen.
+    pub color: Option<String>,
+    pub last_opened: Option<String>,
+    /// The quick-unlock factor set up for the saved session, if any.
+    pub quick_unlock: Option<QuickUnlockKind>,
+}
+
+fn default_file() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".vaultpad").join(FILE_NAME))
+}
+
+fn load(file: &Path) -> DisplayFile {
+    std::fs::read_to_string(file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update(file: Option<PathBuf>, f: impl FnOnce(&mut DisplayFile)) -> Result<(), String> {
+    let file = file.ok_or("Cannot determine home directory")?;
+    let mut contents = load(&file);
+    f(&mut contents);
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&contents).map_err(|e| e.to_string())?;
+    std::fs::write(&file, json).map_err(|e| e.to_string())
+}
+
+fn file_stem(db_path: &str) -> String {
+    Path::new(db_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| db_path.to_string())
+}
+
+fn read_from(file: &Path, db_path: Option<&str>) -> Option<VaultDisplayInfo> {
+    let contents = load(file);
+    let db_path = db_path.map(str::to_string).or(contents.last_vault)?;
+    let entry = contents.vaults.get(&db_path).cloned().unwrap_or_default();
+    Some(VaultDisplayInfo {
+        name: entry.name.unwrap_or_else(|| file_stem(&db_path)),
+        db_path,
+        color: entry.color,
+        last_opened: entry.last_opened,
+        quick_unlock: contents.quick_unlock,
+    })
+}
+
+/// Display info for the vault at `db_path`, or for the one opened last.
+pub fn get(db_path: Option<&str>) -> Option<VaultDisplayInfo> {
+    read_from(&default_file()?, db_path)
+}
+
+fn known_in(file: &Path) -> Vec<VaultDisplayInfo> {
+    let contents = load(file);
+    let mut known: Vec<VaultDisplayInfo> = contents
+        .vaults
+        .into_iter()
+        .map(|(db_path, entry)| VaultDisplayInfo {
+            name: entry.name.unwrap_or_else(|| file_stem(&db_path)),
+            quick_unlock: contents
+                .quick_unlock
+                .filter(|_| contents.last_vault.as_deref() == Some(db_path.as_str())),
+            db_path,
+            color: entry.color,
+            last_opened: entry.last_opened,
+        })
+        .collect();
+    known.sort_by(|a, b| b.last_opened.cmp(&a.last_opened));
+    known
+}
+
+/// Every vault this machine has opened or named, most recently opened first.
+pub fn known() -> Vec<VaultDisplayInfo> {
+    default_file().map(|file| known_in(&file)).unwrap_or_default()
+}
+
+/// Note an unlock of the vault at `db_path`.
+pub fn record_opened(
+    db_path: &str,
+    opened_at: &str,
+    quick_unlock: Option<QuickUnlockKind>,
+) -> Result<(), String> {
+    update(default_file(), |contents| {
+        contents.last_vault = Some(db_path.to_string());
+        contents.quick_unlock = quick_unlock;
+        let entry = contents.vaults.entry(db_path.to_string()).or_default();
+        entry.last_opened = Some(opened_at.to_string());
+    })
+}
+
+pub fn set_quick_unlock(kind: Option<QuickUnlockKind>) -> Result<(), String> {
+    update(default_file(), |contents| contents.quick_unlock = kind)
+}
+
+/// Forget which vault was opened last, along with the quick-unlock factor, when the saved
+/// session is cleared. Names and colors stay.
+pub fn forget_session() -> Result<(), String> {
+    update(default_file(), |contents| {
+        contents.last_vault = None;
+        contents.quick_unlock = None;
+    })
+}
+
+/// Carry the name, color and last-opened time of the vault at `from` over to `to` after it moved.
+pub fn moved(from: &str, to: &str) -> Result<(), String> {
+    update(default_file(), |contents| {
+        if let Some(entry) = contents.vaults.remove(from) {
+            contents.vaults.insert(to.to_string(), entry);
+        }
+        if contents.last_vault.as_deref() == Some(from) {
+            contents.last_vault = Some(to.to_string());
+        }
+    })
+}
+
+fn valid_color(color: &str) -> bool {
+    color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn set_appearance_in(
+    file: Option<PathBuf>,
+    db_path: &str,
+    name: Option<&str>,
+    color: Option<&str>,
+) -> Result<(), String> {
+    let name = name.map(str::trim).filter(|n| !n.is_empty());
+    if name.is_some_and(|n| n.chars().count() > MAX_NAME_LEN) {
+        return Err(format!("Vault names are at most {MAX_NAME_LEN} characters"));
+    }
+    let color = color.filter(|c| !c.is_empty());
+    if color.is_some_and(|c| !valid_color(c)) {
+        return Err("Colors are given as #rrggbb".to_string());
+    }
+    update(file, |contents| {
+        let entry = contents.vaults.entry(db_path.to_string()).or_default();
+        entry.name = name.map(str::to_string);
+        entry.color = color.map(str::to_ascii_lowercase);
+    })
+}
+
+/// Name and color the vault at `db_path`; `None` goes back to the file name and no color.
+pub fn set_appearance(db_path: &str, name: Option<&str>, color: Option<&str>) -> Result<(), String> {
+    set_appearance_in(default_file(), db_path, name, color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_the_last_vault_and_its_file_name() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-display-{}", std::process::id()));
+        let file = dir.join(FILE_NAME);
+        assert!(read_from(&file, None).is_none());
+
+        update(Some(file.clone()), |contents| {
+            contents.last_vault = Some("/vaults/work.db".to_string());
+            contents.quick_unlock = Some(QuickUnlockKind::Pin);
+        })
+        .unwrap();
+        let info = read_from(&file, None).unwrap();
+        assert_eq!(info.name, "work");
+        assert_eq!(info.quick_unlock, Some(QuickUnlockKind::Pin));
+
+        assert!(set_appearance_in(Some(file.clone()), "/vaults/work.db", None, Some("red")).is_err());
+        set_appearance_in(Some(file.clone()), "/vaults/work.db", Some(" Work "), Some("#A0B1C2"))
+            .unwrap();
+        let info = read_from(&file, Some("/vaults/work.db")).unwrap();
+        assert_eq!((info.name.as_str(), info.color.as_deref()), ("Work", Some("#a0b1c2")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Autosaved drafts. While a project is being edited the editor saves its name and content here
+//! every few seconds, encrypted with the project key like custom fields, so a crash or a
+//! force-quit loses at most those few seconds. A project has at most one draft; saving the
+//! project drops it, and so does discarding it. When the project is next opened the draft is
+//! offered back, flagged when the project has been saved since the draft was started.
+//!
+//! Drafts stay on this device: they are neither synced nor kept in backups.
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::{Draft, Project};
+use crate::storage::StorageProvider;
+
+const MAX_NAME_LEN: usize = 1024;
+const MAX_CONTENT_LEN: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredDraft {
+    pub name: String,
+    pub content: String,
+    pub saved_at: String,
+    /// The revision the edit started from.
+    pub base_revision: i64,
+    /// Whether the project was saved after the edit started, so restoring the draft would
+    /// overwrite that save.
+    pub outdated: bool,
+}
+
+/// What is encrypted for a draft.
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    name: String,
+    content: String,
+}
+
+fn seal(project_key: ProjectKey<'_>, project_id: &str, sealed: &Sealed) -> Result<Vec<u8>, String> {
+    let plain = Zeroizing::new(serde_json::to_vec(sealed).map_err(|e| e.to_string())?);
+    match project_key {
+        ProjectKey::Vault(key) => {
+            crypto::encrypt_with_key_aad(&plain, key, &crypto::field_aad(project_id, "draft"))
+        }
+        ProjectKey::Password(password) => crypto::encrypt(&plain, password),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn open(project_key: ProjectKey<'_>, draft: &Draft) -> Result<Sealed, String> {
+    let plain = match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &draft.encrypted_draft,
+            key,
+            &crypto::field_aad(&draft.project_id, "draft"),
+        )
+        .ok_or("Wrong key for this draft")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&draft.encrypted_draft, password).map_err(|e| e.to_string())?
+        }
+    };
+    let plain = Zeroizing::new(plain);
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+/// Keep the edit of `project` in progress, replacing its previous draft. `base_revision` is the
+/// revision the editor loaded.
+pub fn save(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project: &Project,
+    base_revision: i64,
+    name: &str,
+    content: &str,
+) -> Result<(), String> {
+    if name.len() > MAX_NAME_LEN || content.len() > MAX_CONTENT_LEN {
+        return Err("Draft is too large".to_string());
+    }
+    let sealed = Sealed { name: name.to_string(), content: content.to_string() };
+    let encrypted_draft = seal(project_key, &project.id, &sealed)?;
+    storage
+        .save_draft(&Draft {
+            project_id: project.id.clone(),
+            encrypted_draft,
+            base_revision,
+            saved_at: clock.now().to_rfc3339(),
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// The draft of `project`, if there is one.
+pub fn get(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project: &Project,
+) -> Result<Option<RecoveredDraft>, String> {
+    let Some(draft) = storage.get_draft(&project.id).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let sealed = open(project_key, &draft)?;
+    Ok(Some(RecoveredDraft {
+        name: sealed.name,
+        content: sealed.content,
+        saved_at: draft.saved_at,
+        base_revision: draft.base_revision,
+        outdated: draft.base_revision != project.revision,
+    }))
+}
+
+/// Drop a project's draft, once it was saved or the user turned it down.
+pub fn discard(storage: &dyn StorageProvider, project_id: &str) -> Result<(), String> {
+    storage.delete_draft(project_id).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt every draft held under the vault key with `new_key`, for a master password
+/// change. Those of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for mut draft in storage.list_drafts().map_err(|e| e.to_string())? {
+        let Ok(sealed) = open(ProjectKey::Vault(old_key), &draft) else {
+            continue;
+        };
+        draft.encrypted_draft = seal(ProjectKey::Vault(new_key), &draft.project_id, &sealed)?;
+        storage.save_draft(&draft).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::commands::projects::master_key_project;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn drafts_round_trip_and_notice_later_saves() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [6u8; crypto::KEY_LEN];
+        let project = master_key_project(&SystemClock, &key, true, "Notes", "first").unwrap();
+        storage.create_project(&project).unwrap();
+        let project = storage.get_project(&project.id).unwrap();
+        let vault = ProjectKey::Vault(&key);
+
+        assert!(get(&storage, vault, &project).unwrap().is_none());
+        save(&storage, &SystemClock, vault, &project, project.revision, "Notes", "first draft")
+            .unwrap();
+        let draft = get(&storage, vault, &project).unwrap().unwrap();
+        assert_eq!(draft.content, "first draft");
+        assert!(!draft.outdated);
+        assert!(get(&storage, ProjectKey::Vault(&[7u8; crypto::KEY_LEN]), &project).is_err());
+
+        let mut saved = project.clone();
+        saved.updated_at = "2030-01-01T00:00:00Z".to_string();
+        storage.update_project(&saved).unwrap();
+        let saved = storage.get_project(&project.id).unwrap();
+        assert!(get(&storage, vault, &saved).unwrap().unwrap().outdated);
+
+        let new_key = [8u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        assert_eq!(get(&storage, ProjectKey::Vault(&new_key), &saved).unwrap().unwrap().name, "Notes");
+
+        discard(&storage, &project.id).unwrap();
+        assert!(get(&storage, vault, &saved).unwrap().is_none());
+    }
+}
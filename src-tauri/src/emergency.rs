@@ -0,0 +1,170 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Emergency access ("dead man's switch"). The owner seals the vault key to a trusted contact's
+//! X25519 key and leaves it with the server. The contact can ask for access at any time; if the
+//! owner does not deny the request within the waiting period, the server releases the sealed key
+//! and the contact's app can open the owner's projects with it.
+//!
+//! Both sides keep their half locally, encrypted with their own vault key: the owner the list of
+//! contacts they configured, the contact the keys released to them.
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::crypto::{self, sealed, KEY_LEN};
+use crate::storage::StorageProvider;
+
+const META_EMERGENCY_CONTACTS: &str = "emergency_contacts";
+const META_EMERGENCY_KEYS: &str = "emergency_keys";
+
+pub const MIN_WAIT_SECS: u64 = 60 * 60;
+pub const MAX_WAIT_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// A trusted contact configured by the owner of this vault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyContact {
+    pub grant_id: i64,
+    pub grantee_email: String,
+    pub public_key: String,
+    pub wait_secs: u64,
+    pub created_at: String,
+}
+
+/// A vault key another user's server released to us.
+#[derive(Serialize, Deserialize)]
+struct ReleasedKey {
+    grant_id: i64,
+    owner_email: String,
+    key: String,
+}
+
+fn load<T: DeserializeOwned>(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    name: &str,
+) -> Result<Vec<T>, String> {
+    let encoded = match storage.get_meta(name).map_err(|e| e.to_string())? {
+        Some(encoded) if !encoded.is_empty() => encoded,
+        _ => return Ok(Vec::new()),
+    };
+    let encrypted = B64.decode(encoded).map_err(|e| e.to_string())?;
+    let mut json = crypto::try_decrypt_with_key_aad(
+        &encrypted,
+        vault_key,
+        &crypto::field_aad("emergency", name),
+    )
+    .ok_or("Emergency access data cannot be decrypted")?;
+    let items = serde_json::from_slice(&json).map_err(|e| e.to_string());
+    json.zeroize();
+    items
+}
+
+fn save<T: Serialize>(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    name: &str,
+    items: &[T],
+) -> Result<(), String> {
+    let mut json = serde_json::to_vec(items).map_err(|e| e.to_string())?;
+    let encrypted =
+        crypto::encrypt_with_key_aad(&json, vault_key, &crypto::field_aad("emergency", name));
+    json.zeroize();
+    storage
+        .set_meta(name, &B64.encode(encrypted.map_err(|e| e.to_string())?))
+        .map_err(|e| e.to_string())
+}
+
+pub fn parse_public_key(encoded: &str) -> Result<[u8; sealed::PUBLIC_KEY_LEN], String> {
+    B64.decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.as_slice().try_into().ok())
+        .ok_or_else(|| "Invalid emergency contact key".to_string())
+}
+
+pub fn validate_wait(wait_secs: u64) -> Result<(), String> {
+    if !(MIN_WAIT_SECS..=MAX_WAIT_SECS).contains(&wait_secs) {
+        return Err(format!(
+            "Waiting period must be between {MIN_WAIT_SECS} and {MAX_WAIT_SECS} seconds"
+        ));
+    }
+    Ok(())
+}
+
+pub fn contacts(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+) -> Result<Vec<EmergencyContact>, String> {
+    load(storage, vault_key, META_EMERGENCY_CONTACTS)
+}
+
+pub fn add_contact(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    contact: EmergencyContact,
+) -> Result<(), String> {
+    let mut all = contacts(storage, vault_key)?;
+    all.retain(|c| c.grant_id != contact.grant_id);
+    all.push(contact);
+    save(storage, vault_key, META_EMERGENCY_CONTACTS, &all)
+}
+
+pub fn remove_contact(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    grant_id: i64,
+) -> Result<(), String> {
+    let mut all = contacts(storage, vault_key)?;
+    all.retain(|c| c.grant_id != grant_id);
+    save(storage, vault_key, META_EMERGENCY_CONTACTS, &all)
+}
+
+/// Open a key the server released, check it is a vault key and keep it.
+pub fn store_released_key(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    exchange_key: &[u8; sealed::SECRET_KEY_LEN],
+    grant_id: i64,
+    owner_email: &str,
+    sealed_key: &[u8],
+) -> Result<(), String> {
+    let mut plain = sealed::open(sealed_key, exchange_key).map_err(|e| e.to_string())?;
+    if plain.len() != KEY_LEN {
+        plain.zeroize();
+        return Err("Released key has an unexpected length".to_string());
+    }
+    let mut all: Vec<ReleasedKey> = load(storage, vault_key, META_EMERGENCY_KEYS)?;
+    all.retain(|k| k.grant_id != grant_id);
+    all.push(ReleasedKey {
+        grant_id,
+        owner_email: owner_email.to_string(),
+        key: B64.encode(&plain),
+    });
+    plain.zeroize();
+    let result = save(storage, vault_key, META_EMERGENCY_KEYS, &all);
+    for k in &mut all {
+        k.key.zeroize();
+    }
+    result
+}
+
+/// The released key that opens a project with this `key_check`, if any.
+pub fn key_for(
+    storage: &dyn StorageProvider,
+    vault_key: &[u8; KEY_LEN],
+    key_check: &[u8],
+) -> Option<[u8; KEY_LEN]> {
+    let mut all: Vec<ReleasedKey> = load(storage, vault_key, META_EMERGENCY_KEYS).ok()?;
+    let found = all.iter().find_map(|released| {
+        let mut bytes = B64.decode(&released.key).ok()?;
+        let key: Option<[u8; KEY_LEN]> = bytes.as_slice().try_into().ok();
+        bytes.zeroize();
+        key.filter(|key| crypto::try_decrypt_with_key(key_check, key).is_some())
+    });
+    for k in &mut all {
+        k.key.zeroize();
+    }
+    found
+}
@@ -0,0 +1,155 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Short-lived cache for decrypted data the UI wants to keep around (search previews, recently
+//! viewed snippets), so it never has to put such data into the webview's localStorage or
+//! sessionStorage. Values are encrypted under a random per-run key kept on a locked page, expire
+//! after their TTL and are all dropped when the vault locks. Nothing is ever written to disk.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use zeroize::Zeroize;
+
+use crate::clock::Clock;
+use crate::crypto::{self, locked::LockedKey, wrap};
+
+pub const MAX_TTL_SECS: u64 = 60 * 60;
+const MAX_ENTRIES: usize = 512;
+const MAX_VALUE_LEN: usize = 1024 * 1024;
+const MAX_NAME_LEN: usize = 256;
+
+struct Entry {
+    sealed: Vec<u8>,
+    expires_at: Instant,
+}
+
+struct Cache {
+    key: LockedKey,
+    entries: HashMap<String, Entry>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn entry_aad(name: &str) -> Vec<u8> {
+    crypto::field_aad("ephemeral", name)
+}
+
+fn wipe(entry: &mut Entry) {
+    entry.sealed.zeroize();
+}
+
+impl Cache {
+    fn new() -> Self {
+        let mut key = LockedKey::new();
+        key.set(wrap::generate_key());
+        Self {
+            key,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn purge_expired(&mut self, now: Instant) {
+        self.entries.retain(|_, entry| {
+            let live = entry.expires_at > now;
+            if !live {
+                wipe(entry);
+            }
+            live
+        });
+    }
+}
+
+pub fn put(clock: &dyn Clock, name: &str, value: &str, ttl_secs: u64) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err("Invalid cache entry name".to_string());
+    }
+    if value.len() > MAX_VALUE_LEN {
+        return Err("Cache entry is too large".to_string());
+    }
+    if ttl_secs == 0 || ttl_secs > MAX_TTL_SECS {
+        return Err(format!("TTL must be between 1 and {MAX_TTL_SECS} seconds"));
+    }
+
+    let mut guard = CACHE.lock().map_err(|e| e.to_string())?;
+    let cache = guard.get_or_insert_with(Cache::new);
+    let now = clock.instant();
+    cache.purge_expired(now);
+    if cache.entries.len() >= MAX_ENTRIES && !cache.entries.contains_key(name) {
+        return Err("Ephemeral cache is full".to_string());
+    }
+
+    let sealed = cache
+        .key
+        .with_key(|key| crypto::encrypt_with_key_aad(value.as_bytes(), key, &entry_aad(name)))
+        .ok_or("Ephemeral cache key missing")?;
+    let entry = Entry {
+        sealed: sealed.map_err(|e| e.to_string())?,
+        expires_at: now + Duration::from_secs(ttl_secs),
+    };
+    if let Some(mut old) = cache.entries.insert(name.to_string(), entry) {
+        wipe(&mut old);
+    }
+    Ok(())
+}
+
+pub fn get(clock: &dyn Clock, name: &str) -> Result<Option<String>, String> {
+    let mut guard = CACHE.lock().map_err(|e| e.to_string())?;
+    let Some(cache) = guard.as_mut() else {
+        return Ok(None);
+    };
+    cache.purge_expired(clock.instant());
+    let Some(entry) = cache.entries.get(name) else {
+        return Ok(None);
+    };
+    let plain = cache
+        .key
+        .with_key(|key| crypto::try_decrypt_with_key_aad(&entry.sealed, key, &entry_aad(name)))
+        .ok_or("Ephemeral cache key missing")?
+        .ok_or("Ephemeral cache entry cannot be decrypted")?;
+    String::from_utf8(plain).map(Some).map_err(|e| e.to_string())
+}
+
+pub fn remove(name: &str) {
+    if let Ok(mut guard) = CACHE.lock() {
+        if let Some(mut entry) = guard.as_mut().and_then(|c| c.entries.remove(name)) {
+            wipe(&mut entry);
+        }
+    }
+}
+
+/// Drop every entry and the cache key; called whenever the vault locks.
+pub fn clear() {
+    if let Ok(mut guard) = CACHE.lock() {
+        if let Some(mut cache) = guard.take() {
+            cache.entries.values_mut().for_each(wipe);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{ManualClock, SystemClock};
+
+    #[test]
+    fn entries_round_trip_and_clear() {
+        let clock = SystemClock;
+        put(&clock, "preview:test", "secret snippet", 60).unwrap();
+        assert_eq!(get(&clock, "preview:test").unwrap().as_deref(), Some("secret snippet"));
+        assert!(put(&clock, "preview:test", "x", MAX_TTL_SECS + 1).is_err());
+        remove("preview:test");
+        assert_eq!(get(&clock, "preview:test").unwrap(), None);
+    }
+
+    #[test]
+    fn entries_expire_after_their_ttl() {
+        let clock = ManualClock::new(chrono::Utc::now());
+        put(&clock, "preview:expiring", "snippet", 60).unwrap();
+        clock.advance(Duration::from_secs(59));
+        assert!(get(&clock, "preview:expiring").unwrap().is_some());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(get(&clock, "preview:expiring").unwrap(), None);
+    }
+}
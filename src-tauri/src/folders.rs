@@ -0,0 +1,351 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Project folders. Folders form a tree through `parent_id`, and each project sits in at most one
+//! of them. Folder ids are generated once and shared by every device, so a project's `folder_id`
+//! means the same thing wherever it syncs. A parent id or project folder this device does not
+//! know (yet) is shown at the top level rather than hidden.
+//!
+//! Deleting a folder never deletes projects: its projects and subfolders move up to its parent.
+//! A folder that was synced is kept as a `deleted` tombstone until the deletion reaches the
+//! server.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::Folder;
+use crate::ordering;
+use crate::server_features;
+use crate::storage::remote::RemoteStorage;
+use crate::storage::{StorageError, StorageProvider};
+
+const MAX_NAME_LEN: usize = 128;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderInfo {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub position: String,
+}
+
+fn name_aad(folder_id: &str) -> Vec<u8> {
+    crypto::field_aad(folder_id, "folder_name")
+}
+
+fn decrypt_name(folder: &Folder, key: &[u8; crypto::KEY_LEN]) -> Result<String, String> {
+    crypto::try_decrypt_with_key_aad(&folder.encrypted_name, key, &name_aad(&folder.id))
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| format!("Failed to decrypt folder {}", folder.id))
+}
+
+fn encrypt_name(folder_id: &str, name: &str, key: &[u8; crypto::KEY_LEN]) -> Result<Vec<u8>, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Folder name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("Folder names must be at most {MAX_NAME_LEN} characters"));
+    }
+    crypto::encrypt_with_key_aad(name.as_bytes(), key, &name_aad(folder_id)).map_err(|e| e.to_string())
+}
+
+/// Folders that have not been deleted, by id.
+fn live(storage: &dyn StorageProvider) -> Result<HashMap<String, Folder>, String> {
+    Ok(storage
+        .list_folders()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|f| f.sync_status != "deleted")
+        .map(|f| (f.id.clone(), f))
+        .collect())
+}
+
+/// Ids of the folders projects can be shown in.
+pub fn known_ids(storage: &dyn StorageProvider) -> Result<HashSet<String>, String> {
+    Ok(live(storage)?.into_keys().collect())
+}
+
+/// Record a local change: a synced folder needs uploading again.
+fn touch(folder: &mut Folder, clock: &dyn Clock) {
+    folder.updated_at = clock.now().to_rfc3339();
+    if folder.sync_status == "synced" {
+        folder.sync_status = "modified".to_string();
+    }
+}
+
+/// A position after every current child of `parent_id`.
+fn last_position(folders: &HashMap<String, Folder>, parent_id: Option<&str>) -> String {
+    let last = folders
+        .values()
+        .filter(|f| f.parent_id.as_deref() == parent_id)
+        .map(|f| f.position.as_str())
+        .max();
+    ordering::between(last, None)
+}
+
+fn require_parent(folders: &HashMap<String, Folder>, parent_id: Option<&str>) -> Result<(), String> {
+    match parent_id {
+        Some(id) if !folders.contains_key(id) => Err(format!("Folder not found: {id}")),
+        _ => Ok(()),
+    }
+}
+
+pub fn list(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> Result<Vec<FolderInfo>, String> {
+    let folders = storage.list_folders().map_err(|e| e.to_string())?;
+    let ids: HashSet<&str> = folders
+        .iter()
+        .filter(|f| f.sync_status != "deleted")
+        .map(|f| f.id.as_str())
+        .collect();
+    folders
+        .iter()
+        .filter(|f| f.sync_status != "deleted")
+        .map(|f| {
+            Ok(FolderInfo {
+                id: f.id.clone(),
+                parent_id: f.parent_id.clone().filter(|p| ids.contains(p.as_str())),
+                name: decrypt_name(f, key)?,
+                position: f.position.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn create(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    name: &str,
+    parent_id: Option<&str>,
+) -> Result<FolderInfo, String> {
+    let folders = live(storage)?;
+    require_parent(&folders, parent_id)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = clock.now().to_rfc3339();
+    let folder = Folder {
+        encrypted_name: encrypt_name(&id, name, key)?,
+        id,
+        parent_id: parent_id.map(str::to_string),
+        position: last_position(&folders, parent_id),
+        created_at: now.clone(),
+        updated_at: now,
+        sync_status: "local".to_string(),
+    };
+    storage.save_folder(&folder).map_err(|e| e.to_string())?;
+    Ok(FolderInfo {
+        id: folder.id,
+        parent_id: folder.parent_id,
+        name: name.trim().to_string(),
+        position: folder.position,
+    })
+}
+
+pub fn rename(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    id: &str,
+    name: &str,
+) -> Result<(), String> {
+    let mut folder = live(storage)?
+        .remove(id)
+        .ok_or_else(|| format!("Folder not found: {id}"))?;
+    folder.encrypted_name = encrypt_name(id, name, key)?;
+    touch(&mut folder, clock);
+    storage.save_folder(&folder).map_err(|e| e.to_string())
+}
+
+/// Move a folder under `parent_id` (`None` for the top level), after the parent's other
+/// folders. A folder cannot move into itself or one of its descendants.
+pub fn move_to(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    id: &str,
+    parent_id: Option<&str>,
+) -> Result<(), String> {
+    let mut folders = live(storage)?;
+    require_parent(&folders, parent_id)?;
+
+    let mut ancestor = parent_id;
+    while let Some(current) = ancestor {
+        if current == id {
+            return Err("A folder cannot be moved into itself".to_string());
+        }
+        ancestor = folders.get(current).and_then(|f| f.parent_id.as_deref());
+    }
+
+    let position = last_position(&folders, parent_id);
+    let mut folder = folders
+        .remove(id)
+        .ok_or_else(|| format!("Folder not found: {id}"))?;
+    folder.parent_id = parent_id.map(str::to_string);
+    folder.position = position;
+    touch(&mut folder, clock);
+    storage.save_folder(&folder).map_err(|e| e.to_string())
+}
+
+/// Put a project into `folder_id` (`None` for the top level). A synced project is marked
+/// modified so the move is uploaded.
+pub fn move_project(
+    storage: &dyn StorageProvider,
+    project_id: &str,
+    folder_id: Option<&str>,
+) -> Result<(), String> {
+    require_parent(&live(storage)?, folder_id)?;
+    let mut project = storage.get_project(project_id).map_err(|e| e.to_string())?;
+    if project.folder_id.as_deref() == folder_id {
+        return Ok(());
+    }
+    if project.sync_status == "synced" {
+        project.sync_status = "modified".to_string();
+        storage.update_project(&project).map_err(|e| e.to_string())?;
+    }
+    storage
+        .set_project_folder(project_id, folder_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a folder, moving its projects and subfolders up to its parent.
+pub fn delete(storage: &dyn StorageProvider, clock: &dyn Clock, id: &str) -> Result<(), String> {
+    let folders = live(storage)?;
+    let folder = folders
+        .get(id)
+        .cloned()
+        .ok_or_else(|| format!("Folder not found: {id}"))?;
+    let parent = folder.parent_id.as_deref().filter(|p| folders.contains_key(*p));
+
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        if project.folder_id.as_deref() == Some(id) {
+            move_project(storage, &project.id, parent)?;
+        }
+    }
+    for child in folders.values().filter(|f| f.parent_id.as_deref() == Some(id)) {
+        move_to(storage, clock, &child.id, parent)?;
+    }
+
+    if folder.sync_status == "local" {
+        storage.delete_folder(id).map_err(|e| e.to_string())
+    } else {
+        let mut tombstone = folder;
+        tombstone.updated_at = clock.now().to_rfc3339();
+        tombstone.sync_status = "deleted".to_string();
+        storage.save_folder(&tombstone).map_err(|e| e.to_string())
+    }
+}
+
+/// Re-encrypt every folder name under `new_key`, for a master password change.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for mut folder in live(storage)?.into_values() {
+        let name = decrypt_name(&folder, old_key)?;
+        folder.encrypted_name = encrypt_name(&folder.id, &name, new_key)?;
+        touch(&mut folder, clock);
+        storage.save_folder(&folder).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Exchange folders with the server before projects are synced: the newer side of each folder
+/// wins, local changes are uploaded and tombstones from either side remove the folder locally.
+/// Returns whether the server keeps folders at all; if it does not, project folder ids from it
+/// mean nothing and must not overwrite local ones.
+pub fn sync(local: &dyn StorageProvider, remote: &RemoteStorage) -> Result<bool, String> {
+    if !server_features::supports(remote, server_features::FEATURE_FOLDERS)? {
+        return Ok(false);
+    }
+    let remote_folders = match remote.list_folders() {
+        Ok(folders) => folders,
+        Err(StorageError::NotFound(_)) => return Ok(false),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut local_folders: HashMap<String, Folder> = local
+        .list_folders()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|f| (f.id.clone(), f))
+        .collect();
+
+    for theirs in remote_folders {
+        let remote_wins = local_folders
+            .get(&theirs.id)
+            .is_none_or(|ours| ours.sync_status == "synced" || theirs.updated_at > ours.updated_at);
+        if !remote_wins {
+            continue;
+        }
+        local_folders.remove(&theirs.id);
+        if theirs.sync_status == "deleted" {
+            local.delete_folder(&theirs.id).map_err(|e| e.to_string())?;
+        } else {
+            local.save_folder(&theirs).map_err(|e| e.to_string())?;
+        }
+    }
+
+    for mut ours in local_folders.into_values().filter(|f| f.sync_status != "synced") {
+        remote.put_folder(&ours).map_err(|e| e.to_string())?;
+        if ours.sync_status == "deleted" {
+            local.delete_folder(&ours.id).map_err(|e| e.to_string())?;
+        } else {
+            ours.sync_status = "synced".to_string();
+            local.save_folder(&ours).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn deleting_a_folder_moves_its_contents_up() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [4u8; crypto::KEY_LEN];
+        let clock = SystemClock;
+
+        let work = create(&storage, &clock, &key, "Work", None).unwrap();
+        let clients = create(&storage, &clock, &key, "Clients", Some(&work.id)).unwrap();
+        let acme = create(&storage, &clock, &key, "Acme", Some(&clients.id)).unwrap();
+        assert!(move_to(&storage, &clock, &work.id, Some(&acme.id)).is_err());
+
+        storage
+            .create_project(&Project {
+                id: "p".to_string(),
+                name: String::new(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "synced".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+        move_project(&storage, "p", Some(&clients.id)).unwrap();
+        assert_eq!(storage.get_project("p").unwrap().sync_status, "modified");
+
+        delete(&storage, &clock, &clients.id).unwrap();
+        assert_eq!(storage.get_project("p").unwrap().folder_id.as_deref(), Some(work.id.as_str()));
+        let folders = list(&storage, &key).unwrap();
+        let acme = folders.iter().find(|f| f.id == acme.id).unwrap();
+        assert_eq!(acme.parent_id.as_deref(), Some(work.id.as_str()));
+        assert_eq!(acme.name, "Acme");
+        assert_eq!(folders.len(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Saved-password CSV exports from browser password managers.
+//!
+//! Chrome and Edge write `name,url,username,password,note`; Firefox writes
+//! `url,username,password,httpRealm,formActionOrigin,guid,...`. Columns are looked up by name,
+//! so either layout (and older variants without `note`) is accepted.
+
+use super::csv;
+use super::{non_empty, Converter, ImportError, ImportedItem, ItemError, ParsedImport};
+
+/// Tag put on every browser import so the user can review the entries afterwards.
+pub const REVIEW_TAG: &str = "needs-review";
+
+const URL_LABEL: &str = "URL";
+const USERNAME_LABEL: &str = "Username";
+
+pub enum BrowserCsv {
+    Chrome,
+    Edge,
+    Firefox,
+}
+
+impl BrowserCsv {
+    fn tag(&self) -> &'static str {
+        match self {
+            BrowserCsv::Chrome => "chrome-import",
+            BrowserCsv::Edge => "edge-import",
+            BrowserCsv::Firefox => "firefox-import",
+        }
+    }
+}
+
+/// Identity of a login for de-duplication: normalized URL plus username.
+pub fn login_key(url: &str, username: &str) -> (String, String) {
+    let url = url.trim().to_lowercase();
+    (url.trim_end_matches('/').to_string(), username.trim().to_string())
+}
+
+/// `login_key` of an imported item, if it has a URL.
+pub fn item_login_key(item: &ImportedItem) -> Option<(String, String)> {
+    let field = |label: &str| {
+        item.fields
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v.as_str())
+    };
+    field(URL_LABEL).map(|url| login_key(url, field(USERNAME_LABEL).unwrap_or("")))
+}
+
+/// `login_key` of an existing project, read from the `URL:` and `Username:` lines importers
+/// write. Content without a URL line is not a login entry.
+pub fn content_login_key(content: &str) -> Option<(String, String)> {
+    let field = |label: &str| {
+        content.lines().find_map(|line| {
+            line.strip_prefix(label)
+                .and_then(|rest| rest.strip_prefix(':'))
+                .map(str::trim)
+        })
+    };
+    field(URL_LABEL).map(|url| login_key(url, field(USERNAME_LABEL).unwrap_or("")))
+}
+
+/// Host part of a URL, used as the title when the export has none.
+fn host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit('@').next().unwrap_or(host);
+    non_empty(host)
+}
+
+impl Converter for BrowserCsv {
+    fn id(&self) -> &'static str {
+        match self {
+            BrowserCsv::Chrome => "chrome",
+            BrowserCsv::Edge => "edge",
+            BrowserCsv::Firefox => "firefox",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BrowserCsv::Chrome => "Google Chrome (CSV)",
+            BrowserCsv::Edge => "Microsoft Edge (CSV)",
+            BrowserCsv::Firefox => "Mozilla Firefox (CSV)",
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv"]
+    }
+
+    fn parse(&self, data: &[u8]) -> Result<ParsedImport, ImportError> {
+        let text = std::str::from_utf8(data)
+            .map_err(|_| ImportError::InvalidFormat("CSV is not valid UTF-8".to_string()))?;
+        let (header, records) = csv::parse_with_header(text)?;
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let url_col = column("url")
+            .ok_or_else(|| ImportError::InvalidFormat("missing \"url\" column".to_string()))?;
+        let password_col = column("password")
+            .ok_or_else(|| ImportError::InvalidFormat("missing \"password\" column".to_string()))?;
+        let username_col = column("username");
+        let name_col = column("name");
+        let note_col = column("note");
+
+        let mut parsed = ParsedImport::default();
+        for (index, record) in records.iter().enumerate() {
+            let get = |col: Option<usize>| col.and_then(|i| non_empty(record.get(i)));
+            let url = get(Some(url_col));
+            let title = get(name_col).or_else(|| url.as_deref().and_then(host));
+
+            let url = match url {
+                Some(url) => url,
+                None => {
+                    parsed.errors.push(ItemError {
+                        index,
+                        title,
+                        message: format!("line {}: missing URL", record.line),
+                    });
+                    continue;
+                }
+            };
+
+            let mut fields = vec![(URL_LABEL.to_string(), url)];
+            if let Some(username) = get(username_col) {
+                fields.push((USERNAME_LABEL.to_string(), username));
+            }
+            // Passwords are kept verbatim: leading or trailing spaces may be part of them.
+            let password = record.get(password_col);
+            if !password.is_empty() {
+                fields.push(("Password".to_string(), password.to_string()));
+            }
+
+            parsed.items.push(ImportedItem {
+                title: title.unwrap_or_else(|| "Untitled".to_string()),
+                folder: None,
+                fields,
+                notes: get(note_col),
+                attachments: Vec::new(),
+                tags: vec![self.tag().to_string(), REVIEW_TAG.to_string()],
+            });
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imported_content_round_trips_login_key() {
+        let csv = "name,url,username,password,note\n\
+                   ,https://Example.com/,alice, s3cret ,\n\
+                   Bank,,bob,pw,\n";
+        let parsed = BrowserCsv::Chrome.parse(csv.as_bytes()).unwrap();
+        assert_eq!(parsed.items.len(), 1);
+        assert_eq!(parsed.errors.len(), 1);
+
+        let item = &parsed.items[0];
+        assert_eq!(item.title, "Example.com");
+        assert!(item.fields.contains(&("Password".to_string(), " s3cret ".to_string())));
+        let key = item_login_key(item).unwrap();
+        assert_eq!(key, login_key("https://example.com", "alice"));
+        assert_eq!(content_login_key(&item.to_content()), Some(key));
+    }
+}
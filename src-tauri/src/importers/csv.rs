@@ -0,0 +1,112 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Minimal RFC 4180 reader: quoted fields, doubled quotes, embedded newlines, CRLF or LF line
+//! endings and an optional UTF-8 BOM.
+
+use super::ImportError;
+
+/// A data row and the line it starts on, for error messages.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+impl Record {
+    pub fn get(&self, index: usize) -> &str {
+        self.fields.get(index).map(String::as_str).unwrap_or("")
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Record>, ImportError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                push_record(&mut records, record_line, std::mem::take(&mut fields));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ImportError::Csv {
+            line: record_line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        push_record(&mut records, record_line, fields);
+    }
+    Ok(records)
+}
+
+fn push_record(records: &mut Vec<Record>, line: usize, fields: Vec<String>) {
+    // Blank lines carry no data.
+    if fields.len() == 1 && fields[0].is_empty() {
+        return;
+    }
+    records.push(Record { line, fields });
+}
+
+/// Split into a lowercased header and the data rows.
+pub fn parse_with_header(text: &str) -> Result<(Vec<String>, Vec<Record>), ImportError> {
+    let mut records = parse(text)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| ImportError::InvalidFormat("empty CSV file".to_string()))?
+        .fields
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    Ok((header, records.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quotes_and_embedded_newlines() {
+        let text = "\u{feff}title,note\r\n\"a, b\",\"say \"\"hi\"\"\nbye\"\r\n\r\nplain,\n";
+        let (header, rows) = parse_with_header(text).unwrap();
+        assert_eq!(header, vec!["title", "note"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].fields, vec!["a, b", "say \"hi\"\nbye"]);
+        assert_eq!(rows[1].line, 5);
+        assert_eq!(rows[1].fields, vec!["plain", ""]);
+        assert!(parse("\"open").is_err());
+    }
+}
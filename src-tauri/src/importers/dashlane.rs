@@ -0,0 +1,142 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Dashlane CSV export: either a single CSV (e.g. `credentials.csv`) or the ZIP archive holding
+//! one CSV per category (credentials, secure notes, ids, payments, personal info).
+
+use std::io::{Cursor, Read};
+
+use super::csv::{self, Record};
+use super::{non_empty, Converter, ImportError, ImportedItem, ItemError, ParsedImport};
+
+pub struct DashlaneExport;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const TITLE_COLUMNS: [&str; 3] = ["title", "name", "item_name"];
+const NOTE_COLUMNS: [&str; 2] = ["note", "notes"];
+const FOLDER_COLUMN: &str = "category";
+
+fn field_label(column: &str) -> String {
+    match column {
+        "username" => "Username".to_string(),
+        "username2" => "Username 2".to_string(),
+        "username3" => "Username 3".to_string(),
+        "password" => "Password".to_string(),
+        "url" => "URL".to_string(),
+        "otpsecret" | "otpurl" => "TOTP".to_string(),
+        _ => {
+            let mut chars = column.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+fn convert_record(header: &[String], record: &Record) -> Result<ImportedItem, String> {
+    if record.fields.len() > header.len() {
+        return Err(format!(
+            "expected {} columns, found {}",
+            header.len(),
+            record.fields.len()
+        ));
+    }
+
+    let mut item = ImportedItem::default();
+    let mut fallback_title = None;
+    for (i, column) in header.iter().enumerate() {
+        let value = match non_empty(record.get(i)) {
+            Some(value) => value,
+            None => continue,
+        };
+        if TITLE_COLUMNS.contains(&column.as_str()) {
+            item.title = value;
+        } else if NOTE_COLUMNS.contains(&column.as_str()) {
+            item.notes = Some(value);
+        } else if column == FOLDER_COLUMN {
+            item.folder = Some(value);
+        } else {
+            if fallback_title.is_none() && matches!(column.as_str(), "url" | "username" | "email") {
+                fallback_title = Some(value.clone());
+            }
+            item.fields.push((field_label(column), value));
+        }
+    }
+
+    if item.fields.is_empty() && item.notes.is_none() && item.title.is_empty() {
+        return Err("empty entry".to_string());
+    }
+    if item.title.is_empty() {
+        item.title = fallback_title.unwrap_or_else(|| "Untitled".to_string());
+    }
+    Ok(item)
+}
+
+/// Convert one CSV file, numbering entries from `first_index`.
+fn parse_csv(
+    text: &str,
+    source: &str,
+    first_index: usize,
+    parsed: &mut ParsedImport,
+) -> Result<usize, ImportError> {
+    let (header, records) = csv::parse_with_header(text)?;
+    for (offset, record) in records.iter().enumerate() {
+        match convert_record(&header, record) {
+            Ok(item) => parsed.items.push(item),
+            Err(message) => parsed.errors.push(ItemError {
+                index: first_index + offset,
+                title: header
+                    .iter()
+                    .position(|c| TITLE_COLUMNS.contains(&c.as_str()))
+                    .and_then(|i| non_empty(record.get(i))),
+                message: format!("{source}, line {}: {message}", record.line),
+            }),
+        }
+    }
+    Ok(records.len())
+}
+
+impl Converter for DashlaneExport {
+    fn id(&self) -> &'static str {
+        "dashlane"
+    }
+
+    fn name(&self) -> &'static str {
+        "Dashlane (CSV or ZIP)"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv", "zip"]
+    }
+
+    fn parse(&self, data: &[u8]) -> Result<ParsedImport, ImportError> {
+        let mut parsed = ParsedImport::default();
+
+        if !data.starts_with(ZIP_MAGIC) {
+            let text = std::str::from_utf8(data)
+                .map_err(|_| ImportError::InvalidFormat("CSV is not valid UTF-8".to_string()))?;
+            parse_csv(text, "CSV", 0, &mut parsed)?;
+            return Ok(parsed);
+        }
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))
+            .map_err(|e| ImportError::Archive(e.to_string()))?;
+        let mut next_index = 0;
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| ImportError::Archive(e.to_string()))?;
+            let name = entry.name().to_string();
+            if entry.is_dir() || !name.to_lowercase().ends_with(".csv") {
+                continue;
+            }
+            let mut text = String::new();
+            entry
+                .read_to_string(&mut text)
+                .map_err(|e| ImportError::Archive(format!("{name}: {e}")))?;
+            next_index += parse_csv(&text, &name, next_index, &mut parsed)?;
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Folders of Markdown and plain-text notes, such as an Obsidian vault. Every `.md`, `.markdown`
+//! and `.txt` file under the folder, at any depth, becomes a note named after the file and
+//! holding its text as it is. Hidden files and folders (`.obsidian`, `.git`, `.trash`) are left
+//! out and symbolic links are not followed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::ImportError;
+
+pub const EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
+const MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+const MAX_FILES: usize = 10_000;
+
+/// A note file found in the folder.
+#[derive(Debug)]
+pub struct NoteFile {
+    /// Relative to the folder.
+    pub path: PathBuf,
+    /// The file name without its extension.
+    pub name: String,
+    /// The file's text, or why it cannot be imported.
+    pub text: Result<String, String>,
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'))
+}
+
+fn is_note(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+fn read_note(path: &Path) -> Result<String, String> {
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(format!("File is larger than {} MB", MAX_FILE_SIZE / (1024 * 1024)));
+    }
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let text = String::from_utf8(bytes).map_err(|_| "File is not UTF-8 text".to_string())?;
+    Ok(text.strip_prefix('\u{feff}').map(str::to_string).unwrap_or(text))
+}
+
+/// Paths of the note files under `dir`, depth first.
+fn walk(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), ImportError> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| ImportError::Directory(format!("{}: {e}", dir.display())))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+    for path in paths {
+        if is_hidden(&path) {
+            continue;
+        }
+        let Ok(file_type) = fs::symlink_metadata(&path).map(|m| m.file_type()) else {
+            continue;
+        };
+        if file_type.is_dir() {
+            walk(&path, found)?;
+        } else if file_type.is_file() && is_note(&path) {
+            if found.len() == MAX_FILES {
+                return Err(ImportError::Directory(format!(
+                    "More than {MAX_FILES} notes; import a smaller folder"
+                )));
+            }
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The note files under `root`, ordered by path.
+pub fn collect(root: &Path) -> Result<Vec<NoteFile>, ImportError> {
+    if !root.is_dir() {
+        return Err(ImportError::Directory(format!("{} is not a folder", root.display())));
+    }
+    let mut paths = Vec::new();
+    walk(root, &mut paths)?;
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| path.file_name().unwrap_or_default().to_string_lossy().into_owned());
+            NoteFile {
+                text: read_note(&path),
+                path: path.strip_prefix(root).map(Path::to_path_buf).unwrap_or(path),
+                name,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_are_found_at_any_depth_and_hidden_ones_skipped() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-notes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Work/Servers")).unwrap();
+        fs::create_dir_all(dir.join(".obsidian")).unwrap();
+        fs::write(dir.join("Welcome.md"), "\u{feff}# Hello").unwrap();
+        fs::write(dir.join("Work/Servers/db.TXT"), "host: db1").unwrap();
+        fs::write(dir.join("Work/binary.md"), [0xff, 0xfe, 0x00]).unwrap();
+        fs::write(dir.join("Work/picture.png"), [0u8; 4]).unwrap();
+        fs::write(dir.join(".obsidian/app.md"), "{}").unwrap();
+
+        let notes = collect(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        let paths: Vec<PathBuf> = notes.iter().map(|n| n.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("Welcome.md"),
+                PathBuf::from("Work/Servers/db.TXT"),
+                PathBuf::from("Work/binary.md"),
+            ]
+        );
+        assert_eq!(notes[0].name, "Welcome");
+        assert_eq!(notes[0].text.as_deref(), Ok("# Hello"));
+        assert_eq!(notes[1].name, "db");
+        assert!(notes[2].text.is_err());
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Enpass JSON export (`File > Export > .json`).
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{non_empty, Converter, ImportError, ImportedItem, ItemError, ParsedImport};
+
+pub struct EnpassJson;
+
+#[derive(Deserialize)]
+struct Export {
+    #[serde(default)]
+    folders: Vec<Folder>,
+    items: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct Folder {
+    uuid: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    parent_uuid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    folders: Vec<String>,
+    #[serde(default)]
+    fields: Vec<Field>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+    #[serde(default)]
+    trashed: u8,
+}
+
+#[derive(Deserialize)]
+struct Field {
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    deleted: u8,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    #[serde(default)]
+    name: String,
+}
+
+/// Folder path such as `Work / Servers`, following `parent_uuid` links.
+fn folder_path(folders: &HashMap<&str, &Folder>, uuid: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    let mut current = folders.get(uuid);
+    while let Some(folder) = current {
+        parts.push(folder.title.as_str());
+        // Guard against cycles in a hand-edited export.
+        if parts.len() > folders.len() {
+            break;
+        }
+        current = folder
+            .parent_uuid
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .and_then(|p| folders.get(p));
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    parts.reverse();
+    Some(parts.join(" / "))
+}
+
+impl Converter for EnpassJson {
+    fn id(&self) -> &'static str {
+        "enpass"
+    }
+
+    fn name(&self) -> &'static str {
+        "Enpass (JSON)"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, data: &[u8]) -> Result<ParsedImport, ImportError> {
+        let export: Export = serde_json::from_slice(data)
+            .map_err(|e| ImportError::InvalidFormat(e.to_string()))?;
+        let folders: HashMap<&str, &Folder> =
+            export.folders.iter().map(|f| (f.uuid.as_str(), f)).collect();
+
+        let mut parsed = ParsedImport::default();
+        for (index, raw) in export.items.into_iter().enumerate() {
+            let title = raw.get("title").and_then(Value::as_str).map(str::to_string);
+            let item: Item = match serde_json::from_value(raw) {
+                Ok(item) => item,
+                Err(e) => {
+                    parsed.errors.push(ItemError {
+                        index,
+                        title,
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if item.trashed != 0 {
+                continue;
+            }
+
+            let fields = item
+                .fields
+                .iter()
+                .filter(|f| f.deleted == 0 && f.kind != "section")
+                .filter_map(|f| non_empty(&f.value).map(|v| (f.label.trim().to_string(), v)))
+                .collect();
+
+            parsed.items.push(ImportedItem {
+                title: non_empty(&item.title).unwrap_or_else(|| "Untitled".to_string()),
+                folder: item.folders.first().and_then(|uuid| folder_path(&folders, uuid)),
+                fields,
+                notes: non_empty(&item.note),
+                attachments: item
+                    .attachments
+                    .iter()
+                    .filter_map(|a| non_empty(&a.name))
+                    .collect(),
+                tags: Vec::new(),
+            });
+        }
+        Ok(parsed)
+    }
+}
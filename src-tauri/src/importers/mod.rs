@@ -0,0 +1,120 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Converters from other password managers' export formats. Each converter turns an export
+//! into `ImportedItem`s, which become ordinary master-key projects on import.
+
+pub mod browser;
+pub mod csv;
+pub mod dashlane;
+pub mod directory;
+pub mod enpass;
+
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Invalid export file: {0}")]
+    InvalidFormat(String),
+    #[error("CSV error on line {line}: {message}")]
+    Csv { line: usize, message: String },
+    #[error("Archive error: {0}")]
+    Archive(String),
+    #[error("Cannot read folder: {0}")]
+    Directory(String),
+}
+
+/// One entry of an export, before it is turned into a project.
+#[derive(Debug, Clone, Default)]
+pub struct ImportedItem {
+    pub title: String,
+    pub folder: Option<String>,
+    /// Label/value pairs in the order the source listed them.
+    pub fields: Vec<(String, String)>,
+    pub notes: Option<String>,
+    /// Names of attachments found in the export. Their contents are not imported.
+    pub attachments: Vec<String>,
+    /// Labels such as `needs-review`, rendered as a `Tags:` line.
+    pub tags: Vec<String>,
+}
+
+impl ImportedItem {
+    /// Plain-text project content: tags, folder, fields, notes and a list of skipped
+    /// attachments.
+    pub fn to_content(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.tags.is_empty() {
+            lines.push(format!("Tags: {}", self.tags.join(", ")));
+        }
+        if let Some(ref folder) = self.folder {
+            lines.push(format!("Folder: {folder}"));
+        }
+        for (label, value) in &self.fields {
+            lines.push(format!("{label}: {value}"));
+        }
+        if let Some(ref notes) = self.notes {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(notes.clone());
+        }
+        if !self.attachments.is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!(
+                "Attachments (not imported): {}",
+                self.attachments.join(", ")
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// An entry that could not be converted. `index` is the entry's position in the export.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemError {
+    pub index: usize,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub items: Vec<ImportedItem>,
+    pub errors: Vec<ItemError>,
+}
+
+pub trait Converter: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+    /// File extensions (lowercase, without the dot) this converter reads.
+    fn extensions(&self) -> &'static [&'static str];
+    /// Convert a whole export. Problems with single entries go to `ParsedImport::errors`; an
+    /// `Err` means the file as a whole could not be read.
+    fn parse(&self, data: &[u8]) -> Result<ParsedImport, ImportError>;
+}
+
+pub fn registry() -> Vec<Box<dyn Converter>> {
+    vec![
+        Box::new(enpass::EnpassJson),
+        Box::new(dashlane::DashlaneExport),
+        Box::new(browser::BrowserCsv::Chrome),
+        Box::new(browser::BrowserCsv::Edge),
+        Box::new(browser::BrowserCsv::Firefox),
+    ]
+}
+
+pub fn find(id: &str) -> Option<Box<dyn Converter>> {
+    registry().into_iter().find(|c| c.id() == id)
+}
+
+pub(crate) fn non_empty(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
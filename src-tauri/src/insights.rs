@@ -0,0 +1,141 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Opt-in usage counters kept inside the vault, encrypted with the vault key. Nothing here is
+//! ever sent anywhere; the user can only look at their own numbers.
+
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::crypto;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+pub const SETTING_INSIGHTS_ENABLED: &str = "usage_insights_enabled";
+const META_USAGE_INSIGHTS: &str = "usage_insights";
+
+pub const EVENT_UNLOCK: &str = "unlock";
+pub const EVENT_QUICK_UNLOCK: &str = "quick_unlock";
+pub const EVENT_HARDWARE_KEY_UNLOCK: &str = "hardware_key_unlock";
+pub const EVENT_RECOVERY_UNLOCK: &str = "recovery_unlock";
+pub const EVENT_SYNC: &str = "sync";
+pub const EVENT_PROJECT_CREATED: &str = "project_created";
+pub const EVENT_BACKUP_RESTORED: &str = "backup_restored";
+pub const EVENT_EXPORT: &str = "export";
+pub const EVENT_IMPORT: &str = "import";
+
+const MAX_EVENT_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageInsights {
+    pub counters: BTreeMap<String, u64>,
+    /// Events per UTC day (`YYYY-MM-DD`), for activity over time.
+    #[serde(default)]
+    pub daily: BTreeMap<String, u64>,
+    pub first_recorded_at: Option<String>,
+    pub last_recorded_at: Option<String>,
+}
+
+/// Event names are short `snake_case` identifiers (dots allowed for grouping).
+pub fn is_valid_event(event: &str) -> bool {
+    !event.is_empty()
+        && event.len() <= MAX_EVENT_NAME_LEN
+        && event
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+}
+
+pub fn is_enabled(storage: &dyn StorageProvider) -> bool {
+    matches!(
+        storage.get_setting(SETTING_INSIGHTS_ENABLED).ok().flatten().as_deref(),
+        Some("true")
+    )
+}
+
+fn insights_aad() -> Vec<u8> {
+    crypto::field_aad("insights", META_USAGE_INSIGHTS)
+}
+
+/// Stored insights; unreadable data (e.g. written under another vault key) counts as empty.
+pub fn load(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<UsageInsights, String> {
+    let encoded = match storage
+        .get_meta(META_USAGE_INSIGHTS)
+        .map_err(|e| e.to_string())?
+    {
+        Some(encoded) => encoded,
+        None => return Ok(UsageInsights::default()),
+    };
+    Ok(B64
+        .decode(encoded)
+        .ok()
+        .and_then(|data| crypto::try_decrypt_with_key_aad(&data, key, &insights_aad()))
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default())
+}
+
+pub fn save(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    insights: &UsageInsights,
+) -> Result<(), String> {
+    let json = serde_json::to_vec(insights).map_err(|e| e.to_string())?;
+    let encrypted =
+        crypto::encrypt_with_key_aad(&json, key, &insights_aad()).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_USAGE_INSIGHTS, &B64.encode(encrypted))
+        .map_err(|e| e.to_string())
+}
+
+pub fn clear(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> Result<(), String> {
+    save(storage, key, &UsageInsights::default())
+}
+
+/// Count one occurrence of `event` if insights are enabled. Best effort: recording must never
+/// make the action it describes fail.
+pub fn record(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    event: &str,
+) {
+    if !is_enabled(storage) || !is_valid_event(event) {
+        return;
+    }
+    let mut insights = match load(storage, key) {
+        Ok(insights) => insights,
+        Err(_) => return,
+    };
+
+    let now = clock.now();
+    *insights.counters.entry(event.to_string()).or_insert(0) += 1;
+    *insights
+        .daily
+        .entry(now.format("%Y-%m-%d").to_string())
+        .or_insert(0) += 1;
+    let now = now.to_rfc3339();
+    if insights.first_recorded_at.is_none() {
+        insights.first_recorded_at = Some(now.clone());
+    }
+    insights.last_recorded_at = Some(now);
+
+    let _ = save(storage, key, &insights);
+}
+
+/// Same as `record`, for callers that hold neither the storage nor the key lock.
+pub fn record_event(state: &AppState, event: &str) {
+    let cached = state.cached_key.lock().ok().and_then(|k| k.with_key(crypto::locked::copy));
+    let Some(key) = cached else {
+        return;
+    };
+    if let Ok(storage) = state.storage.lock() {
+        if let Some(storage) = storage.as_ref() {
+            record(&**storage, &*state.clock, &key, event);
+        }
+    }
+}
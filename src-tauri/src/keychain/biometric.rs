@@ -0,0 +1,87 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! macOS: one secret kept in its own keychain item whose access control requires Touch ID with
+//! the fingers enrolled when it was saved (`kSecAccessControlBiometryCurrentSet`). The keychain
+//! releases it only after the biometric check it runs itself, so reading the item is the prompt:
+//! no other process, and no copy of the keychain, gets at it without a finger. Enrolling another
+//! finger invalidates the item. Items with access control live in the data protection keychain,
+//! which needs a signed build. Other platforms have no such item.
+
+use zeroize::Zeroizing;
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use security_framework::passwords::{
+        delete_generic_password_options, generic_password, set_generic_password_options,
+        AccessControlOptions, PasswordOptions,
+    };
+    use zeroize::Zeroizing;
+
+    use super::super::SERVICE;
+
+    const ACCOUNT: &str = "touch-id-key";
+    const ERR_ITEM_NOT_FOUND: i32 = -25300;
+    const ERR_USER_CANCELED: i32 = -128;
+    const ERR_AUTH_FAILED: i32 = -25293;
+
+    fn options() -> PasswordOptions {
+        let mut options = PasswordOptions::new_generic_password(SERVICE, ACCOUNT);
+        options.use_protected_keychain();
+        options
+    }
+
+    pub fn save(secret: &[u8]) -> Result<(), String> {
+        // Updating an item with access control would ask for a finger first.
+        remove();
+        let mut options = options();
+        options.set_access_control_options(AccessControlOptions::BIOMETRY_CURRENT_SET);
+        set_generic_password_options(secret, options)
+            .map_err(|e| format!("Cannot save the Touch ID key: {e}"))
+    }
+
+    pub fn load() -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+        match generic_password(options()) {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(e) if e.code() == ERR_ITEM_NOT_FOUND => Ok(None),
+            Err(e) if e.code() == ERR_USER_CANCELED || e.code() == ERR_AUTH_FAILED => {
+                Err("prompt_cancelled".to_string())
+            }
+            Err(e) => Err(format!("Cannot read the Touch ID key: {e}")),
+        }
+    }
+
+    pub fn remove() {
+        let _ = delete_generic_password_options(options());
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod imp {
+    use zeroize::Zeroizing;
+
+    pub fn save(_secret: &[u8]) -> Result<(), String> {
+        Err("Touch ID is only available on macOS".to_string())
+    }
+
+    pub fn load() -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+        Ok(None)
+    }
+
+    pub fn remove() {}
+}
+
+/// Replace the secret; reading it back will need Touch ID.
+pub fn save(secret: &[u8]) -> Result<(), String> {
+    imp::save(secret)
+}
+
+/// The secret, after the keychain has confirmed the user with Touch ID. `Ok(None)` when none
+/// is saved, or it was invalidated; a cancelled prompt is the error `prompt_cancelled`.
+pub fn load() -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+    imp::load()
+}
+
+pub fn remove() {
+    imp::remove()
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! DPAPI-NG sealing for the secrets the keychain keeps to unlock the vault without its password
+//! (the session key and the quick-unlock slot). On Windows they are protected to the current
+//! user on this machine before they reach Credential Manager, so a copied or roamed credential
+//! is useless elsewhere. Other platforms store them as they are; their keychains already bind
+//! entries to the user session.
+
+/// Marks a stored value as a sealed blob, base64-encoded after the prefix.
+pub const PREFIX: &str = "dpapi-ng:";
+
+/// Protection descriptors, strongest first. Machine binding needs a domain-joined or recent
+/// system; the user-only rule is the fallback.
+#[cfg(windows)]
+const DESCRIPTORS: [&str; 2] = ["LOCAL=user AND LOCAL=machine", "LOCAL=user"];
+
+#[cfg(windows)]
+mod ffi {
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Cryptography::{
+        NCryptCloseProtectionDescriptor, NCryptCreateProtectionDescriptor, NCryptProtectSecret,
+        NCryptUnprotectSecret, NCRYPT_SILENT_FLAG,
+    };
+    use windows_sys::Win32::Security::NCRYPT_DESCRIPTOR_HANDLE;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Copy a buffer allocated by ncrypt and release it.
+    unsafe fn take(buf: *mut u8, len: u32) -> Vec<u8> {
+        let out = std::slice::from_raw_parts(buf, len as usize).to_vec();
+        LocalFree(buf as _);
+        out
+    }
+
+    pub fn protect(descriptor: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+        let rule = wide(descriptor);
+        unsafe {
+            let mut handle: NCRYPT_DESCRIPTOR_HANDLE = ptr::null_mut();
+            let status = NCryptCreateProtectionDescriptor(rule.as_ptr(), 0, &mut handle);
+            if status != 0 {
+                return Err(format!("DPAPI-NG descriptor {descriptor:?} failed: {status:#x}"));
+            }
+            let mut blob = ptr::null_mut();
+            let mut blob_len = 0u32;
+            let status = NCryptProtectSecret(
+                handle,
+                NCRYPT_SILENT_FLAG,
+                data.as_ptr(),
+                data.len() as u32,
+                ptr::null(),
+                ptr::null_mut(),
+                &mut blob,
+                &mut blob_len,
+            );
+            NCryptCloseProtectionDescriptor(handle);
+            if status != 0 {
+                return Err(format!("DPAPI-NG protect failed: {status:#x}"));
+            }
+            Ok(take(blob, blob_len))
+        }
+    }
+
+    pub fn unprotect(blob: &[u8]) -> Result<Vec<u8>, String> {
+        unsafe {
+            let mut data = ptr::null_mut();
+            let mut data_len = 0u32;
+            let status = NCryptUnprotectSecret(
+                ptr::null_mut(),
+                NCRYPT_SILENT_FLAG,
+                blob.as_ptr(),
+                blob.len() as u32,
+                ptr::null(),
+                ptr::null_mut(),
+                &mut data,
+                &mut data_len,
+            );
+            if status != 0 {
+                return Err(format!("DPAPI-NG unprotect failed: {status:#x}"));
+            }
+            Ok(take(data, data_len))
+        }
+    }
+}
+
+/// Seal `data` with the strongest descriptor this machine accepts. `None` where DPAPI-NG does
+/// not exist.
+#[cfg(windows)]
+pub fn protect(data: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let mut last_error = String::new();
+    for descriptor in DESCRIPTORS {
+        match ffi::protect(descriptor, data) {
+            Ok(blob) => return Ok(Some(blob)),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(not(windows))]
+pub fn protect(_data: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+#[cfg(windows)]
+pub fn unprotect(blob: &[u8]) -> Result<Vec<u8>, String> {
+    ffi::unprotect(blob)
+}
+
+#[cfg(not(windows))]
+pub fn unprotect(_blob: &[u8]) -> Result<Vec<u8>, String> {
+    Err("DPAPI-NG is only available on Windows".to_string())
+}
+
+/// The descriptor new secrets are sealed with on this machine, if any.
+#[cfg(windows)]
+pub fn active_descriptor() -> Option<&'static str> {
+    DESCRIPTORS
+        .into_iter()
+        .find(|descriptor| ffi::protect(descriptor, b"probe").is_ok())
+}
+
+#[cfg(not(windows))]
+pub fn active_descriptor() -> Option<&'static str> {
+    None
+}
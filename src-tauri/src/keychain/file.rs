@@ -0,0 +1,356 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! File-based keychain for systems without a usable OS keychain (e.g. Linux without a Secret
+//! Service). Entries live in `~/.vaultpad/keychain.dat`, encrypted under a key derived from
+//! this machine's id and the user name.
+//!
+//! That machine key is obfuscation, not protection: the machine id and user name are readable
+//! by any process of the user, and by anyone holding a copy of the disk, so whoever can read
+//! the file can derive the key too. It only keeps the file from being readable as is, and from
+//! opening on another machine. Entries that need real protection belong under a PIN.
+//!
+//! With PIN protection only the entries needed before unlock stay under the machine key; the
+//! rest is encrypted under a random file key that is stored wrapped under the quick-unlock PIN
+//! and under the vault key, so it opens with the PIN or with any other way of unlocking.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::crypto::{self, wrap, KdfParams, KEY_LEN};
+
+const FILE_NAME: &str = "keychain.dat";
+const FORMAT_VERSION: u32 = 1;
+const MACHINE_KEY_INFO: &[u8] = b"vaultpad/keychain-file";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protection {
+    Machine,
+    Pin,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    protection: Protection,
+    /// HKDF salt for the machine key.
+    salt: String,
+    /// Entries readable before unlock, under the machine key.
+    public: String,
+    /// PIN protection only: the remaining entries under the file key.
+    #[serde(default)]
+    private: Option<String>,
+    #[serde(default)]
+    pin_slot: Option<wrap::KeySlot>,
+    /// The file key wrapped under the vault key.
+    #[serde(default)]
+    vault_slot: Option<String>,
+}
+
+/// File key of a PIN-protected keychain, once unwrapped.
+static FILE_KEY: Mutex<Option<[u8; KEY_LEN]>> = Mutex::new(None);
+
+pub fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".vaultpad").join(FILE_NAME))
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Result<String, String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|p| std::fs::read_to_string(p).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| "Cannot determine machine id".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Result<String, String> {
+    let output = std::process::Command::new("/usr/sbin/ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .map_err(|e| format!("Cannot determine machine id: {e}"))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))
+        .and_then(|line| line.split('"').nth(3))
+        .map(str::to_string)
+        .ok_or_else(|| "Cannot determine machine id".to_string())
+}
+
+#[cfg(windows)]
+fn machine_id() -> Result<String, String> {
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .map_err(|e| format!("Cannot determine machine id: {e}"))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("MachineGuid"))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+        .ok_or_else(|| "Cannot determine machine id".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn machine_id() -> Result<String, String> {
+    Err("Machine-bound keys are not supported on this platform".to_string())
+}
+
+fn machine_key(salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let ikm = format!("{}\0{}", machine_id()?, user);
+    let mut key = [0u8; KEY_LEN];
+    Hkdf::<Sha256>::new(Some(salt), ikm.as_bytes())
+        .expand(MACHINE_KEY_INFO, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn seal(entries: &HashMap<String, String>, key: &[u8; KEY_LEN]) -> Result<String, String> {
+    let mut json = serde_json::to_vec(entries).map_err(|e| e.to_string())?;
+    let sealed = crypto::encrypt_with_key(&json, key);
+    json.zeroize();
+    Ok(B64.encode(sealed.map_err(|e| e.to_string())?))
+}
+
+fn open(sealed: &str, key: &[u8; KEY_LEN]) -> Result<HashMap<String, String>, String> {
+    let data = B64.decode(sealed).map_err(|e| e.to_string())?;
+    let mut json = crypto::try_decrypt_with_key(&data, key)
+        .ok_or("Keychain file was written on another machine or is damaged")?;
+    let entries = serde_json::from_slice(&json).map_err(|e| e.to_string());
+    json.zeroize();
+    entries
+}
+
+fn read_envelope() -> Result<Option<Envelope>, String> {
+    let path = path().ok_or("Cannot determine home directory")?;
+    match std::fs::read(&path) {
+        Ok(data) => serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| format!("Invalid keychain file: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Cannot read keychain file: {e}")),
+    }
+}
+
+fn write_envelope(envelope: &Envelope) -> Result<(), String> {
+    let path = path().ok_or("Cannot determine home directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Cannot create folder: {e}"))?;
+    }
+    let json = serde_json::to_vec_pretty(envelope).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("tmp");
+    // Created owner-only rather than narrowed afterwards, so the entries are never readable by
+    // others. A leftover temporary file would keep its own permissions, so it goes first.
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&tmp)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, &json))
+        .map_err(|e| format!("Cannot write keychain file: {e}"))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Cannot write keychain file: {e}"))
+}
+
+fn file_key() -> Option<[u8; KEY_LEN]> {
+    *FILE_KEY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_file_key(key: Option<[u8; KEY_LEN]>) {
+    let mut guard = FILE_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(ref mut old) = *guard {
+        old.zeroize();
+    }
+    *guard = key;
+}
+
+/// Protection of the keychain file, or `None` when there is no file.
+pub fn protection() -> Option<Protection> {
+    read_envelope().ok().flatten().map(|e| e.protection)
+}
+
+/// Whether the PIN-protected part is still closed.
+pub fn is_locked() -> bool {
+    protection() == Some(Protection::Pin) && file_key().is_none()
+}
+
+/// Every readable entry: the public ones, plus the private ones once unlocked.
+pub fn load() -> Result<HashMap<String, String>, String> {
+    let envelope = match read_envelope()? {
+        Some(envelope) => envelope,
+        None => return Ok(HashMap::new()),
+    };
+    let salt = B64.decode(&envelope.salt).map_err(|e| e.to_string())?;
+    let mut machine = machine_key(&salt)?;
+    let entries = open(&envelope.public, &machine);
+    machine.zeroize();
+    let mut entries = entries?;
+
+    if let (Some(private), Some(mut key)) = (envelope.private.as_deref(), file_key()) {
+        let opened = open(private, &key);
+        key.zeroize();
+        entries.extend(opened?);
+    }
+    Ok(entries)
+}
+
+/// Rewrite the file with `entries`. While a PIN-protected file is locked, its private part is
+/// kept as it is; `entries` then only holds public ones.
+pub fn store(
+    entries: &HashMap<String, String>,
+    is_public: impl Fn(&str) -> bool,
+) -> Result<(), String> {
+    let mut envelope = match read_envelope()? {
+        Some(envelope) => envelope,
+        None => return create(entries, Protection::Machine, None, None, is_public),
+    };
+    let salt = B64.decode(&envelope.salt).map_err(|e| e.to_string())?;
+
+    let (public, private): (HashMap<_, _>, HashMap<_, _>) = match envelope.protection {
+        Protection::Machine => (entries.clone(), HashMap::new()),
+        Protection::Pin => entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .partition(|(k, _)| is_public(k)),
+    };
+
+    let mut machine = machine_key(&salt)?;
+    let sealed = seal(&public, &machine);
+    machine.zeroize();
+    envelope.public = sealed?;
+
+    if envelope.protection == Protection::Pin {
+        if let Some(mut key) = file_key() {
+            let sealed = seal(&private, &key);
+            key.zeroize();
+            envelope.private = Some(sealed?);
+        }
+    }
+    write_envelope(&envelope)
+}
+
+/// Write a new keychain file. PIN protection needs the quick-unlock secret and the vault key
+/// to wrap the fresh file key under.
+pub fn create(
+    entries: &HashMap<String, String>,
+    protection: Protection,
+    pin: Option<&str>,
+    vault_key: Option<&[u8; KEY_LEN]>,
+    is_public: impl Fn(&str) -> bool,
+) -> Result<(), String> {
+    let mut salt = [0u8; 32];
+    crypto::rng::fill(&mut salt);
+    let mut envelope = Envelope {
+        version: FORMAT_VERSION,
+        protection,
+        salt: B64.encode(salt),
+        public: String::new(),
+        private: None,
+        pin_slot: None,
+        vault_slot: None,
+    };
+
+    if protection == Protection::Pin {
+        let pin = pin.ok_or("A PIN is required")?;
+        let vault_key = vault_key.ok_or("No cached key. Please unlock first.")?;
+        let mut key = wrap::generate_key();
+        let slots = wrap::wrap_with_secret(&key, pin, &KdfParams::default()).and_then(|pin_slot| {
+            wrap::wrap_key(&key, vault_key).map(|wrapped| (pin_slot, B64.encode(wrapped)))
+        });
+        set_file_key(Some(key));
+        key.zeroize();
+        let (pin_slot, vault_slot) = slots.map_err(|e| e.to_string())?;
+        envelope.pin_slot = Some(pin_slot);
+        envelope.vault_slot = Some(vault_slot);
+    } else {
+        set_file_key(None);
+    }
+
+    // Start from an empty public part so `store` can split the entries.
+    let mut machine = machine_key(&salt)?;
+    let sealed = seal(&HashMap::new(), &machine);
+    machine.zeroize();
+    envelope.public = sealed?;
+    write_envelope(&envelope)?;
+    store(entries, is_public)
+}
+
+fn unlock_with(unwrap: impl FnOnce(&Envelope) -> Option<[u8; KEY_LEN]>) -> Result<bool, String> {
+    let envelope = match read_envelope()? {
+        Some(envelope) if envelope.protection == Protection::Pin => envelope,
+        _ => return Ok(false),
+    };
+    match unwrap(&envelope) {
+        Some(mut key) => {
+            set_file_key(Some(key));
+            key.zeroize();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Open the private part with the quick-unlock secret.
+pub fn unlock_with_pin(pin: &str) -> Result<bool, String> {
+    unlock_with(|envelope| {
+        envelope
+            .pin_slot
+            .as_ref()
+            .and_then(|slot| wrap::unwrap_with_secret(slot, pin).ok())
+    })
+}
+
+/// Open the private part with the vault key.
+pub fn unlock_with_vault_key(vault_key: &[u8; KEY_LEN]) -> Result<bool, String> {
+    unlock_with(|envelope| {
+        envelope
+            .vault_slot
+            .as_deref()
+            .and_then(|wrapped| B64.decode(wrapped).ok())
+            .and_then(|wrapped| wrap::unwrap_key(&wrapped, vault_key).ok())
+    })
+}
+
+/// Re-wrap the file key under a new quick-unlock secret.
+pub fn rekey_pin(pin: &str) -> Result<(), String> {
+    let mut envelope = match read_envelope()? {
+        Some(envelope) if envelope.protection == Protection::Pin => envelope,
+        _ => return Ok(()),
+    };
+    let mut key = file_key().ok_or("Keychain is locked")?;
+    let slot = wrap::wrap_with_secret(&key, pin, &KdfParams::default());
+    key.zeroize();
+    envelope.pin_slot = Some(slot.map_err(|e| e.to_string())?);
+    write_envelope(&envelope)
+}
+
+pub fn delete() -> Result<(), String> {
+    set_file_key(None);
+    let path = path().ok_or("Cannot determine home directory")?;
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Cannot delete keychain file: {e}")),
+    }
+}
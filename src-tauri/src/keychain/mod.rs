@@ -0,0 +1,344 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+pub mod biometric;
+pub mod dpapi;
+pub mod file;
+pub mod secret_service;
+
+use base64::Engine;
+use keyring::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::crypto;
+
+#[cfg(debug_assertions)]
+const SERVICE: &str = "vaultpad-dev";
+#[cfg(not(debug_assertions))]
+const SERVICE: &str = "vaultpad";
+
+#[cfg(debug_assertions)]
+const ACCOUNT: &str = "vaultpad-dev-data";
+#[cfg(not(debug_assertions))]
+const ACCOUNT: &str = "vaultpad-data";
+
+/// Entries needed before the vault is unlocked. A PIN-protected keychain file keeps them
+/// under the machine key only; everything else waits for the PIN.
+const STARTUP_KEYS: [&str; 6] = [
+    "db-path",
+    "db-folder",
+    "pin-slot",
+    "quick-unlock-kind",
+    "pin-hash",
+    "device-id",
+];
+
+/// Where entries are kept: the OS keychain, or the keychain file protected by a machine-bound
+/// key or by the PIN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Os,
+    FileMachine,
+    FilePin,
+}
+
+impl Backend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Backend::Os => "os",
+            Backend::FileMachine => "file-machine",
+            Backend::FilePin => "file-pin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "os" => Some(Backend::Os),
+            "file-machine" => Some(Backend::FileMachine),
+            "file-pin" => Some(Backend::FilePin),
+            _ => None,
+        }
+    }
+
+    /// A keychain file, if present, takes precedence over the OS keychain.
+    fn detect() -> Self {
+        match file::protection() {
+            Some(file::Protection::Machine) => Backend::FileMachine,
+            Some(file::Protection::Pin) => Backend::FilePin,
+            None => Backend::Os,
+        }
+    }
+}
+
+struct Store {
+    backend: Backend,
+    data: HashMap<String, String>,
+}
+
+static CACHE: Mutex<Option<Store>> = Mutex::new(None);
+
+/// The keychain item, in the chosen Secret Service collection if there is one.
+fn os_entry(collection: Option<&str>) -> Result<Entry, keyring::Error> {
+    match collection {
+        Some(target) => Entry::new_with_target(target, SERVICE, ACCOUNT),
+        None => Entry::new(SERVICE, ACCOUNT),
+    }
+}
+
+fn entry() -> Result<Entry, String> {
+    os_entry(secret_service::selected_collection().as_deref())
+        .map_err(|e| format!("Keychain error: {e}"))
+}
+
+fn is_startup_key(key: &str) -> bool {
+    STARTUP_KEYS.contains(&key)
+}
+
+fn load_from_os() -> HashMap<String, String> {
+    entry()
+        .ok()
+        .and_then(|e| e.get_password().ok())
+        .and_then(|json| serde_json::from_str::<HashMap<String, String>>(&json).ok())
+        .unwrap_or_default()
+}
+
+fn ensure_loaded(cache: &mut Option<Store>) -> &mut Store {
+    cache.get_or_insert_with(|| {
+        let backend = Backend::detect();
+        let data = match backend {
+            Backend::Os => load_from_os(),
+            Backend::FileMachine | Backend::FilePin => file::load().unwrap_or_default(),
+        };
+        Store { backend, data }
+    })
+}
+
+fn write_to_os(data: &HashMap<String, String>) -> Result<(), keyring::Error> {
+    let entry = os_entry(secret_service::selected_collection().as_deref())?;
+    if data.is_empty() {
+        let _ = entry.delete_credential();
+        return Ok(());
+    }
+    let json = serde_json::to_string(data).unwrap_or_default();
+    entry.set_password(&json)
+}
+
+/// Persist `store.data`. When the OS keychain cannot be used at all, the entries move to a
+/// machine-bound keychain file instead; a keyring that is only locked is reported rather than
+/// abandoned.
+fn write(store: &mut Store) -> Result<(), String> {
+    match store.backend {
+        Backend::Os => match write_to_os(&store.data) {
+            Ok(()) => Ok(()),
+            Err(keyring::Error::NoStorageAccess(_)) | Err(keyring::Error::PlatformFailure(_)) => {
+                if secret_service::status().state == secret_service::KeyringState::Locked {
+                    return Err(secret_service::LOCKED_ERROR.to_string());
+                }
+                file::create(
+                    &store.data,
+                    file::Protection::Machine,
+                    None,
+                    None,
+                    is_startup_key,
+                )?;
+                store.backend = Backend::FileMachine;
+                Ok(())
+            }
+            Err(e) => Err(format!("Keychain save error: {e}")),
+        },
+        Backend::FileMachine | Backend::FilePin => file::store(&store.data, is_startup_key),
+    }
+}
+
+pub fn save(key: &str, value: &str) -> Result<(), String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let store = ensure_loaded(&mut guard);
+    if store.backend == Backend::FilePin && !is_startup_key(key) && file::is_locked() {
+        return Err("Keychain is locked; unlock the vault first".to_string());
+    }
+    let old = store.data.insert(key.to_string(), value.to_string());
+    if let Err(e) = write(store) {
+        match old {
+            Some(v) => { store.data.insert(key.to_string(), v); }
+            None => { store.data.remove(key); }
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Names of every stored entry, sorted. Values are not returned.
+pub fn keys() -> Vec<String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut keys: Vec<String> = ensure_loaded(&mut guard).data.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+pub fn get(key: &str) -> Option<String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    ensure_loaded(&mut guard).data.get(key).cloned()
+}
+
+/// Save a value that unlocks the vault. Where DPAPI-NG exists it is sealed to this user and
+/// machine first, so the stored entry cannot be opened from a copy of the keychain.
+pub fn save_secret(key: &str, value: &str) -> Result<(), String> {
+    match dpapi::protect(value.as_bytes())? {
+        Some(blob) => save(
+            key,
+            &format!(
+                "{}{}",
+                dpapi::PREFIX,
+                base64::engine::general_purpose::STANDARD.encode(blob)
+            ),
+        ),
+        None => save(key, value),
+    }
+}
+
+/// Read a value saved with `save_secret`. Entries written before sealing are returned as
+/// they are; a sealed entry that no longer opens reads as missing.
+pub fn get_secret(key: &str) -> Option<String> {
+    let stored = get(key)?;
+    let Some(encoded) = stored.strip_prefix(dpapi::PREFIX) else {
+        return Some(stored);
+    };
+    let blob = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+    dpapi::unprotect(&blob).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// Whether the entry under `key` is sealed with DPAPI-NG.
+pub fn is_sealed(key: &str) -> bool {
+    get(key).is_some_and(|v| v.starts_with(dpapi::PREFIX))
+}
+
+pub fn remove(key: &str) {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let store = ensure_loaded(&mut guard);
+    if let Some(old_val) = store.data.remove(key) {
+        if write(store).is_err() {
+            store.data.insert(key.to_string(), old_val);
+        }
+    }
+}
+
+/// Drop every stored entry, including the keychain item and the keychain file.
+pub fn clear() -> Result<(), String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(e) = entry() {
+        match e.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(format!("Keychain delete error: {e}")),
+        }
+    }
+    file::delete()?;
+    *guard = None;
+    Ok(())
+}
+
+pub fn backend() -> Backend {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    ensure_loaded(&mut guard).backend
+}
+
+/// Whether a PIN-protected keychain file still hides its private entries.
+pub fn is_locked() -> bool {
+    backend() == Backend::FilePin && file::is_locked()
+}
+
+fn reload_after_unlock(unlocked: Result<bool, String>) -> Result<bool, String> {
+    if unlocked? {
+        let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = None;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Open a PIN-protected keychain file with the quick-unlock secret. `false` when the file is
+/// not PIN-protected or the secret does not fit.
+pub fn unlock_with_pin(pin: &str) -> Result<bool, String> {
+    if backend() != Backend::FilePin {
+        return Ok(false);
+    }
+    reload_after_unlock(file::unlock_with_pin(pin))
+}
+
+/// Open a PIN-protected keychain file with the vault key, after any other way of unlocking.
+pub fn unlock_with_vault_key(key: &[u8; crypto::KEY_LEN]) -> Result<bool, String> {
+    if backend() != Backend::FilePin || !file::is_locked() {
+        return Ok(false);
+    }
+    reload_after_unlock(file::unlock_with_vault_key(key))
+}
+
+/// Keep a PIN-protected keychain file in step with a new quick-unlock secret.
+pub fn set_pin(pin: &str) -> Result<(), String> {
+    if backend() != Backend::FilePin {
+        return Ok(());
+    }
+    file::rekey_pin(pin)
+}
+
+/// Keep OS keychain entries in another Secret Service collection (`None` for the default
+/// one), moving the current item there.
+pub fn set_collection(label: Option<&str>) -> Result<(), String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let store = ensure_loaded(&mut guard);
+    let old = secret_service::selected_collection();
+    let old_entry = entry()?;
+    secret_service::select_collection(label)?;
+    if store.backend != Backend::Os || secret_service::selected_collection() == old {
+        return Ok(());
+    }
+    if let Err(e) = write_to_os(&store.data) {
+        let locked = secret_service::status().state == secret_service::KeyringState::Locked;
+        let _ = secret_service::select_collection(old.as_deref());
+        if locked {
+            return Err(secret_service::LOCKED_ERROR.to_string());
+        }
+        return Err(format!("Keychain save error: {e}"));
+    }
+    let _ = old_entry.delete_credential();
+    Ok(())
+}
+
+/// Move every entry to `target`, then drop the old copy. Moving to `Backend::FilePin` needs
+/// the quick-unlock secret and the vault key.
+pub fn migrate(
+    target: Backend,
+    pin: Option<&str>,
+    vault_key: Option<&[u8; crypto::KEY_LEN]>,
+) -> Result<(), String> {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let store = ensure_loaded(&mut guard);
+    if store.backend == target {
+        return Ok(());
+    }
+    if store.backend == Backend::FilePin && file::is_locked() {
+        return Err("Keychain is locked; unlock the vault first".to_string());
+    }
+
+    match target {
+        Backend::Os => {
+            write_to_os(&store.data).map_err(|e| format!("Keychain save error: {e}"))?;
+            file::delete()?;
+        }
+        Backend::FileMachine | Backend::FilePin => {
+            let protection = if target == Backend::FilePin {
+                file::Protection::Pin
+            } else {
+                file::Protection::Machine
+            };
+            file::create(&store.data, protection, pin, vault_key, is_startup_key)?;
+            if store.backend == Backend::Os {
+                if let Ok(e) = entry() {
+                    let _ = e.delete_credential();
+                }
+            }
+        }
+    }
+    store.backend = target;
+    Ok(())
+}
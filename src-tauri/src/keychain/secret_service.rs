@@ -0,0 +1,172 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Secret Service (Linux) state and collection choice. A locked keyring makes every save fail
+//! the same way a missing one does, so before falling back to the keychain file the OS backend
+//! asks here whether the user only has to unlock it. Entries go to the default collection
+//! unless another one is chosen; the choice lives in `~/.vaultpad/keyring-collection` because
+//! it is needed before the keychain itself can be read.
+
+use serde::Serialize;
+
+/// Error returned by keychain writes while the chosen collection is locked.
+pub const LOCKED_ERROR: &str = "keyring_locked";
+
+#[cfg(target_os = "linux")]
+const COLLECTION_FILE: &str = "keyring-collection";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyringState {
+    Unlocked,
+    Locked,
+    /// The Secret Service runs but has no such collection; it is created on the next save.
+    Missing,
+    /// No D-Bus session or no Secret Service provider.
+    Unavailable,
+    /// Not a Secret Service platform.
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyringStatus {
+    pub state: KeyringState,
+    /// Label of the collection entries are kept in, `None` for the default collection.
+    pub collection: Option<String>,
+    /// Labels of the collections the Secret Service offers.
+    pub collections: Vec<String>,
+    /// What the user can do about it, when there is something to do.
+    pub hint: Option<String>,
+}
+
+impl KeyringStatus {
+    fn new(state: KeyringState, collection: Option<String>, collections: Vec<String>) -> Self {
+        let hint = match state {
+            KeyringState::Locked => Some(
+                "The keyring is locked. Unlock it (for example in Passwords and Keys, or by signing in again) so saved sessions can be stored.".to_string(),
+            ),
+            KeyringState::Missing if collection.is_none() => Some(
+                "There is no default keyring. Create one in your keyring manager, or choose another collection.".to_string(),
+            ),
+            KeyringState::Unavailable => Some(
+                "No Secret Service is running (gnome-keyring or KWallet). Saved sessions are kept in the keychain file instead.".to_string(),
+            ),
+            _ => None,
+        };
+        KeyringStatus {
+            state,
+            collection,
+            collections,
+            hint,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use dbus_secret_service::{Collection, EncryptionType, Error, SecretService};
+
+    use super::*;
+
+    fn collection_file() -> Option<std::path::PathBuf> {
+        dirs::home_dir().map(|home| home.join(".vaultpad").join(COLLECTION_FILE))
+    }
+
+    pub fn selected_collection() -> Option<String> {
+        let label = std::fs::read_to_string(collection_file()?).ok()?;
+        let label = label.trim();
+        (!label.is_empty()).then(|| label.to_string())
+    }
+
+    pub fn select_collection(label: Option<&str>) -> Result<(), String> {
+        let path = collection_file().ok_or("Cannot determine home directory")?;
+        match label.map(str::trim).filter(|l| !l.is_empty()) {
+            Some(label) => {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&path, label).map_err(|e| e.to_string())
+            }
+            None => match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn find<'a>(ss: &'a SecretService, label: Option<&str>) -> Result<Option<Collection<'a>>, Error> {
+        let Some(label) = label else {
+            return match ss.get_default_collection() {
+                Ok(c) => Ok(Some(c)),
+                Err(Error::NoResult) => Ok(None),
+                Err(e) => Err(e),
+            };
+        };
+        for collection in ss.get_all_collections()? {
+            if collection.get_label()? == label {
+                return Ok(Some(collection));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn status() -> KeyringStatus {
+        let selected = selected_collection();
+        let Ok(ss) = SecretService::connect(EncryptionType::Plain) else {
+            return KeyringStatus::new(KeyringState::Unavailable, selected, Vec::new());
+        };
+        let collections = ss
+            .get_all_collections()
+            .map(|all| all.iter().filter_map(|c| c.get_label().ok()).collect())
+            .unwrap_or_default();
+        let state = match find(&ss, selected.as_deref()) {
+            Ok(Some(c)) => match c.is_locked() {
+                Ok(true) => KeyringState::Locked,
+                Ok(false) => KeyringState::Unlocked,
+                Err(_) => KeyringState::Unavailable,
+            },
+            Ok(None) => KeyringState::Missing,
+            Err(_) => KeyringState::Unavailable,
+        };
+        KeyringStatus::new(state, selected, collections)
+    }
+
+    pub fn unlock() -> Result<(), String> {
+        let ss = SecretService::connect(EncryptionType::Plain).map_err(|e| e.to_string())?;
+        let collection = find(&ss, selected_collection().as_deref())
+            .map_err(|e| e.to_string())?
+            .ok_or("Keyring collection not found")?;
+        collection.unlock().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::*;
+
+    pub fn selected_collection() -> Option<String> {
+        None
+    }
+
+    pub fn select_collection(_label: Option<&str>) -> Result<(), String> {
+        Err("Keyring collections are only available on Linux".to_string())
+    }
+
+    pub fn status() -> KeyringStatus {
+        KeyringStatus::new(KeyringState::Unsupported, None, Vec::new())
+    }
+
+    pub fn unlock() -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// The collection chosen for keychain entries, `None` for the default one.
+pub use imp::selected_collection;
+/// Remember the collection for keychain entries. `keychain::set_collection` also moves them.
+pub(super) use imp::select_collection;
+/// Current state of the chosen collection.
+pub use imp::status;
+/// Ask the Secret Service to unlock the chosen collection; it shows its own password prompt.
+pub use imp::unlock;
@@ -1,16 +1,70 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+mod appearance;
+mod attachments;
+mod audit;
 mod backup;
+mod backup_archive;
+mod backup_drill;
+mod backup_schedule;
+mod breach;
+mod change_feed;
+mod checklist;
+mod clock;
 mod commands;
+mod compliance;
+mod cooling_off;
+mod credential;
 mod crypto;
+mod custom_fields;
+mod demo;
+mod device;
+mod display_info;
+mod drafts;
+mod emergency;
+mod ephemeral;
+mod folders;
+mod importers;
+mod insights;
 mod keychain;
+mod links;
+mod merge;
 mod models;
+mod ordering;
+mod passphrase;
+mod password_history;
 pub mod password_registry;
+mod pin_attempts;
+mod pipeline;
+mod plain_export;
+mod quick_unlock;
+mod recovery;
+mod rehash;
+mod relocate;
+mod search;
+mod search_index;
+mod secure_prompt;
+mod security_report;
 pub mod server_config;
+mod server_features;
+mod session_bundle;
+mod settings_sync;
+mod startup;
 mod storage;
+mod strength;
+mod system_idle;
+mod tags;
+mod templates;
+mod totp;
+mod trash;
+mod vault;
+mod vault_watch;
+mod vaults;
+mod versions;
+mod wipe;
 
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use storage::StorageProvider;
 use storage::local::LocalStorage;
 use tauri::Emitter;
@@ -47,9 +101,11 @@ pub struct AppState {
     pub db_path: Mutex<Option<String>>,
     pub server_token: Mutex<Option<String>>,
     pub server_url: Mutex<Option<String>>,
-    pub cached_key: Mutex<Option<[u8; crypto::KEY_LEN]>>,
-    pub master_password: Mutex<Option<String>>,
+    pub cached_key: Mutex<crypto::locked::LockedKey>,
     pub active_context: Mutex<String>,
+    pub clock: Arc<dyn clock::Clock>,
+    /// Vaults open in the background while another one is active.
+    pub vaults: Mutex<vaults::VaultRegistry>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -126,6 +182,12 @@ pub fn run() {
                     };
                 }
             }
+            backup_drill::spawn(handle.clone());
+            backup_schedule::spawn(handle.clone());
+            change_feed::spawn(handle.clone());
+            trash::spawn(handle.clone());
+            system_idle::spawn(handle.clone());
+            vault_watch::spawn(handle.clone());
 
             Ok(())
         })
@@ -137,15 +199,30 @@ pub fn run() {
             db_path: Mutex::new(None),
             server_token: Mutex::new(None),
             server_url: Mutex::new(None),
-            cached_key: Mutex::new(None),
-            master_password: Mutex::new(None),
+            cached_key: Mutex::new(crypto::locked::LockedKey::new()),
             active_context: Mutex::new("local".to_string()),
+            clock: Arc::new(clock::SystemClock),
+            vaults: Mutex::new(vaults::VaultRegistry::default()),
         })
         .invoke_handler(tauri::generate_handler![
             commands::settings::init_database,
+            commands::vaults::list_known_vaults,
+            commands::vaults::switch_vault,
+            commands::vaults::close_vault,
+            commands::settings::inspect_database,
+            commands::settings::locate_vault_candidates,
+            commands::settings::open_backup_instead,
+            commands::settings::set_database_encryption,
+            commands::settings::compact_database,
+            commands::settings::open_database_read_only,
+            commands::settings::is_database_read_only,
             commands::settings::has_master_password,
             commands::settings::set_master_password,
             commands::settings::verify_master_password,
+            commands::settings::is_secure_prompt_available,
+            commands::settings::unlock_with_secure_prompt,
+            commands::settings::is_touch_id_available,
+            commands::settings::set_touch_id_unlock,
             commands::settings::get_db_path,
             commands::settings::get_setting,
             commands::settings::set_setting,
@@ -153,11 +230,39 @@ pub fn run() {
             commands::settings::cache_master_key,
             commands::settings::clear_cached_key,
             commands::projects::list_projects,
+            commands::projects::stream_projects,
+            commands::projects::probe_project,
             commands::projects::get_project,
             commands::projects::create_project,
+            commands::projects::bulk_create_projects,
+            commands::projects::list_recent_projects,
             commands::projects::update_project,
+            commands::projects::create_credential,
+            commands::projects::update_credential,
+            commands::projects::duplicate_project,
+            commands::projects::get_backlinks,
+            commands::projects::search_projects,
             commands::projects::delete_project,
+            commands::projects::archive_project,
+            commands::projects::unarchive_project,
+            commands::projects::toggle_pin,
+            commands::projects::list_trashed_projects,
+            commands::projects::restore_project,
+            commands::projects::purge_project,
+            commands::projects::get_trash_retention_days,
+            commands::projects::set_trash_retention_days,
             commands::projects::reorder_projects,
+            commands::folders::list_folders,
+            commands::folders::create_folder,
+            commands::folders::rename_folder,
+            commands::folders::move_folder,
+            commands::folders::delete_folder,
+            commands::projects::list_tags,
+            commands::projects::set_project_tags,
+            commands::projects::set_project_appearance,
+            commands::projects::set_cooling_off,
+            commands::projects::get_cooling_off_status,
+            commands::projects::request_access,
             commands::projects::get_project_password,
             commands::projects::import_password_registry,
             commands::projects::get_password_registry,
@@ -169,11 +274,15 @@ pub fn run() {
             commands::sync::check_remote_changes,
             commands::sync::sync_pull_changed,
             commands::sync::resolve_conflict,
+            commands::sync::get_merge_preview,
+            commands::sync::get_settings_sync,
+            commands::sync::set_settings_sync,
             commands::servers::list_servers,
             commands::servers::add_server,
             commands::servers::remove_server,
             commands::servers::switch_context,
             commands::servers::get_active_context,
+            commands::servers::server_status,
             commands::servers::srv_auth,
             commands::servers::refresh_server_user,
             commands::servers::is_server_authenticated,
@@ -189,26 +298,159 @@ pub fn run() {
             commands::servers::unshare_project,
             commands::settings::setup_pin,
             commands::settings::verify_pin,
+            commands::settings::get_pin_attempt_status,
+            commands::settings::set_pin_attempt_policy,
             commands::settings::has_saved_session,
             commands::settings::has_pin,
+            commands::settings::get_keychain_backend,
+            commands::settings::get_platform_protection_info,
+            commands::settings::get_keyring_status,
+            commands::settings::unlock_keyring,
+            commands::settings::set_keyring_collection,
+            commands::settings::health_check,
+            commands::settings::migrate_keychain_backend,
+            commands::keychain::list_keychain_items,
+            commands::keychain::remove_keychain_item,
             commands::settings::get_saved_db_path,
-            commands::settings::get_saved_master_password,
+            commands::settings::restore_saved_session,
             commands::settings::clear_saved_session,
+            commands::settings::get_vault_display_info,
+            commands::settings::set_vault_display_info,
             commands::settings::change_pin,
             commands::settings::remove_pin,
             commands::settings::init_new_database,
             commands::settings::get_db_folder,
             commands::settings::change_db_folder,
+            commands::settings::move_database,
             commands::settings::change_master_password,
             commands::settings::get_default_db_folder,
             commands::settings::init_default_database,
             commands::settings::open_local_database,
+            commands::settings::create_vault,
+            commands::settings::init_demo_vault,
+            commands::settings::destroy_vault,
+            commands::settings::request_panic_wipe_token,
+            commands::settings::panic_wipe,
             commands::servers::change_server_master_password,
             commands::servers::srv_update_profile,
             commands::backups::list_project_backups,
             commands::backups::get_backup_content,
             commands::backups::restore_backup,
             commands::backups::delete_backup_cmd,
+            commands::versions::list_versions,
+            commands::versions::get_version,
+            commands::versions::diff_versions,
+            commands::versions::restore_version,
+            commands::versions::get_version_retention,
+            commands::versions::set_version_retention,
+            commands::attachments::list_attachments,
+            commands::attachments::add_attachment,
+            commands::attachments::get_attachment,
+            commands::attachments::delete_attachment,
+            commands::custom_fields::list_custom_fields,
+            commands::custom_fields::add_custom_field,
+            commands::custom_fields::update_custom_field,
+            commands::custom_fields::remove_custom_field,
+            commands::totp::set_totp_secret,
+            commands::totp::get_totp_info,
+            commands::totp::get_totp_code,
+            commands::totp::remove_totp_secret,
+            commands::drafts::save_draft,
+            commands::drafts::get_draft,
+            commands::drafts::discard_draft,
+            commands::password_history::list_password_history,
+            commands::password_history::restore_password_from_history,
+            commands::templates::list_templates,
+            commands::templates::create_template,
+            commands::templates::delete_template,
+            commands::templates::create_project_from_template,
+            commands::checklist::list_checklist,
+            commands::checklist::add_checklist_item,
+            commands::checklist::toggle_checklist_item,
+            commands::checklist::remove_checklist_item,
+            commands::checklist::reorder_checklist,
+            commands::backups::export_vault_backup,
+            commands::backups::export_to_removable,
+            commands::backups::preview_backup,
+            commands::backups::restore_vault_backup,
+            commands::backups::verify_backup,
+            commands::backups::get_backup_drill,
+            commands::backups::set_backup_drill,
+            commands::backups::configure_backups,
+            commands::backups::set_backup_passphrase,
+            commands::backups::remove_backup_passphrase,
+            commands::backups::get_backup_passphrase_created_at,
+            commands::backups::get_backup_schedule,
+            commands::backups::run_backup_now,
+            commands::backups::list_backups,
+            commands::breach::get_breach_check_enabled,
+            commands::breach::set_breach_check_enabled,
+            commands::breach::check_password_breach,
+            commands::passwords::estimate_password_strength,
+            commands::passwords::generate_passphrase,
+            commands::changes::get_changes_since,
+            commands::export::export_compliance_archive,
+            commands::export::export_project,
+            commands::export::verify_compliance_archive,
+            commands::devices::get_device_id,
+            commands::devices::list_devices,
+            commands::devices::request_device_wipe,
+            commands::emergency::get_emergency_public_key,
+            commands::emergency::add_emergency_contact,
+            commands::emergency::list_emergency_contacts,
+            commands::emergency::deny_emergency_request,
+            commands::emergency::remove_emergency_contact,
+            commands::emergency::list_incoming_emergency_access,
+            commands::emergency::request_emergency_access,
+            commands::emergency::claim_emergency_access,
+            commands::kdf::crypto_self_test,
+            commands::kdf::get_kdf_params,
+            commands::kdf::benchmark_kdf,
+            commands::kdf::security_report,
+            commands::kdf::set_kdf_params,
+            commands::session::get_session_snapshot,
+            commands::session::save_session_snapshot,
+            commands::session::put_ephemeral,
+            commands::session::get_ephemeral,
+            commands::session::remove_ephemeral,
+            commands::session::clear_ephemeral,
+            commands::session::get_system_idle_support,
+            commands::session::get_lock_on_screen_lock,
+            commands::session::set_lock_on_screen_lock,
+            commands::settings::setup_quick_unlock,
+            commands::settings::verify_quick_unlock,
+            commands::settings::get_quick_unlock_kind,
+            commands::settings::list_fido_keys,
+            commands::settings::register_fido_key,
+            commands::settings::unlock_with_fido_key,
+            commands::settings::remove_fido_key,
+            commands::settings::get_challenge_response_status,
+            commands::settings::enable_challenge_response,
+            commands::settings::disable_challenge_response,
+            commands::settings::generate_recovery_code,
+            commands::settings::get_recovery_code_created_at,
+            commands::settings::unlock_with_recovery_code,
+            commands::settings::unlock_with_backup,
+            commands::settings::generate_recovery_shares,
+            commands::settings::get_recovery_shares_status,
+            commands::settings::unlock_with_recovery_shares,
+            commands::settings::set_duress_password,
+            commands::settings::remove_duress_password,
+            commands::settings::has_duress_password,
+            commands::settings::list_audit_events,
+            commands::settings::verify_audit_chain,
+            commands::servers::export_session_bundle,
+            commands::servers::import_session_bundle,
+            commands::insights::get_usage_insights,
+            commands::insights::set_usage_insights_enabled,
+            commands::insights::clear_usage_insights,
+            commands::insights::get_storage_metrics,
+            commands::insights::set_slow_log_threshold,
+            commands::insights::record_usage_event,
+            commands::import::list_importers,
+            commands::import::import_file,
+            commands::import::import_browser_passwords,
+            commands::import::import_directory,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
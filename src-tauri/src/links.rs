@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Links between projects. Content refers to another project as `vaultpad://project/<id>`; the
+//! frontend opens such links in place. Nothing is indexed, since the content is encrypted:
+//! backlinks are found by decrypting every project under the vault key and looking for the
+//! link. Projects with their own password cannot be read that way and never show up as
+//! backlinks, nor do projects still in their cooling-off delay (see `cooling_off`).
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use crate::clock::Clock;
+use crate::cooling_off;
+use crate::crypto;
+use crate::rehash;
+use crate::storage::StorageProvider;
+use crate::trash;
+
+const LINK_PREFIX: &str = "vaultpad://project/";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Backlink {
+    pub id: String,
+    pub name: String,
+}
+
+/// Ids of the projects `content` links to.
+pub fn references(content: &str) -> HashSet<&str> {
+    content
+        .match_indices(LINK_PREFIX)
+        .filter_map(|(start, _)| {
+            let rest = &content[start + LINK_PREFIX.len()..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(rest.len());
+            (end > 0).then(|| &rest[..end])
+        })
+        .collect()
+}
+
+/// Projects under the vault key, outside the trash, whose content links to project `id`, in
+/// list order.
+pub fn backlinks(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    id: &str,
+) -> Result<Vec<Backlink>, String> {
+    let trashed = trash::ids(storage)?;
+    let mut found = Vec::new();
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        if project.id == id || trashed.contains(&project.id) {
+            continue;
+        }
+        if cooling_off::check(storage, clock, &project.id).is_err() {
+            continue;
+        }
+        let Ok(content) = rehash::open_project_content(&project, key).map(Zeroizing::new) else {
+            continue;
+        };
+        let Ok(content) = std::str::from_utf8(&content) else {
+            continue;
+        };
+        if references(content).contains(id) {
+            found.push(Backlink { id: project.id, name: project.name });
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_stop_at_the_end_of_the_id() {
+        let a = "6f1c2d3e-0000-4000-8000-000000000001";
+        let b = "6f1c2d3e-0000-4000-8000-000000000002";
+        let content = format!(
+            "See [the router]({LINK_PREFIX}{a}), {LINK_PREFIX}{b}.\nAgain: {LINK_PREFIX}{a}\n\
+             Empty: vaultpad://project/ and vaultpad://folder/x"
+        );
+        let found = references(&content);
+        assert_eq!(found, HashSet::from([a, b]));
+    }
+
+    #[test]
+    fn projects_in_their_cooling_off_delay_are_not_backlinks() {
+        use crate::clock::SystemClock;
+        use crate::commands::projects::master_key_project;
+        use crate::storage::local::LocalStorage;
+
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [6u8; crypto::KEY_LEN];
+        let target = master_key_project(&SystemClock, &key, true, "Router", "admin").unwrap();
+        let content = format!("See {LINK_PREFIX}{}", target.id);
+        let linking = master_key_project(&SystemClock, &key, true, "Safe", &content).unwrap();
+        storage.create_project(&target).unwrap();
+        storage.create_project(&linking).unwrap();
+        assert_eq!(backlinks(&storage, &SystemClock, &key, &target.id).unwrap().len(), 1);
+
+        cooling_off::configure(&storage, &SystemClock, &linking.id, Some(3600)).unwrap();
+        assert!(backlinks(&storage, &SystemClock, &key, &target.id).unwrap().is_empty());
+    }
+}
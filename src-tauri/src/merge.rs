@@ -0,0 +1,320 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Line-based three-way diff for the merge editor. Local and remote are each diffed against
+//! the base (the revision both started from) and the result is cut into hunks: stretches all
+//! three agree on, changes made on one side only, the same change made on both, and real
+//! conflicts. Without a base the common lines of the two sides stand in for it, and every
+//! difference is a conflict since there is no telling which side changed.
+//!
+//! `diff` is the two-way form, for showing what changed between two versions of a project.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MergeHunk {
+    Unchanged { lines: Vec<String> },
+    /// Changed locally only; the local lines win.
+    Local { base: Vec<String>, local: Vec<String> },
+    /// Changed remotely only; the remote lines win.
+    Remote { base: Vec<String>, remote: Vec<String> },
+    /// Both sides made the same change.
+    Both { base: Vec<String>, lines: Vec<String> },
+    Conflict { base: Vec<String>, local: Vec<String>, remote: Vec<String> },
+}
+
+/// A stretch of a two-way diff: lines both texts have, or old lines replaced by new ones. One
+/// of `removed` and `added` may be empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffHunk {
+    Unchanged { lines: Vec<String> },
+    Changed { removed: Vec<String>, added: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NameMerge {
+    pub base: Option<String>,
+    pub local: String,
+    pub remote: String,
+    /// The name to keep when at most one side renamed the project.
+    pub merged: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergePreview {
+    pub base_available: bool,
+    pub name: NameMerge,
+    pub hunks: Vec<MergeHunk>,
+    pub conflicts: usize,
+    /// The merged content when no hunk conflicts.
+    pub merged: Option<String>,
+}
+
+/// Lines of `text`, each keeping its terminator so a merge puts the text back byte for byte.
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn display(lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|l| l.strip_suffix('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).unwrap_or(l).to_string())
+        .collect()
+}
+
+/// For each line of `a`, the line of `b` it is matched with in a longest common subsequence.
+fn matches(a: &[&str], b: &[&str]) -> Vec<Option<usize>> {
+    let mut matched = vec![None; a.len()];
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    for (i, m) in matched.iter_mut().enumerate().take(prefix) {
+        *m = Some(i);
+    }
+    for k in 0..suffix {
+        matched[a.len() - 1 - k] = Some(b.len() - 1 - k);
+    }
+
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    let (n, m) = (a_mid.len(), b_mid.len());
+    // lcs[i][j]: length of the LCS of a_mid[i..] and b_mid[j..].
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if a_mid[i] == b_mid[j] {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a_mid[i] == b_mid[j] {
+            matched[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matched
+}
+
+fn hunks(base: &[&str], local: &[&str], remote: &[&str], has_base: bool) -> Vec<MergeHunk> {
+    let to_local = matches(base, local);
+    let to_remote = matches(base, remote);
+    let mut hunks = Vec::new();
+    let (mut i, mut a, mut b) = (0, 0, 0);
+
+    loop {
+        let stable = (i..base.len())
+            .take_while(|&k| to_local[k] == Some(a + k - i) && to_remote[k] == Some(b + k - i))
+            .count();
+        if stable > 0 {
+            hunks.push(MergeHunk::Unchanged { lines: display(&base[i..i + stable]) });
+            i += stable;
+            a += stable;
+            b += stable;
+            continue;
+        }
+
+        // Up to the next base line both sides kept.
+        let next = (i..base.len()).find_map(|k| Some((k, to_local[k]?, to_remote[k]?)));
+        let (j, a_end, b_end) = next.unwrap_or((base.len(), local.len(), remote.len()));
+        let (o, l, r) = (&base[i..j], &local[a..a_end], &remote[b..b_end]);
+        if !(o.is_empty() && l.is_empty() && r.is_empty()) {
+            hunks.push(if l == r && has_base {
+                MergeHunk::Both { base: display(o), lines: display(l) }
+            } else if l == o && has_base {
+                MergeHunk::Remote { base: display(o), remote: display(r) }
+            } else if r == o && has_base {
+                MergeHunk::Local { base: display(o), local: display(l) }
+            } else {
+                MergeHunk::Conflict { base: display(o), local: display(l), remote: display(r) }
+            });
+        }
+        if next.is_none() {
+            return hunks;
+        }
+        (i, a, b) = (j, a_end, b_end);
+    }
+}
+
+/// The lines both `local` and `remote` have, in order; the stand-in base for a two-way diff.
+fn common<'a>(local: &[&'a str], remote: &[&str]) -> Vec<&'a str> {
+    matches(local, remote)
+        .iter()
+        .zip(local)
+        .filter_map(|(m, line)| m.map(|_| *line))
+        .collect()
+}
+
+/// Put the text back together from the hunks, unless one of them conflicts.
+fn merge_content(base: &[&str], local: &[&str], remote: &[&str], hunks: &[MergeHunk]) -> Option<String> {
+    if hunks.iter().any(|h| matches!(h, MergeHunk::Conflict { .. })) {
+        return None;
+    }
+    // Hunks only hold display lines; replay the walk over the original lines instead.
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    let mut out = String::new();
+    for hunk in hunks {
+        match hunk {
+            MergeHunk::Unchanged { lines } => {
+                out.extend(local[a..a + lines.len()].iter().copied());
+                i += lines.len();
+                a += lines.len();
+                b += lines.len();
+            }
+            MergeHunk::Local { base: o, local: l } => {
+                out.extend(local[a..a + l.len()].iter().copied());
+                i += o.len();
+                a += l.len();
+                b += o.len();
+            }
+            MergeHunk::Remote { base: o, remote: r } => {
+                out.extend(remote[b..b + r.len()].iter().copied());
+                i += o.len();
+                a += o.len();
+                b += r.len();
+            }
+            MergeHunk::Both { base: o, lines } => {
+                out.extend(local[a..a + lines.len()].iter().copied());
+                i += o.len();
+                a += lines.len();
+                b += lines.len();
+            }
+            MergeHunk::Conflict { .. } => unreachable!(),
+        }
+    }
+    debug_assert_eq!((i, a, b), (base.len(), local.len(), remote.len()));
+    Some(out)
+}
+
+fn merge_name(base: Option<&str>, local: &str, remote: &str) -> NameMerge {
+    let merged = if local == remote {
+        Some(local)
+    } else {
+        match base {
+            Some(base) if base == local => Some(remote),
+            Some(base) if base == remote => Some(local),
+            _ => None,
+        }
+    };
+    NameMerge {
+        base: base.map(str::to_string),
+        local: local.to_string(),
+        remote: remote.to_string(),
+        merged: merged.map(str::to_string),
+    }
+}
+
+/// Line diff from `old` to `new`.
+pub fn diff(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old = split_lines(old);
+    let new = split_lines(new);
+    let to_new = matches(&old, &new);
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    loop {
+        let stable = (i..old.len()).take_while(|&k| to_new[k] == Some(j + k - i)).count();
+        if stable > 0 {
+            hunks.push(DiffHunk::Unchanged { lines: display(&old[i..i + stable]) });
+            i += stable;
+            j += stable;
+            continue;
+        }
+        // Up to the next old line that was kept.
+        let next = (i..old.len()).find_map(|k| Some((k, to_new[k]?)));
+        let (i_end, j_end) = next.unwrap_or((old.len(), new.len()));
+        if i_end > i || j_end > j {
+            hunks.push(DiffHunk::Changed {
+                removed: display(&old[i..i_end]),
+                added: display(&new[j..j_end]),
+            });
+        }
+        if next.is_none() {
+            return hunks;
+        }
+        (i, j) = (i_end, j_end);
+    }
+}
+
+/// Three-way diff of a project's name and content. `base` is `(name, content)` of the revision
+/// both sides last agreed on, when it is still known.
+pub fn preview(
+    base: Option<(&str, &str)>,
+    local: (&str, &str),
+    remote: (&str, &str),
+) -> MergePreview {
+    let local_lines = split_lines(local.1);
+    let remote_lines = split_lines(remote.1);
+    let base_lines = match base {
+        Some((_, content)) => split_lines(content),
+        None => common(&local_lines, &remote_lines),
+    };
+    let hunks = hunks(&base_lines, &local_lines, &remote_lines, base.is_some());
+    MergePreview {
+        base_available: base.is_some(),
+        name: merge_name(base.map(|(name, _)| name), local.0, remote.0),
+        conflicts: hunks.iter().filter(|h| matches!(h, MergeHunk::Conflict { .. })).count(),
+        merged: merge_content(&base_lines, &local_lines, &remote_lines, &hunks),
+        hunks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separate_edits_merge_and_overlapping_ones_conflict() {
+        let base = "host: db1\nuser: admin\nport: 5432\nnotes\n";
+        let local = "host: db2\nuser: admin\nport: 5432\nnotes\n";
+        let remote = "host: db1\nuser: admin\nport: 6432\nnotes\nmore\n";
+        let merged = preview(Some(("db", base)), ("db", local), ("prod db", remote));
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.name.merged.as_deref(), Some("prod db"));
+        assert_eq!(merged.merged.as_deref(), Some("host: db2\nuser: admin\nport: 6432\nnotes\nmore\n"));
+        assert!(merged.hunks.contains(&MergeHunk::Local {
+            base: vec!["host: db1".to_string()],
+            local: vec!["host: db2".to_string()],
+        }));
+
+        let remote = "host: db3\nuser: admin\nport: 5432\nnotes\n";
+        let clash = preview(Some(("db", base)), ("db", local), ("db", remote));
+        assert_eq!(clash.conflicts, 1);
+        assert!(clash.merged.is_none());
+
+        // Without a base even one-sided looking edits are conflicts.
+        let unknown = preview(None, ("db", local), ("db", base));
+        assert_eq!(unknown.conflicts, 1);
+        assert!(matches!(&unknown.hunks[1], MergeHunk::Unchanged { lines } if lines.len() == 3));
+    }
+
+    #[test]
+    fn diff_groups_replaced_lines_between_kept_ones() {
+        let hunks = diff("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n");
+        let lines = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            hunks,
+            [
+                DiffHunk::Unchanged { lines: lines(&["a"]) },
+                DiffHunk::Changed { removed: lines(&["b"]), added: lines(&["B"]) },
+                DiffHunk::Unchanged { lines: lines(&["c", "d"]) },
+                DiffHunk::Changed { removed: vec![], added: lines(&["e"]) },
+            ]
+        );
+        assert!(diff("same\n", "same\n").iter().all(|h| matches!(h, DiffHunk::Unchanged { .. })));
+        assert!(diff("", "").is_empty());
+    }
+}
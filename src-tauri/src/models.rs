@@ -2,6 +2,7 @@
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -9,12 +10,45 @@ pub struct Project {
     pub name: String,
     pub encrypted_content: Vec<u8>,
     pub key_check: Vec<u8>,
-    pub sort_order: i32,
+    /// Ordered key (see `ordering`); projects are listed by it.
+    pub position: String,
     pub created_at: String,
     pub updated_at: String,
     pub server_id: Option<String>,
     pub sync_status: String,
     pub last_synced_at: Option<String>,
+    /// Containing folder; `None` (or a folder this device does not know) is the top level.
+    #[serde(default)]
+    pub folder_id: Option<String>,
+    /// Bumped by every `update_project`; an edit based on an older revision is refused. Set by
+    /// the storage, whatever a project written to it says.
+    #[serde(default)]
+    pub revision: i64,
+    /// Listed before everything else. Set with `set_pinned`; other writes leave it alone.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// What a project holds: free text, or the fields of a credential (see `credential`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    #[default]
+    Note,
+    Credential,
+}
+
+/// The content of a credential project.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct Credential {
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub notes: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,9 +57,16 @@ pub struct DecryptedProject {
     pub name: String,
     pub content: String,
     pub has_custom_password: bool,
-    pub sort_order: i32,
+    pub position: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Pass back as `base_revision` when saving.
+    pub revision: i64,
+    #[serde(default)]
+    pub kind: ProjectKind,
+    /// The parsed `content` of a credential; `None` for a note.
+    #[serde(default)]
+    pub credential: Option<Credential>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,3 +87,206 @@ pub struct ProjectBackup {
     pub trigger_type: String,
     pub content_length: i64,
 }
+
+/// A previous revision of a project, kept for version history. Encrypted exactly as the project
+/// was at the time; `updated_at` is when that revision was written, `saved_at` when it was
+/// replaced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectVersion {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub encrypted_content: Vec<u8>,
+    pub key_check: Vec<u8>,
+    pub updated_at: String,
+    pub saved_at: String,
+}
+
+/// A file attached to a project. The content lives in the attachment shard under the attachment
+/// id, sealed with the attachment's own key; `wrapped_key` is that key encrypted with the project
+/// key, and the file name and MIME type are encrypted with it as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: String,
+    pub project_id: String,
+    pub wrapped_key: Vec<u8>,
+    pub encrypted_name: Vec<u8>,
+    pub encrypted_mime: Vec<u8>,
+    /// Plaintext size in bytes.
+    pub size: u64,
+    pub created_at: String,
+}
+
+/// A labelled field of a project (see `custom_fields`). Label, value and secret flag are
+/// encrypted together with the project key; `position` is an ordered key among the project's
+/// fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectField {
+    pub id: String,
+    pub project_id: String,
+    pub position: String,
+    pub encrypted_field: Vec<u8>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A project's TOTP secret and code settings (see `totp`), encrypted together with the project
+/// key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTotp {
+    pub project_id: String,
+    pub encrypted_secret: Vec<u8>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A password a credential project had before a save replaced it (see `password_history`),
+/// encrypted with the project key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordHistoryEntry {
+    pub id: String,
+    pub project_id: String,
+    pub encrypted_password: Vec<u8>,
+    pub replaced_at: String,
+}
+
+/// An item of a project's checklist (see `checklist`): its text and checked state, encrypted
+/// together with the project key. `sync_status` is `local`, `synced`, `modified` or `deleted`,
+/// as for folders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub project_id: String,
+    pub position: String,
+    pub encrypted_item: Vec<u8>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub sync_status: String,
+}
+
+/// Structured filters for `search`. Every field that is set must hold; `tags` are tag ids, all
+/// of which a project must have. Dates compare as the RFC 3339 strings stored, so a bare
+/// `2026-05-01` works as a bound. `created_before` and `updated_before` are exclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectFilter {
+    pub tags: Vec<String>,
+    /// Only projects without any tag.
+    pub untagged: bool,
+    pub kind: Option<ProjectKind>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub updated_after: Option<String>,
+    pub updated_before: Option<String>,
+    pub has_custom_password: Option<bool>,
+    pub sync_status: Option<String>,
+    pub archived: Option<bool>,
+}
+
+impl ProjectFilter {
+    /// Whether any filter is set.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && !self.untagged
+            && self.kind.is_none()
+            && self.created_after.is_none()
+            && self.created_before.is_none()
+            && self.updated_after.is_none()
+            && self.updated_before.is_none()
+            && self.has_custom_password.is_none()
+            && self.sync_status.is_none()
+            && self.archived.is_none()
+    }
+}
+
+/// A project's entry in the search index (see `search_index`): a Bloom filter of its text,
+/// encrypted with the vault key, and the `updated_at` of the project it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndexEntry {
+    pub project_id: String,
+    pub encrypted_filter: Vec<u8>,
+    pub indexed_at: String,
+}
+
+/// An unsaved edit of a project, kept so a crash does not lose it (see `drafts`). Encrypted with
+/// the project key; `base_revision` is the revision the edit started from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub project_id: String,
+    pub encrypted_draft: Vec<u8>,
+    pub base_revision: i64,
+    pub saved_at: String,
+}
+
+/// A folder of projects. Folders nest through `parent_id`; the name is encrypted with the vault
+/// key, bound to the folder id, which stays the same on every device. `sync_status` is `local`,
+/// `synced`, `modified` or `deleted`, as for projects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub encrypted_name: Vec<u8>,
+    pub position: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub sync_status: String,
+}
+
+/// A project template (see `templates`): a name and prefilled content, encrypted together with
+/// the vault key. `sync_status` is `local`, `synced`, `modified` or `deleted`, as for folders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub id: String,
+    pub encrypted_template: Vec<u8>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub sync_status: String,
+}
+
+/// A project tag. The name is encrypted with the vault key, bound to the tag id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub encrypted_name: Vec<u8>,
+    pub created_at: String,
+}
+
+/// A project's sidebar color and icon, each encrypted with the vault key and bound to the
+/// project id and field (see `appearance`). `None` where unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAppearance {
+    pub project_id: String,
+    pub encrypted_color: Option<Vec<u8>>,
+    pub encrypted_icon: Option<Vec<u8>>,
+}
+
+/// One entry of the append-only security audit log. `mac` chains it to the previous entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub seq: i64,
+    pub event: String,
+    pub detail: String,
+    pub created_at: String,
+    pub mac: String,
+}
+
+/// One row of the change feed: which record changed and how (`insert`, `update` or `delete`).
+/// `entity` is `project`, `folder`, `backup`, `setting` or `tag`; retagging a project counts as
+/// updating it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    pub seq: i64,
+    pub entity: String,
+    pub entity_id: String,
+    pub op: String,
+}
+
+/// Changes after a given sequence number. `seq` is where the next read should start; `reset`
+/// means the caller is too far behind (or on another vault) and has to reload everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeFeed {
+    pub seq: i64,
+    pub changes: Vec<Change>,
+    pub has_more: bool,
+    pub reset: bool,
+}
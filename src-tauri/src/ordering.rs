@@ -0,0 +1,201 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Ordered-key positions (LexoRank-style). A project's place in the list is a base-36 string
+//! compared lexicographically; a key between any two others can always be generated, so moving
+//! a project only changes its own key and devices that reorder concurrently never hand out the
+//! same integer twice. Keys never end in `0`, which keeps the space between them open.
+//!
+//! Moving projects into the same gap again and again lengthens the keys there by about a digit
+//! per five moves. Once a move would hand out a key longer than `MAX_KEY_LEN`, the whole list
+//! is given fresh, evenly spread keys instead; that is the only write touching every row.
+
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: u8 = 36;
+/// Longest key a reorder hands out before the list is rebalanced.
+pub const MAX_KEY_LEN: usize = 12;
+
+fn to_digits(key: &str) -> Vec<u8> {
+    key.bytes()
+        .filter_map(|b| DIGITS.iter().position(|&d| d == b).map(|i| i as u8))
+        .collect()
+}
+
+fn to_key(digits: &[u8]) -> String {
+    digits.iter().map(|&d| DIGITS[d as usize] as char).collect()
+}
+
+/// Digits strictly between `lower` and `upper` (no upper bound when `None`).
+fn midpoint(lower: &[u8], upper: Option<&[u8]>) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut upper = upper;
+    let mut i = 0;
+    loop {
+        let lo = lower.get(i).copied().unwrap_or(0);
+        let hi = upper
+            .map(|u| u.get(i).copied().unwrap_or(BASE))
+            .unwrap_or(BASE);
+        if lo == hi {
+            out.push(lo);
+        } else if hi - lo > 1 {
+            out.push((lo + hi) / 2);
+            return out;
+        } else {
+            // Adjacent digits: keep the lower one and go above the rest of `lower`.
+            out.push(lo);
+            upper = None;
+        }
+        i += 1;
+    }
+}
+
+/// A key that sorts after `before` and before `after`; either bound may be open.
+pub fn between(before: Option<&str>, after: Option<&str>) -> String {
+    let lower = before.map(to_digits).unwrap_or_default();
+    let upper = after.map(to_digits).filter(|u| !u.is_empty() && *u > lower);
+    to_key(&midpoint(&lower, upper.as_deref()))
+}
+
+/// `count` increasing keys between `before` and `after`, bisecting so that their length grows
+/// with the logarithm of `count` rather than linearly.
+pub fn keys_between(before: Option<&str>, after: Option<&str>, count: usize) -> Vec<String> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let mid = between(before, after);
+    let left = count / 2;
+    let mut keys = keys_between(before, Some(&mid), left);
+    keys.push(mid.clone());
+    keys.extend(keys_between(Some(&mid), after, count - left - 1));
+    keys
+}
+
+/// New keys for the projects `order` (id, current key) lists in their new order. Projects in
+/// the longest run that is already correctly ordered keep their keys; only the others get new
+/// ones, which are returned.
+pub fn plan_reorder(order: &[(String, String)]) -> Vec<(String, String)> {
+    let keep = longest_increasing(order);
+    let mut changes = Vec::new();
+    let mut previous: Option<&str> = None;
+    let mut pending: Vec<&str> = Vec::new();
+
+    let mut flush = |pending: &mut Vec<&str>, before: Option<&str>, after: Option<&str>| {
+        let keys = keys_between(before, after, pending.len());
+        changes.extend(pending.drain(..).map(str::to_string).zip(keys));
+    };
+
+    for (i, (id, key)) in order.iter().enumerate() {
+        if keep[i] {
+            flush(&mut pending, previous, Some(key));
+            previous = Some(key);
+        } else {
+            pending.push(id);
+        }
+    }
+    flush(&mut pending, previous, None);
+    changes
+}
+
+/// Whether keys planned by `plan_reorder` ran out of room and the list should be rebalanced.
+pub fn needs_rebalance(changes: &[(String, String)]) -> bool {
+    changes.iter().any(|(_, key)| key.len() > MAX_KEY_LEN)
+}
+
+/// New keys for all of `ids`, in this order, as short as their number allows.
+pub fn rebalance(ids: &[String]) -> Vec<(String, String)> {
+    ids.iter().cloned().zip(keys_between(None, None, ids.len())).collect()
+}
+
+/// Marks the longest strictly increasing subsequence of non-empty keys.
+fn longest_increasing(order: &[(String, String)]) -> Vec<bool> {
+    // tails[k]: index of the smallest tail of an increasing run of length k + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut parent: Vec<Option<usize>> = vec![None; order.len()];
+    for (i, (_, key)) in order.iter().enumerate() {
+        if key.is_empty() {
+            continue;
+        }
+        let len = tails.partition_point(|&t| order[t].1 < *key);
+        parent[i] = len.checked_sub(1).map(|l| tails[l]);
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut keep = vec![false; order.len()];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        keep[i] = true;
+        current = parent[i];
+    }
+    keep
+}
+
+/// Deterministic choice when two devices moved the same project: the greater key wins, so
+/// every device settles on the same position regardless of sync order.
+pub fn resolve<'a>(local: &'a str, remote: &'a str) -> &'a str {
+    if remote.is_empty() || local > remote {
+        local
+    } else {
+        remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_sort_between_bounds() {
+        assert_eq!(between(None, None), "i");
+        for (before, after) in [("a", "b"), ("a", "a1"), ("az", "b"), ("0", "01"), ("zz", "")] {
+            let key = between(Some(before), Some(after).filter(|a| !a.is_empty()));
+            assert!(before < key.as_str(), "{before} < {key}");
+            if !after.is_empty() {
+                assert!(key.as_str() < after, "{key} < {after}");
+            }
+            assert!(!key.ends_with('0'));
+        }
+
+        let keys = keys_between(Some("a"), Some("b"), 1000);
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert!(keys.iter().all(|k| k.len() <= 5));
+    }
+
+    #[test]
+    fn reorder_only_moves_what_changed() {
+        let order: Vec<(String, String)> = [("c", "k"), ("a", "c"), ("b", "f"), ("d", "")]
+            .iter()
+            .map(|(id, key)| (id.to_string(), key.to_string()))
+            .collect();
+        let changes = plan_reorder(&order);
+        let moved: Vec<&str> = changes.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(moved, vec!["c", "d"]);
+        assert!(changes[0].1.as_str() < "c");
+        assert!(changes[1].1.as_str() > "f");
+    }
+
+    #[test]
+    fn crowded_gaps_are_rebalanced() {
+        let mut first = "i".to_string();
+        let mut moves = 0;
+        let changes = loop {
+            moves += 1;
+            let order = [("new".to_string(), String::new()), ("first".to_string(), first.clone())];
+            let changes = plan_reorder(&order);
+            if needs_rebalance(&changes) {
+                break changes;
+            }
+            first = changes[0].1.clone();
+        };
+        assert!(moves > 30, "rebalanced after {moves} moves");
+        assert_eq!(changes.len(), 1);
+
+        let ids: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let keys = rebalance(&ids);
+        assert!(keys.windows(2).all(|w| w[0].1 < w[1].1));
+        assert!(!needs_rebalance(&keys));
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Diceware-style passphrases: words drawn uniformly from an embedded list of 2048 (the BIP-39
+//! English list, whose words are short and unambiguous in their first four letters), so each
+//! word adds 11 bits.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::rng::SecureRng;
+
+const WORDLIST: &str = include_str!("wordlist.txt");
+pub const DEFAULT_WORD_COUNT: usize = 6;
+pub const MIN_WORD_COUNT: usize = 3;
+pub const MAX_WORD_COUNT: usize = 20;
+const MAX_SEPARATOR_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capitalization {
+    #[default]
+    Lower,
+    Upper,
+    /// First letter of every word.
+    Title,
+    /// Each word in lower or title case at random, for one extra bit per word.
+    Random,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratedPassphrase {
+    pub passphrase: String,
+    pub word_count: usize,
+    pub entropy_bits: f64,
+}
+
+fn words() -> Vec<&'static str> {
+    WORDLIST.lines().filter(|w| !w.is_empty()).collect()
+}
+
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+pub fn generate(
+    word_count: usize,
+    separator: &str,
+    capitalization: Capitalization,
+) -> Result<GeneratedPassphrase, String> {
+    if !(MIN_WORD_COUNT..=MAX_WORD_COUNT).contains(&word_count) {
+        return Err(format!(
+            "Word count must be between {MIN_WORD_COUNT} and {MAX_WORD_COUNT}"
+        ));
+    }
+    if separator.chars().count() > MAX_SEPARATOR_LEN {
+        return Err(format!("Separator must be at most {MAX_SEPARATOR_LEN} characters"));
+    }
+
+    let list = words();
+    let mut rng = SecureRng;
+    let chosen: Vec<String> = (0..word_count)
+        .map(|_| {
+            let word = list[rng.gen_range(0..list.len())];
+            match capitalization {
+                Capitalization::Lower => word.to_string(),
+                Capitalization::Upper => word.to_uppercase(),
+                Capitalization::Title => title_case(word),
+                Capitalization::Random if rng.gen_bool(0.5) => title_case(word),
+                Capitalization::Random => word.to_string(),
+            }
+        })
+        .collect();
+
+    let bits_per_word = (list.len() as f64).log2()
+        + if capitalization == Capitalization::Random {
+            1.0
+        } else {
+            0.0
+        };
+    Ok(GeneratedPassphrase {
+        passphrase: chosen.join(separator),
+        word_count,
+        entropy_bits: bits_per_word * word_count as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passphrases_use_listed_words_and_options() {
+        assert_eq!(words().len(), 2048);
+
+        let generated = generate(5, " ", Capitalization::Title).unwrap();
+        let parts: Vec<&str> = generated.passphrase.split(' ').collect();
+        assert_eq!(parts.len(), 5);
+        assert!(parts.iter().all(|p| p.starts_with(|c: char| c.is_ascii_uppercase())));
+        assert!(parts.iter().all(|p| words().contains(&p.to_lowercase().as_str())));
+        assert_eq!(generated.entropy_bits, 55.0);
+
+        assert!(generate(2, "-", Capitalization::Lower).is_err());
+        assert!(generate(6, "---------", Capitalization::Lower).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Previous passwords of credential projects. Each time a save changes a credential's password
+//! the one it replaces is kept, encrypted with the project key like custom fields, so a botched
+//! rotation can be undone. Only the last `MAX_ENTRIES_PER_PROJECT` are kept.
+
+use serde::Serialize;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::attachments::ProjectKey;
+use crate::clock::Clock;
+use crate::credential;
+use crate::crypto;
+use crate::models::PasswordHistoryEntry;
+use crate::storage::StorageProvider;
+
+pub const MAX_ENTRIES_PER_PROJECT: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PastPassword {
+    pub id: String,
+    pub password: String,
+    /// When a save replaced it.
+    pub replaced_at: String,
+}
+
+fn seal(project_key: ProjectKey<'_>, id: &str, password: &str) -> Result<Vec<u8>, String> {
+    match project_key {
+        ProjectKey::Vault(key) => crypto::encrypt_with_key_aad(
+            password.as_bytes(),
+            key,
+            &crypto::field_aad(id, "past-password"),
+        ),
+        ProjectKey::Password(project_password) => {
+            crypto::encrypt(password.as_bytes(), project_password)
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// The password an entry holds. The entry must open with `project_key`.
+pub fn open(
+    project_key: ProjectKey<'_>,
+    entry: &PasswordHistoryEntry,
+) -> Result<Zeroizing<String>, String> {
+    let plain = match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &entry.encrypted_password,
+            key,
+            &crypto::field_aad(&entry.id, "past-password"),
+        )
+        .ok_or("Wrong key for this password history")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&entry.encrypted_password, password).map_err(|e| e.to_string())?
+        }
+    };
+    String::from_utf8(plain).map(Zeroizing::new).map_err(|e| e.to_string())
+}
+
+/// Keep the password a save replaces, when `old_content` and `new_content` are credentials
+/// with different passwords. `project_key` is the key the project is saved under.
+pub fn record_change(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+    old_content: &str,
+    new_content: &str,
+) -> Result<(), String> {
+    let (Some(old), Some(new)) = (credential::decode(old_content), credential::decode(new_content))
+    else {
+        return Ok(());
+    };
+    if old.password.is_empty() || old.password == new.password {
+        return Ok(());
+    }
+    let id = Uuid::new_v4().to_string();
+    storage
+        .add_password_history(&PasswordHistoryEntry {
+            encrypted_password: seal(project_key, &id, &old.password)?,
+            id,
+            project_id: project_id.to_string(),
+            replaced_at: clock.now().to_rfc3339(),
+        })
+        .map_err(|e| e.to_string())?;
+    storage
+        .prune_password_history(project_id, MAX_ENTRIES_PER_PROJECT)
+        .map_err(|e| e.to_string())
+}
+
+/// A project's previous passwords, most recently replaced first.
+pub fn list(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<Vec<PastPassword>, String> {
+    storage
+        .list_password_history(project_id)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|entry| {
+            Ok(PastPassword {
+                id: entry.id.clone(),
+                password: open(project_key, entry)?.to_string(),
+                replaced_at: entry.replaced_at.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Re-encrypt a project's previous passwords with its new key after the project password was
+/// set, changed or removed.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    project_id: &str,
+    old_key: ProjectKey<'_>,
+    new_key: ProjectKey<'_>,
+) -> Result<(), String> {
+    if old_key.same_as(&new_key) {
+        return Ok(());
+    }
+    for mut entry in storage.list_password_history(project_id).map_err(|e| e.to_string())? {
+        let password = open(old_key, &entry)?;
+        entry.encrypted_password = seal(new_key, &entry.id, &password)?;
+        storage.update_password_history(&entry).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Re-encrypt every previous password held under the vault key with `new_key`, for a master
+/// password change. Those of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        for mut entry in storage.list_password_history(&project.id).map_err(|e| e.to_string())? {
+            let Ok(password) = open(ProjectKey::Vault(old_key), &entry) else {
+                continue;
+            };
+            entry.encrypted_password = seal(ProjectKey::Vault(new_key), &entry.id, &password)?;
+            storage.update_password_history(&entry).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::{Credential, Project};
+    use crate::storage::local::LocalStorage;
+
+    fn content(password: &str) -> String {
+        let mut credential = Credential::default();
+        credential.password = password.to_string();
+        credential::encode(&credential).unwrap().to_string()
+    }
+
+    #[test]
+    fn replaced_passwords_are_kept_newest_first_and_bounded() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage
+            .create_project(&Project {
+                id: "p".to_string(),
+                name: "p".to_string(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        let vault = ProjectKey::Vault(&key);
+
+        record_change(&storage, &SystemClock, vault, "p", "a note", &content("one")).unwrap();
+        record_change(&storage, &SystemClock, vault, "p", &content("one"), &content("one"))
+            .unwrap();
+        assert!(list(&storage, vault, "p").unwrap().is_empty());
+
+        for i in 0..=MAX_ENTRIES_PER_PROJECT {
+            let (old, new) = (format!("pw{i}"), format!("pw{}", i + 1));
+            record_change(&storage, &SystemClock, vault, "p", &content(&old), &content(&new))
+                .unwrap();
+        }
+        let history = list(&storage, vault, "p").unwrap();
+        assert_eq!(history.len(), MAX_ENTRIES_PER_PROJECT);
+        assert_eq!(history[0].password, format!("pw{MAX_ENTRIES_PER_PROJECT}"));
+
+        let new_key = [6u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        assert_eq!(list(&storage, ProjectKey::Vault(&new_key), "p").unwrap().len(), history.len());
+    }
+}
@@ -47,8 +47,15 @@ impl RegistryContent {
     }
 }
 
+const KC_PREFIX: &str = "project-password-";
+
 pub fn kc_key(project_id: &str) -> String {
-    format!("project-password-{}", project_id)
+    format!("{KC_PREFIX}{project_id}")
+}
+
+/// The project a keychain key from `kc_key` belongs to.
+pub fn project_of(kc_key: &str) -> Option<&str> {
+    kc_key.strip_prefix(KC_PREFIX)
 }
 
 pub fn is_registry(project_id: &str) -> bool {
@@ -125,27 +132,32 @@ pub fn rebuild_registry(
             name: PASSWORD_REGISTRY_NAME.to_string(),
             encrypted_content,
             key_check,
-            sort_order: existing.sort_order,
+            position: existing.position,
             created_at: existing.created_at,
             updated_at: now,
             server_id: existing.server_id,
             sync_status,
             last_synced_at: existing.last_synced_at,
+            folder_id: existing.folder_id,
+            revision: existing.revision,
+            pinned: existing.pinned,
         };
         storage.update_project(&updated).map_err(|e| e.to_string())?;
     } else {
-        let max_order: i32 = projects.iter().map(|p| p.sort_order).max().unwrap_or(-1);
         let new_project = Project {
             id: PASSWORD_REGISTRY_UUID.to_string(),
             name: PASSWORD_REGISTRY_NAME.to_string(),
             encrypted_content,
             key_check,
-            sort_order: max_order + 1,
+            position: String::new(),
             created_at: now.clone(),
             updated_at: now,
             server_id: None,
             sync_status: "local".to_string(),
             last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
         };
         storage
             .create_project(&new_project)
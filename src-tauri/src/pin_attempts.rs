@@ -0,0 +1,175 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Rate limiting for quick unlock. Failed attempts are counted in the settings table so that
+//! restarting the app does not reset them; each failure doubles the wait before the next try,
+//! and after `lockout_after` failures quick unlock is refused until the master password is
+//! entered. Optionally the keychain session is wiped once `wipe_after` failures are reached.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::storage::StorageProvider;
+
+const SETTING_FAILED_ATTEMPTS: &str = "pin_failed_attempts";
+const SETTING_LAST_FAILURE_AT: &str = "pin_last_failure_at";
+const SETTING_POLICY: &str = "pin_attempt_policy";
+/// Every setting kept here; they are needed before the vault is unlocked.
+pub const SETTINGS: [&str; 3] = [SETTING_FAILED_ATTEMPTS, SETTING_LAST_FAILURE_AT, SETTING_POLICY];
+
+pub const DEFAULT_LOCKOUT_AFTER: u32 = 5;
+const MAX_DELAY_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AttemptPolicy {
+    pub lockout_after: u32,
+    /// Wipe the keychain session after this many failures; `None` never wipes.
+    pub wipe_after: Option<u32>,
+}
+
+impl Default for AttemptPolicy {
+    fn default() -> Self {
+        Self {
+            lockout_after: DEFAULT_LOCKOUT_AFTER,
+            wipe_after: None,
+        }
+    }
+}
+
+impl AttemptPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.lockout_after == 0 {
+            return Err("Lockout needs at least one attempt".to_string());
+        }
+        if self
+            .wipe_after
+            .is_some_and(|w| w == 0 || w > self.lockout_after)
+        {
+            return Err("Wipe threshold must be between 1 and the lockout threshold".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttemptStatus {
+    pub failed_attempts: u32,
+    pub locked_out: bool,
+    /// Seconds to wait before the next attempt is accepted.
+    pub retry_after_secs: i64,
+    pub policy: AttemptPolicy,
+}
+
+/// What a failed attempt led to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Retry,
+    LockedOut,
+    Wipe,
+}
+
+/// Wait after `failures` consecutive failures: 1 s, 2 s, 4 s, ... up to five minutes.
+fn delay_secs(failures: u32) -> i64 {
+    match failures {
+        0 => 0,
+        n => (1i64 << (n - 1).min(20)).min(MAX_DELAY_SECS),
+    }
+}
+
+pub fn policy(storage: &dyn StorageProvider) -> Result<AttemptPolicy, String> {
+    match storage.get_setting(SETTING_POLICY).map_err(|e| e.to_string())? {
+        Some(json) if !json.is_empty() => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid PIN attempt policy: {e}"))
+        }
+        _ => Ok(AttemptPolicy::default()),
+    }
+}
+
+pub fn set_policy(storage: &dyn StorageProvider, policy: &AttemptPolicy) -> Result<(), String> {
+    policy.validate()?;
+    let json = serde_json::to_string(policy).map_err(|e| e.to_string())?;
+    storage
+        .set_setting(SETTING_POLICY, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn status(storage: &dyn StorageProvider, clock: &dyn Clock) -> Result<AttemptStatus, String> {
+    let policy = policy(storage)?;
+    let failed_attempts = storage
+        .get_setting(SETTING_FAILED_ATTEMPTS)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let last_failure = storage
+        .get_setting(SETTING_LAST_FAILURE_AT)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| chrono::DateTime::parse_from_rfc3339(&v).ok());
+    let retry_after_secs = last_failure
+        .map(|at| {
+            let elapsed = clock.now().signed_duration_since(at).num_seconds();
+            (delay_secs(failed_attempts) - elapsed.max(0)).max(0)
+        })
+        .unwrap_or(0);
+    Ok(AttemptStatus {
+        failed_attempts,
+        locked_out: failed_attempts >= policy.lockout_after,
+        retry_after_secs,
+        policy,
+    })
+}
+
+/// Refuse an attempt while locked out (`pin_locked_out`) or backing off
+/// (`pin_retry_after:<seconds>`).
+pub fn check(storage: &dyn StorageProvider, clock: &dyn Clock) -> Result<(), String> {
+    let status = status(storage, clock)?;
+    if status.locked_out {
+        return Err("pin_locked_out".to_string());
+    }
+    if status.retry_after_secs > 0 {
+        return Err(format!("pin_retry_after:{}", status.retry_after_secs));
+    }
+    Ok(())
+}
+
+pub fn record_failure(storage: &dyn StorageProvider, clock: &dyn Clock) -> Result<Outcome, String> {
+    let policy = policy(storage)?;
+    let failures = status(storage, clock)?.failed_attempts.saturating_add(1);
+    storage
+        .set_setting(SETTING_FAILED_ATTEMPTS, &failures.to_string())
+        .and_then(|_| storage.set_setting(SETTING_LAST_FAILURE_AT, &clock.now().to_rfc3339()))
+        .map_err(|e| e.to_string())?;
+    Ok(if policy.wipe_after.is_some_and(|w| failures >= w) {
+        Outcome::Wipe
+    } else if failures >= policy.lockout_after {
+        Outcome::LockedOut
+    } else {
+        Outcome::Retry
+    })
+}
+
+/// Forget failed attempts, after any successful unlock.
+pub fn reset(storage: &dyn StorageProvider) -> Result<(), String> {
+    storage
+        .set_setting(SETTING_FAILED_ATTEMPTS, "0")
+        .and_then(|_| storage.set_setting(SETTING_LAST_FAILURE_AT, ""))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_and_policy_is_checked() {
+        let delays: Vec<i64> = (0..5).map(delay_secs).collect();
+        assert_eq!(delays, vec![0, 1, 2, 4, 8]);
+        assert_eq!(delay_secs(40), MAX_DELAY_SECS);
+
+        assert!(AttemptPolicy::default().validate().is_ok());
+        let wipe_late = AttemptPolicy {
+            lockout_after: 5,
+            wipe_after: Some(6),
+        };
+        assert!(wipe_late.validate().is_err());
+    }
+}
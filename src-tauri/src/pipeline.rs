@@ -0,0 +1,212 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Three-stage batch pipeline for bulk work (imports, re-encryption): items are produced on one
+//! thread, transformed (encrypted) on a second and written to storage on the caller's thread.
+//! The stages are joined by bounded channels, so a slow database holds the producer back
+//! instead of letting converted items pile up. Batches close at `batch_size` items or at their
+//! share of the soft memory limit, whichever comes first, which keeps the data in flight under
+//! roughly `soft_memory_limit` bytes.
+
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::importers::ImportedItem;
+use crate::models::Project;
+
+pub const DEFAULT_BATCH_SIZE: usize = 256;
+pub const MAX_BATCH_SIZE: usize = 10_000;
+/// Batches each channel holds before its sender blocks.
+const CHANNEL_BATCHES: usize = 2;
+const DEFAULT_SOFT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+/// Batches that can be alive at once: two per channel plus one in each stage.
+const BATCHES_IN_FLIGHT: usize = 2 * CHANNEL_BATCHES + 3;
+
+/// Rough heap footprint, used to close batches early when items are large.
+pub trait Weigh {
+    fn weight(&self) -> usize;
+}
+
+impl Weigh for ImportedItem {
+    fn weight(&self) -> usize {
+        self.title.len()
+            + self.folder.as_ref().map_or(0, String::len)
+            + self.fields.iter().map(|(l, v)| l.len() + v.len()).sum::<usize>()
+            + self.notes.as_ref().map_or(0, String::len)
+            + self.attachments.iter().chain(&self.tags).map(String::len).sum::<usize>()
+    }
+}
+
+impl Weigh for Project {
+    fn weight(&self) -> usize {
+        self.id.len() + self.name.len() + self.encrypted_content.len() + self.key_check.len()
+    }
+}
+
+impl<A: Weigh> Weigh for (usize, A) {
+    fn weight(&self) -> usize {
+        self.1.weight()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineOptions {
+    pub batch_size: usize,
+    pub soft_memory_limit: usize,
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            soft_memory_limit: DEFAULT_SOFT_MEMORY_LIMIT,
+        }
+    }
+}
+
+impl PipelineOptions {
+    pub fn with_batch_size(batch_size: Option<usize>) -> Result<Self, String> {
+        match batch_size {
+            None => Ok(Self::default()),
+            Some(size) if (1..=MAX_BATCH_SIZE).contains(&size) => Ok(Self {
+                batch_size: size,
+                ..Self::default()
+            }),
+            Some(_) => Err(format!("Batch size must be between 1 and {MAX_BATCH_SIZE}")),
+        }
+    }
+
+    fn batch_bytes(&self) -> usize {
+        (self.soft_memory_limit / BATCHES_IN_FLIGHT).max(1)
+    }
+}
+
+/// What a run processed and how fast, for the final report.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Throughput {
+    pub items: usize,
+    pub batches: usize,
+    /// Approximate size of the items that went through the transform stage.
+    pub bytes: u64,
+    pub elapsed_ms: u64,
+    pub items_per_sec: f64,
+}
+
+fn produce<T: Weigh>(source: impl Iterator<Item = T>, options: PipelineOptions, tx: SyncSender<Vec<T>>) {
+    let mut batch = Vec::with_capacity(options.batch_size);
+    let mut bytes = 0;
+    for item in source {
+        bytes += item.weight();
+        batch.push(item);
+        if batch.len() >= options.batch_size || bytes >= options.batch_bytes() {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(options.batch_size));
+            bytes = 0;
+            if tx.send(full).is_err() {
+                return;
+            }
+        }
+    }
+    if !batch.is_empty() {
+        let _ = tx.send(batch);
+    }
+}
+
+fn transform_batches<T: Weigh, U>(
+    rx: Receiver<Vec<T>>,
+    transform: &(impl Fn(T) -> U + Sync),
+    tx: SyncSender<(Vec<U>, usize)>,
+) {
+    for batch in rx {
+        let bytes = batch.iter().map(Weigh::weight).sum();
+        let out: Vec<U> = batch.into_iter().map(transform).collect();
+        if tx.send((out, bytes)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Feed `source` through `transform` into `sink`, one batch at a time. The sink runs on the
+/// calling thread, so it may use storage that cannot leave it; an error from the sink stops the
+/// other stages and is returned.
+pub fn run<T, U, E>(
+    source: impl Iterator<Item = T> + Send,
+    options: PipelineOptions,
+    transform: impl Fn(T) -> U + Sync,
+    mut sink: impl FnMut(Vec<U>) -> Result<(), E>,
+) -> Result<Throughput, E>
+where
+    T: Weigh + Send,
+    U: Send,
+{
+    let started = Instant::now();
+    let mut throughput = Throughput::default();
+    let (raw_tx, raw_rx) = sync_channel(CHANNEL_BATCHES);
+    let (done_tx, done_rx) = sync_channel(CHANNEL_BATCHES);
+
+    std::thread::scope(|scope| {
+        scope.spawn(move || produce(source, options, raw_tx));
+        let transform = &transform;
+        scope.spawn(move || transform_batches(raw_rx, transform, done_tx));
+
+        // Dropping `done_rx` on error makes the other stages' sends fail, so they wind down.
+        for (batch, bytes) in done_rx {
+            throughput.items += batch.len();
+            throughput.batches += 1;
+            throughput.bytes += bytes as u64;
+            sink(batch)?;
+        }
+        Ok(())
+    })?;
+
+    let elapsed = started.elapsed();
+    throughput.elapsed_ms = elapsed.as_millis() as u64;
+    throughput.items_per_sec = if elapsed.as_secs_f64() > 0.0 {
+        throughput.items as f64 / elapsed.as_secs_f64()
+    } else {
+        0.0
+    };
+    Ok(throughput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Weigh for u32 {
+        fn weight(&self) -> usize {
+            4
+        }
+    }
+
+    #[test]
+    fn batches_arrive_in_order_and_errors_stop_the_run() {
+        let options = PipelineOptions {
+            batch_size: 10,
+            soft_memory_limit: BATCHES_IN_FLIGHT * 20,
+        };
+        let mut seen = Vec::new();
+        let throughput = run(0..1000u32, options, |n| n * 2, |batch| {
+            // 20 bytes per batch closes it after five four-byte items.
+            assert!(batch.len() <= 5);
+            seen.extend(batch);
+            Ok::<_, String>(())
+        })
+        .unwrap();
+        assert_eq!(seen, (0..1000u32).map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(throughput.items, 1000);
+        assert_eq!(throughput.batches, 200);
+
+        let mut batches = 0;
+        let result = run(0..u32::MAX, PipelineOptions::default(), |n| n, |_| {
+            batches += 1;
+            if batches == 3 {
+                Err("disk full")
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result.unwrap_err(), "disk full");
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! A single project as an unencrypted Markdown or text file, for sharing a note outside the
+//! vault. The name becomes the title; a credential is written as its fields, password
+//! included, followed by its notes.
+
+use serde::Deserialize;
+use zeroize::Zeroizing;
+
+use crate::credential;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Text => "txt",
+        }
+    }
+}
+
+/// The file contents for a project named `name` holding `content`.
+pub fn render(name: &str, content: &str, format: ExportFormat) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    match format {
+        ExportFormat::Markdown => out.push_str(&format!("# {name}\n\n")),
+        ExportFormat::Text => out.push_str(&format!("{name}\n\n")),
+    }
+    let Some(credential) = credential::decode(content) else {
+        out.push_str(content);
+        return out;
+    };
+    let fields = [
+        ("Username", &credential.username),
+        ("Password", &credential.password),
+        ("URL", &credential.url),
+    ];
+    for (label, value) in fields.into_iter().filter(|(_, v)| !v.is_empty()) {
+        match format {
+            ExportFormat::Markdown => out.push_str(&format!("- **{label}:** `{value}`\n")),
+            ExportFormat::Text => out.push_str(&format!("{label}: {value}\n")),
+        }
+    }
+    if !credential.notes.is_empty() {
+        out.push('\n');
+        out.push_str(&credential.notes);
+    }
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Credential;
+
+    #[test]
+    fn notes_get_a_title_and_credentials_their_fields() {
+        assert_eq!(*render("Ideas", "- one\n", ExportFormat::Markdown), "# Ideas\n\n- one\n");
+
+        let mut login = Credential::default();
+        login.username = "jane".to_string();
+        login.password = "hunter2".to_string();
+        login.notes = "Rotate yearly.".to_string();
+        let content = credential::encode(&login).unwrap();
+        assert_eq!(
+            *render("Bank", &content, ExportFormat::Text),
+            "Bank\n\nUsername: jane\nPassword: hunter2\n\nRotate yearly.\n"
+        );
+        assert!(render("Bank", &content, ExportFormat::Markdown).contains("- **Password:** `hunter2`"));
+    }
+}
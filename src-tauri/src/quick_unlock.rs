@@ -0,0 +1,106 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Quick-unlock factors. A PIN, an unlock pattern or a short word phrase is reduced to a
+//! canonical secret string, which then wraps the vault key exactly like the PIN does.
+
+use serde::{Deserialize, Serialize};
+
+/// Nodes of the 3x3 pattern grid, numbered row by row from 0.
+pub const PATTERN_GRID_NODES: u8 = 9;
+pub const PATTERN_MIN_NODES: usize = 4;
+pub const PHRASE_WORDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickUnlockKind {
+    Pin,
+    Pattern,
+    Phrase,
+}
+
+impl QuickUnlockKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuickUnlockKind::Pin => "pin",
+            QuickUnlockKind::Pattern => "pattern",
+            QuickUnlockKind::Phrase => "phrase",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pin" => Some(QuickUnlockKind::Pin),
+            "pattern" => Some(QuickUnlockKind::Pattern),
+            "phrase" => Some(QuickUnlockKind::Phrase),
+            _ => None,
+        }
+    }
+
+    /// Canonical secret for `input`. PINs are used as typed, so existing PIN slots keep working;
+    /// the other kinds are prefixed so that equal strings never collide across kinds.
+    pub fn normalize(&self, input: &str) -> Result<String, String> {
+        match self {
+            QuickUnlockKind::Pin => {
+                if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit()) {
+                    return Err("invalid_pin".to_string());
+                }
+                Ok(input.to_string())
+            }
+            QuickUnlockKind::Pattern => normalize_pattern(input).map(|p| format!("pattern:{p}")),
+            QuickUnlockKind::Phrase => normalize_phrase(input).map(|p| format!("phrase:{p}")),
+        }
+    }
+}
+
+/// A pattern is the sequence of visited grid nodes, e.g. `0-1-2-5-8`.
+fn normalize_pattern(input: &str) -> Result<String, String> {
+    let mut nodes: Vec<u8> = Vec::new();
+    for part in input.split(|c: char| c == '-' || c == ',' || c.is_whitespace()) {
+        if part.is_empty() {
+            continue;
+        }
+        let node: u8 = part.parse().map_err(|_| "invalid_pattern".to_string())?;
+        if node >= PATTERN_GRID_NODES || nodes.contains(&node) {
+            return Err("invalid_pattern".to_string());
+        }
+        nodes.push(node);
+    }
+    if nodes.len() < PATTERN_MIN_NODES {
+        return Err("pattern_too_short".to_string());
+    }
+    Ok(nodes
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("-"))
+}
+
+/// Case and spacing are not significant in a phrase.
+fn normalize_phrase(input: &str) -> Result<String, String> {
+    let words: Vec<String> = input.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.len() != PHRASE_WORDS || !words.iter().all(|w| w.chars().all(char::is_alphabetic)) {
+        return Err("invalid_phrase".to_string());
+    }
+    Ok(words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_patterns_and_phrases() {
+        let pattern = QuickUnlockKind::Pattern;
+        assert_eq!(pattern.normalize("0-1-2-5").unwrap(), "pattern:0-1-2-5");
+        assert_eq!(pattern.normalize(" 0,1 2-5 ").unwrap(), "pattern:0-1-2-5");
+        assert!(pattern.normalize("0-1-2").is_err());
+        assert!(pattern.normalize("0-1-1-2").is_err());
+        assert!(pattern.normalize("0-1-2-9").is_err());
+
+        let phrase = QuickUnlockKind::Phrase;
+        assert_eq!(phrase.normalize("  Blue  Otter river").unwrap(), "phrase:blue otter river");
+        assert!(phrase.normalize("blue otter").is_err());
+        assert!(phrase.normalize("blue otter r1ver").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Recovery codes. A random code (150 bits, Crockford base32 in groups of five) wraps the
+//! vault key like the master password does, so a forgotten password does not mean a lost
+//! vault. The code itself is shown once, in the emergency kit, and never stored.
+//!
+//! Recovery shares are the k-of-n alternative: a random key is split with `crypto::sss` and
+//! handed out as text shares, so no single holder can open the vault alone.
+
+use serde::Serialize;
+
+use crate::crypto::rng;
+use crate::crypto::sss::Share;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+pub const CODE_CHARS: usize = 30;
+const GROUP_LEN: usize = 5;
+const SHARE_PREFIX: &str = "VPS1";
+
+/// Printable sheet handed to the frontend right after a code is generated.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyKit {
+    pub recovery_code: String,
+    pub vault_id: Option<String>,
+    pub database_path: Option<String>,
+    pub created_at: String,
+    pub instructions: Vec<String>,
+}
+
+/// A new code, formatted as `XXXXX-XXXXX-...`.
+pub fn generate_code() -> String {
+    let mut bytes = [0u8; CODE_CHARS];
+    rng::fill(&mut bytes);
+    let chars: Vec<char> = bytes
+        .iter()
+        .map(|b| ALPHABET[(b & 0x1f) as usize] as char)
+        .collect();
+    chars
+        .chunks(GROUP_LEN)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Canonical form of a typed code: separators dropped, upper case, and the look-alikes that
+/// Crockford base32 leaves out (`O`, `I`, `L`) read as digits.
+pub fn normalize(input: &str) -> Result<String, String> {
+    let mut code = String::with_capacity(CODE_CHARS);
+    for c in input.chars() {
+        let c = match c.to_ascii_uppercase() {
+            '-' | ' ' => continue,
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        if !c.is_ascii() || !ALPHABET.contains(&(c as u8)) {
+            return Err("invalid_recovery_code".to_string());
+        }
+        code.push(c);
+    }
+    if code.len() != CODE_CHARS {
+        return Err("invalid_recovery_code".to_string());
+    }
+    Ok(code)
+}
+
+pub fn emergency_kit(
+    recovery_code: String,
+    vault_id: Option<String>,
+    database_path: Option<String>,
+    created_at: String,
+) -> EmergencyKit {
+    EmergencyKit {
+        recovery_code,
+        vault_id,
+        database_path,
+        created_at,
+        instructions: vec![
+            "Print this page or write the recovery code down, and keep it somewhere safe and offline.".to_string(),
+            "If you forget your master password, choose \"Use recovery code\" on the unlock screen and enter the code to set a new password.".to_string(),
+            "Anyone with this code and your database file can open your vault.".to_string(),
+            "Generating a new recovery code makes this one stop working.".to_string(),
+        ],
+    }
+}
+
+/// A decoded recovery share. `set_id` ties the shares of one split together so shares of an
+/// older split are recognised instead of producing a wrong key.
+#[derive(Debug, Clone)]
+pub struct RecoveryShare {
+    pub set_id: String,
+    pub threshold: u8,
+    pub share: Share,
+}
+
+/// Text form of a share: `VPS1-<set id>-<threshold>-<index>-<hex data>`.
+pub fn encode_share(set_id: &str, threshold: u8, share: &Share) -> String {
+    format!(
+        "{SHARE_PREFIX}-{set_id}-{threshold}-{}-{}",
+        share.index,
+        hex::encode(&share.data)
+    )
+}
+
+pub fn parse_share(input: &str) -> Result<RecoveryShare, String> {
+    let invalid = || "invalid_recovery_share".to_string();
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parts = compact.split('-');
+    if !parts
+        .next()
+        .is_some_and(|p| p.eq_ignore_ascii_case(SHARE_PREFIX))
+    {
+        return Err(invalid());
+    }
+    let set_id = parts.next().ok_or_else(invalid)?.to_ascii_lowercase();
+    let threshold = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+    let index = parts
+        .next()
+        .and_then(|p| p.parse().ok())
+        .filter(|&i: &u8| i > 0)
+        .ok_or_else(invalid)?;
+    let data = parts
+        .next()
+        .and_then(|p| hex::decode(p).ok())
+        .ok_or_else(invalid)?;
+    if parts.next().is_some() || set_id.is_empty() {
+        return Err(invalid());
+    }
+    Ok(RecoveryShare {
+        set_id,
+        threshold,
+        share: Share { index, data },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_codes_normalize() {
+        let code = generate_code();
+        assert_eq!(code.len(), CODE_CHARS + CODE_CHARS / GROUP_LEN - 1);
+        let canonical = normalize(&code).unwrap();
+        assert_eq!(normalize(&code.to_lowercase().replace('-', " ")).unwrap(), canonical);
+        assert_eq!(normalize("oil").ok(), None);
+        assert_eq!(normalize(&"OIL".repeat(10)).unwrap(), "011".repeat(10));
+        assert!(normalize(&"U".repeat(CODE_CHARS)).is_err());
+
+        let share = Share {
+            index: 3,
+            data: vec![0xab, 0x01],
+        };
+        let text = encode_share("0a1b2c3d", 2, &share);
+        assert_eq!(text, "VPS1-0a1b2c3d-2-3-ab01");
+        let parsed = parse_share(&format!(" {} ", text.to_lowercase())).unwrap();
+        assert_eq!((parsed.set_id.as_str(), parsed.threshold), ("0a1b2c3d", 2));
+        assert_eq!((parsed.share.index, parsed.share.data), (3, vec![0xab, 0x01]));
+        assert!(parse_share("VPS1-0a1b2c3d-2-0-ab01").is_err());
+    }
+}
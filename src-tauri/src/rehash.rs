@@ -0,0 +1,430 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! V1 -> V2 migration. Master-key content still stored in the V1 format (per-blob Argon2id,
+//! possibly with the legacy 64 MB / 3 iteration fallback) can only be opened with the password
+//! itself, which is no longer kept after unlock. `upgrade_all` therefore re-encrypts such blobs
+//! with the vault key while the password is at hand, so the slow path runs at most once per blob.
+//! Once a pass leaves nothing behind, the vault is marked as upgraded and later unlocks skip the
+//! scan; `census` clears the mark again if V1 content turns up after all, say from a server.
+//!
+//! `bind_content` likewise binds the content of local projects to their id and name once, with
+//! the vault key alone, after which unbound content of local projects is refused.
+
+use serde::Serialize;
+use zeroize::Zeroize;
+
+use crate::crypto;
+use crate::models::{Project, ProjectBackup};
+use crate::password_registry;
+use crate::storage::StorageProvider;
+
+/// Set in `vault_meta` once no V1 master-key content is left.
+pub const META_V1_UPGRADED: &str = "v1_upgraded";
+/// Set in `vault_meta` once all local content is bound to its project's id and name.
+pub const META_CONTENT_BOUND: &str = "content_bound";
+
+/// Whether `data` only decrypts through the V1 paths, i.e. not with the vault key directly.
+/// Content behind a custom password (whose key check the vault key cannot open) is left alone,
+/// even when that password happens to equal the master password.
+fn needs_rehash(
+    data: &[u8],
+    key_check: &[u8],
+    key: &[u8; crypto::KEY_LEN],
+    project_id: &str,
+    name: &str,
+) -> bool {
+    is_master(key_check, key)
+        && crypto::content::decrypt(data, key, project_id, name, true).is_err()
+}
+
+fn is_master(key_check: &[u8], key: &[u8; crypto::KEY_LEN]) -> bool {
+    key_check.is_empty() || crypto::try_decrypt_with_key(key_check, key).is_some()
+}
+
+fn upgrade_project(
+    storage: &dyn StorageProvider,
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+    password: &str,
+    bind: bool,
+) -> Result<bool, String> {
+    let (id, name) = (&project.id, &project.name);
+    if !needs_rehash(&project.encrypted_content, &project.key_check, key, id, name) {
+        return Ok(false);
+    }
+    let encrypted_content = crypto::decrypt_auto(&project.encrypted_content, None, Some(password))
+        .and_then(|plaintext| crypto::content::encrypt(&plaintext, key, id, name, bind))
+        .map_err(|e| e.to_string())?;
+
+    let mut upgraded = project.clone();
+    upgraded.encrypted_content = encrypted_content;
+    if upgraded.key_check.is_empty() {
+        if let Ok(kc) = crypto::encrypt_with_key(b"mk", key) {
+            upgraded.key_check = kc;
+        }
+    }
+    storage.update_project(&upgraded).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Backups are bound along with their project: those of a synced project stay portable.
+fn upgrade_backup(
+    storage: &dyn StorageProvider,
+    backup: &ProjectBackup,
+    key: &[u8; crypto::KEY_LEN],
+    password: &str,
+    bind: bool,
+) -> Result<bool, String> {
+    let (id, name) = (&backup.project_id, &backup.name);
+    if !needs_rehash(&backup.encrypted_content, &backup.key_check, key, id, name) {
+        return Ok(false);
+    }
+    let encrypted_content = crypto::decrypt_auto(&backup.encrypted_content, None, Some(password))
+        .and_then(|plaintext| crypto::content::encrypt(&plaintext, key, id, name, bind))
+        .map_err(|e| e.to_string())?;
+
+    let mut upgraded = backup.clone();
+    upgraded.encrypted_content = encrypted_content;
+    storage.update_backup(&upgraded).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Whether an earlier pass left no V1 content behind.
+pub fn is_upgraded(storage: &dyn StorageProvider) -> bool {
+    storage
+        .get_meta(META_V1_UPGRADED)
+        .ok()
+        .flatten()
+        .is_some_and(|v| !v.is_empty())
+}
+
+/// Re-encrypt every V1 master-key project and backup with `key`, unless the vault is marked as
+/// upgraded already. `bind_local` selects V3 output for projects that were never uploaded. Best
+/// effort: blobs that fail are retried on the next unlock, and the vault is only marked once
+/// none did. Returns the number of blobs upgraded.
+pub fn upgrade_all(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    password: &str,
+    bind_local: bool,
+) -> u32 {
+    if is_upgraded(storage) {
+        return 0;
+    }
+    let projects = match storage.list_projects() {
+        Ok(projects) => projects,
+        Err(_) => return 0,
+    };
+
+    let mut count = 0u32;
+    let mut failed = false;
+    let mut tally = |result: Result<bool, String>| match result {
+        Ok(true) => count += 1,
+        Ok(false) => {}
+        Err(_) => failed = true,
+    };
+    for project in &projects {
+        let bind = bind_local && project.server_id.is_none();
+        tally(upgrade_project(storage, project, key, password, bind));
+        match storage.list_backups(&project.id) {
+            Ok(backups) => {
+                for backup in &backups {
+                    tally(upgrade_backup(storage, backup, key, password, bind));
+                }
+            }
+            Err(e) => tally(Err(e.to_string())),
+        }
+    }
+    if !failed {
+        let _ = storage.set_meta(META_V1_UPGRADED, "1");
+    }
+    count
+}
+
+/// Whether `bind_content` left no unbound local content behind.
+pub fn is_bound(storage: &dyn StorageProvider) -> bool {
+    storage
+        .get_meta(META_CONTENT_BOUND)
+        .ok()
+        .flatten()
+        .is_some_and(|v| !v.is_empty())
+}
+
+/// `data` re-encrypted bound to project `project_id` named `name`, or `None` if it is bound
+/// already or not under the vault key.
+fn rebind(
+    data: &[u8],
+    key_check: &[u8],
+    key: &[u8; crypto::KEY_LEN],
+    project_id: &str,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    if !is_master(key_check, key) || crypto::content::is_bound(data, key, project_id, name) {
+        return Ok(None);
+    }
+    let mut plaintext = crypto::content::decrypt(data, key, project_id, name, true)
+        .map_err(|e| e.to_string())?;
+    let bound = crypto::content::encrypt(&plaintext, key, project_id, name, true);
+    plaintext.zeroize();
+    bound.map(Some).map_err(|e| e.to_string())
+}
+
+fn bind_project(
+    storage: &dyn StorageProvider,
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<u32, String> {
+    let (id, name) = (&project.id, &project.name);
+    let mut count = 0;
+    if let Some(bound) = rebind(&project.encrypted_content, &project.key_check, key, id, name)? {
+        let mut project = project.clone();
+        project.encrypted_content = bound;
+        storage.update_project(&project).map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    for mut backup in storage.list_backups(id).map_err(|e| e.to_string())? {
+        let rebound = rebind(&backup.encrypted_content, &backup.key_check, key, id, &backup.name)?;
+        if let Some(bound) = rebound {
+            backup.encrypted_content = bound;
+            storage.update_backup(&backup).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+    }
+    for mut version in storage.list_versions(id).map_err(|e| e.to_string())? {
+        if version.key_check.is_empty() {
+            continue;
+        }
+        let (data, key_check) = (&version.encrypted_content, &version.key_check);
+        if let Some(bound) = rebind(data, key_check, key, id, &version.name)? {
+            version.encrypted_content = bound;
+            storage.update_version(&version).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Bind the content of every local project under the vault key, and that of its backups and
+/// versions, to the project's id and name (see `crypto::content`). Synced projects stay
+/// portable. Best effort like `upgrade_all`: the vault is only marked as bound once nothing was
+/// left behind, V1 content included. Returns the number of blobs bound.
+pub fn bind_content(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> u32 {
+    let projects = match storage.list_projects() {
+        Ok(projects) => projects,
+        Err(_) => return 0,
+    };
+    let mut count = 0u32;
+    let mut failed = false;
+    for project in &projects {
+        if project.server_id.is_some() || password_registry::is_registry(&project.id) {
+            continue;
+        }
+        match bind_project(storage, project, key) {
+            Ok(bound) => count += bound,
+            Err(_) => failed = true,
+        }
+    }
+    if !failed {
+        let _ = storage.set_meta(META_CONTENT_BOUND, "1");
+    }
+    count
+}
+
+/// How the master-key content of a vault is stored. Content behind a custom password is only
+/// counted, since the vault key cannot tell its format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FormatCensus {
+    /// Still V1: per-blob Argon2id, upgraded on the next password unlock.
+    pub v1: usize,
+    /// V2, or V3 bound to the project id alone: portable, or bound by `bind_content`.
+    pub v2: usize,
+    /// Bound to the project id and name.
+    pub v3: usize,
+    pub custom_password: usize,
+}
+
+impl FormatCensus {
+    fn add(
+        &mut self,
+        data: &[u8],
+        key_check: &[u8],
+        key: &[u8; crypto::KEY_LEN],
+        id: &str,
+        name: &str,
+    ) {
+        if !is_master(key_check, key) {
+            self.custom_password += 1;
+        } else if needs_rehash(data, key_check, key, id, name) {
+            self.v1 += 1;
+        } else if crypto::content::is_bound(data, key, id, name) {
+            self.v3 += 1;
+        } else {
+            self.v2 += 1;
+        }
+    }
+}
+
+/// Count projects and backups by content format. V1 content in a vault marked as upgraded
+/// clears the mark, so the next password unlock upgrades it.
+pub fn census(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<FormatCensus, String> {
+    let mut census = FormatCensus::default();
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        let (id, name) = (&project.id, &project.name);
+        census.add(&project.encrypted_content, &project.key_check, key, id, name);
+        for backup in storage.list_backups(id).map_err(|e| e.to_string())? {
+            census.add(&backup.encrypted_content, &backup.key_check, key, id, &backup.name);
+        }
+    }
+    if census.v1 > 0 && is_upgraded(storage) {
+        storage.set_meta(META_V1_UPGRADED, "").map_err(|e| e.to_string())?;
+    }
+    Ok(census)
+}
+
+/// Decrypt master-key project content with the vault key.
+pub fn open_project_content(
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Vec<u8>, String> {
+    let portable = project.server_id.is_some();
+    crypto::content::decrypt(&project.encrypted_content, key, &project.id, &project.name, portable)
+        .map_err(|e| e.to_string())
+}
+
+/// `open_project_content` decrypting the content as `storage` reads it, so large content is
+/// never held encrypted in full; `project` may come from `get_project_meta`.
+pub fn read_project_content(
+    storage: &dyn StorageProvider,
+    project: &Project,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Vec<u8>, String> {
+    let portable = project.server_id.is_some();
+    let (id, name) = (&project.id, &project.name);
+    let mut opened = None;
+    storage
+        .read_project_content(id, &mut |reader| {
+            opened = Some(crypto::content::decrypt_from(reader, key, id, name, portable));
+            Ok(())
+        })
+        .map_err(|e| e.to_string())?;
+    match opened {
+        Some(Ok(content)) => Ok(content),
+        // Chunked content bound to the id alone only opens whole.
+        _ => open_project_content(&storage.get_project(id).map_err(|e| e.to_string())?, key),
+    }
+}
+
+/// Whether content of project `project_id` may be unbound: that of synced projects, which has
+/// to stay portable.
+pub fn is_portable(storage: &dyn StorageProvider, project_id: &str) -> bool {
+    storage.get_project(project_id).is_ok_and(|p| p.server_id.is_some())
+}
+
+/// Same as `open_project_content`, for a backup of a master-key project; `portable` if that
+/// project is synced.
+pub fn open_backup_content(
+    backup: &ProjectBackup,
+    key: &[u8; crypto::KEY_LEN],
+    portable: bool,
+) -> Result<Vec<u8>, String> {
+    let (id, name) = (&backup.project_id, &backup.name);
+    crypto::content::decrypt(&backup.encrypted_content, key, id, name, portable)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+
+    fn v1_project(id: &str, content: &[u8], password: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: id.to_string(),
+            encrypted_content: crypto::encrypt(content, password).unwrap(),
+            key_check: Vec::new(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn upgrading_runs_until_nothing_is_left() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [2u8; crypto::KEY_LEN];
+        storage.create_project(&v1_project("a", b"alpha", "pw")).unwrap();
+        storage.create_project(&v1_project("b", b"beta", "old pw")).unwrap();
+
+        assert_eq!(upgrade_all(&storage, &key, "pw", true), 1);
+        assert!(!is_upgraded(&storage));
+        let a = storage.get_project("a").unwrap();
+        assert!(crypto::is_aad_bound(&a.encrypted_content));
+        assert_eq!(open_project_content(&a, &key).unwrap(), b"alpha");
+
+        storage.delete_project("b").unwrap();
+        assert_eq!(upgrade_all(&storage, &key, "pw", true), 0);
+        assert!(is_upgraded(&storage));
+    }
+
+    #[test]
+    fn upgraded_vaults_are_scanned_again_once_v1_content_turns_up() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [2u8; crypto::KEY_LEN];
+        assert_eq!(upgrade_all(&storage, &key, "pw", true), 0);
+        assert!(is_upgraded(&storage));
+
+        storage.create_project(&v1_project("a", b"alpha", "pw")).unwrap();
+        assert_eq!(upgrade_all(&storage, &key, "pw", false), 0);
+        assert_eq!(census(&storage, &key).unwrap().v1, 1);
+        assert!(!is_upgraded(&storage));
+        assert_eq!(upgrade_all(&storage, &key, "pw", false), 1);
+        assert_eq!(census(&storage, &key).unwrap().v2, 1);
+    }
+
+    #[test]
+    fn binding_covers_local_projects_and_their_backups() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [6u8; crypto::KEY_LEN];
+        let mut local = v1_project("a", b"", "pw");
+        local.encrypted_content = crypto::encrypt_with_key(b"alpha", &key).unwrap();
+        local.key_check = crypto::encrypt_with_key(b"mk", &key).unwrap();
+        let mut synced = local.clone();
+        synced.id = "b".to_string();
+        synced.server_id = Some("remote-b".to_string());
+        storage.create_project(&local).unwrap();
+        storage.create_project(&synced).unwrap();
+        let backup = ProjectBackup {
+            id: "a-1".to_string(),
+            project_id: "a".to_string(),
+            name: "a".to_string(),
+            encrypted_content: local.encrypted_content.clone(),
+            key_check: local.key_check.clone(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            trigger_type: "auto".to_string(),
+            content_length: 5,
+        };
+        storage.create_backup(&backup).unwrap();
+
+        assert_eq!(bind_content(&storage, &key), 2);
+        assert!(is_bound(&storage));
+        let a = storage.get_project("a").unwrap();
+        assert!(crypto::content::is_bound(&a.encrypted_content, &key, "a", "a"));
+        assert_eq!(open_project_content(&a, &key).unwrap(), b"alpha");
+        let b = storage.get_project("b").unwrap();
+        assert_eq!(b.encrypted_content, synced.encrypted_content);
+        let census = census(&storage, &key).unwrap();
+        assert_eq!((census.v2, census.v3), (1, 2));
+        assert_eq!(bind_content(&storage, &key), 0);
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Moving a vault's files to another path. Every file of the vault (see
+//! `wipe::database_files`) is copied and synced before anything is switched over, so a failure
+//! part way leaves the vault where it was; only once the copy opens are the originals removed.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::storage::lock;
+use crate::wipe;
+
+/// The files of the vault at `from` that exist, each with its path for a vault at `to`.
+fn file_pairs(from: &str, to: &str) -> Vec<(PathBuf, PathBuf)> {
+    wipe::database_files(from)
+        .into_iter()
+        .filter(|file| file.exists())
+        .filter_map(|file| {
+            let suffix = file.to_string_lossy().strip_prefix(from)?.to_string();
+            Some((file, PathBuf::from(format!("{to}{suffix}"))))
+        })
+        .collect()
+}
+
+fn copy_synced(from: &Path, to: &Path) -> std::io::Result<()> {
+    let written = std::fs::copy(from, to)?;
+    File::open(to)?.sync_all()?;
+    if written != std::fs::metadata(from)?.len() {
+        return Err(std::io::Error::other("copy is incomplete"));
+    }
+    Ok(())
+}
+
+/// Copy the vault at `from` to `to`. Writes to the vault must be held off meanwhile; its WAL
+/// is copied along, so it need not be checkpointed. Nothing is left at `to` on failure.
+pub fn copy_vault(from: &str, to: &str) -> Result<(), String> {
+    if Path::new(from) == Path::new(to) {
+        return Err("The vault is already at this path".to_string());
+    }
+    let pairs = file_pairs(from, to);
+    if pairs.iter().any(|(_, target)| target.exists()) {
+        return Err("A database file already exists at the target path".to_string());
+    }
+    for (i, (source, target)) in pairs.iter().enumerate() {
+        if let Err(e) = copy_synced(source, target) {
+            for (_, copied) in &pairs[..=i] {
+                let _ = std::fs::remove_file(copied);
+            }
+            return Err(format!("Failed to copy {}: {e}", source.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Remove the files of the vault at `db_path`, and its lock file, once it is closed. Returns
+/// the ones that could not be removed.
+pub fn remove_vault(db_path: &str) -> Vec<PathBuf> {
+    wipe::database_files(db_path)
+        .into_iter()
+        .chain([lock::lock_path(db_path)])
+        .filter(|file| file.exists() && std::fs::remove_file(file).is_err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::StorageProvider;
+
+    #[test]
+    fn copies_include_uncheckpointed_writes() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-relocate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let from = dir.join("vault.db").to_string_lossy().to_string();
+        let to = dir.join("moved.db").to_string_lossy().to_string();
+
+        let storage = LocalStorage::new(&from).unwrap();
+        storage.set_setting("theme", "dark").unwrap();
+        copy_vault(&from, &to).unwrap();
+        assert!(copy_vault(&from, &to).is_err());
+        drop(storage);
+
+        assert!(remove_vault(&from).is_empty());
+        let moved = LocalStorage::new(&to).unwrap();
+        assert_eq!(moved.get_setting("theme").unwrap().as_deref(), Some("dark"));
+        drop(moved);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,430 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Full-text search over project names and content. Content is encrypted, so every search
+//! decrypts the projects it can: those under the vault key, and those with their own password
+//! when that password is saved in the keychain. Projects in the trash are left out, and so are
+//! projects still in their cooling-off delay (see `cooling_off`).
+//!
+//! The query is split on whitespace and a project matches when each term occurs in its name or
+//! content, ignoring case. Matches in the name count for more than matches in the content. A
+//! credential's password is never searched; its username, URL and notes are.
+//!
+//! Projects under the vault key are first checked against `search_index`, and only opened when
+//! their filter holds every trigram of the query.
+//!
+//! A search can also be narrowed by a `ProjectFilter`. The storage applies what it can in SQL
+//! (tags, dates, sync status, archiving) before anything is decrypted; the project kind and
+//! whether it has its own password are checked here. With filters and no query, every project
+//! that passes them is returned, by name.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use crate::clock::Clock;
+use crate::cooling_off;
+use crate::credential;
+use crate::crypto;
+use crate::keychain;
+use crate::models::{Project, ProjectFilter};
+use crate::password_registry;
+use crate::rehash;
+use crate::search_index;
+use crate::storage::StorageProvider;
+use crate::trash;
+
+const MAX_RESULTS: usize = 50;
+/// Characters of context on each side of the first content match.
+const SNIPPET_CONTEXT: usize = 60;
+const NAME_MATCH_SCORE: u32 = 10;
+/// Content matches beyond this many add nothing, so a long document does not outrank a
+/// matching name just by repeating a word.
+const MAX_CONTENT_MATCHES: u32 = 5;
+
+/// A matched range, in UTF-16 code units as JavaScript indexes strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub name: String,
+    pub name_highlights: Vec<Highlight>,
+    /// Content around the first match, empty when only the name matched.
+    pub snippet: String,
+    pub snippet_highlights: Vec<Highlight>,
+    pub score: u32,
+}
+
+/// `text` as characters folded for comparison, each with its offset in `text`.
+fn folded(text: &str) -> Vec<(usize, char)> {
+    text.char_indices()
+        .map(|(i, c)| (i, c.to_lowercase().next().unwrap_or(c)))
+        .collect()
+}
+
+/// Byte ranges in the text `haystack` was folded from where `term` occurs.
+fn find_all(haystack: &[(usize, char)], text_len: usize, term: &[char]) -> Vec<(usize, usize)> {
+    if term.is_empty() || term.len() > haystack.len() {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i + term.len() <= haystack.len() {
+        if haystack[i..i + term.len()].iter().map(|(_, c)| c).eq(term.iter()) {
+            let end = haystack.get(i + term.len()).map_or(text_len, |(at, _)| *at);
+            found.push((haystack[i].0, end));
+            i += term.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// Byte ranges of `text` to UTF-16 offsets within `text[from..]`.
+fn highlights(text: &str, from: usize, ranges: &[(usize, usize)]) -> Vec<Highlight> {
+    let utf16 = |byte: usize| text[from..byte].encode_utf16().count();
+    ranges
+        .iter()
+        .map(|&(start, end)| Highlight { start: utf16(start), end: utf16(end) })
+        .collect()
+}
+
+/// Sorted and with overlapping ranges merged.
+fn merged(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort_unstable();
+    let mut out: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match out.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => out.push((start, end)),
+        }
+    }
+    out
+}
+
+/// The search terms of `query`, folded.
+pub fn terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .map(|term| folded(term).into_iter().map(|(_, c)| c).collect())
+        .collect()
+}
+
+/// Score `name` and `content` against `terms`; `None` when some term is in neither.
+pub fn score(id: &str, name: &str, content: &str, terms: &[Vec<char>]) -> Option<SearchHit> {
+    if terms.is_empty() {
+        return None;
+    }
+    let folded_name = folded(name);
+    let folded_content = folded(content);
+    let mut name_ranges = Vec::new();
+    let mut content_ranges = Vec::new();
+    let mut score = 0;
+    for term in terms {
+        let in_name = find_all(&folded_name, name.len(), term);
+        let in_content = find_all(&folded_content, content.len(), term);
+        if in_name.is_empty() && in_content.is_empty() {
+            return None;
+        }
+        score += NAME_MATCH_SCORE * in_name.len() as u32;
+        score += (in_content.len() as u32).min(MAX_CONTENT_MATCHES);
+        name_ranges.extend(in_name);
+        content_ranges.extend(in_content);
+    }
+    let name_ranges = merged(name_ranges);
+    let content_ranges = merged(content_ranges);
+
+    let (snippet, snippet_highlights) = match content_ranges.first() {
+        Some(&(first, _)) => {
+            let chars: Vec<usize> = content.char_indices().map(|(i, _)| i).collect();
+            let at = chars.partition_point(|&i| i < first);
+            let start = chars[at.saturating_sub(SNIPPET_CONTEXT)];
+            let end = chars.get(at + SNIPPET_CONTEXT * 2).copied().unwrap_or(content.len());
+            let inside: Vec<(usize, usize)> = content_ranges
+                .iter()
+                .filter(|(s, _)| *s >= start && *s < end)
+                .map(|&(s, e)| (s, e.min(end)))
+                .collect();
+            (content[start..end].to_string(), highlights(content, start, &inside))
+        }
+        None => (String::new(), Vec::new()),
+    };
+
+    Some(SearchHit {
+        id: id.to_string(),
+        name: name.to_string(),
+        name_highlights: highlights(name, 0, &name_ranges),
+        snippet,
+        snippet_highlights,
+        score,
+    })
+}
+
+/// The text of a project that is searched: its content, or for a credential everything but
+/// the password.
+fn searchable(content: &str) -> Zeroizing<String> {
+    match credential::decode(content) {
+        Some(c) => Zeroizing::new(format!("{}\n{}\n{}", c.username, c.url, c.notes)),
+        None => Zeroizing::new(content.to_string()),
+    }
+}
+
+fn under_vault_key(project: &Project, key: &[u8; crypto::KEY_LEN]) -> bool {
+    project.key_check.is_empty() || crypto::try_decrypt_with_key(&project.key_check, key).is_some()
+}
+
+/// A project's content, if it can be opened without asking for a password.
+fn open(project: &Project, key: &[u8; crypto::KEY_LEN]) -> Option<Zeroizing<String>> {
+    let bytes = if under_vault_key(project, key) {
+        rehash::open_project_content(project, key).ok()?
+    } else {
+        let password = Zeroizing::new(keychain::get(&password_registry::kc_key(&project.id))?);
+        crypto::decrypt_auto(&project.encrypted_content, None, Some(&password)).ok()?
+    };
+    String::from_utf8(bytes).ok().map(Zeroizing::new)
+}
+
+/// The folded characters the index holds for a project: its name, then its searchable text.
+fn indexed_text(name: &str, searchable: &str) -> Zeroizing<Vec<char>> {
+    let mut text: Vec<char> = folded(name).into_iter().map(|(_, c)| c).collect();
+    text.push('\n');
+    text.extend(folded(searchable).into_iter().map(|(_, c)| c));
+    Zeroizing::new(text)
+}
+
+/// Bring a project's index entry up to date with `content`, just saved. A project with its own
+/// password loses its entry.
+pub fn index_project(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    project: &Project,
+    content: &str,
+) -> Result<(), String> {
+    if !under_vault_key(project, key) {
+        return search_index::remove(storage, &project.id);
+    }
+    let text = indexed_text(&project.name, &searchable(content));
+    search_index::update(storage, key, project, &text)
+}
+
+/// Index every project under `key` afresh, for a master password change.
+pub fn rebuild_index(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    search_index::clear(storage)?;
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        if password_registry::is_registry(&project.id) || !under_vault_key(&project, key) {
+            continue;
+        }
+        if let Ok(content) = rehash::open_project_content(&project, key) {
+            let content = Zeroizing::new(String::from_utf8_lossy(&content).into_owned());
+            index_project(storage, key, &project, &content)?;
+        }
+    }
+    Ok(())
+}
+
+/// Projects matching `query` and `filter`, best first.
+pub fn search(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    query: &str,
+    filter: &ProjectFilter,
+) -> Result<Vec<SearchHit>, String> {
+    let terms = terms(query);
+    if terms.is_empty() && filter.is_empty() {
+        return Ok(Vec::new());
+    }
+    let passed: Option<HashSet<String>> = if filter.is_empty() {
+        None
+    } else {
+        let ids = storage.filter_projects(filter).map_err(|e| e.to_string())?;
+        Some(ids.into_iter().collect())
+    };
+    let wanted: HashSet<[char; 3]> =
+        terms.iter().flat_map(|term| search_index::trigrams(term)).collect();
+    let index: HashMap<String, _> = storage
+        .list_search_index()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|entry| (entry.project_id.clone(), entry))
+        .collect();
+    let trashed = trash::ids(storage)?;
+    let mut hits = Vec::new();
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        if project.sync_status == "deleted"
+            || trashed.contains(&project.id)
+            || password_registry::is_registry(&project.id)
+        {
+            continue;
+        }
+        if passed.as_ref().is_some_and(|ids| !ids.contains(&project.id)) {
+            continue;
+        }
+        if cooling_off::check(storage, clock, &project.id).is_err() {
+            continue;
+        }
+        let vault_key = under_vault_key(&project, key);
+        if filter.has_custom_password.is_some_and(|custom| custom == vault_key) {
+            continue;
+        }
+        let entry = index.get(&project.id).filter(|e| search_index::is_current(e, &project));
+        if let Some(entry) = entry {
+            if !terms.is_empty() && search_index::may_match(entry, key, &wanted) == Some(false) {
+                continue;
+            }
+        }
+        if terms.is_empty() && filter.kind.is_none() {
+            hits.push(unscored(&project));
+            continue;
+        }
+        let raw = open(&project, key);
+        if let Some(kind) = filter.kind {
+            let is_kind = raw.as_ref().is_some_and(|c| credential::parse(c).0 == kind);
+            if !is_kind {
+                continue;
+            }
+        }
+        let content = raw.map(|c| searchable(&c)).unwrap_or_default();
+        if entry.is_none() && vault_key {
+            let text = indexed_text(&project.name, &content);
+            let _ = search_index::update(storage, key, &project, &text);
+        }
+        if terms.is_empty() {
+            hits.push(unscored(&project));
+        } else if let Some(hit) = score(&project.id, &project.name, &content, &terms) {
+            hits.push(hit);
+        }
+    }
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    hits.truncate(MAX_RESULTS);
+    Ok(hits)
+}
+
+/// A hit for a project that passed the filters of a search without a query.
+fn unscored(project: &Project) -> SearchHit {
+    SearchHit {
+        id: project.id.clone(),
+        name: project.name.clone(),
+        name_highlights: Vec::new(),
+        snippet: String::new(),
+        snippet_highlights: Vec::new(),
+        score: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_term_must_match_and_offsets_are_utf16() {
+        let terms = terms("Router ADMIN");
+        assert!(score("a", "Router", "no second term here", &terms).is_none());
+
+        let hit = score("a", "Home router", "Open 🔑 the admin page. Admin again.", &terms).unwrap();
+        assert_eq!(hit.name_highlights, [Highlight { start: 5, end: 11 }]);
+        // The emoji is two UTF-16 code units.
+        assert_eq!(hit.snippet_highlights[0], Highlight { start: 12, end: 17 });
+        assert_eq!(hit.snippet_highlights.len(), 2);
+        assert_eq!(hit.score, NAME_MATCH_SCORE + 2);
+
+        let content_only = score("b", "Notes", "router admin", &terms).unwrap();
+        assert!(content_only.score < hit.score);
+    }
+
+    #[test]
+    fn stale_index_entries_are_rebuilt_by_the_next_search() {
+        use crate::commands::projects::master_key_project;
+        use crate::clock::SystemClock;
+        use crate::storage::local::LocalStorage;
+
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        let none = ProjectFilter::default();
+        let project = master_key_project(&SystemClock, &key, true, "Router", "admin page").unwrap();
+        storage.create_project(&project).unwrap();
+        index_project(&storage, &key, &project, "admin page").unwrap();
+        assert_eq!(search(&storage, &SystemClock, &key, "admin", &none).unwrap().len(), 1);
+        assert!(search(&storage, &SystemClock, &key, "firmware", &none).unwrap().is_empty());
+
+        let mut edited = project.clone();
+        edited.encrypted_content =
+            crypto::content::encrypt(b"firmware", &key, &project.id, "Router", true).unwrap();
+        edited.updated_at = "2030-01-01T00:00:00Z".to_string();
+        storage.update_project(&edited).unwrap();
+        assert_eq!(search(&storage, &SystemClock, &key, "firmware", &none).unwrap().len(), 1);
+        let entry = &storage.list_search_index().unwrap()[0];
+        assert_eq!(entry.indexed_at, edited.updated_at);
+
+        // A deleted project stays as a tombstone until the deletion is synced.
+        edited.sync_status = "deleted".to_string();
+        storage.update_project(&edited).unwrap();
+        assert!(search(&storage, &SystemClock, &key, "firmware", &none).unwrap().is_empty());
+    }
+
+    #[test]
+    fn filters_narrow_a_search_and_work_without_a_query() {
+        use crate::clock::SystemClock;
+        use crate::demo;
+        use crate::models::ProjectKind;
+        use crate::storage::local::LocalStorage;
+
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [4u8; crypto::KEY_LEN];
+        demo::seed(&storage, &SystemClock, &key).unwrap();
+        let names = |query: &str, filter: &ProjectFilter| -> Vec<String> {
+            let mut names: Vec<String> = search(&storage, &SystemClock, &key, query, filter)
+                .unwrap()
+                .into_iter()
+                .map(|h| h.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        let credentials = ProjectFilter { kind: Some(ProjectKind::Credential), ..Default::default() };
+        assert_eq!(names("example", &credentials), ["Build server", "Example Bank"]);
+        assert_eq!(names("", &credentials).len(), 3);
+
+        let untagged = ProjectFilter { untagged: true, ..Default::default() };
+        assert_eq!(names("", &untagged), ["Welcome"]);
+
+        let bank = storage.list_projects().unwrap().into_iter().find(|p| p.name == "Example Bank");
+        storage.archive_project(&bank.unwrap().id, "2030-01-01T00:00:00Z").unwrap();
+        let archived = ProjectFilter { archived: Some(true), ..Default::default() };
+        assert_eq!(names("", &archived), ["Example Bank"]);
+        let later = ProjectFilter { created_after: Some("2999".into()), ..Default::default() };
+        assert!(names("", &later).is_empty());
+        assert!(names("", &ProjectFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn projects_in_their_cooling_off_delay_are_not_searched() {
+        use crate::commands::projects::master_key_project;
+        use crate::clock::SystemClock;
+        use crate::storage::local::LocalStorage;
+
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        let none = ProjectFilter::default();
+        let project = master_key_project(&SystemClock, &key, true, "Safe", "combination 1234").unwrap();
+        storage.create_project(&project).unwrap();
+        assert_eq!(search(&storage, &SystemClock, &key, "combination", &none).unwrap().len(), 1);
+
+        cooling_off::configure(&storage, &SystemClock, &project.id, Some(3600)).unwrap();
+        assert!(search(&storage, &SystemClock, &key, "combination", &none).unwrap().is_empty());
+        assert!(search(&storage, &SystemClock, &key, "safe", &none).unwrap().is_empty());
+        cooling_off::request_access(&storage, &SystemClock, &project.id).unwrap();
+        assert!(search(&storage, &SystemClock, &key, "combination", &none).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Index that lets `search` skip projects without decrypting them. For each project under the
+//! vault key it keeps a Bloom filter of the character trigrams of its name and searchable text,
+//! encrypted with the vault key and bound to the project. A project whose filter lacks one of
+//! the query's trigrams cannot match and is not opened; the rest are opened and scored as
+//! before, so false positives only cost time.
+//!
+//! An entry is written when a project is created or saved and remembers the `updated_at` it
+//! was built from. Entries that no longer agree with their project, say after a sync or an
+//! import, are rebuilt by the next search. Projects with their own password are not indexed,
+//! as their content must not be readable with the vault key alone. The whole index is rebuilt
+//! after a master password change.
+
+use std::collections::HashSet;
+
+use crate::crypto;
+use crate::models::{Project, SearchIndexEntry};
+use crate::storage::StorageProvider;
+
+const HASHES: u32 = 6;
+const BITS_PER_TRIGRAM: usize = 10;
+const MIN_BITS: usize = 512;
+const MAX_BITS: usize = 1 << 20;
+
+fn aad(project_id: &str) -> Vec<u8> {
+    crypto::field_aad(project_id, "search-index")
+}
+
+/// FNV-1a, seeded. Stable across builds, unlike `std`'s hasher, since filters are stored.
+fn fnv1a(seed: u64, trigram: &[char; 3]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for c in trigram {
+        for byte in (*c as u32).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Bit positions of `trigram` in a filter of `bits` bits, by double hashing.
+fn positions(trigram: &[char; 3], bits: usize) -> impl Iterator<Item = usize> {
+    let h1 = fnv1a(0, trigram);
+    let h2 = fnv1a(0x9e37_79b9_7f4a_7c15, trigram) | 1;
+    (0..HASHES as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits as u64) as usize)
+}
+
+/// The distinct trigrams of already folded characters.
+pub fn trigrams(chars: &[char]) -> HashSet<[char; 3]> {
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// A Bloom filter of `trigrams`, as bytes.
+fn filter(trigrams: &HashSet<[char; 3]>) -> Vec<u8> {
+    let bits = (trigrams.len() * BITS_PER_TRIGRAM).clamp(MIN_BITS, MAX_BITS).next_multiple_of(8);
+    let mut filter = vec![0u8; bits / 8];
+    for trigram in trigrams {
+        for bit in positions(trigram, bits) {
+            filter[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+    filter
+}
+
+fn may_contain(filter: &[u8], trigram: &[char; 3]) -> bool {
+    let bits = filter.len() * 8;
+    bits > 0 && positions(trigram, bits).all(|bit| filter[bit / 8] & (1 << (bit % 8)) != 0)
+}
+
+/// Whether an entry's filter may hold every trigram in `wanted`.
+pub fn may_match(
+    entry: &SearchIndexEntry,
+    key: &[u8; crypto::KEY_LEN],
+    wanted: &HashSet<[char; 3]>,
+) -> Option<bool> {
+    let filter =
+        crypto::try_decrypt_with_key_aad(&entry.encrypted_filter, key, &aad(&entry.project_id))?;
+    Some(wanted.iter().all(|t| may_contain(&filter, t)))
+}
+
+/// Whether `entry` was built from the project as it is now.
+pub fn is_current(entry: &SearchIndexEntry, project: &Project) -> bool {
+    entry.indexed_at == project.updated_at
+}
+
+/// Index `project`, whose name and searchable text fold to `folded`.
+pub fn update(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    project: &Project,
+    folded: &[char],
+) -> Result<(), String> {
+    let filter = filter(&trigrams(folded));
+    let entry = SearchIndexEntry {
+        project_id: project.id.clone(),
+        encrypted_filter: crypto::encrypt_with_key_aad(&filter, key, &aad(&project.id))
+            .map_err(|e| e.to_string())?,
+        indexed_at: project.updated_at.clone(),
+    };
+    storage.save_search_index_entry(&entry).map_err(|e| e.to_string())
+}
+
+/// Drop a project's entry, for one that now has its own password.
+pub fn remove(storage: &dyn StorageProvider, project_id: &str) -> Result<(), String> {
+    storage.delete_search_index_entry(project_id).map_err(|e| e.to_string())
+}
+
+/// Drop the whole index; searches rebuild it under the current key as they go.
+pub fn clear(storage: &dyn StorageProvider) -> Result<(), String> {
+    storage.clear_search_index().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn filters_keep_their_trigrams_and_reject_most_others() {
+        let text = chars("the quick brown fox jumps over the lazy dog");
+        let f = filter(&trigrams(&text));
+        assert!(trigrams(&text).iter().all(|t| may_contain(&f, t)));
+
+        let absent = ["zzz", "qqq", "xyl", "ppp", "kkk", "wvw", "jjj", "bbb"];
+        let hits = absent
+            .iter()
+            .flat_map(|t| trigrams(&chars(t)))
+            .filter(|t| may_contain(&f, t))
+            .count();
+        assert!(hits <= 1);
+    }
+}
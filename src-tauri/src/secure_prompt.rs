@@ -0,0 +1,122 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Native password dialogs, so the master password can be collected without passing through
+//! the webview. Each platform shells out to a system-provided prompt and reads the answer
+//! from its stdout pipe. On macOS this also tells whether Touch ID is set up; the Touch ID prompt
+//! itself comes from the keychain, which releases the item it guards (`keychain::biometric`).
+
+use std::process::{Command, Stdio};
+
+use zeroize::Zeroizing;
+
+pub const PROMPT_TITLE: &str = "VaultPad";
+
+fn run_prompt(mut cmd: Command) -> Result<Option<Zeroizing<String>>, String> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("Secure prompt unavailable: {e}"))?;
+
+    let stdout = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        // Every supported prompt exits non-zero when the user cancels.
+        return Ok(None);
+    }
+
+    let text = std::str::from_utf8(&stdout).map_err(|_| "Invalid prompt output".to_string())?;
+    let password = text.strip_suffix('\n').unwrap_or(text);
+    let password = password.strip_suffix('\r').unwrap_or(password);
+    Ok(Some(Zeroizing::new(password.to_string())))
+}
+
+#[cfg(target_os = "macos")]
+fn prompt_command(message: &str) -> Option<Command> {
+    let script = format!(
+        "text returned of (display dialog \"{}\" with title \"{}\" default answer \"\" with hidden answer with icon caution)",
+        message.replace('"', "'"),
+        PROMPT_TITLE
+    );
+    let mut cmd = Command::new("/usr/bin/osascript");
+    cmd.arg("-e").arg(script);
+    Some(cmd)
+}
+
+#[cfg(target_os = "windows")]
+fn prompt_command(message: &str) -> Option<Command> {
+    let script = format!(
+        "$c = $Host.UI.PromptForCredential('{}', '{}', '{}', ''); if ($c -eq $null) {{ exit 1 }}; [Console]::Out.Write($c.GetNetworkCredential().Password)",
+        PROMPT_TITLE,
+        message.replace('\'', "''"),
+        PROMPT_TITLE
+    );
+    // Not `-NonInteractive`: that makes `PromptForCredential` fail instead of showing the dialog.
+    let mut cmd = Command::new("powershell.exe");
+    cmd.args(["-NoProfile", "-Command", &script]);
+    Some(cmd)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn prompt_command(message: &str) -> Option<Command> {
+    let on_path = |bin: &str| {
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(bin).is_file()))
+            .unwrap_or(false)
+    };
+
+    if on_path("zenity") {
+        let mut cmd = Command::new("zenity");
+        cmd.args(["--password", "--title", PROMPT_TITLE]);
+        return Some(cmd);
+    }
+    if on_path("kdialog") {
+        let mut cmd = Command::new("kdialog");
+        cmd.args(["--title", PROMPT_TITLE, "--password", message]);
+        return Some(cmd);
+    }
+    None
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn prompt_command(_message: &str) -> Option<Command> {
+    None
+}
+
+/// LocalAuthentication policy 1, `LAPolicyDeviceOwnerAuthenticationWithBiometrics`.
+#[cfg(target_os = "macos")]
+const TOUCH_ID_POLICY: u32 = 1;
+#[cfg(target_os = "macos")]
+const TOUCH_ID_PRELUDE: &str =
+    "ObjC.import('LocalAuthentication');\nvar context = $.LAContext.alloc.init;\n";
+
+#[cfg(target_os = "macos")]
+fn touch_id_command(script: &str) -> Command {
+    let mut cmd = Command::new("/usr/bin/osascript");
+    cmd.args(["-l", "JavaScript", "-e"]).arg(format!("{TOUCH_ID_PRELUDE}{script}"));
+    cmd
+}
+
+/// Whether Touch ID is set up and usable.
+#[cfg(target_os = "macos")]
+pub fn touch_id_available() -> bool {
+    let script = format!(
+        "if (!context.canEvaluatePolicyError({TOUCH_ID_POLICY}, null)) {{ throw 'unavailable'; }}"
+    );
+    run_prompt(touch_id_command(&script)).is_ok_and(|answer| answer.is_some())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn touch_id_available() -> bool {
+    false
+}
+
+pub fn is_available() -> bool {
+    prompt_command("").is_some()
+}
+
+/// Ask for a password with the platform's native dialog. `Ok(None)` means the user cancelled.
+pub fn prompt_password(message: &str) -> Result<Option<Zeroizing<String>>, String> {
+    let cmd = prompt_command(message).ok_or("No secure password prompt on this system")?;
+    run_prompt(cmd)
+}
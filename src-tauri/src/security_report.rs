@@ -0,0 +1,199 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! One view of how well the open vault is protected on this machine: how long the master key
+//! takes to derive, which formats its data is stored in, where the keychain keeps its entries
+//! and how soon the vault locks itself, with what to change first.
+
+use serde::Serialize;
+
+use crate::crypto::KdfParams;
+use crate::keychain;
+use crate::quick_unlock::QuickUnlockKind;
+use crate::rehash::FormatCensus;
+
+/// Derivations faster than this make guessing the master password cheap.
+const FAST_KDF_MS: u64 = 250;
+/// Slower than this and every unlock drags.
+const SLOW_KDF_MS: u64 = 3000;
+const LONG_AUTO_LOCK_MINUTES: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KdfTiming {
+    pub params: KdfParams,
+    pub millis: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityReport {
+    pub kdf: KdfTiming,
+    pub format_version: Option<u32>,
+    pub schema_version: u32,
+    pub database_encrypted: bool,
+    pub content: FormatCensus,
+    pub keychain_backend: String,
+    /// A master password saved by an older version is still in the keychain.
+    pub master_password_persisted: bool,
+    /// The vault key is saved for unlocking without the password.
+    pub session_saved: bool,
+    pub quick_unlock: Option<QuickUnlockKind>,
+    /// As configured in the frontend; 0 is never, `None` when not reported.
+    pub auto_lock_minutes: Option<u32>,
+    pub lock_on_screen_lock: bool,
+    pub recommendations: Vec<Recommendation>,
+}
+
+fn recommend(id: &'static str, severity: Severity, message: String) -> Recommendation {
+    Recommendation { id, severity, message }
+}
+
+/// What to change, most important first.
+pub fn recommendations(report: &SecurityReport) -> Vec<Recommendation> {
+    let mut out = Vec::new();
+    if report.master_password_persisted {
+        out.push(recommend(
+            "master_password_saved",
+            Severity::High,
+            "The master password itself is saved in the keychain. Clear the saved session and \
+             unlock again to keep only the vault key."
+                .to_string(),
+        ));
+    }
+    if report.auto_lock_minutes == Some(0) {
+        out.push(recommend(
+            "auto_lock_off",
+            Severity::High,
+            "Auto-lock is off, so the vault stays open until it is locked by hand.".to_string(),
+        ));
+    } else if report.auto_lock_minutes.is_some_and(|m| m > LONG_AUTO_LOCK_MINUTES) {
+        out.push(recommend(
+            "auto_lock_long",
+            Severity::Low,
+            format!("Auto-lock waits more than {LONG_AUTO_LOCK_MINUTES} minutes."),
+        ));
+    }
+    if report.session_saved && report.quick_unlock.is_none() {
+        out.push(recommend(
+            "session_unprotected",
+            Severity::Medium,
+            "The saved session opens the vault without any PIN or other factor. Set up quick \
+             unlock or clear the saved session."
+                .to_string(),
+        ));
+    }
+    if report.kdf.millis < FAST_KDF_MS {
+        out.push(recommend(
+            "kdf_fast",
+            Severity::Medium,
+            format!(
+                "Deriving the master key takes {} ms on this machine. Stronger KDF parameters \
+                 would slow down password guessing.",
+                report.kdf.millis
+            ),
+        ));
+    } else if report.kdf.millis > SLOW_KDF_MS {
+        out.push(recommend(
+            "kdf_slow",
+            Severity::Low,
+            format!(
+                "Deriving the master key takes {} ms on this machine; lighter KDF parameters \
+                 would make unlocking quicker.",
+                report.kdf.millis
+            ),
+        ));
+    }
+    if report.content.v1 > 0 {
+        out.push(recommend(
+            "legacy_records",
+            Severity::Medium,
+            format!(
+                "{} records are still in the V1 format. Unlock with the master password to \
+                 upgrade them.",
+                report.content.v1
+            ),
+        ));
+    }
+    if report.keychain_backend == keychain::Backend::FileMachine.as_str() {
+        out.push(recommend(
+            "keychain_machine_key",
+            Severity::Medium,
+            "The keychain file is protected only by a key bound to this machine. Protect it \
+             with a PIN."
+                .to_string(),
+        ));
+    }
+    if !report.lock_on_screen_lock {
+        out.push(recommend(
+            "screen_lock_ignored",
+            Severity::Low,
+            "The vault stays unlocked while the screen is locked.".to_string(),
+        ));
+    }
+    if !report.database_encrypted {
+        out.push(recommend(
+            "database_not_encrypted",
+            Severity::Low,
+            "Content is encrypted, but the database file shows record counts and timestamps. \
+             Database encryption hides them."
+                .to_string(),
+        ));
+    }
+    if report.content.v2 > 0 {
+        out.push(recommend(
+            "unbound_records",
+            Severity::Low,
+            format!(
+                "{} records are not yet bound to their record; they are on their next save.",
+                report.content.v2
+            ),
+        ));
+    }
+    out.sort_by_key(|r| r.severity);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weak_settings_come_first() {
+        let mut report = SecurityReport {
+            kdf: KdfTiming { params: KdfParams::default(), millis: 500 },
+            format_version: Some(1),
+            schema_version: 1,
+            database_encrypted: true,
+            content: FormatCensus { v3: 4, ..FormatCensus::default() },
+            keychain_backend: keychain::Backend::Os.as_str().to_string(),
+            master_password_persisted: false,
+            session_saved: true,
+            quick_unlock: Some(QuickUnlockKind::Pin),
+            auto_lock_minutes: Some(5),
+            lock_on_screen_lock: true,
+            recommendations: Vec::new(),
+        };
+        assert!(recommendations(&report).is_empty());
+
+        report.kdf.millis = 40;
+        report.content.v1 = 2;
+        report.auto_lock_minutes = Some(0);
+        report.lock_on_screen_lock = false;
+        let ids: Vec<_> = recommendations(&report).iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["auto_lock_off", "kdf_fast", "legacy_records", "screen_lock_ignored"]);
+    }
+}
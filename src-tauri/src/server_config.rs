@@ -5,12 +5,24 @@ use serde::{Deserialize, Serialize};
 
 use crate::keychain;
 
-const KC_SERVERS: &str = "servers";
+pub const KC_SERVERS: &str = "servers";
+/// Per-server keychain entries, `srv-<server id>-<suffix>`. `master-password` is only written by
+/// versions that kept the server vault's password.
+const SERVER_KEY_SUFFIXES: [&str; 5] =
+    ["token", "master-password", "master-password-set", "pin-hash", "is-admin"];
 
 fn srv_key(server_id: &str, suffix: &str) -> String {
     format!("srv-{}-{}", server_id, suffix)
 }
 
+/// The server a per-server keychain key belongs to.
+pub fn server_of(key: &str) -> Option<&str> {
+    let rest = key.strip_prefix("srv-")?;
+    SERVER_KEY_SUFFIXES
+        .iter()
+        .find_map(|suffix| rest.strip_suffix(suffix)?.strip_suffix('-'))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub id: String,
@@ -52,10 +64,9 @@ pub fn remove_server(server_id: &str) -> Result<(), String> {
     }
     save_servers(&servers)?;
 
-    keychain::remove(&srv_key(server_id, "token"));
-    keychain::remove(&srv_key(server_id, "master-password"));
-    keychain::remove(&srv_key(server_id, "pin-hash"));
-    keychain::remove(&srv_key(server_id, "is-admin"));
+    for suffix in SERVER_KEY_SUFFIXES {
+        keychain::remove(&srv_key(server_id, suffix));
+    }
 
     Ok(())
 }
@@ -72,12 +83,19 @@ pub fn remove_server_token(server_id: &str) {
     keychain::remove(&srv_key(server_id, "token"));
 }
 
-pub fn get_server_master_password(server_id: &str) -> Option<String> {
-    keychain::get(&srv_key(server_id, "master-password"))
+/// Whether a master password was set up for the server's vault. The password itself is not
+/// kept: it is asked for once per session. A password kept by an older version is dropped here.
+pub fn has_server_master_password(server_id: &str) -> bool {
+    if keychain::get(&srv_key(server_id, "master-password")).is_some() {
+        let _ = mark_server_master_password(server_id);
+    }
+    keychain::get(&srv_key(server_id, "master-password-set")).is_some()
 }
 
-pub fn save_server_master_password(server_id: &str, password: &str) -> Result<(), String> {
-    keychain::save(&srv_key(server_id, "master-password"), password)
+pub fn mark_server_master_password(server_id: &str) -> Result<(), String> {
+    keychain::save(&srv_key(server_id, "master-password-set"), "true")?;
+    keychain::remove(&srv_key(server_id, "master-password"));
+    Ok(())
 }
 
 pub fn get_server_pin_hash(server_id: &str) -> Option<String> {
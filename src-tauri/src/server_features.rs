@@ -0,0 +1,116 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! What a self-hosted server says about itself on `/health`: its version, the oldest client it
+//! still accepts and the optional features it offers. The last answer per server URL is kept
+//! for the rest of the run, so feature checks do not cost a request each.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::storage::remote::{RemoteStorage, ServerHealth};
+
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub const FEATURE_CHECKLISTS: &str = "checklists";
+pub const FEATURE_DEVICES: &str = "devices";
+pub const FEATURE_EMERGENCY_ACCESS: &str = "emergency_access";
+pub const FEATURE_FOLDERS: &str = "folders";
+pub const FEATURE_SETTINGS_SYNC: &str = "settings_sync";
+pub const FEATURE_TEMPLATES: &str = "templates";
+
+static KNOWN: Mutex<Option<HashMap<String, ServerHealth>>> = Mutex::new(None);
+
+/// Numeric `major.minor.patch` parts; anything after a `-` or `+` is ignored.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_older(version: &str, minimum: &str) -> bool {
+    let (mut have, mut need) = (version_parts(version), version_parts(minimum));
+    let len = have.len().max(need.len());
+    have.resize(len, 0);
+    need.resize(len, 0);
+    have < need
+}
+
+/// `server_requires_newer_client:<version>` when this build is older than the server accepts.
+pub fn ensure_client_supported(health: &ServerHealth) -> Result<(), String> {
+    match health.min_client_version.as_deref() {
+        Some(minimum) if is_older(CLIENT_VERSION, minimum) => {
+            Err(format!("server_requires_newer_client:{minimum}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Query `/health`, remember the answer and refuse to go on if the server rejects this client.
+pub fn check(remote: &RemoteStorage) -> Result<ServerHealth, String> {
+    let health = remote.health_check().map_err(|e| e.to_string())?;
+    if let Ok(mut known) = KNOWN.lock() {
+        known
+            .get_or_insert_with(HashMap::new)
+            .insert(remote.base_url().to_string(), health.clone());
+    }
+    ensure_client_supported(&health)?;
+    Ok(health)
+}
+
+fn known(remote: &RemoteStorage) -> Result<ServerHealth, String> {
+    let cached = KNOWN
+        .lock()
+        .ok()
+        .and_then(|known| known.as_ref()?.get(remote.base_url()).cloned());
+    match cached {
+        Some(health) => {
+            ensure_client_supported(&health)?;
+            Ok(health)
+        }
+        None => check(remote),
+    }
+}
+
+/// Servers that do not list their features predate the list and are assumed to have them all.
+pub fn supports(remote: &RemoteStorage, feature: &str) -> Result<bool, String> {
+    Ok(known(remote)?
+        .features
+        .is_none_or(|features| features.iter().any(|f| f == feature)))
+}
+
+/// `server_feature_unavailable:<feature>` when the server does not offer `feature`.
+pub fn require(remote: &RemoteStorage, feature: &str) -> Result<(), String> {
+    if supports(remote, feature)? {
+        Ok(())
+    } else {
+        Err(format!("server_feature_unavailable:{feature}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimum_client_version_is_enforced() {
+        assert!(is_older("1.0.5", "1.1"));
+        assert!(is_older("1.0.5", "v1.0.10"));
+        assert!(!is_older("1.0.5", "1.0.5-beta"));
+        assert!(!is_older("1.2.0", "1.1.9"));
+
+        let mut health = ServerHealth::default();
+        assert!(ensure_client_supported(&health).is_ok());
+        health.min_client_version = Some("999.0.0".to_string());
+        assert_eq!(
+            ensure_client_supported(&health).unwrap_err(),
+            "server_requires_newer_client:999.0.0"
+        );
+    }
+}
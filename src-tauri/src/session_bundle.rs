@@ -0,0 +1,170 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Session bundles: the keychain state needed to pick up on a new machine (server profiles
+//! with their sign-in tokens, and this installation's device identity) in one file encrypted
+//! under a passphrase. Master passwords, PINs and the vault key are never included.
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::clock::Clock;
+use crate::crypto;
+use crate::device;
+use crate::keychain;
+use crate::server_config::{self, ServerConfig};
+
+const MAGIC: &[u8; 4] = b"VPSB";
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerEntry {
+    pub config: ServerConfig,
+    pub token: Option<String>,
+    pub is_admin: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    pub signing_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionBundle {
+    pub version: u32,
+    pub created_at: String,
+    pub device: Option<DeviceIdentity>,
+    pub servers: Vec<ServerEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub servers_added: usize,
+    pub servers_updated: usize,
+    pub device_restored: bool,
+}
+
+/// Gather the current keychain state.
+pub fn collect(clock: &dyn Clock) -> Result<SessionBundle, String> {
+    let servers = server_config::list_servers()
+        .into_iter()
+        .map(|config| ServerEntry {
+            token: server_config::get_server_token(&config.id),
+            is_admin: server_config::get_server_is_admin(&config.id),
+            config,
+        })
+        .collect();
+    Ok(SessionBundle {
+        version: BUNDLE_VERSION,
+        created_at: clock.now().to_rfc3339(),
+        device: Some(DeviceIdentity {
+            device_id: device::device_id()?,
+            signing_key: device::encoded_signing_key()?,
+        }),
+        servers,
+    })
+}
+
+/// `MAGIC || crypto::encrypt(json, passphrase)`.
+pub fn seal(bundle: &SessionBundle, passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut json = serde_json::to_vec(bundle).map_err(|e| e.to_string())?;
+    let sealed = crypto::encrypt(&json, passphrase);
+    json.zeroize();
+    let mut out = MAGIC.to_vec();
+    out.extend(sealed.map_err(|e| e.to_string())?);
+    Ok(out)
+}
+
+pub fn open(data: &[u8], passphrase: &str) -> Result<SessionBundle, String> {
+    let sealed = data
+        .strip_prefix(MAGIC)
+        .ok_or("Not a VaultPad session bundle")?;
+    let mut json = crypto::decrypt(sealed, passphrase)
+        .map_err(|_| "Wrong passphrase or damaged bundle".to_string())?;
+    let bundle: Result<SessionBundle, _> = serde_json::from_slice(&json);
+    json.zeroize();
+    let bundle = bundle.map_err(|e| format!("Invalid session bundle: {e}"))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err("Session bundle was made by a newer VaultPad".to_string());
+    }
+    Ok(bundle)
+}
+
+/// Write the bundle into the keychain. Servers are matched by id; a server whose database
+/// folder does not exist on this machine gets its database under the local `db-folder`.
+pub fn apply(bundle: SessionBundle) -> Result<ImportSummary, String> {
+    let local_folder = keychain::get("db-folder");
+    let mut servers = server_config::list_servers();
+    let mut summary = ImportSummary {
+        servers_added: 0,
+        servers_updated: 0,
+        device_restored: false,
+    };
+
+    for entry in bundle.servers {
+        let mut config = entry.config;
+        let folder_exists = std::path::Path::new(&config.db_path)
+            .parent()
+            .is_some_and(|dir| dir.is_dir());
+        if let (false, Some(folder)) = (folder_exists, &local_folder) {
+            let file_name = std::path::Path::new(&config.db_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("{}.db", config.id));
+            config.db_path = format!("{}/{}", folder.trim_end_matches('/'), file_name);
+        }
+
+        match servers.iter_mut().find(|s| s.id == config.id) {
+            Some(existing) => {
+                *existing = config.clone();
+                summary.servers_updated += 1;
+            }
+            None => {
+                servers.push(config.clone());
+                summary.servers_added += 1;
+            }
+        }
+        if let Some(token) = entry.token {
+            server_config::save_server_token(&config.id, &token)?;
+        }
+        server_config::save_server_is_admin(&config.id, entry.is_admin)?;
+    }
+    server_config::save_servers(&servers)?;
+
+    if let Some(identity) = bundle.device {
+        device::restore_identity(&identity.device_id, &identity.signing_key)?;
+        summary.device_restored = true;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_bundle_needs_the_passphrase() {
+        let bundle = SessionBundle {
+            version: BUNDLE_VERSION,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            device: None,
+            servers: vec![ServerEntry {
+                config: ServerConfig {
+                    id: "s1".to_string(),
+                    name: "Work".to_string(),
+                    url: "https://vault.example".to_string(),
+                    db_path: "/tmp/work.db".to_string(),
+                },
+                token: Some("token".to_string()),
+                is_admin: true,
+            }],
+        };
+        let sealed = seal(&bundle, "correct horse").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(open(&sealed, "wrong horse").is_err());
+        let opened = open(&sealed, "correct horse").unwrap();
+        assert_eq!(opened.servers[0].token.as_deref(), Some("token"));
+        assert!(open(&sealed[MAGIC.len()..], "correct horse").is_err());
+    }
+}
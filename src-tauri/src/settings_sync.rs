@@ -0,0 +1,193 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Preferences that follow the user to their other machines. Every setting that does not
+//! describe this device goes to the server as one blob encrypted with the vault key, which
+//! every device of a server context shares. Each device remembers what it last synced, so the
+//! blob is merged per setting: one whose value changed on one side only takes that value, one
+//! changed on both keeps this device's. Settings a device excludes are neither sent nor taken
+//! from the blob, and are left in it for the devices that do sync them.
+
+use std::collections::BTreeMap;
+
+use crate::backup_drill;
+use crate::backup_schedule;
+use crate::crypto;
+use crate::pin_attempts;
+use crate::server_features;
+use crate::storage::remote::RemoteStorage;
+use crate::storage::{StorageError, StorageProvider};
+use crate::system_idle;
+
+pub const SETTING_ENABLED: &str = "settings_sync_enabled";
+/// JSON list of further settings this device keeps to itself.
+pub const SETTING_EXCLUDED: &str = "settings_sync_excluded";
+/// The settings as of the last sync, JSON.
+const SETTING_BASE: &str = "settings_sync_base";
+
+/// Never synced: they describe this machine rather than the user.
+pub const DEVICE_SPECIFIC: [&str; 11] = [
+    SETTING_ENABLED,
+    SETTING_EXCLUDED,
+    SETTING_BASE,
+    "db_path",
+    "db_folder",
+    system_idle::SETTING_LOCK_ON_SCREEN_LOCK,
+    backup_drill::SETTING_BACKUP_DRILL,
+    backup_schedule::SETTING_BACKUP_SCHEDULE,
+    pin_attempts::SETTINGS[0],
+    pin_attempts::SETTINGS[1],
+    pin_attempts::SETTINGS[2],
+];
+
+type Settings = BTreeMap<String, String>;
+
+/// On unless turned off.
+pub fn is_enabled(storage: &dyn StorageProvider) -> bool {
+    storage
+        .get_setting(SETTING_ENABLED)
+        .ok()
+        .flatten()
+        .is_none_or(|v| v != "false")
+}
+
+pub fn excluded(storage: &dyn StorageProvider) -> Vec<String> {
+    storage
+        .get_setting(SETTING_EXCLUDED)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_preferences(
+    storage: &dyn StorageProvider,
+    enabled: bool,
+    excluded: &[String],
+) -> Result<(), String> {
+    let json = serde_json::to_string(excluded).map_err(|e| e.to_string())?;
+    storage
+        .set_setting(SETTING_ENABLED, if enabled { "true" } else { "false" })
+        .and_then(|_| storage.set_setting(SETTING_EXCLUDED, &json))
+        .map_err(|e| e.to_string())
+}
+
+fn is_synced(key: &str, excluded: &[String]) -> bool {
+    !DEVICE_SPECIFIC.contains(&key) && !excluded.iter().any(|k| k == key)
+}
+
+fn synced_only(settings: &Settings, excluded: &[String]) -> Settings {
+    settings
+        .iter()
+        .filter(|(key, _)| is_synced(key, excluded))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Per-key three-way merge; a key changed on both sides keeps the local value.
+fn merge(base: &Settings, local: &Settings, remote: &Settings) -> Settings {
+    let keys: std::collections::BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let value = if local.get(key) == base.get(key) { remote.get(key) } else { local.get(key) };
+            Some((key.clone(), value?.clone()))
+        })
+        .collect()
+}
+
+fn aad() -> Vec<u8> {
+    crypto::field_aad("settings", "sync")
+}
+
+fn open_blob(data: &[u8], key: &[u8; crypto::KEY_LEN]) -> Result<Settings, String> {
+    let json = crypto::try_decrypt_with_key_aad(data, key, &aad())
+        .ok_or("Cannot decrypt the synced settings")?;
+    serde_json::from_slice(&json).map_err(|e| e.to_string())
+}
+
+/// Exchange settings with the server. Returns whether any local setting changed. Servers
+/// without settings sync, and devices that turned it off, are skipped.
+pub fn sync(
+    local: &dyn StorageProvider,
+    remote: &RemoteStorage,
+    key: &[u8; crypto::KEY_LEN],
+    now: &str,
+) -> Result<bool, String> {
+    if !is_enabled(local)
+        || !server_features::supports(remote, server_features::FEATURE_SETTINGS_SYNC)?
+    {
+        return Ok(false);
+    }
+    let theirs = match remote.get_settings() {
+        Ok(Some(data)) => open_blob(&data, key)?,
+        Ok(None) => Settings::new(),
+        Err(StorageError::NotFound(_)) => return Ok(false),
+        Err(e) => return Err(e.to_string()),
+    };
+    let excluded = excluded(local);
+    let base: Settings = local
+        .get_setting(SETTING_BASE)
+        .map_err(|e| e.to_string())?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let ours: Settings = local.list_settings().map_err(|e| e.to_string())?.into_iter().collect();
+    let ours = synced_only(&ours, &excluded);
+    let merged = merge(&base, &ours, &synced_only(&theirs, &excluded));
+
+    let mut changed = false;
+    for (name, value) in &merged {
+        if ours.get(name) != Some(value) {
+            local.set_setting(name, value).map_err(|e| e.to_string())?;
+            changed = true;
+        }
+    }
+
+    let mut upload: Settings = theirs
+        .iter()
+        .filter(|(name, _)| !is_synced(name, &excluded))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    upload.extend(merged.clone());
+    if upload != theirs {
+        let json = serde_json::to_vec(&upload).map_err(|e| e.to_string())?;
+        let data = crypto::encrypt_with_key_aad(&json, key, &aad()).map_err(|e| e.to_string())?;
+        remote.put_settings(&data, now).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+    local.set_setting(SETTING_BASE, &json).map_err(|e| e.to_string())?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(pairs: &[(&str, &str)]) -> Settings {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn each_side_keeps_its_own_changes() {
+        let base = settings(&[("theme", "light"), ("sort", "name"), ("trash_retention_days", "30")]);
+        let local = settings(&[("theme", "dark"), ("sort", "name"), ("trash_retention_days", "7")]);
+        let remote = settings(&[
+            ("theme", "light"),
+            ("sort", "updated"),
+            ("trash_retention_days", "14"),
+            ("version_retention", "50"),
+        ]);
+        assert_eq!(
+            merge(&base, &local, &remote),
+            settings(&[
+                ("theme", "dark"),
+                ("sort", "updated"),
+                ("trash_retention_days", "7"),
+                ("version_retention", "50"),
+            ])
+        );
+
+        let excluded = vec!["theme".to_string()];
+        let local = settings(&[("theme", "dark"), (SETTING_BASE, "{}"), ("sort", "name")]);
+        assert_eq!(synced_only(&local, &excluded), settings(&[("sort", "name")]));
+    }
+}
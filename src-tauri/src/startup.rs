@@ -0,0 +1,207 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Recovering from a vault that will not open at startup. Opening failures are reported as one
+//! of the `ERR_*` codes below instead of a raw SQLite message, so the frontend can explain what
+//! happened; it can then look for other vault files and copies on this machine and open one of
+//! those instead.
+
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::display_info;
+use crate::storage::local::{FormatInfo, LocalStorage};
+use crate::storage::shard::{shard_path, SHARD_SUFFIX};
+use crate::storage::{cipher, StorageError};
+
+pub const ERR_MISSING: &str = "vault_missing";
+pub const ERR_PERMISSION: &str = "vault_permission_denied";
+pub const ERR_CORRUPT: &str = "vault_corrupt";
+/// Open in another instance of the app.
+pub const ERR_IN_USE: &str = "vault_in_use";
+/// Written by a newer version; `inspect_database` tells which and whether read-only works.
+pub const ERR_TOO_NEW: &str = "vault_too_new";
+/// Anything else, followed by `:` and the underlying message.
+pub const ERR_OPEN_FAILED: &str = "vault_open_failed";
+
+const VAULT_EXT: &str = ".db";
+const COPY_EXT: &str = ".bak";
+const RESTORED_SUFFIX: &str = "-restored";
+
+/// SQLite's messages for a file that is not, or no longer, a readable database.
+const CORRUPTION_MESSAGES: [&str; 2] = ["file is not a database", "database disk image is malformed"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateKind {
+    Vault,
+    /// A `<vault>*.bak` copy kept next to a vault.
+    Copy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultCandidate {
+    pub path: String,
+    pub kind: CandidateKind,
+    pub size: u64,
+    pub modified_at: Option<String>,
+    /// Opened on this machine before.
+    pub known: bool,
+    /// `None` when the file cannot be read as a vault at all.
+    pub format: Option<FormatInfo>,
+}
+
+/// Open the existing vault at `db_path`, with any failure turned into one of the `ERR_*` codes.
+pub fn open(db_path: &str) -> Result<LocalStorage, String> {
+    if !Path::new(db_path).exists() {
+        return Err(ERR_MISSING.to_string());
+    }
+    LocalStorage::new(db_path).map_err(|e| classify(db_path, &e))
+}
+
+/// The `ERR_*` code for a failure to open the vault at `db_path`.
+pub fn classify(db_path: &str, err: &StorageError) -> String {
+    match err {
+        StorageError::VaultTooNew { .. } | StorageError::SchemaTooNew { .. } => {
+            ERR_TOO_NEW.to_string()
+        }
+        StorageError::InUse => ERR_IN_USE.to_string(),
+        _ if permission_denied(db_path) => ERR_PERMISSION.to_string(),
+        StorageError::Database(message) if is_corruption(message) => ERR_CORRUPT.to_string(),
+        StorageError::Database(message) if message.contains("readonly database") => {
+            ERR_PERMISSION.to_string()
+        }
+        _ => format!("{ERR_OPEN_FAILED}:{err}"),
+    }
+}
+
+fn permission_denied(db_path: &str) -> bool {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(db_path)
+        .is_err_and(|e| e.kind() == ErrorKind::PermissionDenied)
+}
+
+fn is_corruption(message: &str) -> bool {
+    let message = message.to_lowercase();
+    CORRUPTION_MESSAGES.iter().any(|m| message.contains(m))
+}
+
+/// Vault files and their copies in the folders of every vault opened here before, `folder`
+/// (the remembered database folder) and `default_folder`. Known vaults that are gone are left
+/// out. Newest first.
+pub fn locate_candidates(
+    folder: Option<&str>,
+    default_folder: Option<PathBuf>,
+) -> Vec<VaultCandidate> {
+    let known: BTreeSet<String> = display_info::known().into_iter().map(|v| v.db_path).collect();
+    let mut dirs: BTreeSet<PathBuf> = known
+        .iter()
+        .filter_map(|path| Path::new(path).parent().map(Path::to_path_buf))
+        .collect();
+    dirs.extend(folder.map(PathBuf::from));
+    dirs.extend(default_folder);
+
+    let mut candidates: Vec<VaultCandidate> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter_map(|path| candidate(&path, &known))
+        .collect();
+    candidates.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    candidates
+}
+
+fn candidate(path: &Path, known: &BTreeSet<String>) -> Option<VaultCandidate> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    if name.ends_with(SHARD_SUFFIX) {
+        return None;
+    }
+    let kind = if name.ends_with(VAULT_EXT) {
+        CandidateKind::Vault
+    } else if name.ends_with(COPY_EXT) && name.contains(VAULT_EXT) {
+        CandidateKind::Copy
+    } else {
+        return None;
+    };
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let path = path.to_string_lossy().to_string();
+    Some(VaultCandidate {
+        kind,
+        size: metadata.len(),
+        modified_at: metadata
+            .modified()
+            .ok()
+            .map(|at| DateTime::<Utc>::from(at).to_rfc3339()),
+        known: known.contains(&path),
+        format: LocalStorage::inspect(&path).ok(),
+        path,
+    })
+}
+
+/// Copy the vault file or copy at `source` to a new vault next to it, `<name>-restored.db`,
+/// and return the new path. The source is left untouched so it can be tried again.
+pub fn copy_as_vault(source: &str) -> Result<String, String> {
+    let source_path = Path::new(source);
+    if !source_path.is_file() {
+        return Err(ERR_MISSING.to_string());
+    }
+    let dir = source_path.parent().unwrap_or(Path::new("."));
+    let stem = source_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = stem.trim_end_matches(COPY_EXT).trim_end_matches(VAULT_EXT).to_string();
+    let target = (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{stem}{RESTORED_SUFFIX}{VAULT_EXT}")),
+            n => dir.join(format!("{stem}{RESTORED_SUFFIX}-{n}{VAULT_EXT}")),
+        })
+        .find(|path| !path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    std::fs::copy(source, &target).map_err(|e| e.to_string())?;
+    let companions = [
+        (cipher::header_path(source), cipher::header_path(&target)),
+        (shard_path(source), shard_path(&target)),
+    ];
+    for (from, to) in companions {
+        if from.exists() {
+            std::fs::copy(&from, &to).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_failures_are_categorized() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-startup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("gone.db").to_string_lossy().to_string();
+        assert_eq!(open(&missing).err().as_deref(), Some(ERR_MISSING));
+
+        let garbage = dir.join("garbage.db");
+        std::fs::write(&garbage, b"SQLite format 3\0 but then nothing sensible at all".repeat(100))
+            .unwrap();
+        let garbage = garbage.to_string_lossy().to_string();
+        assert_eq!(open(&garbage).err().as_deref(), Some(ERR_CORRUPT));
+
+        let vault = dir.join("vault.db").to_string_lossy().to_string();
+        drop(LocalStorage::new(&vault).unwrap());
+        let copy = copy_as_vault(&vault).unwrap();
+        assert!(copy.ends_with("vault-restored.db"));
+        assert!(open(&copy).is_ok());
+        assert!(copy_as_vault(&vault).unwrap().ends_with("vault-restored-2.db"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,310 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Full-database encryption with SQLCipher. Content is always encrypted field by field; in this
+//! mode the SQLite file is encrypted as a whole too, under a key derived from the vault key, so
+//! row counts, timestamps, sort order and sync status are no longer readable from it.
+//!
+//! What an unlock needs before that key is known (the key slots, KDF parameters, verification
+//! token and the quick-unlock attempt counters) is mirrored into a plain sidecar,
+//! `<vault>.unlock.json`, holding nothing that was not already readable in a plain vault. The
+//! attachment shard stays a plain database: its chunks are ciphertext already.
+//!
+//! The sidecar is the only copy of the key slots of an encrypted vault: a vault whose sidecar is
+//! lost cannot be unlocked, even with the right password, and has to be restored from a backup.
+//! It has to be copied, moved and backed up together with the database file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use hkdf::Hkdf;
+use rusqlite::{params, Connection, ErrorCode, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::migrations::META_SCHEMA_VERSION;
+use super::StorageError;
+use crate::crypto;
+use crate::pin_attempts;
+use crate::vault;
+
+pub const HEADER_SUFFIX: &str = ".unlock.json";
+const KEY_INFO: &[u8] = b"vaultpad-sqlcipher-v1";
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+/// SQLCipher 4's page size; an encrypted file is a whole number of such pages.
+const CIPHER_PAGE_SIZE: u64 = 4096;
+
+/// `vault_meta` entries an unlock reads.
+pub const UNLOCK_META: [&str; 12] = [
+    vault::META_FORMAT_VERSION,
+    vault::META_READ_COMPAT_VERSION,
+    META_SCHEMA_VERSION,
+    vault::META_KDF_PARAMS,
+    vault::META_PASSWORD_SLOT,
+    vault::META_FIDO_KEYS,
+    vault::META_CHALLENGE_RESPONSE,
+    vault::META_RECOVERY_SLOT,
+    vault::META_SHAMIR_SLOT,
+    vault::META_DURESS_SLOT,
+    // For unlocking with a backup file: its vault id and signature are checked first.
+    vault::META_VAULT_ID,
+    vault::META_BACKUP_SIGNING_KEY,
+];
+
+/// Settings read and written before the vault is unlocked. An encrypted vault keeps them in
+/// the sidecar only.
+pub const UNLOCK_SETTINGS: [&str; 3] = pin_attempts::SETTINGS;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Header {
+    /// Base64 of the verification token.
+    pub verification_token: Option<String>,
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+fn db_err(e: rusqlite::Error) -> StorageError {
+    StorageError::Database(e.to_string())
+}
+
+pub fn header_path(db_path: &str) -> PathBuf {
+    PathBuf::from(format!("{db_path}{HEADER_SUFFIX}"))
+}
+
+/// Whether the file at `db_path` is an SQLCipher database: whole SQLCipher pages, starting with
+/// a salt rather than the SQLite header, that SQLite itself cannot read without a key. Empty,
+/// truncated or otherwise unreadable files are not taken for encrypted ones.
+pub fn is_encrypted(db_path: &str) -> bool {
+    let mut magic = [0u8; 16];
+    let salted = std::fs::File::open(db_path)
+        .and_then(|mut file| {
+            let len = file.metadata()?.len();
+            std::io::Read::read_exact(&mut file, &mut magic)?;
+            Ok(len >= CIPHER_PAGE_SIZE && len % CIPHER_PAGE_SIZE == 0)
+        })
+        .is_ok_and(|whole_pages| whole_pages && &magic != SQLITE_MAGIC);
+    salted && needs_key(db_path)
+}
+
+/// Whether SQLite refuses the file at `db_path` as not a database when it is opened unkeyed.
+fn needs_key(db_path: &str) -> bool {
+    let Ok(conn) = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return false;
+    };
+    match conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)) {
+        Err(rusqlite::Error::SqliteFailure(e, _)) => e.code == ErrorCode::NotADatabase,
+        _ => false,
+    }
+}
+
+/// The SQLCipher key of a vault, derived from its vault key.
+pub fn database_key(vault_key: &[u8; crypto::KEY_LEN]) -> Zeroizing<[u8; crypto::KEY_LEN]> {
+    let mut key = Zeroizing::new([0u8; crypto::KEY_LEN]);
+    Hkdf::<Sha256>::new(None, vault_key)
+        .expand(KEY_INFO, &mut *key)
+        .expect("32 bytes is a valid HKDF output length");
+    key
+}
+
+/// `"x'…'"`, SQLCipher's notation for a raw key, or `''` for no encryption.
+fn key_literal(db_key: Option<&[u8; crypto::KEY_LEN]>) -> Zeroizing<String> {
+    Zeroizing::new(match db_key {
+        Some(key) => format!("\"x'{}'\"", hex::encode_upper(key)),
+        None => "''".to_string(),
+    })
+}
+
+/// Key a freshly opened connection and check the key against the file.
+pub fn apply_key(conn: &Connection, db_key: &[u8; crypto::KEY_LEN]) -> Result<(), StorageError> {
+    conn.execute_batch(&format!("PRAGMA key = {};", *key_literal(Some(db_key))))
+        .map_err(db_err)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map(|_| ())
+        .map_err(|_| StorageError::Database("Wrong key for the encrypted database".to_string()))
+}
+
+/// Re-encrypt an open, keyed database under a new key.
+pub fn rekey(conn: &Connection, db_key: &[u8; crypto::KEY_LEN]) -> Result<(), StorageError> {
+    conn.execute_batch(&format!("PRAGMA rekey = {};", *key_literal(Some(db_key))))
+        .map_err(db_err)
+}
+
+pub fn load_header(path: &Path) -> Header {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_header(path: &Path, header: &Header) -> Result<(), StorageError> {
+    let json = serde_json::to_string_pretty(header).map_err(|e| StorageError::Io(e.to_string()))?;
+    let partial = path.with_extension("json.part");
+    std::fs::write(&partial, json)
+        .and_then(|_| std::fs::rename(&partial, path))
+        .map_err(|e| StorageError::Io(e.to_string()))
+}
+
+pub fn update_header(path: &Path, f: impl FnOnce(&mut Header)) -> Result<(), StorageError> {
+    let mut header = load_header(path);
+    f(&mut header);
+    save_header(path, &header)
+}
+
+/// Copy the unlock material of the open database `conn` into the sidecar. Attempt counters
+/// are taken from the database only when the sidecar does not exist yet.
+pub fn refresh_header(conn: &Connection, path: &Path) -> Result<(), StorageError> {
+    let fresh = !path.exists();
+    let token: Option<Vec<u8>> = conn
+        .query_row("SELECT token FROM verification WHERE id = 1", [], |row| row.get(0))
+        .optional()
+        .map_err(db_err)?;
+    let mut meta = BTreeMap::new();
+    for key in UNLOCK_META {
+        let value: Option<String> = conn
+            .query_row("SELECT value FROM vault_meta WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(db_err)?;
+        if let Some(value) = value {
+            meta.insert(key.to_string(), value);
+        }
+    }
+    let mut settings = BTreeMap::new();
+    if fresh {
+        for key in UNLOCK_SETTINGS {
+            let value: Option<String> = conn
+                .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+                .optional()
+                .map_err(db_err)?;
+            if let Some(value) = value {
+                settings.insert(key.to_string(), value);
+            }
+        }
+    }
+    update_header(path, |header| {
+        header.verification_token = token.map(|t| B64.encode(t));
+        header.meta = meta;
+        if fresh {
+            header.settings = settings;
+        }
+    })
+}
+
+/// Rewrite the vault at `db_path` encrypted under `to`, or as a plain database for `None`,
+/// reading it with `from`. The caller must have closed every other connection to it. The
+/// file is replaced only once the copy is complete.
+pub fn convert(
+    db_path: &str,
+    from: Option<&[u8; crypto::KEY_LEN]>,
+    to: Option<&[u8; crypto::KEY_LEN]>,
+) -> Result<(), StorageError> {
+    let header = header_path(db_path);
+    let converted = format!("{db_path}.converting");
+    let _ = std::fs::remove_file(&converted);
+
+    let conn = Connection::open(db_path).map_err(db_err)?;
+    if let Some(key) = from {
+        apply_key(&conn, key)?;
+    }
+    conn.execute(
+        &format!("ATTACH DATABASE ?1 AS converted KEY {}", *key_literal(to)),
+        params![converted],
+    )
+    .map_err(db_err)?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))
+        .and_then(|_| conn.execute_batch("DETACH DATABASE converted;"))
+        .map_err(db_err);
+    if let Err(e) = exported {
+        drop(conn);
+        let _ = std::fs::remove_file(&converted);
+        return Err(e);
+    }
+
+    if to.is_some() {
+        refresh_header(&conn, &header)?;
+    } else {
+        // Attempt counters move back into the settings table.
+        let plain = Connection::open(&converted).map_err(db_err)?;
+        for (key, value) in load_header(&header).settings {
+            plain
+                .execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                    params![key, value],
+                )
+                .map_err(db_err)?;
+        }
+    }
+    drop(conn);
+
+    std::fs::rename(&converted, db_path).map_err(|e| StorageError::Io(e.to_string()))?;
+    if to.is_none() {
+        let _ = std::fs::remove_file(&header);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::StorageProvider;
+
+    #[test]
+    fn converted_vault_unlocks_from_the_sidecar() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-cipher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+        let vault_key = [3u8; crypto::KEY_LEN];
+        {
+            let storage = LocalStorage::new(&db_path).unwrap();
+            storage.set_verification_token(b"token").unwrap();
+            storage.set_meta(vault::META_PASSWORD_SLOT, "slot").unwrap();
+            storage.set_setting("theme", "dark").unwrap();
+        }
+
+        convert(&db_path, None, Some(&database_key(&vault_key))).unwrap();
+        assert!(is_encrypted(&db_path));
+        let storage = LocalStorage::new(&db_path).unwrap();
+        assert!(storage.is_encrypted());
+        assert_eq!(storage.get_verification_token().unwrap().as_deref(), Some(&b"token"[..]));
+        assert_eq!(storage.get_meta(vault::META_PASSWORD_SLOT).unwrap().as_deref(), Some("slot"));
+        assert!(storage.set_setting("theme", "light").is_err());
+
+        assert!(storage.unseal(&[4u8; crypto::KEY_LEN]).is_err());
+        storage.unseal(&vault_key).unwrap();
+        assert_eq!(storage.get_setting("theme").unwrap().as_deref(), Some("dark"));
+        drop(storage);
+
+        convert(&db_path, Some(&database_key(&vault_key)), None).unwrap();
+        assert!(!is_encrypted(&db_path));
+        assert!(!header_path(&db_path).exists());
+        assert!(!LocalStorage::new(&db_path).unwrap().is_encrypted());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn only_sqlcipher_files_count_as_encrypted() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-ciphered-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+
+        assert!(!is_encrypted(&path("missing.db")));
+        std::fs::write(path("empty.db"), b"").unwrap();
+        assert!(!is_encrypted(&path("empty.db")));
+        std::fs::write(path("short.db"), b"not a database at all").unwrap();
+        assert!(!is_encrypted(&path("short.db")));
+        std::fs::write(path("ragged.db"), vec![0x5a; CIPHER_PAGE_SIZE as usize + 100]).unwrap();
+        assert!(!is_encrypted(&path("ragged.db")));
+
+        let db_path = path("vault.db");
+        LocalStorage::new(&db_path).unwrap();
+        assert!(!is_encrypted(&db_path));
+        convert(&db_path, None, Some(&database_key(&[5u8; crypto::KEY_LEN]))).unwrap();
+        assert!(is_encrypted(&db_path));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
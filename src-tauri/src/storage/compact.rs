@@ -0,0 +1,121 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Giving back the space of deleted data. SQLite keeps freed pages in the file for reuse, old
+//! contents and all, so a vault never shrinks and deleted ciphertext stays readable in it.
+//! `secure_delete` (on for every vault connection) zeroes what is freed from then on; `VACUUM`
+//! rebuilds the file from its live pages and drops the free ones, including those freed before.
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::StorageError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactTarget {
+    Vault,
+    Attachments,
+}
+
+/// Sent before each database is rebuilt.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CompactProgress {
+    pub target: CompactTarget,
+    /// 1-based.
+    pub step: usize,
+    pub total_steps: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompactStats {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Free pages that were dropped, each of which may have held deleted data.
+    pub free_pages: u64,
+}
+
+impl CompactStats {
+    pub fn add(&mut self, other: CompactStats) {
+        self.bytes_before += other.bytes_before;
+        self.bytes_after += other.bytes_after;
+        self.free_pages += other.free_pages;
+    }
+}
+
+fn db_err(e: rusqlite::Error) -> StorageError {
+    StorageError::Database(e.to_string())
+}
+
+fn pragma(conn: &Connection, name: &str) -> Result<u64, StorageError> {
+    conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get::<_, i64>(0))
+        .map(|v| v as u64)
+        .map_err(db_err)
+}
+
+/// Rebuild the database behind `conn` without its free pages. In WAL mode the log is
+/// truncated too, since it still holds copies of the old pages.
+pub fn vacuum(conn: &Connection, wal: bool) -> Result<CompactStats, StorageError> {
+    let page_size = pragma(conn, "page_size")?;
+    let bytes_before = pragma(conn, "page_count")? * page_size;
+    let free_pages = pragma(conn, "freelist_count")?;
+
+    conn.execute_batch("PRAGMA secure_delete = ON; VACUUM;").map_err(db_err)?;
+    if wal {
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(db_err)?;
+    }
+    Ok(CompactStats {
+        bytes_before,
+        bytes_after: pragma(conn, "page_count")? * pragma(conn, "page_size")?,
+        free_pages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::StorageProvider;
+
+    #[test]
+    fn deleted_projects_give_their_pages_back() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-compact-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+        let storage = LocalStorage::new(&db_path).unwrap();
+        for i in 0..20 {
+            storage
+                .create_project(&Project {
+                    id: format!("p{i}"),
+                    name: format!("p{i}"),
+                    encrypted_content: vec![0xAB; 64 * 1024],
+                    key_check: Vec::new(),
+                    position: String::new(),
+                    created_at: "2026-01-01T00:00:00Z".to_string(),
+                    updated_at: "2026-01-01T00:00:00Z".to_string(),
+                    server_id: None,
+                    sync_status: "local".to_string(),
+                    last_synced_at: None,
+                    folder_id: None,
+                    revision: 0,
+                    pinned: false,
+                })
+                .unwrap();
+        }
+        for i in 0..20 {
+            storage.delete_project(&format!("p{i}")).unwrap();
+        }
+
+        let mut steps = Vec::new();
+        let stats = storage.compact(&mut |p| steps.push(p.target)).unwrap();
+        assert_eq!(steps, [CompactTarget::Vault, CompactTarget::Attachments]);
+        assert!(stats.free_pages > 0);
+        assert!(stats.bytes_after < stats.bytes_before);
+        drop(storage);
+        let wal = std::fs::metadata(format!("{db_path}-wal")).map(|m| m.len()).unwrap_or(0);
+        assert_eq!(wal, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,139 +1,675 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
-use rusqlite::{params, Connection};
-use std::sync::Mutex;
+use rusqlite::blob::ZeroBlob;
+use rusqlite::{params, Connection, DatabaseName, OpenFlags, OptionalExtension, Params, Row, ToSql};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
-use crate::models::{Project, ProjectBackup};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+
+use crate::models::{
+    Attachment, AuditEvent, Change, ChangeFeed, ChecklistItem, Draft, Folder, PasswordHistoryEntry, Project,
+    ProjectAppearance, ProjectBackup, ProjectField, ProjectFilter, ProjectTotp, ProjectVersion,
+    SearchIndexEntry, Tag, Template,
+};
+use crate::crypto;
+use crate::ordering;
+use crate::vault::{
+    META_FORMAT_VERSION, META_NONCE_COUNTER, META_NONCE_PREFIX, META_READ_COMPAT_VERSION,
+    VAULT_FORMAT_VERSION,
+};
+use super::cipher;
+use super::compact::{self, CompactProgress, CompactStats, CompactTarget};
+use super::lock;
+use super::metrics::{Metrics, Operation, StorageMetrics};
+use super::migrations;
+use super::shard::{BlobInfo, ShardStore};
 use super::{StorageError, StorageProvider};
 
+/// Change feed entries kept; a reader further behind than this gets `reset`.
+const CHANGE_LOG_RETAINED: i64 = 10_000;
+
+/// Project statements, also the text the slow-operation log shows for them.
+const SQL_LIST_PROJECTS: &str = "SELECT id, name, encrypted_content, key_check,
+        position, created_at, updated_at, server_id, sync_status, last_synced_at, folder_id, revision,
+        pinned
+     FROM projects ORDER BY position ASC, created_at ASC, id ASC";
+const SQL_GET_PROJECT: &str = "SELECT id, name, encrypted_content, key_check,
+        position, created_at, updated_at, server_id, sync_status, last_synced_at, folder_id, revision,
+        pinned
+     FROM projects WHERE id = ?1";
+const SQL_GET_PROJECT_META: &str = "SELECT id, name, x'', key_check,
+        position, created_at, updated_at, server_id, sync_status, last_synced_at, folder_id, revision,
+        pinned
+     FROM projects WHERE id = ?1";
+const SQL_INSERT_PROJECT: &str = "INSERT INTO projects (id, name, encrypted_content, key_check,
+        position, created_at, updated_at, server_id, sync_status, last_synced_at, folder_id, pinned)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)";
+const SQL_UPDATE_PROJECT: &str = "UPDATE projects SET name = ?2, encrypted_content = ?3,
+        key_check = ?4, position = CASE WHEN ?5 = '' THEN position ELSE ?5 END,
+        updated_at = ?6, server_id = ?7, sync_status = ?8, last_synced_at = ?9,
+        revision = revision + 1
+     WHERE id = ?1";
+const SQL_DELETE_PROJECT: &str = "DELETE FROM projects WHERE id = ?1";
+
+/// Prepared statements kept per connection; more than `LocalStorage` has distinct statements.
+const STATEMENT_CACHE_SIZE: usize = 128;
+
+/// `execute` and `query_row` through the connection's prepared-statement cache, so the SQL
+/// of a statement run again is not parsed and planned again. With thousands of projects that
+/// is a large part of a list or a sync scan.
+trait CachedStatements {
+    fn execute_cached<P: Params>(&self, sql: &str, params: P) -> rusqlite::Result<usize>;
+    fn query_row_cached<T, P, F>(&self, sql: &str, params: P, f: F) -> rusqlite::Result<T>
+    where
+        P: Params,
+        F: FnOnce(&Row<'_>) -> rusqlite::Result<T>;
+}
+
+impl CachedStatements for Connection {
+    fn execute_cached<P: Params>(&self, sql: &str, params: P) -> rusqlite::Result<usize> {
+        self.prepare_cached(sql)?.execute(params)
+    }
+
+    fn query_row_cached<T, P, F>(&self, sql: &str, params: P, f: F) -> rusqlite::Result<T>
+    where
+        P: Params,
+        F: FnOnce(&Row<'_>) -> rusqlite::Result<T>,
+    {
+        self.prepare_cached(sql)?.query_row(params, f)
+    }
+}
+
+/// `PRAGMA synchronous` levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    /// With WAL a power cut can lose the last transactions, but never corrupts the database.
+    Normal,
+    Full,
+}
+
+impl Synchronous {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "off" => Some(Synchronous::Off),
+            "normal" => Some(Synchronous::Normal),
+            "full" => Some(Synchronous::Full),
+            _ => None,
+        }
+    }
+
+    fn pragma(self) -> &'static str {
+        match self {
+            Synchronous::Off => "OFF",
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+        }
+    }
+}
+
+/// How connections to a vault are set up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tuning {
+    /// Write-ahead logging, so a long write does not block readers.
+    pub wal: bool,
+    /// How long a statement waits on another connection's lock before failing with "database
+    /// is locked".
+    pub busy_timeout: Duration,
+    pub synchronous: Synchronous,
+    pub foreign_keys: bool,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            wal: true,
+            busy_timeout: Duration::from_secs(5),
+            synchronous: Synchronous::Normal,
+            foreign_keys: true,
+        }
+    }
+}
+
+impl Tuning {
+    /// The defaults, overridden by `VAULTPAD_DB_WAL` (`0`/`1`), `VAULTPAD_DB_BUSY_TIMEOUT_MS`
+    /// and `VAULTPAD_DB_SYNCHRONOUS` (`off`/`normal`/`full`) where set. Unparsable values are
+    /// ignored.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            wal: var("VAULTPAD_DB_WAL").map_or(defaults.wal, |v| v.trim() != "0"),
+            busy_timeout: var("VAULTPAD_DB_BUSY_TIMEOUT_MS")
+                .and_then(|v| v.trim().parse().ok())
+                .map_or(defaults.busy_timeout, Duration::from_millis),
+            synchronous: var("VAULTPAD_DB_SYNCHRONOUS")
+                .and_then(|v| Synchronous::parse(v.trim()))
+                .unwrap_or(defaults.synchronous),
+            foreign_keys: defaults.foreign_keys,
+        }
+    }
+}
+
+/// Apply `tuning` to a new connection. A read-only one only gets the busy timeout: the
+/// journal mode is a property of the file. Deleted data is always overwritten with zeros
+/// (see `compact`).
+fn tune(conn: &Connection, tuning: &Tuning, read_only: bool) -> Result<(), StorageError> {
+    let db_err = |e: rusqlite::Error| StorageError::Database(e.to_string());
+    conn.busy_timeout(tuning.busy_timeout).map_err(db_err)?;
+    if read_only {
+        return Ok(());
+    }
+    let journal_mode = if tuning.wal { "WAL" } else { "DELETE" };
+    conn.query_row(&format!("PRAGMA journal_mode = {journal_mode}"), [], |_| Ok(()))
+        .map_err(db_err)?;
+    conn.execute_batch(&format!(
+        "PRAGMA synchronous = {}; PRAGMA secure_delete = ON;",
+        tuning.synchronous.pragma()
+    ))
+    .map_err(db_err)
+}
+
 pub struct LocalStorage {
-    conn: Mutex<Connection>,
+    /// Shared with the nonce reservation, which holds it weakly.
+    conn: Arc<Mutex<Connection>>,
+    metrics: Metrics,
+    read_only: bool,
+    tuning: Tuning,
+    shard: ShardStore,
+    db_path: String,
+    /// The unlock sidecar of a vault encrypted with SQLCipher.
+    header: Option<PathBuf>,
+    /// Set while an encrypted vault waits for its key.
+    sealed: AtomicBool,
+    /// Keeps other app instances out of the vault while it is open here.
+    _lock: Option<Arc<File>>,
+}
+
+/// Format versions recorded in a database's `vault_meta`, compared with what this build reads.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatInfo {
+    pub format_version: Option<u32>,
+    /// Oldest format version a reader must understand to open the vault read-only.
+    pub read_compat_version: Option<u32>,
+    pub supported_version: u32,
+    pub too_new: bool,
+    pub read_only_possible: bool,
+    /// Migrations applied to the database; 0 for a new one.
+    pub schema_version: u32,
+    pub supported_schema_version: u32,
+    /// The database file is encrypted with SQLCipher.
+    pub encrypted: bool,
+}
+
+fn format_info(conn: &Connection) -> Result<FormatInfo, StorageError> {
+    let has_meta: bool = conn
+        .query_row_cached(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'vault_meta'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    let read = |key: &str| -> Result<Option<u32>, StorageError> {
+        if !has_meta {
+            return Ok(None);
+        }
+        let value: Option<String> = conn
+            .query_row_cached("SELECT value FROM vault_meta WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(value.and_then(|v| v.parse().ok()))
+    };
+    format_info_from(read, false)
+}
+
+/// Format versions of an encrypted vault, from its unlock sidecar.
+fn header_format_info(path: &Path) -> Result<FormatInfo, StorageError> {
+    let header = cipher::load_header(path);
+    format_info_from(|key| Ok(header.meta.get(key).and_then(|v| v.parse().ok())), true)
+}
+
+fn format_info_from(
+    read: impl Fn(&str) -> Result<Option<u32>, StorageError>,
+    encrypted: bool,
+) -> Result<FormatInfo, StorageError> {
+    let format_version = read(META_FORMAT_VERSION)?;
+    let read_compat_version = read(META_READ_COMPAT_VERSION)?;
+    let too_new = format_version.is_some_and(|v| v > VAULT_FORMAT_VERSION);
+    Ok(FormatInfo {
+        format_version,
+        read_compat_version,
+        supported_version: VAULT_FORMAT_VERSION,
+        too_new,
+        read_only_possible: too_new
+            && read_compat_version.is_some_and(|v| v <= VAULT_FORMAT_VERSION),
+        schema_version: read(migrations::META_SCHEMA_VERSION)?.unwrap_or(0),
+        supported_schema_version: migrations::SCHEMA_VERSION,
+        encrypted,
+    })
+}
+
+/// Record this build's format in a vault it opens for writing: the blobs it writes cannot be
+/// read by builds of an older format, even read-only.
+fn stamp_format(conn: &Connection) -> Result<(), StorageError> {
+    conn.execute_cached(
+        "UPDATE vault_meta SET value = ?1
+         WHERE key IN (?2, ?3) AND CAST(value AS INTEGER) < ?4",
+        params![
+            VAULT_FORMAT_VERSION.to_string(),
+            META_FORMAT_VERSION,
+            META_READ_COMPAT_VERSION,
+            VAULT_FORMAT_VERSION
+        ],
+    )
+    .map_err(|e| StorageError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Insert `project`, after all others when it has no position.
+fn insert_project(conn: &Connection, project: &Project) -> Result<(), StorageError> {
+    insert_project_with(conn, project, &project.encrypted_content)
+}
+
+/// `insert_project` storing `content` in place of the project's own.
+fn insert_project_with(
+    conn: &Connection,
+    project: &Project,
+    content: &dyn ToSql,
+) -> Result<(), StorageError> {
+    let position = if project.position.is_empty() {
+        let last: Option<String> = conn
+            .query_row_cached("SELECT MAX(position) FROM projects WHERE position != ''", [], |row| row.get(0))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        ordering::between(last.as_deref(), None)
+    } else {
+        project.position.clone()
+    };
+    conn.execute_cached(
+        SQL_INSERT_PROJECT,
+        params![
+            project.id,
+            project.name,
+            content,
+            project.key_check,
+            position,
+            project.created_at,
+            project.updated_at,
+            project.server_id,
+            project.sync_status,
+            project.last_synced_at,
+            project.folder_id,
+            project.pinned,
+        ],
+    )
+    .map_err(|e| StorageError::Database(e.to_string()))?;
+    Ok(())
+}
+
+fn db_err(e: rusqlite::Error) -> StorageError {
+    StorageError::Database(e.to_string())
+}
+
+/// Reserve `count` nonce counter values, none below `after`, creating the vault's nonce prefix
+/// on first use. Runs in a savepoint, so it nests in a write already in progress.
+fn reserve_nonces(
+    conn: &mut Connection,
+    after: u64,
+    count: u64,
+) -> Result<([u8; crypto::nonce::PREFIX_LEN], u64), StorageError> {
+    let tx = conn.savepoint().map_err(db_err)?;
+    let read = |key: &str| -> Result<Option<String>, StorageError> {
+        tx.query_row_cached("SELECT value FROM vault_meta WHERE key = ?1", params![key], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(db_err)
+    };
+
+    let stored = read(META_NONCE_PREFIX)?
+        .and_then(|hex| hex::decode(hex).ok())
+        .and_then(|bytes| <[u8; crypto::nonce::PREFIX_LEN]>::try_from(bytes.as_slice()).ok());
+    let (prefix, start) = match stored {
+        Some(prefix) => {
+            let counter: u64 = read(META_NONCE_COUNTER)?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            (prefix, counter.max(after))
+        }
+        None => {
+            // A new prefix starts a fresh counter space.
+            let mut prefix = [0u8; crypto::nonce::PREFIX_LEN];
+            crypto::rng::fill(&mut prefix);
+            tx.execute_cached(
+                "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?1, ?2)",
+                params![META_NONCE_PREFIX, hex::encode(prefix)],
+            )
+            .map_err(db_err)?;
+            (prefix, 0)
+        }
+    };
+    let end = start
+        .checked_add(count)
+        .ok_or_else(|| StorageError::Database("Nonce counter exhausted".to_string()))?;
+    tx.execute_cached(
+        "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?1, ?2)",
+        params![META_NONCE_COUNTER, end.to_string()],
+    )
+    .map_err(db_err)?;
+    tx.commit().map_err(db_err)?;
+    Ok((prefix, start))
+}
+
+/// Room for `seal` in a project's content, to be filled by `write_project_content`.
+fn zero_blob(seal: &crypto::StreamedSeal) -> Result<ZeroBlob, StorageError> {
+    i32::try_from(seal.sealed_len())
+        .map(ZeroBlob)
+        .map_err(|_| StorageError::Io("Content is too large".to_string()))
+}
+
+/// Seal `seal` straight into the content of project `id`, stored as its `zero_blob`.
+fn write_project_content(
+    conn: &Connection,
+    id: &str,
+    seal: &crypto::StreamedSeal,
+) -> Result<(), StorageError> {
+    let rowid: i64 = conn
+        .query_row_cached("SELECT rowid FROM projects WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(db_err)?;
+    let mut blob = conn
+        .blob_open(DatabaseName::Main, "projects", "encrypted_content", rowid, false)
+        .map_err(db_err)?;
+    seal.write_to(&mut blob).map_err(|e| StorageError::Io(e.to_string()))
+}
+
+fn project_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        encrypted_content: row.get(2)?,
+        key_check: row.get::<_, Option<Vec<u8>>>(3)?.unwrap_or_default(),
+        position: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        server_id: row.get(7)?,
+        sync_status: row.get(8)?,
+        last_synced_at: row.get(9)?,
+        folder_id: row.get(10)?,
+        revision: row.get(11)?,
+        pinned: row.get(12)?,
+    })
+}
+
+fn audit_event_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AuditEvent> {
+    Ok(AuditEvent {
+        seq: row.get(0)?,
+        event: row.get(1)?,
+        detail: row.get(2)?,
+        created_at: row.get(3)?,
+        mac: row.get(4)?,
+    })
+}
+
+fn attachment_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        wrapped_key: row.get(2)?,
+        encrypted_name: row.get(3)?,
+        encrypted_mime: row.get(4)?,
+        size: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn field_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProjectField> {
+    Ok(ProjectField {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        position: row.get(2)?,
+        encrypted_field: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn totp_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProjectTotp> {
+    Ok(ProjectTotp {
+        project_id: row.get(0)?,
+        encrypted_secret: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+fn checklist_item_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ChecklistItem> {
+    Ok(ChecklistItem {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        position: row.get(2)?,
+        encrypted_item: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        sync_status: row.get(6)?,
+    })
+}
+
+fn password_history_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<PasswordHistoryEntry> {
+    Ok(PasswordHistoryEntry {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        encrypted_password: row.get(2)?,
+        replaced_at: row.get(3)?,
+    })
+}
+
+fn draft_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Draft> {
+    Ok(Draft {
+        project_id: row.get(0)?,
+        encrypted_draft: row.get(1)?,
+        base_revision: row.get(2)?,
+        saved_at: row.get(3)?,
+    })
+}
+
+fn version_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProjectVersion> {
+    Ok(ProjectVersion {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        encrypted_content: row.get(3)?,
+        key_check: row.get::<_, Option<Vec<u8>>>(4)?.unwrap_or_default(),
+        updated_at: row.get(5)?,
+        saved_at: row.get(6)?,
+    })
 }
 
 impl LocalStorage {
+    /// Open (creating if needed) and migrate a database. A vault written by a newer format
+    /// version, or migrated by a newer app, is refused before anything is changed. An
+    /// encrypted vault stays sealed, and is only migrated, once `unseal` gives it its key.
     pub fn new(db_path: &str) -> Result<Self, StorageError> {
+        Self::with_tuning(db_path, Tuning::from_env())
+    }
+
+    /// `new` with connection settings other than the defaults.
+    pub fn with_tuning(db_path: &str, tuning: Tuning) -> Result<Self, StorageError> {
+        let lock = lock::acquire(db_path, false)?;
         let conn = Connection::open(db_path)
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        let storage = Self {
-            conn: Mutex::new(conn),
-        };
-        storage.init()?;
+        let storage = Self::with_conn(conn, db_path, false, tuning, lock);
+        storage.refuse(&storage.current_format()?)?;
+        if !storage.is_sealed() {
+            let conn = storage.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+            tune(&conn, &tuning, false)?;
+            drop(conn);
+            storage.init()?;
+        }
+        Ok(storage)
+    }
+
+    /// Open a vault from a newer version without migrating it; every write is refused. Only
+    /// possible when that version declared its format readable by this one.
+    pub fn open_read_only(db_path: &str) -> Result<Self, StorageError> {
+        let lock = lock::acquire(db_path, true)?;
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let tuning = Tuning::from_env();
+        tune(&conn, &tuning, true)?;
+        let storage = Self::with_conn(conn, db_path, true, tuning, lock);
+        storage.refuse(&storage.current_format()?)?;
         Ok(storage)
     }
+
+    fn with_conn(
+        conn: Connection,
+        db_path: &str,
+        read_only: bool,
+        tuning: Tuning,
+        lock: Option<Arc<File>>,
+    ) -> Self {
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_SIZE);
+        let header = cipher::is_encrypted(db_path).then(|| cipher::header_path(db_path));
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            metrics: Metrics::new(),
+            read_only,
+            tuning,
+            shard: ShardStore::new(db_path, read_only),
+            db_path: db_path.to_string(),
+            sealed: AtomicBool::new(header.is_some()),
+            header,
+            _lock: lock,
+        }
+    }
+
+    fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::SeqCst)
+    }
+
+    fn current_format(&self) -> Result<FormatInfo, StorageError> {
+        match &self.header {
+            Some(path) if self.is_sealed() => header_format_info(path),
+            _ => {
+                let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+                let mut info = format_info(&conn)?;
+                info.encrypted = self.header.is_some();
+                Ok(info)
+            }
+        }
+    }
+
+    /// Refuse a vault this build must not open, or in read-only mode cannot read.
+    fn refuse(&self, info: &FormatInfo) -> Result<(), StorageError> {
+        if self.read_only {
+            if info.too_new && !info.read_only_possible {
+                return Err(StorageError::VaultTooNew {
+                    required_version: info
+                        .read_compat_version
+                        .or(info.format_version)
+                        .unwrap_or_default(),
+                });
+            }
+            return Ok(());
+        }
+        if info.too_new {
+            return Err(StorageError::VaultTooNew {
+                required_version: info.format_version.unwrap_or_default(),
+            });
+        }
+        if info.schema_version > info.supported_schema_version {
+            return Err(StorageError::SchemaTooNew {
+                found: info.schema_version,
+                supported: info.supported_schema_version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Format versions of the database at `db_path`, read without modifying it.
+    pub fn inspect(db_path: &str) -> Result<FormatInfo, StorageError> {
+        if cipher::is_encrypted(db_path) {
+            return header_format_info(&cipher::header_path(db_path));
+        }
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        format_info(&conn)
+    }
+
+    fn writable(&self) -> Result<MutexGuard<'_, Connection>, StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnly);
+        }
+        if self.is_sealed() {
+            return Err(StorageError::Sealed);
+        }
+        self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))
+    }
 }
 
 impl StorageProvider for LocalStorage {
     fn init(&self) -> Result<(), StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL DEFAULT '',
-                encrypted_content BLOB NOT NULL,
-                key_check BLOB,
-                sort_order INTEGER DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                server_id TEXT,
-                sync_status TEXT DEFAULT 'local',
-                last_synced_at TEXT
-            );
-            CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            );
-            CREATE TABLE IF NOT EXISTS verification (
-                id INTEGER PRIMARY KEY CHECK (id = 1),
-                token BLOB NOT NULL
-            );"
-        )
-        .map_err(|e| StorageError::Database(e.to_string()))?;
+        migrations::run(&conn)?;
+        stamp_format(&conn)?;
 
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS project_backups (
-                id TEXT PRIMARY KEY,
-                project_id TEXT NOT NULL,
-                name TEXT NOT NULL DEFAULT '',
-                encrypted_content BLOB NOT NULL,
-                key_check BLOB,
-                created_at TEXT NOT NULL,
-                trigger_type TEXT NOT NULL,
-                content_length INTEGER NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
-            );
-            CREATE INDEX IF NOT EXISTS idx_backups_project
-                ON project_backups(project_id, created_at DESC);"
+        conn.execute_cached(
+            "DELETE FROM change_log WHERE seq <= (SELECT MAX(seq) FROM change_log) - ?1",
+            params![CHANGE_LOG_RETAINED],
         )
         .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        conn.execute_batch("PRAGMA foreign_keys = ON;")
+        let foreign_keys = if self.tuning.foreign_keys { "ON" } else { "OFF" };
+        conn.execute_batch(&format!("PRAGMA foreign_keys = {foreign_keys};"))
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        let project_cols: Vec<String> = {
+        // Projects without an ordered key (from before ordered keys, or written without one)
+        // get keys in their existing order, after any project that already has one.
+        let unplaced: Vec<String> = {
             let mut stmt = conn
-                .prepare("PRAGMA table_info(projects)")
+                .prepare_cached("SELECT id FROM projects WHERE position = '' ORDER BY sort_order ASC, created_at ASC")
                 .map_err(|e| StorageError::Database(e.to_string()))?;
-            let rows = stmt.query_map([], |row| row.get::<_, String>(1))
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))
                 .map_err(|e| StorageError::Database(e.to_string()))?;
             rows.filter_map(|r| r.ok()).collect()
         };
-        if !project_cols.contains(&"last_synced_at".to_string()) {
-            conn.execute_batch("ALTER TABLE projects ADD COLUMN last_synced_at TEXT;")
-                .map_err(|e| StorageError::Database(e.to_string()))?;
-        }
-        if !project_cols.contains(&"name".to_string()) {
-            conn.execute_batch(
-                "ALTER TABLE projects ADD COLUMN name TEXT NOT NULL DEFAULT '';
-                 ALTER TABLE projects ADD COLUMN key_check BLOB;"
-            )
-            .map_err(|e| StorageError::Database(e.to_string()))?;
-        }
-
-        let backup_cols: Vec<String> = {
-            let mut stmt = conn
-                .prepare("PRAGMA table_info(project_backups)")
+        if !unplaced.is_empty() {
+            let last: Option<String> = conn
+                .query_row_cached("SELECT MAX(position) FROM projects WHERE position != ''", [], |row| row.get(0))
                 .map_err(|e| StorageError::Database(e.to_string()))?;
-            let rows = stmt.query_map([], |row| row.get::<_, String>(1))
+            let keys = ordering::keys_between(last.as_deref(), None, unplaced.len());
+            let tx = conn.unchecked_transaction()
                 .map_err(|e| StorageError::Database(e.to_string()))?;
-            rows.filter_map(|r| r.ok()).collect()
-        };
-        if !backup_cols.is_empty() && !backup_cols.contains(&"name".to_string()) {
-            conn.execute_batch(
-                "ALTER TABLE project_backups ADD COLUMN name TEXT NOT NULL DEFAULT '';
-                 ALTER TABLE project_backups ADD COLUMN key_check BLOB;"
-            )
-            .map_err(|e| StorageError::Database(e.to_string()))?;
+            for (id, key) in unplaced.iter().zip(keys) {
+                tx.execute_cached("UPDATE projects SET position = ?1 WHERE id = ?2", params![key, id])
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+            }
+            tx.commit().map_err(|e| StorageError::Database(e.to_string()))?;
         }
 
         Ok(())
     }
 
     fn list_projects(&self) -> Result<Vec<Project>, StorageError> {
+        let _timer = self.metrics.start(Operation::List, SQL_LIST_PROJECTS);
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
         let mut stmt = conn
-            .prepare(
-                "SELECT id, name, encrypted_content, key_check,
-                        sort_order, created_at, updated_at, server_id, sync_status, last_synced_at
-                 FROM projects ORDER BY sort_order ASC, created_at ASC",
+            .prepare_cached(
+                SQL_LIST_PROJECTS,
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
         let projects = stmt
-            .query_map([], |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    encrypted_content: row.get(2)?,
-                    key_check: row.get::<_, Option<Vec<u8>>>(3)?.unwrap_or_default(),
-                    sort_order: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    server_id: row.get(7)?,
-                    sync_status: row.get(8)?,
-                    last_synced_at: row.get(9)?,
-                })
-            })
+            .query_map([], project_from_row)
             .map_err(|e| StorageError::Database(e.to_string()))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| StorageError::Database(e.to_string()))?;
@@ -142,72 +678,98 @@ impl StorageProvider for LocalStorage {
     }
 
     fn get_project(&self, id: &str) -> Result<Project, StorageError> {
+        let _timer = self.metrics.start(Operation::Get, SQL_GET_PROJECT);
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.query_row(
-            "SELECT id, name, encrypted_content, key_check,
-                    sort_order, created_at, updated_at, server_id, sync_status, last_synced_at
-             FROM projects WHERE id = ?1",
-            params![id],
-            |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    encrypted_content: row.get(2)?,
-                    key_check: row.get::<_, Option<Vec<u8>>>(3)?.unwrap_or_default(),
-                    sort_order: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    server_id: row.get(7)?,
-                    sync_status: row.get(8)?,
-                    last_synced_at: row.get(9)?,
-                })
-            },
-        )
-        .map_err(|e| match e {
-            rusqlite::Error::QueryReturnedNoRows => {
-                StorageError::NotFound(id.to_string())
-            }
-            _ => StorageError::Database(e.to_string()),
-        })
+        conn.query_row_cached(SQL_GET_PROJECT, params![id], project_from_row)
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => StorageError::NotFound(id.to_string()),
+                _ => StorageError::Database(e.to_string()),
+            })
     }
 
-    fn create_project(&self, project: &Project) -> Result<Option<String>, StorageError> {
+    fn get_project_meta(&self, id: &str) -> Result<Project, StorageError> {
+        let _timer = self.metrics.start(Operation::Get, SQL_GET_PROJECT_META);
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute(
-            "INSERT INTO projects (id, name, encrypted_content, key_check,
-                                   sort_order, created_at, updated_at, server_id, sync_status, last_synced_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                project.id,
-                project.name,
-                project.encrypted_content,
-                project.key_check,
-                project.sort_order,
-                project.created_at,
-                project.updated_at,
-                project.server_id,
-                project.sync_status,
-                project.last_synced_at,
-            ],
-        )
-        .map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(SQL_GET_PROJECT_META, params![id], project_from_row)
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => StorageError::NotFound(id.to_string()),
+                _ => StorageError::Database(e.to_string()),
+            })
+    }
+
+    /// Reads the content in place, through SQLite's incremental blob I/O.
+    fn read_project_content(
+        &self,
+        id: &str,
+        read: &mut dyn FnMut(&mut dyn Read) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let rowid: i64 = conn
+            .query_row_cached("SELECT rowid FROM projects WHERE id = ?1", params![id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(db_err)?
+            .ok_or_else(|| StorageError::NotFound(id.to_string()))?;
+        let mut blob = conn
+            .blob_open(DatabaseName::Main, "projects", "encrypted_content", rowid, true)
+            .map_err(db_err)?;
+        read(&mut blob)
+    }
+
+    /// A project without a position is placed after all others.
+    fn create_project(&self, project: &Project) -> Result<Option<String>, StorageError> {
+        let _timer = self.metrics.start(Operation::Create, SQL_INSERT_PROJECT);
+        let conn = self.writable()?;
+        insert_project(&conn, project)?;
         Ok(None)
     }
 
+    /// The content is written in place, in the same transaction as the rest of the project.
+    fn create_project_streamed(
+        &self,
+        project: &Project,
+        seal: &crypto::StreamedSeal,
+    ) -> Result<Option<String>, StorageError> {
+        let _timer = self.metrics.start(Operation::Create, SQL_INSERT_PROJECT);
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint().map_err(db_err)?;
+        insert_project_with(&tx, project, &zero_blob(seal)?)?;
+        write_project_content(&tx, &project.id, seal)?;
+        tx.commit().map_err(db_err)?;
+        Ok(None)
+    }
+
+    /// All in one transaction: either every project is stored or none is.
+    fn create_projects_batch(
+        &self,
+        projects: &[Project],
+    ) -> Result<Vec<Option<String>>, StorageError> {
+        let _timer = self.metrics.start(Operation::Transaction, "create projects batch");
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        for project in projects {
+            insert_project(&tx, project)?;
+        }
+        tx.commit().map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(vec![None; projects.len()])
+    }
+
+    /// An empty position leaves the stored one unchanged; the folder is only changed by
+    /// `set_project_folder`.
     fn update_project(&self, project: &Project) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let _timer = self.metrics.start(Operation::Update, SQL_UPDATE_PROJECT);
+        let conn = self.writable()?;
         let rows = conn
-            .execute(
-                "UPDATE projects SET name = ?2, encrypted_content = ?3,
-                        key_check = ?4, sort_order = ?5, updated_at = ?6,
-                        server_id = ?7, sync_status = ?8, last_synced_at = ?9
-                 WHERE id = ?1",
+            .execute_cached(
+                SQL_UPDATE_PROJECT,
                 params![
                     project.id,
                     project.name,
                     project.encrypted_content,
                     project.key_check,
-                    project.sort_order,
+                    project.position,
                     project.updated_at,
                     project.server_id,
                     project.sync_status,
@@ -222,26 +784,74 @@ impl StorageProvider for LocalStorage {
         Ok(())
     }
 
+    /// The content is written in place, in the same transaction as the rest of the project.
+    fn update_project_streamed(
+        &self,
+        project: &Project,
+        seal: &crypto::StreamedSeal,
+    ) -> Result<(), StorageError> {
+        let _timer = self.metrics.start(Operation::Update, SQL_UPDATE_PROJECT);
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint().map_err(db_err)?;
+        let rows = tx
+            .execute_cached(
+                SQL_UPDATE_PROJECT,
+                params![
+                    project.id,
+                    project.name,
+                    zero_blob(seal)?,
+                    project.key_check,
+                    project.position,
+                    project.updated_at,
+                    project.server_id,
+                    project.sync_status,
+                    project.last_synced_at,
+                ],
+            )
+            .map_err(db_err)?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(project.id.clone()));
+        }
+        write_project_content(&tx, &project.id, seal)?;
+        tx.commit().map_err(db_err)
+    }
+
     fn delete_project(&self, id: &str) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let _timer = self.metrics.start(Operation::Delete, SQL_DELETE_PROJECT);
+        let conn = self.writable()?;
+        let blob_ids: Vec<String> = {
+            let mut stmt = conn
+                .prepare_cached("SELECT id FROM attachments WHERE project_id = ?1")
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let rows = stmt.query_map(params![id], |row| row.get::<_, String>(0))
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
         let rows = conn
-            .execute("DELETE FROM projects WHERE id = ?1", params![id])
+            .execute_cached(SQL_DELETE_PROJECT, params![id])
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
         if rows == 0 {
             return Err(StorageError::NotFound(id.to_string()));
         }
+        // The attachment rows went with the project; their blobs live in the shard.
+        for blob_id in blob_ids {
+            self.shard.delete_blob(&blob_id)?;
+        }
         Ok(())
     }
 
-    fn reorder_projects(&self, ids_with_order: &[(String, i32)]) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        let tx = conn.unchecked_transaction()
+    fn reorder_projects(&self, positions: &[(String, String)]) -> Result<(), StorageError> {
+        let _timer = self.metrics.start(Operation::Transaction, "reorder projects");
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint()
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        for (id, order) in ids_with_order {
-            tx.execute(
-                "UPDATE projects SET sort_order = ?1 WHERE id = ?2",
-                params![order, id],
+        for (id, position) in positions {
+            tx.execute_cached(
+                "UPDATE projects SET position = ?1,
+                        sync_status = CASE WHEN sync_status = 'synced' THEN 'modified' ELSE sync_status END
+                 WHERE id = ?2",
+                params![position, id],
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
         }
@@ -249,53 +859,449 @@ impl StorageProvider for LocalStorage {
         Ok(())
     }
 
-    fn get_verification_token(&self) -> Result<Option<Vec<u8>>, StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        match conn.query_row("SELECT token FROM verification WHERE id = 1", [], |row| {
-            row.get(0)
-        }) {
-            Ok(token) => Ok(Some(token)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(StorageError::Database(e.to_string())),
+    fn trash_project(&self, id: &str, deleted_at: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE projects SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+                params![id, deleted_at],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
         }
+        Ok(())
     }
 
-    fn set_verification_token(&self, token: &[u8]) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute(
-            "INSERT OR REPLACE INTO verification (id, token) VALUES (1, ?1)",
-            params![token],
-        )
-        .map_err(|e| StorageError::Database(e.to_string()))?;
+    fn restore_project(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE projects SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+                params![id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
+        }
         Ok(())
     }
 
-    fn get_setting(&self, key: &str) -> Result<Option<String>, StorageError> {
+    fn list_trashed_projects(&self) -> Result<Vec<(String, String)>, StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        match conn.query_row(
-            "SELECT value FROM settings WHERE key = ?1",
-            params![key],
-            |row| row.get(0),
-        ) {
-            Ok(value) => Ok(Some(value)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(StorageError::Database(e.to_string())),
+        let mut stmt = conn
+            .prepare_cached("SELECT id, deleted_at FROM projects WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(rows)
+    }
+
+    fn archive_project(&self, id: &str, archived_at: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE projects SET archived_at = ?2 WHERE id = ?1 AND archived_at IS NULL",
+                params![id, archived_at],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
         }
+        Ok(())
     }
 
-    fn set_setting(&self, key: &str, value: &str) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute(
-            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
-            params![key, value],
-        )
-        .map_err(|e| StorageError::Database(e.to_string()))?;
+    fn unarchive_project(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE projects SET archived_at = NULL WHERE id = ?1 AND archived_at IS NOT NULL",
+                params![id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
+        }
         Ok(())
     }
 
-    fn create_backup(&self, backup: &ProjectBackup) -> Result<(), StorageError> {
+    fn list_archived_projects(&self) -> Result<Vec<(String, String)>, StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute(
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, archived_at FROM projects WHERE archived_at IS NOT NULL
+                 ORDER BY archived_at DESC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(rows)
+    }
+
+    fn filter_projects(&self, filter: &ProjectFilter) -> Result<Vec<String>, StorageError> {
+        let mut clauses = vec!["deleted_at IS NULL".to_string()];
+        let mut values: Vec<&str> = Vec::new();
+        let bounds = [
+            ("created_at >=", &filter.created_after),
+            ("created_at <", &filter.created_before),
+            ("updated_at >=", &filter.updated_after),
+            ("updated_at <", &filter.updated_before),
+            ("sync_status =", &filter.sync_status),
+        ];
+        for (test, value) in bounds {
+            if let Some(value) = value {
+                values.push(value);
+                clauses.push(format!("{test} ?{}", values.len()));
+            }
+        }
+        match filter.archived {
+            Some(true) => clauses.push("archived_at IS NOT NULL".to_string()),
+            Some(false) => clauses.push("archived_at IS NULL".to_string()),
+            None => {}
+        }
+        for tag_id in &filter.tags {
+            values.push(tag_id);
+            clauses.push(format!(
+                "id IN (SELECT project_id FROM project_tags WHERE tag_id = ?{})",
+                values.len()
+            ));
+        }
+        if filter.untagged {
+            clauses.push("id NOT IN (SELECT project_id FROM project_tags)".to_string());
+        }
+
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare(&format!("SELECT id FROM projects WHERE {}", clauses.join(" AND ")))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| row.get(0))
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(rows)
+    }
+
+    fn set_pinned(&self, id: &str, pinned: bool) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached("UPDATE projects SET pinned = ?2 WHERE id = ?1", params![id, pinned])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn record_access(&self, id: &str, accessed_at: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "UPDATE projects SET last_accessed_at = ?2 WHERE id = ?1",
+            params![id, accessed_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn recently_accessed(&self, limit: usize) -> Result<Vec<(String, String)>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, last_accessed_at FROM projects
+                 WHERE last_accessed_at IS NOT NULL AND deleted_at IS NULL
+                 ORDER BY last_accessed_at DESC LIMIT ?1",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(rows)
+    }
+
+    fn set_project_folder(&self, project_id: &str, folder_id: Option<&str>) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE projects SET folder_id = ?2 WHERE id = ?1",
+                params![project_id, folder_id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(project_id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn list_folders(&self) -> Result<Vec<Folder>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, parent_id, encrypted_name, position, created_at, updated_at, sync_status
+                 FROM folders ORDER BY position ASC, created_at ASC, id ASC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let folders = stmt
+            .query_map([], |row| {
+                Ok(Folder {
+                    id: row.get(0)?,
+                    parent_id: row.get(1)?,
+                    encrypted_name: row.get(2)?,
+                    position: row.get(3)?,
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    sync_status: row.get(6)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(folders)
+    }
+
+    /// Insert or replace a folder.
+    fn save_folder(&self, folder: &Folder) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO folders (id, parent_id, encrypted_name, position,
+                                             created_at, updated_at, sync_status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                folder.id,
+                folder.parent_id,
+                folder.encrypted_name,
+                folder.position,
+                folder.created_at,
+                folder.updated_at,
+                folder.sync_status,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_folder(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM folders WHERE id = ?1", params![id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_templates(&self) -> Result<Vec<Template>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, encrypted_template, created_at, updated_at, sync_status
+                 FROM templates ORDER BY created_at ASC, id ASC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let templates = stmt
+            .query_map([], |row| {
+                Ok(Template {
+                    id: row.get(0)?,
+                    encrypted_template: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    sync_status: row.get(4)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(templates)
+    }
+
+    fn save_template(&self, template: &Template) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO templates (id, encrypted_template, created_at, updated_at,
+                                               sync_status)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                template.id,
+                template.encrypted_template,
+                template.created_at,
+                template.updated_at,
+                template.sync_status,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_template(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM templates WHERE id = ?1", params![id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn get_verification_token(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        if let Some(path) = self.header.as_deref().filter(|_| self.is_sealed()) {
+            return cipher::load_header(path)
+                .verification_token
+                .map(|token| B64.decode(token).map_err(|e| StorageError::Io(e.to_string())))
+                .transpose();
+        }
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        match conn.query_row_cached("SELECT token FROM verification WHERE id = 1", [], |row| {
+            row.get(0)
+        }) {
+            Ok(token) => Ok(Some(token)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(StorageError::Database(e.to_string())),
+        }
+    }
+
+    fn set_verification_token(&self, token: &[u8]) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO verification (id, token) VALUES (1, ?1)",
+            params![token],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(path) = &self.header {
+            cipher::refresh_header(&conn, path)?;
+        }
+        Ok(())
+    }
+
+    fn get_setting(&self, key: &str) -> Result<Option<String>, StorageError> {
+        if let Some(path) = &self.header {
+            if cipher::UNLOCK_SETTINGS.contains(&key) {
+                return Ok(cipher::load_header(path).settings.get(key).cloned());
+            }
+            if self.is_sealed() {
+                return Ok(None);
+            }
+        }
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        match conn.query_row_cached(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        ) {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(StorageError::Database(e.to_string())),
+        }
+    }
+
+    fn list_settings(&self) -> Result<Vec<(String, String)>, StorageError> {
+        if self.is_sealed() {
+            return Ok(vec![]);
+        }
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT key, value FROM settings ORDER BY key")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        if let Some(path) = self.header.as_deref().filter(|_| cipher::UNLOCK_SETTINGS.contains(&key)) {
+            if self.read_only {
+                return Err(StorageError::ReadOnly);
+            }
+            return cipher::update_header(path, |header| {
+                header.settings.insert(key.to_string(), value.to_string());
+            });
+        }
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>, StorageError> {
+        if let Some(path) = self.header.as_deref().filter(|_| self.is_sealed()) {
+            return Ok(cipher::load_header(path).meta.get(key).cloned());
+        }
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        match conn.query_row_cached(
+            "SELECT value FROM vault_meta WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        ) {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(StorageError::Database(e.to_string())),
+        }
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(path) = self.header.as_deref().filter(|_| cipher::UNLOCK_META.contains(&key)) {
+            cipher::update_header(path, |header| {
+                header.meta.insert(key.to_string(), value.to_string());
+            })?;
+        }
+        Ok(())
+    }
+
+    fn replace_unlock_meta(&self, meta: &[(&str, &str)], token: &[u8]) -> Result<(), StorageError> {
+        let _timer = self.metrics.start(Operation::Transaction, "replace unlock meta");
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        for (key, value) in meta {
+            tx.execute_cached(
+                "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        tx.execute_cached(
+            "INSERT OR REPLACE INTO verification (id, token) VALUES (1, ?1)",
+            params![token],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.commit().map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(path) = &self.header {
+            cipher::refresh_header(&conn, path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes that need several statements use savepoints, so they nest inside this
+    /// transaction rather than failing to start one of their own.
+    fn begin_transaction(&self) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_batch("BEGIN IMMEDIATE").map_err(db_err)
+    }
+
+    fn commit_transaction(&self) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_batch("COMMIT").map_err(db_err)
+    }
+
+    fn rollback_transaction(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.execute_batch("ROLLBACK").map_err(db_err)
+    }
+
+    fn create_backup(&self, backup: &ProjectBackup) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
             "INSERT INTO project_backups (id, project_id, name, encrypted_content,
                                           key_check, created_at, trigger_type, content_length)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
@@ -315,9 +1321,9 @@ impl StorageProvider for LocalStorage {
     }
 
     fn update_backup(&self, backup: &ProjectBackup) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let conn = self.writable()?;
         let rows = conn
-            .execute(
+            .execute_cached(
                 "UPDATE project_backups SET name = ?2,
                         encrypted_content = ?3, key_check = ?4
                  WHERE id = ?1",
@@ -333,7 +1339,7 @@ impl StorageProvider for LocalStorage {
     fn list_backups(&self, project_id: &str) -> Result<Vec<ProjectBackup>, StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT id, project_id, name, encrypted_content,
                         key_check, created_at, trigger_type, content_length
                  FROM project_backups
@@ -364,7 +1370,7 @@ impl StorageProvider for LocalStorage {
 
     fn get_backup(&self, backup_id: &str) -> Result<ProjectBackup, StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.query_row(
+        conn.query_row_cached(
             "SELECT id, project_id, name, encrypted_content,
                     key_check, created_at, trigger_type, content_length
              FROM project_backups WHERE id = ?1",
@@ -392,7 +1398,7 @@ impl StorageProvider for LocalStorage {
 
     fn get_latest_backup(&self, project_id: &str) -> Result<Option<ProjectBackup>, StorageError> {
         let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        match conn.query_row(
+        match conn.query_row_cached(
             "SELECT id, project_id, name, encrypted_content,
                     key_check, created_at, trigger_type, content_length
              FROM project_backups
@@ -419,15 +1425,15 @@ impl StorageProvider for LocalStorage {
     }
 
     fn delete_backup(&self, backup_id: &str) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute("DELETE FROM project_backups WHERE id = ?1", params![backup_id])
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM project_backups WHERE id = ?1", params![backup_id])
             .map_err(|e| StorageError::Database(e.to_string()))?;
         Ok(())
     }
 
     fn cleanup_backups(&self, project_id: &str, keep_count: usize) -> Result<(), StorageError> {
-        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
-        conn.execute(
+        let conn = self.writable()?;
+        conn.execute_cached(
             "DELETE FROM project_backups
              WHERE project_id = ?1
                AND id NOT IN (
@@ -441,4 +1447,1034 @@ impl StorageProvider for LocalStorage {
         .map_err(|e| StorageError::Database(e.to_string()))?;
         Ok(())
     }
+
+    fn create_version(&self, version: &ProjectVersion) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO project_versions (id, project_id, name, encrypted_content, key_check,
+                                           updated_at, saved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                version.id,
+                version.project_id,
+                version.name,
+                version.encrypted_content,
+                version.key_check,
+                version.updated_at,
+                version.saved_at,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn update_version(&self, version: &ProjectVersion) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE project_versions SET encrypted_content = ?2, key_check = ?3 WHERE id = ?1",
+                params![version.id, version.encrypted_content, version.key_check],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(version.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn list_versions(&self, project_id: &str) -> Result<Vec<ProjectVersion>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, project_id, name, encrypted_content, key_check, updated_at, saved_at
+                 FROM project_versions
+                 WHERE project_id = ?1
+                 ORDER BY saved_at DESC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let versions = stmt
+            .query_map(params![project_id], version_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(versions)
+    }
+
+    fn get_version(&self, id: &str) -> Result<ProjectVersion, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT id, project_id, name, encrypted_content, key_check, updated_at, saved_at
+             FROM project_versions WHERE id = ?1",
+            params![id],
+            version_from_row,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => StorageError::NotFound(id.to_string()),
+            _ => StorageError::Database(e.to_string()),
+        })
+    }
+
+    fn prune_versions(
+        &self,
+        project_id: Option<&str>,
+        keep_count: usize,
+        saved_before: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "DELETE FROM project_versions
+             WHERE (?1 IS NULL OR project_id = ?1)
+               AND (
+                   id NOT IN (
+                       SELECT v.id FROM project_versions v
+                       WHERE v.project_id = project_versions.project_id
+                       ORDER BY v.saved_at DESC
+                       LIMIT ?2
+                   )
+                   OR (?3 IS NOT NULL AND saved_at < ?3)
+               )",
+            params![project_id, keep_count as i64, saved_before],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn create_attachment(&self, attachment: &Attachment) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO attachments (id, project_id, wrapped_key, encrypted_name, encrypted_mime,
+                                      size, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                attachment.id,
+                attachment.project_id,
+                attachment.wrapped_key,
+                attachment.encrypted_name,
+                attachment.encrypted_mime,
+                attachment.size,
+                attachment.created_at,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn update_attachment(&self, attachment: &Attachment) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE attachments SET wrapped_key = ?2, encrypted_name = ?3, encrypted_mime = ?4
+                 WHERE id = ?1",
+                params![
+                    attachment.id,
+                    attachment.wrapped_key,
+                    attachment.encrypted_name,
+                    attachment.encrypted_mime,
+                ],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(attachment.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn list_attachments(&self, project_id: &str) -> Result<Vec<Attachment>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, project_id, wrapped_key, encrypted_name, encrypted_mime, size, created_at
+                 FROM attachments
+                 WHERE project_id = ?1
+                 ORDER BY created_at ASC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let attachments = stmt
+            .query_map(params![project_id], attachment_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(attachments)
+    }
+
+    fn get_attachment(&self, id: &str) -> Result<Attachment, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT id, project_id, wrapped_key, encrypted_name, encrypted_mime, size, created_at
+             FROM attachments WHERE id = ?1",
+            params![id],
+            attachment_from_row,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => StorageError::NotFound(id.to_string()),
+            _ => StorageError::Database(e.to_string()),
+        })
+    }
+
+    fn delete_attachment(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached("DELETE FROM attachments WHERE id = ?1", params![id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
+        }
+        self.shard.delete_blob(id)
+    }
+
+    fn list_project_fields(&self, project_id: &str) -> Result<Vec<ProjectField>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, project_id, position, encrypted_field, created_at, updated_at
+                 FROM project_fields
+                 WHERE project_id = ?1
+                 ORDER BY position ASC, created_at ASC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let fields = stmt
+            .query_map(params![project_id], field_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(fields)
+    }
+
+    fn get_project_field(&self, id: &str) -> Result<ProjectField, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT id, project_id, position, encrypted_field, created_at, updated_at
+             FROM project_fields WHERE id = ?1",
+            params![id],
+            field_from_row,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => StorageError::NotFound(id.to_string()),
+            _ => StorageError::Database(e.to_string()),
+        })
+    }
+
+    fn save_project_field(&self, field: &ProjectField) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO project_fields (id, project_id, position, encrypted_field, created_at,
+                                         updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                position = excluded.position,
+                encrypted_field = excluded.encrypted_field,
+                updated_at = excluded.updated_at",
+            params![
+                field.id,
+                field.project_id,
+                field.position,
+                field.encrypted_field,
+                field.created_at,
+                field.updated_at,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_project_field(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached("DELETE FROM project_fields WHERE id = ?1", params![id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn get_project_totp(&self, project_id: &str) -> Result<Option<ProjectTotp>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT project_id, encrypted_secret, created_at, updated_at
+             FROM project_totp WHERE project_id = ?1",
+            params![project_id],
+            totp_from_row,
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn list_project_totps(&self) -> Result<Vec<ProjectTotp>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT project_id, encrypted_secret, created_at, updated_at FROM project_totp")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let records = stmt
+            .query_map([], totp_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(records)
+    }
+
+    fn save_project_totp(&self, totp: &ProjectTotp) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO project_totp (project_id, encrypted_secret, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(project_id) DO UPDATE SET
+                encrypted_secret = excluded.encrypted_secret,
+                updated_at = excluded.updated_at",
+            params![totp.project_id, totp.encrypted_secret, totp.created_at, totp.updated_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_project_totp(&self, project_id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached("DELETE FROM project_totp WHERE project_id = ?1", params![project_id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(project_id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn list_checklist_items(&self, project_id: &str) -> Result<Vec<ChecklistItem>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, project_id, position, encrypted_item, created_at, updated_at, sync_status
+                 FROM checklist_items WHERE project_id = ?1 ORDER BY position ASC, id ASC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let items = stmt
+            .query_map(params![project_id], checklist_item_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(items)
+    }
+
+    fn get_checklist_item(&self, id: &str) -> Result<Option<ChecklistItem>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT id, project_id, position, encrypted_item, created_at, updated_at, sync_status
+             FROM checklist_items WHERE id = ?1",
+            params![id],
+            checklist_item_from_row,
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn save_checklist_item(&self, item: &ChecklistItem) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO checklist_items (id, project_id, position, encrypted_item, created_at,
+                                          updated_at, sync_status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+                position = excluded.position,
+                encrypted_item = excluded.encrypted_item,
+                updated_at = excluded.updated_at,
+                sync_status = excluded.sync_status",
+            params![
+                item.id,
+                item.project_id,
+                item.position,
+                item.encrypted_item,
+                item.created_at,
+                item.updated_at,
+                item.sync_status,
+            ],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_checklist_item(&self, id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM checklist_items WHERE id = ?1", params![id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_search_index(&self) -> Result<Vec<SearchIndexEntry>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT project_id, encrypted_filter, indexed_at FROM search_index")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(SearchIndexEntry {
+                    project_id: row.get(0)?,
+                    encrypted_filter: row.get(1)?,
+                    indexed_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(entries)
+    }
+
+    fn save_search_index_entry(&self, entry: &SearchIndexEntry) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO search_index (project_id, encrypted_filter, indexed_at)
+             VALUES (?1, ?2, ?3)",
+            params![entry.project_id, entry.encrypted_filter, entry.indexed_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_search_index_entry(&self, project_id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM search_index WHERE project_id = ?1", params![project_id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn clear_search_index(&self) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM search_index", [])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_drafts(&self) -> Result<Vec<Draft>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT project_id, encrypted_draft, base_revision, saved_at FROM drafts")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let drafts = stmt
+            .query_map([], draft_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(drafts)
+    }
+
+    fn get_draft(&self, project_id: &str) -> Result<Option<Draft>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT project_id, encrypted_draft, base_revision, saved_at FROM drafts
+             WHERE project_id = ?1",
+            params![project_id],
+            draft_from_row,
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn save_draft(&self, draft: &Draft) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT OR REPLACE INTO drafts (project_id, encrypted_draft, base_revision, saved_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![draft.project_id, draft.encrypted_draft, draft.base_revision, draft.saved_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_draft(&self, project_id: &str) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached("DELETE FROM drafts WHERE project_id = ?1", params![project_id])
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_password_history(&self, project_id: &str) -> Result<Vec<PasswordHistoryEntry>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, project_id, encrypted_password, replaced_at FROM password_history
+                 WHERE project_id = ?1 ORDER BY replaced_at DESC, rowid DESC",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let entries = stmt
+            .query_map(params![project_id], password_history_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(entries)
+    }
+
+    fn get_password_history_entry(&self, id: &str) -> Result<PasswordHistoryEntry, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT id, project_id, encrypted_password, replaced_at FROM password_history WHERE id = ?1",
+            params![id],
+            password_history_from_row,
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(e.to_string()))?
+        .ok_or_else(|| StorageError::NotFound(id.to_string()))
+    }
+
+    fn add_password_history(&self, entry: &PasswordHistoryEntry) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO password_history (id, project_id, encrypted_password, replaced_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![entry.id, entry.project_id, entry.encrypted_password, entry.replaced_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn update_password_history(&self, entry: &PasswordHistoryEntry) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE password_history SET encrypted_password = ?2 WHERE id = ?1",
+                params![entry.id, entry.encrypted_password],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(entry.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn prune_password_history(&self, project_id: &str, keep: usize) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "DELETE FROM password_history WHERE project_id = ?1 AND id NOT IN (
+                SELECT id FROM password_history WHERE project_id = ?1
+                ORDER BY replaced_at DESC, rowid DESC LIMIT ?2
+             )",
+            params![project_id, keep as i64],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_tags(&self) -> Result<Vec<Tag>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT id, encrypted_name, created_at FROM tags ORDER BY created_at ASC, id ASC")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    encrypted_name: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(tags)
+    }
+
+    fn create_tag(&self, tag: &Tag) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO tags (id, encrypted_name, created_at) VALUES (?1, ?2, ?3)",
+            params![tag.id, tag.encrypted_name, tag.created_at],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn update_tag(&self, tag: &Tag) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        let rows = conn
+            .execute_cached(
+                "UPDATE tags SET encrypted_name = ?2 WHERE id = ?1",
+                params![tag.id, tag.encrypted_name],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::NotFound(tag.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn set_project_tags(&self, project_id: &str, tag_ids: &[String]) -> Result<(), StorageError> {
+        let _timer = self.metrics.start(Operation::Transaction, "set project tags");
+        let mut conn = self.writable()?;
+        let tx = conn.savepoint()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        // Only drop the tags that go away: deleting a tag's last link deletes the tag.
+        let current: Vec<String> = {
+            let mut stmt = tx
+                .prepare_cached("SELECT tag_id FROM project_tags WHERE project_id = ?1")
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let rows = stmt.query_map(params![project_id], |row| row.get::<_, String>(0))
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            rows.collect::<Result<_, _>>().map_err(|e| StorageError::Database(e.to_string()))?
+        };
+        for tag_id in current.iter().filter(|id| !tag_ids.contains(id)) {
+            tx.execute_cached(
+                "DELETE FROM project_tags WHERE project_id = ?1 AND tag_id = ?2",
+                params![project_id, tag_id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        for tag_id in tag_ids.iter().filter(|id| !current.contains(id)) {
+            tx.execute_cached(
+                "INSERT OR IGNORE INTO project_tags (project_id, tag_id) VALUES (?1, ?2)",
+                params![project_id, tag_id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_project_tags(&self) -> Result<Vec<(String, String)>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT project_id, tag_id FROM project_tags")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let pairs = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(pairs)
+    }
+
+    fn list_project_appearances(&self) -> Result<Vec<ProjectAppearance>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT project_id, encrypted_color, encrypted_icon FROM project_appearance")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let records = stmt
+            .query_map([], |row| {
+                Ok(ProjectAppearance {
+                    project_id: row.get(0)?,
+                    encrypted_color: row.get(1)?,
+                    encrypted_icon: row.get(2)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(records)
+    }
+
+    fn set_project_appearance(&self, appearance: &ProjectAppearance) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        if appearance.encrypted_color.is_none() && appearance.encrypted_icon.is_none() {
+            conn.execute_cached(
+                "DELETE FROM project_appearance WHERE project_id = ?1",
+                params![appearance.project_id],
+            )
+        } else {
+            conn.execute_cached(
+                "INSERT INTO project_appearance (project_id, encrypted_color, encrypted_icon)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(project_id) DO UPDATE SET
+                    encrypted_color = excluded.encrypted_color,
+                    encrypted_icon = excluded.encrypted_icon",
+                params![appearance.project_id, appearance.encrypted_color, appearance.encrypted_icon],
+            )
+        }
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn append_audit_event(&self, event: &AuditEvent) -> Result<(), StorageError> {
+        let conn = self.writable()?;
+        conn.execute_cached(
+            "INSERT INTO audit_log (seq, event, detail, created_at, mac) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![event.seq, event.event, event.detail, event.created_at, event.mac],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn list_audit_events(&self) -> Result<Vec<AuditEvent>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare_cached("SELECT seq, event, detail, created_at, mac FROM audit_log ORDER BY seq ASC")
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], audit_event_from_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn last_audit_event(&self) -> Result<Option<AuditEvent>, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached(
+            "SELECT seq, event, detail, created_at, mac FROM audit_log ORDER BY seq DESC LIMIT 1",
+            [],
+            audit_event_from_row,
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn change_seq(&self) -> Result<i64, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row_cached("SELECT COALESCE(MAX(seq), 0) FROM change_log", [], |row| row.get(0))
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn get_changes_since(&self, seq: i64, limit: usize) -> Result<ChangeFeed, StorageError> {
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        let (oldest, latest): (Option<i64>, Option<i64>) = conn
+            .query_row_cached("SELECT MIN(seq), MAX(seq) FROM change_log", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let latest = latest.unwrap_or(0);
+        // Behind the pruned part of the log, or ahead of it: a reader of another vault.
+        if seq > latest || oldest.is_some_and(|oldest| seq < oldest - 1) {
+            return Ok(ChangeFeed {
+                seq: latest,
+                changes: Vec::new(),
+                has_more: false,
+                reset: true,
+            });
+        }
+
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT seq, entity, entity_id, op FROM change_log
+                 WHERE seq > ?1 ORDER BY seq ASC LIMIT ?2",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let changes = stmt
+            .query_map(params![seq, limit as i64], |row| {
+                Ok(Change {
+                    seq: row.get(0)?,
+                    entity: row.get(1)?,
+                    entity_id: row.get(2)?,
+                    op: row.get(3)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let next = changes.last().map_or(seq, |c| c.seq);
+        Ok(ChangeFeed {
+            seq: next,
+            has_more: next < latest,
+            changes,
+            reset: false,
+        })
+    }
+
+    fn metrics(&self) -> Option<StorageMetrics> {
+        Some(self.metrics.snapshot())
+    }
+
+    fn set_slow_threshold(&self, threshold: Option<Duration>) {
+        self.metrics.set_slow_threshold(threshold);
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.header.is_some()
+    }
+
+    fn nonce_reserve(&self) -> Option<crypto::nonce::Reserve> {
+        if self.read_only {
+            return None;
+        }
+        // Encryption can run while this storage holds the connection for a write; rather than
+        // wait on itself, the reservation then fails and that one nonce is random.
+        let conn = Arc::downgrade(&self.conn);
+        Some(Box::new(move |after, count| {
+            let conn = conn.upgrade().ok_or("The vault is closed")?;
+            let mut conn = conn
+                .try_lock()
+                .map_err(|_| "The vault connection is busy".to_string())?;
+            reserve_nonces(&mut conn, after, count).map_err(|e| e.to_string())
+        }))
+    }
+
+    fn checkpoint(&self) -> Result<(), StorageError> {
+        if self.read_only || self.is_sealed() {
+            return Ok(());
+        }
+        let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn compact(
+        &self,
+        progress: &mut dyn FnMut(CompactProgress),
+    ) -> Result<CompactStats, StorageError> {
+        let conn = self.writable()?;
+        let total_steps = 2;
+        progress(CompactProgress { target: CompactTarget::Vault, step: 1, total_steps });
+        let mut stats = compact::vacuum(&conn, self.tuning.wal)?;
+        drop(conn);
+        progress(CompactProgress { target: CompactTarget::Attachments, step: 2, total_steps });
+        if let Some(shard) = self.shard.compact()? {
+            stats.add(shard);
+        }
+        Ok(stats)
+    }
+
+    fn unseal(&self, key: &[u8; crypto::KEY_LEN]) -> Result<(), StorageError> {
+        if !self.is_sealed() {
+            return Ok(());
+        }
+        // A connection that was given a wrong key is not reused.
+        let conn = if self.read_only {
+            Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        } else {
+            Connection::open(&self.db_path)
+        }
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        cipher::apply_key(&conn, &cipher::database_key(key))?;
+        let mut info = format_info(&conn)?;
+        info.encrypted = true;
+        self.refuse(&info)?;
+        tune(&conn, &self.tuning, self.read_only)?;
+
+        *self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))? = conn;
+        self.sealed.store(false, Ordering::SeqCst);
+        if !self.read_only {
+            self.init()?;
+            if let Some(path) = &self.header {
+                let conn = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+                cipher::refresh_header(&conn, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rekey(&self, key: &[u8; crypto::KEY_LEN]) -> Result<(), StorageError> {
+        if self.header.is_none() {
+            return Ok(());
+        }
+        let conn = self.writable()?;
+        cipher::rekey(&conn, &cipher::database_key(key))
+    }
+
+    fn put_blob_chunk(&self, blob_id: &str, index: u32, data: &[u8]) -> Result<(), StorageError> {
+        self.shard.put_chunk(blob_id, index, data)
+    }
+
+    fn get_blob_chunk(&self, blob_id: &str, index: u32) -> Result<Option<Vec<u8>>, StorageError> {
+        self.shard.get_chunk(blob_id, index)
+    }
+
+    fn delete_blob(&self, blob_id: &str) -> Result<(), StorageError> {
+        self.shard.delete_blob(blob_id)
+    }
+
+    fn list_blobs(&self) -> Result<Vec<BlobInfo>, StorageError> {
+        self.shard.list_blobs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: String::new(),
+            encrypted_content: vec![1],
+            key_check: Vec::new(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 7,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn archiving_is_undone_by_unarchiving() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage.create_project(&project("a")).unwrap();
+        storage.archive_project("a", "2026-02-01T00:00:00Z").unwrap();
+        assert!(storage.archive_project("a", "2026-02-02T00:00:00Z").is_err());
+        assert_eq!(
+            storage.list_archived_projects().unwrap(),
+            vec![("a".to_string(), "2026-02-01T00:00:00Z".to_string())]
+        );
+
+        storage.unarchive_project("a").unwrap();
+        assert!(storage.list_archived_projects().unwrap().is_empty());
+        assert!(storage.unarchive_project("a").is_err());
+    }
+
+    #[test]
+    fn updates_leave_the_pin_alone() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage.create_project(&project("a")).unwrap();
+        storage.set_pinned("a", true).unwrap();
+        storage.update_project(&project("a")).unwrap();
+        assert!(storage.get_project("a").unwrap().pinned);
+        assert!(storage.set_pinned("missing", true).is_err());
+    }
+
+    #[test]
+    fn every_update_bumps_the_revision() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let mut project = project("a");
+        storage.create_project(&project).unwrap();
+        assert_eq!(storage.get_project("a").unwrap().revision, 0);
+
+        project.revision = 0;
+        storage.update_project(&project).unwrap();
+        storage.update_project(&project).unwrap();
+        assert_eq!(storage.list_projects().unwrap()[0].revision, 2);
+    }
+
+    #[test]
+    fn older_formats_are_stamped_with_this_one() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        crate::vault::write_identity(&storage, "2026-01-01T00:00:00Z").unwrap();
+        storage.set_meta(META_FORMAT_VERSION, "1").unwrap();
+        storage.set_meta(META_READ_COMPAT_VERSION, "1").unwrap();
+
+        storage.init().unwrap();
+        let format = storage.current_format().unwrap();
+        assert_eq!(format.format_version, Some(VAULT_FORMAT_VERSION));
+        assert_eq!(format.read_compat_version, Some(VAULT_FORMAT_VERSION));
+    }
+
+    #[test]
+    fn large_content_is_written_and_read_in_place() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [0x3c; crypto::KEY_LEN];
+        let note = "a long note\n".repeat(crypto::STREAM_THRESHOLD / 10);
+        let mut a = project("a");
+        let seal = crypto::content::stream(note.as_bytes(), &key, "a", "").unwrap();
+        storage.create_project_streamed(&a, &seal).unwrap();
+        let stored = storage.get_project("a").unwrap();
+        assert_eq!(stored.encrypted_content.len() as u64, seal.sealed_len());
+        let meta = storage.get_project_meta("a").unwrap();
+        assert!(meta.encrypted_content.is_empty());
+        let opened = crate::rehash::read_project_content(&storage, &meta, &key).unwrap();
+        assert_eq!(opened, note.as_bytes());
+
+        a.name = "Renamed".to_string();
+        let note = "another long note\n".repeat(crypto::STREAM_THRESHOLD / 10);
+        let seal = crypto::content::stream(note.as_bytes(), &key, "a", "Renamed").unwrap();
+        storage.update_project_streamed(&a, &seal).unwrap();
+        let meta = storage.get_project_meta("a").unwrap();
+        assert_eq!((meta.name.as_str(), meta.revision), ("Renamed", 1));
+        let opened = crate::rehash::read_project_content(&storage, &meta, &key).unwrap();
+        assert_eq!(opened, note.as_bytes());
+        assert!(storage.update_project_streamed(&project("b"), &seal).is_err());
+    }
+
+    #[test]
+    fn opening_a_project_is_not_a_change() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage.create_projects_batch(&[project("a"), project("b")]).unwrap();
+        let seq = storage.change_seq().unwrap();
+        storage.record_access("b", "2026-01-02T00:00:00Z").unwrap();
+        storage.record_access("a", "2026-01-03T00:00:00Z").unwrap();
+        assert_eq!(storage.change_seq().unwrap(), seq);
+        assert_eq!(storage.get_project("a").unwrap().revision, 0);
+
+        let recent: Vec<String> =
+            storage.recently_accessed(10).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(recent, ["a", "b"]);
+        storage.trash_project("a", "2026-01-04T00:00:00Z").unwrap();
+        assert_eq!(storage.recently_accessed(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn a_failing_batch_stores_nothing() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let batch = [project("a"), project("b"), project("a")];
+        assert!(storage.create_projects_batch(&batch).is_err());
+        assert!(storage.list_projects().unwrap().is_empty());
+
+        storage.create_projects_batch(&batch[..2]).unwrap();
+        let ids: Vec<String> =
+            storage.list_projects().unwrap().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[test]
+    fn nonce_reservations_persist_and_never_repeat() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let reserve = storage.nonce_reserve().unwrap();
+        let (prefix, start) = reserve(0, 10).unwrap();
+        assert_eq!(start, 0);
+        assert_eq!(reserve(0, 10).unwrap(), (prefix, 10));
+
+        // A write rolled back with the reservation in it does not hand the values out again.
+        storage.begin_transaction().unwrap();
+        assert_eq!(reserve(0, 10).unwrap(), (prefix, 20));
+        storage.rollback_transaction().unwrap();
+        assert_eq!(reserve(30, 10).unwrap(), (prefix, 30));
+
+        let conn = storage.conn.lock().unwrap();
+        assert!(reserve(0, 10).is_err());
+        drop(conn);
+        drop(storage);
+        assert!(reserve(0, 10).is_err());
+    }
+
+    #[test]
+    fn file_vaults_open_in_wal_mode_and_readers_see_committed_writes() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-tuning-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+
+        let storage = LocalStorage::new(&db_path).unwrap();
+        let mode: String = storage
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        storage.set_setting("theme", "dark").unwrap();
+
+        // Another connection reads while the vault is open.
+        let reader = LocalStorage::open_read_only(&db_path).unwrap();
+        assert_eq!(reader.get_setting("theme").unwrap().as_deref(), Some("dark"));
+        drop((reader, storage));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let tuning = Tuning::from_vars(|name| match name {
+            "VAULTPAD_DB_SYNCHRONOUS" => Some("FULL".to_string()),
+            "VAULTPAD_DB_BUSY_TIMEOUT_MS" => Some("soon".to_string()),
+            _ => None,
+        });
+        assert_eq!(tuning.synchronous, Synchronous::Full);
+        assert_eq!(tuning.busy_timeout, Tuning::default().busy_timeout);
+    }
+
+    #[test]
+    fn failed_transactions_leave_nothing_behind() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage.create_project(&project("a")).unwrap();
+        let failed: Result<(), String> = crate::storage::in_transaction(&storage, || {
+            storage.create_project(&project("b")).map_err(|e| e.to_string())?;
+            storage.create_projects_batch(&[project("c")]).map_err(|e| e.to_string())?;
+            Err("later step failed".to_string())
+        });
+        assert!(failed.is_err());
+        let ids: Vec<String> =
+            storage.list_projects().unwrap().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["a"]);
+
+        crate::storage::in_transaction(&storage, || {
+            storage.create_project(&project("b")).map_err(|e| e.to_string())
+        })
+        .unwrap();
+        assert_eq!(storage.list_projects().unwrap().len(), 2);
+    }
 }
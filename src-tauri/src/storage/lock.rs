@@ -0,0 +1,77 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! An exclusive advisory lock per vault, so two app instances never write the same database.
+//! The lock is taken on a `<db>.lock` file next to it rather than on the database itself, whose
+//! byte ranges SQLite locks on its own (mandatorily, on Windows). Within this process a vault
+//! can be opened any number of times; the lock goes when the last `LocalStorage` holding it is
+//! dropped.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+
+use super::StorageError;
+
+pub const LOCK_SUFFIX: &str = ".lock";
+
+/// Locks this process holds, by lock file path.
+static HELD: Mutex<BTreeMap<PathBuf, Weak<File>>> = Mutex::new(BTreeMap::new());
+
+pub fn lock_path(db_path: &str) -> PathBuf {
+    PathBuf::from(format!("{db_path}{LOCK_SUFFIX}"))
+}
+
+/// Lock the vault at `db_path` for this process. `None` for an in-memory database, and for a
+/// read-only one whose folder does not allow creating the lock file.
+pub fn acquire(db_path: &str, read_only: bool) -> Result<Option<Arc<File>>, StorageError> {
+    if db_path == ":memory:" {
+        return Ok(None);
+    }
+    let path = lock_path(db_path);
+    let mut held = HELD.lock().map_err(|e| StorageError::Io(e.to_string()))?;
+    held.retain(|_, lock| lock.strong_count() > 0);
+    if let Some(lock) = held.get(&path).and_then(Weak::upgrade) {
+        return Ok(Some(lock));
+    }
+
+    let opened = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path);
+    let file = match opened {
+        Ok(file) => file,
+        Err(_) if read_only => return Ok(None),
+        Err(e) => return Err(StorageError::Io(e.to_string())),
+    };
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Err(StorageError::InUse),
+        Err(TryLockError::Error(e)) => return Err(StorageError::Io(e.to_string())),
+    }
+    let lock = Arc::new(file);
+    held.insert(path, Arc::downgrade(&lock));
+    Ok(Some(lock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_lock_held_elsewhere_refuses_the_vault() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+
+        // Another instance, as far as the lock is concerned: a handle not in `HELD`.
+        let other = File::create(lock_path(&db_path)).unwrap();
+        other.lock().unwrap();
+        assert!(matches!(acquire(&db_path, false), Err(StorageError::InUse)));
+        drop(other);
+
+        let first = acquire(&db_path, false).unwrap().unwrap();
+        let second = acquire(&db_path, false).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        drop((first, second));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! In-memory read/write statistics for `LocalStorage`: a call count and a latency histogram per
+//! operation, including time spent waiting for the connection. Nothing is persisted; the
+//! numbers describe the current run, to tell a slow disk or an antivirus scanner from a slow UI.
+//!
+//! With a threshold set, operations slower than it are also kept in a short log with their SQL,
+//! which never includes the bound values, so the log can go into a bug report as it is.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    List,
+    Get,
+    Create,
+    Update,
+    Delete,
+    /// A multi-statement write, timed from its first statement to the commit.
+    Transaction,
+}
+
+const OPERATIONS: [Operation; 6] = [
+    Operation::List,
+    Operation::Get,
+    Operation::Create,
+    Operation::Update,
+    Operation::Delete,
+    Operation::Transaction,
+];
+
+/// How many slow operations are kept; older ones make room.
+const SLOW_LOG_LEN: usize = 100;
+
+/// Upper bounds of the histogram buckets in milliseconds; a last bucket catches the rest.
+const BUCKET_BOUNDS_MS: [u64; 7] = [1, 5, 10, 50, 100, 500, 1000];
+
+impl Operation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operation::List => "list",
+            Operation::Get => "get",
+            Operation::Create => "create",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+            Operation::Transaction => "transaction",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Stats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bucket {
+    /// Upper bound in milliseconds; `None` for the overflow bucket.
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationMetrics {
+    pub operation: String,
+    pub count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<Bucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowOperation {
+    pub operation: String,
+    pub statement: String,
+    pub at: String,
+    pub millis: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageMetrics {
+    pub since: String,
+    pub operations: Vec<OperationMetrics>,
+    /// `None` while the slow-operation log is off.
+    pub slow_threshold_ms: Option<u64>,
+    /// Newest first.
+    pub slow_operations: Vec<SlowOperation>,
+}
+
+pub struct Metrics {
+    since: String,
+    stats: Mutex<[Stats; OPERATIONS.len()]>,
+    slow_threshold: Mutex<Option<Duration>>,
+    slow: Mutex<VecDeque<SlowOperation>>,
+}
+
+/// Records the time from `Metrics::start` until it is dropped.
+pub struct Timer<'a> {
+    metrics: &'a Metrics,
+    operation: Operation,
+    statement: &'static str,
+    started: Instant,
+}
+
+impl Drop for Timer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        self.metrics.record(self.operation, elapsed);
+        self.metrics.record_if_slow(self.operation, self.statement, elapsed);
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            since: chrono::Utc::now().to_rfc3339(),
+            stats: Mutex::new([Stats::default(); OPERATIONS.len()]),
+            slow_threshold: Mutex::new(None),
+            slow: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Time one operation. `statement` is its SQL with placeholders, or for a transaction a
+    /// description of it; never anything with stored values in it.
+    pub fn start(&self, operation: Operation, statement: &'static str) -> Timer<'_> {
+        Timer {
+            metrics: self,
+            operation,
+            statement,
+            started: Instant::now(),
+        }
+    }
+
+    /// Log operations slower than `threshold` from now on; `None` turns the log off and clears it.
+    pub fn set_slow_threshold(&self, threshold: Option<Duration>) {
+        if let Ok(mut current) = self.slow_threshold.lock() {
+            *current = threshold;
+        }
+        if threshold.is_none() {
+            if let Ok(mut slow) = self.slow.lock() {
+                slow.clear();
+            }
+        }
+    }
+
+    fn record_if_slow(&self, operation: Operation, statement: &str, elapsed: Duration) {
+        let threshold = self.slow_threshold.lock().ok().and_then(|t| *t);
+        if threshold.is_none_or(|t| elapsed < t) {
+            return;
+        }
+        let Ok(mut slow) = self.slow.lock() else {
+            return;
+        };
+        if slow.len() == SLOW_LOG_LEN {
+            slow.pop_back();
+        }
+        slow.push_front(SlowOperation {
+            operation: operation.as_str().to_string(),
+            // Collapse the indentation of multi-line SQL.
+            statement: statement.split_whitespace().collect::<Vec<_>>().join(" "),
+            at: chrono::Utc::now().to_rfc3339(),
+            millis: millis(elapsed),
+        });
+    }
+
+    fn record(&self, operation: Operation, elapsed: Duration) {
+        let Ok(mut stats) = self.stats.lock() else {
+            return;
+        };
+        let entry = &mut stats[operation as usize];
+        entry.count += 1;
+        entry.total += elapsed;
+        entry.max = entry.max.max(elapsed);
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|&bound| elapsed <= Duration::from_millis(bound))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        entry.buckets[bucket] += 1;
+    }
+
+    pub fn snapshot(&self) -> StorageMetrics {
+        let stats = self.stats.lock().map(|s| *s).unwrap_or_default();
+        StorageMetrics {
+            since: self.since.clone(),
+            operations: OPERATIONS
+                .iter()
+                .map(|&op| {
+                    let s = &stats[op as usize];
+                    OperationMetrics {
+                        operation: op.as_str().to_string(),
+                        count: s.count,
+                        total_ms: millis(s.total),
+                        mean_ms: if s.count == 0 {
+                            0.0
+                        } else {
+                            millis(s.total) / s.count as f64
+                        },
+                        max_ms: millis(s.max),
+                        buckets: s
+                            .buckets
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &count)| Bucket {
+                                le_ms: BUCKET_BOUNDS_MS.get(i).copied(),
+                                count,
+                            })
+                            .collect(),
+                    }
+                })
+                .collect(),
+            slow_threshold_ms: self
+                .slow_threshold
+                .lock()
+                .ok()
+                .and_then(|t| *t)
+                .map(|t| t.as_millis() as u64),
+            slow_operations: self
+                .slow
+                .lock()
+                .map(|slow| slow.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latencies_land_in_buckets() {
+        let metrics = Metrics::new();
+        metrics.record(Operation::Get, Duration::from_micros(300));
+        metrics.record(Operation::Get, Duration::from_millis(7));
+        metrics.record(Operation::Get, Duration::from_secs(3));
+        let snapshot = metrics.snapshot();
+        let get = &snapshot.operations[Operation::Get as usize];
+        assert_eq!(get.count, 3);
+        let counts: Vec<u64> = get.buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 0, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(get.buckets.last().unwrap().le_ms, None);
+        assert_eq!(snapshot.operations[Operation::List as usize].count, 0);
+    }
+
+    #[test]
+    fn only_slow_operations_are_logged() {
+        let metrics = Metrics::new();
+        metrics.record_if_slow(Operation::Get, "SELECT 1", Duration::from_secs(3));
+        assert!(metrics.snapshot().slow_operations.is_empty());
+
+        metrics.set_slow_threshold(Some(Duration::from_millis(500)));
+        metrics.record_if_slow(Operation::Get, "SELECT 1", Duration::from_millis(20));
+        let statement = "UPDATE projects\n   SET name = ?2";
+        metrics.record_if_slow(Operation::Update, statement, Duration::from_secs(3));
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.slow_threshold_ms, Some(500));
+        assert_eq!(snapshot.slow_operations.len(), 1);
+        assert_eq!(snapshot.slow_operations[0].statement, "UPDATE projects SET name = ?2");
+
+        metrics.set_slow_threshold(None);
+        assert!(metrics.snapshot().slow_operations.is_empty());
+    }
+}
@@ -0,0 +1,621 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Numbered schema migrations. The version a database has reached is kept as `schema_version`
+//! in `vault_meta`; opening it applies every later migration in order, each in its own
+//! transaction together with the version bump, so a crash leaves the database at the last
+//! migration that completed. A database whose version is above `SCHEMA_VERSION` was written by
+//! a newer app and is refused.
+//!
+//! Migrations are append-only: once released, one is never edited, a change gets a new one.
+//! Databases from before versioning start at 0; the baseline brings every layout older builds
+//! may have left behind up to date, which is why it checks columns before adding them.
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::StorageError;
+
+pub const META_SCHEMA_VERSION: &str = "schema_version";
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "baseline",
+        apply: baseline,
+    },
+    Migration {
+        version: 2,
+        description: "project revisions",
+        apply: project_revisions,
+    },
+    Migration {
+        version: 3,
+        description: "project indices",
+        apply: project_indices,
+    },
+    Migration {
+        version: 4,
+        description: "project access times",
+        apply: project_access_times,
+    },
+    Migration {
+        version: 5,
+        description: "archived projects",
+        apply: archived_projects,
+    },
+    Migration {
+        version: 6,
+        description: "pinned projects",
+        apply: pinned_projects,
+    },
+    Migration {
+        version: 7,
+        description: "project appearance",
+        apply: project_appearance,
+    },
+    Migration {
+        version: 8,
+        description: "custom fields",
+        apply: custom_fields,
+    },
+    Migration {
+        version: 9,
+        description: "project TOTP secrets",
+        apply: project_totp,
+    },
+    Migration {
+        version: 10,
+        description: "password history of credential projects",
+        apply: password_history,
+    },
+    Migration {
+        version: 11,
+        description: "project templates",
+        apply: templates,
+    },
+    Migration {
+        version: 12,
+        description: "project checklists",
+        apply: checklist_items,
+    },
+    Migration {
+        version: 13,
+        description: "encrypted search index",
+        apply: search_index,
+    },
+    Migration {
+        version: 14,
+        description: "autosaved drafts",
+        apply: drafts,
+    },
+    Migration {
+        version: 15,
+        description: "KDF parameters in vault_meta",
+        apply: kdf_params_meta,
+    },
+];
+
+/// Schema version this build migrates databases to.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+fn db_err(e: rusqlite::Error) -> StorageError {
+    StorageError::Database(e.to_string())
+}
+
+/// The schema version recorded in the database, 0 when none is.
+pub fn schema_version(conn: &Connection) -> Result<u32, StorageError> {
+    let has_meta: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'vault_meta'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(db_err)?;
+    if !has_meta {
+        return Ok(0);
+    }
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM vault_meta WHERE key = ?1",
+            params![META_SCHEMA_VERSION],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(db_err)?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(0))
+}
+
+/// Apply the pending migrations; returns the version the database is at afterwards.
+pub fn run(conn: &Connection) -> Result<u32, StorageError> {
+    let current = schema_version(conn)?;
+    if current > SCHEMA_VERSION {
+        return Err(StorageError::SchemaTooNew {
+            found: current,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction().map_err(db_err)?;
+        (migration.apply)(&tx).map_err(|e| {
+            StorageError::Database(format!(
+                "Migration {} ({}) failed: {e}",
+                migration.version, migration.description
+            ))
+        })?;
+        tx.execute(
+            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?1, ?2)",
+            params![META_SCHEMA_VERSION, migration.version.to_string()],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)?;
+    }
+    Ok(SCHEMA_VERSION)
+}
+
+/// Every table as of the introduction of versioning, plus the column additions older databases
+/// still need.
+fn baseline(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL DEFAULT '',
+            encrypted_content BLOB NOT NULL,
+            key_check BLOB,
+            sort_order INTEGER DEFAULT 0,
+            position TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            server_id TEXT,
+            sync_status TEXT DEFAULT 'local',
+            last_synced_at TEXT,
+            folder_id TEXT,
+            deleted_at TEXT
+        );
+        CREATE TABLE IF NOT EXISTS folders (
+            id TEXT PRIMARY KEY,
+            parent_id TEXT,
+            encrypted_name BLOB NOT NULL,
+            position TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            sync_status TEXT NOT NULL DEFAULT 'local'
+        );
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS verification (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            token BLOB NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS vault_meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );"
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_backups (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            encrypted_content BLOB NOT NULL,
+            key_check BLOB,
+            created_at TEXT NOT NULL,
+            trigger_type TEXT NOT NULL,
+            content_length INTEGER NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_backups_project
+            ON project_backups(project_id, created_at DESC);"
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_versions (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            encrypted_content BLOB NOT NULL,
+            key_check BLOB,
+            updated_at TEXT NOT NULL,
+            saved_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_versions_project
+            ON project_versions(project_id, saved_at DESC);"
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS attachments (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            wrapped_key BLOB NOT NULL,
+            encrypted_name BLOB NOT NULL,
+            encrypted_mime BLOB NOT NULL,
+            size INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_attachments_project
+            ON attachments(project_id, created_at);"
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            seq INTEGER PRIMARY KEY,
+            event TEXT NOT NULL,
+            detail TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            mac TEXT NOT NULL
+        );
+        CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit log is append-only'); END;
+        CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit log is append-only'); END;"
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            encrypted_name BLOB NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS project_tags (
+            project_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY (project_id, tag_id),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_project_tags_tag ON project_tags(tag_id);
+        CREATE TRIGGER IF NOT EXISTS project_tags_drop_unused AFTER DELETE ON project_tags
+        WHEN NOT EXISTS (SELECT 1 FROM project_tags WHERE tag_id = OLD.tag_id)
+        BEGIN DELETE FROM tags WHERE id = OLD.tag_id; END;"
+    )?;
+
+    // Every write to projects, history and settings lands in the change feed, whichever
+    // code path (or process) made it.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS change_log (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            op TEXT NOT NULL
+        );
+        CREATE TRIGGER IF NOT EXISTS change_log_project_insert AFTER INSERT ON projects
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.id, 'insert'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_update AFTER UPDATE ON projects
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_delete AFTER DELETE ON projects
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.id, 'delete'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_backup_insert AFTER INSERT ON project_backups
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('backup', NEW.id, 'insert'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_backup_update AFTER UPDATE ON project_backups
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('backup', NEW.id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_backup_delete AFTER DELETE ON project_backups
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('backup', OLD.id, 'delete'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_setting_insert AFTER INSERT ON settings
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('setting', NEW.key, 'insert'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_setting_update AFTER UPDATE ON settings
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('setting', NEW.key, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_setting_delete AFTER DELETE ON settings
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('setting', OLD.key, 'delete'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_folder_insert AFTER INSERT ON folders
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('folder', NEW.id, 'insert'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_folder_update AFTER UPDATE ON folders
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('folder', NEW.id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_folder_delete AFTER DELETE ON folders
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('folder', OLD.id, 'delete'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_tag_insert AFTER INSERT ON tags
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('tag', NEW.id, 'insert'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_tag_update AFTER UPDATE ON tags
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('tag', NEW.id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_tag_delete AFTER DELETE ON tags
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('tag', OLD.id, 'delete'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_tag_insert AFTER INSERT ON project_tags
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_tag_delete AFTER DELETE ON project_tags
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )?;
+
+    let project_cols: Vec<String> = {
+        let mut stmt = conn.prepare("PRAGMA table_info(projects)")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    if !project_cols.contains(&"last_synced_at".to_string()) {
+        conn.execute_batch("ALTER TABLE projects ADD COLUMN last_synced_at TEXT;")?;
+    }
+    if !project_cols.contains(&"name".to_string()) {
+        conn.execute_batch(
+            "ALTER TABLE projects ADD COLUMN name TEXT NOT NULL DEFAULT '';
+             ALTER TABLE projects ADD COLUMN key_check BLOB;"
+        )?;
+    }
+
+    if !project_cols.is_empty() && !project_cols.contains(&"position".to_string()) {
+        conn.execute_batch("ALTER TABLE projects ADD COLUMN position TEXT NOT NULL DEFAULT '';")?;
+    }
+    if !project_cols.is_empty() && !project_cols.contains(&"folder_id".to_string()) {
+        conn.execute_batch("ALTER TABLE projects ADD COLUMN folder_id TEXT;")?;
+    }
+    if !project_cols.is_empty() && !project_cols.contains(&"deleted_at".to_string()) {
+        conn.execute_batch("ALTER TABLE projects ADD COLUMN deleted_at TEXT;")?;
+    }
+    let backup_cols: Vec<String> = {
+        let mut stmt = conn.prepare("PRAGMA table_info(project_backups)")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    if !backup_cols.is_empty() && !backup_cols.contains(&"name".to_string()) {
+        conn.execute_batch(
+            "ALTER TABLE project_backups ADD COLUMN name TEXT NOT NULL DEFAULT '';
+             ALTER TABLE project_backups ADD COLUMN key_check BLOB;"
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A revision counter per project, for refusing edits based on stale data.
+fn project_revisions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE projects ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;")
+}
+
+/// Indices for listing projects in order (`sort_order` only orders ones from before positions)
+/// and for the sync scans, which look projects up by status, server id and modification time.
+fn project_indices(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_projects_position ON projects(position, created_at, id);
+        CREATE INDEX IF NOT EXISTS idx_projects_sort_order ON projects(sort_order);
+        CREATE INDEX IF NOT EXISTS idx_projects_updated_at ON projects(updated_at);
+        CREATE INDEX IF NOT EXISTS idx_projects_sync_status ON projects(sync_status);
+        CREATE INDEX IF NOT EXISTS idx_projects_server_id ON projects(server_id);"
+    )
+}
+
+/// When each project was last opened, for the recent list. Recording that is not a change, so
+/// the change log's update trigger now skips updates that only set it.
+fn project_access_times(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE projects ADD COLUMN last_accessed_at TEXT;
+        CREATE INDEX IF NOT EXISTS idx_projects_last_accessed_at ON projects(last_accessed_at);
+        DROP TRIGGER IF EXISTS change_log_project_update;
+        CREATE TRIGGER change_log_project_update AFTER UPDATE ON projects
+        WHEN NEW.last_accessed_at IS OLD.last_accessed_at
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.id, 'update'); END;"
+    )
+}
+
+fn archived_projects(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE projects ADD COLUMN archived_at TEXT;")
+}
+
+fn pinned_projects(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE projects ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;")
+}
+
+fn project_appearance(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_appearance (
+            project_id TEXT PRIMARY KEY,
+            encrypted_color BLOB,
+            encrypted_icon BLOB,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE TRIGGER IF NOT EXISTS change_log_project_appearance_insert
+        AFTER INSERT ON project_appearance
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_appearance_update
+        AFTER UPDATE ON project_appearance
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_appearance_delete
+        AFTER DELETE ON project_appearance
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )
+}
+
+fn custom_fields(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_fields (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            position TEXT NOT NULL,
+            encrypted_field BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_project_fields_project
+            ON project_fields(project_id, position);
+        CREATE TRIGGER IF NOT EXISTS change_log_project_field_insert
+        AFTER INSERT ON project_fields
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_field_update
+        AFTER UPDATE ON project_fields
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_field_delete
+        AFTER DELETE ON project_fields
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )
+}
+
+fn project_totp(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_totp (
+            project_id TEXT PRIMARY KEY,
+            encrypted_secret BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE TRIGGER IF NOT EXISTS change_log_project_totp_insert
+        AFTER INSERT ON project_totp
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_totp_update
+        AFTER UPDATE ON project_totp
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_project_totp_delete
+        AFTER DELETE ON project_totp
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )
+}
+
+fn password_history(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS password_history (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            encrypted_password BLOB NOT NULL,
+            replaced_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_password_history_project
+            ON password_history(project_id, replaced_at);
+        CREATE TRIGGER IF NOT EXISTS change_log_password_history_insert
+        AFTER INSERT ON password_history
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_password_history_update
+        AFTER UPDATE ON password_history
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_password_history_delete
+        AFTER DELETE ON password_history
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )
+}
+
+fn templates(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS templates (
+            id TEXT PRIMARY KEY,
+            encrypted_template BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            sync_status TEXT NOT NULL DEFAULT 'local'
+        );"
+    )
+}
+
+fn checklist_items(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS checklist_items (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            position TEXT NOT NULL DEFAULT '',
+            encrypted_item BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            sync_status TEXT NOT NULL DEFAULT 'local',
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_checklist_items_project
+            ON checklist_items(project_id, position);
+        CREATE TRIGGER IF NOT EXISTS change_log_checklist_items_insert
+        AFTER INSERT ON checklist_items
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_checklist_items_update
+        AFTER UPDATE ON checklist_items
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', NEW.project_id, 'update'); END;
+        CREATE TRIGGER IF NOT EXISTS change_log_checklist_items_delete
+        AFTER DELETE ON checklist_items
+        WHEN EXISTS (SELECT 1 FROM projects WHERE id = OLD.project_id)
+        BEGIN INSERT INTO change_log (entity, entity_id, op) VALUES ('project', OLD.project_id, 'update'); END;"
+    )
+}
+
+fn search_index(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS search_index (
+            project_id TEXT PRIMARY KEY,
+            encrypted_filter BLOB NOT NULL,
+            indexed_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );"
+    )
+}
+
+fn drafts(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS drafts (
+            project_id TEXT PRIMARY KEY,
+            encrypted_draft BLOB NOT NULL,
+            base_revision INTEGER NOT NULL,
+            saved_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );"
+    )
+}
+
+/// Vaults from before `vault_meta` kept their KDF parameters among the settings, where an
+/// unlock no longer looks for them; without this they would unlock with the defaults.
+fn kdf_params_meta(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO vault_meta (key, value)
+            SELECT key, value FROM settings WHERE key = 'kdf_params';
+        DELETE FROM settings WHERE key = 'kdf_params';"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_run_once_and_newer_schemas_are_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert_eq!(run(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(run(&conn).unwrap(), SCHEMA_VERSION);
+
+        conn.execute(
+            "UPDATE vault_meta SET value = ?1 WHERE key = ?2",
+            params![(SCHEMA_VERSION + 1).to_string(), META_SCHEMA_VERSION],
+        )
+        .unwrap();
+        assert!(matches!(run(&conn), Err(StorageError::SchemaTooNew { .. })));
+    }
+
+    #[test]
+    fn kdf_params_move_from_settings_to_vault_meta() {
+        let conn = Connection::open_in_memory().unwrap();
+        baseline(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('kdf_params', ?1)",
+            params![r#"{"memory_kib":65536}"#],
+        )
+        .unwrap();
+        run(&conn).unwrap();
+
+        let meta: String = conn
+            .query_row("SELECT value FROM vault_meta WHERE key = 'kdf_params'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(meta, r#"{"memory_kib":65536}"#);
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM settings WHERE key = 'kdf_params'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn versions_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as u32 + 1);
+        }
+    }
+}
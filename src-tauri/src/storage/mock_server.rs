@@ -0,0 +1,426 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! In-process stand-in for the VaultPad server, for tests. It implements `/health`, login,
+//! `/auth/me` and the projects API, keeps everything in memory and serves on a local port from
+//! its own thread, so the blocking `RemoteStorage` and the sync commands can talk to it as they
+//! would to a real server. Tests can revoke tokens, queue failures and change what `/health`
+//! reports. New protocol features should get their endpoints here along with the client code.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+#[derive(Debug, Clone)]
+struct StoredProject {
+    id: i64,
+    owner: i64,
+    name: String,
+    encrypted_content: String,
+    key_check: String,
+    position: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl StoredProject {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "encrypted_content": self.encrypted_content,
+            "key_check": self.key_check,
+            "position": self.position,
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+        })
+    }
+}
+
+struct User {
+    id: i64,
+    email: String,
+    password: String,
+}
+
+#[derive(Default)]
+struct MockState {
+    users: Vec<User>,
+    tokens: BTreeMap<String, i64>,
+    projects: BTreeMap<i64, StoredProject>,
+    next_id: i64,
+    health: Value,
+    /// Statuses returned, in order, instead of handling the next requests.
+    failures: Vec<StatusCode>,
+}
+
+type Shared = Arc<Mutex<MockState>>;
+
+#[derive(Deserialize)]
+struct LoginPayload {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct ProjectPayload {
+    name: String,
+    encrypted_content: String,
+    key_check: String,
+    #[serde(default)]
+    position: String,
+}
+
+fn lock(shared: &Shared) -> MutexGuard<'_, MockState> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A refused request: the status and a plain-text message, as the real server sends.
+type Refusal = (StatusCode, &'static str);
+
+fn take_failure(state: &mut MockState) -> Result<(), Refusal> {
+    if state.failures.is_empty() {
+        return Ok(());
+    }
+    Err((state.failures.remove(0), "injected failure"))
+}
+
+/// The user behind the bearer token, after any queued failure.
+fn authorize(state: &mut MockState, headers: &HeaderMap) -> Result<i64, Refusal> {
+    take_failure(state)?;
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|token| state.tokens.get(token).copied())
+        .ok_or((StatusCode::UNAUTHORIZED, "invalid token"))
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+async fn health(State(shared): State<Shared>) -> Response {
+    let mut state = lock(&shared);
+    if let Err(refusal) = take_failure(&mut state) {
+        return refusal.into_response();
+    }
+    Json(state.health.clone()).into_response()
+}
+
+async fn login(State(shared): State<Shared>, Json(payload): Json<LoginPayload>) -> Response {
+    let mut state = lock(&shared);
+    if let Err(refusal) = take_failure(&mut state) {
+        return refusal.into_response();
+    }
+    let Some(user_id) = state
+        .users
+        .iter()
+        .find(|u| u.email == payload.email && u.password == payload.password)
+        .map(|u| u.id)
+    else {
+        return (StatusCode::UNAUTHORIZED, "invalid credentials").into_response();
+    };
+    let token = uuid::Uuid::new_v4().to_string();
+    state.tokens.insert(token.clone(), user_id);
+    Json(json!({
+        "token": token,
+        "user": { "id": user_id, "email": payload.email, "is_admin": false },
+    }))
+    .into_response()
+}
+
+async fn me(State(shared): State<Shared>, headers: HeaderMap) -> Response {
+    let mut state = lock(&shared);
+    match authorize(&mut state, &headers) {
+        Ok(user_id) => {
+            let email = state
+                .users
+                .iter()
+                .find(|u| u.id == user_id)
+                .map(|u| u.email.clone());
+            Json(json!({ "id": user_id, "email": email, "is_admin": false })).into_response()
+        }
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+async fn list_projects(State(shared): State<Shared>, headers: HeaderMap) -> Response {
+    let mut state = lock(&shared);
+    match authorize(&mut state, &headers) {
+        Ok(user_id) => Json(
+            state
+                .projects
+                .values()
+                .filter(|p| p.owner == user_id)
+                .map(StoredProject::to_json)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+async fn list_meta(State(shared): State<Shared>, headers: HeaderMap) -> Response {
+    let mut state = lock(&shared);
+    match authorize(&mut state, &headers) {
+        Ok(user_id) => Json(
+            state
+                .projects
+                .values()
+                .filter(|p| p.owner == user_id)
+                .map(|p| json!({ "id": p.id, "updated_at": p.updated_at }))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+async fn create_project(
+    State(shared): State<Shared>,
+    headers: HeaderMap,
+    Json(payload): Json<ProjectPayload>,
+) -> Response {
+    let mut state = lock(&shared);
+    let user_id = match authorize(&mut state, &headers) {
+        Ok(user_id) => user_id,
+        Err(refusal) => return refusal.into_response(),
+    };
+    state.next_id += 1;
+    let stamp = now();
+    let project = StoredProject {
+        id: state.next_id,
+        owner: user_id,
+        name: payload.name,
+        encrypted_content: payload.encrypted_content,
+        key_check: payload.key_check,
+        position: payload.position,
+        created_at: stamp.clone(),
+        updated_at: stamp,
+    };
+    let body = project.to_json();
+    state.projects.insert(project.id, project);
+    (StatusCode::CREATED, Json(body)).into_response()
+}
+
+fn owned_project(
+    state: &mut MockState,
+    headers: &HeaderMap,
+    id: i64,
+) -> Result<StoredProject, Refusal> {
+    let user_id = authorize(state, headers)?;
+    state
+        .projects
+        .get(&id)
+        .filter(|p| p.owner == user_id)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "project not found"))
+}
+
+async fn get_project(
+    State(shared): State<Shared>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Response {
+    let mut state = lock(&shared);
+    match owned_project(&mut state, &headers, id) {
+        Ok(project) => Json(project.to_json()).into_response(),
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+async fn update_project(
+    State(shared): State<Shared>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Json(payload): Json<ProjectPayload>,
+) -> Response {
+    let mut state = lock(&shared);
+    let mut project = match owned_project(&mut state, &headers, id) {
+        Ok(project) => project,
+        Err(refusal) => return refusal.into_response(),
+    };
+    project.name = payload.name;
+    project.encrypted_content = payload.encrypted_content;
+    project.key_check = payload.key_check;
+    project.position = payload.position;
+    project.updated_at = now();
+    let body = project.to_json();
+    state.projects.insert(id, project);
+    Json(body).into_response()
+}
+
+async fn delete_project(
+    State(shared): State<Shared>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Response {
+    let mut state = lock(&shared);
+    match owned_project(&mut state, &headers, id) {
+        Ok(_) => {
+            state.projects.remove(&id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+pub struct MockServer {
+    pub url: String,
+    state: Shared,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Serve on a free local port until the returned value is dropped.
+    pub fn start() -> Self {
+        let state: Shared = Arc::new(Mutex::new(MockState {
+            health: json!({ "version": "mock", "features": [] }),
+            ..MockState::default()
+        }));
+        let app = Router::new()
+            .route("/api/health", get(health))
+            .route("/api/auth/login", post(login))
+            .route("/api/auth/me", get(me))
+            .route("/api/projects", get(list_projects).post(create_project))
+            .route("/api/projects/meta", get(list_meta))
+            .route(
+                "/api/projects/:id",
+                get(get_project).put(update_project).delete(delete_project),
+            )
+            .with_state(state.clone());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        listener.set_nonblocking(true).expect("non-blocking listener");
+        let url = format!("http://{}", listener.local_addr().expect("local address"));
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("mock server runtime");
+            runtime.block_on(async move {
+                let listener =
+                    tokio::net::TcpListener::from_std(listener).expect("tokio listener");
+                let _ = axum::serve(listener, app)
+                    .with_graceful_shutdown(async {
+                        let _ = stopped.await;
+                    })
+                    .await;
+            });
+        });
+        Self {
+            url,
+            state,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    pub fn add_user(&self, email: &str, password: &str) -> i64 {
+        let mut state = lock(&self.state);
+        let id = state.users.len() as i64 + 1;
+        state.users.push(User {
+            id,
+            email: email.to_string(),
+            password: password.to_string(),
+        });
+        id
+    }
+
+    /// A valid token for `user_id`, without going through the login endpoint.
+    pub fn issue_token(&self, user_id: i64) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        lock(&self.state).tokens.insert(token.clone(), user_id);
+        token
+    }
+
+    /// Invalidate every token, as an expired session would.
+    pub fn revoke_tokens(&self) {
+        lock(&self.state).tokens.clear();
+    }
+
+    /// Answer the next request with `status` instead of handling it.
+    pub fn fail_next(&self, status: u16) {
+        let status = StatusCode::from_u16(status).expect("valid status code");
+        lock(&self.state).failures.push(status);
+    }
+
+    pub fn set_health(&self, health: Value) {
+        lock(&self.state).health = health;
+    }
+
+    pub fn project_names(&self) -> Vec<String> {
+        lock(&self.state)
+            .projects
+            .values()
+            .map(|p| p.name.clone())
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::storage::remote::RemoteStorage;
+    use crate::storage::StorageProvider;
+
+    fn project(name: &str) -> Project {
+        Project {
+            id: "local-id".to_string(),
+            name: name.to_string(),
+            encrypted_content: vec![1, 2, 3],
+            key_check: vec![4, 5],
+            position: "a0".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn remote_storage_round_trips_and_maps_errors() {
+        let server = MockServer::start();
+        let user = server.add_user("a@example.com", "secret");
+        let remote = RemoteStorage::new(&server.url, &server.issue_token(user));
+
+        let server_id = remote.create_project(&project("Notes")).unwrap().unwrap();
+        let fetched = remote.get_project(&server_id).unwrap();
+        assert_eq!(fetched.encrypted_content, vec![1, 2, 3]);
+        assert_eq!(fetched.sync_status, "synced");
+        assert_eq!(remote.list_projects_meta().unwrap().len(), 1);
+
+        server.fail_next(500);
+        let err = remote.list_projects_meta().unwrap_err().to_string();
+        assert!(err.contains("Server error"), "{err}");
+
+        server.revoke_tokens();
+        assert!(remote.list_projects().is_err());
+        assert!(matches!(
+            remote.get_project(&server_id),
+            Err(crate::storage::StorageError::NotFound(_))
+        ));
+    }
+}
@@ -1,10 +1,24 @@
 // Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
+pub mod cipher;
+pub mod compact;
 pub mod local;
+pub mod lock;
+pub mod metrics;
+pub mod migrations;
+#[cfg(test)]
+pub mod mock_server;
 pub mod remote;
+pub mod shard;
 
-use crate::models::{Project, ProjectBackup};
+use std::io::Read;
+
+use crate::crypto::StreamedSeal;
+use crate::models::{
+    Attachment, AuditEvent, ChangeFeed, ChecklistItem, Draft, Folder, PasswordHistoryEntry, Project, ProjectAppearance,
+    ProjectBackup, ProjectField, ProjectFilter, ProjectTotp, ProjectVersion, SearchIndexEntry, Tag, Template,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
@@ -14,6 +28,22 @@ pub enum StorageError {
     NotFound(String),
     #[error("IO error: {0}")]
     Io(String),
+    #[error("This vault was created by a newer version of VaultPad (format version {required_version}); please update the app")]
+    VaultTooNew { required_version: u32 },
+    #[error("The vault is open read-only")]
+    ReadOnly,
+    #[error("This vault was last opened by a newer version of VaultPad (schema version {found}, this version supports {supported}); please update the app")]
+    SchemaTooNew { found: u32, supported: u32 },
+    #[error("The vault database is encrypted; unlock the vault first")]
+    Sealed,
+    #[error("This vault is open in another VaultPad window")]
+    InUse,
+}
+
+fn with_sealed(project: &Project, seal: &StreamedSeal) -> Result<Project, StorageError> {
+    let mut sealed = Vec::with_capacity(seal.sealed_len() as usize);
+    seal.write_to(&mut sealed).map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(Project { encrypted_content: sealed, ..project.clone() })
 }
 
 pub trait StorageProvider: Send + Sync {
@@ -21,16 +51,101 @@ pub trait StorageProvider: Send + Sync {
     fn list_projects(&self) -> Result<Vec<Project>, StorageError>;
     fn get_project(&self, id: &str) -> Result<Project, StorageError>;
     fn create_project(&self, project: &Project) -> Result<Option<String>, StorageError>;
+    /// Create several projects, returning what `create_project` would for each. All or nothing
+    /// where the storage has transactions; otherwise one at a time, stopping at the first error.
+    fn create_projects_batch(&self, projects: &[Project]) -> Result<Vec<Option<String>>, StorageError> {
+        projects.iter().map(|p| self.create_project(p)).collect()
+    }
     fn update_project(&self, project: &Project) -> Result<(), StorageError>;
+    /// `create_project` with the content sealed from `seal` as it is written, in place of
+    /// `project.encrypted_content`. Storages that cannot write in place seal it in memory first.
+    fn create_project_streamed(&self, project: &Project, seal: &StreamedSeal) -> Result<Option<String>, StorageError> {
+        self.create_project(&with_sealed(project, seal)?)
+    }
+    /// `update_project` with the content sealed from `seal`, as `create_project_streamed`.
+    fn update_project_streamed(&self, project: &Project, seal: &StreamedSeal) -> Result<(), StorageError> {
+        self.update_project(&with_sealed(project, seal)?)
+    }
+    /// `get_project` without the content, which `read_project_content` reads.
+    fn get_project_meta(&self, id: &str) -> Result<Project, StorageError> { self.get_project(id) }
+    /// Hand a project's stored content to `read`, without loading it all first where the storage
+    /// can read it in place.
+    fn read_project_content(
+        &self,
+        id: &str,
+        read: &mut dyn FnMut(&mut dyn Read) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        read(&mut self.get_project(id)?.encrypted_content.as_slice())
+    }
     fn delete_project(&self, id: &str) -> Result<(), StorageError>;
 
-    fn reorder_projects(&self, ids_with_order: &[(String, i32)]) -> Result<(), StorageError>;
+    /// Store new ordered-key positions; moved projects that were synced need uploading again.
+    fn reorder_projects(&self, positions: &[(String, String)]) -> Result<(), StorageError>;
+
+    /// Move a project to the trash. It stays in `list_projects` (so sync and re-encryption
+    /// still cover it) until purged with `delete_project`. Without trash support it is deleted.
+    fn trash_project(&self, id: &str, _deleted_at: &str) -> Result<(), StorageError> { self.delete_project(id) }
+    fn restore_project(&self, id: &str) -> Result<(), StorageError> { Err(StorageError::NotFound(id.to_string())) }
+    /// `(project id, deleted_at)` of every trashed project, most recently deleted first.
+    fn list_trashed_projects(&self) -> Result<Vec<(String, String)>, StorageError> { Ok(vec![]) }
+    /// Note that a project was opened. Not a change to it: nothing is synced or fed to the
+    /// change log.
+    fn record_access(&self, _id: &str, _accessed_at: &str) -> Result<(), StorageError> { Ok(()) }
+    /// `(project id, last_accessed_at)` of the `limit` most recently opened projects that are not
+    /// in the trash, most recent first.
+    fn recently_accessed(&self, _limit: usize) -> Result<Vec<(String, String)>, StorageError> { Ok(vec![]) }
+    /// Archive a project: it stays in the vault and in `list_projects`, but the project list
+    /// leaves it out unless asked for archived ones. Local to this vault, like the trash.
+    fn archive_project(&self, id: &str, _archived_at: &str) -> Result<(), StorageError> { Err(StorageError::NotFound(id.to_string())) }
+    fn unarchive_project(&self, id: &str) -> Result<(), StorageError> { Err(StorageError::NotFound(id.to_string())) }
+    /// `(project id, archived_at)` of every archived project, most recently archived first.
+    fn list_archived_projects(&self) -> Result<Vec<(String, String)>, StorageError> { Ok(vec![]) }
+    /// Ids of the projects outside the trash that pass the parts of `filter` the storage can
+    /// check: tags, dates, sync status and archiving. `kind` and `has_custom_password` need the
+    /// content and are left to the caller. Without filtering support every project is returned.
+    fn filter_projects(&self, _filter: &ProjectFilter) -> Result<Vec<String>, StorageError> {
+        Ok(self.list_projects()?.into_iter().map(|p| p.id).collect())
+    }
+    fn set_pinned(&self, id: &str, _pinned: bool) -> Result<(), StorageError> { Err(StorageError::NotFound(id.to_string())) }
+
+    /// Move a project into a folder (`None` for the top level). Nothing else about it changes.
+    fn set_project_folder(&self, _project_id: &str, _folder_id: Option<&str>) -> Result<(), StorageError> { Ok(()) }
+    /// All folders, including ones deleted locally whose deletion has not been synced yet.
+    fn list_folders(&self) -> Result<Vec<Folder>, StorageError> { Ok(vec![]) }
+    fn save_folder(&self, _folder: &Folder) -> Result<(), StorageError> { Ok(()) }
+    fn delete_folder(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    /// All templates, including ones deleted locally whose deletion has not been synced yet.
+    fn list_templates(&self) -> Result<Vec<Template>, StorageError> { Ok(vec![]) }
+    fn save_template(&self, _template: &Template) -> Result<(), StorageError> { Ok(()) }
+    fn delete_template(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
 
     fn get_verification_token(&self) -> Result<Option<Vec<u8>>, StorageError>;
     fn set_verification_token(&self, token: &[u8]) -> Result<(), StorageError>;
 
     fn get_setting(&self, key: &str) -> Result<Option<String>, StorageError>;
     fn set_setting(&self, key: &str, value: &str) -> Result<(), StorageError>;
+    /// Every setting as `(key, value)`, sorted by key.
+    fn list_settings(&self) -> Result<Vec<(String, String)>, StorageError> { Ok(vec![]) }
+
+    /// Vault-level metadata (format version, KDF parameters, ...), kept apart from user settings.
+    fn get_meta(&self, _key: &str) -> Result<Option<String>, StorageError> { Ok(None) }
+    fn set_meta(&self, _key: &str, _value: &str) -> Result<(), StorageError> { Ok(()) }
+    /// Set `meta` entries and the verification token together. All or nothing where the storage
+    /// has transactions; otherwise one at a time, stopping at the first error.
+    fn replace_unlock_meta(&self, meta: &[(&str, &str)], token: &[u8]) -> Result<(), StorageError> {
+        for (key, value) in meta {
+            self.set_meta(key, value)?;
+        }
+        self.set_verification_token(token)
+    }
+
+    /// Start a transaction that `commit_transaction` or `rollback_transaction` ends, for a run of
+    /// writes that must land together. Storages without transactions write straight through and
+    /// have nothing to roll back.
+    fn begin_transaction(&self) -> Result<(), StorageError> { Ok(()) }
+    fn commit_transaction(&self) -> Result<(), StorageError> { Ok(()) }
+    fn rollback_transaction(&self) -> Result<(), StorageError> { Ok(()) }
 
     fn create_backup(&self, _backup: &ProjectBackup) -> Result<(), StorageError> { Ok(()) }
     fn update_backup(&self, _backup: &ProjectBackup) -> Result<(), StorageError> { Ok(()) }
@@ -41,4 +156,151 @@ pub trait StorageProvider: Send + Sync {
     fn get_latest_backup(&self, _project_id: &str) -> Result<Option<ProjectBackup>, StorageError> { Ok(None) }
     fn delete_backup(&self, _backup_id: &str) -> Result<(), StorageError> { Ok(()) }
     fn cleanup_backups(&self, _project_id: &str, _keep_count: usize) -> Result<(), StorageError> { Ok(()) }
+
+    fn create_version(&self, _version: &ProjectVersion) -> Result<(), StorageError> { Ok(()) }
+    fn update_version(&self, _version: &ProjectVersion) -> Result<(), StorageError> { Ok(()) }
+    /// A project's versions, newest first.
+    fn list_versions(&self, _project_id: &str) -> Result<Vec<ProjectVersion>, StorageError> { Ok(vec![]) }
+    fn get_version(&self, id: &str) -> Result<ProjectVersion, StorageError> {
+        Err(StorageError::NotFound(id.to_string()))
+    }
+    /// Keep the newest `keep_count` versions of each project (of one project with `project_id`),
+    /// dropping any saved before `saved_before` as well.
+    fn prune_versions(&self, _project_id: Option<&str>, _keep_count: usize, _saved_before: Option<&str>) -> Result<(), StorageError> { Ok(()) }
+
+    fn create_attachment(&self, _attachment: &Attachment) -> Result<(), StorageError> {
+        Err(StorageError::Io("Attachments are not supported by this storage".to_string()))
+    }
+    fn update_attachment(&self, _attachment: &Attachment) -> Result<(), StorageError> { Ok(()) }
+    /// A project's attachments, oldest first.
+    fn list_attachments(&self, _project_id: &str) -> Result<Vec<Attachment>, StorageError> { Ok(vec![]) }
+    fn get_attachment(&self, id: &str) -> Result<Attachment, StorageError> {
+        Err(StorageError::NotFound(id.to_string()))
+    }
+    /// Remove an attachment along with its blob.
+    fn delete_attachment(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    /// A project's custom fields, by position.
+    fn list_project_fields(&self, _project_id: &str) -> Result<Vec<ProjectField>, StorageError> { Ok(vec![]) }
+    fn get_project_field(&self, id: &str) -> Result<ProjectField, StorageError> {
+        Err(StorageError::NotFound(id.to_string()))
+    }
+    /// Insert a custom field, or replace the one with its id.
+    fn save_project_field(&self, _field: &ProjectField) -> Result<(), StorageError> { Ok(()) }
+    fn delete_project_field(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    fn get_project_totp(&self, _project_id: &str) -> Result<Option<ProjectTotp>, StorageError> { Ok(None) }
+    fn list_project_totps(&self) -> Result<Vec<ProjectTotp>, StorageError> { Ok(vec![]) }
+    /// Insert a project's TOTP secret, or replace the one it has.
+    fn save_project_totp(&self, _totp: &ProjectTotp) -> Result<(), StorageError> { Ok(()) }
+    fn delete_project_totp(&self, _project_id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    /// A project's checklist items by position, including removed ones whose removal has not
+    /// been synced yet.
+    fn list_checklist_items(&self, _project_id: &str) -> Result<Vec<ChecklistItem>, StorageError> { Ok(vec![]) }
+    fn get_checklist_item(&self, _id: &str) -> Result<Option<ChecklistItem>, StorageError> { Ok(None) }
+    /// Insert a checklist item, or replace the one with its id.
+    fn save_checklist_item(&self, _item: &ChecklistItem) -> Result<(), StorageError> { Ok(()) }
+    fn delete_checklist_item(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    fn list_search_index(&self) -> Result<Vec<SearchIndexEntry>, StorageError> { Ok(vec![]) }
+    /// Insert a project's search index entry, or replace the one it has.
+    fn save_search_index_entry(&self, _entry: &SearchIndexEntry) -> Result<(), StorageError> { Ok(()) }
+    fn delete_search_index_entry(&self, _project_id: &str) -> Result<(), StorageError> { Ok(()) }
+    fn clear_search_index(&self) -> Result<(), StorageError> { Ok(()) }
+
+    fn list_drafts(&self) -> Result<Vec<Draft>, StorageError> { Ok(vec![]) }
+    fn get_draft(&self, _project_id: &str) -> Result<Option<Draft>, StorageError> { Ok(None) }
+    /// Insert a project's draft, or replace the one it has.
+    fn save_draft(&self, _draft: &Draft) -> Result<(), StorageError> { Ok(()) }
+    fn delete_draft(&self, _project_id: &str) -> Result<(), StorageError> { Ok(()) }
+
+    /// A project's previous passwords, most recently replaced first.
+    fn list_password_history(&self, _project_id: &str) -> Result<Vec<PasswordHistoryEntry>, StorageError> { Ok(vec![]) }
+    fn get_password_history_entry(&self, id: &str) -> Result<PasswordHistoryEntry, StorageError> {
+        Err(StorageError::NotFound(id.to_string()))
+    }
+    fn add_password_history(&self, _entry: &PasswordHistoryEntry) -> Result<(), StorageError> { Ok(()) }
+    fn update_password_history(&self, _entry: &PasswordHistoryEntry) -> Result<(), StorageError> { Ok(()) }
+    /// Drop all but a project's `keep` most recently replaced passwords.
+    fn prune_password_history(&self, _project_id: &str, _keep: usize) -> Result<(), StorageError> { Ok(()) }
+
+    fn list_tags(&self) -> Result<Vec<Tag>, StorageError> { Ok(vec![]) }
+    fn create_tag(&self, _tag: &Tag) -> Result<(), StorageError> { Ok(()) }
+    fn update_tag(&self, _tag: &Tag) -> Result<(), StorageError> { Ok(()) }
+    /// Replace a project's tags. Tags left on no project are removed.
+    fn set_project_tags(&self, _project_id: &str, _tag_ids: &[String]) -> Result<(), StorageError> { Ok(()) }
+    /// Every (project id, tag id) pair.
+    fn list_project_tags(&self) -> Result<Vec<(String, String)>, StorageError> { Ok(vec![]) }
+
+    fn list_project_appearances(&self) -> Result<Vec<ProjectAppearance>, StorageError> { Ok(vec![]) }
+    /// Replace a project's color and icon; with neither set, the record is removed.
+    fn set_project_appearance(&self, _appearance: &ProjectAppearance) -> Result<(), StorageError> { Ok(()) }
+
+    /// Append to the security audit log; existing entries can never be changed or removed.
+    fn append_audit_event(&self, _event: &AuditEvent) -> Result<(), StorageError> { Ok(()) }
+    /// Audit log entries, oldest first.
+    fn list_audit_events(&self) -> Result<Vec<AuditEvent>, StorageError> { Ok(vec![]) }
+    fn last_audit_event(&self) -> Result<Option<AuditEvent>, StorageError> { Ok(None) }
+
+    /// Latest change sequence number; it only ever grows while the vault is open.
+    fn change_seq(&self) -> Result<i64, StorageError> { Ok(0) }
+    /// Changes recorded after `seq`, oldest first, at most `limit` of them.
+    fn get_changes_since(&self, _seq: i64, _limit: usize) -> Result<ChangeFeed, StorageError> {
+        Ok(ChangeFeed::default())
+    }
+
+    /// Per-operation call counts and latencies since the storage was opened.
+    fn metrics(&self) -> Option<metrics::StorageMetrics> { None }
+    /// Log operations slower than `threshold` in the metrics; `None` turns the log off.
+    fn set_slow_threshold(&self, _threshold: Option<std::time::Duration>) {}
+
+    /// Whether writes are refused, for a vault opened in compatibility mode.
+    fn is_read_only(&self) -> bool { false }
+
+    /// Whether the database file itself is encrypted (SQLCipher).
+    fn is_encrypted(&self) -> bool { false }
+    /// Reserves nonce counter values persisted in this vault, see `crypto::nonce`; `None`
+    /// when the vault cannot keep a counter.
+    fn nonce_reserve(&self) -> Option<crate::crypto::nonce::Reserve> { None }
+    /// Move everything in the write-ahead log into the database file, so the file can be
+    /// copied on its own.
+    fn checkpoint(&self) -> Result<(), StorageError> { Ok(()) }
+    /// Rebuild the database files without their free pages, so deleted data neither takes up
+    /// space nor lingers on disk. `progress` is called before each file.
+    fn compact(
+        &self,
+        _progress: &mut dyn FnMut(compact::CompactProgress),
+    ) -> Result<compact::CompactStats, StorageError> {
+        Ok(compact::CompactStats::default())
+    }
+    /// Give an encrypted database the key derived from the vault key, once it is unlocked.
+    fn unseal(&self, _key: &[u8; crate::crypto::KEY_LEN]) -> Result<(), StorageError> { Ok(()) }
+    /// Re-encrypt an encrypted database for a new vault key.
+    fn rekey(&self, _key: &[u8; crate::crypto::KEY_LEN]) -> Result<(), StorageError> { Ok(()) }
+
+    /// Encrypted attachment chunks, kept in the vault's shard rather than its main database.
+    fn put_blob_chunk(&self, _blob_id: &str, _index: u32, _data: &[u8]) -> Result<(), StorageError> {
+        Err(StorageError::Io("Attachments are not supported by this storage".to_string()))
+    }
+    fn get_blob_chunk(&self, _blob_id: &str, _index: u32) -> Result<Option<Vec<u8>>, StorageError> { Ok(None) }
+    fn delete_blob(&self, _blob_id: &str) -> Result<(), StorageError> { Ok(()) }
+    fn list_blobs(&self) -> Result<Vec<shard::BlobInfo>, StorageError> { Ok(vec![]) }
+}
+
+/// Run `write` in one storage transaction, committed when it succeeds and rolled back on any
+/// error.
+pub fn in_transaction<T>(
+    storage: &dyn StorageProvider,
+    write: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    storage.begin_transaction().map_err(|e| e.to_string())?;
+    let written = write().and_then(|value| {
+        storage.commit_transaction().map_err(|e| e.to_string())?;
+        Ok(value)
+    });
+    if written.is_err() {
+        let _ = storage.rollback_transaction();
+    }
+    written
 }
@@ -6,7 +6,7 @@ use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
 use super::{StorageError, StorageProvider};
-use crate::models::Project;
+use crate::models::{ChecklistItem, Folder, Project, Template};
 
 #[derive(Serialize, Deserialize)]
 struct ServerProject {
@@ -14,9 +14,14 @@ struct ServerProject {
     name: String,
     encrypted_content: String,
     key_check: Option<String>,
-    sort_order: i32,
+    /// Absent on servers that predate ordered keys.
+    #[serde(default)]
+    position: String,
     created_at: String,
     updated_at: String,
+    /// Absent on servers without folders.
+    #[serde(default)]
+    folder_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -24,7 +29,8 @@ struct CreateProjectPayload {
     name: String,
     encrypted_content: String,
     key_check: String,
-    sort_order: i32,
+    position: String,
+    folder_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -32,7 +38,54 @@ struct UpdateProjectPayload {
     name: String,
     encrypted_content: String,
     key_check: String,
-    sort_order: i32,
+    position: String,
+    folder_id: Option<String>,
+}
+
+/// A folder as the server keeps it. Deleted folders stay as tombstones so every device learns
+/// of the deletion.
+#[derive(Serialize, Deserialize)]
+struct ServerFolder {
+    id: String,
+    parent_id: Option<String>,
+    encrypted_name: String,
+    position: String,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// A template as the server keeps it; deleted ones stay as tombstones, like folders.
+#[derive(Serialize, Deserialize)]
+struct ServerTemplate {
+    id: String,
+    encrypted_template: String,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// A checklist item as the server keeps it, under its project's server id; removed ones stay
+/// as tombstones.
+#[derive(Serialize, Deserialize)]
+struct ServerChecklistItem {
+    id: String,
+    project_id: String,
+    position: String,
+    encrypted_item: String,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// A user's synced preferences, encrypted on the client.
+#[derive(Serialize, Deserialize)]
+struct ServerSettings {
+    encrypted_settings: String,
+    updated_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +94,72 @@ pub struct RemoteProjectMeta {
     pub updated_at: String,
 }
 
+#[derive(Serialize)]
+struct DeviceCheckinPayload<'a> {
+    device_id: &'a str,
+    name: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCheckin {
+    pub wipe_requested: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteDevice {
+    pub device_id: String,
+    pub name: String,
+    pub last_seen_at: Option<String>,
+    pub wipe_requested: bool,
+    pub wiped_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmergencyGrantPayload<'a> {
+    grantee_email: &'a str,
+    grantee_public_key: &'a str,
+    wrapped_key: &'a str,
+    wait_secs: u64,
+}
+
+/// An emergency access grant, as seen by either the owner or the trusted contact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteEmergencyGrant {
+    pub id: i64,
+    pub owner_email: String,
+    pub grantee_email: String,
+    pub wait_secs: u64,
+    pub requested_at: Option<String>,
+    pub denied_at: Option<String>,
+    pub released_at: Option<String>,
+    pub created_at: String,
+}
+
+/// The body of `/health`. Every field is optional: older servers answer with an empty body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerHealth {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub min_client_version: Option<String>,
+    /// Optional features the server offers; `None` if it does not advertise them.
+    #[serde(default)]
+    pub features: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct EmergencyKeyResponse {
+    wrapped_key: String,
+}
+
+/// The HTTP client for everything the app sends over the network.
+pub fn http_client() -> Client {
+    Client::builder()
+        .user_agent(concat!("VaultPad/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
 pub struct RemoteStorage {
     client: Client,
     base_url: String,
@@ -54,12 +173,16 @@ fn req_err(e: reqwest::Error) -> StorageError {
 impl RemoteStorage {
     pub fn new(base_url: &str, token: &str) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
             base_url: base_url.trim_end_matches('/').to_string(),
             token: token.to_string(),
         }
     }
 
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     fn url(&self, path: &str) -> String {
         format!("{}/api{}", self.base_url, path)
     }
@@ -84,12 +207,402 @@ impl RemoteStorage {
         resp.json().map_err(req_err)
     }
 
-    pub fn health_check(&self) -> Result<(), StorageError> {
+    /// Register this device (or refresh its last-seen time) and learn whether a wipe is pending.
+    pub fn device_checkin(&self, device_id: &str, name: &str) -> Result<DeviceCheckin, StorageError> {
+        let resp = self
+            .client
+            .post(self.url("/devices/checkin"))
+            .header("Authorization", self.auth_header())
+            .json(&DeviceCheckinPayload { device_id, name })
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+
+        resp.json().map_err(req_err)
+    }
+
+    pub fn list_devices(&self) -> Result<Vec<RemoteDevice>, StorageError> {
+        let resp = self
+            .client
+            .get(self.url("/devices"))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+
+        resp.json().map_err(req_err)
+    }
+
+    pub fn request_device_wipe(&self, device_id: &str) -> Result<(), StorageError> {
+        let resp = self
+            .client
+            .post(self.url(&format!("/devices/{}/wipe", device_id)))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            return Err(StorageError::NotFound(device_id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn confirm_device_wiped(&self, device_id: &str) -> Result<(), StorageError> {
+        let resp = self
+            .client
+            .post(self.url(&format!("/devices/{}/wiped", device_id)))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(())
+    }
+
+    fn emergency_request(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response, StorageError> {
+        let resp = request
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(resp)
+    }
+
+    /// Hand the server a vault key sealed to the contact's public key. The server keeps it until
+    /// the contact asks for access and the owner lets the waiting period pass without denying.
+    pub fn create_emergency_grant(
+        &self,
+        grantee_email: &str,
+        grantee_public_key: &[u8],
+        wrapped_key: &[u8],
+        wait_secs: u64,
+    ) -> Result<RemoteEmergencyGrant, StorageError> {
+        let payload = EmergencyGrantPayload {
+            grantee_email,
+            grantee_public_key: &B64.encode(grantee_public_key),
+            wrapped_key: &B64.encode(wrapped_key),
+            wait_secs,
+        };
+        self.emergency_request(self.client.post(self.url("/emergency/grants")).json(&payload))?
+            .json()
+            .map_err(req_err)
+    }
+
+    pub fn list_emergency_grants(&self) -> Result<Vec<RemoteEmergencyGrant>, StorageError> {
+        self.emergency_request(self.client.get(self.url("/emergency/grants")))?
+            .json()
+            .map_err(req_err)
+    }
+
+    pub fn delete_emergency_grant(&self, grant_id: i64) -> Result<(), StorageError> {
+        self.emergency_request(
+            self.client
+                .delete(self.url(&format!("/emergency/grants/{}", grant_id))),
+        )?;
+        Ok(())
+    }
+
+    pub fn deny_emergency_request(&self, grant_id: i64) -> Result<(), StorageError> {
+        self.emergency_request(
+            self.client
+                .post(self.url(&format!("/emergency/grants/{}/deny", grant_id))),
+        )?;
+        Ok(())
+    }
+
+    /// Grants where the signed-in user is the trusted contact.
+    pub fn list_incoming_emergency_grants(
+        &self,
+    ) -> Result<Vec<RemoteEmergencyGrant>, StorageError> {
+        self.emergency_request(self.client.get(self.url("/emergency/incoming")))?
+            .json()
+            .map_err(req_err)
+    }
+
+    pub fn request_emergency_access(
+        &self,
+        grant_id: i64,
+    ) -> Result<RemoteEmergencyGrant, StorageError> {
+        self.emergency_request(
+            self.client
+                .post(self.url(&format!("/emergency/incoming/{}/request", grant_id))),
+        )?
+        .json()
+        .map_err(req_err)
+    }
+
+    /// The sealed vault key; the server refuses until the waiting period has passed undenied.
+    pub fn fetch_emergency_key(&self, grant_id: i64) -> Result<Vec<u8>, StorageError> {
+        let resp: EmergencyKeyResponse = self
+            .emergency_request(
+                self.client
+                    .get(self.url(&format!("/emergency/incoming/{}/key", grant_id))),
+            )?
+            .json()
+            .map_err(req_err)?;
+        B64.decode(&resp.wrapped_key)
+            .map_err(|e| StorageError::Io(format!("Invalid key from server: {}", e)))
+    }
+
+    /// Every folder on the server, tombstones included, with `sync_status` `synced` (or
+    /// `deleted` for a tombstone). `NotFound` when the server has no folders.
+    pub fn list_folders(&self) -> Result<Vec<Folder>, StorageError> {
+        let resp = self
+            .client
+            .get(self.url("/folders"))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        // Servers from before the feature list have no folder endpoint.
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound("folders".to_string()));
+        }
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+
+        let folders: Vec<ServerFolder> = resp.json().map_err(req_err)?;
+        folders
+            .into_iter()
+            .map(|sf| {
+                Ok(Folder {
+                    id: sf.id,
+                    parent_id: sf.parent_id,
+                    encrypted_name: B64
+                        .decode(&sf.encrypted_name)
+                        .map_err(|e| StorageError::Io(e.to_string()))?,
+                    position: sf.position,
+                    created_at: sf.created_at,
+                    updated_at: sf.updated_at,
+                    sync_status: if sf.deleted { "deleted" } else { "synced" }.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Create or replace a folder on the server; a folder with `sync_status` `deleted` is
+    /// stored as a tombstone.
+    pub fn put_folder(&self, folder: &Folder) -> Result<(), StorageError> {
+        let payload = ServerFolder {
+            id: folder.id.clone(),
+            parent_id: folder.parent_id.clone(),
+            encrypted_name: B64.encode(&folder.encrypted_name),
+            position: folder.position.clone(),
+            created_at: folder.created_at.clone(),
+            updated_at: folder.updated_at.clone(),
+            deleted: folder.sync_status == "deleted",
+        };
+        let resp = self
+            .client
+            .put(self.url(&format!("/folders/{}", folder.id)))
+            .header("Authorization", self.auth_header())
+            .json(&payload)
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(())
+    }
+
+    /// Every template on the server, tombstones included, with `sync_status` `synced` (or
+    /// `deleted` for a tombstone). `NotFound` when the server has no templates.
+    pub fn list_templates(&self) -> Result<Vec<Template>, StorageError> {
+        let resp = self
+            .client
+            .get(self.url("/templates"))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound("templates".to_string()));
+        }
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+
+        let templates: Vec<ServerTemplate> = resp.json().map_err(req_err)?;
+        templates
+            .into_iter()
+            .map(|st| {
+                Ok(Template {
+                    id: st.id,
+                    encrypted_template: B64
+                        .decode(&st.encrypted_template)
+                        .map_err(|e| StorageError::Io(e.to_string()))?,
+                    created_at: st.created_at,
+                    updated_at: st.updated_at,
+                    sync_status: if st.deleted { "deleted" } else { "synced" }.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Create or replace a template on the server; one with `sync_status` `deleted` is stored
+    /// as a tombstone.
+    pub fn put_template(&self, template: &Template) -> Result<(), StorageError> {
+        let payload = ServerTemplate {
+            id: template.id.clone(),
+            encrypted_template: B64.encode(&template.encrypted_template),
+            created_at: template.created_at.clone(),
+            updated_at: template.updated_at.clone(),
+            deleted: template.sync_status == "deleted",
+        };
+        let resp = self
+            .client
+            .put(self.url(&format!("/templates/{}", template.id)))
+            .header("Authorization", self.auth_header())
+            .json(&payload)
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(())
+    }
+
+    /// Every checklist item on the server, tombstones included, with `sync_status` `synced`
+    /// (or `deleted` for a tombstone) and `project_id` the project's server id. `NotFound` when
+    /// the server has no checklists.
+    pub fn list_checklist_items(&self) -> Result<Vec<ChecklistItem>, StorageError> {
+        let resp = self
+            .client
+            .get(self.url("/checklist-items"))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound("checklist items".to_string()));
+        }
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+
+        let items: Vec<ServerChecklistItem> = resp.json().map_err(req_err)?;
+        items
+            .into_iter()
+            .map(|si| {
+                Ok(ChecklistItem {
+                    id: si.id,
+                    project_id: si.project_id,
+                    position: si.position,
+                    encrypted_item: B64
+                        .decode(&si.encrypted_item)
+                        .map_err(|e| StorageError::Io(e.to_string()))?,
+                    created_at: si.created_at,
+                    updated_at: si.updated_at,
+                    sync_status: if si.deleted { "deleted" } else { "synced" }.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Create or replace a checklist item on the server; `project_id` must be the project's
+    /// server id. An item with `sync_status` `deleted` is stored as a tombstone.
+    pub fn put_checklist_item(&self, item: &ChecklistItem) -> Result<(), StorageError> {
+        let payload = ServerChecklistItem {
+            id: item.id.clone(),
+            project_id: item.project_id.clone(),
+            position: item.position.clone(),
+            encrypted_item: B64.encode(&item.encrypted_item),
+            created_at: item.created_at.clone(),
+            updated_at: item.updated_at.clone(),
+            deleted: item.sync_status == "deleted",
+        };
+        let resp = self
+            .client
+            .put(self.url(&format!("/checklist-items/{}", item.id)))
+            .header("Authorization", self.auth_header())
+            .json(&payload)
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(())
+    }
+
+    /// The encrypted settings blob, `None` when none was stored yet. `NotFound` when the
+    /// server has no settings endpoint.
+    pub fn get_settings(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        let resp = self
+            .client
+            .get(self.url("/settings"))
+            .header("Authorization", self.auth_header())
+            .send()
+            .map_err(req_err)?;
+
+        match resp.status() {
+            reqwest::StatusCode::NOT_FOUND => return Err(StorageError::NotFound("settings".to_string())),
+            reqwest::StatusCode::NO_CONTENT => return Ok(None),
+            status if !status.is_success() => {
+                let text = resp.text().unwrap_or_default();
+                return Err(StorageError::Io(format!("Server error: {}", text)));
+            }
+            _ => {}
+        }
+        let settings: ServerSettings = resp.json().map_err(req_err)?;
+        B64.decode(&settings.encrypted_settings)
+            .map(Some)
+            .map_err(|e| StorageError::Io(format!("Invalid settings from server: {}", e)))
+    }
+
+    pub fn put_settings(&self, encrypted: &[u8], updated_at: &str) -> Result<(), StorageError> {
+        let payload = ServerSettings {
+            encrypted_settings: B64.encode(encrypted),
+            updated_at: updated_at.to_string(),
+        };
+        let resp = self
+            .client
+            .put(self.url("/settings"))
+            .header("Authorization", self.auth_header())
+            .json(&payload)
+            .send()
+            .map_err(req_err)?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(StorageError::Io(format!("Server error: {}", text)));
+        }
+        Ok(())
+    }
+
+    pub fn health_check(&self) -> Result<ServerHealth, StorageError> {
         let resp = self.client.get(self.url("/health")).send().map_err(req_err)?;
         if !resp.status().is_success() {
             return Err(StorageError::Io("Server health check failed".into()));
         }
-        Ok(())
+        Ok(resp.json().unwrap_or_default())
     }
 }
 
@@ -135,12 +648,15 @@ impl StorageProvider for RemoteStorage {
                         .decode(&sp.encrypted_content)
                         .map_err(|e| StorageError::Io(e.to_string()))?,
                     key_check,
-                    sort_order: sp.sort_order,
+                    position: sp.position,
                     created_at: sp.created_at,
                     updated_at: sp.updated_at,
                     server_id: Some(sp.id.to_string()),
                     sync_status: "synced".to_string(),
                     last_synced_at: None,
+                    folder_id: sp.folder_id,
+                    revision: 0,
+                    pinned: false,
                 })
             })
             .collect()
@@ -175,12 +691,15 @@ impl StorageProvider for RemoteStorage {
                 .decode(&sp.encrypted_content)
                 .map_err(|e| StorageError::Io(e.to_string()))?,
             key_check,
-            sort_order: sp.sort_order,
+            position: sp.position,
             created_at: sp.created_at,
             updated_at: sp.updated_at,
             server_id: Some(sp.id.to_string()),
             sync_status: "synced".to_string(),
             last_synced_at: None,
+            folder_id: sp.folder_id,
+            revision: 0,
+            pinned: false,
         })
     }
 
@@ -189,7 +708,8 @@ impl StorageProvider for RemoteStorage {
             name: project.name.clone(),
             encrypted_content: B64.encode(&project.encrypted_content),
             key_check: B64.encode(&project.key_check),
-            sort_order: project.sort_order,
+            position: project.position.clone(),
+            folder_id: project.folder_id.clone(),
         };
 
         let resp = self
@@ -216,7 +736,8 @@ impl StorageProvider for RemoteStorage {
             name: project.name.clone(),
             encrypted_content: B64.encode(&project.encrypted_content),
             key_check: B64.encode(&project.key_check),
-            sort_order: project.sort_order,
+            position: project.position.clone(),
+            folder_id: project.folder_id.clone(),
         };
 
         let resp = self
@@ -248,7 +769,7 @@ impl StorageProvider for RemoteStorage {
         Ok(())
     }
 
-    fn reorder_projects(&self, _ids_with_order: &[(String, i32)]) -> Result<(), StorageError> {
+    fn reorder_projects(&self, _positions: &[(String, String)]) -> Result<(), StorageError> {
         Ok(())
     }
 
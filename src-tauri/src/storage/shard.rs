@@ -0,0 +1,291 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Attachment shard: encrypted blob chunks live in a sidecar database next to the vault
+//! (`<vault>.attachments.db`) instead of the main file, so a vault holding gigabytes of
+//! attachments keeps its project queries and page cache small. The sidecar is opened on first
+//! use; a vault that never stored a blob never gets one. Callers encrypt chunks before handing
+//! them over — the shard only sees ciphertext.
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::compact::{self, CompactStats};
+use super::{StorageError, StorageProvider};
+
+pub const SHARD_SUFFIX: &str = ".attachments.db";
+/// Size of the chunks `BlobWriter` stores.
+pub const CHUNK_LEN: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlobInfo {
+    pub id: String,
+    pub chunks: u32,
+    pub bytes: u64,
+}
+
+pub struct ShardStore {
+    /// `None` for an in-memory vault, whose shard is in memory too.
+    path: Option<PathBuf>,
+    read_only: bool,
+    conn: Mutex<Option<Connection>>,
+}
+
+fn db_err(e: rusqlite::Error) -> StorageError {
+    StorageError::Database(e.to_string())
+}
+
+/// Path of the shard belonging to the vault at `db_path`.
+pub fn shard_path(db_path: &str) -> PathBuf {
+    PathBuf::from(format!("{db_path}{SHARD_SUFFIX}"))
+}
+
+impl ShardStore {
+    pub fn new(db_path: &str, read_only: bool) -> Self {
+        Self {
+            path: (db_path != ":memory:").then(|| shard_path(db_path)),
+            read_only,
+            conn: Mutex::new(None),
+        }
+    }
+
+    fn open(path: Option<&Path>, read_only: bool) -> Result<Connection, StorageError> {
+        let conn = match path {
+            None => Connection::open_in_memory().map_err(db_err)?,
+            Some(path) if read_only => {
+                Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(db_err)?
+            }
+            Some(path) => Connection::open(path).map_err(db_err)?,
+        };
+        if !read_only {
+            conn.execute_batch(
+                "PRAGMA secure_delete = ON;
+                CREATE TABLE IF NOT EXISTS attachment_chunks (
+                    blob_id TEXT NOT NULL,
+                    idx INTEGER NOT NULL,
+                    data BLOB NOT NULL,
+                    PRIMARY KEY (blob_id, idx)
+                ) WITHOUT ROWID;",
+            )
+            .map_err(db_err)?;
+        }
+        Ok(conn)
+    }
+
+    /// Run `f` on the shard connection, opening it first if needed. Returns `None` without
+    /// creating anything when a read-only vault has no shard yet.
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, StorageError>,
+    ) -> Result<Option<T>, StorageError> {
+        let mut guard = self.conn.lock().map_err(|e| StorageError::Database(e.to_string()))?;
+        if guard.is_none() {
+            if self.read_only && !self.path.as_deref().is_some_and(Path::exists) {
+                return Ok(None);
+            }
+            *guard = Some(Self::open(self.path.as_deref(), self.read_only)?);
+        }
+        match guard.as_ref() {
+            Some(conn) => f(conn).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_chunk(&self, blob_id: &str, index: u32, data: &[u8]) -> Result<(), StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnly);
+        }
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO attachment_chunks (blob_id, idx, data) VALUES (?1, ?2, ?3)",
+                params![blob_id, index, data],
+            )
+            .map_err(db_err)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    pub fn get_chunk(&self, blob_id: &str, index: u32) -> Result<Option<Vec<u8>>, StorageError> {
+        let chunk = self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT data FROM attachment_chunks WHERE blob_id = ?1 AND idx = ?2",
+                params![blob_id, index],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)
+        })?;
+        Ok(chunk.flatten())
+    }
+
+    pub fn delete_blob(&self, blob_id: &str) -> Result<(), StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnly);
+        }
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM attachment_chunks WHERE blob_id = ?1", params![blob_id])
+                .map_err(db_err)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Vacuum the shard, if there is one.
+    pub fn compact(&self) -> Result<Option<CompactStats>, StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnly);
+        }
+        if self.path.as_deref().is_some_and(|p| !p.exists()) {
+            return Ok(None);
+        }
+        self.with_conn(|conn| compact::vacuum(conn, false))
+    }
+
+    pub fn list_blobs(&self) -> Result<Vec<BlobInfo>, StorageError> {
+        let blobs = self.with_conn(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT blob_id, COUNT(*), SUM(LENGTH(data)) FROM attachment_chunks
+                     GROUP BY blob_id ORDER BY blob_id",
+                )
+                .map_err(db_err)?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(BlobInfo {
+                        id: row.get(0)?,
+                        chunks: row.get(1)?,
+                        bytes: row.get(2)?,
+                    })
+                })
+                .map_err(db_err)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+        })?;
+        Ok(blobs.unwrap_or_default())
+    }
+}
+
+/// Streams a blob into the storage's shard one chunk at a time, so a large file is never held
+/// in memory whole. `flush` stores whatever is buffered; chunks need not all be the same size.
+pub struct BlobWriter<'a> {
+    storage: &'a dyn StorageProvider,
+    blob_id: String,
+    index: u32,
+    buffer: Vec<u8>,
+}
+
+impl<'a> BlobWriter<'a> {
+    pub fn new(storage: &'a dyn StorageProvider, blob_id: &str) -> Self {
+        Self {
+            storage,
+            blob_id: blob_id.to_string(),
+            index: 0,
+            buffer: Vec::with_capacity(CHUNK_LEN),
+        }
+    }
+
+    fn store(&mut self) -> io::Result<()> {
+        self.storage
+            .put_blob_chunk(&self.blob_id, self.index, &self.buffer)
+            .map_err(io::Error::other)?;
+        self.buffer.clear();
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl Write for BlobWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let take = data.len().min(CHUNK_LEN - self.buffer.len());
+        self.buffer.extend_from_slice(&data[..take]);
+        if self.buffer.len() == CHUNK_LEN {
+            self.store()?;
+        }
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.store()
+    }
+}
+
+/// Reads a blob back from the shard, fetching one chunk at a time.
+pub struct BlobReader<'a> {
+    storage: &'a dyn StorageProvider,
+    blob_id: String,
+    index: u32,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl<'a> BlobReader<'a> {
+    pub fn new(storage: &'a dyn StorageProvider, blob_id: &str) -> Self {
+        Self {
+            storage,
+            blob_id: blob_id.to_string(),
+            index: 0,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for BlobReader<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self
+                .storage
+                .get_blob_chunk(&self.blob_id, self.index)
+                .map_err(io::Error::other)?
+            {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                    self.index += 1;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = out.len().min(self.chunk.len() - self.pos);
+        out[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_live_in_a_sidecar_file() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-shard-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db").to_string_lossy().to_string();
+
+        let read_only = ShardStore::new(&db_path, true);
+        assert!(read_only.list_blobs().unwrap().is_empty());
+        assert!(!shard_path(&db_path).exists());
+
+        let shard = ShardStore::new(&db_path, false);
+        shard.put_chunk("blob", 0, b"first").unwrap();
+        shard.put_chunk("blob", 1, b"second").unwrap();
+        assert!(shard_path(&db_path).exists());
+        assert_eq!(shard.get_chunk("blob", 1).unwrap().as_deref(), Some(&b"second"[..]));
+        assert_eq!(
+            shard.list_blobs().unwrap(),
+            vec![BlobInfo { id: "blob".to_string(), chunks: 2, bytes: 11 }]
+        );
+        assert!(matches!(read_only.put_chunk("blob", 2, b"x"), Err(StorageError::ReadOnly)));
+
+        shard.delete_blob("blob").unwrap();
+        assert_eq!(shard.get_chunk("blob", 0).unwrap(), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,789 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Password strength estimation after zxcvbn (Wheeler, USENIX Security 2016). The password is
+//! matched against common passwords and words (also reversed or in l33t), keyboard walks,
+//! sequences, repeats and dates; the cheapest way to cover it with those matches and brute
+//! force gives the number of guesses an attacker needs. Warnings and suggestions are returned
+//! as keys for the frontend to translate.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+/// Only this many characters are analysed; the rest is counted as brute force.
+const MAX_ANALYSED_LEN: usize = 100;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_GUESSES_MULTI_CHAR: f64 = 50.0;
+/// Penalty per extra match, so that splitting a password into many pieces is not free.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+const MIN_YEAR_SPACE: i32 = 20;
+const KEYBOARD_STARTING_POSITIONS: f64 = 94.0;
+const KEYBOARD_AVERAGE_DEGREE: f64 = 4.595;
+
+/// Guesses per second for each attack scenario.
+const ONLINE_THROTTLED_RATE: f64 = 100.0 / 3600.0;
+const ONLINE_UNTHROTTLED_RATE: f64 = 10.0;
+const OFFLINE_SLOW_HASH_RATE: f64 = 1e4;
+const OFFLINE_FAST_HASH_RATE: f64 = 1e10;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111",
+    "1234567", "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein",
+    "696969", "shadow", "master", "666666", "qwertyuiop", "123321", "mustang", "1234567890",
+    "michael", "654321", "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx",
+    "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter",
+    "buster", "soccer", "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou",
+    "charlie", "robert", "thomas", "hockey", "ranger", "daniel", "starwars", "112233",
+    "george", "computer", "michelle", "jessica", "pepper", "zxcvbn", "555555", "11111111",
+    "131313", "freedom", "777777", "maggie", "159753", "aaaaaa", "ginger", "princess",
+    "joshua", "cheese", "amanda", "summer", "ashley", "nicole", "chelsea", "matthew",
+    "access", "yankees", "987654321", "dallas", "austin", "thunder", "taylor", "matrix",
+    "welcome", "admin", "login", "passw0rd", "password1", "qwerty123", "hello", "secret",
+    "whatever", "changeme", "default", "guest", "root", "test", "qwerty1", "iloveyou1",
+    "admin123", "welcome1", "p@ssw0rd", "letmein1", "monkey1", "dragon1", "abcdef",
+    "abcd1234", "q1w2e3r4", "1q2w3e4r", "asdf1234", "zaq12wsx", "vaultpad",
+];
+
+const COMMON_WORDS: &[&str] = &[
+    "love", "house", "family", "friend", "happy", "money", "music", "dream", "magic", "heart",
+    "angel", "baby", "star", "moon", "sun", "sky", "blue", "red", "green", "black", "white",
+    "purple", "yellow", "silver", "golden", "gold", "summer", "winter", "spring", "autumn",
+    "apple", "orange", "banana", "cherry", "coffee", "pizza", "cookie", "chocolate", "flower",
+    "garden", "forest", "river", "ocean", "mountain", "horse", "tiger", "lion", "eagle",
+    "wolf", "bear", "dog", "cat", "fish", "bird", "dragon", "knight", "king", "queen",
+    "prince", "princess", "hello", "world", "secret", "power", "super", "hunter", "player",
+    "soccer", "guitar", "school", "office", "phone", "email", "google", "apple", "windows",
+    "correct", "battery", "staple", "password", "welcome", "monday", "friday", "sunday",
+    "january", "december", "london", "paris", "moscow", "berlin", "america", "russia",
+];
+
+const KEYBOARD_ROWS: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+const L33T_TABLE: &[(char, char)] = &[
+    ('4', 'a'), ('@', 'a'), ('8', 'b'), ('(', 'c'), ('{', 'c'), ('3', 'e'), ('6', 'g'),
+    ('1', 'i'), ('!', 'i'), ('|', 'i'), ('0', 'o'), ('$', 's'), ('5', 's'), ('7', 't'),
+    ('+', 't'), ('%', 'x'), ('2', 'z'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dictionary {
+    Passwords,
+    Words,
+    UserInputs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    Dictionary {
+        dictionary: Dictionary,
+        rank: usize,
+        reversed: bool,
+        l33t: bool,
+    },
+    Spatial {
+        turns: usize,
+    },
+    Repeat {
+        base_len: usize,
+    },
+    Sequence,
+    Year,
+    Date,
+    BruteForce,
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    /// Character range, `end` exclusive.
+    start: usize,
+    end: usize,
+    pattern: Pattern,
+    guesses_log10: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrackTimes {
+    pub online_throttled_secs: f64,
+    pub online_unthrottled_secs: f64,
+    /// The case that matters for the vault itself: an offline attack against Argon2.
+    pub offline_slow_hash_secs: f64,
+    pub offline_fast_hash_secs: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Feedback {
+    pub warning: Option<&'static str>,
+    pub suggestions: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordStrength {
+    /// 0 (too guessable) to 4 (very unguessable), as in zxcvbn.
+    pub score: u8,
+    pub guesses_log10: f64,
+    pub entropy_bits: f64,
+    pub crack_times: CrackTimes,
+    pub feedback: Feedback,
+}
+
+fn log10_factorial(n: usize) -> f64 {
+    (2..=n).map(|k| (k as f64).log10()).sum()
+}
+
+fn n_choose_k(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (1..=k).fold(1.0, |acc, i| acc * (n - k + i) as f64 / i as f64)
+}
+
+/// `log10(10^a + 10^b)` without overflowing.
+fn log10_add(a: f64, b: f64) -> f64 {
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    hi + (1.0 + 10f64.powf(lo - hi)).log10()
+}
+
+fn match_guesses_log10(pattern: &Pattern, raw_guesses_log10: f64, len: usize) -> f64 {
+    let floor = if len == 1 {
+        MIN_GUESSES_SINGLE_CHAR
+    } else {
+        MIN_GUESSES_MULTI_CHAR
+    };
+    match pattern {
+        Pattern::BruteForce => raw_guesses_log10,
+        _ => raw_guesses_log10.max(floor.log10()),
+    }
+}
+
+fn push(matches: &mut Vec<Match>, start: usize, end: usize, pattern: Pattern, guesses: f64) {
+    let guesses_log10 = match_guesses_log10(&pattern, guesses.max(1.0).log10(), end - start);
+    matches.push(Match {
+        start,
+        end,
+        pattern,
+        guesses_log10,
+    });
+}
+
+/// Extra guesses for capitalisation: none for all lower case, a factor of two for the usual
+/// placements, otherwise every way of choosing the upper-case letters.
+fn uppercase_variations(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|c| c.is_uppercase()).count();
+    let lower = word.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    let first_only = word[0].is_uppercase() && upper == 1;
+    let last_only = word[word.len() - 1].is_uppercase() && upper == 1;
+    if first_only || last_only || lower == 0 {
+        return 2.0;
+    }
+    (1..=upper.min(lower))
+        .map(|i| n_choose_k(upper + lower, i))
+        .sum()
+}
+
+fn l33t_variations(original: &[char], plain: &[char]) -> f64 {
+    let mut by_letter: HashMap<char, (usize, usize)> = HashMap::new();
+    for (o, p) in original.iter().zip(plain) {
+        let o = o.to_lowercase().next().unwrap_or(*o);
+        if o != *p {
+            by_letter.entry(*p).or_default().0 += 1;
+        } else if L33T_TABLE.iter().any(|(_, letter)| letter == p) {
+            by_letter.entry(*p).or_default().1 += 1;
+        }
+    }
+    by_letter
+        .values()
+        .filter(|(subbed, _)| *subbed > 0)
+        .map(|&(subbed, unsubbed)| {
+            if unsubbed == 0 {
+                2.0
+            } else {
+                (1..=subbed.min(unsubbed))
+                    .map(|i| n_choose_k(subbed + unsubbed, i))
+                    .sum()
+            }
+        })
+        .product::<f64>()
+        .max(1.0)
+}
+
+fn ranked_dictionaries(user_inputs: &[String]) -> Vec<(Dictionary, HashMap<String, usize>)> {
+    let rank = |words: &mut dyn Iterator<Item = String>| {
+        let mut ranked = HashMap::new();
+        for (i, word) in words.enumerate() {
+            ranked.entry(word).or_insert(i + 1);
+        }
+        ranked
+    };
+    vec![
+        (
+            Dictionary::Passwords,
+            rank(&mut COMMON_PASSWORDS.iter().map(|w| w.to_string())),
+        ),
+        (
+            Dictionary::Words,
+            rank(&mut COMMON_WORDS.iter().map(|w| w.to_string())),
+        ),
+        (
+            Dictionary::UserInputs,
+            rank(&mut user_inputs
+                .iter()
+                .flat_map(|input| {
+                    input
+                        .split(|c: char| !c.is_alphanumeric())
+                        .map(str::to_lowercase)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|w| w.chars().count() >= 3)),
+        ),
+    ]
+}
+
+/// Every way to read the password without l33t substitutions; `1` may stand for `i` or `l`.
+fn unl33t_variants(lower: &[char]) -> Vec<Vec<char>> {
+    let subbed = |one: char| {
+        lower
+            .iter()
+            .map(|c| match c {
+                '1' => one,
+                c => L33T_TABLE
+                    .iter()
+                    .find(|(sub, _)| sub == c)
+                    .map_or(*c, |(_, letter)| *letter),
+            })
+            .collect::<Vec<char>>()
+    };
+    let mut variants = vec![subbed('i'), subbed('l')];
+    variants.retain(|v| v != lower);
+    variants.dedup();
+    variants
+}
+
+fn dictionary_matches(chars: &[char], user_inputs: &[String], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let reversed: Vec<char> = lower.iter().rev().copied().collect();
+    let l33t = unl33t_variants(&lower);
+    let n = chars.len();
+
+    for (dictionary, ranked) in ranked_dictionaries(user_inputs) {
+        for start in 0..n {
+            for end in start + 3..=n {
+                let word: String = lower[start..end].iter().collect();
+                if let Some(&rank) = ranked.get(&word) {
+                    let guesses = rank as f64 * uppercase_variations(&chars[start..end]);
+                    let pattern = Pattern::Dictionary {
+                        dictionary,
+                        rank,
+                        reversed: false,
+                        l33t: false,
+                    };
+                    push(matches, start, end, pattern, guesses);
+                }
+
+                let backwards: String = reversed[n - end..n - start].iter().collect();
+                if backwards != word {
+                    if let Some(&rank) = ranked.get(&backwards) {
+                        let guesses = rank as f64 * uppercase_variations(&chars[start..end]) * 2.0;
+                        let pattern = Pattern::Dictionary {
+                            dictionary,
+                            rank,
+                            reversed: true,
+                            l33t: false,
+                        };
+                        push(matches, start, end, pattern, guesses);
+                    }
+                }
+
+                for plain in &l33t {
+                    let candidate: String = plain[start..end].iter().collect();
+                    if candidate == word {
+                        continue;
+                    }
+                    if let Some(&rank) = ranked.get(&candidate) {
+                        let guesses = rank as f64
+                            * uppercase_variations(&chars[start..end])
+                            * l33t_variations(&chars[start..end], &plain[start..end]);
+                        let pattern = Pattern::Dictionary {
+                            dictionary,
+                            rank,
+                            reversed: false,
+                            l33t: true,
+                        };
+                        push(matches, start, end, pattern, guesses);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn key_position(c: char) -> Option<(i32, i32, bool)> {
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, (plain, shifted))| {
+            plain
+                .chars()
+                .position(|k| k == c)
+                .map(|col| (row as i32, col as i32, false))
+                .or_else(|| {
+                    shifted
+                        .chars()
+                        .position(|k| k == c)
+                        .map(|col| (row as i32, col as i32, true))
+                })
+        })
+}
+
+/// Direction from `a` to `b` if they are neighbouring keys. Each row sits half a key to the
+/// right of the one above it.
+fn key_step(a: (i32, i32), b: (i32, i32)) -> Option<(i32, i32)> {
+    let (dr, dc) = (b.0 - a.0, b.1 - a.1);
+    let adjacent = match dr {
+        0 => dc.abs() == 1,
+        1 => dc == 0 || dc == -1,
+        -1 => dc == 0 || dc == 1,
+        _ => false,
+    };
+    adjacent.then_some((dr, dc))
+}
+
+fn spatial_guesses(len: usize, turns: usize, shifted: usize) -> f64 {
+    let mut guesses = 0.0;
+    for i in 2..=len {
+        for j in 1..=turns.min(i - 1) {
+            guesses += n_choose_k(i - 1, j - 1)
+                * KEYBOARD_STARTING_POSITIONS
+                * KEYBOARD_AVERAGE_DEGREE.powi(j as i32);
+        }
+    }
+    let unshifted = len - shifted;
+    if shifted > 0 {
+        guesses *= if unshifted == 0 {
+            2.0
+        } else {
+            (1..=shifted.min(unshifted))
+                .map(|i| n_choose_k(shifted + unshifted, i))
+                .sum()
+        };
+    }
+    guesses
+}
+
+fn spatial_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let positions: Vec<_> = chars.iter().map(|&c| key_position(c)).collect();
+    let mut start = 0;
+    while start < chars.len() {
+        let Some(first) = positions[start] else {
+            start += 1;
+            continue;
+        };
+        let mut end = start + 1;
+        let mut turns = 0;
+        let mut direction = None;
+        let mut shifted = first.2 as usize;
+        let mut previous = first;
+        while let Some(Some(next)) = positions.get(end) {
+            let Some(step) = key_step((previous.0, previous.1), (next.0, next.1)) else {
+                break;
+            };
+            if direction != Some(step) {
+                turns += 1;
+                direction = Some(step);
+            }
+            shifted += next.2 as usize;
+            previous = *next;
+            end += 1;
+        }
+        if end - start >= 3 {
+            let guesses = spatial_guesses(end - start, turns, shifted);
+            push(matches, start, end, Pattern::Spatial { turns }, guesses);
+        }
+        start = if end - start >= 3 { end } else { start + 1 };
+    }
+}
+
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let n = chars.len();
+    let mut start = 0;
+    while start + 2 < n {
+        let delta = chars[start + 1] as i64 - chars[start] as i64;
+        let mut end = start + 1;
+        while end < n && chars[end] as i64 - chars[end - 1] as i64 == delta {
+            end += 1;
+        }
+        let same_class = chars[start..end].iter().all(|c| c.is_ascii_digit())
+            || chars[start..end].iter().all(|c| c.is_ascii_lowercase())
+            || chars[start..end].iter().all(|c| c.is_ascii_uppercase());
+        if end - start >= 3 && (1..=5).contains(&delta.abs()) && same_class {
+            let first = chars[start];
+            let mut base = if "aAzZ019".contains(first) {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            if delta < 0 {
+                base *= 2.0;
+            }
+            push(matches, start, end, Pattern::Sequence, base * (end - start) as f64);
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn repeat_matches(
+    chars: &[char],
+    user_inputs: &[String],
+    reference_year: i32,
+    matches: &mut Vec<Match>,
+) {
+    let n = chars.len();
+    let mut start = 0;
+    while start < n {
+        // The shortest block that repeats from here, taken as far as it goes.
+        let best = (1..=(n - start) / 2).find_map(|block| {
+            let mut count = 1;
+            while start + (count + 1) * block <= n
+                && chars[start + count * block..start + (count + 1) * block]
+                    == chars[start..start + block]
+            {
+                count += 1;
+            }
+            let enough = if block == 1 { count >= 3 } else { count >= 2 };
+            enough.then_some((block, count))
+        });
+        match best {
+            Some((block, count)) => {
+                let end = start + block * count;
+                let base = &chars[start..start + block];
+                let base_log10 = minimum_guesses(base, user_inputs, reference_year).0;
+                let guesses = 10f64.powf(base_log10) * count as f64;
+                push(matches, start, end, Pattern::Repeat { base_len: block }, guesses);
+                start = end;
+            }
+            None => start += 1,
+        }
+    }
+}
+
+fn year_space(year: i32, reference_year: i32) -> f64 {
+    (year - reference_year).abs().max(MIN_YEAR_SPACE) as f64
+}
+
+fn plausible_year(year: i32) -> bool {
+    (1900..=2050).contains(&year)
+}
+
+/// Two-digit years are read as the nearest century, as people write them.
+fn full_year(year: i32, digits: usize) -> i32 {
+    match digits {
+        2 if year > 50 => 1900 + year,
+        2 => 2000 + year,
+        _ => year,
+    }
+}
+
+fn date_matches(chars: &[char], reference_year: i32, matches: &mut Vec<Match>) {
+    let n = chars.len();
+    let number = |s: &[char]| s.iter().collect::<String>().parse::<i32>().ok();
+    for start in 0..n {
+        for len in [4usize, 6, 8] {
+            let end = start + len;
+            if end > n || !chars[start..end].iter().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let digits = &chars[start..end];
+            if len == 4 {
+                if let Some(year) = number(digits).filter(|y| plausible_year(*y)) {
+                    let guesses = year_space(year, reference_year);
+                    push(matches, start, end, Pattern::Year, guesses);
+                }
+                continue;
+            }
+            // Day, month and year in the usual orders: d m y, m d y and y m d.
+            let year_len = len - 4;
+            let layouts = [
+                (&digits[0..2], &digits[2..4], &digits[4..]),
+                (&digits[2..4], &digits[0..2], &digits[4..]),
+                (&digits[len - 2..], &digits[year_len..year_len + 2], &digits[..year_len]),
+            ];
+            let valid_year = layouts.iter().find_map(|(day, month, year)| {
+                let (day, month, year) = (number(day)?, number(month)?, number(year)?);
+                let year = full_year(year, year_len);
+                ((1..=31).contains(&day) && (1..=12).contains(&month) && plausible_year(year))
+                    .then_some(year)
+            });
+            if let Some(year) = valid_year {
+                let guesses = year_space(year, reference_year) * 365.0;
+                push(matches, start, end, Pattern::Date, guesses);
+            }
+        }
+    }
+}
+
+/// The cheapest sequence of matches covering `chars`, with brute force filling any gaps.
+/// Returns the guesses (log10) and the sequence.
+fn minimum_guesses(chars: &[char], user_inputs: &[String], reference_year: i32) -> (f64, Vec<Match>) {
+    let mut matches = Vec::new();
+    dictionary_matches(chars, user_inputs, &mut matches);
+    spatial_matches(chars, &mut matches);
+    sequence_matches(chars, &mut matches);
+    repeat_matches(chars, user_inputs, reference_year, &mut matches);
+    date_matches(chars, reference_year, &mut matches);
+    search(chars.len(), matches)
+}
+
+fn search(n: usize, matches: Vec<Match>) -> (f64, Vec<Match>) {
+    if n == 0 {
+        return (0.0, Vec::new());
+    }
+    let mut by_end: Vec<Vec<Match>> = vec![Vec::new(); n + 1];
+    for m in matches {
+        by_end[m.end].push(m);
+    }
+    for (end, candidates) in by_end.iter_mut().enumerate().skip(1) {
+        for start in 0..end {
+            let len = end - start;
+            let guesses = BRUTEFORCE_CARDINALITY.powi(len as i32);
+            let floor = if len == 1 {
+                MIN_GUESSES_SINGLE_CHAR + 1.0
+            } else {
+                MIN_GUESSES_MULTI_CHAR + 1.0
+            };
+            candidates.push(Match {
+                start,
+                end,
+                pattern: Pattern::BruteForce,
+                guesses_log10: guesses.max(floor).log10(),
+            });
+        }
+    }
+
+    // best[k][l]: cheapest product (log10) of `l` matches covering the first `k` characters.
+    let mut best = vec![vec![f64::INFINITY; n + 1]; n + 1];
+    let mut back: Vec<Vec<Option<usize>>> = vec![vec![None; n + 1]; n + 1];
+    best[0][0] = 0.0;
+    for end in 1..=n {
+        for (index, m) in by_end[end].iter().enumerate() {
+            for l in 1..=end {
+                let previous = best[m.start][l - 1];
+                if previous.is_finite() && previous + m.guesses_log10 < best[end][l] {
+                    best[end][l] = previous + m.guesses_log10;
+                    back[end][l] = Some(index);
+                }
+            }
+        }
+    }
+
+    let total = |l: usize| {
+        log10_add(
+            log10_factorial(l) + best[n][l],
+            MIN_GUESSES_BEFORE_GROWING_SEQUENCE.log10() * (l - 1) as f64,
+        )
+    };
+    let (mut l, guesses_log10) = (1..=n)
+        .filter(|&l| best[n][l].is_finite())
+        .map(|l| (l, total(l)))
+        .fold((1, f64::INFINITY), |acc, cur| if cur.1 < acc.1 { cur } else { acc });
+
+    let mut sequence = Vec::with_capacity(l);
+    let mut end = n;
+    while end > 0 {
+        let Some(index) = back[end][l] else {
+            break;
+        };
+        let m = by_end[end][index].clone();
+        end = m.start;
+        l -= 1;
+        sequence.push(m);
+    }
+    sequence.reverse();
+    (guesses_log10, sequence)
+}
+
+fn score(guesses_log10: f64) -> u8 {
+    // zxcvbn adds a small margin so that a guess count right at a threshold scores low.
+    let guesses = guesses_log10;
+    if guesses < (1e3f64 + 5.0).log10() {
+        0
+    } else if guesses < (1e6f64 + 5.0).log10() {
+        1
+    } else if guesses < (1e8f64 + 5.0).log10() {
+        2
+    } else if guesses < (1e10f64 + 5.0).log10() {
+        3
+    } else {
+        4
+    }
+}
+
+fn dictionary_feedback(
+    m: &Match,
+    chars: &[char],
+    dictionary: Dictionary,
+    rank: usize,
+    reversed: bool,
+    l33t: bool,
+    only_match: bool,
+) -> Feedback {
+    let warning = match dictionary {
+        Dictionary::Passwords if only_match && !reversed && !l33t && rank <= 10 => {
+            Some("top_10_password")
+        }
+        Dictionary::Passwords if only_match && !reversed && !l33t && rank <= 100 => {
+            Some("top_100_password")
+        }
+        Dictionary::Passwords => Some("similar_to_common_password"),
+        Dictionary::Words if only_match => Some("word_by_itself"),
+        Dictionary::Words => None,
+        Dictionary::UserInputs => Some("contains_personal_info"),
+    };
+    let word = &chars[m.start..m.end];
+    let mut suggestions = vec!["add_another_word"];
+    if word[0].is_uppercase() && word[1..].iter().all(|c| !c.is_uppercase()) {
+        suggestions.push("capitalization_doesnt_help");
+    } else if word.iter().all(|c| !c.is_lowercase()) && word.iter().any(|c| c.is_uppercase()) {
+        suggestions.push("all_uppercase_doesnt_help");
+    }
+    if reversed {
+        suggestions.push("reversed_words_dont_help");
+    }
+    if l33t {
+        suggestions.push("predictable_substitutions_dont_help");
+    }
+    Feedback {
+        warning,
+        suggestions,
+    }
+}
+
+fn feedback(score: u8, chars: &[char], sequence: &[Match]) -> Feedback {
+    if chars.is_empty() {
+        return Feedback {
+            warning: None,
+            suggestions: vec!["use_a_few_words", "no_need_for_symbols"],
+        };
+    }
+    if score > 2 {
+        return Feedback {
+            warning: None,
+            suggestions: Vec::new(),
+        };
+    }
+    let Some(longest) = sequence
+        .iter()
+        .filter(|m| m.pattern != Pattern::BruteForce)
+        .max_by_key(|m| m.end - m.start)
+    else {
+        return Feedback {
+            warning: None,
+            suggestions: vec!["add_another_word"],
+        };
+    };
+    let (warning, suggestion) = match &longest.pattern {
+        Pattern::Dictionary {
+            dictionary,
+            rank,
+            reversed,
+            l33t,
+        } => {
+            return dictionary_feedback(
+                longest,
+                chars,
+                *dictionary,
+                *rank,
+                *reversed,
+                *l33t,
+                sequence.len() == 1,
+            );
+        }
+        Pattern::Spatial { turns: 1 } => ("straight_keyboard_row", "use_longer_keyboard_pattern"),
+        Pattern::Spatial { .. } => ("short_keyboard_pattern", "use_longer_keyboard_pattern"),
+        Pattern::Repeat { base_len: 1 } => ("repeated_characters", "avoid_repeats"),
+        Pattern::Repeat { .. } => ("repeated_pattern", "avoid_repeats"),
+        Pattern::Sequence => ("sequence", "avoid_sequences"),
+        Pattern::Year => ("recent_year", "avoid_years_and_dates"),
+        Pattern::Date => ("date", "avoid_years_and_dates"),
+        Pattern::BruteForce => unreachable!("brute force matches are filtered out"),
+    };
+    Feedback {
+        warning: Some(warning),
+        suggestions: vec!["add_another_word", suggestion],
+    }
+}
+
+fn crack_secs(guesses_log10: f64, rate: f64) -> f64 {
+    10f64.powf(guesses_log10 - rate.log10())
+}
+
+/// Estimate how hard `password` is to guess. `user_inputs` (an e-mail address, the project
+/// name) count as a dictionary of their own, since attackers know them too. Dates are judged
+/// against `current_year`.
+pub fn estimate(password: &str, user_inputs: &[String], current_year: i32) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let analysed = &chars[..chars.len().min(MAX_ANALYSED_LEN)];
+    let (guesses_log10, sequence) = minimum_guesses(analysed, user_inputs, current_year);
+    let guesses_log10 = guesses_log10
+        + (chars.len() - analysed.len()) as f64 * BRUTEFORCE_CARDINALITY.log10();
+    let score = score(guesses_log10);
+    PasswordStrength {
+        score,
+        guesses_log10,
+        entropy_bits: guesses_log10 * std::f64::consts::LOG2_10,
+        crack_times: CrackTimes {
+            online_throttled_secs: crack_secs(guesses_log10, ONLINE_THROTTLED_RATE),
+            online_unthrottled_secs: crack_secs(guesses_log10, ONLINE_UNTHROTTLED_RATE),
+            offline_slow_hash_secs: crack_secs(guesses_log10, OFFLINE_SLOW_HASH_RATE),
+            offline_fast_hash_secs: crack_secs(guesses_log10, OFFLINE_FAST_HASH_RATE),
+        },
+        feedback: feedback(score, analysed, &sequence),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(password: &str) -> Option<&'static str> {
+        estimate(password, &[], 2026).feedback.warning
+    }
+
+    #[test]
+    fn common_patterns_score_low_and_say_why() {
+        assert_eq!(estimate("", &[], 2026).score, 0);
+        assert_eq!(estimate("password", &[], 2026).score, 0);
+        assert_eq!(warning("password"), Some("top_10_password"));
+        assert_eq!(warning("P@ssw0rd"), Some("similar_to_common_password"));
+        assert_eq!(warning("qwertyuiop"), Some("top_100_password"));
+        assert_eq!(warning("zxcvfdsa"), Some("short_keyboard_pattern"));
+        assert_eq!(warning("aaaaaaaaaa"), Some("repeated_characters"));
+        assert_eq!(warning("jklmnopq"), Some("sequence"));
+        assert_eq!(warning("19041987"), Some("date"));
+        assert_eq!(
+            estimate("ivanov2026", &["ivanov@example.com".to_string()], 2026).feedback.warning,
+            Some("contains_personal_info")
+        );
+
+        let strong = estimate("correct horse battery staple violin", &[], 2026);
+        assert_eq!(strong.score, 4);
+        assert!(strong.feedback.suggestions.is_empty());
+        assert!(strong.crack_times.offline_slow_hash_secs > 1e6);
+        assert!(estimate("Tr0ub4dour&3", &[], 2026).guesses_log10 > 6.0);
+    }
+}
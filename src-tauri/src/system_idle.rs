@@ -0,0 +1,167 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Session idle time and screen-lock notifications for auto-lock on Linux. The frontend timer
+//! only sees activity inside the window and stalls while the window is hidden; this watcher
+//! reports how long the whole session has been idle (`system-idle {idle_secs}`, while the vault
+//! is unlocked) and, with `lock_on_screen_lock` on, locks the vault as soon as the screen locks
+//! (`screen-locked {vault_locked}`).
+//!
+//! Screen locks come from the `ActiveChanged` signal of org.freedesktop.ScreenSaver (KDE and
+//! most desktops) or org.gnome.ScreenSaver; idle time from Mutter's idle monitor on GNOME or
+//! `GetSessionIdleTime` elsewhere. Compositors offering neither over D-Bus (wlroots ones that
+//! only have ext-idle-notify) are left to the in-app timer.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::settings::lock_session;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+pub const SETTING_LOCK_ON_SCREEN_LOCK: &str = "lock_on_screen_lock";
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SystemIdle {
+    pub idle_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ScreenLocked {
+    pub vault_locked: bool,
+}
+
+/// What this desktop session lets the watcher see.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IdleSupport {
+    pub idle_time: bool,
+    pub screen_lock: bool,
+}
+
+/// On unless turned off.
+pub fn lock_on_screen_lock(storage: &dyn StorageProvider) -> bool {
+    storage
+        .get_setting(SETTING_LOCK_ON_SCREEN_LOCK)
+        .ok()
+        .flatten()
+        .is_none_or(|v| v != "false")
+}
+
+pub fn set_lock_on_screen_lock(storage: &dyn StorageProvider, enabled: bool) -> Result<(), String> {
+    storage
+        .set_setting(SETTING_LOCK_ON_SCREEN_LOCK, if enabled { "true" } else { "false" })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use zbus::blocking::{Connection, Proxy};
+
+    /// `(bus name, object path, interface)` of the screensaver services that announce locks.
+    const SCREENSAVERS: [(&str, &str, &str); 2] = [
+        ("org.freedesktop.ScreenSaver", "/org/freedesktop/ScreenSaver", "org.freedesktop.ScreenSaver"),
+        ("org.gnome.ScreenSaver", "/org/gnome/ScreenSaver", "org.gnome.ScreenSaver"),
+    ];
+
+    fn proxy<'a>(conn: &Connection, (name, path, iface): (&'a str, &'a str, &'a str)) -> Option<Proxy<'a>> {
+        Proxy::new(conn, name, path, iface).ok()
+    }
+
+    pub fn idle_secs() -> Option<u64> {
+        let conn = Connection::session().ok()?;
+        let mutter = (
+            "org.gnome.Mutter.IdleMonitor",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "org.gnome.Mutter.IdleMonitor",
+        );
+        if let Some(ms) = proxy(&conn, mutter).and_then(|p| p.call::<_, _, u64>("GetIdletime", &()).ok()) {
+            return Some(ms / 1000);
+        }
+        // KDE answers in milliseconds, whatever the draft spec says.
+        proxy(&conn, SCREENSAVERS[0])
+            .and_then(|p| p.call::<_, _, u32>("GetSessionIdleTime", &()).ok())
+            .map(|ms| u64::from(ms) / 1000)
+    }
+
+    pub fn screen_lock_available() -> bool {
+        let Ok(conn) = Connection::session() else {
+            return false;
+        };
+        SCREENSAVERS.into_iter().any(|service| {
+            proxy(&conn, service).is_some_and(|p| p.call::<_, _, bool>("GetActive", &()).is_ok())
+        })
+    }
+
+    /// Call `on_lock` whenever a screensaver service reports the screen as locked.
+    pub fn watch_screen_lock(on_lock: impl Fn() + Clone + Send + 'static) {
+        for service in SCREENSAVERS {
+            let on_lock = on_lock.clone();
+            std::thread::spawn(move || {
+                let Ok(conn) = Connection::session() else {
+                    return;
+                };
+                let Some(signals) = proxy(&conn, service).and_then(|p| p.receive_signal("ActiveChanged").ok()) else {
+                    return;
+                };
+                for message in signals {
+                    if message.body().deserialize::<bool>().unwrap_or(false) {
+                        on_lock();
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    pub fn idle_secs() -> Option<u64> {
+        None
+    }
+
+    pub fn screen_lock_available() -> bool {
+        false
+    }
+
+    pub fn watch_screen_lock(_on_lock: impl Fn() + Clone + Send + 'static) {}
+}
+
+pub fn support() -> IdleSupport {
+    IdleSupport {
+        idle_time: imp::idle_secs().is_some(),
+        screen_lock: imp::screen_lock_available(),
+    }
+}
+
+fn is_unlocked(state: &AppState) -> bool {
+    state.cached_key.lock().is_ok_and(|key| key.is_some())
+}
+
+fn on_screen_locked(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .storage
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|storage| lock_on_screen_lock(&**storage)))
+        .unwrap_or(false);
+    let vault_locked = enabled && is_unlocked(&state) && lock_session(&state).is_ok();
+    let _ = app.emit("screen-locked", ScreenLocked { vault_locked });
+}
+
+pub fn spawn(app: AppHandle) {
+    let handle = app.clone();
+    imp::watch_screen_lock(move || on_screen_locked(&handle));
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if !is_unlocked(&app.state::<AppState>()) {
+            continue;
+        }
+        if let Some(idle_secs) = imp::idle_secs() {
+            let _ = app.emit("system-idle", SystemIdle { idle_secs });
+        }
+    });
+}
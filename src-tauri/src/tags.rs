@@ -0,0 +1,218 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Project tags. Names are encrypted with the vault key like project content, so the database
+//! only reveals how many tags there are and which projects share one. Tags exist while at least
+//! one project carries them; names match case-insensitively, keeping the first spelling.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::Tag;
+use crate::storage::StorageProvider;
+
+const MAX_TAG_LEN: usize = 64;
+const MAX_TAGS_PER_PROJECT: usize = 32;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagInfo {
+    pub id: String,
+    pub name: String,
+    pub project_count: usize,
+}
+
+fn name_aad(tag_id: &str) -> Vec<u8> {
+    crypto::field_aad(tag_id, "tag_name")
+}
+
+fn decrypt_name(tag: &Tag, key: &[u8; crypto::KEY_LEN]) -> Result<String, String> {
+    crypto::try_decrypt_with_key_aad(&tag.encrypted_name, key, &name_aad(&tag.id))
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| format!("Failed to decrypt tag {}", tag.id))
+}
+
+fn encrypt_name(tag_id: &str, name: &str, key: &[u8; crypto::KEY_LEN]) -> Result<Vec<u8>, String> {
+    crypto::encrypt_with_key_aad(name.as_bytes(), key, &name_aad(tag_id)).map_err(|e| e.to_string())
+}
+
+/// Trimmed, non-empty names with case-insensitive duplicates dropped.
+fn normalize(names: &[String]) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        if name.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tags must be at most {MAX_TAG_LEN} characters"));
+        }
+        if !out.iter().any(|n| n.to_lowercase() == name.to_lowercase()) {
+            out.push(name.to_string());
+        }
+    }
+    if out.len() > MAX_TAGS_PER_PROJECT {
+        return Err(format!("A project can have at most {MAX_TAGS_PER_PROJECT} tags"));
+    }
+    Ok(out)
+}
+
+/// Tag ids of every project that has tags.
+pub fn by_project(storage: &dyn StorageProvider) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (project_id, tag_id) in storage.list_project_tags().map_err(|e| e.to_string())? {
+        map.entry(project_id).or_default().push(tag_id);
+    }
+    Ok(map)
+}
+
+/// All tags with their decrypted names, sorted by name.
+pub fn list(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN]) -> Result<Vec<TagInfo>, String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, tag_id) in storage.list_project_tags().map_err(|e| e.to_string())? {
+        *counts.entry(tag_id).or_default() += 1;
+    }
+    let mut tags = storage
+        .list_tags()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|tag| {
+            Ok(TagInfo {
+                id: tag.id.clone(),
+                name: decrypt_name(tag, key)?,
+                project_count: counts.get(&tag.id).copied().unwrap_or(0),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    tags.sort_by_key(|t| t.name.to_lowercase());
+    Ok(tags)
+}
+
+/// Give a project exactly the tags named in `names`, creating tags that do not exist yet.
+/// Returns the project's tags.
+pub fn set_for_project(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    project_id: &str,
+    names: &[String],
+) -> Result<Vec<TagInfo>, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let existing = list(storage, key)?;
+
+    let mut tag_ids = Vec::new();
+    for name in normalize(names)? {
+        let found = existing.iter().find(|t| t.name.to_lowercase() == name.to_lowercase());
+        let id = match found {
+            Some(tag) => tag.id.clone(),
+            None => {
+                let id = Uuid::new_v4().to_string();
+                storage
+                    .create_tag(&Tag {
+                        encrypted_name: encrypt_name(&id, &name, key)?,
+                        id: id.clone(),
+                        created_at: clock.now().to_rfc3339(),
+                    })
+                    .map_err(|e| e.to_string())?;
+                id
+            }
+        };
+        tag_ids.push(id);
+    }
+    storage
+        .set_project_tags(project_id, &tag_ids)
+        .map_err(|e| e.to_string())?;
+
+    Ok(list(storage, key)?
+        .into_iter()
+        .filter(|t| tag_ids.contains(&t.id))
+        .collect())
+}
+
+/// The id under which `tag`, brought back from a backup, is kept: its own while the vault still
+/// has it, otherwise that of a tag with the same name, otherwise its own once it is recreated.
+pub fn adopt(storage: &dyn StorageProvider, key: &[u8; crypto::KEY_LEN], tag: &Tag) -> Result<String, String> {
+    let existing = storage.list_tags().map_err(|e| e.to_string())?;
+    if existing.iter().any(|t| t.id == tag.id) {
+        return Ok(tag.id.clone());
+    }
+    let name = decrypt_name(tag, key)?.to_lowercase();
+    for other in &existing {
+        if decrypt_name(other, key)?.to_lowercase() == name {
+            return Ok(other.id.clone());
+        }
+    }
+    storage.create_tag(tag).map_err(|e| e.to_string())?;
+    Ok(tag.id.clone())
+}
+
+/// Re-encrypt every tag name under `new_key`, for a master password change.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for mut tag in storage.list_tags().map_err(|e| e.to_string())? {
+        let name = decrypt_name(&tag, old_key)?;
+        tag.encrypted_name = encrypt_name(&tag.id, &name, new_key)?;
+        storage.update_tag(&tag).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    fn project(storage: &LocalStorage, id: &str) {
+        storage
+            .create_project(&Project {
+                id: id.to_string(),
+                name: String::new(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: None,
+                sync_status: "local".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn tags_are_shared_by_name_and_dropped_when_unused() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [5u8; crypto::KEY_LEN];
+        project(&storage, "a");
+        project(&storage, "b");
+
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        set_for_project(&storage, &SystemClock, &key, "a", &names(&["Work", " finance ", "work"])).unwrap();
+        set_for_project(&storage, &SystemClock, &key, "b", &names(&["WORK"])).unwrap();
+
+        let tags = list(&storage, &key).unwrap();
+        let summary: Vec<(&str, usize)> = tags.iter().map(|t| (t.name.as_str(), t.project_count)).collect();
+        assert_eq!(summary, [("finance", 1), ("Work", 2)]);
+        assert!(storage.list_tags().unwrap().iter().all(|t| !t.encrypted_name.windows(4).any(|w| w == b"Work")));
+
+        storage.delete_project("a").unwrap();
+        let tags = list(&storage, &key).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(by_project(&storage).unwrap()["b"], [tags[0].id.clone()]);
+
+        let new_key = [6u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        assert_eq!(list(&storage, &new_key).unwrap()[0].name, "Work");
+    }
+}
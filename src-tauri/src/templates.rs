@@ -0,0 +1,252 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Project templates: a name and prefilled content, such as the fields of a server login or a
+//! bank account, that new projects start from. The name and content are encrypted together
+//! with the vault key, bound to the template id. Templates sync like folders: the newer side
+//! wins, and a template that was synced is kept as a `deleted` tombstone until the deletion
+//! reaches the server.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::clock::Clock;
+use crate::credential;
+use crate::crypto;
+use crate::models::{ProjectKind, Template};
+use crate::server_features;
+use crate::storage::remote::RemoteStorage;
+use crate::storage::{StorageError, StorageProvider};
+
+const MAX_NAME_LEN: usize = 128;
+const MAX_CONTENT_LEN: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateInfo {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub kind: ProjectKind,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What is encrypted for a template.
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    name: String,
+    content: String,
+}
+
+fn seal(id: &str, sealed: &Sealed, key: &[u8; crypto::KEY_LEN]) -> Result<Vec<u8>, String> {
+    let plain = Zeroizing::new(serde_json::to_vec(sealed).map_err(|e| e.to_string())?);
+    crypto::encrypt_with_key_aad(&plain, key, &crypto::field_aad(id, "template"))
+        .map_err(|e| e.to_string())
+}
+
+fn open(template: &Template, key: &[u8; crypto::KEY_LEN]) -> Result<Sealed, String> {
+    let aad = crypto::field_aad(&template.id, "template");
+    let plain = crypto::try_decrypt_with_key_aad(&template.encrypted_template, key, &aad)
+        .map(Zeroizing::new)
+        .ok_or_else(|| format!("Failed to decrypt template {}", template.id))?;
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+fn info(template: &Template, sealed: Sealed) -> TemplateInfo {
+    TemplateInfo {
+        id: template.id.clone(),
+        kind: credential::parse(&sealed.content).0,
+        name: sealed.name,
+        content: sealed.content,
+        created_at: template.created_at.clone(),
+        updated_at: template.updated_at.clone(),
+    }
+}
+
+/// Templates that have not been deleted.
+fn live(storage: &dyn StorageProvider) -> Result<Vec<Template>, String> {
+    Ok(storage
+        .list_templates()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|t| t.sync_status != "deleted")
+        .collect())
+}
+
+/// Templates in the order they were created.
+pub fn list(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<Vec<TemplateInfo>, String> {
+    live(storage)?
+        .iter()
+        .map(|t| Ok(info(t, open(t, key)?)))
+        .collect()
+}
+
+/// One template, with its content for a new project.
+pub fn get(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    id: &str,
+) -> Result<TemplateInfo, String> {
+    let template = live(storage)?
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("Template not found: {id}"))?;
+    Ok(info(&template, open(&template, key)?))
+}
+
+pub fn create(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+    name: &str,
+    content: &str,
+) -> Result<TemplateInfo, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("Template names must be at most {MAX_NAME_LEN} characters"));
+    }
+    if content.len() > MAX_CONTENT_LEN {
+        return Err(format!("Templates must be at most {} MiB", MAX_CONTENT_LEN / 1024 / 1024));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = clock.now().to_rfc3339();
+    let sealed = Sealed { name: name.to_string(), content: content.to_string() };
+    let template = Template {
+        encrypted_template: seal(&id, &sealed, key)?,
+        id,
+        created_at: now.clone(),
+        updated_at: now,
+        sync_status: "local".to_string(),
+    };
+    storage.save_template(&template).map_err(|e| e.to_string())?;
+    Ok(info(&template, sealed))
+}
+
+pub fn delete(storage: &dyn StorageProvider, clock: &dyn Clock, id: &str) -> Result<(), String> {
+    let mut template = live(storage)?
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("Template not found: {id}"))?;
+    if template.sync_status == "local" {
+        return storage.delete_template(id).map_err(|e| e.to_string());
+    }
+    template.updated_at = clock.now().to_rfc3339();
+    template.sync_status = "deleted".to_string();
+    storage.save_template(&template).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt every template under `new_key`, for a master password change.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for mut template in live(storage)? {
+        let sealed = open(&template, old_key)?;
+        template.encrypted_template = seal(&template.id, &sealed, new_key)?;
+        template.updated_at = clock.now().to_rfc3339();
+        if template.sync_status == "synced" {
+            template.sync_status = "modified".to_string();
+        }
+        storage.save_template(&template).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Exchange templates with the server, as `folders::sync` does folders. Does nothing on a
+/// server without templates.
+pub fn sync(local: &dyn StorageProvider, remote: &RemoteStorage) -> Result<(), String> {
+    if !server_features::supports(remote, server_features::FEATURE_TEMPLATES)? {
+        return Ok(());
+    }
+    let remote_templates = match remote.list_templates() {
+        Ok(templates) => templates,
+        Err(StorageError::NotFound(_)) => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut local_templates: HashMap<String, Template> = local
+        .list_templates()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+
+    for theirs in remote_templates {
+        let remote_wins = local_templates
+            .get(&theirs.id)
+            .is_none_or(|ours| ours.sync_status == "synced" || theirs.updated_at > ours.updated_at);
+        if !remote_wins {
+            continue;
+        }
+        local_templates.remove(&theirs.id);
+        if theirs.sync_status == "deleted" {
+            local.delete_template(&theirs.id).map_err(|e| e.to_string())?;
+        } else {
+            local.save_template(&theirs).map_err(|e| e.to_string())?;
+        }
+    }
+
+    for mut ours in local_templates.into_values().filter(|t| t.sync_status != "synced") {
+        remote.put_template(&ours).map_err(|e| e.to_string())?;
+        if ours.sync_status == "deleted" {
+            local.delete_template(&ours.id).map_err(|e| e.to_string())?;
+        } else {
+            ours.sync_status = "synced".to_string();
+            local.save_template(&ours).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Credential;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn templates_are_encrypted_and_deleted_through_tombstones() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [4u8; crypto::KEY_LEN];
+        let clock = SystemClock;
+
+        assert!(create(&storage, &clock, &key, "  ", "").is_err());
+        let wifi = create(&storage, &clock, &key, " Wi-Fi ", "SSID:\nPassword:").unwrap();
+        assert_eq!(wifi.name, "Wi-Fi");
+        assert_eq!(wifi.kind, ProjectKind::Note);
+        let mut login = Credential::default();
+        login.url = "https://".to_string();
+        let login = credential::encode(&login).unwrap();
+        let server = create(&storage, &clock, &key, "Server login", &login).unwrap();
+        assert_eq!(server.kind, ProjectKind::Credential);
+
+        let stored = storage.list_templates().unwrap();
+        assert!(!stored[0].encrypted_template.windows(4).any(|w| w == b"SSID"));
+
+        let new_key = [5u8; crypto::KEY_LEN];
+        reencrypt(&storage, &clock, &key, &new_key).unwrap();
+        assert_eq!(get(&storage, &new_key, &wifi.id).unwrap().content, "SSID:\nPassword:");
+
+        let mut synced = storage.list_templates().unwrap().remove(1);
+        synced.sync_status = "synced".to_string();
+        storage.save_template(&synced).unwrap();
+        delete(&storage, &clock, &wifi.id).unwrap();
+        delete(&storage, &clock, &server.id).unwrap();
+        assert!(list(&storage, &new_key).unwrap().is_empty());
+        let tombstones = storage.list_templates().unwrap();
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].sync_status, "deleted");
+    }
+}
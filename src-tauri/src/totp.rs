@@ -0,0 +1,425 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Time-based one-time passwords (RFC 6238) for a project's second factor. The secret is
+//! encrypted with the project key, like custom fields, and only ever decrypted here: the
+//! frontend gets the current code and the settings, never the secret itself.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::attachments::ProjectKey;
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::ProjectTotp;
+use crate::storage::StorageProvider;
+
+const DEFAULT_DIGITS: u32 = 6;
+const DEFAULT_PERIOD: u64 = 30;
+const MAX_PERIOD: u64 = 300;
+/// Shorter secrets than 80 bits are below what RFC 4226 allows.
+const MIN_SECRET_LEN: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Algorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(Algorithm::Sha1),
+            "SHA256" => Ok(Algorithm::Sha256),
+            "SHA512" => Ok(Algorithm::Sha512),
+            _ => Err(format!("Unsupported TOTP algorithm: {name}")),
+        }
+    }
+}
+
+/// Code settings; whatever is not given takes the common defaults (SHA-1, 6 digits, 30 s).
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct TotpOptions {
+    pub algorithm: Option<Algorithm>,
+    pub digits: Option<u32>,
+    pub period: Option<u64>,
+}
+
+/// A project's TOTP settings, without the secret.
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpInfo {
+    pub algorithm: Algorithm,
+    pub digits: u32,
+    pub period: u64,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpCode {
+    pub code: String,
+    pub period: u64,
+    /// Seconds until the next code.
+    pub remaining: u64,
+}
+
+/// What is encrypted for a project.
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct Sealed {
+    /// Base32, upper case, without padding.
+    secret: String,
+    #[zeroize(skip)]
+    algorithm: Algorithm,
+    digits: u32,
+    period: u64,
+    issuer: Option<String>,
+    account: Option<String>,
+}
+
+impl Sealed {
+    fn info(&self) -> TotpInfo {
+        TotpInfo {
+            algorithm: self.algorithm,
+            digits: self.digits,
+            period: self.period,
+            issuer: self.issuer.clone(),
+            account: self.account.clone(),
+        }
+    }
+}
+
+/// RFC 4648 base32, ignoring case, spaces, dashes and padding.
+fn decode_base32(text: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let mut out = Zeroizing::new(Vec::new());
+    let mut bits: u64 = 0;
+    let mut count = 0;
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '-' && *c != '=') {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        bits = (bits << 5) | value;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    bits.zeroize();
+    Some(out)
+}
+
+fn hotp(secret: &[u8], counter: u64, algorithm: Algorithm, digits: u32) -> String {
+    let message = counter.to_be_bytes();
+    let mac = Zeroizing::new(match algorithm {
+        Algorithm::Sha1 => Hmac::<Sha1>::new_from_slice(secret)
+            .expect("HMAC accepts any key length")
+            .chain_update(message)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+        Algorithm::Sha256 => Hmac::<Sha256>::new_from_slice(secret)
+            .expect("HMAC accepts any key length")
+            .chain_update(message)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+        Algorithm::Sha512 => Hmac::<Sha512>::new_from_slice(secret)
+            .expect("HMAC accepts any key length")
+            .chain_update(message)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+    });
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        mac[offset] & 0x7f,
+        mac[offset + 1],
+        mac[offset + 2],
+        mac[offset + 3],
+    ]);
+    format!("{:0width$}", binary % 10u32.pow(digits), width = digits as usize)
+}
+
+/// The code for `unix_time`, with the seconds it stays valid.
+fn code_at(sealed: &Sealed, unix_time: u64) -> Result<TotpCode, String> {
+    let secret = decode_base32(&sealed.secret).ok_or("The stored TOTP secret is invalid")?;
+    Ok(TotpCode {
+        code: hotp(&secret, unix_time / sealed.period, sealed.algorithm, sealed.digits),
+        period: sealed.period,
+        remaining: sealed.period - unix_time % sealed.period,
+    })
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.filter(|_| bytes[i] == b'%').and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A secret given either as base32 or as an `otpauth://totp/...` URI, whose parameters then
+/// fill in what `options` leaves open.
+fn parse_secret(input: &str, options: TotpOptions) -> Result<Sealed, String> {
+    let input = input.trim();
+    let mut sealed = Sealed {
+        secret: String::new(),
+        algorithm: Algorithm::default(),
+        digits: DEFAULT_DIGITS,
+        period: DEFAULT_PERIOD,
+        issuer: None,
+        account: None,
+    };
+    if input.to_ascii_lowercase().starts_with("otpauth://") {
+        let uri = url::Url::parse(input).map_err(|_| "Invalid otpauth URI".to_string())?;
+        if uri.host_str() != Some("totp") {
+            return Err("Only TOTP (time-based) otpauth URIs are supported".to_string());
+        }
+        let label = percent_decode(uri.path().trim_start_matches('/'));
+        match label.split_once(':') {
+            Some((issuer, account)) => {
+                sealed.issuer = Some(issuer.trim().to_string());
+                sealed.account = Some(account.trim().to_string());
+            }
+            None if !label.is_empty() => sealed.account = Some(label),
+            None => {}
+        }
+        for (name, value) in uri.query_pairs() {
+            match name.as_ref() {
+                "secret" => sealed.secret = value.to_string(),
+                "algorithm" => sealed.algorithm = Algorithm::parse(&value)?,
+                "digits" => sealed.digits = value.parse().map_err(|_| "Invalid TOTP digits")?,
+                "period" => sealed.period = value.parse().map_err(|_| "Invalid TOTP period")?,
+                "issuer" => sealed.issuer = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    } else {
+        sealed.secret = input.to_string();
+    }
+
+    sealed.algorithm = options.algorithm.unwrap_or(sealed.algorithm);
+    sealed.digits = options.digits.unwrap_or(sealed.digits);
+    sealed.period = options.period.unwrap_or(sealed.period);
+    if !(6..=8).contains(&sealed.digits) {
+        return Err("TOTP codes have 6 to 8 digits".to_string());
+    }
+    if !(1..=MAX_PERIOD).contains(&sealed.period) {
+        return Err(format!("The TOTP period must be 1 to {MAX_PERIOD} seconds"));
+    }
+    let decoded = decode_base32(&sealed.secret).ok_or("The TOTP secret is not valid base32")?;
+    if decoded.len() < MIN_SECRET_LEN {
+        return Err("The TOTP secret is too short".to_string());
+    }
+    let normalized: String = sealed
+        .secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    sealed.secret.zeroize();
+    sealed.secret = normalized;
+    Ok(sealed)
+}
+
+fn seal(project_key: ProjectKey<'_>, project_id: &str, sealed: &Sealed) -> Result<Vec<u8>, String> {
+    let plain = Zeroizing::new(serde_json::to_vec(sealed).map_err(|e| e.to_string())?);
+    match project_key {
+        ProjectKey::Vault(key) => {
+            crypto::encrypt_with_key_aad(&plain, key, &crypto::field_aad(project_id, "totp"))
+        }
+        ProjectKey::Password(password) => crypto::encrypt(&plain, password),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn open(project_key: ProjectKey<'_>, record: &ProjectTotp) -> Result<Sealed, String> {
+    let plain = Zeroizing::new(match project_key {
+        ProjectKey::Vault(key) => crypto::try_decrypt_with_key_aad(
+            &record.encrypted_secret,
+            key,
+            &crypto::field_aad(&record.project_id, "totp"),
+        )
+        .ok_or("Wrong key for this TOTP secret")?,
+        ProjectKey::Password(password) => {
+            crypto::decrypt(&record.encrypted_secret, password).map_err(|e| e.to_string())?
+        }
+    });
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+fn stored(storage: &dyn StorageProvider, project_id: &str) -> Result<ProjectTotp, String> {
+    storage
+        .get_project_totp(project_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "This project has no TOTP secret".to_string())
+}
+
+/// Store a project's TOTP secret, replacing any it had.
+pub fn set(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+    secret: &str,
+    options: TotpOptions,
+) -> Result<TotpInfo, String> {
+    storage.get_project(project_id).map_err(|e| e.to_string())?;
+    let sealed = parse_secret(secret, options)?;
+    let now = clock.now().to_rfc3339();
+    let created_at = storage
+        .get_project_totp(project_id)
+        .map_err(|e| e.to_string())?
+        .map_or_else(|| now.clone(), |existing| existing.created_at);
+    storage
+        .save_project_totp(&ProjectTotp {
+            project_id: project_id.to_string(),
+            encrypted_secret: seal(project_key, project_id, &sealed)?,
+            created_at,
+            updated_at: now,
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(sealed.info())
+}
+
+/// The project's TOTP settings; `None` when it has no secret.
+pub fn info(
+    storage: &dyn StorageProvider,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<Option<TotpInfo>, String> {
+    storage
+        .get_project_totp(project_id)
+        .map_err(|e| e.to_string())?
+        .map(|record| Ok(open(project_key, &record)?.info()))
+        .transpose()
+}
+
+pub fn code(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_key: ProjectKey<'_>,
+    project_id: &str,
+) -> Result<TotpCode, String> {
+    let sealed = open(project_key, &stored(storage, project_id)?)?;
+    let now = u64::try_from(clock.now().timestamp()).map_err(|e| e.to_string())?;
+    code_at(&sealed, now)
+}
+
+pub fn remove(storage: &dyn StorageProvider, project_id: &str) -> Result<(), String> {
+    storage.delete_project_totp(project_id).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt a project's TOTP secret with its new key after the project password was set,
+/// changed or removed.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    project_id: &str,
+    old_key: ProjectKey<'_>,
+    new_key: ProjectKey<'_>,
+) -> Result<(), String> {
+    if old_key.same_as(&new_key) {
+        return Ok(());
+    }
+    let Some(mut record) = storage.get_project_totp(project_id).map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+    let sealed = open(old_key, &record)?;
+    record.encrypted_secret = seal(new_key, project_id, &sealed)?;
+    storage.save_project_totp(&record).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt every TOTP secret held under the vault key with `new_key`, for a master password
+/// change. Secrets of projects with a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for mut record in storage.list_project_totps().map_err(|e| e.to_string())? {
+        let Ok(sealed) = open(ProjectKey::Vault(old_key), &record) else {
+            continue;
+        };
+        record.encrypted_secret = seal(ProjectKey::Vault(new_key), &record.project_id, &sealed)?;
+        storage.save_project_totp(&record).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base32(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+        let mut out = String::new();
+        for chunk in bytes.chunks(5) {
+            let mut buf = [0u8; 5];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            let n = u64::from_be_bytes([0, 0, 0, buf[0], buf[1], buf[2], buf[3], buf[4]]);
+            for i in 0..(chunk.len() * 8).div_ceil(5) {
+                out.push(ALPHABET[((n >> (35 - i * 5)) & 31) as usize] as char);
+            }
+        }
+        out
+    }
+
+    /// The test vectors of RFC 6238, appendix B.
+    #[test]
+    fn codes_match_the_rfc_vectors() {
+        let cases = [
+            (Algorithm::Sha1, &b"12345678901234567890"[..], 59, "94287082"),
+            (Algorithm::Sha1, &b"12345678901234567890"[..], 1111111109, "07081804"),
+            (Algorithm::Sha256, &b"12345678901234567890123456789012"[..], 59, "46119246"),
+            (
+                Algorithm::Sha512,
+                &b"1234567890123456789012345678901234567890123456789012345678901234"[..],
+                20000000000,
+                "47863826",
+            ),
+        ];
+        for (algorithm, secret, time, expected) in cases {
+            let options = TotpOptions { algorithm: Some(algorithm), digits: Some(8), period: None };
+            let sealed = parse_secret(&base32(secret).to_lowercase(), options).unwrap();
+            let code = code_at(&sealed, time).unwrap();
+            assert_eq!(code.code, expected);
+            assert_eq!(code.remaining, 30 - time % 30);
+        }
+    }
+
+    #[test]
+    fn otpauth_uris_are_parsed() {
+        let uri = "otpauth://totp/Example:alice%40example.com?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP\
+                   &issuer=Example&digits=8&period=60&algorithm=SHA256";
+        let sealed = parse_secret(uri, TotpOptions::default()).unwrap();
+        let info = sealed.info();
+        assert_eq!(info.issuer.as_deref(), Some("Example"));
+        assert_eq!(info.account.as_deref(), Some("alice@example.com"));
+        assert_eq!((info.algorithm, info.digits, info.period), (Algorithm::Sha256, 8, 60));
+
+        assert!(parse_secret("otpauth://hotp/x?secret=JBSWY3DPEHPK3PXP", TotpOptions::default())
+            .is_err());
+        assert!(parse_secret("not base32!", TotpOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Trash. Deleting a project only marks it as deleted, so it can be restored until it is purged
+//! by hand or, once older than the retention period, by the hourly sweep. Purging does what
+//! deleting used to: synced projects become tombstones so the deletion reaches the server.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::clock::Clock;
+use crate::cooling_off;
+use crate::crypto;
+use crate::keychain;
+use crate::password_registry;
+use crate::storage::StorageProvider;
+use crate::AppState;
+
+const SETTING_TRASH_RETENTION_DAYS: &str = "trash_retention_days";
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedProject {
+    pub id: String,
+    pub name: String,
+    pub deleted_at: String,
+    /// When the sweep will purge it; `None` while the retention is off.
+    pub purge_at: Option<String>,
+}
+
+/// Days a project stays in the trash; 0 keeps it until purged by hand.
+pub fn retention_days(storage: &dyn StorageProvider) -> Result<u32, String> {
+    Ok(storage
+        .get_setting(SETTING_TRASH_RETENTION_DAYS)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+pub fn set_retention_days(storage: &dyn StorageProvider, days: u32) -> Result<(), String> {
+    storage
+        .set_setting(SETTING_TRASH_RETENTION_DAYS, &days.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Ids of the trashed projects.
+pub fn ids(storage: &dyn StorageProvider) -> Result<HashSet<String>, String> {
+    Ok(storage
+        .list_trashed_projects()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect())
+}
+
+fn purge_at(deleted_at: &str, days: u32) -> Option<DateTime<Utc>> {
+    if days == 0 {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(deleted_at)
+        .ok()
+        .map(|at| at.with_timezone(&Utc) + chrono::Duration::days(days.into()))
+}
+
+pub fn list(storage: &dyn StorageProvider) -> Result<Vec<TrashedProject>, String> {
+    let days = retention_days(storage)?;
+    let mut items = Vec::new();
+    for (id, deleted_at) in storage.list_trashed_projects().map_err(|e| e.to_string())? {
+        let project = storage.get_project(&id).map_err(|e| e.to_string())?;
+        if project.sync_status == "deleted" {
+            continue;
+        }
+        items.push(TrashedProject {
+            name: if project.name.is_empty() { id.clone() } else { project.name },
+            purge_at: purge_at(&deleted_at, days).map(|at| at.to_rfc3339()),
+            id,
+            deleted_at,
+        });
+    }
+    Ok(items)
+}
+
+pub fn trash(storage: &dyn StorageProvider, clock: &dyn Clock, id: &str) -> Result<(), String> {
+    let project = storage.get_project(id).map_err(|e| e.to_string())?;
+    if project.sync_status == "deleted" {
+        return Err(format!("Project not found: {id}"));
+    }
+    storage
+        .trash_project(id, &clock.now().to_rfc3339())
+        .map_err(|e| e.to_string())
+}
+
+pub fn restore(storage: &dyn StorageProvider, id: &str) -> Result<(), String> {
+    storage.restore_project(id).map_err(|e| e.to_string())
+}
+
+/// Delete a trashed project for good, with its saved custom password.
+pub fn purge(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+    id: &str,
+) -> Result<(), String> {
+    if !ids(storage)?.contains(id) {
+        return Err("Project is not in the trash".to_string());
+    }
+    let kc_key = password_registry::kc_key(id);
+    let had_custom_password = keychain::get(&kc_key).is_some();
+    keychain::remove(&kc_key);
+
+    let existing = storage.get_project(id).map_err(|e| e.to_string())?;
+    if existing.server_id.is_some() {
+        if existing.sync_status != "deleted" {
+            let mut tombstone = existing;
+            tombstone.sync_status = "deleted".to_string();
+            storage.update_project(&tombstone).map_err(|e| e.to_string())?;
+        }
+    } else {
+        storage.delete_project(id).map_err(|e| e.to_string())?;
+    }
+    let _ = cooling_off::forget(storage, id);
+
+    if had_custom_password {
+        let _ = password_registry::rebuild_registry(storage, key);
+    }
+    Ok(())
+}
+
+/// Purge every project that has been in the trash longer than the retention period.
+pub fn purge_expired(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<usize, String> {
+    let days = retention_days(storage)?;
+    let now = clock.now();
+    let mut purged = 0;
+    for item in list(storage)? {
+        if purge_at(&item.deleted_at, days).is_some_and(|at| at <= now) {
+            purge(storage, key, &item.id)?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+fn sweep(state: &AppState) -> Result<(), String> {
+    let cached = state.cached_key.lock().map_err(|e| e.to_string())?.with_key(crypto::locked::copy);
+    let Some(key) = cached else {
+        return Ok(());
+    };
+    let guard = state.storage.lock().map_err(|e| e.to_string());
+    let outcome = guard.and_then(|guard| {
+        let storage = guard.as_ref().ok_or("Database not initialized")?;
+        purge_expired(&**storage, &*state.clock, &key).map(|_| ())
+    });
+    outcome
+}
+
+/// Empty expired projects from the trash of the open vault once an hour.
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let _ = sweep(&app.state::<AppState>());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Project;
+    use crate::storage::local::LocalStorage;
+
+    fn project(storage: &LocalStorage, id: &str, server_id: Option<&str>) {
+        storage
+            .create_project(&Project {
+                id: id.to_string(),
+                name: id.to_string(),
+                encrypted_content: vec![1],
+                key_check: Vec::new(),
+                position: String::new(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                server_id: server_id.map(str::to_string),
+                sync_status: "synced".to_string(),
+                last_synced_at: None,
+                folder_id: None,
+                revision: 0,
+                pinned: false,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn trashed_projects_restore_or_expire() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [3u8; crypto::KEY_LEN];
+        project(&storage, "local", None);
+        project(&storage, "synced", Some("42"));
+        project(&storage, "kept", None);
+
+        trash(&storage, &SystemClock, "local").unwrap();
+        trash(&storage, &SystemClock, "kept").unwrap();
+        restore(&storage, "kept").unwrap();
+        assert!(restore(&storage, "kept").is_err());
+        assert_eq!(ids(&storage).unwrap(), HashSet::from(["local".to_string()]));
+
+        let long_ago = (Utc::now() - chrono::Duration::days(31)).to_rfc3339();
+        storage.trash_project("synced", &long_ago).unwrap();
+        assert_eq!(list(&storage).unwrap().len(), 2);
+
+        assert_eq!(purge_expired(&storage, &SystemClock, &key).unwrap(), 1);
+        assert_eq!(storage.get_project("synced").unwrap().sync_status, "deleted");
+        assert_eq!(list(&storage).unwrap().len(), 1);
+        assert_eq!(purge_expired(&storage, &SystemClock, &key).unwrap(), 0);
+
+        set_retention_days(&storage, 0).unwrap();
+        assert!(list(&storage).unwrap()[0].purge_at.is_none());
+        purge(&storage, &key, "local").unwrap();
+        assert!(storage.get_project("local").is_err());
+        assert!(purge(&storage, &key, "kept").is_err());
+    }
+}
@@ -0,0 +1,458 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::crypto::{self, challenge_response, signing, wrap, KdfParams};
+use crate::rehash;
+use crate::storage::StorageProvider;
+
+/// Layout version of a vault database, bumped on incompatible changes:
+/// 2. zstd-compressed blobs (format bytes 0x12 and 0x13), which older builds cannot open.
+/// 3. chunked blobs sealed under per-blob chunk keys (format bytes 0x72 and 0x73).
+pub const VAULT_FORMAT_VERSION: u32 = 3;
+
+pub const META_VAULT_ID: &str = "vault_id";
+pub const META_FORMAT_VERSION: &str = "format_version";
+/// Oldest format version that can still read this vault (read-only).
+pub const META_READ_COMPAT_VERSION: &str = "read_compat_version";
+pub const META_CREATED_AT: &str = "created_at";
+pub const META_KDF_PARAMS: &str = "kdf_params";
+pub const META_PASSWORD_SLOT: &str = "password_slot";
+pub const META_FIDO_KEYS: &str = "fido_keys";
+pub const META_CHALLENGE_RESPONSE: &str = "challenge_response";
+pub const META_RECOVERY_SLOT: &str = "recovery_slot";
+pub const META_BACKUP_PASSPHRASE_SLOT: &str = "backup_passphrase_slot";
+pub const META_SHAMIR_SLOT: &str = "shamir_slot";
+pub const META_DURESS_SLOT: &str = "duress_slot";
+/// Random per-vault nonce prefix and next free nonce counter, see `crypto::nonce`.
+pub const META_NONCE_PREFIX: &str = "nonce_prefix";
+pub const META_NONCE_COUNTER: &str = "nonce_counter";
+/// Ed25519 key signing this vault's backup files, encrypted with the vault key.
+pub const META_BACKUP_SIGNING_KEY: &str = "backup_signing_key";
+
+/// The data key wrapped under a FIDO2 security key's `hmac-secret` output for `salt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FidoKeySlot {
+    pub credential_id: String,
+    pub label: String,
+    pub salt: String,
+    pub wrapped_key: String,
+    pub created_at: String,
+}
+
+/// The data key wrapped under a recovery code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySlot {
+    pub slot: wrap::KeySlot,
+    pub created_at: String,
+}
+
+/// The data key wrapped under a random key that was split into recovery shares.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShamirSlot {
+    pub set_id: String,
+    pub threshold: u8,
+    pub shares: u8,
+    pub wrapped_key: String,
+    pub created_at: String,
+}
+
+/// Where the duress password leads: the decoy database path, encrypted under a random key
+/// that is wrapped under the duress password. Without that password the path is unreadable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuressSlot {
+    pub slot: wrap::KeySlot,
+    pub decoy_path: String,
+    pub created_at: String,
+}
+
+/// YubiKey challenge-response protecting the password slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeResponseConfig {
+    pub slot: u8,
+    pub challenge: String,
+    pub enabled_at: String,
+}
+
+/// Argon2 parameters of the vault; vaults created before they were configurable use the defaults.
+/// They are read from the vault file, so they are clamped to the range `KdfParams::validate`
+/// accepts.
+pub fn kdf_params(storage: &dyn StorageProvider) -> Result<KdfParams, String> {
+    match storage.get_meta(META_KDF_PARAMS).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json)
+            .map(KdfParams::clamped)
+            .map_err(|e| format!("Invalid KDF parameters: {e}")),
+        None => Ok(KdfParams::default()),
+    }
+}
+
+pub fn set_kdf_params(storage: &dyn StorageProvider, params: &KdfParams) -> Result<(), String> {
+    let json = serde_json::to_string(params).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_KDF_PARAMS, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Stamp a freshly created vault with its identity and format version.
+pub fn write_identity(storage: &dyn StorageProvider, created_at: &str) -> Result<String, String> {
+    let vault_id = uuid::Uuid::new_v4().to_string();
+    for (key, value) in [
+        (META_VAULT_ID, vault_id.as_str()),
+        (META_FORMAT_VERSION, &VAULT_FORMAT_VERSION.to_string()),
+        (META_READ_COMPAT_VERSION, &VAULT_FORMAT_VERSION.to_string()),
+        (META_CREATED_AT, created_at),
+    ] {
+        storage.set_meta(key, value).map_err(|e| e.to_string())?;
+    }
+    Ok(vault_id)
+}
+
+pub fn vault_id(storage: &dyn StorageProvider) -> Result<Option<String>, String> {
+    storage.get_meta(META_VAULT_ID).map_err(|e| e.to_string())
+}
+
+/// The vault's backup signing key, generated and stored on first use.
+pub fn backup_signing_key(
+    storage: &dyn StorageProvider,
+    key: &[u8; crypto::KEY_LEN],
+) -> Result<[u8; signing::SECRET_KEY_LEN], String> {
+    let aad = crypto::field_aad("vault", META_BACKUP_SIGNING_KEY);
+    if let Some(encoded) = storage
+        .get_meta(META_BACKUP_SIGNING_KEY)
+        .map_err(|e| e.to_string())?
+        .filter(|v| !v.is_empty())
+    {
+        let encrypted = B64.decode(encoded).map_err(|e| e.to_string())?;
+        let mut secret = crypto::try_decrypt_with_key_aad(&encrypted, key, &aad)
+            .ok_or("Backup signing key cannot be decrypted")?;
+        let result = secret
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Invalid backup signing key".to_string());
+        secret.zeroize();
+        return result;
+    }
+
+    let secret = signing::generate_secret_key();
+    let encrypted = crypto::encrypt_with_key_aad(&secret, key, &aad).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_BACKUP_SIGNING_KEY, &B64.encode(encrypted))
+        .map_err(|e| e.to_string())?;
+    Ok(secret)
+}
+
+fn password_slot(storage: &dyn StorageProvider) -> Result<Option<wrap::KeySlot>, String> {
+    match storage.get_meta(META_PASSWORD_SLOT).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid password slot: {e}")),
+        None => Ok(None),
+    }
+}
+
+fn set_password_slot(storage: &dyn StorageProvider, slot: &wrap::KeySlot) -> Result<(), String> {
+    let json = serde_json::to_string(slot).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_PASSWORD_SLOT, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Set up the keys of a new vault: a random data key wrapped under the master password, plus
+/// the verification token. Returns the data key.
+pub fn initialize(
+    storage: &dyn StorageProvider,
+    password: &str,
+) -> Result<[u8; crypto::KEY_LEN], String> {
+    let dek = wrap::generate_key();
+    let slot = wrap::wrap_with_secret(&dek, password, &kdf_params(storage)?)
+        .map_err(|e| e.to_string())?;
+    set_password_slot(storage, &slot)?;
+
+    let token = crypto::create_verification_token(password).map_err(|e| e.to_string())?;
+    storage
+        .set_verification_token(&token)
+        .map_err(|e| e.to_string())?;
+    Ok(dek)
+}
+
+/// Unwrap the vault's data key with the master password; `Ok(None)` means a wrong password.
+/// Vaults created before key wrapping encrypted everything with the password-derived key, so
+/// that key becomes their data key and is wrapped on the first unlock. Leftover V1 content is
+/// upgraded while the password is available. With challenge-response, `password` is the
+/// mixed secret from `unlock_secret`.
+pub fn unlock(
+    storage: &dyn StorageProvider,
+    password: &str,
+) -> Result<Option<[u8; crypto::KEY_LEN]>, String> {
+    let token = storage
+        .get_verification_token()
+        .map_err(|e| e.to_string())?
+        .ok_or("No master password set")?;
+    if !crypto::verify_password(&token, password) {
+        return Ok(None);
+    }
+
+    let dek = match password_slot(storage)? {
+        Some(slot) => wrap::unwrap_with_secret(&slot, password).map_err(|e| e.to_string())?,
+        None => {
+            let params = kdf_params(storage)?;
+            let dek = crypto::derive_master_key_with_params(password, &params)
+                .map_err(|e| e.to_string())?;
+            let slot =
+                wrap::wrap_with_secret(&dek, password, &params).map_err(|e| e.to_string())?;
+            set_password_slot(storage, &slot)?;
+            dek
+        }
+    };
+
+    rehash::upgrade_all(storage, &dek, password, true);
+    Ok(Some(dek))
+}
+
+/// Like `unlock`, but without writing anything: no slot is created for an old vault and no
+/// content is upgraded. For checks that must leave the vault as it is.
+pub fn peek_data_key(
+    storage: &dyn StorageProvider,
+    password: &str,
+) -> Result<Option<[u8; crypto::KEY_LEN]>, String> {
+    let token = storage
+        .get_verification_token()
+        .map_err(|e| e.to_string())?
+        .ok_or("No master password set")?;
+    if !crypto::verify_password(&token, password) {
+        return Ok(None);
+    }
+    match password_slot(storage)? {
+        Some(slot) => wrap::unwrap_with_secret(&slot, password)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => crypto::derive_master_key_with_params(password, &kdf_params(storage)?)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Wrap the data key under a new password and/or KDF parameters. The content itself is not
+/// touched. The slot, parameters and verification token are replaced together, so a failure
+/// cannot leave a slot the stored parameters or token do not match.
+pub fn rewrap(
+    storage: &dyn StorageProvider,
+    dek: &[u8; crypto::KEY_LEN],
+    password: &str,
+    params: &KdfParams,
+) -> Result<(), String> {
+    let slot = wrap::wrap_with_secret(dek, password, params).map_err(|e| e.to_string())?;
+    let slot = serde_json::to_string(&slot).map_err(|e| e.to_string())?;
+    let params = serde_json::to_string(params).map_err(|e| e.to_string())?;
+    let token = crypto::create_verification_token(password).map_err(|e| e.to_string())?;
+    storage
+        .replace_unlock_meta(&[(META_PASSWORD_SLOT, &slot), (META_KDF_PARAMS, &params)], &token)
+        .map_err(|e| e.to_string())
+}
+
+pub fn fido_keys(storage: &dyn StorageProvider) -> Result<Vec<FidoKeySlot>, String> {
+    match storage.get_meta(META_FIDO_KEYS).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid FIDO keys: {e}")),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub fn set_fido_keys(storage: &dyn StorageProvider, keys: &[FidoKeySlot]) -> Result<(), String> {
+    let json = serde_json::to_string(keys).map_err(|e| e.to_string())?;
+    storage
+        .set_meta(META_FIDO_KEYS, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn challenge_response_config(
+    storage: &dyn StorageProvider,
+) -> Result<Option<ChallengeResponseConfig>, String> {
+    match storage
+        .get_meta(META_CHALLENGE_RESPONSE)
+        .map_err(|e| e.to_string())?
+    {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid challenge-response settings: {e}")),
+        _ => Ok(None),
+    }
+}
+
+/// Store the challenge-response settings; `None` turns it off.
+pub fn set_challenge_response_config(
+    storage: &dyn StorageProvider,
+    config: Option<&ChallengeResponseConfig>,
+) -> Result<(), String> {
+    let json = match config {
+        Some(config) => serde_json::to_string(config).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    storage
+        .set_meta(META_CHALLENGE_RESPONSE, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// Ask the YubiKey for its response to the vault's challenge. `None` when the vault does not
+/// use challenge-response.
+pub fn challenge_response(
+    storage: &dyn StorageProvider,
+) -> Result<Option<Zeroizing<[u8; challenge_response::RESPONSE_LEN]>>, String> {
+    match challenge_response_config(storage)? {
+        Some(config) => respond(&config).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Ask the YubiKey for its response to the challenge in `config`. This waits for a touch, so
+/// callers should not hold the storage lock meanwhile.
+pub fn respond(
+    config: &ChallengeResponseConfig,
+) -> Result<Zeroizing<[u8; challenge_response::RESPONSE_LEN]>, String> {
+    let challenge = B64
+        .decode(&config.challenge)
+        .map_err(|e| format!("Invalid challenge: {e}"))?;
+    challenge_response::respond(config.slot, &challenge).map_err(|e| e.to_string())
+}
+
+/// The secret the password slot is wrapped under: the master password itself, or the password
+/// mixed with the YubiKey response when challenge-response is on.
+pub fn password_secret(password: &str, response: Option<&[u8]>) -> Zeroizing<String> {
+    match response {
+        Some(response) => challenge_response::mix(password, response),
+        None => Zeroizing::new(password.to_string()),
+    }
+}
+
+/// `password_secret` for the vault's current configuration, querying the YubiKey if needed.
+pub fn unlock_secret(
+    storage: &dyn StorageProvider,
+    password: &str,
+) -> Result<Zeroizing<String>, String> {
+    let response = challenge_response(storage)?;
+    Ok(password_secret(password, response.as_ref().map(|r| &r[..])))
+}
+
+pub fn recovery_slot(storage: &dyn StorageProvider) -> Result<Option<RecoverySlot>, String> {
+    match storage.get_meta(META_RECOVERY_SLOT).map_err(|e| e.to_string())? {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid recovery slot: {e}")),
+        _ => Ok(None),
+    }
+}
+
+pub fn set_recovery_slot(
+    storage: &dyn StorageProvider,
+    slot: Option<&RecoverySlot>,
+) -> Result<(), String> {
+    let json = match slot {
+        Some(slot) => serde_json::to_string(slot).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    storage
+        .set_meta(META_RECOVERY_SLOT, &json)
+        .map_err(|e| e.to_string())
+}
+
+/// The data key wrapped under the backup passphrase, copied into backups that use it. It has
+/// the same shape as the recovery slot.
+pub fn backup_passphrase_slot(
+    storage: &dyn StorageProvider,
+) -> Result<Option<RecoverySlot>, String> {
+    match storage
+        .get_meta(META_BACKUP_PASSPHRASE_SLOT)
+        .map_err(|e| e.to_string())?
+    {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid backup passphrase slot: {e}")),
+        _ => Ok(None),
+    }
+}
+
+pub fn set_backup_passphrase_slot(
+    storage: &dyn StorageProvider,
+    slot: Option<&RecoverySlot>,
+) -> Result<(), String> {
+    let json = match slot {
+        Some(slot) => serde_json::to_string(slot).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    storage
+        .set_meta(META_BACKUP_PASSPHRASE_SLOT, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn shamir_slot(storage: &dyn StorageProvider) -> Result<Option<ShamirSlot>, String> {
+    match storage.get_meta(META_SHAMIR_SLOT).map_err(|e| e.to_string())? {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid recovery share slot: {e}")),
+        _ => Ok(None),
+    }
+}
+
+pub fn set_shamir_slot(
+    storage: &dyn StorageProvider,
+    slot: Option<&ShamirSlot>,
+) -> Result<(), String> {
+    let json = match slot {
+        Some(slot) => serde_json::to_string(slot).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    storage
+        .set_meta(META_SHAMIR_SLOT, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn duress_slot(storage: &dyn StorageProvider) -> Result<Option<DuressSlot>, String> {
+    match storage.get_meta(META_DURESS_SLOT).map_err(|e| e.to_string())? {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid duress slot: {e}")),
+        _ => Ok(None),
+    }
+}
+
+pub fn set_duress_slot(
+    storage: &dyn StorageProvider,
+    slot: Option<&DuressSlot>,
+) -> Result<(), String> {
+    let json = match slot {
+        Some(slot) => serde_json::to_string(slot).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    storage
+        .set_meta(META_DURESS_SLOT, &json)
+        .map_err(|e| e.to_string())
+}
+
+pub fn seal_duress(
+    decoy_path: &str,
+    password: &str,
+    params: &KdfParams,
+) -> Result<DuressSlot, String> {
+    let mut path_key = wrap::generate_key();
+    let sealed = crypto::encrypt_with_key(decoy_path.as_bytes(), &path_key);
+    let slot = wrap::wrap_with_secret(&path_key, password, params);
+    path_key.zeroize();
+    Ok(DuressSlot {
+        slot: slot.map_err(|e| e.to_string())?,
+        decoy_path: B64.encode(sealed.map_err(|e| e.to_string())?),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// The decoy database path if `password` is the duress password.
+pub fn open_duress(slot: &DuressSlot, password: &str) -> Option<String> {
+    let mut path_key = wrap::unwrap_with_secret(&slot.slot, password).ok()?;
+    let path = B64
+        .decode(&slot.decoy_path)
+        .ok()
+        .and_then(|sealed| crypto::try_decrypt_with_key(&sealed, &path_key));
+    path_key.zeroize();
+    path.and_then(|p| String::from_utf8(p).ok())
+}
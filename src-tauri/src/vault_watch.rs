@@ -0,0 +1,192 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Notices when the open vault's file is replaced under the app, as cloud-sync clients like
+//! Dropbox do: they write a new file and rename it over the old one. The open connection keeps
+//! reading the old, unlinked file, so lists go stale and writes are lost. Writes by another
+//! process to the same file are already seen through the change log (see `change_feed`).
+//!
+//! The watcher polls the file's identity (its inode, or its creation time where there are no
+//! inodes). When that changes, the vault is reopened on the new file the way an unlock opens
+//! it, with its key if unlocked, cached plaintext is dropped and `vault-changed-externally` is
+//! emitted so the frontend reloads everything. The new file is only adopted when it holds the
+//! same vault and its change log is not behind the open one's: a sync conflict or an older copy
+//! put back would otherwise replace the vault or undo what was written since.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::settings::{
+    install_nonce_counter, open_unlocked, require_bound_content, unseal_cached,
+};
+use crate::crypto;
+use crate::ephemeral;
+use crate::storage::local::LocalStorage;
+use crate::storage::StorageProvider;
+use crate::vault;
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultChangedExternally {
+    pub db_path: String,
+    /// Whether the vault was reopened on the new file. When not, the old file stays open and
+    /// the new one is left alone; it may be a sync conflict or not a vault at all.
+    pub reopened: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    inode: u64,
+    created: Option<SystemTime>,
+}
+
+fn identity(path: &Path) -> Option<FileIdentity> {
+    let metadata = std::fs::metadata(path).ok()?;
+    #[cfg(unix)]
+    let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+    #[cfg(not(unix))]
+    let inode = 0;
+    Some(FileIdentity { inode, created: metadata.created().ok() })
+}
+
+/// Why `incoming` may not replace `current`, if it may not: it is another vault, or its change
+/// log is behind. The logs are only compared where both can be read, i.e. while unlocked.
+fn refuse_replacement(
+    current: &dyn StorageProvider,
+    incoming: &dyn StorageProvider,
+) -> Result<(), String> {
+    if vault::vault_id(incoming)? != vault::vault_id(current)? {
+        return Err("The new file holds a different vault".to_string());
+    }
+    if let (Ok(current), Ok(incoming)) = (current.change_seq(), incoming.change_seq()) {
+        if incoming < current {
+            return Err("The new file is older than the open vault".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Reopen the vault at `db_path` on the file now at that path, once it is checked read-only.
+/// Holds the storage throughout so no command runs against the old file meanwhile.
+fn reopen(state: &AppState, db_path: &str) -> Result<(), String> {
+    let mut guard = state.storage.lock().map_err(|e| e.to_string())?;
+    let Some(current) = guard.as_ref().filter(|storage| !storage.is_read_only()) else {
+        return Err("No writable vault is open".to_string());
+    };
+    let incoming = LocalStorage::open_read_only(db_path).map_err(|e| e.to_string())?;
+    unseal_cached(state, &incoming)?;
+    refuse_replacement(&**current, &incoming)?;
+    drop(incoming);
+
+    *guard = Some(Box::new(open_unlocked(state, db_path)?));
+    Ok(())
+}
+
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut watched: Option<(String, FileIdentity)> = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let state = app.state::<AppState>();
+            let Some(db_path) = state.db_path.lock().ok().and_then(|p| p.clone()) else {
+                watched = None;
+                continue;
+            };
+            // A file that is gone for now (mid-rename, or moved away) is compared once it is back.
+            let Some(current) = identity(Path::new(&db_path)) else {
+                continue;
+            };
+            match watched.replace((db_path.clone(), current)) {
+                Some((path, previous)) if path == db_path && previous != current => {}
+                // Unchanged, or a different vault became active and is watched from here.
+                _ => continue,
+            }
+
+            let reopened = reopen(&state, &db_path).is_ok();
+            if reopened {
+                ephemeral::clear();
+                let key = state.cached_key.lock().ok();
+                let key = key.and_then(|k| k.with_key(crypto::locked::copy));
+                if let Some(key) = key {
+                    install_nonce_counter(&state, &key);
+                    require_bound_content(&state, &key);
+                }
+            }
+            let changed = VaultChangedExternally { db_path, reopened };
+            let _ = app.emit("vault-changed-externally", changed);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+
+    fn vault_with_id(vault_id: &str) -> LocalStorage {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        storage.set_meta(vault::META_VAULT_ID, vault_id).unwrap();
+        storage
+    }
+
+    fn add_project(storage: &LocalStorage, id: &str) {
+        let project = Project {
+            id: id.to_string(),
+            name: String::new(),
+            encrypted_content: vec![1],
+            key_check: Vec::new(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        };
+        storage.create_project(&project).unwrap();
+    }
+
+    #[test]
+    fn only_the_same_vault_at_least_as_new_replaces_the_open_one() {
+        let current = vault_with_id("vault-a");
+        add_project(&current, "p1");
+
+        let synced = vault_with_id("vault-a");
+        add_project(&synced, "p1");
+        add_project(&synced, "p2");
+        assert!(refuse_replacement(&current, &synced).is_ok());
+
+        let older = vault_with_id("vault-a");
+        assert!(refuse_replacement(&current, &older).is_err());
+
+        let other = vault_with_id("vault-b");
+        add_project(&other, "p1");
+        add_project(&other, "p2");
+        assert!(refuse_replacement(&current, &other).is_err());
+    }
+
+    #[test]
+    fn replacing_the_file_changes_its_identity() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("vault.db");
+        std::fs::write(&db_path, b"first").unwrap();
+        let before = identity(&db_path).unwrap();
+
+        std::fs::write(&db_path, b"rewritten in place").unwrap();
+        assert_eq!(identity(&db_path), Some(before));
+
+        let incoming = dir.join(".vault.db.sync-tmp");
+        std::fs::write(&incoming, b"from the cloud").unwrap();
+        std::fs::rename(&incoming, &db_path).unwrap();
+        assert_ne!(identity(&db_path), Some(before));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Vaults kept open in the background. The active vault stays in `AppState`'s `storage`,
+//! `db_path` and `cached_key`, which is what every command works on; switching swaps it with
+//! one parked here. Each parked vault keeps its own key, so switching back does not ask for the
+//! password again. The keychain session still belongs to one vault: the one unlocked last.
+
+use std::collections::HashMap;
+
+use crate::crypto::locked::LockedKey;
+use crate::storage::StorageProvider;
+
+pub struct ParkedVault {
+    pub storage: Box<dyn StorageProvider>,
+    pub key: LockedKey,
+}
+
+#[derive(Default)]
+pub struct VaultRegistry {
+    parked: HashMap<String, ParkedVault>,
+}
+
+impl VaultRegistry {
+    pub fn park(&mut self, db_path: String, vault: ParkedVault) {
+        self.parked.insert(db_path, vault);
+    }
+
+    pub fn take(&mut self, db_path: &str) -> Option<ParkedVault> {
+        self.parked.remove(db_path)
+    }
+
+    /// Close a parked vault; its key is zeroed as it is dropped. Returns whether it was open.
+    pub fn close(&mut self, db_path: &str) -> bool {
+        self.parked.remove(db_path).is_some()
+    }
+
+    /// Forget the keys of all parked vaults, leaving them open but locked.
+    pub fn lock_all(&mut self) {
+        for vault in self.parked.values_mut() {
+            vault.key.clear();
+        }
+    }
+
+    /// Close every parked vault, zeroing their keys.
+    pub fn close_all(&mut self) {
+        self.lock_all();
+        self.parked.clear();
+    }
+
+    /// Paths of the parked vaults, each with whether it is unlocked.
+    pub fn list(&self) -> Vec<(String, bool)> {
+        self.parked
+            .iter()
+            .map(|(path, vault)| (path.clone(), vault.key.is_some()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+
+    #[test]
+    fn parked_vaults_keep_their_own_keys_until_locked() {
+        let mut registry = VaultRegistry::default();
+        for (path, key) in [("work.db", Some([1u8; 32])), ("home.db", None)] {
+            let mut locked = LockedKey::new();
+            if let Some(key) = key {
+                locked.set(key);
+            }
+            registry.park(
+                path.to_string(),
+                ParkedVault { storage: Box::new(LocalStorage::new(":memory:").unwrap()), key: locked },
+            );
+        }
+        let mut listed = registry.list();
+        listed.sort();
+        assert_eq!(listed, [("home.db".to_string(), false), ("work.db".to_string(), true)]);
+
+        registry.lock_all();
+        assert!(registry.list().iter().all(|(_, unlocked)| !unlocked));
+        assert!(registry.take("work.db").is_some());
+        assert!(!registry.close("work.db"));
+        assert!(registry.close("home.db"));
+
+        let storage = Box::new(LocalStorage::new(":memory:").unwrap());
+        registry.park("work.db".to_string(), ParkedVault { storage, key: LockedKey::new() });
+        registry.close_all();
+        assert!(registry.list().is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+//! Version history. Every saved edit keeps the revision it replaces in `project_versions`,
+//! encrypted exactly as it was, so an overwritten note can be brought back. Unlike the backups,
+//! which only catch large changes, every revision is kept; the retention policy bounds the
+//! history per project by count and, optionally, by age.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::crypto;
+use crate::models::{Project, ProjectVersion};
+use crate::storage::StorageProvider;
+
+const SETTING_VERSION_RETENTION: &str = "version_retention";
+const MAX_VERSIONS_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRetention {
+    /// Versions kept per project.
+    pub max_versions: u32,
+    /// Versions older than this are dropped; 0 keeps them regardless of age.
+    pub max_age_days: u32,
+}
+
+impl Default for VersionRetention {
+    fn default() -> Self {
+        Self {
+            max_versions: 50,
+            max_age_days: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub id: String,
+    pub name: String,
+    pub updated_at: String,
+    pub saved_at: String,
+    /// Size of the encrypted revision in bytes.
+    pub size: usize,
+}
+
+pub fn retention(storage: &dyn StorageProvider) -> Result<VersionRetention, String> {
+    match storage
+        .get_setting(SETTING_VERSION_RETENTION)
+        .map_err(|e| e.to_string())?
+    {
+        Some(json) if !json.is_empty() => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid version retention settings: {e}")),
+        _ => Ok(VersionRetention::default()),
+    }
+}
+
+/// Save the policy and apply it to the existing history right away.
+pub fn set_retention(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    policy: VersionRetention,
+) -> Result<(), String> {
+    if policy.max_versions == 0 || policy.max_versions > MAX_VERSIONS_LIMIT {
+        return Err(format!("Keep between 1 and {MAX_VERSIONS_LIMIT} versions"));
+    }
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    storage
+        .set_setting(SETTING_VERSION_RETENTION, &json)
+        .map_err(|e| e.to_string())?;
+    apply_retention(storage, clock, None)
+}
+
+/// Prune the history of `project_id`, or of every project, to the saved policy.
+pub fn apply_retention(
+    storage: &dyn StorageProvider,
+    clock: &dyn Clock,
+    project_id: Option<&str>,
+) -> Result<(), String> {
+    let policy = retention(storage)?;
+    let saved_before = (policy.max_age_days > 0)
+        .then(|| (clock.now() - chrono::Duration::days(policy.max_age_days.into())).to_rfc3339());
+    storage
+        .prune_versions(project_id, policy.max_versions as usize, saved_before.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Keep `existing` as a version before it is overwritten.
+pub fn record(storage: &dyn StorageProvider, clock: &dyn Clock, existing: &Project) -> Result<(), String> {
+    storage
+        .create_version(&ProjectVersion {
+            id: Uuid::new_v4().to_string(),
+            project_id: existing.id.clone(),
+            name: existing.name.clone(),
+            encrypted_content: existing.encrypted_content.clone(),
+            key_check: existing.key_check.clone(),
+            updated_at: existing.updated_at.clone(),
+            saved_at: clock.now().to_rfc3339(),
+        })
+        .map_err(|e| e.to_string())?;
+    apply_retention(storage, clock, Some(&existing.id))
+}
+
+pub fn list(storage: &dyn StorageProvider, project_id: &str) -> Result<Vec<VersionInfo>, String> {
+    Ok(storage
+        .list_versions(project_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|v| VersionInfo {
+            size: v.encrypted_content.len(),
+            id: v.id,
+            name: v.name,
+            updated_at: v.updated_at,
+            saved_at: v.saved_at,
+        })
+        .collect())
+}
+
+/// Decrypt a version of a master-key project with the vault key; `portable` if the project is
+/// synced.
+pub fn open_content(
+    version: &ProjectVersion,
+    key: &[u8; crypto::KEY_LEN],
+    portable: bool,
+) -> Result<Vec<u8>, String> {
+    let (id, name) = (&version.project_id, &version.name);
+    crypto::content::decrypt(&version.encrypted_content, key, id, name, portable)
+        .map_err(|e| e.to_string())
+}
+
+/// Make a version the project's current revision. The revision it replaces is kept as a
+/// version too, so a restore can be undone.
+pub fn restore(storage: &dyn StorageProvider, clock: &dyn Clock, version_id: &str) -> Result<(), String> {
+    let version = storage.get_version(version_id).map_err(|e| e.to_string())?;
+    let mut project = storage.get_project(&version.project_id).map_err(|e| e.to_string())?;
+    record(storage, clock, &project)?;
+
+    project.name = version.name;
+    project.encrypted_content = version.encrypted_content;
+    project.key_check = version.key_check;
+    project.updated_at = clock.now().to_rfc3339();
+    if project.sync_status == "synced" {
+        project.sync_status = "modified".to_string();
+    }
+    storage.update_project(&project).map_err(|e| e.to_string())
+}
+
+/// Re-encrypt every version under the vault key with `new_key`, for a master password change.
+/// Versions written under a custom password are left as they are.
+pub fn reencrypt(
+    storage: &dyn StorageProvider,
+    old_key: &[u8; crypto::KEY_LEN],
+    new_key: &[u8; crypto::KEY_LEN],
+) -> Result<(), String> {
+    for project in storage.list_projects().map_err(|e| e.to_string())? {
+        for mut version in storage.list_versions(&project.id).map_err(|e| e.to_string())? {
+            if version.key_check.is_empty()
+                || crypto::try_decrypt_with_key(&version.key_check, old_key).is_none()
+            {
+                continue;
+            }
+            let content = open_content(&version, old_key, project.server_id.is_some())?;
+            let bind = crypto::is_aad_bound(&version.encrypted_content);
+            let (id, name) = (&version.project_id, &version.name);
+            version.encrypted_content = crypto::content::encrypt(&content, new_key, id, name, bind)
+                .map_err(|e| e.to_string())?;
+            version.key_check = crypto::encrypt_with_key(b"mk", new_key).map_err(|e| e.to_string())?;
+            storage.update_version(&version).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::storage::local::LocalStorage;
+
+    fn revision(key: &[u8; crypto::KEY_LEN], text: &str) -> Project {
+        Project {
+            id: "p".to_string(),
+            name: text.to_string(),
+            encrypted_content: crypto::encrypt_with_key(text.as_bytes(), key).unwrap(),
+            key_check: crypto::encrypt_with_key(b"mk", key).unwrap(),
+            position: String::new(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: SystemClock.now().to_rfc3339(),
+            server_id: None,
+            sync_status: "local".to_string(),
+            last_synced_at: None,
+            folder_id: None,
+            revision: 0,
+            pinned: false,
+        }
+    }
+
+    fn save(storage: &LocalStorage, key: &[u8; crypto::KEY_LEN], text: &str) {
+        record(storage, &SystemClock, &storage.get_project("p").unwrap()).unwrap();
+        storage.update_project(&revision(key, text)).unwrap();
+    }
+
+    #[test]
+    fn history_is_bounded_and_restorable() {
+        let storage = LocalStorage::new(":memory:").unwrap();
+        let key = [9u8; crypto::KEY_LEN];
+        storage.create_project(&revision(&key, "v1")).unwrap();
+        set_retention(&storage, &SystemClock, VersionRetention { max_versions: 2, max_age_days: 0 }).unwrap();
+        for text in ["v2", "v3", "v4"] {
+            save(&storage, &key, text);
+        }
+
+        let history = list(&storage, "p").unwrap();
+        assert_eq!(history.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["v3", "v2"]);
+
+        let new_key = [8u8; crypto::KEY_LEN];
+        reencrypt(&storage, &key, &new_key).unwrap();
+        let oldest = storage.get_version(&history[1].id).unwrap();
+        assert_eq!(open_content(&oldest, &new_key, false).unwrap(), b"v2");
+
+        restore(&storage, &SystemClock, &history[1].id).unwrap();
+        assert_eq!(storage.get_project("p").unwrap().name, "v2");
+        let history = list(&storage, "p").unwrap();
+        assert_eq!(history.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["v4", "v3"]);
+    }
+}
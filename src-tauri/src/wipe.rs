@@ -0,0 +1,246 @@
+// Copyright (c) 2026 Pavel <mr.terks@yandex.ru>
+// Licensed under the PolyForm Noncommercial License 1.0.0
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::clock::Clock;
+use crate::crypto::rng;
+use crate::display_info;
+use crate::keychain;
+use crate::server_config;
+use crate::storage::cipher::header_path;
+use crate::storage::lock::lock_path;
+use crate::storage::shard::shard_path;
+use crate::AppState;
+
+const SQLITE_SIDE_FILES: [&str; 3] = ["-wal", "-shm", "-journal"];
+const BACKUP_FILE_EXT: &str = ".bak";
+const PANIC_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// The last confirmation token handed out for a panic wipe, and when.
+static PANIC_TOKEN: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Erasure {
+    /// Contents were overwritten with random bytes, then the file was unlinked.
+    Overwritten,
+    /// The filesystem refused the overwrite; the file was only unlinked.
+    Unlinked,
+    Missing,
+}
+
+fn overwrite(path: &Path, len: u64) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut chunk = vec![0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(chunk.len() as u64) as usize;
+        rng::fill(&mut chunk[..n]);
+        file.write_all(&chunk[..n])?;
+        remaining -= n as u64;
+    }
+    file.sync_all()
+}
+
+/// Overwrite a file with random bytes where the filesystem allows it, then unlink it.
+pub fn erase_file(path: &Path) -> std::io::Result<Erasure> {
+    let len = match std::fs::metadata(path) {
+        Ok(m) => m.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Erasure::Missing),
+        Err(e) => return Err(e),
+    };
+
+    let erasure = match overwrite(path, len) {
+        Ok(()) => Erasure::Overwritten,
+        Err(_) => Erasure::Unlinked,
+    };
+    std::fs::remove_file(path)?;
+    Ok(erasure)
+}
+
+/// The SQLite database, its attachment shard, the WAL/SHM/journal companions of both, and
+/// the unlock sidecar of an encrypted database.
+pub fn database_files(db_path: &str) -> Vec<PathBuf> {
+    let shard = shard_path(db_path).to_string_lossy().to_string();
+    let mut files = Vec::new();
+    for db in [db_path, shard.as_str()] {
+        files.push(PathBuf::from(db));
+        files.extend(SQLITE_SIDE_FILES.iter().map(|s| PathBuf::from(format!("{db}{s}"))));
+    }
+    files.push(header_path(db_path));
+    files
+}
+
+/// Backup copies kept next to the database: `<db file name>.bak`, or `<db file name>.<label>.bak`
+/// for a labelled one. Files that only start with the same name, such as `vault.db2.bak` next
+/// to `vault.db`, belong to another vault and are left alone.
+pub fn backup_files(db_path: &str) -> Vec<PathBuf> {
+    let path = Path::new(db_path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let name = name.to_string_lossy().to_string();
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let is_backup = |file: &str| {
+        file.strip_prefix(&name)
+            .and_then(|rest| rest.strip_suffix(BACKUP_FILE_EXT))
+            .is_some_and(|label| label.is_empty() || label.starts_with('.'))
+    };
+
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.file_name().is_some_and(|f| is_backup(&f.to_string_lossy())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn erase_database(db_path: &str) -> Result<(), String> {
+    for file in database_files(db_path) {
+        erase_file(&file).map_err(|e| format!("Failed to wipe {}: {e}", file.display()))?;
+    }
+    // Empty, so there is nothing to overwrite.
+    let _ = std::fs::remove_file(lock_path(db_path));
+    Ok(())
+}
+
+/// Forget keys and close the open vault, and the vaults parked in the background. Returns the
+/// database path that was active, if any.
+pub fn reset_state(state: &AppState) -> Result<Option<String>, String> {
+    {
+        let mut cached = state.cached_key.lock().map_err(|e| e.to_string())?;
+        cached.clear();
+    }
+    state.vaults.lock().map_err(|e| e.to_string())?.close_all();
+    crate::crypto::nonce::uninstall();
+    crate::ephemeral::clear();
+    {
+        // Dropping the provider closes the SQLite connection so the files can be overwritten.
+        let mut storage = state.storage.lock().map_err(|e| e.to_string())?;
+        *storage = None;
+    }
+    *state.server_token.lock().map_err(|e| e.to_string())? = None;
+    *state.server_url.lock().map_err(|e| e.to_string())? = None;
+    *state.active_context.lock().map_err(|e| e.to_string())? = "local".to_string();
+
+    let db_path = state.db_path.lock().map_err(|e| e.to_string())?.take();
+    Ok(db_path)
+}
+
+/// Every vault database this installation knows of: the last local one, every other local vault
+/// it opened or keeps open in the background, and the per-server caches.
+fn known_databases(state: &AppState) -> Result<Vec<String>, String> {
+    let mut db_paths: Vec<String> = Vec::new();
+    db_paths.extend(keychain::get("db-path"));
+    db_paths.extend(display_info::known().into_iter().map(|info| info.db_path));
+    let parked = state.vaults.lock().map_err(|e| e.to_string())?.list();
+    db_paths.extend(parked.into_iter().map(|(path, _)| path));
+    db_paths.extend(server_config::list_servers().into_iter().map(|s| s.db_path));
+    Ok(db_paths)
+}
+
+/// Erase everything this installation holds: in-memory keys, every vault database
+/// (`known_databases`) and all keychain entries.
+pub fn wipe_local_data(state: &AppState) -> Result<(), String> {
+    let mut db_paths = known_databases(state)?;
+    db_paths.extend(reset_state(state)?);
+
+    db_paths.sort();
+    db_paths.dedup();
+    for path in &db_paths {
+        erase_database(path)?;
+    }
+
+    keychain::clear()
+}
+
+#[derive(Debug, Serialize)]
+pub struct PanicWipeReport {
+    pub keychain_cleared: bool,
+    /// Databases overwritten and removed; empty unless a valid confirmation token was given.
+    pub databases_erased: Vec<String>,
+    pub token_rejected: bool,
+}
+
+/// A one-time token, valid for a minute, that `panic_wipe` requires before it erases databases.
+pub fn issue_panic_token(clock: &dyn Clock) -> String {
+    let mut bytes = [0u8; 4];
+    rng::fill(&mut bytes);
+    let token = hex::encode_upper(bytes);
+    *PANIC_TOKEN.lock().unwrap_or_else(|e| e.into_inner()) = Some((token.clone(), clock.instant()));
+    token
+}
+
+fn take_panic_token(clock: &dyn Clock, token: &str) -> bool {
+    PANIC_TOKEN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .is_some_and(|(issued, at)| {
+            clock.instant().saturating_duration_since(at) <= PANIC_TOKEN_TTL && issued.eq_ignore_ascii_case(token.trim())
+        })
+}
+
+/// Kill switch. Keys are always dropped from memory and the keychain is erased (its file
+/// overwritten first); with a valid `confirmation_token` every vault database is overwritten
+/// and removed as well. A wrong token does not stop the rest of the wipe.
+pub fn panic_wipe(
+    state: &AppState,
+    confirmation_token: Option<&str>,
+) -> Result<PanicWipeReport, String> {
+    let mut db_paths = known_databases(state)?;
+    db_paths.extend(reset_state(state)?);
+    db_paths.sort();
+    db_paths.dedup();
+
+    if let Some(path) = keychain::file::path() {
+        let _ = erase_file(&path);
+    }
+    let keychain_cleared = keychain::clear().is_ok();
+
+    let confirmed = confirmation_token.map(|token| take_panic_token(&*state.clock, token));
+    let mut databases_erased = Vec::new();
+    if confirmed == Some(true) {
+        for path in db_paths {
+            erase_database(&path)?;
+            databases_erased.push(path);
+        }
+    }
+    Ok(PanicWipeReport {
+        keychain_cleared,
+        databases_erased,
+        token_rejected: confirmed == Some(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_of_other_vaults_are_not_matched() {
+        let dir = std::env::temp_dir().join(format!("vaultpad-wipe-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["vault.db.bak", "vault.db.2026-01-01.bak", "vault.db2.bak", "vault.dbbak"] {
+            std::fs::write(dir.join(name), b"copy").unwrap();
+        }
+
+        let mut found: Vec<_> = backup_files(&dir.join("vault.db").to_string_lossy())
+            .into_iter()
+            .filter_map(|p| p.file_name().map(|f| f.to_string_lossy().to_string()))
+            .collect();
+        found.sort();
+        assert_eq!(found, ["vault.db.2026-01-01.bak", "vault.db.bak"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -22,7 +22,7 @@ function App() {
   const setView = useAppStore((s) => s.setView);
   const setDbPath = useAppStore((s) => s.setDbPath);
   const setDbFolder = useAppStore((s) => s.setDbFolder);
-  const setMasterPassword = useAppStore((s) => s.setMasterPassword);
+  const setUnlocked = useAppStore((s) => s.setUnlocked);
   const setHasSavedSession = useAppStore((s) => s.setHasSavedSession);
   const setHasPinCode = useAppStore((s) => s.setHasPinCode);
   const touchActivity = useAppStore((s) => s.touchActivity);
@@ -127,13 +127,11 @@ function App() {
         if (pinExists) {
           setView("pin-unlock");
         } else {
-          const mp = await tauri.getSavedMasterPassword();
+          const restored = await tauri.restoreSavedSession();
           if (cancelled) return;
 
-          if (mp) {
-            await tauri.cacheMasterKey(mp);
-            if (cancelled) return;
-            setMasterPassword(mp);
+          if (restored) {
+            setUnlocked(true);
             touchActivity();
             setView("main");
           } else {
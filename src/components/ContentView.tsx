@@ -29,7 +29,6 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
   const tauri = useTauri();
   const openProject = useAppStore((s) => s.openProject);
   const setOpenProject = useAppStore((s) => s.setOpenProject);
-  const masterPassword = useAppStore((s) => s.masterPassword);
   const fontSize = useAppStore((s) => s.fontSize);
   const wordWrap = useAppStore((s) => s.wordWrap);
   const selectedProjectId = useAppStore((s) => s.selectedProjectId);
@@ -54,8 +53,6 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
 
   const openProjectRef = useRef(openProject);
   openProjectRef.current = openProject;
-  const masterPasswordRef = useRef(masterPassword);
-  masterPasswordRef.current = masterPassword;
   const onLocalSaveRef = useRef(onLocalSave);
   onLocalSaveRef.current = onLocalSave;
 
@@ -68,10 +65,8 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
       if (!dirtyRef.current || !openProjectRef.current) return;
       saveDebounce.cancel();
       const proj = openProjectRef.current;
-      const mp = masterPasswordRef.current;
-      const password = proj.has_custom_password ? "" : (mp ?? "");
       await tauriRef.current.updateProject(
-        proj.id, proj.name, contentRef.current, password, proj.has_custom_password
+        proj.id, proj.name, contentRef.current, "", proj.has_custom_password
       );
       dirtyRef.current = false;
       setDirty(false);
@@ -87,9 +82,7 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
       saveDebounce.cancel();
       const prev = prevOpenProjectRef.current;
       const prevContent = contentRef.current;
-      const mp = masterPasswordRef.current;
-      const password = prev.has_custom_password ? "" : (mp ?? "");
-      tauri.updateProject(prev.id, prev.name, prevContent, password, prev.has_custom_password)
+      tauri.updateProject(prev.id, prev.name, prevContent, "", prev.has_custom_password)
         .then(() => { onLocalSaveRef.current?.(); })
         .catch(e => console.error("Flush save failed:", e));
     }
@@ -108,19 +101,17 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
 
   const handleSave = useCallback(async () => {
     const proj = openProjectRef.current;
-    const mp = masterPasswordRef.current;
     if (!proj || !dirtyRef.current) return;
 
     setError("");
     setSaveStatus("saving");
 
     try {
-      const password = proj.has_custom_password ? "" : (mp ?? "");
       await tauri.updateProject(
         proj.id,
         proj.name,
         contentRef.current,
-        password,
+        "",
         proj.has_custom_password
       );
 
@@ -173,9 +164,7 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
       if (dirtyRef.current && openProjectRef.current) {
         saveDebounce.cancel();
         const proj = openProjectRef.current;
-        const mp = masterPasswordRef.current;
-        const password = proj.has_custom_password ? "" : (mp ?? "");
-        tauri.updateProject(proj.id, proj.name, contentRef.current, password, proj.has_custom_password)
+        tauri.updateProject(proj.id, proj.name, contentRef.current, "", proj.has_custom_password)
           .catch(() => {});
       }
     };
@@ -202,11 +191,9 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
 
   const handleBackupRestore = useCallback(async () => {
     const proj = openProjectRef.current;
-    const mp = masterPasswordRef.current;
     if (!proj) return;
     try {
-      const password = proj.has_custom_password ? "" : (mp ?? "");
-      const updated = await tauri.getProject(proj.id, password);
+      const updated = await tauri.getProject(proj.id, "");
       setContent(updated.content);
       setOpenProject(updated);
       setDirty(false);
@@ -313,7 +300,7 @@ export function ContentView({ openingProject, onLocalSave, registerFlushSave }:
         <BackupHistoryPanel
           projectId={openProject.id}
           currentContent={content}
-          password={openProject.has_custom_password ? "" : (masterPassword ?? "")}
+          password=""
           onRestore={handleBackupRestore}
           onClose={() => setHistoryOpen(false)}
         />
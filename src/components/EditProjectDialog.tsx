@@ -4,7 +4,6 @@
 import { useState, useEffect } from "react";
 import { useTranslation } from "react-i18next";
 import { useTauri } from "../hooks/useTauri";
-import { useAppStore } from "../store";
 import type { DecryptedProject } from "../lib/types";
 
 interface EditProjectDialogProps {
@@ -24,7 +23,6 @@ export function EditProjectDialog({
 }: EditProjectDialogProps) {
   const { t } = useTranslation();
   const tauri = useTauri();
-  const masterPassword = useAppStore((s) => s.masterPassword);
 
   const [name, setName] = useState("");
   const [useCustomPassword, setUseCustomPassword] = useState(false);
@@ -90,7 +88,7 @@ export function EditProjectDialog({
         password = "";
       }
     } else {
-      password = masterPassword ?? "";
+      password = "";
     }
 
     try {
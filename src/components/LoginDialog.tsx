@@ -14,7 +14,7 @@ interface LoginDialogProps {
 export function LoginDialog({ open, onClose }: LoginDialogProps) {
   const { t } = useTranslation();
   const tauri = useTauri();
-  const masterPassword = useAppStore((s) => s.masterPassword);
+  const unlocked = useAppStore((s) => s.unlocked);
   const setProjects = useAppStore((s) => s.setProjects);
 
   const [serverUrl, setServerUrl] = useState("http://localhost:8080");
@@ -49,7 +49,7 @@ export function LoginDialog({ open, onClose }: LoginDialogProps) {
     try {
       const result = await tauri.syncProjects();
       setSyncMessage(`Sync: ${result.uploaded} uploaded, ${result.downloaded} downloaded`);
-      if (masterPassword) {
+      if (unlocked) {
         const projects = await tauri.listProjects();
         setProjects(projects);
       }
@@ -41,7 +41,7 @@ function getInitialSidebarWidth(): number {
 export function MainLayout() {
   const { t } = useTranslation();
   const tauri = useTauri();
-  const masterPassword = useAppStore((s) => s.masterPassword);
+  const unlocked = useAppStore((s) => s.unlocked);
   const setProjects = useAppStore((s) => s.setProjects);
   const selectedProjectId = useAppStore((s) => s.selectedProjectId);
   const setSelectedProjectId = useAppStore((s) => s.setSelectedProjectId);
@@ -135,7 +135,7 @@ export function MainLayout() {
 
   useEffect(() => {
     loadProjectsWithUI();
-  }, [masterPassword]);
+  }, [unlocked]);
 
   // Deep link при разблокированном приложении: открываем диалог сразу.
   useEffect(() => {
@@ -227,8 +227,7 @@ export function MainLayout() {
       if (selectedProjectId === project.id && openProject) {
         decrypted = openProject;
       } else {
-        const password = project.has_custom_password ? "" : (masterPassword ?? "");
-        decrypted = await tauri.getProject(project.id, password);
+        decrypted = await tauri.getProject(project.id, "");
       }
 
       let currentPw: string | null = null;
@@ -287,7 +286,7 @@ export function MainLayout() {
         setOpenProject(null);
         setSelectedProjectId(null);
       }
-      if (masterPassword) {
+      if (unlocked) {
         const projects = await tauri.listProjects();
         setProjects(projects);
       }
@@ -10,7 +10,7 @@ export function MasterPasswordSetup() {
   const { t } = useTranslation();
   const tauri = useTauri();
   const setView = useAppStore((s) => s.setView);
-  const setMasterPassword = useAppStore((s) => s.setMasterPassword);
+  const setUnlocked = useAppStore((s) => s.setUnlocked);
 
   const [password, setPassword] = useState("");
   const [confirm, setConfirm] = useState("");
@@ -36,7 +36,7 @@ export function MasterPasswordSetup() {
     try {
       setLoading(true);
       await tauri.setMasterPassword(password);
-      setMasterPassword(password);
+      setUnlocked(true);
       setView("pin-setup");
     } catch (e) {
       setError(String(e));
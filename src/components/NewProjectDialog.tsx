@@ -14,7 +14,7 @@ interface NewProjectDialogProps {
 export function NewProjectDialog({ open, onClose }: NewProjectDialogProps) {
   const { t } = useTranslation();
   const tauri = useTauri();
-  const masterPassword = useAppStore((s) => s.masterPassword);
+  const unlocked = useAppStore((s) => s.unlocked);
   const setProjects = useAppStore((s) => s.setProjects);
   const setSelectedProjectId = useAppStore((s) => s.setSelectedProjectId);
   const setOpenProject = useAppStore((s) => s.setOpenProject);
@@ -49,8 +49,8 @@ export function NewProjectDialog({ open, onClose }: NewProjectDialogProps) {
       }
     }
 
-    const password = useCustomPassword ? customPassword : masterPassword;
-    if (!password) {
+    const password = useCustomPassword ? customPassword : "";
+    if (useCustomPassword && !password) {
       setError(t("newProject.validation.noPassword"));
       return;
     }
@@ -64,7 +64,7 @@ export function NewProjectDialog({ open, onClose }: NewProjectDialogProps) {
         useCustomPassword
       );
 
-      if (masterPassword) {
+      if (unlocked) {
         const projects = await tauri.listProjects();
         setProjects(projects);
       }
@@ -11,7 +11,7 @@ import { useAppStore } from "../store";
 export function PinSetup() {
   const { t } = useTranslation();
   const tauri = useTauri();
-  const masterPassword = useAppStore((s) => s.masterPassword);
+  const unlocked = useAppStore((s) => s.unlocked);
   const setView = useAppStore((s) => s.setView);
   const setHasSavedSession = useAppStore((s) => s.setHasSavedSession);
   const setHasPinCode = useAppStore((s) => s.setHasPinCode);
@@ -34,12 +34,12 @@ export function PinSetup() {
       setError(err);
       return;
     }
-    if (!masterPassword) return;
+    if (!unlocked) return;
 
     try {
       setLoading(true);
       setError("");
-      await tauri.setupPin(pin, masterPassword);
+      await tauri.setupPin(pin);
       setHasSavedSession(true);
       setHasPinCode(true);
       setView("main");
@@ -14,7 +14,7 @@ export function PinUnlock() {
   const { t } = useTranslation();
   const tauri = useTauri();
   const setView = useAppStore((s) => s.setView);
-  const setMasterPassword = useAppStore((s) => s.setMasterPassword);
+  const setUnlocked = useAppStore((s) => s.setUnlocked);
   const touchActivity = useAppStore((s) => s.touchActivity);
 
   const [pin, setPin] = useState("");
@@ -29,15 +29,20 @@ export function PinUnlock() {
       try {
         setLoading(true);
         setError("");
-        const masterPassword = await tauri.verifyPin(completed);
-        setMasterPassword(masterPassword);
+        await tauri.verifyPin(completed);
+        setUnlocked(true);
         touchActivity();
         setView("main");
-      } catch {
+      } catch (e) {
         const next = attemptsRef.current + 1;
         attemptsRef.current = next;
         setPin("");
-        if (next >= MAX_ATTEMPTS) {
+        const message = String(e);
+        if (
+          next >= MAX_ATTEMPTS ||
+          message.includes("pin_locked_out") ||
+          message.includes("pin_session_wiped")
+        ) {
           setView("unlock");
           return;
         }
@@ -48,7 +53,7 @@ export function PinUnlock() {
         setLoading(false);
       }
     },
-    [loading, tauri, setMasterPassword, touchActivity, setView, t],
+    [loading, tauri, setUnlocked, touchActivity, setView, t],
   );
 
   return (
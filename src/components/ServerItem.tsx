@@ -15,6 +15,7 @@ interface ServerItemServer {
   url: string;
   is_authenticated: boolean;
   has_master_password: boolean;
+  is_unlocked: boolean;
   is_admin: boolean;
   sync_status: ServerSyncStatus;
   sync_error: string | null;
@@ -65,7 +66,7 @@ export function ServerItem({
   const isActive = activeContextId === server.id;
   const projectsSelector = useMemo(() => selectProjectsForContext(server.id), [server.id]);
   const serverProjects = useAppStore(projectsSelector);
-  const isReady = server.is_authenticated && server.has_master_password;
+  const isReady = server.is_authenticated && server.is_unlocked;
 
   return (
     <div>
@@ -93,7 +94,7 @@ export function ServerItem({
           onClick={() => {
             if (!server.is_authenticated) {
               onAuth(server.id);
-            } else if (!server.has_master_password) {
+            } else if (!server.is_unlocked) {
               onMasterPassword(server.id);
             } else {
               onSwitch(server.id);
@@ -11,7 +11,7 @@ export function UnlockScreen() {
   const { t } = useTranslation();
   const tauri = useTauri();
   const setView = useAppStore((s) => s.setView);
-  const setMasterPassword = useAppStore((s) => s.setMasterPassword);
+  const setUnlocked = useAppStore((s) => s.setUnlocked);
   const touchActivity = useAppStore((s) => s.touchActivity);
 
   const [password, setPassword] = useState("");
@@ -29,7 +29,7 @@ export function UnlockScreen() {
         setError(t("unlock.error"));
         return;
       }
-      setMasterPassword(password);
+      setUnlocked(true);
       touchActivity();
 
       const pinExists = await tauri.hasPin();
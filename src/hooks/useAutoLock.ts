@@ -2,6 +2,7 @@
 // Licensed under the PolyForm Noncommercial License 1.0.0
 
 import { useEffect, useCallback } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "../store";
 import { useTauri } from "./useTauri";
 
@@ -47,4 +48,25 @@ export function useAutoLock() {
 
     return () => clearInterval(interval);
   }, [view, autoLockMinutes, lastActivity, doLock]);
+
+  // Session idle time and screen locks reported by the backend (Linux desktops).
+  useEffect(() => {
+    if (view !== "main") return;
+
+    const unlistenIdle = listen<{ idle_secs: number }>("system-idle", (event) => {
+      if (autoLockMinutes > 0 && event.payload.idle_secs > autoLockMinutes * 60) {
+        doLock();
+      }
+    });
+    const unlistenScreen = listen<{ vault_locked: boolean }>("screen-locked", (event) => {
+      if (event.payload.vault_locked) {
+        doLock();
+      }
+    });
+
+    return () => {
+      unlistenIdle.then((fn) => fn());
+      unlistenScreen.then((fn) => fn());
+    };
+  }, [view, autoLockMinutes, doLock]);
 }
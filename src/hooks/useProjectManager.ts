@@ -23,8 +23,8 @@ export function useProjectManager() {
 
   const loadProjectsForContext = useCallback(async (contextId?: string) => {
     const ctx = contextId ?? useAppStore.getState().activeContextId;
-    const mp = useAppStore.getState().masterPassword;
-    if (!mp && ctx === "local") return;
+    const unlocked = useAppStore.getState().unlocked;
+    if (!unlocked && ctx === "local") return;
     try {
       const projects = await tauri.listProjects();
       if (useAppStore.getState().activeContextId === ctx) {
@@ -65,12 +65,7 @@ export function useProjectManager() {
     setSelectedProjectId(null);
     setActiveContextId(contextId);
 
-    if (contextId === "local") {
-      const mp = useAppStore.getState().masterPassword;
-      if (mp) {
-        await tauri.cacheMasterKey(mp);
-      }
-    } else {
+    if (contextId !== "local") {
       setServersExpanded(true);
       const expanded = useAppStore.getState().expandedServers;
       if (!expanded.has(contextId)) {
@@ -105,10 +100,8 @@ export function useProjectManager() {
       return;
     }
 
-    const mp = useAppStore.getState().masterPassword;
-    const password = project.has_custom_password ? "" : (mp ?? "");
     try {
-      const decrypted = await tauri.getProject(project.id, password);
+      const decrypted = await tauri.getProject(project.id, "");
       setSelectedProjectId(project.id);
       setOpenProject(decrypted);
     } catch {
@@ -34,7 +34,7 @@ export function useSyncManager({ onConflicts, loadProjects }: UseSyncManagerOpti
   const canSync =
     isServerContext &&
     !!activeServer?.is_authenticated &&
-    !!activeServer?.has_master_password &&
+    !!activeServer?.is_unlocked &&
     autoSyncEnabled;
 
   const doSyncPush = useCallback(async () => {
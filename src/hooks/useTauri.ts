@@ -3,7 +3,7 @@
 
 import { useMemo } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { BackupContent, BackupListItem, DecryptedProject, PasswordRegistryEntry, ProjectListItem, RemoteChangedInfo, ServerInfo, SyncPullResult, SyncPushResult, SyncResult } from "../lib/types";
+import type { Backlink, BackupContent, BackupListItem, ChecklistItem, Credential, DecryptedProject, PastPassword, PasswordRegistryEntry, ProjectAppearance, ProjectListItem, ProjectTemplate, RecoveredDraft, RemoteChangedInfo, SearchFilters, SearchHit, ServerInfo, SyncPullResult, SyncPushResult, SyncResult } from "../lib/types";
 
 export function useTauri() {
   return useMemo(() => ({
@@ -31,8 +31,8 @@ export function useTauri() {
     setSetting: (key: string, value: string) =>
       invoke<void>("set_setting", { key, value }),
 
-    listProjects: () =>
-      invoke<ProjectListItem[]>("list_projects"),
+    listProjects: (includeArchived?: boolean) =>
+      invoke<ProjectListItem[]>("list_projects", { includeArchived }),
 
     getProject: (id: string, password: string) =>
       invoke<DecryptedProject>("get_project", { id, password }),
@@ -55,7 +55,8 @@ export function useTauri() {
       name: string,
       content: string,
       password: string,
-      hasCustomPassword: boolean
+      hasCustomPassword: boolean,
+      baseRevision?: number
     ) =>
       invoke<void>("update_project", {
         id,
@@ -63,10 +64,107 @@ export function useTauri() {
         content,
         password,
         hasCustomPassword,
+        baseRevision,
+      }),
+
+    createCredential: (
+      name: string,
+      credential: Credential,
+      password: string,
+      hasCustomPassword: boolean
+    ) =>
+      invoke<string>("create_credential", {
+        name,
+        credential,
+        password,
+        hasCustomPassword,
+      }),
+
+    updateCredential: (
+      id: string,
+      name: string,
+      credential: Credential,
+      password: string,
+      hasCustomPassword: boolean,
+      baseRevision?: number
+    ) =>
+      invoke<void>("update_credential", {
+        id,
+        name,
+        credential,
+        password,
+        hasCustomPassword,
+        baseRevision,
+      }),
+
+    listTemplates: () => invoke<ProjectTemplate[]>("list_templates"),
+
+    createTemplate: (name: string, content: string, credential?: Credential) =>
+      invoke<ProjectTemplate>("create_template", { name, content, credential }),
+
+    deleteTemplate: (id: string) => invoke<void>("delete_template", { id }),
+
+    createProjectFromTemplate: (
+      templateId: string,
+      name: string | null,
+      password: string,
+      hasCustomPassword: boolean
+    ) =>
+      invoke<string>("create_project_from_template", {
+        templateId,
+        name,
+        password,
+        hasCustomPassword,
       }),
 
+    listChecklist: (projectId: string, password: string) =>
+      invoke<ChecklistItem[]>("list_checklist", { projectId, password }),
+
+    addChecklistItem: (projectId: string, text: string, password: string) =>
+      invoke<ChecklistItem>("add_checklist_item", { projectId, text, password }),
+
+    toggleChecklistItem: (projectId: string, id: string, password: string) =>
+      invoke<ChecklistItem>("toggle_checklist_item", { projectId, id, password }),
+
+    removeChecklistItem: (id: string) => invoke<void>("remove_checklist_item", { id }),
+
+    reorderChecklist: (projectId: string, ids: string[]) =>
+      invoke<void>("reorder_checklist", { projectId, ids }),
+
+    listPasswordHistory: (projectId: string, password: string) =>
+      invoke<PastPassword[]>("list_password_history", { projectId, password }),
+
+    restorePasswordFromHistory: (id: string, password: string, baseRevision?: number) =>
+      invoke<void>("restore_password_from_history", { id, password, baseRevision }),
+
+    saveDraft: (projectId: string, baseRevision: number, name: string, content: string, password: string) =>
+      invoke<void>("save_draft", { projectId, baseRevision, name, content, password }),
+
+    getDraft: (projectId: string, password: string) =>
+      invoke<RecoveredDraft | null>("get_draft", { projectId, password }),
+
+    discardDraft: (projectId: string) => invoke<void>("discard_draft", { projectId }),
+
+    duplicateProject: (id: string, password: string) =>
+      invoke<string>("duplicate_project", { id, password }),
+
+    searchProjects: (query: string, filters?: SearchFilters) =>
+      invoke<SearchHit[]>("search_projects", { query, filters }),
+
+    getBacklinks: (id: string) => invoke<Backlink[]>("get_backlinks", { id }),
+
     deleteProject: (id: string) => invoke<void>("delete_project", { id }),
 
+    archiveProject: (id: string) => invoke<void>("archive_project", { id }),
+
+    unarchiveProject: (id: string) =>
+      invoke<void>("unarchive_project", { id }),
+
+    togglePin: (id: string) => invoke<boolean>("toggle_pin", { id }),
+
+    setProjectAppearance: (id: string, color: string | null, icon: string | null) =>
+      invoke<ProjectAppearance>("set_project_appearance", { id, color, icon }),
+
     reorderProjects: (ids: string[]) =>
       invoke<void>("reorder_projects", { ids }),
 
@@ -92,10 +190,9 @@ export function useTauri() {
     syncPullChanged: (changedIds: string[]) =>
       invoke<SyncPullResult>("sync_pull_changed", { changedIds }),
 
-    setupPin: (pin: string, masterPassword: string) =>
-      invoke<void>("setup_pin", { pin, masterPassword }),
+    setupPin: (pin: string) => invoke<void>("setup_pin", { pin }),
 
-    verifyPin: (pin: string) => invoke<string>("verify_pin", { pin }),
+    verifyPin: (pin: string) => invoke<void>("verify_pin", { pin }),
 
     hasSavedSession: () => invoke<boolean>("has_saved_session"),
 
@@ -103,7 +200,7 @@ export function useTauri() {
 
     getSavedDbPath: () => invoke<string | null>("get_saved_db_path"),
 
-    getSavedMasterPassword: () => invoke<string | null>("get_saved_master_password"),
+    restoreSavedSession: () => invoke<boolean>("restore_saved_session"),
 
     clearSavedSession: () => invoke<void>("clear_saved_session"),
 
@@ -6,13 +6,267 @@ export interface ProjectListItem {
   name: string;
   has_custom_password: boolean;
   password_saved: boolean;
-  sort_order: number;
+  probed?: boolean;
+  position: string;
   created_at: string;
   updated_at: string;
   server_id?: string | null;
   sync_status?: string;
   last_synced_at?: string | null;
   is_password_registry: boolean;
+  tags?: string[];
+  folder_id?: string | null;
+  archived: boolean;
+  pinned: boolean;
+  color: string | null;
+  icon: string | null;
+}
+
+export interface ProjectPage {
+  stream_id: number;
+  items: ProjectListItem[];
+  total: number;
+  done: boolean;
+}
+
+export interface ProjectBatch {
+  stream_id: number;
+  items: ProjectListItem[];
+  done: boolean;
+}
+
+export interface ProjectAppearance {
+  color: string | null;
+  icon: string | null;
+}
+
+export interface TrashedProject {
+  id: string;
+  name: string;
+  deleted_at: string;
+  purge_at: string | null;
+}
+
+export interface AttachmentInfo {
+  id: string;
+  project_id: string;
+  name: string;
+  mime_type: string;
+  size: number;
+  created_at: string;
+}
+
+export type TotpAlgorithm = "SHA1" | "SHA256" | "SHA512";
+
+export interface TotpOptions {
+  algorithm?: TotpAlgorithm;
+  digits?: number;
+  period?: number;
+}
+
+export interface TotpInfo {
+  algorithm: TotpAlgorithm;
+  digits: number;
+  period: number;
+  issuer: string | null;
+  account: string | null;
+}
+
+export interface TotpCode {
+  code: string;
+  period: number;
+  /** Seconds until the next code. */
+  remaining: number;
+}
+
+export interface ProjectTemplate {
+  id: string;
+  name: string;
+  content: string;
+  kind: ProjectKind;
+  created_at: string;
+  updated_at: string;
+}
+
+/** A project linking to another through `vaultpad://project/<id>`. */
+export interface Backlink {
+  id: string;
+  name: string;
+}
+
+export interface ChecklistItem {
+  id: string;
+  text: string;
+  checked: boolean;
+  position: string;
+}
+
+/** A matched range, in UTF-16 code units (string indexes). */
+export interface Highlight {
+  start: number;
+  end: number;
+}
+
+export interface SearchHit {
+  id: string;
+  name: string;
+  name_highlights: Highlight[];
+  /** Content around the first match; empty when only the name matched. */
+  snippet: string;
+  snippet_highlights: Highlight[];
+  score: number;
+}
+
+/** Filters for `searchProjects`; every field that is set must hold. Tags are tag ids. */
+export interface SearchFilters {
+  tags?: string[];
+  untagged?: boolean;
+  kind?: ProjectKind;
+  created_after?: string;
+  /** Exclusive. */
+  created_before?: string;
+  updated_after?: string;
+  /** Exclusive. */
+  updated_before?: string;
+  has_custom_password?: boolean;
+  sync_status?: "local" | "synced" | "modified";
+  archived?: boolean;
+}
+
+export interface PastPassword {
+  id: string;
+  password: string;
+  /** When a save replaced it. */
+  replaced_at: string;
+}
+
+/** An edit autosaved but never saved, offered back when the project is opened. */
+export interface RecoveredDraft {
+  name: string;
+  content: string;
+  saved_at: string;
+  base_revision: number;
+  /** The project was saved after the edit started; restoring would overwrite that save. */
+  outdated: boolean;
+}
+
+export interface CustomField {
+  id: string;
+  label: string;
+  value: string;
+  is_secret: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface VaultDisplayInfo {
+  db_path: string;
+  name: string;
+  color: string | null;
+  last_opened: string | null;
+  quick_unlock: "pin" | "pattern" | "phrase" | null;
+}
+
+export interface FormatInfo {
+  format_version: number | null;
+  read_compat_version: number | null;
+  supported_version: number;
+  too_new: boolean;
+  read_only_possible: boolean;
+  schema_version: number;
+  supported_schema_version: number;
+  encrypted: boolean;
+}
+
+/** Codes `init_database` fails with; `vault_open_failed` is followed by `:` and a message. */
+export type VaultOpenError =
+  | "vault_missing"
+  | "vault_permission_denied"
+  | "vault_corrupt"
+  | "vault_in_use"
+  | "vault_too_new"
+  | "vault_open_failed";
+
+export interface VaultCandidate {
+  path: string;
+  kind: "vault" | "copy";
+  size: number;
+  modified_at: string | null;
+  known: boolean;
+  format: FormatInfo | null;
+}
+
+/** Payload of `vault-changed-externally`. */
+export interface VaultChangedExternally {
+  db_path: string;
+  reopened: boolean;
+}
+
+export interface KnownVault {
+  db_path: string;
+  name: string;
+  color: string | null;
+  last_opened: string | null;
+  open: boolean;
+  unlocked: boolean;
+  active: boolean;
+}
+
+export type KeychainItemKind =
+  | "saved_session"
+  | "quick_unlock"
+  | "vault_location"
+  | "project_password"
+  | "server_profiles"
+  | "server_credential"
+  | "device"
+  | "audit_key"
+  | "other";
+
+export interface KeychainItem {
+  key: string;
+  kind: KeychainItemKind;
+  label: string | null;
+  project_id: string | null;
+  server_id: string | null;
+  sealed: boolean;
+  removable: boolean;
+}
+
+export interface SecurityRecommendation {
+  id: string;
+  severity: "high" | "medium" | "low";
+  message: string;
+}
+
+export interface SecurityReport {
+  kdf: {
+    params: { memory_kb: number; iterations: number; parallelism: number };
+    millis: number;
+  };
+  format_version: number | null;
+  schema_version: number;
+  database_encrypted: boolean;
+  content: { v1: number; v2: number; v3: number; custom_password: number };
+  keychain_backend: "os" | "file-machine" | "file-pin";
+  master_password_persisted: boolean;
+  session_saved: boolean;
+  quick_unlock: "pin" | "pattern" | "phrase" | null;
+  auto_lock_minutes: number | null;
+  lock_on_screen_lock: boolean;
+  recommendations: SecurityRecommendation[];
+}
+
+export interface CompactProgress {
+  target: "vault" | "attachments";
+  step: number;
+  total_steps: number;
+}
+
+export interface CompactStats {
+  bytes_before: number;
+  bytes_after: number;
+  free_pages: number;
 }
 
 export interface PasswordRegistryEntry {
@@ -27,9 +281,29 @@ export interface DecryptedProject {
   name: string;
   content: string;
   has_custom_password: boolean;
-  sort_order: number;
+  position: string;
   created_at: string;
   updated_at: string;
+  /** Pass back as `baseRevision` to `updateProject`. */
+  revision: number;
+  kind: ProjectKind;
+  /** The parsed content of a credential; null for a note. */
+  credential: Credential | null;
+}
+
+export type ProjectKind = "note" | "credential";
+
+export interface Credential {
+  username: string;
+  password: string;
+  url: string;
+  notes: string;
+}
+
+export interface RecentProject {
+  id: string;
+  name: string;
+  last_accessed_at: string;
 }
 
 export interface ServerInfo {
@@ -39,6 +313,8 @@ export interface ServerInfo {
   db_path: string;
   is_authenticated: boolean;
   has_master_password: boolean;
+  /** The vault key is held this session; otherwise the master password is asked for. */
+  is_unlocked: boolean;
   is_admin: boolean;
 }
 
@@ -52,17 +328,45 @@ export interface ConflictInfo {
   remote_updated_at: string;
 }
 
+export type MergeHunk =
+  | { kind: "unchanged"; lines: string[] }
+  | { kind: "local"; base: string[]; local: string[] }
+  | { kind: "remote"; base: string[]; remote: string[] }
+  | { kind: "both"; base: string[]; lines: string[] }
+  | { kind: "conflict"; base: string[]; local: string[]; remote: string[] };
+
+export interface MergePreview {
+  base_available: boolean;
+  name: {
+    base: string | null;
+    local: string;
+    remote: string;
+    merged: string | null;
+  };
+  hunks: MergeHunk[];
+  conflicts: number;
+  merged: string | null;
+}
+
 export interface SyncResult {
   uploaded: number;
   downloaded: number;
   deleted: number;
   conflicts: ConflictInfo[];
+  settings_updated: boolean;
 }
 
 export interface SyncPushResult {
   uploaded: number;
   deleted: number;
   conflicts: ConflictInfo[];
+  settings_updated: boolean;
+}
+
+export interface SettingsSyncPreferences {
+  enabled: boolean;
+  excluded: string[];
+  device_specific: string[];
 }
 
 export interface RemoteChangedInfo {
@@ -85,6 +389,61 @@ export interface BackupListItem {
   content_length: number;
 }
 
+export type BackupEncryption = "vault_key" | "passphrase" | "recovery_code";
+
+export interface ScheduleSettings {
+  frequency: "daily" | "weekly" | null;
+  directory: string | null;
+  keep_count: number | null;
+  keep_days: number | null;
+  encryption: BackupEncryption;
+  last_run_at: string | null;
+  last_error: string | null;
+}
+
+export interface BackupFile {
+  path: string;
+  created_at: string;
+  size: number;
+}
+
+export interface ScheduledBackup {
+  path: string;
+  created_at: string;
+  project_count: number;
+  pruned: string[];
+}
+
+export type RestoreMode = "replace" | "merge-newer-wins" | "import-as-copies";
+
+export interface BackupPreview {
+  vault_id: string;
+  same_vault: boolean;
+  created_at: string;
+  project_count: number;
+  encryption: BackupEncryption;
+  contents: {
+    history_count: number;
+    attachment_count: number;
+    oldest_update: string | null;
+    newest_update: string | null;
+    new_projects: number;
+    newer_in_vault: number;
+    newer_in_backup: number;
+    only_in_vault: number;
+  } | null;
+}
+
+export interface RestoreSummary {
+  projects_restored: number;
+  projects_added: number;
+  projects_skipped: number;
+  projects_trashed: number;
+  backups_added: number;
+  blobs_added: number;
+  attachments_added: number;
+}
+
 export interface BackupContent {
   name: string;
   content: string;
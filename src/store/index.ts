@@ -21,8 +21,8 @@ interface AppStore {
   view: AppView;
   setView: (view: AppView) => void;
 
-  masterPassword: string | null;
-  setMasterPassword: (password: string | null) => void;
+  unlocked: boolean;
+  setUnlocked: (unlocked: boolean) => void;
 
   projectsByContext: Record<string, ProjectListItem[]>;
   setProjectsForContext: (contextId: string, projects: ProjectListItem[]) => void;
@@ -95,8 +95,8 @@ export const useAppStore = create<AppStore>((set) => ({
   view: "loading",
   setView: (view) => set({ view }),
 
-  masterPassword: null,
-  setMasterPassword: (password) => set({ masterPassword: password }),
+  unlocked: false,
+  setUnlocked: (unlocked) => set({ unlocked }),
 
   projectsByContext: {},
   setProjectsForContext: (contextId, projects) =>
@@ -202,7 +202,7 @@ export const useAppStore = create<AppStore>((set) => ({
 
   lock: () =>
     set((state) => ({
-      masterPassword: null,
+      unlocked: false,
       openProject: null,
       projectsByContext: {},
       selectedProjectId: null,